@@ -1,8 +1,14 @@
-use std::{path::Path, process::Command};
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use color_eyre::Result;
 use crossterm::{
-    ExecutableCommand, clipboard, execute,
+    ExecutableCommand, clipboard, event, execute,
     terminal::{
         EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode,
         enable_raw_mode,
@@ -13,14 +19,52 @@ use ratatui_textarea::TextArea;
 
 use crate::{
     app_state::{AppState, DatabaseExplorerState},
+    autocomplete::CatalogCache,
+    background_task::{BackgroundTask, ProgressSender},
+    compare_plans::PlanBaseline,
+    compare_tables::CompareSource,
+    confirmations::{ConfirmAction, Confirmations},
+    connect_retry::{ConnectRetry, DEFAULT_CONNECT_RETRIES},
+    connection_sort::{CONNECTION_SORT_SETTING, ConnectionSort},
     database_explorer_state::DatabaseExplorer,
-    db::{RowDeleteSpec, TableData, sqlite::init_db},
+    db::{
+        RowDeleteSpec, TableData,
+        connection::{Connection, ConnectionType},
+        connection_column::{CONNECTION_COLUMNS_SETTING, ConnectionColumn},
+        postgres::search_path_options,
+        query_estimate::{ExplainThresholds, QueryEstimate},
+        sqlite::init_db,
+        time_box::TimeBox,
+    },
+    deep_link::DeepLink,
     filtered_data::FilteredData,
-    services::{ConnectionService, PasswordService},
-    sql::safety::{StatementSafety, classify_statement, split_statements},
-    ui::widgets::{
-        hotkey::Hotkey, modal::ModalManager, status_line::StatusLine,
-        top_bar_view::CONNECTION_HOTKEYS,
+    macros::MacroRecorder,
+    services::{
+        ConnectionService, PasswordService, ScratchBufferService,
+        SettingsService,
+    },
+    sql::{
+        keywords::{KEYWORD_CASE_SETTING, KeywordCase},
+        parens::unbalanced_paren,
+        safety::{StatementSafety, classify_statement, split_statements},
+        secrets::{SECRET_MASKING_SETTING, SecretMasking},
+    },
+    statement_queue::StatementQueue,
+    ui::{
+        accessible::{
+            ACCESSIBLE_ENV, ACCESSIBLE_SETTING, AccessibleMode,
+            modal_announcement, view_announcement,
+        },
+        layout::{
+            COMPACT_MODE_SETTING, LayoutSettings, TOP_BAR_HEIGHT_SETTING,
+        },
+        number_format::NumberFormat,
+        palette::Palette,
+        relative_time::TimeDisplay,
+        widgets::{
+            modal::{InfoModal, InputModal, InputPurpose, ModalManager},
+            status_line::StatusLine,
+        },
     },
 };
 
@@ -36,18 +80,25 @@ pub const APP_NAME: &str = r"_________________
 pub const PKG_NAME: &str = env!("CARGO_PKG_NAME");
 pub const PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// How often the screen is redrawn while a background task runs.
+const PROGRESS_REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long typing in the search bar pauses before the filter is applied.
+pub(crate) const FILTER_DEBOUNCE: Duration = Duration::from_millis(150);
+
 /// The main application which holds the state and logic of the application.
 pub struct App<'a> {
     /// Is the application running?
     pub(crate) running: bool,
     pub(crate) modal_manager: ModalManager,
-    pub(crate) hotkeys: Vec<Hotkey>,
     /// Current application state
     pub(crate) state: AppState,
     /// Database explorer state (when connected to a database)
     pub(crate) database_explorer: DatabaseExplorer,
     /// Search filter widget
     pub(crate) search_filter: Option<TextArea<'a>>,
+    /// When the search bar text last changed, until the filter catches up
+    pub(crate) filter_typed_at: Option<Instant>,
     /// Status line widget
     pub(crate) status_line: StatusLine,
     /// Password management service
@@ -56,8 +107,59 @@ pub struct App<'a> {
     pub(crate) build_info: String,
     /// Signal to the run loop to open the external editor
     pub(crate) open_editor_requested: bool,
+    /// Signal to the run loop to suspend into `psql`
+    pub(crate) open_psql_requested: bool,
     /// Table data: after `d`, row locators awaiting delete confirmation.
     pub(crate) pending_row_deletes: Option<Vec<RowDeleteSpec>>,
+    /// Ask before running statements the planner expects to be expensive.
+    pub(crate) explain_thresholds: ExplainThresholds,
+    /// Stop reading the rows of SQL editor queries at these limits.
+    pub(crate) time_box: TimeBox,
+    /// Actions that ask before they run
+    pub(crate) confirmations: Confirmations,
+    /// Times to connect again after a failure that may pass
+    pub(crate) connect_retries: u32,
+    /// Connection attempt waiting to be retried
+    pub(crate) connect_retry: Option<ConnectRetry>,
+    /// `.sql` file from `--source`, run once a connection opens
+    pub(crate) pending_source: Option<PathBuf>,
+    /// Link from `d7s open`, followed once its connection opens
+    pub(crate) pending_link: Option<DeepLink>,
+    /// Sizes of the resizable screen regions
+    pub(crate) layout: LayoutSettings,
+    /// Export or other long operation running off the key handler
+    pub(crate) background_task: Option<BackgroundTask>,
+    /// SQL executor statements waiting for a background task to free the
+    /// connection
+    pub(crate) statement_queue: StatementQueue,
+    /// Keyboard macro registers and the recording in progress
+    pub(crate) macros: MacroRecorder,
+    /// Table marked with `C`, compared with the next one marked
+    pub(crate) compare_source: Option<CompareSource>,
+    /// Casing applied to SQL keywords read back from the editor
+    pub(crate) keyword_case: KeywordCase,
+    /// Whether secrets are masked out of SQL kept in the result history
+    pub(crate) secret_masking: SecretMasking,
+    /// Order of the connection list
+    pub(crate) connection_sort: ConnectionSort,
+    /// Columns shown in the connection list, in order
+    pub(crate) connection_columns: Arc<Vec<ConnectionColumn>>,
+    /// Executed plan kept with `B` to compare later runs against
+    pub(crate) plan_baseline: Option<PlanBaseline>,
+    /// Whether the statement behind the table data page is shown above it
+    pub(crate) show_fetch_sql: bool,
+    /// Colors the terminal shows; each frame is mapped down to them
+    pub(crate) palette: Palette,
+    /// Plain glyphs, high contrast and announcements for screen readers
+    pub(crate) accessible: AccessibleMode,
+    /// View or dialog last announced in accessible mode
+    announced: Option<String>,
+    /// How numbers in result grids are drawn, for this session
+    pub(crate) number_format: NumberFormat,
+    /// How timestamps in result grids are drawn, for this session
+    pub(crate) time_display: TimeDisplay,
+    /// Table and column names read for completion, by connection
+    pub(crate) catalogs: CatalogCache,
 }
 
 impl Default for App<'_> {
@@ -65,38 +167,165 @@ impl Default for App<'_> {
         Self {
             running: false,
             modal_manager: ModalManager::new(),
-            hotkeys: CONNECTION_HOTKEYS.to_vec(),
             state: AppState::ConnectionList,
             database_explorer: DatabaseExplorer::default(),
             search_filter: None,
+            filter_typed_at: None,
             status_line: StatusLine::new(),
             password_service: PasswordService::new(),
             build_info: String::new(),
             open_editor_requested: false,
+            open_psql_requested: false,
             pending_row_deletes: None,
+            explain_thresholds: ExplainThresholds::default(),
+            time_box: TimeBox::default(),
+            confirmations: Confirmations::all(),
+            connect_retries: DEFAULT_CONNECT_RETRIES,
+            connect_retry: None,
+            pending_source: None,
+            pending_link: None,
+            layout: LayoutSettings::default(),
+            background_task: None,
+            statement_queue: StatementQueue::default(),
+            macros: MacroRecorder::default(),
+            compare_source: None,
+            keyword_case: KeywordCase::AsWritten,
+            secret_masking: SecretMasking::On,
+            connection_sort: ConnectionSort::default(),
+            connection_columns: Arc::default(),
+            plan_baseline: None,
+            show_fetch_sql: false,
+            palette: Palette::default(),
+            accessible: AccessibleMode::Off,
+            announced: None,
+            number_format: NumberFormat::Raw,
+            time_display: TimeDisplay::Exact,
+            catalogs: CatalogCache::default(),
         }
     }
 }
 
 impl App<'_> {
+    #[must_use]
+    pub const fn with_explain_thresholds(
+        mut self,
+        thresholds: ExplainThresholds,
+    ) -> Self {
+        self.explain_thresholds = thresholds;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_time_box(mut self, time_box: TimeBox) -> Self {
+        self.time_box = time_box;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_confirmations(
+        mut self,
+        confirmations: Confirmations,
+    ) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_connect_retries(mut self, retries: u32) -> Self {
+        self.connect_retries = retries;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    #[must_use]
+    pub fn with_source(mut self, source: Option<PathBuf>) -> Self {
+        self.pending_source = source;
+        self
+    }
+
+    #[must_use]
+    pub fn with_link(mut self, link: Option<DeepLink>) -> Self {
+        self.pending_link = link;
+        self
+    }
+
     /// Post initilisation for the App
     pub fn init(mut self) -> Result<Self> {
-        init_db()?;
+        // Offered a recovery once running rather than failing to start
+        let store_error = init_db().err();
 
+        let connection_sort =
+            SettingsService::get(CONNECTION_SORT_SETTING).unwrap_or_default();
+        self.connection_sort =
+            ConnectionSort::from_saved(connection_sort.as_deref());
+        let connection_columns =
+            SettingsService::get(CONNECTION_COLUMNS_SETTING)
+                .unwrap_or_default();
+        self.connection_columns = Arc::new(ConnectionColumn::from_saved(
+            connection_columns.as_deref(),
+        ));
         let items = ConnectionService::get_all().unwrap_or_default();
-        self.database_explorer.connections = FilteredData::new(items);
+        self.database_explorer.connections = self.connection_list(items);
+
+        let top_bar_height =
+            SettingsService::get(TOP_BAR_HEIGHT_SETTING).unwrap_or_default();
+        let compact =
+            SettingsService::get(COMPACT_MODE_SETTING).unwrap_or_default();
+        self.layout = LayoutSettings::from_saved(
+            top_bar_height.as_deref(),
+            compact.as_deref(),
+        );
+
+        let keyword_case =
+            SettingsService::get(KEYWORD_CASE_SETTING).unwrap_or_default();
+        self.keyword_case = KeywordCase::from_saved(keyword_case.as_deref());
+
+        let secret_masking =
+            SettingsService::get(SECRET_MASKING_SETTING).unwrap_or_default();
+        self.secret_masking =
+            SecretMasking::from_saved(secret_masking.as_deref());
+
+        let accessible = std::env::var(ACCESSIBLE_ENV).ok().or_else(|| {
+            SettingsService::get(ACCESSIBLE_SETTING).unwrap_or_default()
+        });
+        self.accessible = AccessibleMode::from_saved(accessible.as_deref());
 
         self.build_info = build_info()?;
 
+        if let Some(e) = store_error {
+            self.open_store_recovery(&e.to_string());
+        } else {
+            self.check_keyring();
+        }
+
         Ok(self)
     }
 
     /// Run the application's main loop.
     pub async fn run(&mut self, mut terminal: DefaultTerminal) -> Result<()> {
         self.running = true;
+        self.connect_to_link().await?;
         while self.running {
+            self.announce_view();
             terminal.draw(|frame| self.render(frame))?;
-            self.handle_crossterm_events().await?;
+            if let Some(timeout) = self.poll_timeout() {
+                // Keep redrawing the progress, catch the end of typing in
+                // the search bar and retry connecting while waiting for keys
+                if event::poll(timeout)? {
+                    self.handle_crossterm_events().await?;
+                }
+                self.poll_background_task().await;
+                self.run_queued_statements().await;
+                self.apply_typed_filter(false);
+                self.poll_connect_retry().await?;
+            } else {
+                self.handle_crossterm_events().await?;
+            }
 
             self.handle_external_terminal(&mut terminal).await?;
         }
@@ -107,48 +336,129 @@ impl App<'_> {
         &mut self,
         terminal: &mut DefaultTerminal,
     ) -> Result<(), color_eyre::eyre::Error> {
+        if self.open_psql_requested {
+            self.open_psql_requested = false;
+            self.run_psql(terminal)?;
+        }
         if self.open_editor_requested {
             self.open_editor_requested = false;
             let temp_path = std::path::Path::new("/tmp/d7s_sql_editor.sql");
-            let current_sql =
-                self.database_explorer.sql_executor.sql_input().clone();
+            let mut current_sql =
+                self.database_explorer.sql_executor.sql_input();
+            // Start an empty buffer from the table being browsed
+            if current_sql.trim().is_empty()
+                && let Some(template) = self.current_table_select_template()
+            {
+                current_sql = template;
+            }
             std::fs::write(temp_path, &current_sql)?;
-            Self::run_editor(terminal, temp_path)?;
+            let error_line =
+                self.database_explorer.sql_executor.error_line_in_input();
+            Self::run_editor(terminal, temp_path, error_line)?;
             let new_sql =
                 std::fs::read_to_string(temp_path).unwrap_or_default();
-            let new_sql = new_sql.trim_end_matches('\n');
+            let new_sql =
+                self.keyword_case.apply(new_sql.trim_end_matches('\n'));
             if !new_sql.is_empty() {
-                self.database_explorer.sql_executor.set_sql(new_sql);
-                let statements = split_statements(new_sql);
-                if statements.is_empty() {
-                    self.set_status("No SQL statements found in editor file.");
-                    return Ok(());
-                }
-
-                if statements.len() == 1 {
-                    if let Some(statement) = statements.first() {
-                        self.prepare_sql_statement_execution(
-                            statement.text.clone(),
-                        )
-                        .await;
-                    }
-                } else {
-                    let options = statements
-                        .into_iter()
-                        .map(|s| s.text)
-                        .collect::<Vec<_>>();
-                    self.modal_manager.open_sql_query_selection_modal(options);
-                }
+                self.load_sql(&new_sql).await;
             }
         }
 
         Ok(())
     }
 
-    /// Refresh the table data from the database
+    /// Put `sql` in the SQL buffer, then run its statement or ask which of
+    /// its statements to run
+    pub(crate) async fn load_sql(&mut self, sql: &str) {
+        let executor = &mut self.database_explorer.sql_executor;
+        let current = executor.selected_statement().map(String::from);
+        executor.set_sql(sql);
+        self.save_scratch_buffer(sql);
+        let statements = split_statements(sql);
+        if statements.is_empty() {
+            self.set_status("No SQL statements found in editor file.");
+            return;
+        }
+
+        if statements.len() == 1 {
+            if let Some(statement) = statements.first() {
+                self.prepare_sql_statement_execution(statement.text.clone())
+                    .await;
+            }
+        } else {
+            let options =
+                statements.into_iter().map(|s| s.text).collect::<Vec<_>>();
+            self.modal_manager
+                .open_sql_query_selection_modal(options, current.as_deref());
+        }
+    }
+
+    /// The connection list for `connections`, in the chosen order and with
+    /// the chosen columns
+    fn connection_list(
+        &self,
+        mut connections: Vec<Connection>,
+    ) -> FilteredData<Connection> {
+        self.connection_sort.apply(&mut connections);
+        for connection in &mut connections {
+            connection.columns = Arc::clone(&self.connection_columns);
+        }
+        let mut list = FilteredData::new(connections);
+        list.table.set_column_names(
+            self.connection_columns
+                .iter()
+                .map(|column| column.label().to_string())
+                .collect(),
+        );
+        list
+    }
+
+    /// Ask which columns the connection list shows, starting from the
+    /// current ones
+    pub(crate) fn open_connection_columns_prompt(&mut self) {
+        let prompt = format!(
+            "Columns in order, comma-separated ({}):",
+            ConnectionColumn::names(&ConnectionColumn::ALL)
+        );
+        self.modal_manager.open_input_modal(
+            InputModal::new(
+                " Connection columns ",
+                prompt,
+                InputPurpose::ConnectionColumns,
+            )
+            .with_value(&ConnectionColumn::names(&self.connection_columns)),
+        );
+    }
+
+    /// Show `list` (e.g. `name, host, notes`) as the connection list's
+    /// columns and remember them
+    pub(crate) fn set_connection_columns(&mut self, list: &str) {
+        let columns = match ConnectionColumn::parse_list(list) {
+            Ok(columns) => columns,
+            Err(e) => {
+                self.set_status(e);
+                return;
+            }
+        };
+        let saved = ConnectionColumn::names(&columns);
+        self.connection_columns = Arc::new(columns);
+        self.refresh_connections();
+        if let Err(e) =
+            SettingsService::save(CONNECTION_COLUMNS_SETTING, &saved)
+        {
+            self.set_status(format!("Failed to save setting: {e}"));
+        } else {
+            self.set_status(format!("Connection list columns: {saved}."));
+        }
+    }
+
+    /// Reload the saved connections, keeping the selected one selected
     pub(crate) fn refresh_connections(&mut self) {
         if let Ok(connections) = ConnectionService::get_all() {
-            self.database_explorer.connections = FilteredData::new(connections);
+            let anchor =
+                self.database_explorer.connections.table.selection_anchor();
+            self.database_explorer.connections =
+                self.connection_list(connections);
             // Reapply filter if one is active
             if let Some(search_filter) = &self.search_filter
                 && let Some(line) = search_filter.lines().first()
@@ -156,6 +466,10 @@ impl App<'_> {
             {
                 self.apply_filter();
             }
+            self.database_explorer
+                .connections
+                .table
+                .restore_selection(anchor.as_ref());
         }
     }
 
@@ -229,21 +543,27 @@ impl App<'_> {
             Some(v)
         })();
         if let Some(value) = value
-            && execute!(
-                std::io::stdout(),
-                clipboard::CopyToClipboard {
-                    content: value.clone(),
-                    destination: clipboard::ClipboardSelection(vec![
-                        clipboard::ClipboardType::Clipboard,
-                    ]),
-                }
-            )
-            .is_ok()
+            && Self::copy_to_clipboard(&value)
         {
             self.set_status(format!("Copied: {value}"));
         }
     }
 
+    /// Put `value` on the system clipboard (OSC 52). Returns whether the
+    /// terminal accepted it.
+    pub(crate) fn copy_to_clipboard(value: &str) -> bool {
+        execute!(
+            std::io::stdout(),
+            clipboard::CopyToClipboard {
+                content: value.to_string(),
+                destination: clipboard::ClipboardSelection(vec![
+                    clipboard::ClipboardType::Clipboard,
+                ]),
+            }
+        )
+        .is_ok()
+    }
+
     /// Set running to false to quit the application.
     pub(crate) const fn quit(&mut self) {
         self.running = false;
@@ -259,20 +579,99 @@ impl App<'_> {
         self.status_line.clear();
     }
 
-    fn run_editor(terminal: &mut DefaultTerminal, path: &Path) -> Result<()> {
+    /// Put `sql` into the SQL executor buffer, ready to review in the
+    /// editor
+    pub(crate) fn copy_into_sql_executor(&mut self, sql: &str) {
+        self.database_explorer.sql_executor.set_sql(sql);
+        self.save_scratch_buffer(sql);
+        // e.g. normalized statements from pg_stat_statements
+        let message = if sql.contains("$1") {
+            "Copied into the SQL executor; fill in its $n parameters with e \
+             before running it."
+        } else {
+            "Copied into the SQL executor; e reviews it, E runs it."
+        };
+        self.set_status(message);
+    }
+
+    /// Keep the SQL buffer for the next session on this connection, with its
+    /// secrets masked unless masking is off
+    pub(crate) fn save_scratch_buffer(&mut self, sql: &str) {
+        let connection_name = &self.database_explorer.connection.name;
+        let sql = self.secret_masking.apply(sql);
+        if let Err(e) = ScratchBufferService::save(connection_name, &sql) {
+            self.set_status(format!("Failed to save SQL buffer: {e}"));
+        }
+    }
+
+    /// Edit `path`, starting at `line` if given. `+<line>` is understood by
+    /// vi, nano, emacs and most other terminal editors.
+    fn run_editor(
+        terminal: &mut DefaultTerminal,
+        path: &Path,
+        line: Option<usize>,
+    ) -> Result<()> {
         let editor = std::env::var("VISUAL")
             .or_else(|_| std::env::var("EDITOR"))
             .unwrap_or_else(|_| "vim".to_string());
         let (program, args) = Self::parse_editor_command(&editor);
 
-        std::io::stdout().execute(LeaveAlternateScreen)?;
-        disable_raw_mode()?;
         let mut cmd = Command::new(&program);
         cmd.args(args);
-        cmd.arg(path).status()?;
+        if let Some(line) = line {
+            cmd.arg(format!("+{line}"));
+        }
+        cmd.arg(path);
+        Self::run_suspended(terminal, &mut cmd)??;
+        Ok(())
+    }
+
+    /// Hand the terminal to `command` until it exits. The outer error is
+    /// the terminal's, the inner one the command's.
+    fn run_suspended(
+        terminal: &mut DefaultTerminal,
+        command: &mut Command,
+    ) -> Result<std::io::Result<ExitStatus>> {
+        std::io::stdout().execute(LeaveAlternateScreen)?;
+        disable_raw_mode()?;
+        let status = command.status();
         std::io::stdout().execute(EnterAlternateScreen)?;
         enable_raw_mode()?;
         terminal.clear()?;
+        Ok(status)
+    }
+
+    /// Suspend d7s and open `psql` on the current connection, with the
+    /// table being browsed in the `:table` variable
+    fn run_psql(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let connection = &self.database_explorer.connection;
+        if connection.r#type != ConnectionType::Postgres {
+            self.set_status("psql only opens PostgreSQL connections.");
+            return Ok(());
+        }
+
+        let mut cmd = Command::new("psql");
+        cmd.arg(connection.connection_uri(None));
+        if let Some(password) =
+            connection.password.as_deref().filter(|p| !p.is_empty())
+        {
+            cmd.env("PGPASSWORD", password);
+        }
+        if let Some(search_path) = &connection.session_search_path {
+            cmd.env("PGOPTIONS", search_path_options(search_path));
+        }
+        if let Some(table) = self.current_table_qualified_name() {
+            cmd.arg("-v").arg(format!("table={table}"));
+        }
+
+        match Self::run_suspended(terminal, &mut cmd)? {
+            Ok(status) if status.success() => {}
+            Ok(status) => self.set_status(format!("psql exited with {status}")),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.set_status("psql was not found on PATH.");
+            }
+            Err(e) => self.set_status(format!("Failed to start psql: {e}")),
+        }
         Ok(())
     }
 
@@ -283,7 +682,234 @@ impl App<'_> {
         (program, args)
     }
 
-    fn enter_sql_results_state(&mut self, statement: String) {
+    /// Start `work` in the background unless another task is running.
+    /// Returns whether it was started.
+    pub(crate) fn start_background_task<F, Fut>(
+        &mut self,
+        label: &str,
+        work: F,
+    ) -> bool
+    where
+        F: FnOnce(ProgressSender) -> Fut,
+        Fut: Future<Output = Result<String, String>> + Send + 'static,
+    {
+        if let Some(task) = &self.background_task {
+            self.set_status(format!("Wait for {} to finish.", task.label));
+            return false;
+        }
+        self.background_task = Some(BackgroundTask::spawn(label, work));
+        true
+    }
+
+    /// [`Self::start_background_task`] for `work` that runs statements on
+    /// the SQL executor's session; the executor's statements are queued
+    /// until it ends.
+    pub(crate) fn start_connection_task<F, Fut>(
+        &mut self,
+        label: &str,
+        work: F,
+    ) -> bool
+    where
+        F: FnOnce(ProgressSender) -> Fut,
+        Fut: Future<Output = Result<String, String>> + Send + 'static,
+    {
+        let started = self.start_background_task(label, work);
+        if let Some(task) = self.background_task.as_mut().filter(|_| started) {
+            task.holds_connection = true;
+        }
+        started
+    }
+
+    /// Whether a background task is running statements on the session.
+    pub(crate) fn connection_busy(&self) -> bool {
+        self.background_task
+            .as_ref()
+            .is_some_and(|task| task.holds_connection)
+    }
+
+    /// Run the statements queued while the session was busy, once it
+    /// no longer is.
+    async fn run_queued_statements(&mut self) {
+        while !self.connection_busy()
+            && let Some(statement) = self.statement_queue.pop()
+        {
+            self.execute_sql_statement_now(statement).await;
+        }
+    }
+
+    /// How long to wait for a key before the screen needs another look, or
+    /// `None` to wait for as long as it takes.
+    fn poll_timeout(&self) -> Option<Duration> {
+        let filter = self
+            .filter_typed_at
+            .map(|typed_at| FILTER_DEBOUNCE.saturating_sub(typed_at.elapsed()));
+        let progress =
+            self.background_task.is_some().then_some(PROGRESS_REDRAW_INTERVAL);
+        let retry = self
+            .connect_retry
+            .as_ref()
+            .map(|retry| retry.due.saturating_duration_since(Instant::now()));
+        [filter, progress, retry].into_iter().flatten().min()
+    }
+
+    /// Pick up progress from the background task and report its outcome
+    /// once it finishes
+    async fn poll_background_task(&mut self) {
+        let Some(outcome) =
+            self.background_task.as_mut().and_then(BackgroundTask::poll)
+        else {
+            return;
+        };
+        let Some(task) = self.background_task.take() else {
+            return;
+        };
+        match outcome {
+            Ok(message) | Err(message) => self.set_status(message),
+        }
+        if let Some(summary) = task.summary {
+            self.modal_manager
+                .open_info_modal(InfoModal::new(summary.title, summary.lines));
+        }
+        // Statements it ran may have opened or ended a transaction
+        if task.holds_connection {
+            self.refresh_transaction_status().await;
+        }
+    }
+
+    /// Grow or shrink the top bar by a row and remember its height
+    pub(crate) fn resize_top_bar(&mut self, grow: bool) {
+        if !self.layout.resize_top_bar(grow) {
+            return;
+        }
+        let height = self.layout.top_bar_height;
+        if let Err(e) =
+            SettingsService::save(TOP_BAR_HEIGHT_SETTING, &height.to_string())
+        {
+            self.set_status(format!("Failed to save layout: {e}"));
+        } else if height == 0 {
+            self.set_status("Top bar hidden (Alt+Down to show it again).");
+        } else {
+            self.set_status(format!("Top bar height: {height} rows."));
+        }
+    }
+
+    /// Step the compact top bar through auto, on and off
+    pub(crate) fn cycle_compact_mode(&mut self) {
+        let mode = self.layout.compact.next();
+        self.layout.compact = mode;
+        if let Err(e) =
+            SettingsService::save(COMPACT_MODE_SETTING, mode.as_str())
+        {
+            self.set_status(format!("Failed to save layout: {e}"));
+        } else {
+            self.set_status(format!("Compact top bar: {}.", mode.as_str()));
+        }
+    }
+
+    /// Switch SQL keywords from the editor between upper case and as written
+    pub(crate) fn toggle_keyword_case(&mut self) {
+        let case = self.keyword_case.toggled();
+        self.keyword_case = case;
+        if let Err(e) =
+            SettingsService::save(KEYWORD_CASE_SETTING, case.as_str())
+        {
+            self.set_status(format!("Failed to save setting: {e}"));
+        } else {
+            self.set_status(format!("SQL keywords: {}.", case.as_str()));
+        }
+    }
+
+    /// Turn accessible mode on or off and remember it
+    pub(crate) fn toggle_accessible_mode(&mut self) {
+        let mode = self.accessible.toggled();
+        self.accessible = mode;
+        if let Err(e) = SettingsService::save(ACCESSIBLE_SETTING, mode.as_str())
+        {
+            self.set_status(format!("Failed to save setting: {e}"));
+        } else {
+            self.set_status(format!("Accessible mode: {}.", mode.as_str()));
+        }
+    }
+
+    /// Draw numbers in result grids with the locale's thousands separator
+    /// and decimal mark, or as the database returned them
+    pub(crate) fn toggle_number_format(&mut self) {
+        self.number_format = match self.number_format {
+            NumberFormat::Raw => NumberFormat::from_env(),
+            NumberFormat::Grouped { .. } => NumberFormat::Raw,
+        };
+        match self.number_format.format("1234567.89") {
+            Some(example) => {
+                self.set_status(format!("Numbers shown as {example}."));
+            }
+            None => self.set_status("Numbers shown as returned."),
+        }
+    }
+
+    /// Draw timestamps in result grids as the time from now, or exact
+    pub(crate) fn toggle_time_display(&mut self) {
+        self.time_display = self.time_display.toggled();
+        self.set_status(match self.time_display {
+            TimeDisplay::Relative => {
+                "Timestamps shown relative to now; Enter shows the exact value."
+            }
+            TimeDisplay::Exact => "Timestamps shown exact.",
+        });
+    }
+
+    /// In accessible mode, say in the status line which view or dialog was
+    /// just opened, unless something else is reported there already
+    fn announce_view(&mut self) {
+        let current = match self.modal_manager.active_modal_type() {
+            Some(kind) => modal_announcement(kind).to_string(),
+            None => view_announcement(&self.database_explorer.state),
+        };
+        if self.announced.as_ref() == Some(&current) {
+            return;
+        }
+        if self.accessible == AccessibleMode::On
+            && self.status_line.message().is_empty()
+        {
+            self.set_status(format!("{current}."));
+        }
+        self.announced = Some(current);
+    }
+
+    /// Move the connection list on to the next order and remember it
+    pub(crate) fn cycle_connection_sort(&mut self) {
+        let sort = self.connection_sort.cycled();
+        self.connection_sort = sort;
+        self.refresh_connections();
+        if let Err(e) =
+            SettingsService::save(CONNECTION_SORT_SETTING, &sort.as_saved())
+        {
+            self.set_status(format!("Failed to save setting: {e}"));
+        } else {
+            self.set_status(format!(
+                "Connections sorted by {}.",
+                sort.describe()
+            ));
+        }
+    }
+
+    /// Switch masking of secrets in the result history and saved SQL
+    /// buffers on or off
+    pub(crate) fn toggle_secret_masking(&mut self) {
+        let masking = self.secret_masking.toggled();
+        self.secret_masking = masking;
+        if let Err(e) =
+            SettingsService::save(SECRET_MASKING_SETTING, masking.as_str())
+        {
+            self.set_status(format!("Failed to save setting: {e}"));
+        } else {
+            self.set_status(format!(
+                "Mask secrets in result history and saved SQL: {}.",
+                masking.as_str()
+            ));
+        }
+    }
+
+    pub(crate) fn enter_sql_results_state(&mut self, statement: String) {
         if !matches!(
             self.database_explorer.state,
             DatabaseExplorerState::SqlResults(_)
@@ -299,9 +925,36 @@ impl App<'_> {
         &mut self,
         statement: String,
     ) {
-        if classify_statement(&statement)
-            == StatementSafety::RequiresConfirmation
+        // Probably a typo, so ask before sending it to the database
+        if let Some(paren) = unbalanced_paren(&statement) {
+            let warning =
+                format!("Unbalanced parentheses: {}", paren.summary());
+            self.modal_manager
+                .open_sql_execution_warning_modal(statement, &warning);
+            return;
+        }
+        let safety = classify_statement(&statement);
+        let mutating = safety != StatementSafety::ReadOnly;
+        let confirm = match safety {
+            StatementSafety::ReadOnly => false,
+            StatementSafety::RequiresConfirmation => {
+                self.confirmations.asks(ConfirmAction::Sql)
+            }
+            StatementSafety::Destructive => {
+                self.confirmations.asks(ConfirmAction::Sql)
+                    || self.confirmations.asks(ConfirmAction::Drop)
+            }
+        };
+        if let Some(estimate) = self.expensive_query_estimate(&statement).await
         {
+            let warning = if mutating {
+                format!("Modifies data; {}", estimate.summary())
+            } else {
+                format!("Expensive: {}", estimate.summary())
+            };
+            self.modal_manager
+                .open_sql_execution_warning_modal(statement, &warning);
+        } else if confirm {
             self.modal_manager
                 .open_sql_execution_confirmation_modal(statement);
         } else {
@@ -309,10 +962,52 @@ impl App<'_> {
         }
     }
 
+    /// The planner's estimate for `statement` when it goes over the
+    /// configured thresholds. Statements `EXPLAIN` can't handle run as usual.
+    async fn expensive_query_estimate(
+        &self,
+        statement: &str,
+    ) -> Option<QueryEstimate> {
+        if !self.explain_thresholds.is_enabled() {
+            return None;
+        }
+        let database = self.database_explorer.database.as_ref()?;
+        let estimate = database.estimate_query(statement).await.ok()??;
+        self.explain_thresholds
+            .exceeded_by(&estimate)
+            .then_some(estimate)
+    }
+
+    /// Run the statement shown in the SQL executor again, with the same
+    /// checks as the first time
+    pub(crate) async fn rerun_sql_statement(&mut self) {
+        let Some(statement) = self
+            .database_explorer
+            .sql_executor
+            .selected_statement()
+            .map(String::from)
+        else {
+            self.set_status("No statement to run again.");
+            return;
+        };
+        self.prepare_sql_statement_execution(statement).await;
+    }
+
     pub(crate) async fn execute_sql_statement_now(
         &mut self,
         statement: String,
     ) {
+        // One statement at a time on the client; this one waits its turn
+        if let Some(task) = self.background_task.as_ref()
+            && task.holds_connection
+        {
+            let label = task.label.to_lowercase();
+            self.statement_queue.push(statement);
+            if let Some(queued) = self.statement_queue.describe() {
+                self.set_status(format!("{queued} until {label} finishes."));
+            }
+            return;
+        }
         self.enter_sql_results_state(statement.clone());
         self.database_explorer
             .sql_executor
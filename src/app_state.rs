@@ -20,6 +20,22 @@ pub enum DatabaseExplorerState {
     SqlResults(String),        // SQL execution mode
 }
 
+impl DatabaseExplorerState {
+    /// Short name of what is being browsed, e.g. for the compact top bar.
+    #[must_use]
+    pub const fn mode_label(&self) -> &'static str {
+        match self {
+            Self::Connections => "Connections",
+            Self::Databases => "Databases",
+            Self::Schemas => "Schemas",
+            Self::Tables(_) => "Tables",
+            Self::Columns(..) => "Columns",
+            Self::TableData(..) => "Table data",
+            Self::SqlResults(_) => "SQL results",
+        }
+    }
+}
+
 impl Display for DatabaseExplorerState {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
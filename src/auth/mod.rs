@@ -9,6 +9,11 @@ use keyring::Entry;
 #[cfg(not(debug_assertions))]
 const SERVICE_NAME: &str = "d7s";
 
+/// Entry looked up to tell whether the platform store can be reached; it
+/// isn't expected to exist.
+#[cfg(not(debug_assertions))]
+const PROBE_USER: &str = "d7s-health-check";
+
 #[cfg(not(debug_assertions))]
 pub struct Keyring {
     entry: Entry,
@@ -70,6 +75,57 @@ impl std::fmt::Display for Error {
 #[cfg(not(debug_assertions))]
 impl std::error::Error for Error {}
 
+impl Error {
+    /// Whether the platform store is locked or unreachable, as opposed to
+    /// the entry being missing.
+    #[cfg(not(debug_assertions))]
+    #[must_use]
+    pub fn is_storage_unavailable(&self) -> bool {
+        match self {
+            Error::KeyringError(e) => matches!(
+                e,
+                keyring::Error::NoStorageAccess(_)
+                    | keyring::Error::PlatformFailure(_)
+            ),
+        }
+    }
+
+    /// The in-memory dev store is always available.
+    #[cfg(debug_assertions)]
+    #[must_use]
+    #[allow(clippy::unused_self)]
+    pub const fn is_storage_unavailable(&self) -> bool {
+        false
+    }
+}
+
+/// Check that the platform store can be reached, before a password has to
+/// be saved to it.
+///
+/// # Errors
+///
+/// Returns the store's error when it is locked or unreachable
+#[cfg(not(debug_assertions))]
+pub fn check_storage() -> Result<(), Error> {
+    let probe =
+        Keyring::new(PROBE_USER).and_then(|keyring| keyring.get_password());
+    match probe {
+        Err(e) if e.is_storage_unavailable() => Err(e),
+        Ok(_) | Err(_) => Ok(()),
+    }
+}
+
+/// The in-memory dev store is always available.
+///
+/// # Errors
+///
+/// Never returns an error in dev mode
+#[cfg(debug_assertions)]
+#[allow(clippy::unnecessary_wraps)]
+pub const fn check_storage() -> Result<(), Error> {
+    Ok(())
+}
+
 impl Keyring {
     /// Creates a new keyring entry
     ///
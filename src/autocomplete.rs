@@ -0,0 +1,193 @@
+//! Table and column names read in the background when a connection opens,
+//! so prompts that take them complete them on Tab and Ctrl+p goes to a
+//! table by part of its name. Kept per connection and database for the
+//! session; refreshing table data reads them again.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::{
+    app::App,
+    background_task::ProgressSender,
+    db::{
+        Database,
+        catalog::{Catalog, CatalogTable},
+    },
+    ui::widgets::modal::{InputModal, InputPurpose},
+};
+
+/// Connection name and selected database a catalog was read from.
+type CatalogKey = (String, Option<String>);
+
+/// Catalogs read this session, shared with the task reading one.
+#[derive(Debug, Clone, Default)]
+pub struct CatalogCache(Arc<Mutex<HashMap<CatalogKey, Catalog>>>);
+
+impl CatalogCache {
+    fn get(&self, key: &CatalogKey) -> Option<Catalog> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .cloned()
+    }
+
+    fn insert(&self, key: CatalogKey, catalog: Catalog) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, catalog);
+    }
+
+    fn remove(&self, key: &CatalogKey) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(key);
+    }
+}
+
+/// Every table of every schema with its columns, reporting tables read.
+async fn read_catalog(
+    database: &dyn Database,
+    progress: &ProgressSender,
+) -> Result<Catalog, String> {
+    let schemas = database
+        .get_schemas()
+        .await
+        .map_err(|e| format!("Failed to read schemas: {e}"))?;
+    let mut names = Vec::new();
+    for schema in schemas {
+        let tables = database.get_tables(&schema.name).await.map_err(|e| {
+            format!("Failed to read the tables of {}: {e}", schema.name)
+        })?;
+        names.extend(
+            tables
+                .into_iter()
+                .map(|table| (schema.name.clone(), table.name)),
+        );
+    }
+    let total = Some(names.len() as u64);
+    progress.report(0, total);
+    let mut catalog = Catalog::default();
+    for (schema, name) in names {
+        let columns =
+            database.get_columns(&schema, &name).await.map_err(|e| {
+                format!("Failed to read the columns of {schema}.{name}: {e}")
+            })?;
+        catalog.tables.push(CatalogTable {
+            schema,
+            name,
+            columns: columns.into_iter().map(|column| column.name).collect(),
+        });
+        progress.report(catalog.tables.len() as u64, total);
+    }
+    Ok(catalog)
+}
+
+impl App<'_> {
+    fn catalog_key(&self) -> CatalogKey {
+        let connection = &self.database_explorer.connection;
+        (
+            connection.name.clone(),
+            connection.selected_database.clone(),
+        )
+    }
+
+    /// Read the table and column names of the connected database in the
+    /// background, unless they were read already or another task runs
+    pub(crate) fn prefetch_catalog(&mut self) {
+        let key = self.catalog_key();
+        if self.background_task.is_some() || self.catalogs.get(&key).is_some() {
+            return;
+        }
+        let Some(database) = self.database_explorer.database.clone() else {
+            return;
+        };
+        let catalogs = self.catalogs.clone();
+        self.start_background_task(
+            "Reading table names",
+            |progress| async move {
+                let catalog =
+                    read_catalog(database.as_ref(), &progress).await?;
+                let message = format!(
+                    "Read {} table names for completion.",
+                    catalog.tables.len()
+                );
+                catalogs.insert(key, catalog);
+                Ok(message)
+            },
+        );
+    }
+
+    /// Forget the names read for the connected database, so they are read
+    /// again when next needed
+    pub(crate) fn invalidate_catalog(&mut self) {
+        self.catalogs.remove(&self.catalog_key());
+    }
+
+    /// The names read for the connected database, once they have been.
+    fn catalog(&self) -> Option<Catalog> {
+        self.catalogs.get(&self.catalog_key())
+    }
+
+    /// Tables of the connected database as `schema.table`, to complete a
+    /// table name with; none until they have been read
+    pub(crate) fn table_completions(&self) -> Vec<String> {
+        self.catalog()
+            .map(|catalog| catalog.table_names())
+            .unwrap_or_default()
+    }
+
+    /// Columns of `schema.table`, to complete a column name with; none
+    /// until they have been read
+    pub(crate) fn column_completions(
+        &self,
+        schema: &str,
+        table: &str,
+    ) -> Vec<String> {
+        self.catalog()
+            .and_then(|catalog| {
+                catalog.columns(schema, table).map(<[_]>::to_vec)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Ask for a table to open, completing its name on Tab; starts reading
+    /// the names when they haven't been yet
+    pub(crate) fn open_table_finder(&mut self) {
+        let Some(catalog) = self.catalog() else {
+            self.prefetch_catalog();
+            let waiting = self.background_task.as_ref().map_or_else(
+                || "Connect to a database to read its table names.".to_string(),
+                |task| format!("Wait for {} to finish.", task.label),
+            );
+            self.set_status(waiting);
+            return;
+        };
+        self.modal_manager.open_input_modal(
+            InputModal::new(
+                " Go to table ",
+                "Table, or letters of its name in order:",
+                InputPurpose::GoToTable,
+            )
+            .with_completions(catalog.table_names()),
+        );
+    }
+
+    /// Open the table `name` refers to
+    pub(crate) async fn go_to_table(&mut self, name: &str) {
+        let table = self
+            .catalog()
+            .and_then(|catalog| catalog.find(name).cloned());
+        let Some(table) = table else {
+            self.set_status(format!("No single table matches '{name}'."));
+            return;
+        };
+        if let Err(e) = self.open_table(&table.schema, &table.name).await {
+            self.set_status(format!("Failed to open {name}: {e}"));
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Long-running work such as exports, run off the key handler so the UI
+//! keeps drawing. The task reports progress over a channel and the status
+//! line shows it until the task finishes.
+
+use std::future::Future;
+
+use ratatui::text::Line;
+use tokio::sync::mpsc::{
+    UnboundedReceiver, UnboundedSender, error::TryRecvError, unbounded_channel,
+};
+
+/// Messages from a running task to the UI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TaskEvent {
+    Progress { done: u64, total: Option<u64> },
+    Summary(TaskSummary),
+    Finished(Result<String, String>),
+}
+
+/// Handed to the task to report how far it got.
+#[derive(Debug, Clone)]
+pub struct ProgressSender(UnboundedSender<TaskEvent>);
+
+impl ProgressSender {
+    /// `done` units of work out of `total`, when the total is known.
+    pub fn report(&self, done: u64, total: Option<u64>) {
+        // The UI may have stopped listening; the task still finishes
+        let _ = self.0.send(TaskEvent::Progress { done, total });
+    }
+
+    /// Lines to show under `title` once the task has finished.
+    pub fn summary(&self, title: impl Into<String>, lines: Vec<Line<'static>>) {
+        let _ = self.0.send(TaskEvent::Summary(TaskSummary {
+            title: title.into(),
+            lines,
+        }));
+    }
+}
+
+/// Report a task leaves behind, such as the outcome of each statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskSummary {
+    pub title: String,
+    pub lines: Vec<Line<'static>>,
+}
+
+/// A task running in the background and the latest progress it reported.
+#[derive(Debug)]
+pub struct BackgroundTask {
+    /// What is running, e.g. "Exporting query".
+    pub label: String,
+    pub done: u64,
+    pub total: Option<u64>,
+    /// Redraws since the task started; drives the spinner.
+    pub ticks: usize,
+    /// Runs statements on the SQL executor's session, so the executor's
+    /// statements wait for it to finish.
+    pub holds_connection: bool,
+    /// Report sent by the task, shown once it finishes.
+    pub summary: Option<TaskSummary>,
+    receiver: UnboundedReceiver<TaskEvent>,
+}
+
+impl BackgroundTask {
+    /// Start `work` on the runtime. It resolves to the status message to
+    /// show when it's done, or an error message.
+    pub fn spawn<F, Fut>(label: impl Into<String>, work: F) -> Self
+    where
+        F: FnOnce(ProgressSender) -> Fut,
+        Fut: Future<Output = Result<String, String>> + Send + 'static,
+    {
+        let (sender, receiver) = unbounded_channel();
+        let task = work(ProgressSender(sender.clone()));
+        tokio::spawn(async move {
+            let _ = sender.send(TaskEvent::Finished(task.await));
+        });
+        Self {
+            label: label.into(),
+            done: 0,
+            total: None,
+            ticks: 0,
+            holds_connection: false,
+            summary: None,
+            receiver,
+        }
+    }
+
+    /// Apply the progress reported since the last call. Returns the
+    /// outcome once the task has finished.
+    pub fn poll(&mut self) -> Option<Result<String, String>> {
+        self.ticks = self.ticks.wrapping_add(1);
+        loop {
+            match self.receiver.try_recv() {
+                Ok(TaskEvent::Progress { done, total }) => {
+                    self.done = done;
+                    self.total = total;
+                }
+                Ok(TaskEvent::Summary(summary)) => self.summary = Some(summary),
+                Ok(TaskEvent::Finished(outcome)) => return Some(outcome),
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    return Some(Err(format!(
+                        "{} stopped unexpectedly",
+                        self.label
+                    )));
+                }
+            }
+        }
+    }
+
+    /// Completed fraction, when the total is known.
+    #[must_use]
+    pub fn ratio(&self) -> Option<f64> {
+        #[allow(clippy::cast_precision_loss)]
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.done as f64 / total as f64).clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_progress_then_outcome() {
+        let (release, wait) = tokio::sync::oneshot::channel::<()>();
+        let mut task =
+            BackgroundTask::spawn("Counting", |progress| async move {
+                progress.report(3, Some(4));
+                let _ = wait.await;
+                Ok("Counted.".to_string())
+            });
+        while task.done == 0 {
+            assert_eq!(task.poll(), None);
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(task.ratio(), Some(0.75));
+
+        let _ = release.send(());
+        let outcome = loop {
+            if let Some(outcome) = task.poll() {
+                break outcome;
+            }
+            tokio::task::yield_now().await;
+        };
+        assert_eq!(outcome, Ok("Counted.".to_string()));
+    }
+}
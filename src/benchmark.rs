@@ -0,0 +1,213 @@
+//! Run the selected statement of the SQL executor several times off the
+//! key handler, throwing its rows away, and report how long it took: a
+//! quick way to compare two ways of writing a query.
+
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    style::{Modifier, Style},
+    text::Line,
+};
+
+use crate::{
+    app::App,
+    metrics,
+    sql::safety::{StatementSafety, classify_statement},
+    ui::widgets::modal::{InputModal, InputPurpose},
+};
+
+/// Runs offered in the prompt.
+const DEFAULT_RUNS: usize = 10;
+
+/// Most runs one benchmark makes.
+const MAX_RUNS: usize = 1_000;
+
+/// Latencies of the runs of a benchmark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchmarkStats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+impl BenchmarkStats {
+    /// Statistics of `timings`; `None` without any.
+    #[must_use]
+    pub fn from_timings(timings: &[Duration]) -> Option<Self> {
+        let mut sorted = timings.to_vec();
+        sorted.sort_unstable();
+        Some(Self {
+            runs: sorted.len(),
+            min: *sorted.first()?,
+            median: percentile(&sorted, 50),
+            p95: percentile(&sorted, 95),
+            max: *sorted.last()?,
+        })
+    }
+
+    /// One-line report, e.g. "10 runs: min 3.1 ms, median 3.4 ms, p95
+    /// 7.9 ms".
+    #[must_use]
+    pub fn summary(&self) -> String {
+        format!(
+            "{} runs: min {}, median {}, p95 {}",
+            self.runs,
+            millis(self.min),
+            millis(self.median),
+            millis(self.p95)
+        )
+    }
+
+    /// Report shown once the benchmark is done: the statement, then a
+    /// line per statistic.
+    #[must_use]
+    pub fn lines(&self, statement: &str) -> Vec<Line<'static>> {
+        let sql = statement.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut lines = vec![
+            Line::styled(sql, Style::default().add_modifier(Modifier::BOLD)),
+            Line::raw(""),
+            Line::raw(format!("{:<8}{:>12}", "runs", self.runs)),
+        ];
+        lines.extend(
+            [
+                ("min", self.min),
+                ("median", self.median),
+                ("p95", self.p95),
+                ("max", self.max),
+            ]
+            .map(|(label, value)| {
+                Line::raw(format!("{label:<8}{:>12}", millis(value)))
+            }),
+        );
+        lines
+    }
+}
+
+/// The value at or below which `pct` percent of `sorted` lie, by nearest
+/// rank.
+fn percentile(sorted: &[Duration], pct: usize) -> Duration {
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied().unwrap_or_default()
+}
+
+/// `duration` in milliseconds to a tenth.
+fn millis(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1_000.0)
+}
+
+/// Runs asked for in the prompt, between 1 and [`MAX_RUNS`].
+fn parse_runs(input: &str) -> Option<usize> {
+    input
+        .trim()
+        .parse()
+        .ok()
+        .filter(|runs| (1..=MAX_RUNS).contains(runs))
+}
+
+impl App<'_> {
+    /// Ask how many times to run the selected statement
+    pub(crate) fn open_benchmark_prompt(&mut self) {
+        let Some(statement) =
+            self.database_explorer.sql_executor.selected_statement()
+        else {
+            self.set_status("No statement to benchmark.");
+            return;
+        };
+        if classify_statement(statement) != StatementSafety::ReadOnly {
+            self.set_status(
+                "Only read-only statements can be benchmarked; running this \
+                 one again would repeat its changes.",
+            );
+            return;
+        }
+        self.modal_manager.open_input_modal(
+            InputModal::new(
+                " Benchmark ",
+                "Run the statement this many times, discarding its rows:",
+                InputPurpose::Benchmark,
+            )
+            .with_value(&DEFAULT_RUNS.to_string()),
+        );
+    }
+
+    /// Run the selected statement the number of times typed into the
+    /// prompt and report its latencies
+    pub(crate) fn benchmark_statement(&mut self, input: &str) {
+        let Some(runs) = parse_runs(input) else {
+            self.set_status(format!(
+                "Give a number of runs from 1 to {MAX_RUNS}."
+            ));
+            return;
+        };
+        let explorer = &self.database_explorer;
+        let (Some(database), Some(statement)) = (
+            explorer.database.clone(),
+            explorer.sql_executor.selected_statement().map(String::from),
+        ) else {
+            return;
+        };
+        let connection = explorer.connection.name.clone();
+        self.start_background_task("Benchmarking", |progress| async move {
+            let total = Some(runs as u64);
+            progress.report(0, total);
+            let mut timings = Vec::with_capacity(runs);
+            for run in 1..=runs {
+                let started = Instant::now();
+                let result = database.execute_sql(&statement).await;
+                let elapsed = started.elapsed();
+                metrics::record_query(
+                    &connection,
+                    elapsed,
+                    result.as_ref().ok().map(Vec::len),
+                );
+                if let Err(e) = result {
+                    return Err(format!("Benchmark run {run} failed: {e}"));
+                }
+                timings.push(elapsed);
+                progress.report(timings.len() as u64, total);
+            }
+            let stats = BenchmarkStats::from_timings(&timings)
+                .ok_or_else(|| "Benchmark made no runs.".to_string())?;
+            progress.summary(" Benchmark ", stats.lines(&statement));
+            Ok(format!("Benchmark: {}.", stats.summary()))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(values: &[u64]) -> Vec<Duration> {
+        values.iter().copied().map(Duration::from_millis).collect()
+    }
+
+    #[test]
+    fn reports_percentiles_by_nearest_rank() {
+        let timings = ms(&[9, 1, 4, 3, 2, 8, 5, 7, 6, 40]);
+        let stats = BenchmarkStats::from_timings(&timings).unwrap();
+        assert_eq!(stats.runs, 10);
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(5));
+        assert_eq!(stats.p95, Duration::from_millis(40));
+        assert_eq!(stats.max, Duration::from_millis(40));
+        assert_eq!(
+            stats.summary(),
+            "10 runs: min 1.0 ms, median 5.0 ms, p95 40.0 ms"
+        );
+
+        let one = BenchmarkStats::from_timings(&ms(&[3])).unwrap();
+        assert_eq!((one.median, one.p95), (one.min, one.max));
+        assert_eq!(BenchmarkStats::from_timings(&[]), None);
+    }
+
+    #[test]
+    fn limits_the_runs() {
+        assert_eq!(parse_runs(" 25 "), Some(25));
+        assert_eq!(parse_runs("0"), None);
+        assert_eq!(parse_runs("1001"), None);
+        assert_eq!(parse_runs("many"), None);
+    }
+}
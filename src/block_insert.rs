@@ -0,0 +1,195 @@
+//! Block insert in the SQL buffer: the same text put on a run of lines at
+//! one column, for quoting the values of an `IN` list or closing the rows
+//! of a `VALUES` block without going through the external editor.
+
+use std::str::FromStr;
+
+use crate::{
+    app::App,
+    ui::widgets::modal::{InputModal, InputPurpose},
+};
+
+/// Where on each line the text goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockColumn {
+    /// Before the character at this 1-based column; shorter lines are
+    /// padded with spaces to reach it.
+    At(usize),
+    /// After the last character of the line.
+    End,
+}
+
+/// Text to insert on lines `first..=last` (1-based) at `column`, read from
+/// `LINES:COLUMN TEXT` such as `3-9:1 '` or `3-9:$ ',`: a line or range of
+/// lines, a column or `$` for the end of the line, then the text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockInsert {
+    pub first: usize,
+    pub last: usize,
+    pub column: BlockColumn,
+    pub text: String,
+}
+
+impl FromStr for BlockInsert {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let usage =
+            || format!("'{input}' isn't LINES:COLUMN TEXT, e.g. 2-9:$ ,");
+        let (target, text) =
+            input.trim_start().split_once(' ').ok_or_else(usage)?;
+        let (lines, column) = target.split_once(':').ok_or_else(usage)?;
+        let (first, last) = lines.split_once('-').unwrap_or((lines, lines));
+        let number = |text: &str| text.parse::<usize>().ok().filter(|&n| n > 0);
+        let (Some(first), Some(last)) = (number(first), number(last)) else {
+            return Err(usage());
+        };
+        if first > last {
+            return Err(format!("Line {first} comes after line {last}."));
+        }
+        let column = if column == "$" {
+            BlockColumn::End
+        } else {
+            BlockColumn::At(number(column).ok_or_else(usage)?)
+        };
+        if text.is_empty() {
+            return Err("Nothing to insert.".to_string());
+        }
+        Ok(Self {
+            first,
+            last,
+            column,
+            text: text.to_string(),
+        })
+    }
+}
+
+impl BlockInsert {
+    /// `sql` with the text inserted on each of the lines; blank lines are
+    /// left alone. Fails when the lines run past the end of `sql`.
+    ///
+    /// # Errors
+    ///
+    /// The message for the status line when `sql` is too short.
+    pub fn apply(&self, sql: &str) -> Result<String, String> {
+        let mut lines = sql.lines().map(String::from).collect::<Vec<_>>();
+        if self.last > lines.len() {
+            return Err(format!(
+                "The SQL has {} lines; line {} is past its end.",
+                lines.len(),
+                self.last
+            ));
+        }
+        for line in lines
+            .iter_mut()
+            .take(self.last)
+            .skip(self.first.saturating_sub(1))
+            .filter(|line| !line.trim().is_empty())
+        {
+            let len = line.chars().count();
+            let at = match self.column {
+                BlockColumn::At(column) => column.saturating_sub(1),
+                BlockColumn::End => len,
+            };
+            line.extend(std::iter::repeat_n(' ', at.saturating_sub(len)));
+            let idx = line
+                .char_indices()
+                .nth(at)
+                .map_or(line.len(), |(idx, _)| idx);
+            line.insert_str(idx, &self.text);
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+impl App<'_> {
+    /// Ask for the lines and column of the SQL buffer to insert text at
+    pub(crate) fn open_block_insert_prompt(&mut self) {
+        let sql = self.database_explorer.sql_executor.sql_input();
+        let lines = sql.lines().count();
+        if sql.trim().is_empty() {
+            self.set_status("The SQL buffer is empty; press e to write some.");
+            return;
+        }
+        self.modal_manager.open_input_modal(
+            InputModal::new(
+                " Block insert ",
+                format!(
+                    "LINES:COLUMN TEXT, of lines 1-{lines}; a column of $ \
+                     appends to each line:"
+                ),
+                InputPurpose::BlockInsert,
+            )
+            .with_value(&format!("1-{lines}:1 ")),
+        );
+    }
+
+    /// Insert the text typed into the prompt and load the SQL as if it came
+    /// back from the editor
+    pub(crate) async fn block_insert(&mut self, input: &str) {
+        let sql = self.database_explorer.sql_executor.sql_input();
+        match input
+            .parse::<BlockInsert>()
+            .and_then(|insert| insert.apply(&sql))
+        {
+            Ok(sql) => self.load_sql(&sql).await,
+            Err(e) => self.set_status(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_lines_column_and_text() {
+        assert_eq!(
+            "2-4:$ ',".parse::<BlockInsert>().unwrap(),
+            BlockInsert {
+                first: 2,
+                last: 4,
+                column: BlockColumn::End,
+                text: "',".to_string(),
+            }
+        );
+        let one = "3:5 x y".parse::<BlockInsert>().unwrap();
+        assert_eq!((one.first, one.last), (3, 3));
+        assert_eq!(one.column, BlockColumn::At(5));
+        assert_eq!(one.text, "x y");
+        assert!("4-2:1 x".parse::<BlockInsert>().is_err());
+        assert!("0-2:1 x".parse::<BlockInsert>().is_err());
+        assert!("1-2:1".parse::<BlockInsert>().is_err());
+        assert!("1-2 x".parse::<BlockInsert>().is_err());
+    }
+
+    #[test]
+    fn inserts_at_the_same_column_on_each_line() {
+        let sql = "SELECT * FROM t WHERE id IN (\n  a\n  bb\n\n  c\n)";
+        let quote = BlockInsert {
+            first: 2,
+            last: 5,
+            column: BlockColumn::At(3),
+            text: "'".to_string(),
+        };
+        let close = BlockInsert {
+            column: BlockColumn::End,
+            text: "',".to_string(),
+            ..quote.clone()
+        };
+        let sql = close.apply(&quote.apply(sql).unwrap()).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT * FROM t WHERE id IN (\n  'a',\n  'bb',\n\n  'c',\n)"
+        );
+
+        let pad = BlockInsert {
+            first: 1,
+            last: 2,
+            column: BlockColumn::At(4),
+            text: "|".to_string(),
+        };
+        assert_eq!(pad.apply("ab\nabcdé").unwrap(), "ab |\nabc|dé");
+        assert!(pad.apply("one line").is_err());
+    }
+}
@@ -0,0 +1,688 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use color_eyre::{Result, eyre};
+
+use crate::{
+    app::{PKG_NAME, PKG_VERSION},
+    confirmations::{ConfirmAction, Confirmations},
+    connect_retry::DEFAULT_CONNECT_RETRIES,
+    db::{
+        pool::PoolLimits,
+        query_estimate::ExplainThresholds,
+        time_box::{TimeBox, parse_rows, parse_seconds},
+    },
+    deep_link::DeepLink,
+    ui::palette::Palette,
+};
+
+pub const WARN_COST_ENV: &str = "D7S_WARN_COST";
+pub const WARN_ROWS_ENV: &str = "D7S_WARN_ROWS";
+pub const STOP_AFTER_ENV: &str = "D7S_STOP_AFTER";
+pub const STOP_AFTER_ROWS_ENV: &str = "D7S_STOP_AFTER_ROWS";
+pub const CONFIRM_DISCONNECT_ENV: &str = "D7S_CONFIRM_DISCONNECT";
+pub const CONFIRM_ENV: &str = "D7S_CONFIRM";
+pub const CONNECT_RETRIES_ENV: &str = "D7S_CONNECT_RETRIES";
+pub const COLOR_ENV: &str = "D7S_COLOR";
+pub const METRICS_ENV: &str = "D7S_METRICS";
+pub const POOL_SIZE_ENV: &str = "D7S_POOL_SIZE";
+pub const POOL_IDLE_ENV: &str = "D7S_POOL_IDLE";
+
+const USAGE: &str = "\
+Usage: d7s [OPTIONS] [COMMAND]
+
+Commands:
+  test-connections       Try every saved connection and report pass/fail
+  restore-backup [FILE]  Replace saved connections and settings with a
+                         backup, or list the backups without FILE
+  export-queries FILE    Write the SQL buffer of every connection to a
+                         JSON file
+  import-queries FILE    Add the SQL buffers in a file from export-queries
+                         to the connections of the same name
+  open LINK              Open a table, or a row of it, from a link such as
+                         d7s://connection/[database/]schema/table?pk=1
+
+Options:
+      --data-dir <PATH>  Directory for d7s state (overrides D7S_DATA_DIR)
+      --warn-cost <N>    Ask before running queries the planner estimates
+                         to cost more than N (overrides D7S_WARN_COST)
+      --warn-rows <N>    Ask before running queries estimated to return
+                         more than N rows (overrides D7S_WARN_ROWS)
+      --stop-after <SECONDS>
+                         Stop reading the rows of a query run from the SQL
+                         editor after SECONDS and show those read so far as
+                         a partial result (overrides D7S_STOP_AFTER)
+      --stop-after-rows <N>
+                         The same after N rows (overrides
+                         D7S_STOP_AFTER_ROWS)
+      --confirm <ACTIONS>
+                         Actions that ask first: all (default), none, or
+                         some of delete-connection, disconnect, sql, drop
+                         and row-delete, comma-separated (overrides
+                         D7S_CONFIRM)
+      --no-confirm-disconnect
+                         Let Esc leave a connection without asking
+                         (or set D7S_CONFIRM_DISCONNECT=0)
+      --connect-retries <N>
+                         Connect again up to N times, waiting longer each
+                         time, when connecting fails for a reason that may
+                         pass (default 3, overrides D7S_CONNECT_RETRIES)
+      --pool-size <N>    Connections open at the same time per database for
+                         background work (default 4, overrides
+                         D7S_POOL_SIZE)
+      --pool-idle <N>    Of those, connections kept open between queries
+                         (default 1, overrides D7S_POOL_IDLE)
+      --source <FILE>    Run the statements in a .sql file once connected
+      --color <COLORS>   Colors the terminal shows: 16, 8 or none (default
+                         detected from TERM, overrides D7S_COLOR)
+      --metrics <FILE>   Count queries, rows, latency and cache hits, and
+                         write them to FILE on exit in the Prometheus text
+                         format; - for stdout (overrides D7S_METRICS)
+  -h, --help             Print help
+  -V, --version          Print version";
+
+/// What the binary should do after parsing its arguments.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Run,
+    Help,
+    Version,
+    TestConnections,
+    /// List the store backups, or restore the one given.
+    RestoreBackup(Option<PathBuf>),
+    /// Write the connections' SQL buffers to a file.
+    ExportQueries(PathBuf),
+    /// Add the SQL buffers in a file to the connections.
+    ImportQueries(PathBuf),
+}
+
+/// Parsed command-line options.
+#[derive(Debug, PartialEq)]
+pub struct Cli {
+    pub command: Command,
+    pub data_dir: Option<PathBuf>,
+    /// Thresholds from `--warn-cost` / `--warn-rows`.
+    pub explain_thresholds: ExplainThresholds,
+    /// Limits from `--stop-after` / `--stop-after-rows`.
+    pub time_box: TimeBox,
+    /// Actions from `--confirm`.
+    pub confirm: Option<Confirmations>,
+    /// `--no-confirm-disconnect` was passed.
+    pub no_confirm_disconnect: bool,
+    /// Retries from `--connect-retries`.
+    pub connect_retries: Option<u32>,
+    /// Connections from `--pool-size`.
+    pub pool_size: Option<usize>,
+    /// Idle connections from `--pool-idle`.
+    pub pool_idle: Option<usize>,
+    /// `.sql` file from `--source`, run on the first connection opened.
+    pub source: Option<PathBuf>,
+    /// Palette from `--color`.
+    pub color: Option<Palette>,
+    /// Table or row from `open`, shown once its connection opens.
+    pub link: Option<DeepLink>,
+    /// Report file from `--metrics`.
+    pub metrics: Option<PathBuf>,
+}
+
+impl Default for Cli {
+    fn default() -> Self {
+        Self {
+            command: Command::Run,
+            data_dir: None,
+            explain_thresholds: ExplainThresholds::default(),
+            time_box: TimeBox::default(),
+            confirm: None,
+            no_confirm_disconnect: false,
+            connect_retries: None,
+            pool_size: None,
+            pool_idle: None,
+            source: None,
+            color: None,
+            link: None,
+            metrics: None,
+        }
+    }
+}
+
+impl Cli {
+    /// Parse the process arguments.
+    pub fn parse() -> Result<Self> {
+        Self::parse_from(std::env::args_os().skip(1))
+    }
+
+    fn parse_from(args: impl IntoIterator<Item = OsString>) -> Result<Self> {
+        let mut cli = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let Some(arg_str) = arg.to_str() else {
+                return Err(eyre::eyre!("Invalid argument: {}", arg.display()));
+            };
+
+            match arg_str {
+                "-h" | "--help" => cli.command = Command::Help,
+                "-V" | "--version" => cli.command = Command::Version,
+                "test-connections" => cli.command = Command::TestConnections,
+                "restore-backup" => cli.command = Command::RestoreBackup(None),
+                "export-queries" | "import-queries" => {
+                    let Some(path) = args.next() else {
+                        return Err(eyre::eyre!(
+                            "{arg_str} requires a file\n\n{USAGE}"
+                        ));
+                    };
+                    let path = PathBuf::from(path);
+                    cli.command = if arg_str == "export-queries" {
+                        Command::ExportQueries(path)
+                    } else {
+                        Command::ImportQueries(path)
+                    };
+                }
+                "open" => {
+                    let link = args.next();
+                    let Some(link) = link.as_ref().and_then(|v| v.to_str())
+                    else {
+                        return Err(eyre::eyre!(
+                            "open requires a link\n\n{USAGE}"
+                        ));
+                    };
+                    let link = link.parse().map_err(|e| {
+                        eyre::eyre!("Invalid link: {e}\n\n{USAGE}")
+                    })?;
+                    cli.link = Some(link);
+                }
+                "--no-confirm-disconnect" => cli.no_confirm_disconnect = true,
+                "--confirm" => {
+                    let value = args.next();
+                    cli.set_confirm(value.as_ref().and_then(|v| v.to_str()))?;
+                }
+                "--data-dir" => {
+                    let Some(value) = args.next() else {
+                        return Err(eyre::eyre!(
+                            "--data-dir requires a path\n\n{USAGE}"
+                        ));
+                    };
+                    cli.data_dir = Some(PathBuf::from(value));
+                }
+                "--warn-cost" | "--warn-rows" => {
+                    let value = args.next();
+                    let value = value.as_ref().and_then(|v| v.to_str());
+                    cli.set_threshold(arg_str, value)?;
+                }
+                "--stop-after" | "--stop-after-rows" => {
+                    let value = args.next();
+                    let value = value.as_ref().and_then(|v| v.to_str());
+                    cli.set_time_box(arg_str, value)?;
+                }
+                "--source" => {
+                    let Some(value) = args.next() else {
+                        return Err(eyre::eyre!(
+                            "--source requires a file\n\n{USAGE}"
+                        ));
+                    };
+                    cli.source = Some(PathBuf::from(value));
+                }
+                "--metrics" => {
+                    let Some(value) = args.next() else {
+                        return Err(eyre::eyre!(
+                            "--metrics requires a file\n\n{USAGE}"
+                        ));
+                    };
+                    cli.metrics = Some(PathBuf::from(value));
+                }
+                "--color" => {
+                    let value = args.next();
+                    cli.set_color(value.as_ref().and_then(|v| v.to_str()))?;
+                }
+                "--connect-retries" => {
+                    let value = args.next();
+                    cli.set_connect_retries(
+                        value.as_ref().and_then(|v| v.to_str()),
+                    )?;
+                }
+                "--pool-size" | "--pool-idle" => {
+                    let value = args.next();
+                    let value = value.as_ref().and_then(|v| v.to_str());
+                    cli.set_pool_limit(arg_str, value)?;
+                }
+                _ => {
+                    if let Some(value) = arg_str.strip_prefix("--data-dir=") {
+                        cli.data_dir = Some(PathBuf::from(value));
+                    } else if let Some(value) =
+                        arg_str.strip_prefix("--source=")
+                    {
+                        cli.source = Some(PathBuf::from(value));
+                    } else if let Some(value) =
+                        arg_str.strip_prefix("--metrics=")
+                    {
+                        cli.metrics = Some(PathBuf::from(value));
+                    } else if let Some(value) = arg_str.strip_prefix("--color=")
+                    {
+                        cli.set_color(Some(value))?;
+                    } else if let Some(value) =
+                        arg_str.strip_prefix("--confirm=")
+                    {
+                        cli.set_confirm(Some(value))?;
+                    } else if let Some((flag, value)) = arg_str.split_once('=')
+                        && matches!(flag, "--warn-cost" | "--warn-rows")
+                    {
+                        cli.set_threshold(flag, Some(value))?;
+                    } else if let Some((flag, value)) = arg_str.split_once('=')
+                        && matches!(flag, "--stop-after" | "--stop-after-rows")
+                    {
+                        cli.set_time_box(flag, Some(value))?;
+                    } else if let Some(value) =
+                        arg_str.strip_prefix("--connect-retries=")
+                    {
+                        cli.set_connect_retries(Some(value))?;
+                    } else if let Some((flag, value)) = arg_str.split_once('=')
+                        && matches!(flag, "--pool-size" | "--pool-idle")
+                    {
+                        cli.set_pool_limit(flag, Some(value))?;
+                    } else if cli.command == Command::RestoreBackup(None)
+                        && !arg_str.starts_with('-')
+                    {
+                        cli.command =
+                            Command::RestoreBackup(Some(PathBuf::from(arg)));
+                    } else {
+                        return Err(eyre::eyre!(
+                            "Unknown argument: {arg_str}\n\n{USAGE}"
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(cli)
+    }
+
+    fn set_threshold(&mut self, flag: &str, value: Option<&str>) -> Result<()> {
+        let Some(limit) = value.and_then(|v| v.parse::<f64>().ok()) else {
+            return Err(eyre::eyre!("{flag} requires a number\n\n{USAGE}"));
+        };
+        if flag == "--warn-cost" {
+            self.explain_thresholds.max_cost = Some(limit);
+        } else {
+            self.explain_thresholds.max_rows = Some(limit);
+        }
+        Ok(())
+    }
+
+    fn set_time_box(&mut self, flag: &str, value: Option<&str>) -> Result<()> {
+        if flag == "--stop-after" {
+            let Some(limit) = value.and_then(parse_seconds) else {
+                return Err(eyre::eyre!(
+                    "--stop-after requires a number of seconds\n\n{USAGE}"
+                ));
+            };
+            self.time_box.max_time = Some(limit);
+        } else {
+            let Some(limit) = value.and_then(parse_rows) else {
+                return Err(eyre::eyre!(
+                    "--stop-after-rows requires a number of rows\n\n{USAGE}"
+                ));
+            };
+            self.time_box.max_rows = Some(limit);
+        }
+        Ok(())
+    }
+
+    fn set_connect_retries(&mut self, value: Option<&str>) -> Result<()> {
+        let Some(retries) = value.and_then(|v| v.parse().ok()) else {
+            return Err(eyre::eyre!(
+                "--connect-retries requires a number\n\n{USAGE}"
+            ));
+        };
+        self.connect_retries = Some(retries);
+        Ok(())
+    }
+
+    fn set_pool_limit(
+        &mut self,
+        flag: &str,
+        value: Option<&str>,
+    ) -> Result<()> {
+        let Some(limit) = value.and_then(|v| v.parse().ok()) else {
+            return Err(eyre::eyre!("{flag} requires a number\n\n{USAGE}"));
+        };
+        if flag == "--pool-size" {
+            if limit == 0 {
+                return Err(eyre::eyre!(
+                    "--pool-size must be at least 1\n\n{USAGE}"
+                ));
+            }
+            self.pool_size = Some(limit);
+        } else {
+            self.pool_idle = Some(limit);
+        }
+        Ok(())
+    }
+
+    fn set_confirm(&mut self, value: Option<&str>) -> Result<()> {
+        let Some(value) = value else {
+            return Err(eyre::eyre!(
+                "--confirm requires a list of actions\n\n{USAGE}"
+            ));
+        };
+        let confirm = Confirmations::parse(value)
+            .map_err(|e| eyre::eyre!("--confirm: {e}\n\n{USAGE}"))?;
+        self.confirm = Some(confirm);
+        Ok(())
+    }
+
+    fn set_color(&mut self, value: Option<&str>) -> Result<()> {
+        let Some(palette) = value.and_then(Palette::parse) else {
+            return Err(eyre::eyre!(
+                "--color requires 16, 8 or none\n\n{USAGE}"
+            ));
+        };
+        self.color = Some(palette);
+        Ok(())
+    }
+
+    /// Colors to draw with: the flag, else `D7S_COLOR`, else what `TERM`,
+    /// `COLORTERM` and `NO_COLOR` suggest.
+    #[must_use]
+    pub fn palette(&self) -> Palette {
+        self.color
+            .or_else(|| {
+                std::env::var(COLOR_ENV)
+                    .ok()
+                    .and_then(|v| Palette::parse(&v))
+            })
+            .unwrap_or_else(Palette::from_env)
+    }
+
+    /// Where to write the metrics report on exit: the flag, else
+    /// `D7S_METRICS`; `None` keeps no metrics.
+    #[must_use]
+    pub fn metrics(&self) -> Option<PathBuf> {
+        self.metrics.clone().or_else(|| {
+            std::env::var_os(METRICS_ENV)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        })
+    }
+
+    /// Retries for connections failing for a reason that may pass: the
+    /// flag, else `D7S_CONNECT_RETRIES`, else
+    /// [`DEFAULT_CONNECT_RETRIES`].
+    #[must_use]
+    pub fn connect_retries(&self) -> u32 {
+        self.connect_retries
+            .or_else(|| {
+                std::env::var(CONNECT_RETRIES_ENV)
+                    .ok()
+                    .and_then(|v| v.trim().parse().ok())
+            })
+            .unwrap_or(DEFAULT_CONNECT_RETRIES)
+    }
+
+    /// Connections per database from the flags, falling back to
+    /// `D7S_POOL_SIZE` and `D7S_POOL_IDLE`, then the defaults.
+    #[must_use]
+    pub fn pool_limits(&self) -> PoolLimits {
+        let env = |name: &str| {
+            std::env::var(name).ok().and_then(|v| v.trim().parse().ok())
+        };
+        let defaults = PoolLimits::default();
+        PoolLimits {
+            max_idle: self
+                .pool_idle
+                .or_else(|| env(POOL_IDLE_ENV))
+                .unwrap_or(defaults.max_idle),
+            max_size: self
+                .pool_size
+                .or_else(|| env(POOL_SIZE_ENV))
+                .filter(|&size| size > 0)
+                .unwrap_or(defaults.max_size),
+        }
+    }
+
+    /// Thresholds from the flags, falling back to `D7S_WARN_COST` and
+    /// `D7S_WARN_ROWS`.
+    #[must_use]
+    pub fn explain_thresholds(&self) -> ExplainThresholds {
+        let env = |name: &str| {
+            std::env::var(name).ok().and_then(|v| v.trim().parse().ok())
+        };
+        ExplainThresholds {
+            max_cost: self
+                .explain_thresholds
+                .max_cost
+                .or_else(|| env(WARN_COST_ENV)),
+            max_rows: self
+                .explain_thresholds
+                .max_rows
+                .or_else(|| env(WARN_ROWS_ENV)),
+        }
+    }
+
+    /// Limits on reading query rows from the flags, falling back to
+    /// `D7S_STOP_AFTER` and `D7S_STOP_AFTER_ROWS`.
+    #[must_use]
+    pub fn time_box(&self) -> TimeBox {
+        let env = |name: &str| std::env::var(name).ok();
+        TimeBox {
+            max_time: self.time_box.max_time.or_else(|| {
+                env(STOP_AFTER_ENV).and_then(|v| parse_seconds(&v))
+            }),
+            max_rows: self.time_box.max_rows.or_else(|| {
+                env(STOP_AFTER_ROWS_ENV).and_then(|v| parse_rows(&v))
+            }),
+        }
+    }
+
+    /// Whether Esc asks before leaving a connection: not with
+    /// `--no-confirm-disconnect`, nor when `D7S_CONFIRM_DISCONNECT` is `0`,
+    /// `false`, `no` or `off`.
+    #[must_use]
+    pub fn confirm_disconnect(&self) -> bool {
+        if self.no_confirm_disconnect {
+            return false;
+        }
+        std::env::var(CONFIRM_DISCONNECT_ENV)
+            .ok()
+            .is_none_or(|value| {
+                !matches!(
+                    value.trim().to_ascii_lowercase().as_str(),
+                    "0" | "false" | "no" | "off"
+                )
+            })
+    }
+
+    /// Actions that ask first: the flag, else `D7S_CONFIRM`, else all of
+    /// them; less disconnecting when [`Self::confirm_disconnect`] says so.
+    #[must_use]
+    pub fn confirmations(&self) -> Confirmations {
+        let confirmations = self
+            .confirm
+            .or_else(|| {
+                std::env::var(CONFIRM_ENV)
+                    .ok()
+                    .and_then(|v| Confirmations::parse(&v).ok())
+            })
+            .unwrap_or_default();
+        if self.confirm_disconnect() {
+            confirmations
+        } else {
+            confirmations.without(ConfirmAction::Disconnect)
+        }
+    }
+
+    pub fn print_help() {
+        println!("{PKG_NAME} {PKG_VERSION}\n\n{USAGE}");
+    }
+
+    pub fn print_version() {
+        println!("{PKG_NAME} {PKG_VERSION}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli> {
+        Cli::parse_from(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn parses_data_dir_forms() {
+        let separate = parse(&["--data-dir", "/tmp/d7s"]).unwrap();
+        assert_eq!(separate.data_dir, Some(PathBuf::from("/tmp/d7s")));
+
+        let inline = parse(&["--data-dir=/tmp/d7s"]).unwrap();
+        assert_eq!(inline.data_dir, Some(PathBuf::from("/tmp/d7s")));
+        assert_eq!(inline.command, Command::Run);
+    }
+
+    #[test]
+    fn parses_test_connections_subcommand() {
+        let cli =
+            parse(&["test-connections", "--data-dir", "/tmp/d7s"]).unwrap();
+        assert_eq!(cli.command, Command::TestConnections);
+        assert_eq!(cli.data_dir, Some(PathBuf::from("/tmp/d7s")));
+    }
+
+    #[test]
+    fn parses_restore_backup_subcommand() {
+        let list = parse(&["restore-backup"]).unwrap();
+        assert_eq!(list.command, Command::RestoreBackup(None));
+
+        let restore =
+            parse(&["restore-backup", "d7s-x-delete.db", "--data-dir", "/d"])
+                .unwrap();
+        assert_eq!(
+            restore.command,
+            Command::RestoreBackup(Some(PathBuf::from("d7s-x-delete.db")))
+        );
+        assert!(parse(&["restore-backup", "a.db", "b.db"]).is_err());
+    }
+
+    #[test]
+    fn parses_query_library_subcommands() {
+        let export = parse(&["export-queries", "queries.json"]).unwrap();
+        assert_eq!(
+            export.command,
+            Command::ExportQueries(PathBuf::from("queries.json"))
+        );
+        let import = parse(&["import-queries", "q.json", "--data-dir", "/d"])
+            .unwrap();
+        assert_eq!(
+            import.command,
+            Command::ImportQueries(PathBuf::from("q.json"))
+        );
+        assert!(parse(&["import-queries"]).is_err());
+    }
+
+    #[test]
+    fn parses_explain_thresholds() {
+        let cli = parse(&["--warn-cost", "1e6", "--warn-rows=5000"]).unwrap();
+        assert_eq!(cli.explain_thresholds.max_cost, Some(1e6));
+        assert_eq!(cli.explain_thresholds.max_rows, Some(5000.0));
+        assert!(parse(&["--warn-rows", "many"]).is_err());
+    }
+
+    #[test]
+    fn parses_time_box_limits() {
+        let cli =
+            parse(&["--stop-after", "2.5", "--stop-after-rows=500"]).unwrap();
+        assert_eq!(
+            cli.time_box.max_time,
+            Some(std::time::Duration::from_millis(2500))
+        );
+        assert_eq!(cli.time_box.max_rows, Some(500));
+        assert!(parse(&["--stop-after", "soon"]).is_err());
+        assert!(parse(&["--stop-after-rows=0"]).is_err());
+    }
+
+    #[test]
+    fn parses_confirmations() {
+        let cli = parse(&["--confirm", "drop,row-delete"]).unwrap();
+        assert_eq!(
+            cli.confirmations(),
+            Confirmations::parse("drop,row-delete").unwrap()
+        );
+        let cli = parse(&["--confirm=all", "--no-confirm-disconnect"]).unwrap();
+        assert!(!cli.confirmations().asks(ConfirmAction::Disconnect));
+        assert!(cli.confirmations().asks(ConfirmAction::Sql));
+        assert!(parse(&["--confirm", "everything"]).is_err());
+        assert!(parse(&["--confirm"]).is_err());
+    }
+
+    #[test]
+    fn parses_no_confirm_disconnect() {
+        assert!(!parse(&[]).unwrap().no_confirm_disconnect);
+        let cli = parse(&["--no-confirm-disconnect"]).unwrap();
+        assert!(!cli.confirm_disconnect());
+    }
+
+    #[test]
+    fn parses_connect_retries() {
+        assert_eq!(parse(&[]).unwrap().connect_retries, None);
+        let cli = parse(&["--connect-retries", "5"]).unwrap();
+        assert_eq!(cli.connect_retries(), 5);
+        let off = parse(&["--connect-retries=0"]).unwrap();
+        assert_eq!(off.connect_retries(), 0);
+        assert!(parse(&["--connect-retries", "-1"]).is_err());
+    }
+
+    #[test]
+    fn parses_pool_limits() {
+        let cli = parse(&["--pool-size", "8", "--pool-idle=2"]).unwrap();
+        assert_eq!(
+            cli.pool_limits(),
+            PoolLimits {
+                max_idle: 2,
+                max_size: 8,
+            }
+        );
+        assert!(parse(&["--pool-size", "0"]).is_err());
+        assert!(parse(&["--pool-idle", "some"]).is_err());
+    }
+
+    #[test]
+    fn parses_source_file() {
+        let cli = parse(&["--source", "fix.sql"]).unwrap();
+        assert_eq!(cli.source, Some(PathBuf::from("fix.sql")));
+        assert_eq!(cli.command, Command::Run);
+        let inline = parse(&["--source=fixes/orders.sql"]).unwrap();
+        assert_eq!(inline.source, Some(PathBuf::from("fixes/orders.sql")));
+        assert!(parse(&["--source"]).is_err());
+    }
+
+    #[test]
+    fn parses_open_link() {
+        let cli = parse(&["open", "d7s://prod/public/orders?pk=7"]).unwrap();
+        assert_eq!(cli.command, Command::Run);
+        let link = cli.link.unwrap();
+        assert_eq!(link.table, "orders");
+        assert_eq!(link.key.as_deref(), Some("7"));
+        assert!(parse(&["open"]).is_err());
+        assert!(parse(&["open", "prod/public/orders"]).is_err());
+    }
+
+    #[test]
+    fn parses_metrics_file() {
+        let cli = parse(&["--metrics", "-"]).unwrap();
+        assert_eq!(cli.metrics(), Some(PathBuf::from("-")));
+        let inline = parse(&["--metrics=d7s.prom"]).unwrap();
+        assert_eq!(inline.metrics, Some(PathBuf::from("d7s.prom")));
+        assert!(parse(&["--metrics"]).is_err());
+    }
+
+    #[test]
+    fn parses_color() {
+        let basic = parse(&["--color", "8"]).unwrap();
+        assert_eq!(basic.color, Some(Palette::Ansi8));
+        let none = parse(&["--color=none"]).unwrap();
+        assert_eq!(none.palette(), Palette::Monochrome);
+        assert!(parse(&["--color", "256"]).is_err());
+    }
+
+    #[test]
+    fn rejects_missing_value_and_unknown_flags() {
+        assert!(parse(&["--data-dir"]).is_err());
+        assert!(parse(&["--bogus"]).is_err());
+    }
+}
@@ -0,0 +1,416 @@
+//! Quick insight into the selected column of table data or SQL results:
+//! aggregate stats, a value histogram, row counts per value and values
+//! extracted from JSON, plus a diff of two rows and copying the column as
+//! an `IN` list.
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Line,
+};
+
+use crate::{
+    app::App,
+    app_state::DatabaseExplorerState,
+    db::{
+        column_stats::ColumnStats,
+        group_summary::group_rows,
+        histogram::{HISTOGRAM_BUCKETS, Histogram},
+        in_list::in_list,
+        json_path::{JsonExtraction, json_extraction_query},
+        row_diff::diff_rows,
+    },
+    sort::ColumnKind,
+    ui::widgets::{
+        group_summary::GroupSummaryView,
+        histogram::HistogramView,
+        modal::{ChoiceModal, InfoModal, InputModal, InputPurpose},
+        row_diff::RowDiffView,
+        table::{RawTableRow, TableDataState},
+    },
+};
+
+/// The grid being looked at and its selected column.
+struct SelectedColumn<'a> {
+    table: &'a TableDataState<RawTableRow>,
+    /// Index and name of the selected column, if any.
+    column: Option<(usize, String)>,
+    /// Schema and table when browsing table data, for whole-table queries.
+    source: Option<(&'a str, &'a str)>,
+}
+
+impl App<'_> {
+    /// The grid of table data or SQL results and its selected column.
+    fn selected_result_column(&self) -> Option<SelectedColumn<'_>> {
+        let explorer = &self.database_explorer;
+        let (table, source) = match &explorer.state {
+            DatabaseExplorerState::TableData(schema_name, table_name) => (
+                &explorer.table_data.as_ref()?.table,
+                Some((schema_name.as_str(), table_name.as_str())),
+            ),
+            DatabaseExplorerState::SqlResults(_) => {
+                (&explorer.sql_executor.table_state, None)
+            }
+            DatabaseExplorerState::Connections
+            | DatabaseExplorerState::Databases
+            | DatabaseExplorerState::Schemas
+            | DatabaseExplorerState::Tables(_)
+            | DatabaseExplorerState::Columns(..) => return None,
+        };
+        let column = table.view.state.selected_column().and_then(|column| {
+            Some((column, table.column_name(column)?.to_string()))
+        });
+        Some(SelectedColumn {
+            table,
+            column,
+            source,
+        })
+    }
+
+    /// Show count, sum, mean, min and max of the selected numeric column over
+    /// the loaded rows, and over the whole table when browsing table data
+    pub(crate) async fn show_column_stats(&mut self) {
+        let Some(SelectedColumn {
+            table,
+            column,
+            source,
+        }) = self.selected_result_column()
+        else {
+            return;
+        };
+        let Some((column, column_name)) = column else {
+            self.set_status("Select a column (h/l) to see its stats.");
+            return;
+        };
+        let rows = table
+            .model
+            .items
+            .iter()
+            .filter(|row| !row.is_draft)
+            .collect::<Vec<_>>();
+        let values = rows
+            .iter()
+            .map(|row| row.values.get(column).map_or("NULL", String::as_str));
+        let Some(loaded) = ColumnStats::from_values(values) else {
+            self.set_status(format!("Column '{column_name}' is not numeric."));
+            return;
+        };
+
+        let heading = Style::default().add_modifier(Modifier::BOLD);
+        let stat_lines = |stats: &ColumnStats| {
+            stats
+                .rows()
+                .into_iter()
+                .map(|(label, value)| {
+                    Line::from(format!("  {label:<6} {value}"))
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut lines = vec![Line::styled(
+            format!("Loaded rows ({})", rows.len()),
+            heading,
+        )];
+        lines.extend(stat_lines(&loaded));
+
+        if let Some((schema_name, table_name)) = source
+            && let Some(database) = self.database_explorer.database.as_ref()
+        {
+            lines.push(Line::default());
+            lines.push(Line::styled("Whole table", heading));
+            match database
+                .get_column_stats(schema_name, table_name, &column_name)
+                .await
+            {
+                Ok(stats) => lines.extend(stat_lines(&stats)),
+                Err(e) => lines.push(Line::styled(
+                    format!("  {e}"),
+                    Style::default().fg(Color::Red),
+                )),
+            }
+        }
+
+        self.modal_manager.open_info_modal(InfoModal::new(
+            format!(" Stats: {column_name} "),
+            lines,
+        ));
+    }
+
+    /// Show a histogram of the selected numeric or date/time column, bucketed
+    /// by the server over the whole table when browsing table data and over
+    /// the loaded rows for SQL results
+    pub(crate) async fn show_column_histogram(&mut self) {
+        let Some(SelectedColumn {
+            table,
+            column,
+            source,
+        }) = self.selected_result_column()
+        else {
+            return;
+        };
+        let Some((column, column_name)) = column else {
+            self.set_status("Select a column (h/l) to chart it.");
+            return;
+        };
+
+        let values = table
+            .model
+            .items
+            .iter()
+            .filter(|row| !row.is_draft)
+            .map(|row| row.values.get(column).map_or("NULL", String::as_str))
+            .collect::<Vec<_>>();
+        let temporal = match ColumnKind::infer(values.iter().copied()) {
+            ColumnKind::Numeric => false,
+            ColumnKind::Temporal => true,
+            ColumnKind::Text => {
+                self.set_status(format!(
+                    "Column '{column_name}' is not numeric or a date/time."
+                ));
+                return;
+            }
+        };
+
+        let (histogram, scope) =
+            match source.zip(self.database_explorer.database.as_ref()) {
+                Some(((schema_name, table_name), database)) => {
+                    match database
+                        .get_column_histogram(
+                            schema_name,
+                            table_name,
+                            &column_name,
+                            temporal,
+                            HISTOGRAM_BUCKETS,
+                        )
+                        .await
+                    {
+                        Ok(histogram) => (histogram, "whole table"),
+                        Err(e) => {
+                            self.set_status(format!(
+                                "Failed to build histogram: {e}"
+                            ));
+                            return;
+                        }
+                    }
+                }
+                None => (
+                    Histogram::from_values(values, temporal, HISTOGRAM_BUCKETS),
+                    "loaded rows",
+                ),
+            };
+        let Some(histogram) = histogram else {
+            self.set_status(format!("Column '{column_name}' has no values."));
+            return;
+        };
+
+        self.modal_manager.open_info_modal(InfoModal::new(
+            format!(" Histogram: {column_name} ({scope}) "),
+            HistogramView::new(&histogram).lines(),
+        ));
+    }
+
+    /// Offer to count the loaded rows per value of the selected column,
+    /// alone or with the sum of a numeric column
+    pub(crate) fn open_group_summary(&mut self) {
+        let Some(SelectedColumn { table, column, .. }) =
+            self.selected_result_column()
+        else {
+            return;
+        };
+        let Some((column, column_name)) = column else {
+            self.set_status("Select a column (h/l) to group by it.");
+            return;
+        };
+        let sum_columns = table
+            .shown_columns()
+            .into_iter()
+            .filter(|&idx| idx != column)
+            .filter(|&idx| {
+                let values =
+                    table.model.items.iter().filter(|row| !row.is_draft);
+                ColumnKind::infer(values.map(|row| {
+                    row.values.get(idx).map_or("NULL", String::as_str)
+                })) == ColumnKind::Numeric
+            })
+            .filter_map(|idx| Some((idx, table.column_name(idx)?.to_string())))
+            .collect::<Vec<_>>();
+        if sum_columns.is_empty() {
+            self.show_group_summary(column, None);
+            return;
+        }
+        self.modal_manager
+            .open_choice_modal(ChoiceModal::group_summary(
+                &column_name,
+                column,
+                sum_columns,
+            ));
+    }
+
+    /// Show the number of loaded rows per value of `column`, and the sum
+    /// of `sum_column` for each, largest groups first
+    pub(crate) fn show_group_summary(
+        &mut self,
+        column: usize,
+        sum_column: Option<usize>,
+    ) {
+        let Some(SelectedColumn { table, .. }) = self.selected_result_column()
+        else {
+            return;
+        };
+        let (Some(column_name), sum_name) = (
+            table.column_name(column),
+            sum_column.and_then(|idx| table.column_name(idx)),
+        ) else {
+            return;
+        };
+        let rows = table
+            .model
+            .items
+            .iter()
+            .filter(|row| !row.is_draft)
+            .map(|row| row.values.as_slice());
+        let groups = group_rows(rows, column, sum_column);
+        let lines =
+            GroupSummaryView::new(&groups, column_name, sum_name).lines();
+        let title = format!(" Group by: {column_name} ");
+        self.modal_manager
+            .open_info_modal(InfoModal::new(title, lines));
+    }
+
+    /// Copy the distinct values of the selected column over the loaded rows
+    /// as a SQL `IN` list
+    pub(crate) fn copy_column_in_list(&mut self) {
+        let Some(database) = self.database_explorer.database.clone() else {
+            return;
+        };
+        let Some(SelectedColumn { table, column, .. }) =
+            self.selected_result_column()
+        else {
+            return;
+        };
+        let Some((column, column_name)) = column else {
+            self.set_status("Select a column (h/l) to copy its values.");
+            return;
+        };
+        let values = table
+            .model
+            .items
+            .iter()
+            .filter(|row| !row.is_draft)
+            .filter_map(|row| row.values.get(column).map(String::as_str));
+        let Some((list, count)) = in_list(database.dialect(), values) else {
+            self.set_status(format!("{column_name} has no values to copy."));
+            return;
+        };
+        if Self::copy_to_clipboard(&list) {
+            self.set_status(format!(
+                "Copied {count} values of {column_name} as an IN list."
+            ));
+        } else {
+            self.set_status("Failed to copy to the clipboard.");
+        }
+    }
+
+    /// Ask for a JSON path or `->` / `->>` operators to apply to the
+    /// selected column
+    pub(crate) fn open_json_path_prompt(&mut self) {
+        let Some(SelectedColumn { column, .. }) =
+            self.selected_result_column()
+        else {
+            return;
+        };
+        let Some((_, column)) = column else {
+            self.set_status("Select a JSON column (h/l) to extract from it.");
+            return;
+        };
+        let prompt = format!(
+            "Extract from {column}: a path like $.customer.name or operators like ->'items'->>0"
+        );
+        self.modal_manager.open_input_modal(InputModal::new(
+            " JSON path ",
+            prompt,
+            InputPurpose::JsonPath { column },
+        ));
+    }
+
+    /// Run the rows again with the value `input` extracts from the JSON in
+    /// `column` as an extra column: the whole table when browsing table
+    /// data, or the query behind the SQL results
+    pub(crate) async fn extract_json_values(
+        &mut self,
+        column: &str,
+        input: &str,
+    ) {
+        let Some(extraction) = JsonExtraction::parse(input) else {
+            return;
+        };
+        let explorer = &self.database_explorer;
+        let Some(database) = explorer.database.as_ref() else {
+            return;
+        };
+        let dialect = database.dialect();
+        let source = match &explorer.state {
+            DatabaseExplorerState::TableData(schema_name, table_name) => {
+                dialect.qualified_table(schema_name, table_name)
+            }
+            DatabaseExplorerState::SqlResults(_) => {
+                // Masked statements aren't kept to run again
+                let Some(statement) = explorer.sql_executor.selected_statement()
+                else {
+                    self.set_status(
+                        "Run the query from the editor again to extract from its results.",
+                    );
+                    return;
+                };
+                format!(
+                    "(\n{}\n) results",
+                    statement.trim().trim_end_matches(';')
+                )
+            }
+            DatabaseExplorerState::Connections
+            | DatabaseExplorerState::Databases
+            | DatabaseExplorerState::Schemas
+            | DatabaseExplorerState::Tables(_)
+            | DatabaseExplorerState::Columns(..) => return,
+        };
+        let sql = json_extraction_query(dialect, &source, column, &extraction);
+        self.execute_sql_statement_now(sql).await;
+    }
+
+    /// Compare two rows column by column: the two rows marked with Space,
+    /// or the marked row and the one under the cursor
+    pub(crate) fn show_row_diff(&mut self) {
+        let Some(SelectedColumn { table, .. }) = self.selected_result_column()
+        else {
+            return;
+        };
+        let mut marked = table.multi_row_selection.iter().copied();
+        let pair = match table.multi_row_selection.len() {
+            2 => marked.next().zip(marked.next()),
+            1 => marked
+                .next()
+                .zip(table.view.state.selected())
+                .filter(|(marked, cursor)| marked != cursor)
+                .map(|(a, b)| (a.min(b), a.max(b))),
+            _ => None,
+        };
+        let Some((left, right)) = pair else {
+            self.set_status("Mark two rows with Space to compare them.");
+            return;
+        };
+        let (Some(left_row), Some(right_row), Some(columns)) = (
+            table.model.items.get(left),
+            table.model.items.get(right),
+            table.model.dynamic_column_names.as_deref(),
+        ) else {
+            return;
+        };
+
+        let diffs = diff_rows(columns, &left_row.values, &right_row.values);
+        let labels =
+            (format!("row {}", left + 1), format!("row {}", right + 1));
+        let lines = RowDiffView::new(&diffs, (&labels.0, &labels.1)).lines();
+        self.modal_manager.open_info_modal(InfoModal::new(
+            format!(" Diff: {} / {} ", labels.0, labels.1),
+            lines,
+        ));
+    }
+}
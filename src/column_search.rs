@@ -0,0 +1,92 @@
+//! Searching a text column of the table being browsed on the server.
+
+use crate::{
+    app::App,
+    app_state::DatabaseExplorerState,
+    db::{
+        connection::ConnectionType,
+        text_search::{TextSearchMode, text_search_query},
+    },
+    ui::widgets::modal::{ChoiceModal, InputModal, InputPurpose},
+};
+
+impl App<'_> {
+    /// Start a search of the selected column: choose how to match on
+    /// PostgreSQL, which has full-text search, or ask for the term
+    pub(crate) fn open_column_search(&mut self) {
+        let explorer = &self.database_explorer;
+        if !matches!(explorer.state, DatabaseExplorerState::TableData(..)) {
+            return;
+        }
+        let Some(column) = explorer.table_data.as_ref().and_then(|data| {
+            let table = &data.table;
+            table.column_name(table.view.state.selected_column()?)
+        }) else {
+            self.set_status("Select a column (h/l) to search it.");
+            return;
+        };
+        let column = column.to_string();
+        let connection_type = explorer.connection.r#type;
+        match connection_type {
+            ConnectionType::Postgres => self
+                .modal_manager
+                .open_choice_modal(ChoiceModal::column_search_mode(column)),
+            ConnectionType::Sqlite => {
+                let mode = TextSearchMode::Contains;
+                self.open_column_search_prompt(column, mode);
+            }
+        }
+    }
+
+    /// Ask for the term to search `column` for
+    pub(crate) fn open_column_search_prompt(
+        &mut self,
+        column: String,
+        mode: TextSearchMode,
+    ) {
+        let prompt = match mode {
+            TextSearchMode::Contains => {
+                format!("Rows whose {column} contains:")
+            }
+            TextSearchMode::FullText => {
+                format!("Rows whose {column} matches the words:")
+            }
+        };
+        self.modal_manager.open_input_modal(InputModal::new(
+            " Search column ",
+            prompt,
+            InputPurpose::ColumnSearch { column, mode },
+        ));
+    }
+
+    /// Show the rows of the table being browsed whose `column` matches
+    /// `term`
+    pub(crate) async fn search_column(
+        &mut self,
+        column: &str,
+        term: &str,
+        mode: TextSearchMode,
+    ) {
+        let explorer = &self.database_explorer;
+        let DatabaseExplorerState::TableData(schema_name, table_name) =
+            &explorer.state
+        else {
+            return;
+        };
+        let Some(database) = explorer.database.as_ref() else {
+            return;
+        };
+        let Some(sql) = text_search_query(
+            database.dialect(),
+            schema_name,
+            table_name,
+            column,
+            term,
+            mode,
+        ) else {
+            self.set_status("This database has no full-text search.");
+            return;
+        };
+        self.execute_sql_statement_now(sql).await;
+    }
+}
@@ -0,0 +1,107 @@
+//! Comparing the executed plan of a statement with a baseline run, e.g. to
+//! check that a new index helped.
+
+use crate::{
+    app::App,
+    db::query_plan::QueryPlan,
+    sql::safety::{StatementSafety, classify_statement},
+    ui::widgets::{
+        modal::{ChoiceModal, InfoModal, PlanBaselineChoice},
+        plan_compare::PlanCompareView,
+    },
+};
+
+/// Plan kept with `B` to compare later runs against, for the session.
+pub struct PlanBaseline {
+    pub sql: String,
+    pub plan: QueryPlan,
+}
+
+impl App<'_> {
+    /// Keep the plan of the current statement as the baseline, or offer to
+    /// compare with it when one is kept already
+    pub(crate) async fn plan_baseline_action(&mut self) {
+        let Some(baseline) = &self.plan_baseline else {
+            self.run_plan(PlanBaselineChoice::Replace).await;
+            return;
+        };
+        let sql = baseline.sql.split_whitespace().collect::<Vec<_>>();
+        let description = format!(
+            "Baseline: {} ms, {}",
+            baseline
+                .plan
+                .execution_ms
+                .map_or_else(|| "?".to_string(), |ms| format!("{ms:.2}")),
+            sql.join(" ")
+        );
+        self.modal_manager
+            .open_choice_modal(ChoiceModal::plan_baseline(description));
+    }
+
+    pub(crate) async fn handle_plan_baseline_choice(
+        &mut self,
+        choice: PlanBaselineChoice,
+    ) {
+        if choice == PlanBaselineChoice::Clear {
+            self.plan_baseline = None;
+            self.set_status("Plan baseline cleared.");
+        } else {
+            self.run_plan(choice).await;
+        }
+    }
+
+    /// Run the current statement under `EXPLAIN ANALYZE` and keep or
+    /// compare its plan
+    async fn run_plan(&mut self, choice: PlanBaselineChoice) {
+        let Some(sql) = self
+            .database_explorer
+            .sql_executor
+            .selected_statement()
+            .map(str::to_string)
+        else {
+            self.set_status("Run a statement first.");
+            return;
+        };
+        // `EXPLAIN ANALYZE` executes the statement
+        if classify_statement(&sql) != StatementSafety::ReadOnly {
+            self.set_status(
+                "Plans are only compared for read-only statements.",
+            );
+            return;
+        }
+        let Some(database) = self.database_explorer.database.as_ref() else {
+            return;
+        };
+        let plan = match database.explain_analyze(&sql).await {
+            Ok(Some(plan)) => plan,
+            Ok(None) => {
+                self.set_status("This database doesn't report executed plans.");
+                return;
+            }
+            Err(e) => {
+                self.set_status(format!("EXPLAIN ANALYZE failed: {e}"));
+                return;
+            }
+        };
+
+        match (choice, &self.plan_baseline) {
+            (PlanBaselineChoice::Compare, Some(baseline)) => {
+                let lines = PlanCompareView::new(&baseline.plan, &plan).lines();
+                self.modal_manager.open_info_modal(InfoModal::new(
+                    " Plan vs baseline ",
+                    lines,
+                ));
+            }
+            (PlanBaselineChoice::Compare, None)
+            | (PlanBaselineChoice::Replace | PlanBaselineChoice::Clear, _) => {
+                let ms = plan
+                    .execution_ms
+                    .map_or_else(|| "?".to_string(), |ms| format!("{ms:.2}"));
+                self.plan_baseline = Some(PlanBaseline { sql, plan });
+                self.set_status(format!(
+                    "Plan baseline kept ({ms} ms); press B after a change to compare."
+                ));
+            }
+        }
+    }
+}
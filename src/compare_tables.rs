@@ -0,0 +1,83 @@
+//! Comparing two tables, possibly on different connections, by row count
+//! and per-column checksums.
+
+use std::sync::Arc;
+
+use crate::{
+    app::App,
+    db::Database,
+    ui::widgets::{modal::InfoModal, table_compare::TableCompareView},
+};
+
+/// Table marked to compare the next one against. Keeps its database, so the
+/// comparison still works after switching connection.
+pub struct CompareSource {
+    /// Connection, database and table, e.g. `prod/app: public.orders`.
+    pub label: String,
+    pub database: Arc<dyn Database>,
+    pub schema: String,
+    pub table: String,
+}
+
+impl App<'_> {
+    /// Mark the current table for comparison, or compare it with the one
+    /// marked before. Marking the same table again clears the mark.
+    pub(crate) async fn compare_current_table(&mut self) {
+        let Some((schema, table)) = self.current_table() else {
+            return;
+        };
+        let Some(database) = self.database_explorer.database.clone() else {
+            return;
+        };
+        let connection = &self.database_explorer.connection;
+        let database_name = connection
+            .selected_database
+            .as_ref()
+            .map(|name| format!("/{name}"))
+            .unwrap_or_default();
+        let label =
+            format!("{}{database_name}: {schema}.{table}", connection.name);
+
+        let Some(source) = self.compare_source.take() else {
+            self.set_status(format!(
+                "Marked {label} for comparison; press C on another table, on any connection."
+            ));
+            self.compare_source = Some(CompareSource {
+                label,
+                database,
+                schema,
+                table,
+            });
+            return;
+        };
+        if source.label == label {
+            self.set_status("Comparison mark cleared.");
+            return;
+        }
+
+        let left = source
+            .database
+            .get_table_checksums(&source.schema, &source.table)
+            .await
+            .map_err(|e| format!("Failed to read {}: {e}", source.label));
+        let right = database
+            .get_table_checksums(&schema, &table)
+            .await
+            .map_err(|e| format!("Failed to read {label}: {e}"));
+        match (left, right) {
+            (Ok(left), Ok(right)) => {
+                let lines = TableCompareView::new(
+                    &left,
+                    &right,
+                    (&source.label, &label),
+                )
+                .lines();
+                self.modal_manager.open_info_modal(InfoModal::new(
+                    format!(" Compare: {} / {table} ", source.table),
+                    lines,
+                ));
+            }
+            (Err(message), _) | (_, Err(message)) => self.set_status(message),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Which actions ask before they run, from `--confirm` or `D7S_CONFIRM`, so
+//! cautious users can be asked about everything and others only about what
+//! can't be taken back.
+
+use std::fmt;
+
+/// An action that can ask for confirmation first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmAction {
+    /// `d` on the connection list.
+    DeleteConnection,
+    /// Esc out of the last level of a connection.
+    Disconnect,
+    /// SQL that writes, such as `UPDATE` or `CREATE TABLE`.
+    Sql,
+    /// `DROP` and `TRUNCATE`, asked about even when [`Self::Sql`] isn't.
+    Drop,
+    /// `d` on rows of table data.
+    RowDelete,
+}
+
+impl ConfirmAction {
+    pub const ALL: [Self; 5] = [
+        Self::DeleteConnection,
+        Self::Disconnect,
+        Self::Sql,
+        Self::Drop,
+        Self::RowDelete,
+    ];
+
+    /// Name in `--confirm` lists.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::DeleteConnection => "delete-connection",
+            Self::Disconnect => "disconnect",
+            Self::Sql => "sql",
+            Self::Drop => "drop",
+            Self::RowDelete => "row-delete",
+        }
+    }
+
+    const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// The actions that ask first; every one of them by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Confirmations(u8);
+
+impl Default for Confirmations {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl Confirmations {
+    #[must_use]
+    pub const fn all() -> Self {
+        Self((1 << ConfirmAction::ALL.len()) - 1)
+    }
+
+    #[must_use]
+    pub const fn none() -> Self {
+        Self(0)
+    }
+
+    /// Whether `action` asks first.
+    #[must_use]
+    pub const fn asks(self, action: ConfirmAction) -> bool {
+        self.0 & action.bit() != 0
+    }
+
+    /// The same, with `action` no longer asking.
+    #[must_use]
+    pub const fn without(self, action: ConfirmAction) -> Self {
+        Self(self.0 & !action.bit())
+    }
+
+    /// Read `all`, `none`, or a comma-separated list of the actions that
+    /// should ask, e.g. `drop,row-delete`.
+    ///
+    /// # Errors
+    ///
+    /// A message naming the valid actions when one isn't known.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "all" => return Ok(Self::all()),
+            "none" | "" => return Ok(Self::none()),
+            _ => {}
+        }
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .try_fold(Self::none(), |confirmations, name| {
+                ConfirmAction::ALL
+                    .into_iter()
+                    .find(|action| action.name().eq_ignore_ascii_case(name))
+                    .map(|action| Self(confirmations.0 | action.bit()))
+                    .ok_or_else(|| {
+                        format!(
+                            "unknown action '{name}'; use all, none or some \
+                             of {}",
+                            Self::all()
+                        )
+                    })
+            })
+    }
+}
+
+impl fmt::Display for Confirmations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = ConfirmAction::ALL
+            .into_iter()
+            .filter(|action| self.asks(*action))
+            .map(ConfirmAction::name)
+            .collect::<Vec<_>>();
+        if names.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", names.join(","))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lists_of_actions() {
+        let some = Confirmations::parse("Drop, row-delete").unwrap();
+        assert!(some.asks(ConfirmAction::Drop));
+        assert!(some.asks(ConfirmAction::RowDelete));
+        assert!(!some.asks(ConfirmAction::Sql));
+        assert_eq!(some.to_string(), "drop,row-delete");
+
+        assert_eq!(Confirmations::parse("all").unwrap(), Confirmations::all());
+        assert_eq!(Confirmations::parse("none").unwrap().to_string(), "none");
+        assert_eq!(
+            Confirmations::default().to_string(),
+            "delete-connection,disconnect,sql,drop,row-delete"
+        );
+        assert!(
+            !Confirmations::all()
+                .without(ConfirmAction::Disconnect)
+                .asks(ConfirmAction::Disconnect)
+        );
+        assert!(Confirmations::parse("drop,tables").is_err());
+    }
+}
@@ -0,0 +1,57 @@
+//! Connecting again after a failure that may pass, such as a refused
+//! connection while a server fails over. Retries wait longer each time and
+//! are made from the run loop, so the status line shows each attempt.
+
+use std::time::{Duration, Instant};
+
+use crate::db::connection::Connection;
+
+/// Retries after the first failed attempt unless configured otherwise.
+pub const DEFAULT_CONNECT_RETRIES: u32 = 3;
+
+/// Wait before the first retry; doubled for each further one.
+const FIRST_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest wait between two attempts.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(8);
+
+/// Wait before retry number `retry` (from 1): 0.5s, 1s, 2s and so on, up
+/// to 8s.
+#[must_use]
+pub fn backoff(retry: u32) -> Duration {
+    let factor = 1_u32
+        .checked_shl(retry.saturating_sub(1))
+        .unwrap_or(u32::MAX);
+    FIRST_RETRY_DELAY
+        .saturating_mul(factor)
+        .min(MAX_RETRY_DELAY)
+}
+
+/// A connection attempt waiting for its turn.
+#[derive(Debug, Clone)]
+pub struct ConnectRetry {
+    /// Connection to open, with its password
+    pub connection: Connection,
+    /// Number of the attempt to make, 2 for the first retry
+    pub attempt: u32,
+    /// When to make it
+    pub due: Instant,
+    /// Keep the password for the session once connected, as after typing
+    /// it into the password prompt
+    pub remember_password: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doubles_the_wait_up_to_a_limit() {
+        let waits = (1..=6).map(backoff).collect::<Vec<_>>();
+        assert_eq!(
+            waits,
+            [500, 1000, 2000, 4000, 8000, 8000].map(Duration::from_millis)
+        );
+        assert_eq!(backoff(40), MAX_RETRY_DELAY);
+    }
+}
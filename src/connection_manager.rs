@@ -1,11 +1,22 @@
+use std::time::Instant;
+
 use color_eyre::Result;
 
 use crate::{
     app::App,
     app_state::{AppState, DatabaseExplorerState},
+    confirmations::ConfirmAction,
+    connect_retry::{ConnectRetry, backoff},
     database_explorer_state::DatabaseExplorer,
-    db::connection::{Connection, ConnectionType},
-    ui::widgets::top_bar_view::{CONNECTION_HOTKEYS, DATABASE_HOTKEYS},
+    db::{
+        Database,
+        connection::{
+            Connection, ConnectionType, check_target_session_attrs,
+            quick_connection,
+        },
+    },
+    services::{ConnectionService, RecentTableService, ScratchBufferService},
+    ui::widgets::modal::{ChoiceModal, InputModal, InputPurpose},
 };
 
 impl App<'_> {
@@ -53,29 +64,59 @@ impl App<'_> {
         Ok(())
     }
 
+    /// Ask for a target to connect to without saving it
+    pub(crate) fn open_quick_connect_prompt(&mut self) {
+        self.modal_manager.open_input_modal(
+            InputModal::new(
+                " Quick connect ",
+                "user[:password]@host[:port][/db], a postgres:// URL or \
+                 sqlite:<path>:",
+                InputPurpose::QuickConnect,
+            )
+            .with_value(":connect "),
+        );
+    }
+
+    /// Connect to the target typed into the quick-connect prompt, asking
+    /// for the password when it has none. Nothing is saved; leaving the
+    /// connection offers to save it.
+    pub(crate) async fn quick_connect(&mut self, input: &str) -> Result<()> {
+        let Some(connection) = quick_connection(input) else {
+            self.set_status(
+                "Usage: :connect user@host:port/db, a postgres:// URL or sqlite:<path>",
+            );
+            return Ok(());
+        };
+        if connection.r#type == ConnectionType::Postgres
+            && let Err(e) = check_target_session_attrs(&connection.url)
+        {
+            self.set_status(e);
+            return Ok(());
+        }
+        match (connection.r#type, connection.password.clone()) {
+            (ConnectionType::Sqlite, _) => {
+                self.connect_sqlite_direct(connection).await
+            }
+            (ConnectionType::Postgres, Some(password)) => {
+                self.connect_with_password(connection, password).await
+            }
+            (ConnectionType::Postgres, None) => {
+                let prompt = format!(
+                    "Enter password for user '{}':",
+                    connection.user_display()
+                );
+                self.modal_manager.open_password_modal(connection, prompt);
+                Ok(())
+            }
+        }
+    }
+
     /// Connect to `SQLite` database (no password)
     async fn connect_sqlite_direct(
         &mut self,
         connection: Connection,
     ) -> Result<()> {
-        let sqlite = connection.to_sqlite();
-        if !sqlite.test().await {
-            self.set_status(format!(
-                "Failed to connect to database: {}",
-                connection.name
-            ));
-            return Ok(());
-        }
-
-        self.database_explorer =
-            DatabaseExplorer::new(connection, Some(sqlite));
-        self.state = AppState::DatabaseConnected;
-        self.hotkeys = DATABASE_HOTKEYS.to_vec();
-
-        // SQLite doesn't need the Databases/Schemas navigation steps
-        // Load tables directly from the default sqlite_schema
-        self.load_tables("sqlite_schema").await?;
-        Ok(())
+        self.connect_attempt(connection, 1, false).await
     }
 
     /// Connect to database with the provided password
@@ -84,46 +125,212 @@ impl App<'_> {
         connection: Connection,
         password: String,
     ) -> Result<()> {
-        // Create connection with password
-        let mut connection_with_password = connection.clone();
-        connection_with_password.password = Some(password);
-
-        // For PostgreSQL, connect to a default database first to list databases
-        let default_db = "postgres".to_string();
-
-        // Create a temporary connection to the default database
-        let mut temp_connection = connection_with_password.clone();
-        temp_connection.selected_database = Some(default_db.clone());
-        let postgres = temp_connection.to_postgres();
-
-        if postgres.test().await {
-            // Connection successful; keep selected_database so explorer is on "postgres"
-            connection_with_password.selected_database = Some(default_db);
-            self.database_explorer =
-                DatabaseExplorer::new(connection_with_password, Some(postgres));
-            self.state = AppState::DatabaseConnected;
-
-            // Update hotkeys for database mode
-            self.hotkeys = DATABASE_HOTKEYS.to_vec();
-
-            // Load databases after successful connection
-            self.load_databases().await?;
+        let mut connection = connection;
+        connection.password = Some(password);
+        self.connect_attempt(connection, 1, false).await
+    }
+
+    /// Make connection attempt number `attempt`. When it fails for a reason
+    /// that may pass, the run loop makes the next one after a growing wait,
+    /// up to the configured number of retries. `remember_password` keeps
+    /// the password for the session once connected.
+    async fn connect_attempt(
+        &mut self,
+        mut connection: Connection,
+        attempt: u32,
+        remember_password: bool,
+    ) -> Result<()> {
+        self.connect_retry = None;
+        let database = match connection.r#type {
+            ConnectionType::Postgres => {
+                // Connect to a default database first to list databases
+                connection.selected_database = Some("postgres".to_string());
+                connection.to_postgres()
+            }
+            ConnectionType::Sqlite => connection.to_sqlite(),
+        };
+        let error = match database.check_connection().await {
+            Ok(()) => {
+                if remember_password
+                    && let Some(password) = connection.password.clone()
+                {
+                    self.password_service
+                        .store_session_password(&connection, password);
+                }
+                return self.open_connection(connection, database).await;
+            }
+            Err(error) => error,
+        };
+
+        let name = connection.name.clone();
+        if error.transient && attempt <= self.connect_retries {
+            let wait = backoff(attempt);
+            self.set_status(format!(
+                "Connecting to {name}: attempt {attempt} of {} failed \
+                 ({error}), trying again in {:.1}s. Esc stops.",
+                self.connect_retries + 1,
+                wait.as_secs_f64()
+            ));
+            self.connect_retry = Some(ConnectRetry {
+                connection,
+                attempt: attempt + 1,
+                due: Instant::now() + wait,
+                remember_password,
+            });
+        } else if attempt > 1 {
+            self.set_status(format!(
+                "Failed to connect to database: {name} after {attempt} \
+                 attempts ({error})"
+            ));
         } else {
             self.set_status(format!(
-                "Failed to connect to database: {}",
-                connection.name
+                "Failed to connect to database: {name} ({error})"
             ));
         }
         Ok(())
     }
 
+    /// Make the scheduled connection attempt once it is due
+    pub(crate) async fn poll_connect_retry(&mut self) -> Result<()> {
+        let Some(retry) = self
+            .connect_retry
+            .take_if(|retry| retry.due <= Instant::now())
+        else {
+            return Ok(());
+        };
+        self.connect_attempt(
+            retry.connection,
+            retry.attempt,
+            retry.remember_password,
+        )
+        .await
+    }
+
+    /// Stop retrying a connection, returning whether one was pending
+    pub(crate) fn cancel_connect_retry(&mut self) -> bool {
+        let Some(retry) = self.connect_retry.take() else {
+            return false;
+        };
+        self.set_status(format!(
+            "Stopped connecting to {}.",
+            retry.connection.name
+        ));
+        true
+    }
+
+    /// Open the explorer on a connection that just passed its check
+    async fn open_connection(
+        &mut self,
+        connection: Connection,
+        database: Box<dyn Database>,
+    ) -> Result<()> {
+        let connection_type = connection.r#type;
+        self.database_explorer =
+            DatabaseExplorer::new(connection, Some(database));
+        self.record_connection_use();
+        self.load_recent_tables();
+        self.load_scratch_buffer();
+        self.state = AppState::DatabaseConnected;
+        self.refresh_transaction_status().await;
+
+        let loaded = match connection_type {
+            ConnectionType::Postgres => {
+                self.refresh_search_path().await;
+                self.load_databases().await
+            }
+            // SQLite has no Databases step; attached databases are its
+            // schemas
+            ConnectionType::Sqlite => self.load_schemas().await,
+        };
+        if let Some(path) = self.pending_source.take() {
+            self.source_sql_file(&path);
+        }
+        self.prefetch_catalog();
+        loaded?;
+        self.follow_link().await
+    }
+
+    /// Remember the connection as the last one used
+    fn record_connection_use(&mut self) {
+        let connection = &self.database_explorer.connection;
+        if connection.transient {
+            return;
+        }
+        let name = &connection.name;
+        if let Err(e) = ConnectionService::record_use(name) {
+            self.set_status(format!("Failed to record connection use: {e}"));
+        }
+    }
+
+    /// Restore the recently opened tables of the connected connection
+    fn load_recent_tables(&mut self) {
+        let explorer = &mut self.database_explorer;
+        match RecentTableService::get_all(&explorer.connection.name) {
+            Ok(recent_tables) => explorer.recent_tables = recent_tables,
+            Err(e) => {
+                self.set_status(format!("Failed to load recent tables: {e}"));
+            }
+        }
+    }
+
+    /// Restore the SQL executor buffer left by the last session on this
+    /// connection
+    fn load_scratch_buffer(&mut self) {
+        let explorer = &mut self.database_explorer;
+        match ScratchBufferService::get(&explorer.connection.name) {
+            Ok(Some(sql)) => explorer.sql_executor.set_sql(&sql),
+            Ok(None) => {}
+            Err(e) => {
+                self.set_status(format!("Failed to load SQL buffer: {e}"));
+            }
+        }
+    }
+
+    /// Leave the connection for the connection list, asking first unless
+    /// that was turned off
+    pub(crate) fn request_disconnect(&mut self) {
+        if self.confirmations.asks(ConfirmAction::Disconnect) {
+            let name = self.database_explorer.connection.name.clone();
+            self.modal_manager
+                .open_choice_modal(ChoiceModal::disconnect(&name));
+        } else {
+            self.disconnect();
+        }
+    }
+
+    /// Leave the connection for the connection list, offering to save it
+    /// when it came from the quick-connect prompt
+    pub(crate) fn disconnect(&mut self) {
+        let connection = self.database_explorer.connection.clone();
+        self.disconnect_from_database();
+        self.refresh_connections();
+        self.set_status(format!("Disconnected from {}.", connection.name));
+        if connection.transient {
+            self.modal_manager.open_choice_modal(
+                ChoiceModal::save_quick_connection(connection),
+            );
+        }
+    }
+
+    /// Open the new connection form filled in from a quick connection,
+    /// password included
+    pub(crate) fn save_quick_connection(&mut self, connection: &Connection) {
+        let url = match connection.r#type {
+            ConnectionType::Postgres => Connection {
+                selected_database: None,
+                ..connection.clone()
+            }
+            .connection_uri(connection.password.as_deref()),
+            ConnectionType::Sqlite => connection.url.clone(),
+        };
+        self.modal_manager.open_new_connection_modal_with_url(&url);
+    }
+
     /// Disconnect from the current database
     pub fn disconnect_from_database(&mut self) {
         self.database_explorer.state = DatabaseExplorerState::Connections;
         self.database_explorer.recent_tables.clear();
+        self.statement_queue.clear();
         self.state = AppState::ConnectionList;
-
-        // Update hotkeys for connection mode
-        self.hotkeys = CONNECTION_HOTKEYS.to_vec();
     }
 }
@@ -0,0 +1,264 @@
+//! Order of the connection list, cycled with `s` and remembered.
+
+use std::cmp::Ordering;
+
+use crate::{
+    db::{connection::Connection, connection_column::ConnectionColumn},
+    sort::SortDirection,
+};
+
+/// Setting key the connection list order is stored under.
+pub const CONNECTION_SORT_SETTING: &str = "connections.sort";
+
+/// What the connection list is ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConnectionSortKey {
+    #[default]
+    Name,
+    Host,
+    Database,
+    LastUsed,
+}
+
+impl ConnectionSortKey {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Host => "host",
+            Self::Database => "database",
+            Self::LastUsed => "last_used",
+        }
+    }
+
+    /// Shown in the status line.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Host => "host",
+            Self::Database => "database",
+            Self::LastUsed => "last used",
+        }
+    }
+
+    const fn next(self) -> Self {
+        match self {
+            Self::Name => Self::Host,
+            Self::Host => Self::Database,
+            Self::Database => Self::LastUsed,
+            Self::LastUsed => Self::Name,
+        }
+    }
+
+    /// Direction the key is sorted in first: most recently used first for
+    /// [`Self::LastUsed`], A to Z for the rest.
+    const fn first_direction(self) -> SortDirection {
+        match self {
+            Self::LastUsed => SortDirection::Descending,
+            Self::Name | Self::Host | Self::Database => {
+                SortDirection::Ascending
+            }
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        [Self::Name, Self::Host, Self::Database, Self::LastUsed]
+            .into_iter()
+            .find(|key| key.as_str() == value)
+    }
+}
+
+/// Order of the connection list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionSort {
+    pub key: ConnectionSortKey,
+    pub direction: SortDirection,
+}
+
+impl Default for ConnectionSort {
+    fn default() -> Self {
+        Self {
+            key: ConnectionSortKey::Name,
+            direction: SortDirection::Ascending,
+        }
+    }
+}
+
+impl ConnectionSort {
+    /// Next step of the `s` cycle: each key in both directions, then the
+    /// next key.
+    #[must_use]
+    pub const fn cycled(self) -> Self {
+        let key = self.key;
+        if matches!(
+            (key.first_direction(), self.direction),
+            (SortDirection::Ascending, SortDirection::Ascending)
+                | (SortDirection::Descending, SortDirection::Descending)
+        ) {
+            let direction = match self.direction {
+                SortDirection::Ascending => SortDirection::Descending,
+                SortDirection::Descending => SortDirection::Ascending,
+            };
+            return Self { key, direction };
+        }
+        let key = key.next();
+        Self {
+            key,
+            direction: key.first_direction(),
+        }
+    }
+
+    /// Stored form, e.g. `host:desc`.
+    #[must_use]
+    pub fn as_saved(self) -> String {
+        let direction = match self.direction {
+            SortDirection::Ascending => "asc",
+            SortDirection::Descending => "desc",
+        };
+        format!("{}:{direction}", self.key.as_str())
+    }
+
+    #[must_use]
+    pub fn from_saved(saved: Option<&str>) -> Self {
+        let Some((key, direction)) =
+            saved.and_then(|s| s.trim().split_once(':'))
+        else {
+            return Self::default();
+        };
+        let direction = match direction {
+            "asc" => SortDirection::Ascending,
+            "desc" => SortDirection::Descending,
+            _ => return Self::default(),
+        };
+        ConnectionSortKey::parse(key)
+            .map_or_else(Self::default, |key| Self { key, direction })
+    }
+
+    /// Status line description, e.g. `host ▲`.
+    #[must_use]
+    pub fn describe(self) -> String {
+        format!("{} {}", self.key.label(), self.direction.arrow())
+    }
+
+    /// Order `connections`, by name where the key is equal. Connections
+    /// never used go last when sorting by last use.
+    pub fn apply(self, connections: &mut [Connection]) {
+        connections.sort_by(|a, b| {
+            let ordering = match self.key {
+                ConnectionSortKey::Name => Ordering::Equal,
+                ConnectionSortKey::Host => {
+                    let host = ConnectionColumn::Host;
+                    compare_text(&host.value(a), &host.value(b))
+                }
+                ConnectionSortKey::Database => {
+                    let database = ConnectionColumn::Database;
+                    compare_text(&database.value(a), &database.value(b))
+                }
+                ConnectionSortKey::LastUsed => {
+                    match (a.last_used, b.last_used) {
+                        (Some(a), Some(b)) => a.cmp(&b),
+                        (Some(_), None) => Ordering::Less,
+                        (None, Some(_)) => Ordering::Greater,
+                        (None, None) => Ordering::Equal,
+                    }
+                }
+            };
+            let ordering = match (self.key, self.direction) {
+                // Unused connections stay last either way
+                (ConnectionSortKey::LastUsed, _)
+                    if a.last_used.is_none() || b.last_used.is_none() =>
+                {
+                    ordering
+                }
+                (_, SortDirection::Ascending) => ordering,
+                (_, SortDirection::Descending) => ordering.reverse(),
+            };
+            ordering.then_with(|| {
+                let by_name = compare_text(&a.name, &b.name);
+                if self.key == ConnectionSortKey::Name
+                    && self.direction == SortDirection::Descending
+                {
+                    by_name.reverse()
+                } else {
+                    by_name
+                }
+            })
+        });
+    }
+}
+
+fn compare_text(a: &str, b: &str) -> Ordering {
+    a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection(name: &str, url: &str, last_used: Option<i64>) -> Connection {
+        Connection {
+            name: name.to_string(),
+            url: url.to_string(),
+            last_used,
+            ..Connection::default()
+        }
+    }
+
+    fn names(sort: ConnectionSort, connections: &mut [Connection]) -> String {
+        sort.apply(connections);
+        connections
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    #[test]
+    fn cycles_keys_and_directions() {
+        let mut sort = ConnectionSort::default();
+        let mut seen = Vec::new();
+        for _ in 0..8 {
+            sort = sort.cycled();
+            seen.push(sort.as_saved());
+        }
+        assert_eq!(
+            seen,
+            [
+                "name:desc",
+                "host:asc",
+                "host:desc",
+                "database:asc",
+                "database:desc",
+                "last_used:desc",
+                "last_used:asc",
+                "name:asc",
+            ]
+        );
+        let host = ConnectionSort::from_saved(Some("host:desc"));
+        assert_eq!(host.key, ConnectionSortKey::Host);
+        assert_eq!(
+            ConnectionSort::from_saved(Some("bogus")),
+            ConnectionSort::default()
+        );
+    }
+
+    #[test]
+    fn sorts_connections_by_key() {
+        let mut connections = [
+            connection("b", "postgres://u@alpha:5432/zoo", None),
+            connection("a", "postgres://u@beta:5432/app", Some(2)),
+            connection("c", "postgres://u@Alpha:5432/mid", Some(5)),
+        ];
+        let by = |key, direction| ConnectionSort { key, direction };
+        let host = by(ConnectionSortKey::Host, SortDirection::Ascending);
+        assert_eq!(names(host, &mut connections), "b,c,a");
+        let database =
+            by(ConnectionSortKey::Database, SortDirection::Descending);
+        assert_eq!(names(database, &mut connections), "b,c,a");
+        let recent = by(ConnectionSortKey::LastUsed, SortDirection::Descending);
+        assert_eq!(names(recent, &mut connections), "c,a,b");
+        let oldest = by(ConnectionSortKey::LastUsed, SortDirection::Ascending);
+        assert_eq!(names(oldest, &mut connections), "a,c,b");
+    }
+}
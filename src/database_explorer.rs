@@ -1,14 +1,44 @@
+use std::{io::Write, sync::Arc, time::Instant};
+
 use color_eyre::Result;
 use crossterm::event::{KeyCode, KeyEvent};
 
 use crate::{
     app::App,
     app_state::DatabaseExplorerState,
-    db::{Database, DbRowId, TableDataPage, connection::ConnectionType},
+    db::{
+        Database, DbRowId, TableDataPage, TransactionStatus,
+        connection::ConnectionType,
+        postgres::error_position,
+        recent_table::RecentTable,
+        sort_tables_by_kind,
+        sqlite::SQLITE_MAIN_SCHEMA,
+        time_box::{TimeBox, TimeBoxedRows},
+    },
+    export::{ExportFormat, ExportSettings, create_export_file},
     filtered_data::FilteredData,
+    metrics,
+    result_history::ResultSnapshot,
+    row_colors::parse_rules,
+    services::{
+        ColumnWidthService, RecentTableService, RowColorService, RowNoteService,
+    },
+    sort::ColumnSort,
+    sql::{
+        paging::{PAGED_QUERY_PREFIX, paged_query},
+        position::ErrorMarker,
+        safety::{StatementSafety, classify_statement},
+    },
     ui::{
         handlers::TableNavigationHandler,
-        widgets::{modal::CellValueApply, table::TableDataState},
+        widgets::{
+            modal::{
+                CellColumn, CellValueApply, ChoiceAction, ChoiceModal,
+                InputModal, InputPurpose,
+            },
+            sql_executor::SQL_RESULT_ROW_LIMIT,
+            table::TableDataState,
+        },
     },
     virtual_table::{VIRTUAL_TABLE_PAGE_SIZE, VirtualTableMeta},
 };
@@ -18,7 +48,7 @@ impl App<'_> {
     pub async fn load_databases(&mut self) -> Result<()> {
         let explorer = &mut self.database_explorer;
 
-        let Some(database) = explorer.database.as_mut() else {
+        let Some(database) = explorer.database.as_ref() else {
             self.set_status("Not connected to database.");
             return Ok(());
         };
@@ -41,8 +71,10 @@ impl App<'_> {
         let explorer = &mut self.database_explorer;
         if explorer.database.is_some() {
             // Update connection with selected database
-            explorer.connection.selected_database =
-                Some(database_name.to_string());
+            let previous = explorer
+                .connection
+                .selected_database
+                .replace(database_name.to_string());
 
             let db: Box<dyn Database> = match explorer.connection.r#type {
                 ConnectionType::Postgres => explorer.connection.to_postgres(),
@@ -50,9 +82,13 @@ impl App<'_> {
             };
 
             if db.test().await {
-                explorer.database = Some(db);
+                explorer.database = Some(Arc::from(db));
+                self.refresh_search_path().await;
+                self.refresh_transaction_status().await;
+                self.prefetch_catalog();
                 self.load_schemas().await?;
             } else {
+                explorer.connection.selected_database = previous;
                 // TODO probably dont need database name here or at all
                 self.set_status(format!(
                     "Failed to connect to database: {database_name}",
@@ -63,43 +99,156 @@ impl App<'_> {
         Ok(())
     }
 
+    /// Read the effective search path for the top bar
+    pub(crate) async fn refresh_search_path(&mut self) {
+        let explorer = &mut self.database_explorer;
+        let Some(database) = explorer.database.as_ref() else {
+            return;
+        };
+        match database.get_search_path().await {
+            Ok(search_path) => explorer.connection.search_path = search_path,
+            Err(e) => {
+                explorer.connection.search_path = None;
+                self.set_status(format!("Failed to read search_path: {e}"));
+            }
+        }
+    }
+
+    /// Read the transaction state for the top bar
+    pub(crate) async fn refresh_transaction_status(&mut self) {
+        let explorer = &mut self.database_explorer;
+        let Some(database) = explorer.database.as_ref() else {
+            return;
+        };
+        match database.transaction_status().await {
+            Ok(status) => {
+                // Committing or rolling back ends the savepoints too
+                if status == Some(TransactionStatus::Idle) {
+                    explorer.savepoints.clear();
+                }
+                explorer.transaction_status = status;
+            }
+            Err(e) => {
+                explorer.transaction_status = None;
+                self.set_status(format!(
+                    "Failed to read the transaction state: {e}"
+                ));
+            }
+        }
+    }
+
+    /// Ask for a `search_path` to use for the rest of the session
+    pub(crate) fn open_search_path_prompt(&mut self) {
+        let connection = &self.database_explorer.connection;
+        if connection.r#type != ConnectionType::Postgres {
+            self.set_status("search_path only applies to PostgreSQL.");
+            return;
+        }
+        let current = connection.search_path.clone().unwrap_or_default();
+        self.modal_manager.open_input_modal(
+            InputModal::new(
+                " search_path ",
+                "Schemas for unqualified names, comma-separated (default resets):",
+                InputPurpose::SearchPath,
+            )
+            .with_value(&current),
+        );
+    }
+
+    /// Use `search_path` on every connection, keeping the current one if the
+    /// server rejects it. `default` goes back to the server's setting. The
+    /// SQL executor's session is changed in place, keeping its transaction
+    /// and savepoints.
+    pub(crate) async fn set_session_search_path(&mut self, search_path: &str) {
+        let explorer = &mut self.database_explorer;
+        let Some(database) = explorer.database.as_ref() else {
+            return;
+        };
+        let search_path = (!search_path.eq_ignore_ascii_case("default"))
+            .then(|| search_path.to_string());
+
+        let database = match database
+            .with_search_path(search_path.as_deref())
+            .await
+        {
+            Ok(Some(database)) => database,
+            Ok(None) => {
+                self.set_status("search_path only applies to PostgreSQL.");
+                return;
+            }
+            Err(e) => {
+                self.set_status(format!("Invalid search_path: {e}"));
+                return;
+            }
+        };
+        explorer.connection.session_search_path = search_path;
+        explorer.database = Some(Arc::from(database));
+        self.refresh_search_path().await;
+        self.refresh_transaction_status().await;
+        let shown = self
+            .database_explorer
+            .connection
+            .search_path
+            .clone()
+            .unwrap_or_default();
+        self.set_status(format!("search_path for this session: {shown}"));
+    }
+
     /// Load schemas from the database
     pub async fn load_schemas(&mut self) -> Result<()> {
         let explorer = &mut self.database_explorer;
-        let Some(database) = explorer.database.as_mut() else {
+        let Some(database) = explorer.database.as_ref() else {
             self.set_status("Not connected to database");
             return Ok(());
         };
 
-        // SQLite doesn't need the Schemas navigation step
-        // Skip directly to loading tables from the default sqlite_schema
-        if explorer.connection.r#type == ConnectionType::Sqlite {
-            return self.load_tables("sqlite_schema").await;
+        let schemas = match database.get_schemas().await {
+            Ok(schemas) => schemas,
+            Err(e) => {
+                self.set_status(format!("Failed to load schemas: {e}"));
+                return Ok(());
+            }
+        };
+
+        explorer.schemas = Some(FilteredData::new(schemas));
+        // SQLite without attached databases skips the Schemas step
+        if !explorer.has_schema_step() {
+            explorer.connection.schema = Some(SQLITE_MAIN_SCHEMA.to_string());
+            return self.load_tables(SQLITE_MAIN_SCHEMA).await;
         }
+        explorer.state = DatabaseExplorerState::Schemas;
+
+        Ok(())
+    }
+
+    /// Reload the schema list without changing the current view.
+    async fn refresh_schemas(&mut self) {
+        let explorer = &mut self.database_explorer;
+        let Some(database) = explorer.database.as_ref() else {
+            return;
+        };
 
         match database.get_schemas().await {
             Ok(schemas) => {
                 explorer.schemas = Some(FilteredData::new(schemas));
-                explorer.state = DatabaseExplorerState::Schemas;
             }
             Err(e) => {
                 self.set_status(format!("Failed to load schemas: {e}"));
             }
         }
-
-        Ok(())
     }
 
     /// Load tables for a schema
     pub async fn load_tables(&mut self, schema_name: &str) -> Result<()> {
         let explorer = &mut self.database_explorer;
-        let Some(database) = explorer.database.as_mut() else {
+        let Some(database) = explorer.database.as_ref() else {
             self.set_status("Not connected to database");
             return Ok(());
         };
 
         match database.get_tables(schema_name).await {
-            Ok(tables) => {
+            Ok(mut tables) => {
+                sort_tables_by_kind(&mut tables);
                 explorer.tables = Some(FilteredData::new(tables));
                 explorer.state =
                     DatabaseExplorerState::Tables(schema_name.to_string());
@@ -112,6 +261,60 @@ impl App<'_> {
         Ok(())
     }
 
+    /// Open a table's data, loading its schema's tables first so Esc
+    /// returns to them.
+    pub async fn open_table(
+        &mut self,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<()> {
+        self.load_tables(schema_name).await?;
+        self.load_table_data(schema_name, table_name).await?;
+        let explorer = &mut self.database_explorer;
+        if explorer.state
+            == DatabaseExplorerState::TableData(
+                schema_name.to_string(),
+                table_name.to_string(),
+            )
+        {
+            explorer.connection.schema = Some(schema_name.to_string());
+            explorer.connection.table = Some(table_name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Open a recently viewed table, switching database first when it was
+    /// opened in another one.
+    pub async fn open_recent_table(
+        &mut self,
+        recent: &RecentTable,
+    ) -> Result<()> {
+        if let Some(database_name) = &recent.database
+            && self.database_explorer.connection.selected_database.as_ref()
+                != Some(database_name)
+        {
+            self.select_database(database_name).await?;
+            if self.database_explorer.connection.selected_database.as_ref()
+                != Some(database_name)
+            {
+                return Ok(());
+            }
+        }
+        self.open_table(&recent.schema_name, &recent.table_name)
+            .await
+    }
+
+    /// Show every recently viewed table of the current connection
+    pub fn open_recent_tables_list(&mut self) {
+        let tables = self.database_explorer.recent_tables.clone();
+        if tables.is_empty() {
+            self.set_status("No recent tables yet.");
+            return;
+        }
+        self.modal_manager
+            .open_choice_modal(ChoiceModal::recent_tables(tables));
+    }
+
     /// Load columns for a table
     pub async fn load_columns(
         &mut self,
@@ -119,7 +322,7 @@ impl App<'_> {
         table_name: &str,
     ) -> Result<()> {
         let explorer = &mut self.database_explorer;
-        let Some(database) = explorer.database.as_mut() else {
+        let Some(database) = explorer.database.as_ref() else {
             self.set_status("Not connected to database");
             return Ok(());
         };
@@ -151,6 +354,31 @@ impl App<'_> {
             self.set_status("Not connected to database");
             return Ok(());
         };
+        // Keep the order when coming back to the same table
+        let same_table = match &explorer.state {
+            DatabaseExplorerState::TableData(schema, table)
+            | DatabaseExplorerState::Columns(schema, table) => {
+                schema == schema_name && table == table_name
+            }
+            DatabaseExplorerState::Connections
+            | DatabaseExplorerState::Databases
+            | DatabaseExplorerState::Schemas
+            | DatabaseExplorerState::Tables(_)
+            | DatabaseExplorerState::SqlResults(_) => false,
+        };
+        if !same_table {
+            explorer.table_order = None;
+            explorer.table_keyset = None;
+        }
+        let filter = explorer
+            .table_data
+            .as_ref()
+            .filter(|_| same_table)
+            .and_then(|fd| fd.query.clone());
+        let keyset = explorer
+            .table_keyset
+            .as_ref()
+            .map(|cursor| cursor.page(cursor.seek.clone()));
 
         let total_rows = database
             .get_table_row_count(schema_name, table_name)
@@ -158,33 +386,86 @@ impl App<'_> {
             .ok();
         let page_size = VIRTUAL_TABLE_PAGE_SIZE;
 
-        if let Ok(page) = database
-            .get_table_data_page(schema_name, table_name, 0, page_size)
-            .await
-        {
+        let started = Instant::now();
+        let page = database
+            .get_table_data_page(
+                schema_name,
+                table_name,
+                explorer.table_order.as_ref(),
+                keyset.as_ref(),
+                0,
+                page_size,
+            )
+            .await;
+        metrics::record_page(&explorer.connection.name, started, &page);
+        if let Ok(page) = page {
             let TableDataPage {
                 rows: data,
                 column_names,
                 row_ids,
+                sql,
             } = page;
             let loaded = data.len();
-            let meta =
-                VirtualTableMeta::from_fetch(0, page_size, loaded, total_rows);
+            let meta = match keyset {
+                Some(keyset) => {
+                    let meta = VirtualTableMeta::from_keyset_fetch(
+                        &keyset.seek,
+                        page_size,
+                        loaded,
+                        total_rows,
+                    );
+                    if let Some(cursor) = explorer.table_keyset.as_mut() {
+                        cursor.shown(keyset.seek, &column_names, &data);
+                    }
+                    meta
+                }
+                None => VirtualTableMeta::from_fetch(
+                    0, page_size, loaded, total_rows,
+                ),
+            };
             let mut table = TableDataState::default();
             table.reset(data, &column_names, Some(row_ids));
-            let filtered = FilteredData {
-                original: table.model.items.clone(),
-                table,
-            };
-            explorer.table_data = Some(filtered);
+            if let Ok(widths) = ColumnWidthService::get_all(
+                &explorer.connection.name,
+                schema_name,
+                table_name,
+            ) {
+                table.set_column_widths_by_name(&widths);
+            }
+            if let Ok(Some(rules)) = RowColorService::get(
+                &explorer.connection.name,
+                schema_name,
+                table_name,
+            ) {
+                table.view.row_colors = parse_rules(&rules).unwrap_or_default();
+            }
+            if let Ok(notes) = RowNoteService::get_all(
+                &explorer.connection.name,
+                schema_name,
+                table_name,
+            ) {
+                table.view.row_notes = notes;
+            }
+            table.show_order(explorer.table_order.as_ref());
+            let mut table_data = FilteredData::from_table(table);
+            if let Some(filter) = filter {
+                table_data.apply_filter(&filter);
+            }
+            explorer.table_data = Some(table_data);
             explorer.table_data_virtual = Some(meta);
+            explorer.table_data_sql = Some(sql);
             explorer.state = DatabaseExplorerState::TableData(
                 schema_name.to_string(),
                 table_name.to_string(),
             );
-            explorer.record_recent_table_open(schema_name, table_name);
+            let recent =
+                explorer.record_recent_table_open(schema_name, table_name);
+            // Best effort: the history is a convenience, not worth an error
+            let _ =
+                RecentTableService::record(&explorer.connection.name, &recent);
         } else {
             explorer.table_data_virtual = None;
+            explorer.table_data_sql = None;
             self.set_status("Failed to load table data");
         }
 
@@ -204,6 +485,10 @@ impl App<'_> {
             self.set_status("Already at last page.");
             return Ok(());
         }
+        if explorer.table_keyset.is_some() {
+            self.step_keyset_page(true).await;
+            return Ok(());
+        }
         let DatabaseExplorerState::TableData(schema, table) = &explorer.state
         else {
             return Ok(());
@@ -215,15 +500,25 @@ impl App<'_> {
             return Ok(());
         };
 
-        match database
-            .get_table_data_page(schema, table, new_start, page_size)
-            .await
-        {
+        let started = Instant::now();
+        let page = database
+            .get_table_data_page(
+                schema,
+                table,
+                explorer.table_order.as_ref(),
+                None,
+                new_start,
+                page_size,
+            )
+            .await;
+        metrics::record_page(&explorer.connection.name, started, &page);
+        match page {
             Ok(page) => {
                 let TableDataPage {
                     rows: data,
                     column_names,
                     row_ids,
+                    sql,
                 } = page;
                 let loaded = data.len();
                 let meta = VirtualTableMeta::from_fetch(
@@ -231,11 +526,9 @@ impl App<'_> {
                 );
                 let mut table_state = TableDataState::default();
                 table_state.reset(data, &column_names, Some(row_ids));
-                explorer.table_data = Some(FilteredData {
-                    original: table_state.model.items.clone(),
-                    table: table_state,
-                });
+                explorer.replace_table_data_page(table_state);
                 explorer.table_data_virtual = Some(meta);
+                explorer.table_data_sql = Some(sql);
             }
             Err(e) => {
                 self.set_status(format!("Failed to load page: {e}"));
@@ -258,6 +551,10 @@ impl App<'_> {
             self.set_status("Already at first page.");
             return Ok(());
         }
+        if explorer.table_keyset.is_some() {
+            self.step_keyset_page(false).await;
+            return Ok(());
+        }
         let page_size = meta.page_size;
         let new_start = meta.window_start.saturating_sub(u64::from(page_size));
         let total_rows = meta.total_rows;
@@ -270,15 +567,25 @@ impl App<'_> {
             return Ok(());
         };
 
-        match database
-            .get_table_data_page(schema, table, new_start, page_size)
-            .await
-        {
+        let started = Instant::now();
+        let page = database
+            .get_table_data_page(
+                schema,
+                table,
+                explorer.table_order.as_ref(),
+                None,
+                new_start,
+                page_size,
+            )
+            .await;
+        metrics::record_page(&explorer.connection.name, started, &page);
+        match page {
             Ok(page) => {
                 let TableDataPage {
                     rows: data,
                     column_names,
                     row_ids,
+                    sql,
                 } = page;
                 let loaded = data.len();
                 let meta = VirtualTableMeta::from_fetch(
@@ -286,11 +593,9 @@ impl App<'_> {
                 );
                 let mut table_state = TableDataState::default();
                 table_state.reset(data, &column_names, Some(row_ids));
-                explorer.table_data = Some(FilteredData {
-                    original: table_state.model.items.clone(),
-                    table: table_state,
-                });
+                explorer.replace_table_data_page(table_state);
                 explorer.table_data_virtual = Some(meta);
+                explorer.table_data_sql = Some(sql);
             }
             Err(e) => {
                 self.set_status(format!("Failed to load page: {e}"));
@@ -327,24 +632,23 @@ impl App<'_> {
             let Some(ref table_fd) = explorer.table_data else {
                 return Ok(false);
             };
-            if table_fd.is_filtered() {
-                return Ok(false);
-            }
-            let Some(selected) = table_fd.table.view.state.selected() else {
-                return Ok(false);
-            };
+            // A filter stays on across pages, so a page with no matches
+            // is passed through in both directions
             let len = table_fd.table.model.items.len();
-            if len == 0 {
-                return Ok(false);
-            }
+            let selected = table_fd.table.view.state.selected();
+            let (at_first, at_last) = match (len, selected) {
+                (0, _) if table_fd.query.is_some() => (true, true),
+                (0, _) | (_, None) => return Ok(false),
+                (len, Some(selected)) => (selected == 0, selected == len - 1),
+            };
 
             if matches!(code, KeyCode::Char('j') | KeyCode::Down)
-                && selected == len - 1
+                && at_last
                 && meta.has_more_after
             {
                 Some(true)
             } else if matches!(code, KeyCode::Char('k') | KeyCode::Up)
-                && selected == 0
+                && at_first
                 && meta.has_more_before
             {
                 Some(false)
@@ -404,7 +708,7 @@ impl App<'_> {
                 self.load_table_data(&schema_name, &table_name).await?;
             }
             DatabaseExplorerState::TableData(schema_name, table_name) => {
-                if let Some((column_name, cell_value, row_idx, col_idx, snap)) =
+                if let Some((cell_value, row_idx, col_idx, snap)) =
                     self.get_selected_cell_for_modal()
                 {
                     let schema = schema_name.clone();
@@ -424,6 +728,10 @@ impl App<'_> {
                             .await
                             .unwrap_or_default()
                     };
+                    let declared = database
+                        .get_columns(&schema, &table)
+                        .await
+                        .unwrap_or_default();
                     let col_names: &[String] = self
                         .database_explorer
                         .table_data
@@ -447,8 +755,19 @@ impl App<'_> {
                             Some((pk, val))
                         })
                         .collect();
+                    let columns = col_names
+                        .iter()
+                        .map(|name| CellColumn {
+                            label: name.clone(),
+                            column: Some(name.clone()),
+                            data_type: declared
+                                .iter()
+                                .find(|c| c.name.eq_ignore_ascii_case(name))
+                                .map(|c| c.data_type.clone()),
+                        })
+                        .collect();
                     self.modal_manager.open_cell_value_modal(
-                        column_name,
+                        columns,
                         &cell_value,
                         row_idx,
                         col_idx,
@@ -461,12 +780,97 @@ impl App<'_> {
                 }
             }
             DatabaseExplorerState::SqlResults(_) => {
-                // Enter should not re-run SQL in results mode.
+                // Enter should not re-run SQL in results mode; it edits
+                // the cell when the results come from a single table.
+                self.edit_sql_result_cell().await;
             }
         }
         Ok(())
     }
 
+    /// Open the selected cell of SQL results read from a single table for
+    /// editing, finding its row by the table's primary key.
+    async fn edit_sql_result_cell(&mut self) {
+        let executor = &self.database_explorer.sql_executor;
+        let Some(origin) = executor.origin.clone() else {
+            self.set_status(
+                "Only results read from a single table can be edited.",
+            );
+            return;
+        };
+        let table = &executor.table_state;
+        let Some(row_idx) = table.view.state.selected() else {
+            return;
+        };
+        let col_idx = table.view.state.selected_column().unwrap_or(0);
+        let Some(snap) =
+            table.model.items.get(row_idx).map(|r| r.values.clone())
+        else {
+            return;
+        };
+        let Some(cell_value) = snap.get(col_idx).cloned() else {
+            return;
+        };
+        if origin
+            .columns
+            .get(col_idx)
+            .and_then(Option::as_ref)
+            .is_none()
+        {
+            self.set_status(format!(
+                "This column is computed, not read from {}.",
+                origin.table_name
+            ));
+            return;
+        }
+        let table = &self.database_explorer.sql_executor.table_state;
+        let names = table.model.dynamic_column_names.as_deref();
+        let types = table.model.dynamic_column_types.as_deref();
+        let columns = (0..snap.len())
+            .map(|idx| CellColumn {
+                label: names
+                    .and_then(|names| names.get(idx))
+                    .cloned()
+                    .unwrap_or_default(),
+                column: origin.columns.get(idx).cloned().flatten(),
+                data_type: types.and_then(|types| types.get(idx)).cloned(),
+            })
+            .collect();
+        let Some(database) = self.database_explorer.database.as_ref() else {
+            return;
+        };
+        let pk_names = database
+            .get_primary_key_columns(&origin.schema_name, &origin.table_name)
+            .await
+            .unwrap_or_default();
+        let primary_key = pk_names
+            .into_iter()
+            .map(|pk| {
+                let val = snap.get(origin.result_column(&pk)?)?.clone();
+                Some((pk, val))
+            })
+            .collect::<Option<Vec<_>>>()
+            .filter(|key| !key.is_empty());
+        let Some(primary_key) = primary_key else {
+            self.set_status(format!(
+                "Select the primary key of {} to edit its rows.",
+                origin.table_name
+            ));
+            return;
+        };
+        self.modal_manager.open_cell_value_modal(
+            columns,
+            &cell_value,
+            row_idx,
+            col_idx,
+            snap,
+            origin.schema_name,
+            origin.table_name,
+            primary_key,
+            None,
+        );
+    }
+
     /// Get the name of the currently selected database
     fn get_selected_database_name(&self) -> Option<String> {
         let explorer = &self.database_explorer;
@@ -485,6 +889,49 @@ impl App<'_> {
         Some(schema.name.clone())
     }
 
+    /// Starter query for the table being browsed, in the backend's dialect.
+    pub(crate) fn current_table_select_template(&self) -> Option<String> {
+        let database = self.database_explorer.database.as_ref()?;
+        let (schema_name, table_name) = self.current_table()?;
+        let dialect = database.dialect();
+        Some(format!(
+            "SELECT *\nFROM {}\n{};",
+            dialect.qualified_table(&schema_name, &table_name),
+            dialect.paging_clause("100", None)
+        ))
+    }
+
+    /// The current table as a quoted `schema.table`
+    pub(crate) fn current_table_qualified_name(&self) -> Option<String> {
+        let database = self.database_explorer.database.as_ref()?;
+        let (schema_name, table_name) = self.current_table()?;
+        Some(
+            database
+                .dialect()
+                .qualified_table(&schema_name, &table_name),
+        )
+    }
+
+    /// Schema and name of the table being browsed, or selected in the
+    /// tables list
+    pub(crate) fn current_table(&self) -> Option<(String, String)> {
+        let explorer = &self.database_explorer;
+        let (schema_name, table_name) = match &explorer.state {
+            DatabaseExplorerState::Tables(schema_name) => {
+                (schema_name.clone(), self.get_selected_table_name()?)
+            }
+            DatabaseExplorerState::Columns(schema_name, table_name)
+            | DatabaseExplorerState::TableData(schema_name, table_name) => {
+                (schema_name.clone(), table_name.clone())
+            }
+            DatabaseExplorerState::Connections
+            | DatabaseExplorerState::Databases
+            | DatabaseExplorerState::Schemas
+            | DatabaseExplorerState::SqlResults(_) => return None,
+        };
+        Some((schema_name, table_name))
+    }
+
     /// Get the name of the currently selected table
     fn get_selected_table_name(&self) -> Option<String> {
         let explorer = &self.database_explorer;
@@ -497,7 +944,7 @@ impl App<'_> {
     /// Selected cell plus row index, column index, and full row snapshot (for syncing filtered data).
     fn get_selected_cell_for_modal(
         &self,
-    ) -> Option<(String, String, usize, usize, Vec<String>)> {
+    ) -> Option<(String, usize, usize, Vec<String>)> {
         let explorer = &self.database_explorer;
         let table_data_filtered = explorer.table_data.as_ref()?;
         let table_data = &table_data_filtered.table;
@@ -513,10 +960,9 @@ impl App<'_> {
             return None;
         }
 
-        let column_name = column_names.get(selected_col)?.clone();
         let cell_value = row.values.get(selected_col)?.clone();
         let snap = row.values.clone();
-        Some((column_name, cell_value, selected_row, selected_col, snap))
+        Some((cell_value, selected_row, selected_col, snap))
     }
 
     fn get_selected_row_db_id(&self) -> Option<DbRowId> {
@@ -532,12 +978,17 @@ impl App<'_> {
         &mut self,
         apply: CellValueApply,
     ) -> Result<()> {
-        let is_draft = self
-            .database_explorer
-            .table_data
-            .as_ref()
-            .and_then(|fd| fd.table.model.items.get(apply.row_index))
-            .is_some_and(|r| r.is_draft);
+        let in_sql_results = matches!(
+            self.database_explorer.state,
+            DatabaseExplorerState::SqlResults(_)
+        );
+        let is_draft = !in_sql_results
+            && self
+                .database_explorer
+                .table_data
+                .as_ref()
+                .and_then(|fd| fd.table.model.items.get(apply.row_index))
+                .is_some_and(|r| r.is_draft);
         if is_draft {
             self.apply_cell_value_edit_in_memory(&apply);
             self.set_status("Draft cell updated — commit with s when ready.");
@@ -563,6 +1014,14 @@ impl App<'_> {
                     "No row was updated (it may have changed in the database).",
                 );
             }
+            Ok(_) if in_sql_results => {
+                self.database_explorer.sql_executor.update_result_cell(
+                    &apply.row_snapshot,
+                    apply.col_index,
+                    &apply.new_value,
+                );
+                self.set_status("Cell updated.");
+            }
             Ok(_) => {
                 self.apply_cell_value_edit_in_memory(&apply);
                 self.set_status("Cell updated.");
@@ -611,41 +1070,433 @@ impl App<'_> {
             return;
         }
 
+        // Queries that only read can stop early with the rows read so far
+        let time_box = if classify_statement(&sql) == StatementSafety::ReadOnly
+        {
+            self.time_box
+        } else {
+            TimeBox::default()
+        };
         let Some(database) = self.database_explorer.database.as_ref() else {
             return;
         };
 
         // Clear any previous results/errors before executing
         self.database_explorer.sql_executor.clear_results();
+        self.database_explorer.result_history.clear_current();
 
-        match database.execute_sql(&sql).await {
-            Ok(results) => {
-                let data: Vec<Vec<String>> =
+        // Plain queries are fetched a chunk at a time; one extra row tells
+        // whether there's more to fetch
+        let paged = paged_query(&sql, 0, SQL_RESULT_ROW_LIMIT + 1);
+        let started = Instant::now();
+        let result = database
+            .execute_sql_within(paged.as_deref().unwrap_or(&sql), time_box)
+            .await;
+        metrics::record_query(
+            &self.database_explorer.connection.name,
+            started.elapsed(),
+            result.as_ref().ok().map(|read| read.rows.len()),
+        );
+        match result {
+            Ok(TimeBoxedRows {
+                rows: results,
+                cutoff,
+            }) => {
+                // Results of a single table can be edited in place
+                let origin = match results.first() {
+                    Some(first) => database
+                        .resolve_result_origin(&first.column_sources)
+                        .await
+                        .ok()
+                        .flatten(),
+                    None => None,
+                };
+                let mut data: Vec<Vec<String>> =
                     results.iter().map(|row| row.values.clone()).collect();
-                if data.is_empty() {
+                // A paged query cut short carries on from where it stopped
+                let has_more = data.len() > SQL_RESULT_ROW_LIMIT
+                    || (cutoff.is_some() && paged.is_some());
+                data.truncate(SQL_RESULT_ROW_LIMIT);
+                if let Some(cutoff) = cutoff.filter(|_| data.is_empty()) {
+                    self.set_status(format!(
+                        "Partial result: {cutoff} before any rows arrived."
+                    ));
+                } else if data.is_empty() {
                     // No data returned - show message in status bar
                     self.set_status(
                         "Query executed successfully but returned no data",
                     );
                 } else if let Some(first_result) = results.first() {
                     // Has data - show results in SQL executor
+                    let logged_sql = self.secret_masking.apply(&sql);
+                    let explorer = &mut self.database_explorer;
+                    let mut snapshot = ResultSnapshot::new(
+                        &logged_sql,
+                        explorer.connection.selected_database.clone(),
+                        started.elapsed(),
+                        first_result.column_names.clone(),
+                        data.clone(),
+                    );
+                    snapshot.has_more_rows = has_more;
+                    snapshot.masked = logged_sql != sql;
+                    snapshot
+                        .column_types
+                        .clone_from(&first_result.column_types);
+                    explorer.result_history.push(snapshot);
+                    let executor = &mut explorer.sql_executor;
+                    executor.set_results(data, &first_result.column_names);
+                    executor
+                        .set_column_types(first_result.column_types.clone());
+                    executor.has_more_rows = has_more;
+                    executor.cutoff = cutoff;
+                    executor.origin = origin;
+                }
+            }
+            Err(e) => {
+                // Error occurred - show in status bar, and in the SQL
+                // executor when it points at a character of the statement
+                let error = format!("SQL Error: {e}");
+                let prefix = paged
+                    .as_ref()
+                    .map_or(0, |_| PAGED_QUERY_PREFIX.chars().count());
+                if let Some(marker) = error_position(e.as_ref())
+                    .and_then(|idx| idx.checked_sub(prefix))
+                    .and_then(|idx| ErrorMarker::new(&sql, idx))
+                {
                     self.database_explorer
                         .sql_executor
-                        .set_results(data, &first_result.column_names);
+                        .set_error_at(error.clone(), marker);
                 }
+                self.set_status(error);
             }
-            Err(e) => {
-                // Error occurred - show in status bar instead of SQL executor widget
-                self.set_status(format!("SQL Error: {e}"));
+        }
+
+        self.refresh_transaction_status().await;
+
+        // Pick up `ATTACH DATABASE` / `DETACH DATABASE`
+        if self.database_explorer.connection.r#type == ConnectionType::Sqlite {
+            self.refresh_schemas().await;
+        }
+    }
+
+    /// Sort the SQL results by the selected column, cycling ascending,
+    /// descending and the original order
+    pub(crate) fn sort_sql_results(&mut self) {
+        let executor = &mut self.database_explorer.sql_executor;
+        let Some(column) = executor.table_state.view.state.selected_column()
+        else {
+            self.set_status("Select a column (h/l) to sort by it.");
+            return;
+        };
+        let sort = ColumnSort::cycle(executor.table_state.view.sort, column);
+        let kind = executor.sort_results(sort);
+        let column_name = executor
+            .column_names
+            .get(column)
+            .cloned()
+            .unwrap_or_default();
+        match sort {
+            Some(sort) => self.set_status(format!(
+                "Sorted by {column_name} {} ({}).",
+                sort.direction.arrow(),
+                kind.label()
+            )),
+            None => self.set_status("Original order."),
+        }
+    }
+
+    /// Hide the SQL result rows that repeat a row shown above them, or
+    /// show them again
+    pub(crate) fn toggle_distinct_sql_results(&mut self) {
+        let executor = &mut self.database_explorer.sql_executor;
+        if executor.results.is_none() {
+            return;
+        }
+        let status = if executor.toggle_distinct() {
+            match executor.collapsed_rows() {
+                0 => "Distinct rows: no duplicates among the rows loaded."
+                    .to_string(),
+                1 => "Distinct rows: 1 duplicate hidden.".to_string(),
+                n => format!("Distinct rows: {n} duplicates hidden."),
+            }
+        } else {
+            "Showing every row, duplicates included.".to_string()
+        };
+        self.set_status(status);
+    }
+
+    /// List the result sets run earlier on this connection
+    pub(crate) fn open_result_history(&mut self) {
+        let history = &self.database_explorer.result_history;
+        if history.is_empty() {
+            self.set_status("No results yet; run a query first.");
+            return;
+        }
+        self.modal_manager
+            .open_choice_modal(ChoiceModal::result_history(history));
+    }
+
+    pub(crate) fn handle_history_result_choice(
+        &mut self,
+        id: u64,
+        action: ChoiceAction,
+    ) {
+        match action {
+            ChoiceAction::Select => self.reopen_history_result(id),
+            ChoiceAction::Delete => {
+                self.database_explorer.result_history.remove(id);
+                self.set_status("Dropped result from history.");
             }
         }
     }
 
+    /// Show a result set from the history in the SQL executor as it was
+    /// loaded, without running its query again
+    fn reopen_history_result(&mut self, id: u64) {
+        let explorer = &mut self.database_explorer;
+        let Some(snapshot) = explorer.result_history.open(id).cloned() else {
+            return;
+        };
+        // More rows can only come from the database the query ran in, and
+        // by running the query as written rather than masked
+        let same_database =
+            snapshot.database == explorer.connection.selected_database;
+
+        self.enter_sql_results_state(snapshot.sql.clone());
+        let executor = &mut self.database_explorer.sql_executor;
+        if snapshot.masked {
+            executor.clear_selected_statement();
+        } else {
+            executor.set_selected_statement(snapshot.sql);
+        }
+        executor.set_results(snapshot.rows, &snapshot.column_names);
+        executor.set_column_types(snapshot.column_types);
+        executor.has_more_rows =
+            snapshot.has_more_rows && same_database && !snapshot.masked;
+        let masked = if snapshot.masked {
+            "; its query has masked secrets, so run it from the editor again"
+        } else {
+            ""
+        };
+        self.set_status(format!(
+            "Result from {} ({} ms){masked}.",
+            snapshot.executed_at,
+            snapshot.elapsed.as_millis()
+        ));
+    }
+
+    /// Fetch the next chunk of a query whose results were cut off
+    pub(crate) async fn fetch_more_sql_results(&mut self) {
+        let executor = &self.database_explorer.sql_executor;
+        if !executor.has_more_rows {
+            self.set_status("All rows are already loaded.");
+            return;
+        }
+        if self.connection_busy() {
+            self.set_status("Wait for the .sql file to finish.");
+            return;
+        }
+        let executor = &self.database_explorer.sql_executor;
+        let loaded = executor.results.as_ref().map_or(0, Vec::len);
+        let Some(sql) = executor
+            .selected_statement()
+            .and_then(|sql| paged_query(sql, loaded, SQL_RESULT_ROW_LIMIT + 1))
+        else {
+            return;
+        };
+        let Some(database) = self.database_explorer.database.as_ref() else {
+            return;
+        };
+
+        match database.execute_in_session(&sql).await {
+            Ok(results) => {
+                let mut rows: Vec<Vec<String>> =
+                    results.into_iter().map(|row| row.values).collect();
+                let has_more = rows.len() > SQL_RESULT_ROW_LIMIT;
+                rows.truncate(SQL_RESULT_ROW_LIMIT);
+                let fetched = rows.len();
+                let explorer = &mut self.database_explorer;
+                explorer.result_history.append_to_current(&rows, has_more);
+                let executor = &mut explorer.sql_executor;
+                executor.append_results(rows, has_more);
+                let total = executor.results.as_ref().map_or(0, Vec::len);
+                self.set_status(format!(
+                    "Fetched {fetched} more rows ({total} loaded)."
+                ));
+            }
+            Err(e) => self.set_status(format!("SQL Error: {e}")),
+        }
+    }
+
+    /// Run the current statement again without a row limit and write every
+    /// row to a file, in the background. The connection's export settings
+    /// pick the path and format; by default a CSV file in the working
+    /// directory. SQL scripts first ask for the table to insert into.
+    pub(crate) fn export_sql_results(&mut self) {
+        let executor = &self.database_explorer.sql_executor;
+        if executor.selected_statement().is_none() {
+            self.set_status("Run a query (e) before exporting its results.");
+            return;
+        }
+        let settings =
+            ExportSettings::for_connection(&self.database_explorer.connection);
+        if settings.format != ExportFormat::Sql {
+            self.write_sql_results_export(settings);
+            return;
+        }
+        let table = executor
+            .origin
+            .as_ref()
+            .map(|origin| origin.table_name.clone())
+            .unwrap_or_default();
+        let completions = self.table_completions();
+        self.modal_manager.open_input_modal(
+            InputModal::new(
+                " Export as INSERT statements ",
+                "Table to insert the rows into:",
+                InputPurpose::InsertExportTable,
+            )
+            .with_value(&table)
+            .with_completions(completions),
+        );
+    }
+
+    /// Export the results as `INSERT` statements into `table`.
+    pub(crate) fn export_sql_results_as_inserts(&mut self, table: String) {
+        let mut settings =
+            ExportSettings::for_connection(&self.database_explorer.connection);
+        settings.insert_table = Some(table);
+        self.write_sql_results_export(settings);
+    }
+
+    fn write_sql_results_export(&mut self, settings: ExportSettings) {
+        let Some(sql) = self
+            .database_explorer
+            .sql_executor
+            .selected_statement()
+            .map(str::to_string)
+        else {
+            return;
+        };
+        let Some(database) = self.database_explorer.database.clone() else {
+            return;
+        };
+        let fallback_columns =
+            self.database_explorer.sql_executor.column_names.clone();
+
+        self.start_background_task("Exporting query", |progress| async move {
+            let results = database
+                .execute_sql(&sql)
+                .await
+                .map_err(|e| format!("SQL Error: {e}"))?;
+            let column_names = results
+                .first()
+                .map_or(fallback_columns, |row| row.column_names.clone());
+            let column_types = results
+                .first()
+                .map(|row| row.column_types.clone())
+                .unwrap_or_default();
+            let rows: Vec<Vec<String>> =
+                results.into_iter().map(|row| row.values).collect();
+
+            let total = Some(rows.len() as u64);
+            progress.report(0, total);
+            let path = settings.path("query");
+            create_export_file(&path)
+                .and_then(|mut file| {
+                    settings.write(
+                        &mut file,
+                        database.dialect(),
+                        &column_names,
+                        &column_types,
+                        &rows,
+                        |done| progress.report(done as u64, total),
+                    )
+                })
+                .map_err(|e| {
+                    format!("Failed to write {}: {e}", path.display())
+                })?;
+            Ok(format!(
+                "Exported {} rows to {}.",
+                rows.len(),
+                path.display()
+            ))
+        });
+    }
+
+    /// Ask whether a schema's DDL export should include constraints and
+    /// indexes. Works on the selected schema, or the schema whose tables
+    /// are listed.
+    pub(crate) fn open_schema_ddl_export(&mut self) {
+        let explorer = &self.database_explorer;
+        let schema = match &explorer.state {
+            DatabaseExplorerState::Schemas => explorer
+                .schemas
+                .as_ref()
+                .and_then(|schemas| {
+                    schemas
+                        .table
+                        .view
+                        .state
+                        .selected()
+                        .and_then(|idx| schemas.table.model.items.get(idx))
+                })
+                .map(|schema| schema.name.clone()),
+            DatabaseExplorerState::Tables(schema) => Some(schema.clone()),
+            DatabaseExplorerState::Connections
+            | DatabaseExplorerState::Databases
+            | DatabaseExplorerState::Columns(..)
+            | DatabaseExplorerState::TableData(..)
+            | DatabaseExplorerState::SqlResults(_) => None,
+        };
+        if let Some(schema) = schema {
+            self.modal_manager
+                .open_choice_modal(ChoiceModal::export_schema_ddl(schema));
+        }
+    }
+
+    /// Write `CREATE` statements for every table in `schema` to one `.sql`
+    /// file, in the background. The path follows the connection's export
+    /// settings.
+    pub(crate) fn export_schema_ddl(
+        &mut self,
+        schema: String,
+        with_extras: bool,
+    ) {
+        let Some(database) = self.database_explorer.database.clone() else {
+            return;
+        };
+        let settings =
+            ExportSettings::for_connection(&self.database_explorer.connection);
+
+        self.start_background_task("Exporting DDL", |_| async move {
+            let ddl = database
+                .get_schema_ddl(&schema, with_extras)
+                .await
+                .map_err(|e| format!("Failed to read the schema: {e}"))?;
+            let path = settings.script_path(&schema);
+            create_export_file(&path)
+                .and_then(|mut file| {
+                    file.write_all(ddl.script(&schema).as_bytes())?;
+                    file.flush()
+                })
+                .map_err(|e| {
+                    format!("Failed to write {}: {e}", path.display())
+                })?;
+            Ok(format!(
+                "Exported the DDL of {} tables in {schema} to {}.",
+                ddl.tables.len(),
+                path.display()
+            ))
+        });
+    }
+
     /// Go back to previous level in database navigation
     pub fn go_back_in_database(&mut self) {
         let explorer_state = self.database_explorer.state.clone();
         let explorer = &mut self.database_explorer;
         let is_sqlite = explorer.connection.r#type == ConnectionType::Sqlite;
+        let has_schema_step = explorer.has_schema_step();
 
         match explorer_state {
             DatabaseExplorerState::Connections => {
@@ -660,38 +1511,43 @@ impl App<'_> {
                 }
             }
             DatabaseExplorerState::Tables(_) => {
-                // SQLite: Go back to connections (disconnect)
-                // Postgres: Go back to schemas
-                if is_sqlite {
-                    self.disconnect_from_database();
+                // SQLite without attachments: Go back to connections (disconnect)
+                // Otherwise: Go back to schemas
+                if !has_schema_step {
+                    self.request_disconnect();
                 } else if explorer.schemas.is_some() {
                     explorer.state = DatabaseExplorerState::Schemas;
                     explorer.connection.schema = None;
                 }
             }
             DatabaseExplorerState::Schemas => {
-                // Go back to databases
-                if explorer.databases.is_some() {
+                // SQLite: Go back to connections (disconnect)
+                // Postgres: Go back to databases
+                if is_sqlite {
+                    self.request_disconnect();
+                } else if explorer.databases.is_some() {
                     explorer.state = DatabaseExplorerState::Databases;
                 }
             }
             DatabaseExplorerState::SqlResults(_) => {
-                // SQLite: Go back to tables
-                // Postgres: Go back to schemas
-                if is_sqlite {
-                    if explorer.tables.is_some() {
-                        explorer.state = DatabaseExplorerState::Tables(
-                            "sqlite_schema".to_string(),
-                        );
+                // SQLite without attachments: Go back to tables
+                // Otherwise: Go back to schemas
+                if has_schema_step {
+                    if explorer.schemas.is_some() {
+                        explorer.state = DatabaseExplorerState::Schemas;
                     }
-                } else if explorer.schemas.is_some() {
-                    explorer.state = DatabaseExplorerState::Schemas;
+                } else if explorer.tables.is_some() {
+                    let schema = explorer
+                        .connection
+                        .schema
+                        .clone()
+                        .unwrap_or_else(|| SQLITE_MAIN_SCHEMA.to_string());
+                    explorer.state = DatabaseExplorerState::Tables(schema);
                 }
             }
             DatabaseExplorerState::Databases => {
                 // Go back to connection list (disconnect)
-                self.disconnect_from_database();
-                self.refresh_connections();
+                self.request_disconnect();
             }
         }
     }
@@ -1,20 +1,27 @@
-use crossterm::event::KeyCode;
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyModifiers};
 use ratatui::widgets::TableState;
 
 use crate::{
     app_state::DatabaseExplorerState,
     db::{
-        Column, Database, DatabaseInfo, Schema, Table, connection::Connection,
+        Column, Database, DatabaseInfo, Schema, Table, TransactionStatus,
+        connection::{Connection, ConnectionType},
+        recent_table::RecentTable,
     },
     filtered_data::FilteredData,
+    result_history::ResultHistory,
+    services::recent_table_service::RECENT_TABLES_LIMIT,
+    sort::TableOrder,
     ui::{
         sql_executor::SqlExecutorState,
         widgets::{
             hotkey::{Hotkey, HotkeyDescription},
-            table::RawTableRow,
+            table::{RawTableRow, TableDataState},
         },
     },
-    virtual_table::VirtualTableMeta,
+    virtual_table::{KeysetCursor, VirtualTableMeta},
 };
 
 /// Groups all database exploration state together
@@ -23,7 +30,7 @@ pub struct DatabaseExplorer {
     /// The active database connection
     pub connection: Connection,
     /// The active database client
-    pub database: Option<Box<dyn Database>>,
+    pub database: Option<Arc<dyn Database>>,
     /// Current navigation state in the database
     pub state: DatabaseExplorerState,
     /// Connection list with filtering
@@ -42,10 +49,24 @@ pub struct DatabaseExplorer {
     pub table_data: Option<FilteredData<RawTableRow>>,
     /// Paging metadata when browsing table rows (`None` when not viewing table data).
     pub table_data_virtual: Option<VirtualTableMeta>,
+    /// Server-side row order of the table being browsed; kept while paging
+    /// and refreshing.
+    pub table_order: Option<TableOrder>,
+    /// Keyset paging of the table being browsed, when it's on
+    pub table_keyset: Option<KeysetCursor>,
+    /// Statement the shown page of table data was read with
+    pub table_data_sql: Option<String>,
     /// SQL executor state
     pub sql_executor: SqlExecutorState,
-    /// Most recently opened tables (schema, table), newest first; max 5 entries.
-    pub recent_tables: Vec<(String, String)>,
+    /// Transaction state of the session, read after each statement
+    pub transaction_status: Option<TransactionStatus>,
+    /// Savepoints set with `(` in the open transaction, oldest first
+    pub savepoints: Vec<String>,
+    /// Most recently opened tables, newest first; loaded from the local
+    /// store on connect. The first five get `1`–`5` hotkeys.
+    pub recent_tables: Vec<RecentTable>,
+    /// Result sets run on this connection, to reopen without re-running
+    pub result_history: ResultHistory,
 }
 
 impl DatabaseExplorer {
@@ -56,7 +77,7 @@ impl DatabaseExplorer {
     ) -> Self {
         Self {
             connection,
-            database,
+            database: database.map(Arc::from),
             connections: FilteredData::default(),
             state: DatabaseExplorerState::Databases,
             previous_state: None,
@@ -66,17 +87,95 @@ impl DatabaseExplorer {
             columns: None,
             table_data: None,
             table_data_virtual: None,
+            table_order: None,
+            table_keyset: None,
+            table_data_sql: None,
             sql_executor: SqlExecutorState::new(),
+            transaction_status: None,
+            savepoints: Vec::new(),
             recent_tables: Vec::new(),
+            result_history: ResultHistory::default(),
+        }
+    }
+
+    /// Record that a table was opened for data view; updates the MRU list
+    /// and returns the entry to persist.
+    pub fn record_recent_table_open(
+        &mut self,
+        schema: &str,
+        table: &str,
+    ) -> RecentTable {
+        let recent = RecentTable {
+            database: match self.connection.r#type {
+                ConnectionType::Postgres => {
+                    self.connection.selected_database.clone()
+                }
+                ConnectionType::Sqlite => None,
+            },
+            schema_name: schema.to_string(),
+            table_name: table.to_string(),
+        };
+        self.recent_tables.retain(|r| r != &recent);
+        self.recent_tables.insert(0, recent.clone());
+        self.recent_tables.truncate(RECENT_TABLES_LIMIT);
+        recent
+    }
+
+    /// Show a freshly fetched page of the current table, keeping the
+    /// previous page's column widths, hidden columns, display modes, row
+    /// colors and filter.
+    pub fn replace_table_data_page(
+        &mut self,
+        mut table: TableDataState<RawTableRow>,
+    ) {
+        if let Some(previous) = self.table_data.as_ref() {
+            table
+                .view
+                .hidden_columns
+                .clone_from(&previous.table.view.hidden_columns);
+            table.view.expanded_column = previous.table.view.expanded_column;
+            table.view.wrapped_column = previous.table.view.wrapped_column;
+            table
+                .view
+                .column_widths
+                .clone_from(&previous.table.view.column_widths);
+            table.view.multi_line = previous.table.view.multi_line;
+            table
+                .view
+                .pinned_rows
+                .clone_from(&previous.table.view.pinned_rows);
+            table
+                .view
+                .row_colors
+                .clone_from(&previous.table.view.row_colors);
         }
+        let filter = self.table_data.as_ref().and_then(|fd| fd.query.clone());
+        table.show_order(self.table_order.as_ref());
+        let mut table_data = FilteredData::from_table(table);
+        if let Some(filter) = filter {
+            table_data.apply_filter(&filter);
+        }
+        self.table_data = Some(table_data);
     }
 
-    /// Record that a table was opened for data view; updates MRU (max 5).
-    pub fn record_recent_table_open(&mut self, schema: &str, table: &str) {
-        let pair = (schema.to_string(), table.to_string());
-        self.recent_tables.retain(|p| p != &pair);
-        self.recent_tables.insert(0, pair);
-        self.recent_tables.truncate(5);
+    /// Filter, order and keyset paging kept on the table being browsed
+    /// while paging and switching to its structure and back.
+    #[must_use]
+    pub fn sticky_predicates(&self) -> Vec<String> {
+        let filter = self
+            .table_data
+            .as_ref()
+            .and_then(|fd| fd.query.as_ref())
+            .map(|query| format!("/{query}"));
+        let order = self
+            .table_order
+            .as_ref()
+            .map(|order| format!("order {}", order.describe()));
+        let keyset = self
+            .table_keyset
+            .as_ref()
+            .map(|cursor| format!("by key {}", cursor.key_columns.join(", ")));
+        [filter, order, keyset].into_iter().flatten().collect()
     }
 
     /// Hotkey strip for the MRU column (`1`–`5` → reopen table data).
@@ -86,18 +185,30 @@ impl DatabaseExplorer {
             .iter()
             .enumerate()
             .take(5)
-            .map(|(i, (schema, table))| Hotkey {
+            .map(|(i, recent)| Hotkey {
                 keycode: KeyCode::Char(
                     (b'1' + u8::try_from(i).unwrap_or(0)) as char,
                 ),
+                modifiers: KeyModifiers::NONE,
                 description: HotkeyDescription::RecentTable {
-                    schema: schema.clone(),
-                    table: table.clone(),
+                    schema: recent.schema_name.clone(),
+                    table: recent.table_name.clone(),
                 },
             })
             .collect()
     }
 
+    /// Whether navigation passes through the Schemas level. `SQLite` only
+    /// shows it once other databases are attached.
+    #[must_use]
+    pub fn has_schema_step(&self) -> bool {
+        self.connection.r#type != ConnectionType::Sqlite
+            || self
+                .schemas
+                .as_ref()
+                .is_some_and(|schemas| schemas.original.len() > 1)
+    }
+
     /// Navigate the currently active explorer table (excludes Connections and `SqlExecutor`)
     pub fn navigate_current(&mut self, key: KeyCode) {
         match &self.state {
@@ -131,6 +242,25 @@ impl DatabaseExplorer {
         }
     }
 
+    /// Grid of rows being shown: table data or SQL results.
+    pub fn current_rows_mut(
+        &mut self,
+    ) -> Option<&mut TableDataState<RawTableRow>> {
+        match self.state {
+            DatabaseExplorerState::TableData(_, _) => {
+                self.table_data.as_mut().map(|fd| &mut fd.table)
+            }
+            DatabaseExplorerState::SqlResults(_) => {
+                Some(&mut self.sql_executor.table_state)
+            }
+            DatabaseExplorerState::Connections
+            | DatabaseExplorerState::Databases
+            | DatabaseExplorerState::Schemas
+            | DatabaseExplorerState::Tables(_)
+            | DatabaseExplorerState::Columns(_, _) => None,
+        }
+    }
+
     pub fn current_table_state_mut(&mut self) -> Option<&mut TableState> {
         let state = &mut self.state;
         match state {
@@ -162,3 +292,29 @@ impl DatabaseExplorer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(names: &[&str]) -> TableDataState<RawTableRow> {
+        let mut table = TableDataState::default();
+        let rows = names.iter().map(|name| vec![name.to_string()]).collect();
+        table.reset(rows, &["name".to_string()], None);
+        table
+    }
+
+    #[test]
+    fn keeps_the_filter_across_pages() {
+        let mut explorer = DatabaseExplorer::default();
+        explorer.replace_table_data_page(page(&["ann", "bob"]));
+        assert!(explorer.sticky_predicates().is_empty());
+        explorer.table_data.as_mut().unwrap().apply_filter("bo");
+
+        explorer.replace_table_data_page(page(&["bob2", "cat", "bolt"]));
+        let fd = explorer.table_data.as_ref().unwrap();
+        assert_eq!(fd.query.as_deref(), Some("bo"));
+        assert_eq!(fd.table.model.items.len(), 2);
+        assert_eq!(explorer.sticky_predicates(), ["/bo"]);
+    }
+}
@@ -0,0 +1,180 @@
+//! Timestamped copies of the local store (`d7s.db`), taken before it is
+//! migrated or a connection is deleted, and restoring one of them.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use chrono::DateTime;
+use color_eyre::{Result, eyre::eyre};
+use rusqlite::{Connection as SqliteConnection, OpenFlags};
+
+use crate::db::{get_app_data_dir, get_db_path};
+
+/// Backups kept in [`backup_dir`]; older ones are removed.
+pub const BACKUP_RETENTION: usize = 10;
+
+const BACKUP_PREFIX: &str = "d7s-";
+const BACKUP_EXTENSION: &str = "db";
+
+/// `backups` in the data directory.
+///
+/// # Errors
+///
+/// Returns an error if the directory can't be found or created.
+pub fn backup_dir() -> Result<PathBuf> {
+    let dir = get_app_data_dir()?.join("backups");
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Current UTC time for file names, e.g. `20261016-145438-120`; sorts in
+/// the order the files were written.
+#[must_use]
+pub fn file_timestamp() -> String {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|elapsed| i64::try_from(elapsed.as_millis()).ok())
+        .and_then(DateTime::from_timestamp_millis)
+        .unwrap_or_default()
+        .format("%Y%m%d-%H%M%S-%3f")
+        .to_string()
+}
+
+/// Write a copy of the store open in `conn` to [`backup_dir`], named after
+/// the time and `reason` (e.g. `migration`), then drop the backups beyond
+/// [`BACKUP_RETENTION`].
+///
+/// # Errors
+///
+/// Returns an error if the copy can't be written.
+pub fn backup_store(conn: &SqliteConnection, reason: &str) -> Result<PathBuf> {
+    let dir = backup_dir()?;
+    let path = dir.join(format!(
+        "{BACKUP_PREFIX}{}-{reason}.{BACKUP_EXTENSION}",
+        file_timestamp()
+    ));
+    conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])?;
+    prune_backups(&dir, BACKUP_RETENTION)?;
+    Ok(path)
+}
+
+/// Backups in `dir`, newest first.
+///
+/// # Errors
+///
+/// Returns an error if the directory can't be read.
+pub fn list_backups(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut backups = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == BACKUP_EXTENSION)
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(BACKUP_PREFIX))
+        })
+        .collect::<Vec<_>>();
+    backups.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
+    Ok(backups)
+}
+
+/// Remove all but the newest `keep` backups in `dir`.
+fn prune_backups(dir: &Path, keep: usize) -> Result<()> {
+    for old in list_backups(dir)?.iter().skip(keep) {
+        fs::remove_file(old)?;
+    }
+    Ok(())
+}
+
+/// Replace the store with `backup`, a file name in [`backup_dir`] or a
+/// path. The store being replaced is backed up first; returns where.
+///
+/// # Errors
+///
+/// Returns an error if `backup` isn't a readable, intact database or the
+/// store can't be replaced.
+pub fn restore_backup(backup: &Path) -> Result<Option<PathBuf>> {
+    let dir = backup_dir()?;
+    let backup = if backup.is_file() {
+        backup.to_path_buf()
+    } else {
+        dir.join(backup)
+    };
+    let source = SqliteConnection::open_with_flags(
+        &backup,
+        OpenFlags::SQLITE_OPEN_READ_ONLY,
+    )
+    .map_err(|e| eyre!("Can't open {}: {e}", backup.display()))?;
+    let check: String =
+        source.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+    if check != "ok" {
+        return Err(eyre!(
+            "{} failed its integrity check: {check}",
+            backup.display()
+        ));
+    }
+    drop(source);
+
+    let db_path = get_db_path()?;
+    // A plain copy, so even a store SQLite can't read is kept
+    let previous = if db_path.exists() {
+        let path = dir.join(format!(
+            "{BACKUP_PREFIX}{}-restore.{BACKUP_EXTENSION}",
+            file_timestamp()
+        ));
+        fs::copy(&db_path, &path)?;
+        Some(path)
+    } else {
+        None
+    };
+    for suffix in ["-journal", "-wal", "-shm"] {
+        let mut sidecar = db_path.clone().into_os_string();
+        sidecar.push(suffix);
+        if Path::new(&sidecar).exists() {
+            fs::remove_file(sidecar)?;
+        }
+    }
+    fs::copy(&backup, &db_path)?;
+    Ok(previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_newest_backups() {
+        let dir = std::env::temp_dir()
+            .join(format!("d7s-backups-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "d7s-20260101-000000-000-migration.db",
+            "d7s-20260301-000000-000-delete.db",
+            "d7s-20260201-000000-000-delete.db",
+            "notes.db",
+            "d7s-20260401-000000-000.json",
+        ] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        prune_backups(&dir, 2).unwrap();
+        let names = list_backups(&dir)
+            .unwrap()
+            .iter()
+            .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "d7s-20260301-000000-000-delete.db",
+                "d7s-20260201-000000-000-delete.db"
+            ]
+        );
+        assert!(dir.join("notes.db").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+//! Table and column names of a connected database, read ahead of time so
+//! prompts that take a table or column can complete them without going
+//! back to the server.
+
+/// A table and the names of its columns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CatalogTable {
+    pub schema: String,
+    pub name: String,
+    pub columns: Vec<String>,
+}
+
+impl CatalogTable {
+    /// `schema.table`, as typed into prompts.
+    #[must_use]
+    pub fn qualified_name(&self) -> String {
+        format!("{}.{}", self.schema, self.name)
+    }
+}
+
+/// The tables of every schema of a database.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Catalog {
+    pub tables: Vec<CatalogTable>,
+}
+
+impl Catalog {
+    /// Every table as `schema.table`.
+    #[must_use]
+    pub fn table_names(&self) -> Vec<String> {
+        self.tables
+            .iter()
+            .map(CatalogTable::qualified_name)
+            .collect()
+    }
+
+    /// Column names of `schema.table`.
+    #[must_use]
+    pub fn columns(&self, schema: &str, table: &str) -> Option<&[String]> {
+        self.tables
+            .iter()
+            .find(|t| t.schema == schema && t.name == table)
+            .map(|t| t.columns.as_slice())
+    }
+
+    /// The table `name` refers to: one named `schema.table` or `table`,
+    /// ignoring case, else the only one `name` fuzzily matches.
+    #[must_use]
+    pub fn find(&self, name: &str) -> Option<&CatalogTable> {
+        let named = self.tables.iter().find(|table| {
+            table.qualified_name().eq_ignore_ascii_case(name)
+                || table.name.eq_ignore_ascii_case(name)
+        });
+        if named.is_some() {
+            return named;
+        }
+        let mut fuzzy = self
+            .tables
+            .iter()
+            .filter(|table| is_fuzzy_match(&table.qualified_name(), name));
+        match (fuzzy.next(), fuzzy.next()) {
+            (Some(table), None) => Some(table),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the characters of `typed` appear in `candidate` in order,
+/// ignoring case, e.g. `ordit` in `public.order_items`.
+#[must_use]
+pub fn is_fuzzy_match(candidate: &str, typed: &str) -> bool {
+    let mut chars = candidate.chars().flat_map(char::to_lowercase);
+    typed
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| chars.any(|other| other == c))
+}
+
+fn starts_with_ignore_case(candidate: &str, typed: &str) -> bool {
+    candidate.to_lowercase().starts_with(&typed.to_lowercase())
+}
+
+/// `value` split before its last comma-separated item.
+fn split_last_item(value: &str) -> (&str, &str) {
+    value.split_at(value.rfind(',').map_or(0, |idx| idx + 1))
+}
+
+/// The candidates for the last comma-separated item of `value`: those
+/// starting with it, ignoring case, else those it fuzzily matches.
+#[must_use]
+pub fn matches<'a>(value: &str, candidates: &'a [String]) -> Vec<&'a str> {
+    let typed = split_last_item(value).1.trim();
+    let starting = candidates
+        .iter()
+        .filter(|candidate| starts_with_ignore_case(candidate, typed))
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    if !starting.is_empty() {
+        return starting;
+    }
+    candidates
+        .iter()
+        .filter(|candidate| is_fuzzy_match(candidate, typed))
+        .map(String::as_str)
+        .collect()
+}
+
+/// `value` with its last comma-separated item completed: to the only
+/// candidate matching it, else as far as the candidates starting with it
+/// agree. `None` when that adds nothing.
+#[must_use]
+pub fn complete(value: &str, candidates: &[String]) -> Option<String> {
+    let (head, item) = split_last_item(value);
+    let (indent, typed) = item.split_at(item.len() - item.trim_start().len());
+    let typed = typed.trim_end();
+    let found = matches(value, candidates);
+    let completed = match found.as_slice() {
+        [only] => (*only).to_string(),
+        [first, rest @ ..] => {
+            rest.iter().fold(first.to_string(), |common, other| {
+                common
+                    .chars()
+                    .zip(other.chars())
+                    .take_while(|(a, b)| a.to_lowercase().eq(b.to_lowercase()))
+                    .map(|(a, _)| a)
+                    .collect()
+            })
+        }
+        [] => return None,
+    };
+    // Fuzzy matches may have nothing in common with what was typed
+    if found.len() > 1 && !starts_with_ignore_case(&completed, typed) {
+        return None;
+    }
+    (completed != typed).then(|| format!("{head}{indent}{completed}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn completes_the_last_item() {
+        let tables = strings(&[
+            "public.orders",
+            "public.order_items",
+            "sales.customers",
+        ]);
+        assert_eq!(complete("pub", &tables).as_deref(), Some("public.order"));
+        assert_eq!(complete("public.order", &tables), None);
+        assert_eq!(
+            complete("cust", &tables).as_deref(),
+            Some("sales.customers")
+        );
+        assert_eq!(matches("oit", &tables), ["public.order_items"]);
+        assert_eq!(complete("zzz", &tables), None);
+
+        let columns = strings(&["id", "Name", "created_at"]);
+        assert_eq!(complete("id, na", &columns).as_deref(), Some("id, Name"));
+        assert_eq!(complete("id,", &columns), None);
+    }
+
+    #[test]
+    fn finds_tables_by_name_or_fuzzy_match() {
+        let catalog = Catalog {
+            tables: vec![
+                CatalogTable {
+                    schema: "public".to_string(),
+                    name: "orders".to_string(),
+                    columns: strings(&["id", "total"]),
+                },
+                CatalogTable {
+                    schema: "public".to_string(),
+                    name: "order_items".to_string(),
+                    columns: Vec::new(),
+                },
+            ],
+        };
+        let name = |found: Option<&CatalogTable>| found.map(|t| t.name.clone());
+        assert_eq!(name(catalog.find("ORDERS")).as_deref(), Some("orders"));
+        assert_eq!(
+            name(catalog.find("oitems")).as_deref(),
+            Some("order_items")
+        );
+        assert_eq!(catalog.find("ord"), None);
+        assert_eq!(
+            catalog.columns("public", "orders"),
+            Some(strings(&["id", "total"]).as_slice())
+        );
+    }
+}
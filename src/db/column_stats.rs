@@ -0,0 +1,109 @@
+use crate::sort::parse_number;
+
+/// Aggregates of a numeric column, over loaded rows or a whole table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnStats {
+    /// Non-NULL values.
+    pub count: u64,
+    pub nulls: u64,
+    pub sum: Option<f64>,
+    pub mean: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl ColumnStats {
+    /// Aggregate cell values as shown in the grid; `NULL` cells are counted
+    /// separately. Returns `None` when a value isn't a number.
+    pub fn from_values<'a>(
+        values: impl IntoIterator<Item = &'a str>,
+    ) -> Option<Self> {
+        let mut stats = Self::default();
+        for value in values {
+            if value == "NULL" {
+                stats.nulls += 1;
+                continue;
+            }
+            let n = parse_number(value)?;
+            stats.count += 1;
+            stats.sum = Some(stats.sum.unwrap_or_default() + n);
+            stats.min = Some(stats.min.map_or(n, |min| min.min(n)));
+            stats.max = Some(stats.max.map_or(n, |max| max.max(n)));
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let count = stats.count as f64;
+        stats.mean = stats.sum.map(|sum| sum / count);
+        Some(stats)
+    }
+
+    /// Read `COUNT(col), COUNT(*), SUM, AVG, MIN, MAX` from one result row.
+    #[must_use]
+    pub fn from_aggregate_row(values: &[String]) -> Self {
+        let number = |idx: usize| values.get(idx).and_then(|v| parse_number(v));
+        let count = number(0).unwrap_or_default();
+        let total = number(1).unwrap_or_default();
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Self {
+            count: count as u64,
+            nulls: (total - count).max(0.0) as u64,
+            sum: number(2),
+            mean: number(3),
+            min: number(4),
+            max: number(5),
+        }
+    }
+
+    /// `label  value` rows for display.
+    #[must_use]
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        let fmt =
+            |v: Option<f64>| v.map_or_else(|| "-".to_string(), format_number);
+        vec![
+            ("count", self.count.to_string()),
+            ("nulls", self.nulls.to_string()),
+            ("sum", fmt(self.sum)),
+            ("mean", fmt(self.mean)),
+            ("min", fmt(self.min)),
+            ("max", fmt(self.max)),
+        ]
+    }
+}
+
+/// Whole numbers without a fraction, others to at most 6 decimals.
+pub fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{n:.0}")
+    } else {
+        let s = format!("{n:.6}");
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregates_loaded_values() {
+        let stats =
+            ColumnStats::from_values(["1", "NULL", "2.5", "-0.5"]).unwrap();
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.nulls, 1);
+        assert_eq!(stats.sum, Some(3.0));
+        assert_eq!(stats.mean, Some(1.0));
+        assert_eq!(stats.min, Some(-0.5));
+        assert_eq!(stats.max, Some(2.5));
+
+        assert!(ColumnStats::from_values(["1", "x"]).is_none());
+    }
+
+    #[test]
+    fn reads_aggregate_rows_and_formats() {
+        let row =
+            ["2", "3", "7", "3.5000000000", "3", "4", ""].map(String::from);
+        let stats = ColumnStats::from_aggregate_row(&row);
+        assert_eq!(stats.nulls, 1);
+        assert_eq!(stats.rows().get(3), Some(&("mean", "3.5".to_string())));
+        assert_eq!(format_number(1.0 / 3.0), "0.333333");
+    }
+}
@@ -0,0 +1,121 @@
+//! `CREATE` statements for the structure of a schema, assembled from what
+//! the backends read out of their catalogs.
+
+use std::fmt::Write;
+
+/// Column of a generated `CREATE TABLE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DdlColumn {
+    /// Quoted column name.
+    pub name: String,
+    pub data_type: String,
+    pub not_null: bool,
+    pub default: Option<String>,
+    /// Generation clause, e.g. `GENERATED ALWAYS AS IDENTITY`.
+    pub generated: Option<String>,
+}
+
+impl DdlColumn {
+    fn definition(&self) -> String {
+        let mut definition = format!("{} {}", self.name, self.data_type);
+        if let Some(generated) = &self.generated {
+            let _ = write!(definition, " {generated}");
+        }
+        if let Some(default) = &self.default {
+            let _ = write!(definition, " DEFAULT {default}");
+        }
+        if self.not_null {
+            definition.push_str(" NOT NULL");
+        }
+        definition
+    }
+}
+
+/// `CREATE TABLE <table> (...)<suffix>`; `table` is already qualified and
+/// quoted, `suffix` is e.g. a `PARTITION BY` clause.
+#[must_use]
+pub fn create_table_statement(
+    table: &str,
+    columns: &[DdlColumn],
+    suffix: Option<&str>,
+) -> String {
+    let columns = columns
+        .iter()
+        .map(|column| format!("    {}", column.definition()))
+        .collect::<Vec<_>>()
+        .join(",\n");
+    let suffix = suffix.map(|s| format!(" {s}")).unwrap_or_default();
+    format!("CREATE TABLE {table} (\n{columns}\n){suffix}")
+}
+
+/// The structure of a schema as statements, in the order they can be run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDdl {
+    /// Objects the tables depend on, e.g. sequences.
+    pub prelude: Vec<String>,
+    /// One `CREATE TABLE` per table.
+    pub tables: Vec<String>,
+    /// Constraints, indexes and triggers, run once every table exists.
+    pub extras: Vec<String>,
+}
+
+impl SchemaDdl {
+    /// The statements as one `.sql` script, headed by a comment naming the
+    /// schema.
+    #[must_use]
+    pub fn script(&self, schema_name: &str) -> String {
+        let mut script = format!(
+            "-- Structure of schema {schema_name}: {} tables\n",
+            self.tables.len()
+        );
+        for section in [&self.prelude, &self.tables, &self.extras] {
+            for statement in section {
+                let _ =
+                    write!(script, "\n{};\n", statement.trim_end_matches(';'));
+            }
+        }
+        script
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_a_runnable_script() {
+        let columns = [
+            DdlColumn {
+                name: "\"id\"".to_string(),
+                data_type: "integer".to_string(),
+                not_null: true,
+                default: None,
+                generated: Some("GENERATED ALWAYS AS IDENTITY".to_string()),
+            },
+            DdlColumn {
+                name: "\"note\"".to_string(),
+                data_type: "text".to_string(),
+                not_null: false,
+                default: Some("'x'::text".to_string()),
+                generated: None,
+            },
+        ];
+        let ddl = SchemaDdl {
+            prelude: Vec::new(),
+            tables: vec![create_table_statement(
+                "\"public\".\"t\"",
+                &columns,
+                None,
+            )],
+            extras: vec!["CREATE INDEX i ON t (note);".to_string()],
+        };
+        assert_eq!(
+            ddl.script("public"),
+            "-- Structure of schema public: 1 tables\n\n\
+             CREATE TABLE \"public\".\"t\" (\n    \
+             \"id\" integer GENERATED ALWAYS AS IDENTITY NOT NULL,\n    \
+             \"note\" text DEFAULT 'x'::text\n);\n\n\
+             CREATE INDEX i ON t (note);\n"
+        );
+    }
+}
@@ -0,0 +1,595 @@
+use crate::{
+    db::top_query::{TOP_QUERIES_LIMIT, TopQueryOrder},
+    sort::{NullsOrder, SortDirection, TableOrder},
+};
+
+/// SQL differences between backends: identifier quoting, paging, bind
+/// parameters and the catalog queries behind the explorer.
+///
+/// Catalog queries bind their arguments with [`Dialect::placeholder`]; each
+/// backend maps the returned rows itself.
+pub trait Dialect: Send + Sync {
+    /// Quote an identifier, escaping embedded quote characters.
+    fn quote_ident(&self, ident: &str) -> String {
+        format!("\"{}\"", ident.replace('"', "\"\""))
+    }
+
+    fn qualified_table(&self, schema_name: &str, table_name: &str) -> String {
+        format!(
+            "{}.{}",
+            self.quote_ident(schema_name),
+            self.quote_ident(table_name)
+        )
+    }
+
+    /// Quote a string literal, escaping embedded quotes.
+    fn quote_literal(&self, value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
+    /// Bind parameter for the 1-based `index`.
+    fn placeholder(&self, index: usize) -> String;
+
+    /// Paging clause appended to a `SELECT`; `limit` and `offset` are SQL
+    /// expressions (literals or placeholders).
+    fn paging_clause(&self, limit: &str, offset: Option<&str>) -> String {
+        offset.map_or_else(
+            || format!("LIMIT {limit}"),
+            |offset| format!("LIMIT {limit} OFFSET {offset}"),
+        )
+    }
+
+    /// `ORDER BY` clause for a table order, including NULL placement.
+    fn order_by_clause(&self, order: &TableOrder) -> String {
+        let direction = match order.direction {
+            SortDirection::Ascending => "ASC",
+            SortDirection::Descending => "DESC",
+        };
+        let nulls = match order.nulls {
+            NullsOrder::First => "NULLS FIRST",
+            NullsOrder::Last => "NULLS LAST",
+        };
+        format!(
+            "ORDER BY {} {direction} {nulls}",
+            self.quote_ident(&order.column)
+        )
+    }
+
+    /// A column as a double: the value itself, or seconds since the Unix
+    /// epoch for dates and timestamps.
+    fn numeric_expr(&self, column: &str, temporal: bool) -> String;
+
+    /// 0-based histogram bucket of `value`; `min` and `width` are SQL
+    /// expressions.
+    fn bucket_expr(&self, value: &str, min: &str, width: &str) -> String {
+        format!("CAST(({value} - {min}) / {width} AS INTEGER)")
+    }
+
+    /// Order-independent checksum of a column's non-NULL values: the sum of
+    /// [`value_checksum`](crate::db::table_compare::value_checksum) over
+    /// their text.
+    fn checksum_expr(&self, column: &str) -> String;
+
+    /// Text of the first value SQL/JSON `path` (a quoted literal) selects
+    /// from the JSON in `column` (quoted), NULL when none.
+    fn json_path_expr(&self, column: &str, path: &str) -> String;
+
+    /// `column` (quoted) followed by `->` / `->>` operators as typed.
+    fn json_operators_expr(&self, column: &str, operators: &str) -> String {
+        format!("{column}{operators}")
+    }
+
+    /// Case-insensitive `LIKE` of `column` (quoted) against `pattern`, a
+    /// quoted literal whose wildcards are escaped with `\`.
+    fn contains_text_expr(&self, column: &str, pattern: &str) -> String;
+
+    /// Full-text match of `column` (quoted) against the words of `term`, a
+    /// quoted literal, for backends with full-text search.
+    fn full_text_match_expr(
+        &self,
+        _column: &str,
+        _term: &str,
+    ) -> Option<String> {
+        None
+    }
+
+    /// Statement returning the planner's estimate for `sql` as JSON, when
+    /// the backend reports costs and row counts.
+    fn explain_estimate_query(&self, _sql: &str) -> Option<String> {
+        None
+    }
+
+    /// Statement returning the plan for `sql` as JSON with the schema and
+    /// alias of every scanned relation, without running it.
+    fn explain_verbose_query(&self, _sql: &str) -> Option<String> {
+        None
+    }
+
+    /// Statement running `sql` and returning its executed plan as JSON,
+    /// with per-node timings.
+    fn explain_analyze_query(&self, _sql: &str) -> Option<String> {
+        None
+    }
+
+    /// Statement returning the schemas unqualified names resolve in, for
+    /// backends with a search path.
+    fn search_path_query(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Statement returning the (quoted) schema `pg_stat_statements` is
+    /// installed in; no rows when it isn't.
+    fn stat_statements_schema_query(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Statement returning the current database's normalized statements as
+    /// `query, calls, total_ms, mean_ms, rows`, highest `order` first.
+    fn top_queries_query(
+        &self,
+        _schema: &str,
+        _order: TopQueryOrder,
+    ) -> Option<String> {
+        None
+    }
+
+    /// Statement returning the disk used per schema, largest first: the
+    /// number of tables and the MB their data, indexes and (where the backend
+    /// has it) TOAST take.
+    fn schema_sizes_query(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Statement returning the disk used by each table of `schema` the way
+    /// [`Dialect::schema_sizes_query`] does per schema.
+    fn table_sizes_query(&self, _schema: &str) -> Option<String> {
+        None
+    }
+
+    /// Statement returning `schema, table, attnum, attname` for each
+    /// column of the table with OID `table_oid`, for backends whose result
+    /// columns carry one.
+    fn table_attributes_query(&self, _table_oid: u32) -> Option<String> {
+        None
+    }
+
+    /// Schemas visible to the connection.
+    fn schemas_query(&self) -> &'static str;
+
+    /// Tables, views and the other relations listed in a schema, with
+    /// their kind; binds the schema name.
+    fn tables_query(&self) -> &'static str;
+
+    /// Columns of a table in ordinal order; binds table and schema names.
+    fn columns_query(&self) -> &'static str;
+
+    /// Primary-key columns in key order; binds table and schema names.
+    fn primary_key_query(&self) -> &'static str;
+
+    /// Foreign keys as one row per column: constraint, column, parent
+    /// schema, parent table, parent column and whether it's checked for
+    /// every row; binds table and schema names.
+    fn foreign_keys_query(&self) -> &'static str;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostgresDialect;
+
+impl Dialect for PostgresDialect {
+    fn placeholder(&self, index: usize) -> String {
+        format!("${index}")
+    }
+
+    fn numeric_expr(&self, column: &str, temporal: bool) -> String {
+        let column = self.quote_ident(column);
+        if temporal {
+            format!("EXTRACT(EPOCH FROM {column})::double precision")
+        } else {
+            format!("{column}::double precision")
+        }
+    }
+
+    // `#>> '{}'` turns the jsonb result into text, unquoting strings
+    fn json_path_expr(&self, column: &str, path: &str) -> String {
+        format!("jsonb_path_query_first({column}::jsonb, {path}) #>> '{{}}'")
+    }
+
+    // The operators need `json`/`jsonb`, and text columns often hold JSON
+    fn json_operators_expr(&self, column: &str, operators: &str) -> String {
+        format!("{column}::jsonb{operators}")
+    }
+
+    fn contains_text_expr(&self, column: &str, pattern: &str) -> String {
+        format!("{column}::text ILIKE {pattern} ESCAPE '\\'")
+    }
+
+    fn full_text_match_expr(&self, column: &str, term: &str) -> Option<String> {
+        Some(format!(
+            "to_tsvector({column}::text) @@ plainto_tsquery({term})"
+        ))
+    }
+
+    // `CAST(... AS INTEGER)` rounds in PostgreSQL
+    fn bucket_expr(&self, value: &str, min: &str, width: &str) -> String {
+        format!("FLOOR(({value} - {min}) / {width})::bigint")
+    }
+
+    fn checksum_expr(&self, column: &str) -> String {
+        format!(
+            "SUM(('x' || substr(md5({}::text), 1, 8))::bit(32)::int)",
+            self.quote_ident(column)
+        )
+    }
+
+    fn explain_estimate_query(&self, sql: &str) -> Option<String> {
+        Some(format!("EXPLAIN (FORMAT JSON) {sql}"))
+    }
+
+    fn explain_verbose_query(&self, sql: &str) -> Option<String> {
+        Some(format!("EXPLAIN (VERBOSE, FORMAT JSON) {sql}"))
+    }
+
+    fn explain_analyze_query(&self, sql: &str) -> Option<String> {
+        Some(format!("EXPLAIN (ANALYZE, FORMAT JSON) {sql}"))
+    }
+
+    fn search_path_query(&self) -> Option<&'static str> {
+        Some("SHOW search_path")
+    }
+
+    fn stat_statements_schema_query(&self) -> Option<&'static str> {
+        Some(
+            "SELECT extnamespace::regnamespace::text FROM pg_extension \
+             WHERE extname = 'pg_stat_statements'",
+        )
+    }
+
+    // The time columns were renamed in PostgreSQL 13
+    fn top_queries_query(
+        &self,
+        schema: &str,
+        order: TopQueryOrder,
+    ) -> Option<String> {
+        Some(format!(
+            "
+            SELECT query, calls, total_ms, mean_ms, rows FROM (
+                SELECT
+                    s.query,
+                    s.calls,
+                    COALESCE(
+                        to_jsonb(s) ->> 'total_exec_time',
+                        to_jsonb(s) ->> 'total_time'
+                    )::float8 AS total_ms,
+                    COALESCE(
+                        to_jsonb(s) ->> 'mean_exec_time',
+                        to_jsonb(s) ->> 'mean_time'
+                    )::float8 AS mean_ms,
+                    s.rows
+                FROM {schema}.pg_stat_statements s
+                JOIN pg_database d ON d.oid = s.dbid
+                WHERE d.datname = current_database()
+            ) q
+            ORDER BY {} DESC
+            LIMIT {TOP_QUERIES_LIMIT}
+            ",
+            order.column()
+        ))
+    }
+
+    fn schema_sizes_query(&self) -> Option<&'static str> {
+        Some(
+            "
+            SELECT
+                schema,
+                count(*) AS tables,
+                round(sum(total) / 1048576.0, 2) AS total_mb,
+                round(sum(total - indexes - toast) / 1048576.0, 2) AS data_mb,
+                round(sum(indexes) / 1048576.0, 2) AS indexes_mb,
+                round(sum(toast) / 1048576.0, 2) AS toast_mb
+            FROM (
+                SELECT
+                    n.nspname AS schema,
+                    pg_total_relation_size(c.oid) AS total,
+                    pg_indexes_size(c.oid) AS indexes,
+                    COALESCE(
+                        pg_total_relation_size(NULLIF(c.reltoastrelid, 0)), 0
+                    ) AS toast
+                FROM pg_catalog.pg_class c
+                JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+                WHERE c.relkind IN ('r', 'm')
+            ) t
+            GROUP BY schema
+            ORDER BY sum(total) DESC
+            ",
+        )
+    }
+
+    fn table_sizes_query(&self, schema: &str) -> Option<String> {
+        Some(format!(
+            "
+            SELECT
+                \"table\",
+                est_rows,
+                round(total / 1048576.0, 2) AS total_mb,
+                round((total - indexes - toast) / 1048576.0, 2) AS data_mb,
+                round(indexes / 1048576.0, 2) AS indexes_mb,
+                round(toast / 1048576.0, 2) AS toast_mb
+            FROM (
+                SELECT
+                    c.relname AS \"table\",
+                    c.reltuples::bigint AS est_rows,
+                    pg_total_relation_size(c.oid) AS total,
+                    pg_indexes_size(c.oid) AS indexes,
+                    COALESCE(
+                        pg_total_relation_size(NULLIF(c.reltoastrelid, 0)), 0
+                    ) AS toast
+                FROM pg_catalog.pg_class c
+                JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+                WHERE c.relkind IN ('r', 'm') AND n.nspname = {}
+            ) t
+            ORDER BY total DESC
+            ",
+            self.quote_literal(schema)
+        ))
+    }
+
+    fn table_attributes_query(&self, table_oid: u32) -> Option<String> {
+        Some(format!(
+            "
+            SELECT n.nspname, c.relname, a.attnum, a.attname
+            FROM pg_catalog.pg_attribute a
+            JOIN pg_catalog.pg_class c ON c.oid = a.attrelid
+            JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+            WHERE a.attrelid = {table_oid}
+                AND a.attnum > 0
+                AND NOT a.attisdropped
+            ORDER BY a.attnum
+            "
+        ))
+    }
+
+    fn schemas_query(&self) -> &'static str {
+        "
+            SELECT schema_name, schema_owner
+            FROM information_schema.schemata
+            WHERE schema_name NOT IN ('information_schema', 'pg_catalog', 'pg_toast')
+            ORDER BY schema_name
+        "
+    }
+
+    // Partitions are left out; their parent is listed as `p`
+    fn tables_query(&self) -> &'static str {
+        "
+            SELECT
+                c.relname,
+                n.nspname,
+                CASE WHEN c.relkind IN ('r', 'm', 'p')
+                    THEN pg_size_pretty(pg_total_relation_size(c.oid))
+                END AS size,
+                c.relkind::text
+            FROM pg_catalog.pg_class c
+            JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+            WHERE n.nspname = $1
+            AND c.relkind IN ('r', 'p', 'v', 'm', 'f')
+            AND NOT c.relispartition
+            ORDER BY c.relname;
+        "
+    }
+
+    fn columns_query(&self) -> &'static str {
+        "
+            SELECT
+                c.column_name,
+                c.data_type,
+                c.is_nullable,
+                c.column_default,
+                pgd.description
+            FROM information_schema.columns c
+            LEFT JOIN pg_catalog.pg_statio_all_tables st ON (c.table_schema = st.schemaname AND c.table_name = st.relname)
+            LEFT JOIN pg_catalog.pg_description pgd ON (pgd.objoid = st.relid AND pgd.objsubid = c.ordinal_position)
+            WHERE c.table_name = $1
+            AND c.table_schema = $2
+            ORDER BY c.ordinal_position
+        "
+    }
+
+    fn primary_key_query(&self) -> &'static str {
+        "
+            SELECT kcu.column_name
+            FROM information_schema.table_constraints tc
+            INNER JOIN information_schema.key_column_usage kcu
+                ON tc.constraint_schema = kcu.constraint_schema
+                AND tc.constraint_name = kcu.constraint_name
+                AND tc.table_schema = kcu.table_schema
+                AND tc.table_name = kcu.table_name
+            WHERE tc.constraint_type = 'PRIMARY KEY'
+                AND tc.table_name = $1
+                AND tc.table_schema = $2
+            ORDER BY kcu.ordinal_position
+        "
+    }
+
+    fn foreign_keys_query(&self) -> &'static str {
+        "
+            SELECT
+                con.conname::text,
+                a.attname::text,
+                pn.nspname::text,
+                pc.relname::text,
+                pa.attname::text,
+                con.convalidated
+            FROM pg_catalog.pg_constraint con
+            JOIN pg_catalog.pg_class c ON c.oid = con.conrelid
+            JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+            JOIN pg_catalog.pg_class pc ON pc.oid = con.confrelid
+            JOIN pg_catalog.pg_namespace pn ON pn.oid = pc.relnamespace
+            CROSS JOIN LATERAL unnest(con.conkey, con.confkey)
+                WITH ORDINALITY AS k(attnum, parent_attnum, position)
+            JOIN pg_catalog.pg_attribute a
+                ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+            JOIN pg_catalog.pg_attribute pa
+                ON pa.attrelid = con.confrelid AND pa.attnum = k.parent_attnum
+            WHERE con.contype = 'f'
+                AND c.relname = $1
+                AND n.nspname = $2
+            ORDER BY con.conname, k.position
+        "
+    }
+}
+
+/// Scalar function the `SQLite` backend registers for
+/// [`Dialect::checksum_expr`].
+pub const SQLITE_CHECKSUM_FUNCTION: &str = "d7s_checksum";
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SqliteDialect;
+
+impl Dialect for SqliteDialect {
+    fn placeholder(&self, index: usize) -> String {
+        format!("?{index}")
+    }
+
+    fn numeric_expr(&self, column: &str, temporal: bool) -> String {
+        let column = self.quote_ident(column);
+        if temporal {
+            format!("((julianday({column}) - 2440587.5) * 86400.0)")
+        } else {
+            format!("CAST({column} AS REAL)")
+        }
+    }
+
+    fn json_path_expr(&self, column: &str, path: &str) -> String {
+        format!("json_extract({column}, {path})")
+    }
+
+    // `LIKE` ignores the case of ASCII letters
+    fn contains_text_expr(&self, column: &str, pattern: &str) -> String {
+        format!("CAST({column} AS TEXT) LIKE {pattern} ESCAPE '\\'")
+    }
+
+    // SQLite has no hash function; the connection registers one
+    fn checksum_expr(&self, column: &str) -> String {
+        format!(
+            "SUM({SQLITE_CHECKSUM_FUNCTION}(CAST({} AS TEXT)))",
+            self.quote_ident(column)
+        )
+    }
+
+    // `dbstat` has a row per b-tree page, under the name of the table or
+    // index it belongs to
+    fn schema_sizes_query(&self) -> Option<&'static str> {
+        Some(
+            "
+            SELECT
+                l.name AS schema,
+                (
+                    SELECT count(*) FROM pragma_table_list
+                    WHERE schema = l.name AND type = 'table'
+                    AND name NOT LIKE 'sqlite_%'
+                ) AS tables,
+                round(sum(d.pgsize) / 1048576.0, 2) AS total_mb,
+                round(sum(iif(t.name IS NULL, 0, d.pgsize)) / 1048576.0, 2)
+                    AS data_mb,
+                round(sum(iif(t.name IS NULL, d.pgsize, 0)) / 1048576.0, 2)
+                    AS indexes_mb
+            FROM pragma_database_list l
+            JOIN dbstat(l.name) d
+            LEFT JOIN pragma_table_list t
+                ON t.schema = l.name AND t.name = d.name
+            GROUP BY l.name
+            ORDER BY sum(d.pgsize) DESC
+            ",
+        )
+    }
+
+    fn table_sizes_query(&self, schema: &str) -> Option<String> {
+        Some(format!(
+            "
+            SELECT
+                m.tbl_name AS \"table\",
+                round(sum(d.pgsize) / 1048576.0, 2) AS total_mb,
+                round(sum(iif(m.type = 'table', d.pgsize, 0)) / 1048576.0, 2)
+                    AS data_mb,
+                round(sum(iif(m.type = 'index', d.pgsize, 0)) / 1048576.0, 2)
+                    AS indexes_mb
+            FROM dbstat({}) d
+            JOIN {}.sqlite_schema m ON m.name = d.name
+            GROUP BY m.tbl_name
+            ORDER BY sum(d.pgsize) DESC
+            ",
+            self.quote_literal(schema),
+            self.quote_ident(schema)
+        ))
+    }
+
+    fn schemas_query(&self) -> &'static str {
+        "PRAGMA database_list"
+    }
+
+    fn tables_query(&self) -> &'static str {
+        "SELECT name, type FROM pragma_table_list
+         WHERE schema = ?1 AND type IN ('table', 'view', 'virtual')
+         AND name NOT IN ('sqlite_schema', 'sqlite_temp_schema')
+         ORDER BY name"
+    }
+
+    fn columns_query(&self) -> &'static str {
+        r#"SELECT name, type, "notnull", dflt_value
+           FROM pragma_table_info(?1, ?2)
+           ORDER BY cid"#
+    }
+
+    fn primary_key_query(&self) -> &'static str {
+        "SELECT name FROM pragma_table_info(?1, ?2) WHERE pk > 0 ORDER BY pk"
+    }
+
+    // Keys have no name, and reference the parent's primary key when `to`
+    // is NULL; the parent is always in the same schema. Rows written with
+    // `PRAGMA foreign_keys` off are never checked, so no key counts as
+    // validated.
+    fn foreign_keys_query(&self) -> &'static str {
+        r#"SELECT 'foreign key ' || id, "from", ?2, "table", "to", 0
+           FROM pragma_foreign_key_list(?1, ?2)
+           ORDER BY id, seq"#
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_identifiers_and_qualifies_tables() {
+        assert_eq!(PostgresDialect.quote_ident(r#"a"b"#), r#""a""b""#);
+        assert_eq!(SqliteDialect.quote_literal("it's"), "'it''s'");
+        assert_eq!(
+            SqliteDialect.qualified_table("main", "users"),
+            r#""main"."users""#
+        );
+    }
+
+    #[test]
+    fn placeholders_and_paging_follow_backend_style() {
+        let pg = PostgresDialect;
+        let paging =
+            pg.paging_clause(&pg.placeholder(1), Some(&pg.placeholder(2)));
+        assert_eq!(paging, "LIMIT $1 OFFSET $2");
+
+        let sqlite = SqliteDialect;
+        assert_eq!(sqlite.placeholder(3), "?3");
+        assert_eq!(sqlite.paging_clause("100", None), "LIMIT 100");
+    }
+
+    #[test]
+    fn orders_with_null_placement() {
+        let order = TableOrder {
+            column: "created at".to_string(),
+            direction: SortDirection::Descending,
+            nulls: NullsOrder::First,
+        };
+        assert_eq!(
+            SqliteDialect.order_by_clause(&order),
+            r#"ORDER BY "created at" DESC NULLS FIRST"#
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Rows sharing the same values in a set of columns that should be unique
+//! together.
+
+use crate::db::dialect::Dialect;
+
+/// Statement returning every row of `schema.table` whose `columns` match
+/// another row's, NULLs included, grouped by those values.
+#[must_use]
+pub fn duplicate_rows_query(
+    dialect: &dyn Dialect,
+    schema: &str,
+    table: &str,
+    columns: &[String],
+) -> String {
+    let quoted = columns
+        .iter()
+        .map(|column| dialect.quote_ident(column))
+        .collect::<Vec<_>>();
+    let list = quoted.join(", ");
+    let matches = quoted
+        .iter()
+        .map(|column| format!("t.{column} IS NOT DISTINCT FROM d.{column}"))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let order = quoted
+        .iter()
+        .map(|column| format!("t.{column}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let table = dialect.qualified_table(schema, table);
+    format!(
+        "SELECT t.*\nFROM {table} t\nJOIN (\n    SELECT {list} FROM {table}\n    \
+         GROUP BY {list} HAVING COUNT(*) > 1\n) d ON {matches}\nORDER BY {order}"
+    )
+}
+
+/// Column names from comma-separated input, trimmed, with surrounding
+/// double quotes removed.
+#[must_use]
+pub fn parse_column_list(input: &str) -> Vec<String> {
+    input
+        .split(',')
+        .map(|name| {
+            let name = name.trim();
+            name.strip_prefix('"')
+                .and_then(|name| name.strip_suffix('"'))
+                .unwrap_or(name)
+                .to_string()
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::dialect::PostgresDialect;
+
+    #[test]
+    fn groups_rows_on_the_chosen_columns() {
+        let columns = parse_column_list(" email, \"Tenant Id\" ,");
+        assert_eq!(columns, ["email", "Tenant Id"]);
+        assert_eq!(
+            duplicate_rows_query(&PostgresDialect, "public", "users", &columns),
+            "SELECT t.*\n\
+             FROM \"public\".\"users\" t\n\
+             JOIN (\n    \
+             SELECT \"email\", \"Tenant Id\" FROM \"public\".\"users\"\n    \
+             GROUP BY \"email\", \"Tenant Id\" HAVING COUNT(*) > 1\n\
+             ) d ON t.\"email\" IS NOT DISTINCT FROM d.\"email\" AND \
+             t.\"Tenant Id\" IS NOT DISTINCT FROM d.\"Tenant Id\"\n\
+             ORDER BY t.\"email\", t.\"Tenant Id\""
+        );
+    }
+}
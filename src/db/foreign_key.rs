@@ -0,0 +1,233 @@
+//! Foreign keys of a table, declared or guessed from column names, and the
+//! query counting the rows whose parent is missing.
+
+use crate::db::dialect::Dialect;
+
+/// How far the database stands behind a [`ForeignKey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForeignKeyKind {
+    /// Declared and checked for every row.
+    Validated,
+    /// Declared `NOT VALID`: checked for new rows only.
+    NotValid,
+    /// Declared, but existing rows may not match, e.g. in `SQLite`.
+    Unvalidated,
+    /// Not declared; guessed from a `<parent>_id` column.
+    Convention,
+}
+
+impl ForeignKeyKind {
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Validated => "validated",
+            Self::NotValid => "NOT VALID",
+            Self::Unvalidated => "not validated",
+            Self::Convention => "by name",
+        }
+    }
+}
+
+/// Columns of a table referencing the key of a parent table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKey {
+    pub name: String,
+    pub kind: ForeignKeyKind,
+    pub columns: Vec<String>,
+    pub parent_schema: String,
+    pub parent_table: String,
+    pub parent_columns: Vec<String>,
+}
+
+impl ForeignKey {
+    /// Keys from catalog rows of `constraint, column, parent schema, parent
+    /// table, parent column`, ordered by constraint and column position.
+    #[must_use]
+    pub fn from_catalog_rows(
+        rows: impl IntoIterator<Item = ([String; 5], ForeignKeyKind)>,
+    ) -> Vec<Self> {
+        let mut keys: Vec<Self> = Vec::new();
+        for (
+            [name, column, parent_schema, parent_table, parent_column],
+            kind,
+        ) in rows
+        {
+            match keys.last_mut() {
+                Some(key) if key.name == name => {
+                    key.columns.push(column);
+                    key.parent_columns.push(parent_column);
+                }
+                _ => keys.push(Self {
+                    name,
+                    kind,
+                    columns: vec![column],
+                    parent_schema,
+                    parent_table,
+                    parent_columns: vec![parent_column],
+                }),
+            }
+        }
+        keys
+    }
+
+    /// Key guessed from `column` naming `parent`, referencing its primary
+    /// key `parent_column`.
+    #[must_use]
+    pub fn by_convention(
+        schema: &str,
+        column: String,
+        parent: String,
+        parent_column: String,
+    ) -> Self {
+        Self {
+            name: column.clone(),
+            kind: ForeignKeyKind::Convention,
+            columns: vec![column],
+            parent_schema: schema.to_string(),
+            parent_table: parent,
+            parent_columns: vec![parent_column],
+        }
+    }
+
+    /// e.g. `customer_id → public.customers.id`
+    #[must_use]
+    pub fn describe(&self) -> String {
+        format!(
+            "{} → {}.{}.{}",
+            self.columns.join(", "),
+            self.parent_schema,
+            self.parent_table,
+            self.parent_columns.join(", ")
+        )
+    }
+
+    /// Statement counting the rows of `schema.table` whose key columns are
+    /// all set but match no parent row.
+    #[must_use]
+    pub fn orphan_count_query(
+        &self,
+        dialect: &dyn Dialect,
+        schema: &str,
+        table: &str,
+    ) -> String {
+        let child = |column: &str| format!("c.{}", dialect.quote_ident(column));
+        let not_null = self
+            .columns
+            .iter()
+            .map(|column| format!("{} IS NOT NULL", child(column)))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let matches = self
+            .columns
+            .iter()
+            .zip(&self.parent_columns)
+            .map(|(column, parent)| {
+                format!("p.{} = {}", dialect.quote_ident(parent), child(column))
+            })
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        format!(
+            "SELECT COUNT(*) FROM {} c WHERE {not_null} AND NOT EXISTS \
+             (SELECT 1 FROM {} p WHERE {matches})",
+            dialect.qualified_table(schema, table),
+            dialect.qualified_table(&self.parent_schema, &self.parent_table)
+        )
+    }
+}
+
+/// `<parent>_id` columns no declared key covers, paired with the table of
+/// the schema named after `<parent>` (singular or plural).
+#[must_use]
+pub fn conventional_parents(
+    table: &str,
+    columns: &[String],
+    declared: &[ForeignKey],
+    tables: &[String],
+) -> Vec<(String, String)> {
+    columns
+        .iter()
+        .filter(|column| {
+            !declared.iter().any(|key| key.columns.contains(column))
+        })
+        .filter_map(|column| {
+            let prefix = column.strip_suffix("_id")?;
+            let parent = parent_names(prefix)
+                .into_iter()
+                .filter(|name| name != table)
+                .find(|name| tables.contains(name))?;
+            Some((column.clone(), parent))
+        })
+        .collect()
+}
+
+/// Table names a `<prefix>_id` column could refer to.
+fn parent_names(prefix: &str) -> Vec<String> {
+    let mut names = vec![
+        prefix.to_string(),
+        format!("{prefix}s"),
+        format!("{prefix}es"),
+    ];
+    if let Some(stem) = prefix.strip_suffix('y') {
+        names.push(format!("{stem}ies"));
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::dialect::PostgresDialect;
+
+    fn row(name: &str, column: &str, parent_column: &str) -> [String; 5] {
+        [name, column, "public", "accounts", parent_column].map(String::from)
+    }
+
+    #[test]
+    fn groups_catalog_rows_and_counts_orphans() {
+        let keys = ForeignKey::from_catalog_rows([
+            (
+                row("fk_account", "region", "region"),
+                ForeignKeyKind::NotValid,
+            ),
+            (row("fk_account", "number", "no"), ForeignKeyKind::NotValid),
+        ]);
+        assert_eq!(keys.len(), 1);
+        assert_eq!(
+            keys.first().map(ForeignKey::describe),
+            Some("region, number → public.accounts.region, no".to_string())
+        );
+        assert_eq!(
+            keys.first().map(|key| key.orphan_count_query(
+                &PostgresDialect,
+                "public",
+                "payments"
+            )),
+            Some(
+                "SELECT COUNT(*) FROM \"public\".\"payments\" c WHERE \
+                 c.\"region\" IS NOT NULL AND c.\"number\" IS NOT NULL AND \
+                 NOT EXISTS (SELECT 1 FROM \"public\".\"accounts\" p WHERE \
+                 p.\"region\" = c.\"region\" AND p.\"no\" = c.\"number\")"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn guesses_parents_from_id_columns() {
+        let declared = ForeignKey::from_catalog_rows([(
+            row("fk_account", "account_id", "id"),
+            ForeignKeyKind::Validated,
+        )]);
+        let columns = ["id", "account_id", "category_id", "box_id", "order_id"]
+            .map(String::from);
+        let tables =
+            ["orders", "accounts", "categories", "boxes"].map(String::from);
+        assert_eq!(
+            conventional_parents("orders", &columns, &declared, &tables),
+            [
+                ("category_id".to_string(), "categories".to_string()),
+                ("box_id".to_string(), "boxes".to_string()),
+            ]
+        );
+    }
+}
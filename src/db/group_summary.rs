@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use crate::sort::parse_number;
+
+/// Rows sharing one value of the grouped column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    pub value: String,
+    pub count: usize,
+    /// Sum of the summed column over the group's numeric values.
+    pub sum: Option<f64>,
+}
+
+/// Count the rows per value of column `column`, adding up column
+/// `sum_column` when given; values that aren't numbers, such as `NULL`,
+/// are left out of the sum. The largest groups come first, ties in value
+/// order.
+#[must_use]
+pub fn group_rows<'a>(
+    rows: impl IntoIterator<Item = &'a [String]>,
+    column: usize,
+    sum_column: Option<usize>,
+) -> Vec<Group> {
+    let mut groups: HashMap<&str, Group> = HashMap::new();
+    for row in rows {
+        let value = row.get(column).map_or("NULL", String::as_str);
+        let group = groups.entry(value).or_insert_with(|| Group {
+            value: value.to_string(),
+            count: 0,
+            sum: None,
+        });
+        group.count += 1;
+        if let Some(n) = sum_column
+            .and_then(|idx| row.get(idx))
+            .and_then(|cell| parse_number(cell))
+        {
+            group.sum = Some(group.sum.unwrap_or_default() + n);
+        }
+    }
+    let mut groups = groups.into_values().collect::<Vec<_>>();
+    groups.sort_by(|a, b| b.count.cmp(&a.count).then(a.value.cmp(&b.value)));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_and_sums_per_value() {
+        let rows = [
+            ["eu", "10"],
+            ["us", "5"],
+            ["eu", "2.5"],
+            ["apac", "1"],
+            ["us", "NULL"],
+            ["eu", "x"],
+        ]
+        .map(|row| row.map(String::from));
+        let groups = group_rows(rows.iter().map(|r| r.as_slice()), 0, Some(1));
+        assert_eq!(
+            groups,
+            [
+                Group {
+                    value: "eu".to_string(),
+                    count: 3,
+                    sum: Some(12.5),
+                },
+                Group {
+                    value: "us".to_string(),
+                    count: 2,
+                    sum: Some(5.0),
+                },
+                Group {
+                    value: "apac".to_string(),
+                    count: 1,
+                    sum: Some(1.0),
+                },
+            ]
+        );
+        let counts = group_rows(rows.iter().map(|r| r.as_slice()), 1, None);
+        assert_eq!(counts.len(), 6);
+        assert!(counts.iter().all(|g| g.count == 1 && g.sum.is_none()));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::sort::{parse_number, parse_temporal};
+
+/// Buckets a column's values are spread over.
+pub const HISTOGRAM_BUCKETS: usize = 10;
+
+/// Counts of a column's values in equal-width buckets between `min` and
+/// `max`. Timestamps are bucketed as seconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    pub counts: Vec<u64>,
+    pub temporal: bool,
+}
+
+impl Histogram {
+    /// Width of one bucket; ranges of a single value get width 1.
+    #[must_use]
+    pub fn bucket_width(min: f64, max: f64, buckets: usize) -> f64 {
+        #[allow(clippy::cast_precision_loss)]
+        let width = (max - min) / buckets.max(1) as f64;
+        if width > 0.0 { width } else { 1.0 }
+    }
+
+    /// Start of bucket `idx`.
+    #[must_use]
+    pub fn bucket_start(&self, idx: usize) -> f64 {
+        #[allow(clippy::cast_precision_loss)]
+        let idx = idx as f64;
+        idx.mul_add(
+            Self::bucket_width(self.min, self.max, self.counts.len()),
+            self.min,
+        )
+    }
+
+    /// Empty buckets for `min..=max`.
+    #[must_use]
+    pub fn empty(min: f64, max: f64, buckets: usize, temporal: bool) -> Self {
+        Self {
+            min,
+            max,
+            counts: vec![0; buckets.max(1)],
+            temporal,
+        }
+    }
+
+    /// Add `count` values to the bucket at `idx`; the maximum itself lands
+    /// one past the end and is folded into the last bucket.
+    pub fn add(&mut self, idx: usize, count: u64) {
+        let last = self.counts.len().saturating_sub(1);
+        if let Some(bucket) = self.counts.get_mut(idx.min(last)) {
+            *bucket += count;
+        }
+    }
+
+    /// Bucket cell values as shown in the grid; NULLs and values that don't
+    /// parse are skipped. Returns `None` when nothing is left.
+    pub fn from_values<'a>(
+        values: impl IntoIterator<Item = &'a str>,
+        temporal: bool,
+        buckets: usize,
+    ) -> Option<Self> {
+        let points = values
+            .into_iter()
+            .filter_map(|value| {
+                if temporal {
+                    #[allow(clippy::cast_precision_loss)]
+                    parse_temporal(value)
+                        .map(|dt| dt.and_utc().timestamp() as f64)
+                } else {
+                    parse_number(value)
+                }
+            })
+            .collect::<Vec<_>>();
+        let min = points.iter().copied().reduce(f64::min)?;
+        let max = points.iter().copied().reduce(f64::max)?;
+
+        let mut histogram = Self::empty(min, max, buckets, temporal);
+        let width = Self::bucket_width(min, max, buckets);
+        for point in points {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            histogram.add(((point - min) / width) as usize, 1);
+        }
+        Some(histogram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_values_and_folds_the_maximum() {
+        let histogram =
+            Histogram::from_values(["0", "1", "NULL", "5", "10"], false, 5)
+                .unwrap();
+        assert_eq!(histogram.counts, [2, 0, 1, 0, 1]);
+        assert!((histogram.bucket_start(1) - 2.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn buckets_timestamps_by_epoch_seconds() {
+        let histogram = Histogram::from_values(
+            ["2024-01-01", "2024-01-02 12:00:00", "2024-01-03"],
+            true,
+            2,
+        )
+        .unwrap();
+        assert_eq!(histogram.counts, [1, 2]);
+        assert!(Histogram::from_values(["NULL"], true, 2).is_none());
+    }
+}
@@ -0,0 +1,45 @@
+//! A column's loaded values as a SQL `IN` list, to paste into a query.
+
+use std::collections::HashSet;
+
+use crate::db::dialect::Dialect;
+
+/// How the grid shows a NULL.
+const NULL_TEXT: &str = "NULL";
+
+/// The distinct values of a column as an `IN` list such as `('a','b')`, in
+/// the order first seen, and how many it holds. Every value is a string
+/// literal, which both backends convert to the column's type; NULLs are
+/// left out, as `IN` never matches them. `None` when no value is left.
+#[must_use]
+pub fn in_list<'a>(
+    dialect: &dyn Dialect,
+    values: impl IntoIterator<Item = &'a str>,
+) -> Option<(String, usize)> {
+    let mut seen = HashSet::new();
+    let literals = values
+        .into_iter()
+        .filter(|value| *value != NULL_TEXT && seen.insert(*value))
+        .map(|value| dialect.quote_literal(value))
+        .collect::<Vec<_>>();
+    if literals.is_empty() {
+        return None;
+    }
+    Some((format!("({})", literals.join(",")), literals.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::dialect::SqliteDialect;
+
+    #[test]
+    fn lists_distinct_values_as_literals() {
+        let values = ["a", "NULL", "it's", "a", "7"];
+        assert_eq!(
+            in_list(&SqliteDialect, values),
+            Some(("('a','it''s','7')".to_string(), 3))
+        );
+        assert_eq!(in_list(&SqliteDialect, ["NULL"]), None);
+    }
+}
@@ -0,0 +1,239 @@
+//! Candidate indexes for a query, guessed from the sequential scans with
+//! filters in its plan. A heuristic: the planner may still prefer the scan,
+//! and an index may exist already that it chose not to use.
+
+use serde_json::Value;
+use sqlparser::{
+    ast::{BinaryOperator, Expr},
+    dialect::PostgreSqlDialect,
+    parser::Parser,
+};
+
+use crate::db::dialect::Dialect;
+
+/// An index that could replace a filtered sequential scan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexSuggestion {
+    pub schema: Option<String>,
+    pub table: String,
+    /// Equality columns first, then at most one range column.
+    pub columns: Vec<String>,
+    /// Filter of the scan, as the planner printed it.
+    pub filter: String,
+    /// Rows the planner expects the scan to return.
+    pub rows: Option<f64>,
+}
+
+impl IndexSuggestion {
+    #[must_use]
+    pub fn create_statement(&self, dialect: &dyn Dialect) -> String {
+        let table = self.schema.as_deref().map_or_else(
+            || dialect.quote_ident(&self.table),
+            |schema| dialect.qualified_table(schema, &self.table),
+        );
+        let columns = self
+            .columns
+            .iter()
+            .map(|column| dialect.quote_ident(column))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("CREATE INDEX ON {table} ({columns});")
+    }
+
+    /// [`Self::create_statement`] preceded by a comment with the scan it
+    /// was guessed from, for reviewing it in the editor.
+    #[must_use]
+    pub fn annotated_statement(&self, dialect: &dyn Dialect) -> String {
+        let rows = self
+            .rows
+            .map_or_else(String::new, |rows| format!(", ~{rows} rows"));
+        format!(
+            "-- Heuristic: sequential scan of {} filtering on\n\
+             -- {}{rows}\n{}",
+            self.table,
+            self.filter,
+            self.create_statement(dialect)
+        )
+    }
+}
+
+/// Suggestions for the output of `EXPLAIN (VERBOSE, FORMAT JSON)`, one per
+/// table and column list.
+#[must_use]
+pub fn suggest_indexes(plan: &str) -> Vec<IndexSuggestion> {
+    let mut suggestions = Vec::new();
+    if let Ok(plan) = serde_json::from_str::<Value>(plan)
+        && let Some(root) = plan.get(0).and_then(|plan| plan.get("Plan"))
+    {
+        collect(root, &mut suggestions);
+    }
+    suggestions
+}
+
+fn collect(node: &Value, suggestions: &mut Vec<IndexSuggestion>) {
+    let text = |key: &str| node.get(key).and_then(Value::as_str);
+    if text("Node Type") == Some("Seq Scan")
+        && let Some((table, filter)) = text("Relation Name").zip(text("Filter"))
+    {
+        let columns = filter_columns(filter, text("Alias").unwrap_or(table));
+        let suggestion = IndexSuggestion {
+            schema: text("Schema").map(str::to_string),
+            table: table.to_string(),
+            columns,
+            filter: filter.to_string(),
+            rows: node.get("Plan Rows").and_then(Value::as_f64),
+        };
+        if !suggestion.columns.is_empty()
+            && !suggestions.iter().any(|s| {
+                (&s.schema, &s.table, &s.columns)
+                    == (
+                        &suggestion.schema,
+                        &suggestion.table,
+                        &suggestion.columns,
+                    )
+            })
+        {
+            suggestions.push(suggestion);
+        }
+    }
+    for child in node
+        .get("Plans")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        collect(child, suggestions);
+    }
+}
+
+/// Columns of the scanned table compared with a value in the `AND`ed parts
+/// of `filter`: the equality ones, then the first range one. Parts the
+/// parser can't read, `OR`s and comparisons between columns are skipped.
+#[allow(clippy::wildcard_enum_match_arm)]
+fn filter_columns(filter: &str, alias: &str) -> Vec<String> {
+    let Ok(expr) = Parser::new(&PostgreSqlDialect {})
+        .try_with_sql(filter)
+        .and_then(|mut parser| parser.parse_expr())
+    else {
+        return Vec::new();
+    };
+    let mut conditions = Vec::new();
+    conjuncts(&expr, &mut conditions);
+
+    let mut equality = Vec::new();
+    let mut range = Vec::new();
+    for condition in conditions {
+        let (left, op, right) = match condition {
+            Expr::BinaryOp { left, op, right } => (left, op, right),
+            Expr::AnyOp {
+                left,
+                compare_op: BinaryOperator::Eq,
+                right,
+                ..
+            } => (left, &BinaryOperator::Eq, right),
+            _ => continue,
+        };
+        let (column, value) =
+            match (column_of(left, alias), column_of(right, alias)) {
+                (Some(column), None) => (column, right),
+                (None, Some(column)) => (column, left),
+                _ => continue,
+            };
+        if is_column(value) {
+            continue;
+        }
+        let columns = match op {
+            BinaryOperator::Eq => &mut equality,
+            BinaryOperator::Lt
+            | BinaryOperator::LtEq
+            | BinaryOperator::Gt
+            | BinaryOperator::GtEq => &mut range,
+            _ => continue,
+        };
+        if !columns.contains(&column) {
+            columns.push(column);
+        }
+    }
+    range.retain(|column| !equality.contains(column));
+    equality.extend(range.into_iter().take(1));
+    equality
+}
+
+/// The parts of `expr` joined by `AND`.
+#[allow(clippy::wildcard_enum_match_arm)]
+fn conjuncts<'a>(expr: &'a Expr, parts: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::Nested(inner) => conjuncts(inner, parts),
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            conjuncts(left, parts);
+            conjuncts(right, parts);
+        }
+        _ => parts.push(expr),
+    }
+}
+
+/// `expr` without parentheses and casts around it.
+#[allow(clippy::wildcard_enum_match_arm)]
+fn unwrap(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Nested(inner) | Expr::Cast { expr: inner, .. } => unwrap(inner),
+        _ => expr,
+    }
+}
+
+fn is_column(expr: &Expr) -> bool {
+    matches!(
+        unwrap(expr),
+        Expr::Identifier(_) | Expr::CompoundIdentifier(_)
+    )
+}
+
+/// Name of the column `expr` refers to, when it's one of the scanned
+/// table's (unqualified or qualified with `alias`).
+#[allow(clippy::wildcard_enum_match_arm)]
+fn column_of(expr: &Expr, alias: &str) -> Option<String> {
+    match unwrap(expr) {
+        Expr::Identifier(column) => Some(column.value.clone()),
+        Expr::CompoundIdentifier(parts) => match parts.as_slice() {
+            [qualifier, column] if qualifier.value == alias => {
+                Some(column.value.clone())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::dialect::PostgresDialect;
+
+    const PLAN: &str = r#"[{"Plan": {"Node Type": "Nested Loop",
+        "Plans": [
+            {"Node Type": "Seq Scan", "Relation Name": "orders",
+             "Schema": "shop", "Alias": "o", "Plan Rows": 12,
+             "Filter": "((o.status = ANY ('{a,b}'::text[])) AND (o.total > '5'::numeric) AND (o.customer_id = 4) AND ((o.created_at)::date < now()))"},
+            {"Node Type": "Seq Scan", "Relation Name": "customers",
+             "Schema": "shop", "Alias": "c",
+             "Filter": "((c.id = o.customer_id) OR (c.vip IS TRUE))"}
+        ]}}]"#;
+
+    #[test]
+    fn suggests_equality_then_range_columns_for_filtered_scans() {
+        let suggestions = suggest_indexes(PLAN);
+        assert_eq!(
+            suggestions
+                .iter()
+                .map(|s| s.create_statement(&PostgresDialect))
+                .collect::<Vec<_>>(),
+            ["CREATE INDEX ON \"shop\".\"orders\" \
+                 (\"status\", \"customer_id\", \"total\");"]
+        );
+        assert_eq!(suggestions.first().and_then(|s| s.rows), Some(12.0));
+    }
+}
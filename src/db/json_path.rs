@@ -0,0 +1,133 @@
+//! Pulling a value out of a JSON column with a path or an operator chain,
+//! shown as a derived column next to the rows' own.
+
+use crate::db::dialect::Dialect;
+
+/// What to extract from each JSON value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonExtraction {
+    /// SQL/JSON path such as `$.customer.name` or `$.items[0].sku`
+    Path(String),
+    /// `->` / `->>` operators as typed, such as `->'items'->0->>'sku'`
+    Operators(String),
+}
+
+impl JsonExtraction {
+    /// Read prompt input: operators when it starts with `->` or `#>`,
+    /// otherwise a path whose leading `$` may be left out
+    /// (`customer.name` is `$.customer.name`).
+    #[must_use]
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if input.is_empty() {
+            return None;
+        }
+        if input.starts_with("->") || input.starts_with("#>") {
+            return Some(Self::Operators(input.to_string()));
+        }
+        let path = if input.starts_with('$') {
+            input.to_string()
+        } else if input.starts_with('[') {
+            format!("${input}")
+        } else {
+            format!("$.{}", input.trim_start_matches('.'))
+        };
+        Some(Self::Path(path))
+    }
+
+    /// The expression applied to `column` (quoted), in the backend's
+    /// dialect.
+    #[must_use]
+    pub fn expr(&self, dialect: &dyn Dialect, column: &str) -> String {
+        match self {
+            Self::Path(path) => {
+                dialect.json_path_expr(column, &dialect.quote_literal(path))
+            }
+            Self::Operators(operators) => {
+                dialect.json_operators_expr(column, operators)
+            }
+        }
+    }
+
+    /// Name of the derived column, e.g. `payload $.id` or `payload->>'id'`.
+    #[must_use]
+    pub fn label(&self, column: &str) -> String {
+        match self {
+            Self::Path(path) => format!("{column} {path}"),
+            Self::Operators(operators) => format!("{column}{operators}"),
+        }
+    }
+}
+
+/// Statement returning every row of `source` (a table or a parenthesised
+/// query with an alias) with `column` run through `extraction` as an extra
+/// last column.
+#[must_use]
+pub fn json_extraction_query(
+    dialect: &dyn Dialect,
+    source: &str,
+    column: &str,
+    extraction: &JsonExtraction,
+) -> String {
+    format!(
+        "SELECT *, {} AS {}\nFROM {source}",
+        extraction.expr(dialect, &dialect.quote_ident(column)),
+        dialect.quote_ident(&extraction.label(column))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::dialect::{PostgresDialect, SqliteDialect};
+
+    #[test]
+    fn reads_paths_and_operator_chains() {
+        let path = |p: &str| Some(JsonExtraction::Path(p.to_string()));
+        assert_eq!(JsonExtraction::parse("$.a.b"), path("$.a.b"));
+        assert_eq!(
+            JsonExtraction::parse(" customer.name "),
+            path("$.customer.name")
+        );
+        assert_eq!(JsonExtraction::parse(".id"), path("$.id"));
+        assert_eq!(JsonExtraction::parse("[0].sku"), path("$[0].sku"));
+        assert_eq!(
+            JsonExtraction::parse("->'items'->>0"),
+            Some(JsonExtraction::Operators("->'items'->>0".to_string()))
+        );
+        assert_eq!(JsonExtraction::parse("  "), None);
+    }
+
+    #[test]
+    fn adds_the_extracted_value_as_a_column() {
+        let path = JsonExtraction::parse("customer.name").unwrap();
+        assert_eq!(
+            json_extraction_query(
+                &PostgresDialect,
+                "\"public\".\"orders\"",
+                "payload",
+                &path
+            ),
+            "SELECT *, jsonb_path_query_first(\"payload\"::jsonb, '$.customer.name') #>> '{}' AS \"payload $.customer.name\"\nFROM \"public\".\"orders\""
+        );
+        assert_eq!(
+            json_extraction_query(
+                &SqliteDialect,
+                "\"main\".\"orders\"",
+                "payload",
+                &path
+            ),
+            "SELECT *, json_extract(\"payload\", '$.customer.name') AS \"payload $.customer.name\"\nFROM \"main\".\"orders\""
+        );
+
+        let operators = JsonExtraction::parse("->'items'->>0").unwrap();
+        assert_eq!(
+            operators.expr(&PostgresDialect, "\"payload\""),
+            "\"payload\"::jsonb->'items'->>0"
+        );
+        assert_eq!(
+            operators.expr(&SqliteDialect, "\"payload\""),
+            "\"payload\"->'items'->>0"
+        );
+    }
+}
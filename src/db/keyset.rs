@@ -0,0 +1,174 @@
+//! Keyset ("seek") paging: pages of a table in primary key order, each
+//! found from a key value instead of an `OFFSET`, so a page deep into a
+//! huge table costs as little to read as the first.
+
+use crate::db::dialect::Dialect;
+
+/// Where a page starts, relative to a primary key value (one text value
+/// per key column).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySeek {
+    /// The first rows of the table
+    Start,
+    /// Rows whose key is greater
+    After(Vec<String>),
+    /// Rows whose key is smaller, the closest ones
+    Before(Vec<String>),
+    /// Rows from the key on, for jumping to a value
+    From(Vec<String>),
+}
+
+impl KeySeek {
+    /// Whether rows are likely before and after a page read from here;
+    /// `full_page` when it has as many rows as were asked for.
+    #[must_use]
+    pub const fn more_rows(&self, full_page: bool) -> (bool, bool) {
+        match self {
+            Self::Start => (false, full_page),
+            Self::After(_) | Self::From(_) => (true, full_page),
+            Self::Before(_) => (full_page, true),
+        }
+    }
+}
+
+/// A page to read by key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeysetPage {
+    /// Primary key columns, in key order
+    pub key_columns: Vec<String>,
+    pub seek: KeySeek,
+}
+
+impl KeysetPage {
+    /// `WHERE` clause selecting the page's rows; empty for the first page.
+    /// Values are quoted as text literals, which both backends compare as
+    /// the key column's type.
+    #[must_use]
+    pub fn where_clause(&self, dialect: &dyn Dialect) -> String {
+        let (operator, key) = match &self.seek {
+            KeySeek::Start => return String::new(),
+            KeySeek::After(key) => (">", key),
+            KeySeek::Before(key) => ("<", key),
+            KeySeek::From(key) => (">=", key),
+        };
+        let columns = self
+            .key_columns
+            .iter()
+            .map(|column| dialect.quote_ident(column))
+            .collect::<Vec<_>>();
+        let values = key
+            .iter()
+            .map(|value| dialect.quote_literal(value))
+            .collect::<Vec<_>>();
+        if let ([column], [value]) = (columns.as_slice(), values.as_slice()) {
+            return format!("WHERE {column} {operator} {value}");
+        }
+        // Row values compare column by column, like the index does
+        format!(
+            "WHERE ({}) {operator} ({})",
+            columns.join(", "),
+            values.join(", ")
+        )
+    }
+
+    /// `ORDER BY` the key; descending when reading backwards.
+    #[must_use]
+    pub fn order_by_clause(&self, dialect: &dyn Dialect) -> String {
+        let direction = if self.reads_backwards() { "DESC" } else { "ASC" };
+        let columns = self
+            .key_columns
+            .iter()
+            .map(|column| {
+                format!("{} {direction}", dialect.quote_ident(column))
+            })
+            .collect::<Vec<_>>();
+        format!("ORDER BY {}", columns.join(", "))
+    }
+
+    /// Whether rows come back last first and need reversing into key
+    /// order.
+    #[must_use]
+    pub const fn reads_backwards(&self) -> bool {
+        matches!(self.seek, KeySeek::Before(_))
+    }
+}
+
+/// The key of `row`, whose columns are named by `column_names`; `None`
+/// when a key column isn't among them.
+#[must_use]
+pub fn row_key(
+    key_columns: &[String],
+    column_names: &[String],
+    row: &[String],
+) -> Option<Vec<String>> {
+    key_columns
+        .iter()
+        .map(|key| {
+            let idx = column_names.iter().position(|name| name == key)?;
+            row.get(idx).cloned()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::dialect::PostgresDialect;
+
+    fn page(key_columns: &[&str], seek: KeySeek) -> KeysetPage {
+        KeysetPage {
+            key_columns: key_columns.iter().map(ToString::to_string).collect(),
+            seek,
+        }
+    }
+
+    fn key(values: &[&str]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn seeks_from_the_key() {
+        let dialect = &PostgresDialect;
+        let start = page(&["id"], KeySeek::Start);
+        assert_eq!(start.where_clause(dialect), "");
+        assert_eq!(start.order_by_clause(dialect), "ORDER BY \"id\" ASC");
+
+        let after = page(&["id"], KeySeek::After(key(&["1200"])));
+        assert_eq!(after.where_clause(dialect), "WHERE \"id\" > '1200'");
+
+        let before = page(&["tenant", "id"], KeySeek::Before(key(&["a", "7"])));
+        assert_eq!(
+            before.where_clause(dialect),
+            "WHERE (\"tenant\", \"id\") < ('a', '7')"
+        );
+        assert_eq!(
+            before.order_by_clause(dialect),
+            "ORDER BY \"tenant\" DESC, \"id\" DESC"
+        );
+        assert!(before.reads_backwards());
+
+        let from = page(&["id"], KeySeek::From(key(&["it's"])));
+        assert_eq!(from.where_clause(dialect), "WHERE \"id\" >= 'it''s'");
+    }
+
+    #[test]
+    fn knows_which_way_more_rows_are() {
+        assert_eq!(KeySeek::Start.more_rows(true), (false, true));
+        assert_eq!(KeySeek::After(key(&["1"])).more_rows(false), (true, false));
+        assert_eq!(
+            KeySeek::Before(key(&["1"])).more_rows(false),
+            (false, true)
+        );
+    }
+
+    #[test]
+    fn reads_the_key_of_a_row() {
+        let columns = key(&["name", "id", "tenant"]);
+        let row = key(&["Ann", "7", "a"]);
+        assert_eq!(
+            row_key(&key(&["tenant", "id"]), &columns, &row),
+            Some(key(&["a", "7"]))
+        );
+        assert_eq!(row_key(&key(&["email"]), &columns, &row), None);
+    }
+}
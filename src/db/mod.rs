@@ -1,10 +1,78 @@
+pub mod backup;
+pub mod catalog;
+pub mod column_stats;
 pub mod connection;
+pub mod connection_column;
+pub mod ddl;
+pub mod dialect;
+pub mod duplicates;
+pub mod foreign_key;
+pub mod group_summary;
+pub mod histogram;
+pub mod in_list;
+pub mod index_advice;
+pub mod json_path;
+pub mod keyset;
+pub mod pool;
 pub mod postgres;
+pub mod query_estimate;
+pub mod query_plan;
+pub mod recent_table;
+pub mod result_origin;
+pub mod row_diff;
+pub mod row_note;
+pub mod saved_view;
 pub mod sqlite;
+pub mod table_compare;
+pub mod text_search;
+pub mod time_box;
+pub mod top_query;
 
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::OnceLock};
 
 use color_eyre::{Result, eyre};
+use ratatui::style::Color;
+
+use crate::{
+    db::{
+        column_stats::ColumnStats,
+        ddl::SchemaDdl,
+        dialect::Dialect,
+        foreign_key::{ForeignKey, conventional_parents},
+        histogram::Histogram,
+        index_advice::{IndexSuggestion, suggest_indexes},
+        keyset::KeysetPage,
+        query_estimate::QueryEstimate,
+        query_plan::QueryPlan,
+        result_origin::{ColumnSource, ResultOrigin, single_source_table},
+        table_compare::TableChecksums,
+        time_box::{TimeBox, TimeBoxedRows},
+        top_query::{TopQuery, TopQueryOrder},
+    },
+    sort::{TableOrder, parse_number},
+};
+
+/// Transaction state of the session, as psql's prompt shows it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// No transaction open; each statement commits on its own
+    Idle,
+    /// Inside `BEGIN` … `COMMIT`
+    InTransaction,
+    /// A statement failed inside the transaction; only `ROLLBACK` works
+    Failed,
+}
+
+impl TransactionStatus {
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::InTransaction => "in transaction",
+            Self::Failed => "aborted, ROLLBACK to continue",
+        }
+    }
+}
 
 /// Stable-enough row locator for `UPDATE` when the table has no primary key.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -26,6 +94,25 @@ pub struct TableDataPage {
     pub rows: Vec<Vec<String>>,
     pub column_names: Vec<String>,
     pub row_ids: Vec<Option<DbRowId>>,
+    /// The statement the page was read with, paging values filled in.
+    pub sql: String,
+}
+
+/// Why opening a connection failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectError {
+    /// The driver's message
+    pub message: String,
+    /// Likely to pass on a later attempt: the server refused, dropped or
+    /// timed out the connection, or is starting up, shutting down or has
+    /// no primary yet while failing over.
+    pub transient: bool,
+}
+
+impl std::fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
 }
 
 pub trait TableData {
@@ -43,16 +130,80 @@ pub trait TableData {
     fn is_draft_row(&self) -> bool {
         false
     }
+
+    /// Colour of one cell, for columns that encode a category.
+    fn col_color(&self, _column: usize) -> Option<Color> {
+        None
+    }
+
+    /// Value the filter matches `key:value` terms against, for keys the
+    /// row knows; other terms are matched as plain text.
+    fn filter_field(&self, _key: &str) -> Option<String> {
+        None
+    }
+
+    /// Identifies the row when the rows are reloaded or filtered, to keep it
+    /// selected. The first column by default.
+    fn selection_key(&self) -> String {
+        self.col(0)
+    }
 }
 
 #[async_trait::async_trait]
 pub trait Database: Send + Sync {
-    async fn test(&self) -> bool;
+    /// SQL dialect for statements generated against this backend.
+    fn dialect(&self) -> &'static dyn Dialect;
+
+    /// Open a connection, returning the driver's error on failure.
+    async fn check_connection(&self) -> Result<(), ConnectError>;
+
+    async fn test(&self) -> bool {
+        self.check_connection().await.is_ok()
+    }
+
+    /// Transaction state of the session the SQL executor's next statement
+    /// runs in. `None` when the backend can't tell.
+    async fn transaction_status(
+        &self,
+    ) -> Result<Option<TransactionStatus>, Box<dyn std::error::Error>> {
+        Ok(None)
+    }
+
     async fn execute_sql(
         &self,
         sql: &str,
     ) -> Result<Vec<TableRow>, Box<dyn std::error::Error>>;
 
+    /// Run SQL the user typed on the session the SQL executor uses, so it
+    /// takes part in the transaction open there. Backends without a
+    /// separate session run it like [`Self::execute_sql`].
+    async fn execute_in_session(
+        &self,
+        sql: &str,
+    ) -> Result<Vec<TableRow>, Box<dyn std::error::Error>> {
+        self.execute_sql(sql).await
+    }
+
+    /// Run a statement that returns no rows, such as `SAVEPOINT`, on the
+    /// session in a single round trip.
+    async fn batch_execute_in_session(
+        &self,
+        sql: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.execute_in_session(sql).await.map(drop)
+    }
+
+    /// Run a query of the SQL executor, reading its rows only until
+    /// `time_box` runs out; the rows read by then come back with the limit
+    /// that stopped them.
+    async fn execute_sql_within(
+        &self,
+        sql: &str,
+        time_box: TimeBox,
+    ) -> Result<TimeBoxedRows, Box<dyn std::error::Error>> {
+        self.execute_sql(sql).await.map(|rows| time_box.cut(rows))
+    }
+
     async fn get_schemas(
         &self,
     ) -> Result<Vec<Schema>, Box<dyn std::error::Error>>;
@@ -69,10 +220,15 @@ pub trait Database: Send + Sync {
     ) -> Result<Vec<Column>, Box<dyn std::error::Error>>;
 
     /// Returns up to `limit` rows starting at `offset` (0-based), plus column names and row locators.
+    /// Rows follow `order` when given, else the backend's natural order.
+    /// A `keyset` page replaces `order` with the key's and is read from its
+    /// key, with `offset` 0; its rows come back in key order either way.
     async fn get_table_data_page(
         &self,
         schema_name: &str,
         table_name: &str,
+        order: Option<&TableOrder>,
+        keyset: Option<&KeysetPage>,
         offset: u64,
         limit: u32,
     ) -> Result<TableDataPage, Box<dyn std::error::Error>>;
@@ -84,6 +240,67 @@ pub trait Database: Send + Sync {
         table_name: &str,
     ) -> Result<Vec<String>, Box<dyn std::error::Error>>;
 
+    /// Declared foreign keys of a table, by constraint name.
+    async fn get_foreign_keys(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<Vec<ForeignKey>, Box<dyn std::error::Error>>;
+
+    /// Foreign keys of a table that aren't declared but its `<parent>_id`
+    /// columns suggest, referencing a single-column primary key.
+    async fn guess_foreign_keys(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        declared: &[ForeignKey],
+    ) -> Result<Vec<ForeignKey>, Box<dyn std::error::Error>> {
+        let columns = self
+            .get_columns(schema_name, table_name)
+            .await?
+            .into_iter()
+            .map(|column| column.name)
+            .collect::<Vec<_>>();
+        let tables = self
+            .get_tables(schema_name)
+            .await?
+            .into_iter()
+            .map(|table| table.name)
+            .collect::<Vec<_>>();
+        let mut keys = Vec::new();
+        for (column, parent) in
+            conventional_parents(table_name, &columns, declared, &tables)
+        {
+            let key =
+                self.get_primary_key_columns(schema_name, &parent).await?;
+            if let [parent_column] = key.as_slice() {
+                keys.push(ForeignKey::by_convention(
+                    schema_name,
+                    column,
+                    parent,
+                    parent_column.clone(),
+                ));
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Rows of a table whose `key` columns are set but have no parent row.
+    async fn count_orphans(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        key: &ForeignKey,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let sql =
+            key.orphan_count_query(self.dialect(), schema_name, table_name);
+        let rows = self.execute_sql(&sql).await?;
+        rows.first()
+            .and_then(|row| row.values.first())
+            .and_then(|count| count.parse().ok())
+            .ok_or_else(|| "Orphan count query returned no count".into())
+    }
+
     /// Update a single cell. Uses `primary_key` for the `WHERE` clause when non-empty; otherwise
     /// `row_id_fallback` (`rowid` / `ctid`) when present.
     async fn update_table_cell(
@@ -121,9 +338,246 @@ pub trait Database: Send + Sync {
         table_name: &str,
     ) -> Result<u64, Box<dyn std::error::Error>>;
 
+    /// Count, sum, mean, min and max of a column over the whole table.
+    async fn get_column_stats(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+    ) -> Result<ColumnStats, Box<dyn std::error::Error>> {
+        let dialect = self.dialect();
+        let column = dialect.quote_ident(column_name);
+        let sql = format!(
+            "SELECT COUNT({column}), COUNT(*), SUM({column}), AVG({column}), \
+             MIN({column}), MAX({column}) FROM {}",
+            dialect.qualified_table(schema_name, table_name)
+        );
+        let rows = self.execute_sql(&sql).await?;
+        let row = rows.first().ok_or("Aggregate query returned no rows")?;
+        Ok(ColumnStats::from_aggregate_row(&row.values))
+    }
+
+    /// Histogram of a numeric or date/time column over the whole table,
+    /// bucketed by the server. `None` when the column has no values.
+    async fn get_column_histogram(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+        temporal: bool,
+        buckets: usize,
+    ) -> Result<Option<Histogram>, Box<dyn std::error::Error>> {
+        let dialect = self.dialect();
+        let value = dialect.numeric_expr(column_name, temporal);
+        let from = format!(
+            "FROM {} WHERE {} IS NOT NULL",
+            dialect.qualified_table(schema_name, table_name),
+            dialect.quote_ident(column_name)
+        );
+
+        let range = self
+            .execute_sql(&format!("SELECT MIN({value}), MAX({value}) {from}"))
+            .await?;
+        let bound = |idx: usize| {
+            range
+                .first()
+                .and_then(|row| row.values.get(idx))
+                .and_then(|v| parse_number(v))
+        };
+        let (Some(min), Some(max)) = (bound(0), bound(1)) else {
+            return Ok(None);
+        };
+
+        let mut histogram = Histogram::empty(min, max, buckets, temporal);
+        let width = Histogram::bucket_width(min, max, buckets);
+        let bucket = dialect.bucket_expr(
+            &value,
+            &format!("({min})"),
+            &width.to_string(),
+        );
+        let rows = self
+            .execute_sql(&format!(
+                "SELECT bucket, COUNT(*) FROM (SELECT {bucket} AS bucket {from}) buckets GROUP BY bucket"
+            ))
+            .await?;
+        for row in rows {
+            let mut values = row.values.iter().map(|v| parse_number(v));
+            if let (Some(Some(idx)), Some(Some(count))) =
+                (values.next(), values.next())
+            {
+                #[allow(
+                    clippy::cast_possible_truncation,
+                    clippy::cast_sign_loss
+                )]
+                histogram.add(idx.max(0.0) as usize, count as u64);
+            }
+        }
+        Ok(Some(histogram))
+    }
+
+    /// Row count and a checksum per column over the whole table, for
+    /// comparing it with a copy.
+    async fn get_table_checksums(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<TableChecksums, Box<dyn std::error::Error>> {
+        let names = self
+            .get_columns(schema_name, table_name)
+            .await?
+            .into_iter()
+            .map(|column| column.name)
+            .collect::<Vec<_>>();
+        let dialect = self.dialect();
+        let aggregates = std::iter::once("COUNT(*)".to_string())
+            .chain(names.iter().flat_map(|name| {
+                [
+                    format!("COUNT({})", dialect.quote_ident(name)),
+                    dialect.checksum_expr(name),
+                ]
+            }))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            "SELECT {aggregates} FROM {}",
+            dialect.qualified_table(schema_name, table_name)
+        );
+        let rows = self.execute_sql(&sql).await?;
+        let row = rows.first().ok_or("Aggregate query returned no rows")?;
+        Ok(TableChecksums::from_aggregate_row(names, &row.values))
+    }
+
+    /// The planner's estimate for `sql`, without running it. `None` when
+    /// the backend has no cost-based `EXPLAIN`.
+    async fn estimate_query(
+        &self,
+        sql: &str,
+    ) -> Result<Option<QueryEstimate>, Box<dyn std::error::Error>> {
+        let Some(explain) = self.dialect().explain_estimate_query(sql) else {
+            return Ok(None);
+        };
+        let rows = self.execute_sql(&explain).await?;
+        Ok(rows
+            .first()
+            .and_then(|row| row.values.first())
+            .and_then(|plan| QueryEstimate::from_json_plan(plan)))
+    }
+
+    /// The single table a result set with these column sources was read
+    /// from. `None` for results of several tables, or when the backend
+    /// doesn't describe where result columns come from.
+    async fn resolve_result_origin(
+        &self,
+        sources: &[Option<ColumnSource>],
+    ) -> Result<Option<ResultOrigin>, Box<dyn std::error::Error>> {
+        let Some(sql) = single_source_table(sources)
+            .and_then(|oid| self.dialect().table_attributes_query(oid))
+        else {
+            return Ok(None);
+        };
+        let rows = self.execute_sql(&sql).await?;
+        Ok(ResultOrigin::from_catalog_rows(
+            sources,
+            rows.into_iter()
+                .filter_map(|row| <[String; 4]>::try_from(row.values).ok()),
+        ))
+    }
+
+    /// Indexes that might replace the filtered sequential scans in the plan
+    /// for `sql`. `None` when the backend has no plan to read them from.
+    async fn suggest_indexes(
+        &self,
+        sql: &str,
+    ) -> Result<Option<Vec<IndexSuggestion>>, Box<dyn std::error::Error>> {
+        let Some(explain) = self.dialect().explain_verbose_query(sql) else {
+            return Ok(None);
+        };
+        let rows = self.execute_sql(&explain).await?;
+        Ok(Some(
+            rows.first()
+                .and_then(|row| row.values.first())
+                .map(|plan| suggest_indexes(plan))
+                .unwrap_or_default(),
+        ))
+    }
+
+    /// Run `sql` under `EXPLAIN ANALYZE` and read the executed plan. `None`
+    /// when the backend doesn't report per-node timings.
+    async fn explain_analyze(
+        &self,
+        sql: &str,
+    ) -> Result<Option<QueryPlan>, Box<dyn std::error::Error>> {
+        let Some(explain) = self.dialect().explain_analyze_query(sql) else {
+            return Ok(None);
+        };
+        let rows = self.execute_sql(&explain).await?;
+        Ok(rows
+            .first()
+            .and_then(|row| row.values.first())
+            .and_then(|plan| QueryPlan::from_json_plan(plan)))
+    }
+
+    /// The effective search path, e.g. `"$user", public`. `None` when the
+    /// backend has no search path.
+    async fn get_search_path(
+        &self,
+    ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let Some(query) = self.dialect().search_path_query() else {
+            return Ok(None);
+        };
+        let rows = self.execute_sql(query).await?;
+        Ok(rows.first().and_then(|row| row.values.first()).cloned())
+    }
+
+    /// A handle on the same target whose connections use `search_path`, or
+    /// the server's setting for `None`. The SQL executor's session is kept
+    /// and changed in place, so a transaction open there stays open. `None`
+    /// when the backend has no search path.
+    async fn with_search_path(
+        &self,
+        _search_path: Option<&str>,
+    ) -> Result<Option<Box<dyn Database>>, Box<dyn std::error::Error>> {
+        Ok(None)
+    }
+
+    /// The current database's most expensive statements according to
+    /// `pg_stat_statements`. `None` when the backend doesn't have it or the
+    /// extension isn't installed.
+    async fn get_top_queries(
+        &self,
+        order: TopQueryOrder,
+    ) -> Result<Option<Vec<TopQuery>>, Box<dyn std::error::Error>> {
+        let dialect = self.dialect();
+        let Some(schema_query) = dialect.stat_statements_schema_query() else {
+            return Ok(None);
+        };
+        let rows = self.execute_sql(schema_query).await?;
+        let Some(query) = rows
+            .first()
+            .and_then(|row| row.values.first())
+            .and_then(|schema| dialect.top_queries_query(schema, order))
+        else {
+            return Ok(None);
+        };
+        let rows = self.execute_sql(&query).await?;
+        Ok(Some(
+            rows.iter()
+                .filter_map(|row| TopQuery::from_row(&row.values))
+                .collect(),
+        ))
+    }
+
     async fn get_databases(
         &self,
     ) -> Result<Vec<DatabaseInfo>, Box<dyn std::error::Error>>;
+
+    /// `CREATE` statements for every table in a schema; `with_extras` adds
+    /// their constraints and indexes.
+    async fn get_schema_ddl(
+        &self,
+        schema_name: &str,
+        with_extras: bool,
+    ) -> Result<SchemaDdl, Box<dyn std::error::Error>>;
 }
 
 /// Database information
@@ -184,12 +638,85 @@ pub fn should_omit_for_insert_default(
     false
 }
 
+/// Kind of relation listed in the Tables step. The order is the order the
+/// kinds are grouped in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TableKind {
+    #[default]
+    Table,
+    Partitioned,
+    View,
+    MaterializedView,
+    Foreign,
+    /// `SQLite` virtual table, e.g. an FTS index.
+    Virtual,
+}
+
+impl TableKind {
+    /// Kind from a `PostgreSQL` `relkind` or a `SQLite` `pragma_table_list`
+    /// type.
+    #[must_use]
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "p" => Self::Partitioned,
+            "v" | "view" => Self::View,
+            "m" => Self::MaterializedView,
+            "f" => Self::Foreign,
+            "virtual" => Self::Virtual,
+            _ => Self::Table,
+        }
+    }
+
+    /// Name shown in the Kind column and matched by `kind:` filters.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Table => "table",
+            Self::Partitioned => "partitioned",
+            Self::View => "view",
+            Self::MaterializedView => "matview",
+            Self::Foreign => "foreign",
+            Self::Virtual => "virtual",
+        }
+    }
+
+    #[must_use]
+    pub const fn glyph(self) -> char {
+        match self {
+            Self::Table => '▦',
+            Self::Partitioned => '▤',
+            Self::View => '◇',
+            Self::MaterializedView => '◆',
+            Self::Foreign => '⇄',
+            Self::Virtual => '○',
+        }
+    }
+
+    #[must_use]
+    pub const fn color(self) -> Color {
+        match self {
+            Self::Table => Color::Reset,
+            Self::Partitioned => Color::LightBlue,
+            Self::View => Color::Cyan,
+            Self::MaterializedView => Color::Magenta,
+            Self::Foreign => Color::Yellow,
+            Self::Virtual => Color::Gray,
+        }
+    }
+}
+
 /// Table information
 #[derive(Debug, Clone)]
 pub struct Table {
     pub name: String,
     pub schema: String,
     pub size: Option<String>,
+    pub kind: TableKind,
+}
+
+/// Group tables by kind, by name within a kind.
+pub fn sort_tables_by_kind(tables: &mut [Table]) {
+    tables.sort_by(|a, b| (a.kind, &a.name).cmp(&(b.kind, &b.name)));
 }
 
 /// Column information
@@ -207,6 +734,12 @@ pub struct Column {
 pub struct TableRow {
     pub values: Vec<String>,
     pub column_names: Vec<String>,
+    /// Type of each column as the server describes it; empty when the
+    /// backend doesn't say.
+    pub column_types: Vec<String>,
+    /// Table column each column was read from, `None` for computed ones;
+    /// empty when the backend doesn't say.
+    pub column_sources: Vec<Option<ColumnSource>>,
 }
 
 impl TableData for DatabaseInfo {
@@ -253,6 +786,7 @@ impl TableData for Table {
     fn ref_array(&self) -> Vec<String> {
         vec![
             self.name.clone(),
+            format!("{} {}", self.kind.glyph(), self.kind.label()),
             self.schema.clone(),
             self.size.clone().unwrap_or_default(),
         ]
@@ -263,7 +797,20 @@ impl TableData for Table {
     }
 
     fn cols() -> Vec<&'static str> {
-        vec!["Name", "Schema", "Size"]
+        vec!["Name", "Kind", "Schema", "Size"]
+    }
+
+    fn col_color(&self, column: usize) -> Option<Color> {
+        (column == 1 && self.kind != TableKind::Table)
+            .then(|| self.kind.color())
+    }
+
+    fn filter_field(&self, key: &str) -> Option<String> {
+        (key == "kind").then(|| self.kind.label().to_string())
+    }
+
+    fn selection_key(&self) -> String {
+        format!("{}.{}", self.schema, self.name)
     }
 }
 
@@ -314,16 +861,35 @@ impl TableData for TableRow {
     }
 }
 
-pub fn get_app_data_dir() -> Result<PathBuf> {
-    let Some(path) = directories::BaseDirs::new() else {
-        return Err(eyre::eyre!(
-            "Unable to find data directory for ratatui-template"
-        ));
-    };
+/// Environment variable that relocates the data directory.
+pub const DATA_DIR_ENV: &str = "D7S_DATA_DIR";
 
-    let mut path = PathBuf::from(path.data_dir());
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
 
-    path.push("d7s");
+/// Use `path` as the data directory for the rest of the process (`--data-dir`).
+/// Takes precedence over [`DATA_DIR_ENV`]; only the first call has any effect.
+pub fn set_data_dir_override(path: PathBuf) {
+    let _ = DATA_DIR_OVERRIDE.set(path);
+}
+
+/// Resolves the data directory: `--data-dir`, then `D7S_DATA_DIR`, then the
+/// platform data dir (`$XDG_DATA_HOME/d7s` on Linux).
+pub fn get_app_data_dir() -> Result<PathBuf> {
+    let path = if let Some(path) = DATA_DIR_OVERRIDE.get() {
+        path.clone()
+    } else if let Some(path) = std::env::var_os(DATA_DIR_ENV)
+        && !path.is_empty()
+    {
+        PathBuf::from(path)
+    } else {
+        let Some(base_dirs) = directories::BaseDirs::new() else {
+            return Err(eyre::eyre!(
+                "Unable to find data directory for d7s, set {DATA_DIR_ENV} \
+                 or pass --data-dir"
+            ));
+        };
+        base_dirs.data_dir().join("d7s")
+    };
 
     // Create directory if it doesn't exist
     std::fs::create_dir_all(&path)?;
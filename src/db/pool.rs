@@ -0,0 +1,303 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::Deref,
+    sync::{Arc, Mutex, OnceLock, PoisonError},
+};
+
+use tokio::sync::{
+    Mutex as AsyncMutex, OwnedMutexGuard, OwnedSemaphorePermit, Semaphore,
+    TryLockError,
+};
+use tokio_postgres::{Client, NoTls};
+
+/// Connection limits for one database target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolLimits {
+    /// Connections kept open between queries, at most; more are closed when
+    /// given back.
+    pub max_idle: usize,
+    /// Connections open at the same time; further callers wait for a free one.
+    pub max_size: usize,
+}
+
+impl Default for PoolLimits {
+    fn default() -> Self {
+        Self {
+            max_idle: 1,
+            max_size: 4,
+        }
+    }
+}
+
+static LIMITS: OnceLock<PoolLimits> = OnceLock::new();
+
+/// Pools by connection config, so every handle on a target shares one.
+static POOLS: OnceLock<Mutex<HashMap<String, ClientPool>>> = OnceLock::new();
+
+/// Use `limits` for the pools created from now on (`--pool-size`,
+/// `--pool-idle`); only the first call has any effect.
+pub fn set_limits(limits: PoolLimits) {
+    let _ = LIMITS.set(limits);
+}
+
+#[derive(Debug)]
+pub enum PoolError {
+    Closed,
+    Connect(tokio_postgres::Error),
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Closed => write!(f, "Connection pool is closed"),
+            Self::Connect(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for PoolError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Closed => None,
+            Self::Connect(e) => Some(e),
+        }
+    }
+}
+
+impl From<tokio_postgres::Error> for PoolError {
+    fn from(e: tokio_postgres::Error) -> Self {
+        Self::Connect(e)
+    }
+}
+
+struct PoolInner {
+    limits: PoolLimits,
+    permits: Arc<Semaphore>,
+    idle: Mutex<Vec<Client>>,
+}
+
+/// Small pool of `PostgreSQL` clients for a single target, so metadata
+/// queries, page fetches and user SQL don't each pay for a new connection
+/// while staying below `max_size` connections on the server.
+///
+/// Clones share the same connections.
+#[derive(Clone)]
+pub struct ClientPool {
+    inner: Arc<PoolInner>,
+}
+
+impl Default for ClientPool {
+    fn default() -> Self {
+        Self::new(PoolLimits::default())
+    }
+}
+
+impl fmt::Debug for ClientPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientPool")
+            .field("limits", &self.inner.limits)
+            .field("idle", &self.idle_count())
+            .finish()
+    }
+}
+
+impl ClientPool {
+    #[must_use]
+    pub fn new(limits: PoolLimits) -> Self {
+        let max_size = limits.max_size.max(1);
+        Self {
+            inner: Arc::new(PoolInner {
+                limits: PoolLimits {
+                    max_idle: limits.max_idle.min(max_size),
+                    max_size,
+                },
+                permits: Arc::new(Semaphore::new(max_size)),
+                idle: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// The pool of the target `config` connects to, created with the limits
+    /// given to [`set_limits`] the first time the target is used.
+    #[must_use]
+    pub fn for_target(config: &str) -> Self {
+        let mut pools = POOLS
+            .get_or_init(Mutex::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        pools
+            .entry(config.to_string())
+            .or_insert_with(|| {
+                Self::new(LIMITS.get().copied().unwrap_or_default())
+            })
+            .clone()
+    }
+
+    /// Check out a client, reusing an idle one when possible. Waits while
+    /// `max_size` clients are already checked out.
+    pub async fn get(&self, config: &str) -> Result<PooledClient, PoolError> {
+        let permit = Arc::clone(&self.inner.permits)
+            .acquire_owned()
+            .await
+            .map_err(|_| PoolError::Closed)?;
+
+        let reused = {
+            let mut idle = self
+                .inner
+                .idle
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            // Drop clients whose server connection has gone away
+            idle.retain(|client| !client.is_closed());
+            idle.pop()
+        };
+
+        let client = match reused {
+            Some(client) => client,
+            None => connect(config).await?,
+        };
+
+        Ok(PooledClient {
+            client: Some(client),
+            pool: Arc::clone(&self.inner),
+            _permit: permit,
+        })
+    }
+
+    fn idle_count(&self) -> usize {
+        self.inner
+            .idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+}
+
+async fn connect(config: &str) -> Result<Client, tokio_postgres::Error> {
+    let (client, connection) = tokio_postgres::connect(config, NoTls).await?;
+
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            eprintln!("Database connection error: {e}");
+        }
+    });
+
+    Ok(client)
+}
+
+/// A checked-out client; returned to the pool (or closed) on drop.
+pub struct PooledClient {
+    client: Option<Client>,
+    pool: Arc<PoolInner>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Deref for PooledClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Self::Target {
+        // Only taken in `drop`
+        self.client.as_ref().unwrap_or_else(|| unreachable!())
+    }
+}
+
+impl Drop for PooledClient {
+    fn drop(&mut self) {
+        let Some(client) = self.client.take() else {
+            return;
+        };
+        if client.is_closed() {
+            return;
+        }
+        let mut idle = self
+            .pool
+            .idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if idle.len() < self.pool.limits.max_idle {
+            idle.push(client);
+        }
+    }
+}
+
+/// The one client the SQL executor's statements run on, kept out of the
+/// pool so a transaction opened there sees every statement that follows it
+/// and nothing else. Background work uses the pool instead.
+///
+/// Clones share the same client.
+#[derive(Clone, Default)]
+pub struct SessionClient {
+    client: Arc<AsyncMutex<Option<Client>>>,
+}
+
+impl fmt::Debug for SessionClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionClient")
+            .field("busy", &self.client.try_lock().is_err())
+            .finish()
+    }
+}
+
+impl SessionClient {
+    /// Take the session, waiting while a statement runs on it. Connects
+    /// when it isn't open yet or its server connection went away, which
+    /// ends any transaction that was open on it.
+    pub async fn get(&self, config: &str) -> Result<SessionGuard, PoolError> {
+        let mut client = Arc::clone(&self.client).lock_owned().await;
+        if client.as_ref().is_none_or(Client::is_closed) {
+            *client = Some(connect(config).await?);
+        }
+        Ok(SessionGuard { client })
+    }
+
+    /// The session if it is open, without connecting or waiting; fails
+    /// while a statement runs on it.
+    pub fn try_get(&self) -> Result<Option<SessionGuard>, TryLockError> {
+        let client = Arc::clone(&self.client).try_lock_owned()?;
+        Ok(client
+            .as_ref()
+            .is_some_and(|client| !client.is_closed())
+            .then_some(SessionGuard { client }))
+    }
+}
+
+/// The session client, held until dropped.
+pub struct SessionGuard {
+    client: OwnedMutexGuard<Option<Client>>,
+}
+
+impl Deref for SessionGuard {
+    type Target = Client;
+
+    fn deref(&self) -> &Self::Target {
+        // Only handed out once connected
+        self.client.as_ref().unwrap_or_else(|| unreachable!())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_one_pool_per_target() {
+        let config = "host=db1 user=app dbname=shop";
+        let pool = ClientPool::for_target(config);
+        assert!(Arc::ptr_eq(
+            &pool.inner,
+            &ClientPool::for_target(config).inner
+        ));
+        let other = ClientPool::for_target("host=db2 user=app dbname=shop");
+        assert!(!Arc::ptr_eq(&pool.inner, &other.inner));
+    }
+
+    #[test]
+    fn keeps_at_most_max_size_idle_clients() {
+        let pool = ClientPool::new(PoolLimits {
+            max_idle: 9,
+            max_size: 2,
+        });
+        assert_eq!(pool.inner.limits.max_idle, 2);
+    }
+}
@@ -6,17 +6,33 @@ use std::{
 };
 
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use futures_util::StreamExt;
 use rust_decimal::Decimal;
 use serde_json::Value;
 use tokio_postgres::{
-    NoTls, Row,
-    types::{FromSql, ToSql, Type},
+    NoTls, Row, SimpleQueryMessage,
+    error::{ErrorPosition, SqlState},
+    types::{FromSql, Kind, ToSql, Type},
 };
 use uuid::Uuid;
 
-use crate::db::{
-    Column, Database, DatabaseInfo, DbRowId, Schema, Table, TableData,
-    TableDataPage, TableRow, should_omit_for_insert_default,
+use crate::{
+    db::{
+        Column, ConnectError, Database, DatabaseInfo, DbRowId, Schema, Table,
+        TableData, TableDataPage, TableKind, TableRow, TransactionStatus,
+        ddl::{DdlColumn, SchemaDdl, create_table_statement},
+        dialect::{Dialect, PostgresDialect},
+        foreign_key::{ForeignKey, ForeignKeyKind},
+        keyset::KeysetPage,
+        pool::{
+            ClientPool, PoolError, PooledClient, SessionClient, SessionGuard,
+        },
+        result_origin::ColumnSource,
+        should_omit_for_insert_default,
+        time_box::{Cutoff, TimeBox, TimeBoxedRows},
+    },
+    metrics,
+    sort::TableOrder,
 };
 
 /// Cache key: one physical Postgres database table (server + db + schema + table).
@@ -45,10 +61,7 @@ fn table_column_cache()
     TABLE_COLUMN_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
-/// Escape a `PostgreSQL` identifier (double-quoted).
-fn pg_quote_ident(ident: &str) -> String {
-    format!("\"{}\"", ident.replace('"', "\"\""))
-}
+const DIALECT: PostgresDialect = PostgresDialect;
 
 /// `pg_catalog.format_type` string for a column, for `CAST($n AS …)` in updates.
 fn pg_resolve_format_type(
@@ -83,7 +96,7 @@ async fn pg_column_format_types(
           AND a.attnum > 0
           AND NOT a.attisdropped
     ";
-    let rows = client.query(q, &[&schema_name, &table_name]).await?;
+    let rows = client.query(q, &[&table_name, &schema_name]).await?;
     let mut m = HashMap::with_capacity(rows.len());
     for row in rows {
         let col: String = row.get(0);
@@ -295,7 +308,7 @@ fn build_table_data_select_base(
         info.ordered_columns
             .iter()
             .map(|col| {
-                let q = pg_quote_ident(col);
+                let q = DIALECT.quote_ident(col);
                 if info.udt_columns.contains(col) {
                     format!("{q}::text")
                 } else {
@@ -307,12 +320,38 @@ fn build_table_data_select_base(
     };
 
     format!(
-        "SELECT {select_list} FROM {}.{}",
-        pg_quote_ident(schema_name),
-        pg_quote_ident(table_name)
+        "SELECT {select_list} FROM {}",
+        DIALECT.qualified_table(schema_name, table_name)
     )
 }
 
+/// Server `options` setting `search_path`, e.g. for `PGOPTIONS`. The server
+/// splits options on spaces, so those in the value are escaped.
+#[must_use]
+pub fn search_path_options(search_path: &str) -> String {
+    let value = search_path.replace('\\', "\\\\").replace(' ', "\\ ");
+    format!("-c search_path={value}")
+}
+
+/// 0-based character index in the statement that the server's error points
+/// at, if it points anywhere.
+#[must_use]
+pub fn error_position(
+    error: &(dyn std::error::Error + 'static),
+) -> Option<usize> {
+    let position = error
+        .downcast_ref::<tokio_postgres::Error>()?
+        .as_db_error()?
+        .position()?;
+    match position {
+        ErrorPosition::Original(position) => {
+            usize::try_from(*position).ok()?.checked_sub(1)
+        }
+        // In a query the server generated, e.g. a function body
+        ErrorPosition::Internal { .. } => None,
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Postgres {
     pub name: String,
@@ -321,6 +360,14 @@ pub struct Postgres {
     pub user: String,
     pub database: String,
     pub password: String,
+    /// `search_path` set on every connection instead of the server default.
+    pub search_path: Option<String>,
+    /// libpq `target_session_attrs` when `host` lists several servers,
+    /// e.g. `primary`; any server will do when unset.
+    pub target_session_attrs: Option<String>,
+    /// Where the SQL executor's statements, its transaction and savepoints
+    /// run; shared by clones.
+    pub session: SessionClient,
 }
 
 impl TableData for Postgres {
@@ -347,19 +394,60 @@ impl TableData for Postgres {
     }
 }
 
+// Pooled clients are checked back in when each method returns
+#[allow(clippy::significant_drop_tightening)]
 #[async_trait::async_trait]
 impl Database for Postgres {
-    async fn test(&self) -> bool {
-        let config = format!(
-            "host={} port={} user={} password={} dbname={}",
-            self.host.clone().unwrap_or_else(|| "localhost".to_string()),
-            self.port.clone().unwrap_or_else(|| "5432".to_string()),
-            self.user,
-            self.password,
-            self.database
-        );
+    fn dialect(&self) -> &'static dyn Dialect {
+        &DIALECT
+    }
+
+    async fn check_connection(&self) -> Result<(), ConnectError> {
+        tokio_postgres::connect(&self.connection_config(), NoTls)
+            .await
+            .map(|_| ())
+            .map_err(|e| ConnectError {
+                message: e.to_string(),
+                transient: is_transient_connect_error(&e),
+            })
+    }
 
-        tokio_postgres::connect(&config, NoTls).await.is_ok()
+    async fn transaction_status(
+        &self,
+    ) -> Result<Option<TransactionStatus>, Box<dyn std::error::Error>> {
+        // Within a transaction block the transaction started earlier than
+        // this statement; once one failed, no statement runs at all. No
+        // session yet means no transaction, and one in use can't be asked.
+        let client = match self.session.try_get() {
+            Ok(Some(client)) => client,
+            Ok(None) => return Ok(Some(TransactionStatus::Idle)),
+            Err(_) => return Ok(None),
+        };
+        // Sent as one simple query: the extended protocol's separate
+        // messages would move the statement timestamp past the start of the
+        // implicit transaction
+        let query = "SELECT transaction_timestamp() <> statement_timestamp()";
+        match client.simple_query(query).await {
+            Ok(messages) => {
+                let in_transaction = messages.iter().any(|message| {
+                    matches!(
+                        message,
+                        SimpleQueryMessage::Row(row) if row.get(0) == Some("t")
+                    )
+                });
+                Ok(Some(if in_transaction {
+                    TransactionStatus::InTransaction
+                } else {
+                    TransactionStatus::Idle
+                }))
+            }
+            Err(e)
+                if e.code() == Some(&SqlState::IN_FAILED_SQL_TRANSACTION) =>
+            {
+                Ok(Some(TransactionStatus::Failed))
+            }
+            Err(e) => Err(e.into()),
+        }
     }
 
     async fn execute_sql(
@@ -367,41 +455,102 @@ impl Database for Postgres {
         sql: &str,
     ) -> Result<Vec<TableRow>, Box<dyn std::error::Error>> {
         let client = self.get_connection().await?;
+        run_statement(&client, sql).await
+    }
 
-        let rows = client.query(sql, &[]).await?;
-        let mut result = Vec::new();
+    async fn execute_in_session(
+        &self,
+        sql: &str,
+    ) -> Result<Vec<TableRow>, Box<dyn std::error::Error>> {
+        let client = self.get_session().await?;
+        run_statement(&client, sql).await
+    }
 
-        if rows.is_empty() {
-            let affected_rows = client.execute(sql, &[]).await?;
-            result.push(TableRow {
-                values: vec![format!("Affected rows: {}", affected_rows)],
-                column_names: vec!["Result".to_string()],
-            });
-        } else {
-            let Some(first_row) = rows.first() else {
-                return Ok(result);
-            };
-            let column_names: Vec<String> = first_row
-                .columns()
-                .iter()
-                .map(|col| col.name().to_string())
-                .collect();
+    async fn batch_execute_in_session(
+        &self,
+        sql: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let client = self.get_session().await?;
+        client.batch_execute(sql).await?;
+        Ok(())
+    }
 
-            for row in &rows {
-                let values = row
-                    .columns()
-                    .iter()
-                    .enumerate()
-                    .map(|(i, col)| column_to_string(row, i, col.type_()))
-                    .collect();
-                result.push(TableRow {
-                    values,
-                    column_names: column_names.clone(),
-                });
+    async fn with_search_path(
+        &self,
+        search_path: Option<&str>,
+    ) -> Result<Option<Box<dyn Database>>, Box<dyn std::error::Error>> {
+        // Clones share the session, which is changed with SET rather than
+        // replaced, as a new connection would end its transaction
+        let database = Self {
+            search_path: search_path.map(str::to_string),
+            ..self.clone()
+        };
+        database.check_connection().await.map_err(|e| e.message)?;
+        let set = search_path.map_or_else(
+            || "SET search_path TO DEFAULT".to_string(),
+            |search_path| format!("SET search_path TO {search_path}"),
+        );
+        database.batch_execute_in_session(&set).await?;
+        Ok(Some(Box::new(database)))
+    }
+
+    async fn execute_sql_within(
+        &self,
+        sql: &str,
+        time_box: TimeBox,
+    ) -> Result<TimeBoxedRows, Box<dyn std::error::Error>> {
+        // Without limits the statement is run as is, so writes report the
+        // rows they changed
+        if !time_box.is_enabled() {
+            let rows = self.execute_in_session(sql).await?;
+            return Ok(TimeBoxedRows { rows, cutoff: None });
+        }
+        let deadline = time_box
+            .max_time
+            .map(|limit| tokio::time::Instant::now() + limit);
+        let timed_out = TimeBoxedRows {
+            rows: Vec::new(),
+            cutoff: time_box.max_time.map(Cutoff::Time),
+        };
+        let client = self.get_session().await?;
+
+        // Rows are read as they arrive. A cutoff cancels the statement on
+        // the server and waits for it to end, so the session is free for
+        // the next statement once this returns
+        let params = std::iter::empty::<&(dyn ToSql + Sync)>();
+        let mut query = std::pin::pin!(client.query_raw(sql, params));
+        let Some(stream) = before(deadline, query.as_mut()).await else {
+            cancel(&client).await;
+            // The statement's outcome doesn't matter once it is cut off
+            let _ = query.await;
+            return Ok(timed_out);
+        };
+        let mut stream = std::pin::pin!(stream?);
+        let mut rows = Vec::new();
+        let mut cutoff = None;
+        loop {
+            let Some(next) = before(deadline, stream.next()).await else {
+                cutoff = timed_out.cutoff;
+                break;
+            };
+            let Some(row) = next.transpose()? else {
+                break;
+            };
+            if time_box.rows_exceeded(rows.len() + 1) {
+                cutoff = time_box.max_rows.map(Cutoff::Rows);
+                break;
             }
+            rows.push(row);
+        }
+        if cutoff.is_some() {
+            cancel(&client).await;
+            while let Some(Ok(_)) = stream.next().await {}
         }
 
-        Ok(result)
+        Ok(TimeBoxedRows {
+            rows: table_rows(&rows),
+            cutoff,
+        })
     }
 
     async fn get_schemas(
@@ -409,12 +558,7 @@ impl Database for Postgres {
     ) -> Result<Vec<Schema>, Box<dyn std::error::Error>> {
         let client = self.get_connection().await?;
 
-        let query = "
-            SELECT schema_name, schema_owner
-            FROM information_schema.schemata
-            WHERE schema_name NOT IN ('information_schema', 'pg_catalog', 'pg_toast')
-            ORDER BY schema_name
-        ";
+        let query = DIALECT.schemas_query();
 
         let rows = client.query(query, &[]).await?;
         let mut schemas = Vec::new();
@@ -436,16 +580,7 @@ impl Database for Postgres {
     ) -> Result<Vec<Table>, Box<dyn std::error::Error>> {
         let client = self.get_connection().await?;
 
-        let query = "
-            SELECT
-                t.table_name,
-                t.table_schema,
-                pg_size_pretty(pg_total_relation_size(quote_ident(t.table_schema)||'.'||quote_ident(t.table_name))) as size
-            FROM information_schema.tables t
-            WHERE t.table_schema = $1
-            AND t.table_type = 'BASE TABLE'
-            ORDER BY t.table_name;
-        ";
+        let query = DIALECT.tables_query();
 
         let rows = client.query(query, &[&schema_name]).await?;
         let tables = rows
@@ -454,6 +589,7 @@ impl Database for Postgres {
                 name: row.get(0),
                 schema: row.get(1),
                 size: row.get(2),
+                kind: TableKind::from_code(row.get(3)),
             })
             .collect();
 
@@ -467,22 +603,9 @@ impl Database for Postgres {
     ) -> Result<Vec<Column>, Box<dyn std::error::Error>> {
         let client = self.get_connection().await?;
 
-        let query = "
-            SELECT
-                c.column_name,
-                c.data_type,
-                c.is_nullable,
-                c.column_default,
-                pgd.description
-            FROM information_schema.columns c
-            LEFT JOIN pg_catalog.pg_statio_all_tables st ON (c.table_schema = st.schemaname AND c.table_name = st.relname)
-            LEFT JOIN pg_catalog.pg_description pgd ON (pgd.objoid = st.relid AND pgd.objsubid = c.ordinal_position)
-            WHERE c.table_schema = $1
-            AND c.table_name = $2
-            ORDER BY c.ordinal_position
-        ";
+        let query = DIALECT.columns_query();
 
-        let rows = client.query(query, &[&schema_name, &table_name]).await?;
+        let rows = client.query(query, &[&table_name, &schema_name]).await?;
         let columns = rows
             .iter()
             .map(|row| Column {
@@ -501,6 +624,8 @@ impl Database for Postgres {
         &self,
         schema_name: &str,
         table_name: &str,
+        order: Option<&TableOrder>,
+        keyset: Option<&KeysetPage>,
         offset: u64,
         limit: u32,
     ) -> Result<TableDataPage, Box<dyn std::error::Error>> {
@@ -512,8 +637,34 @@ impl Database for Postgres {
 
         let base =
             build_table_data_select_base(schema_name, table_name, &layout);
-        let query =
-            format!("{} LIMIT $1 OFFSET $2", prepend_ctid_to_select(&base));
+        let (filter, order_by) = match keyset {
+            Some(page) => {
+                (page.where_clause(&DIALECT), page.order_by_clause(&DIALECT))
+            }
+            None => (
+                String::new(),
+                order
+                    .map(|order| DIALECT.order_by_clause(order))
+                    .unwrap_or_default(),
+            ),
+        };
+        let select = [prepend_ctid_to_select(&base), filter, order_by]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let query = format!(
+            "{select} {}",
+            DIALECT.paging_clause(
+                &DIALECT.placeholder(1),
+                Some(&DIALECT.placeholder(2))
+            )
+        );
+        let sql = format!(
+            "{} {}",
+            select.trim_end(),
+            DIALECT.paging_clause(&limit.to_string(), Some(&offset.to_string()))
+        );
         let limit_i: i64 = i64::from(limit);
         let offset_i: i64 = offset.try_into().unwrap_or(i64::MAX);
         let rows = client.query(&query, &[&limit_i, &offset_i]).await?;
@@ -546,11 +697,16 @@ impl Database for Postgres {
                 .collect();
             data.push(values);
         }
+        if keyset.is_some_and(KeysetPage::reads_backwards) {
+            data.reverse();
+            row_ids.reverse();
+        }
 
         Ok(TableDataPage {
             rows: data,
             column_names,
             row_ids,
+            sql,
         })
     }
 
@@ -560,23 +716,29 @@ impl Database for Postgres {
         table_name: &str,
     ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
         let client = self.get_connection().await?;
-        let q = "
-            SELECT kcu.column_name
-            FROM information_schema.table_constraints tc
-            INNER JOIN information_schema.key_column_usage kcu
-                ON tc.constraint_schema = kcu.constraint_schema
-                AND tc.constraint_name = kcu.constraint_name
-                AND tc.table_schema = kcu.table_schema
-                AND tc.table_name = kcu.table_name
-            WHERE tc.constraint_type = 'PRIMARY KEY'
-                AND tc.table_schema = $1
-                AND tc.table_name = $2
-            ORDER BY kcu.ordinal_position
-        ";
-        let rows = client.query(q, &[&schema_name, &table_name]).await?;
+        let q = DIALECT.primary_key_query();
+        let rows = client.query(q, &[&table_name, &schema_name]).await?;
         Ok(rows.iter().map(|r| r.get::<_, String>(0)).collect())
     }
 
+    async fn get_foreign_keys(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<Vec<ForeignKey>, Box<dyn std::error::Error>> {
+        let client = self.get_connection().await?;
+        let q = DIALECT.foreign_keys_query();
+        let rows = client.query(q, &[&table_name, &schema_name]).await?;
+        Ok(ForeignKey::from_catalog_rows(rows.iter().map(|row| {
+            let kind = if row.get::<_, bool>(5) {
+                ForeignKeyKind::Validated
+            } else {
+                ForeignKeyKind::NotValid
+            };
+            ([0, 1, 2, 3, 4].map(|idx| row.get::<_, String>(idx)), kind)
+        })))
+    }
+
     async fn update_table_cell(
         &self,
         schema_name: &str,
@@ -590,12 +752,8 @@ impl Database for Postgres {
         let col_types =
             pg_column_format_types(&client, schema_name, table_name).await?;
         let set_ty = pg_resolve_format_type(&col_types, set_column);
-        let set_q = pg_quote_ident(set_column);
-        let tgt = format!(
-            "{}.{}",
-            pg_quote_ident(schema_name),
-            pg_quote_ident(table_name),
-        );
+        let set_q = DIALECT.quote_ident(set_column);
+        let tgt = DIALECT.qualified_table(schema_name, table_name);
 
         if !primary_key.is_empty() {
             // Bind every value as text on the wire (`$n::text`), then cast to the column type.
@@ -618,7 +776,7 @@ impl Database for Postgres {
                 let _ = write!(
                     sql,
                     "{} = CAST(${}::text AS {pk_ty})",
-                    pg_quote_ident(k),
+                    DIALECT.quote_ident(k),
                     param_num
                 );
                 owned.push(pg_coerce_typed_text_input(v, &pk_ty).into_owned());
@@ -663,11 +821,7 @@ impl Database for Postgres {
         }
         let col_types =
             pg_column_format_types(&client, schema_name, table_name).await?;
-        let tgt = format!(
-            "{}.{}",
-            pg_quote_ident(schema_name),
-            pg_quote_ident(table_name),
-        );
+        let tgt = DIALECT.qualified_table(schema_name, table_name);
         let mut col_list: Vec<String> = Vec::new();
         let mut val_placeholders: Vec<String> = Vec::new();
         let mut owned: Vec<String> = Vec::new();
@@ -678,7 +832,7 @@ impl Database for Postgres {
             }
             if raw.trim().is_empty() || raw.eq_ignore_ascii_case("null") {
                 if c.is_nullable {
-                    col_list.push(pg_quote_ident(&c.name));
+                    col_list.push(DIALECT.quote_ident(&c.name));
                     val_placeholders.push("NULL".to_string());
                 } else {
                     return Err(format!(
@@ -688,7 +842,7 @@ impl Database for Postgres {
                     .into());
                 }
             } else {
-                col_list.push(pg_quote_ident(&c.name));
+                col_list.push(DIALECT.quote_ident(&c.name));
                 let ty = pg_resolve_format_type(&col_types, &c.name);
                 let param_num = owned.len() + 1;
                 val_placeholders
@@ -724,11 +878,7 @@ impl Database for Postgres {
         let client = self.get_connection().await?;
         let col_types =
             pg_column_format_types(&client, schema_name, table_name).await?;
-        let tgt = format!(
-            "{}.{}",
-            pg_quote_ident(schema_name),
-            pg_quote_ident(table_name),
-        );
+        let tgt = DIALECT.qualified_table(schema_name, table_name);
 
         if !primary_key.is_empty() {
             let mut sql = format!("DELETE FROM {tgt} WHERE ");
@@ -742,7 +892,7 @@ impl Database for Postgres {
                 let _ = write!(
                     sql,
                     "{} = CAST(${param_num}::text AS {pk_ty})",
-                    pg_quote_ident(k)
+                    DIALECT.quote_ident(k)
                 );
                 owned.push(pg_coerce_typed_text_input(v, &pk_ty).into_owned());
             }
@@ -775,9 +925,8 @@ impl Database for Postgres {
     ) -> Result<u64, Box<dyn std::error::Error>> {
         let client = self.get_connection().await?;
         let q = format!(
-            "SELECT COUNT(*)::bigint FROM {}.{}",
-            pg_quote_ident(schema_name),
-            pg_quote_ident(table_name),
+            "SELECT COUNT(*)::bigint FROM {}",
+            DIALECT.qualified_table(schema_name, table_name),
         );
         let row = client.query_one(&q, &[]).await?;
         let count: i64 = row.get(0);
@@ -804,9 +953,223 @@ impl Database for Postgres {
 
         Ok(databases)
     }
+
+    async fn get_schema_ddl(
+        &self,
+        schema_name: &str,
+        with_extras: bool,
+    ) -> Result<SchemaDdl, Box<dyn std::error::Error>> {
+        let client = self.get_connection().await?;
+
+        // Sequences behind `serial` defaults; identity sequences come with
+        // their column
+        let sequences = client
+            .query(
+                "
+                SELECT c.relname
+                FROM pg_catalog.pg_class c
+                JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+                WHERE n.nspname = $1
+                AND c.relkind = 'S'
+                AND NOT EXISTS (
+                    SELECT 1 FROM pg_catalog.pg_depend d
+                    WHERE d.objid = c.oid AND d.deptype = 'i'
+                )
+                ORDER BY c.relname
+            ",
+                &[&schema_name],
+            )
+            .await?;
+        let prelude = sequences
+            .iter()
+            .map(|row| {
+                format!(
+                    "CREATE SEQUENCE {}",
+                    DIALECT.qualified_table(schema_name, row.get(0))
+                )
+            })
+            .collect();
+
+        let tables = Self::table_ddl(&client, schema_name).await?;
+        let extras = if with_extras {
+            Self::constraint_and_index_ddl(&client, schema_name).await?
+        } else {
+            Vec::new()
+        };
+        Ok(SchemaDdl {
+            prelude,
+            tables,
+            extras,
+        })
+    }
 }
 
+#[allow(clippy::significant_drop_tightening)]
 impl Postgres {
+    /// `CREATE TABLE` for every table in the schema, partitioned tables
+    /// before their partitions.
+    async fn table_ddl(
+        client: &tokio_postgres::Client,
+        schema_name: &str,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let rows = client
+            .query(
+                "
+                SELECT
+                    c.relname,
+                    pg_catalog.pg_get_partkeydef(c.oid),
+                    (
+                        SELECT format('%I.%I', pn.nspname, p.relname)
+                        FROM pg_catalog.pg_inherits i
+                        JOIN pg_catalog.pg_class p ON p.oid = i.inhparent
+                        JOIN pg_catalog.pg_namespace pn ON pn.oid = p.relnamespace
+                        WHERE i.inhrelid = c.oid AND c.relispartition
+                    ),
+                    pg_catalog.pg_get_expr(c.relpartbound, c.oid),
+                    quote_ident(a.attname),
+                    pg_catalog.format_type(a.atttypid, a.atttypmod),
+                    a.attnotnull,
+                    pg_catalog.pg_get_expr(ad.adbin, ad.adrelid),
+                    a.attidentity::text,
+                    a.attgenerated::text
+                FROM pg_catalog.pg_class c
+                JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+                LEFT JOIN pg_catalog.pg_attribute a
+                    ON a.attrelid = c.oid AND a.attnum > 0 AND NOT a.attisdropped
+                LEFT JOIN pg_catalog.pg_attrdef ad
+                    ON ad.adrelid = c.oid AND ad.adnum = a.attnum
+                WHERE n.nspname = $1
+                AND c.relkind IN ('r', 'p')
+                ORDER BY c.relispartition, c.relname, a.attnum
+            ",
+                &[&schema_name],
+            )
+            .await?;
+
+        // One row per column; a table's rows are consecutive
+        let mut tables = Vec::new();
+        let mut rows = rows.iter().peekable();
+        while let Some(first) = rows.peek() {
+            let name: String = first.get(0);
+            let partition_key: Option<String> = first.get(1);
+            let parent: Option<String> = first.get(2);
+            let bound: Option<String> = first.get(3);
+            let mut columns = Vec::new();
+            while let Some(row) =
+                rows.next_if(|row| row.get::<_, String>(0) == name)
+            {
+                let Some(column) = row.get::<_, Option<String>>(4) else {
+                    continue;
+                };
+                let default: Option<String> = row.get(7);
+                let identity: String = row.get(8);
+                let generated: String = row.get(9);
+                let (default, generated) =
+                    match (identity.as_str(), generated.as_str()) {
+                        ("a", _) => (
+                            default,
+                            Some("GENERATED ALWAYS AS IDENTITY".to_string()),
+                        ),
+                        ("d", _) => (
+                            default,
+                            Some(
+                                "GENERATED BY DEFAULT AS IDENTITY".to_string(),
+                            ),
+                        ),
+                        (_, "s") => (
+                            None,
+                            default.map(|expr| {
+                                format!("GENERATED ALWAYS AS ({expr}) STORED")
+                            }),
+                        ),
+                        _ => (default, None),
+                    };
+                columns.push(DdlColumn {
+                    name: column,
+                    data_type: row.get(5),
+                    not_null: row.get(6),
+                    default,
+                    generated,
+                });
+            }
+
+            let table = DIALECT.qualified_table(schema_name, &name);
+            let partition_by =
+                partition_key.map(|key| format!("PARTITION BY {key}"));
+            tables.push(match (parent, bound) {
+                (Some(parent), Some(bound)) => {
+                    let suffix = partition_by
+                        .map(|p| format!(" {p}"))
+                        .unwrap_or_default();
+                    format!("CREATE TABLE {table} PARTITION OF {parent} {bound}{suffix}")
+                }
+                _ => create_table_statement(
+                    &table,
+                    &columns,
+                    partition_by.as_deref(),
+                ),
+            });
+        }
+        Ok(tables)
+    }
+
+    /// Constraints and the indexes not behind one, foreign keys last so
+    /// the keys they reference exist.
+    async fn constraint_and_index_ddl(
+        client: &tokio_postgres::Client,
+        schema_name: &str,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let rows = client
+            .query(
+                "
+                SELECT statement FROM (
+                    SELECT
+                        CASE con.contype WHEN 'f' THEN 2 ELSE 0 END AS rank,
+                        c.relname,
+                        format(
+                            'ALTER TABLE %I.%I ADD CONSTRAINT %I %s',
+                            n.nspname, c.relname, con.conname,
+                            pg_catalog.pg_get_constraintdef(con.oid)
+                        ) AS statement
+                    FROM pg_catalog.pg_constraint con
+                    JOIN pg_catalog.pg_class c ON c.oid = con.conrelid
+                    JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+                    WHERE n.nspname = $1
+                    AND c.relkind IN ('r', 'p')
+                    AND con.conparentid = 0
+                    AND con.contype IN ('p', 'u', 'c', 'f', 'x')
+                    UNION ALL
+                    -- `ON ONLY` would leave the partitions without the index
+                    SELECT
+                        1,
+                        c.relname,
+                        replace(
+                            pg_catalog.pg_get_indexdef(i.indexrelid),
+                            ' ON ONLY ', ' ON '
+                        )
+                    FROM pg_catalog.pg_index i
+                    JOIN pg_catalog.pg_class c ON c.oid = i.indrelid
+                    JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+                    WHERE n.nspname = $1
+                    AND c.relkind IN ('r', 'p')
+                    AND NOT EXISTS (
+                        SELECT 1 FROM pg_catalog.pg_constraint con
+                        WHERE con.conindid = i.indexrelid
+                        AND con.contype IN ('p', 'u', 'x')
+                    )
+                    AND NOT EXISTS (
+                        SELECT 1 FROM pg_catalog.pg_inherits inh
+                        WHERE inh.inhrelid = i.indexrelid
+                    )
+                ) extras
+                ORDER BY rank, relname, statement
+            ",
+                &[&schema_name],
+            )
+            .await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
     /// Load ordered columns and UDT flags from `information_schema`, using a process-wide cache.
     async fn get_or_fetch_table_column_layout(
         &self,
@@ -826,7 +1189,9 @@ impl Postgres {
             let guard = table_column_cache().lock().map_err(|_| {
                 std::io::Error::other("table column cache lock poisoned")
             })?;
-            if let Some(info) = guard.get(&key) {
+            let cached = guard.get(&key);
+            metrics::record_cache_lookup(cached.is_some());
+            if let Some(info) = cached {
                 return Ok(info.clone());
             }
         }
@@ -866,28 +1231,39 @@ impl Postgres {
         }
     }
 
-    async fn get_connection(
-        &self,
-    ) -> Result<tokio_postgres::Client, tokio_postgres::Error> {
-        let config = format!(
-            "host={} port={} user={} password={} dbname={}",
-            self.host.clone().unwrap_or_else(|| "localhost".to_string()),
-            self.port.clone().unwrap_or_else(|| "5432".to_string()),
-            self.user,
-            self.password,
-            self.database
+    fn connection_config(&self) -> String {
+        let mut config = format!(
+            "host={} port={} user={} dbname={}",
+            config_value(self.host.as_deref().unwrap_or("localhost")),
+            config_value(self.port.as_deref().unwrap_or("5432")),
+            config_value(&self.user),
+            config_value(&self.database),
         );
+        if !self.password.is_empty() {
+            let password = config_value(&self.password);
+            let _ = write!(config, " password={password}");
+        }
+        if let Some(attrs) = &self.target_session_attrs {
+            let _ = write!(
+                config,
+                " target_session_attrs={}",
+                config_value(session_attrs_option(attrs))
+            );
+        }
+        if let Some(search_path) = &self.search_path {
+            let options = search_path_options(search_path);
+            let _ = write!(config, " options={}", config_value(&options));
+        }
+        config
+    }
 
-        let (client, connection) =
-            tokio_postgres::connect(&config, NoTls).await?;
-
-        tokio::spawn(async move {
-            if let Err(e) = connection.await {
-                eprintln!("Database connection error: {e}");
-            }
-        });
+    async fn get_connection(&self) -> Result<PooledClient, PoolError> {
+        let config = self.connection_config();
+        ClientPool::for_target(&config).get(&config).await
+    }
 
-        Ok(client)
+    async fn get_session(&self) -> Result<SessionGuard, PoolError> {
+        self.session.get(&self.connection_config()).await
     }
 
     /// Retrieves sample data from a table.
@@ -901,7 +1277,7 @@ impl Postgres {
         schema_name: &str,
         table_name: &str,
         limit: i64,
-    ) -> Result<Vec<Vec<String>>, tokio_postgres::Error> {
+    ) -> Result<Vec<Vec<String>>, Box<dyn std::error::Error>> {
         let client = self.get_connection().await?;
 
         let query =
@@ -923,6 +1299,100 @@ impl Postgres {
     }
 }
 
+/// `future`'s output, or `None` when `deadline` passes first.
+async fn before<F: Future>(
+    deadline: Option<tokio::time::Instant>,
+    future: F,
+) -> Option<F::Output> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, future).await.ok(),
+        None => Some(future.await),
+    }
+}
+
+/// Ask the server to cancel the statement `client` is running; a failed
+/// request is ignored, as the statement then runs to its end.
+async fn cancel(client: &tokio_postgres::Client) {
+    let _ = client.cancel_token().cancel_query(NoTls).await;
+}
+
+/// Run `sql` once: statements with result columns are queried, the others
+/// (INSERT/UPDATE/DDL/...) executed for their affected row count.
+async fn run_statement(
+    client: &tokio_postgres::Client,
+    sql: &str,
+) -> Result<Vec<TableRow>, Box<dyn std::error::Error>> {
+    let statement = client.prepare(sql).await?;
+    if !statement.columns().is_empty() {
+        let rows = client.query(&statement, &[]).await?;
+        return Ok(table_rows(&rows));
+    }
+
+    let affected_rows = client.execute(&statement, &[]).await?;
+    Ok(vec![TableRow {
+        values: vec![format!("Affected rows: {affected_rows}")],
+        column_names: vec!["Result".to_string()],
+        column_types: Vec::new(),
+        column_sources: Vec::new(),
+    }])
+}
+
+/// `rows` as text, each with the names, types and source columns of the
+/// result's columns.
+fn table_rows(rows: &[Row]) -> Vec<TableRow> {
+    let Some(first_row) = rows.first() else {
+        return Vec::new();
+    };
+    let column_names: Vec<String> = first_row
+        .columns()
+        .iter()
+        .map(|col| col.name().to_string())
+        .collect();
+    let column_types: Vec<String> = first_row
+        .columns()
+        .iter()
+        .map(|col| type_label(col.type_()))
+        .collect();
+    let column_sources: Vec<Option<ColumnSource>> = first_row
+        .columns()
+        .iter()
+        .map(|col| {
+            let (table_oid, column_id) =
+                col.table_oid().zip(col.column_id())?;
+            Some(ColumnSource {
+                table_oid,
+                column_id,
+            })
+        })
+        .collect();
+
+    rows.iter()
+        .map(|row| {
+            let values = row
+                .columns()
+                .iter()
+                .enumerate()
+                .map(|(i, col)| column_to_string(row, i, col.type_()))
+                .collect();
+            TableRow {
+                values,
+                column_names: column_names.clone(),
+                column_types: column_types.clone(),
+                column_sources: column_sources.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Type name for a result column header, e.g. `numeric` or `text[]`.
+fn type_label(ty: &Type) -> String {
+    if let Kind::Array(element) = ty.kind() {
+        format!("{}[]", element.name())
+    } else {
+        ty.name().to_string()
+    }
+}
+
 /// Try and convert the value of the row to a string based on the type of the
 /// column.
 ///
@@ -1270,6 +1740,54 @@ fn try_get_date(row: &Row, index: usize) -> String {
     try_get::<String>(row, index)
 }
 
+/// Whether connecting may work on a later attempt: refused, reset or
+/// timed-out connections, servers that can't take connections yet (57P03)
+/// or are shutting down (57P01, 57P02), and `target_session_attrs` finding
+/// no suitable server, as while a replica is being promoted.
+fn is_transient_connect_error(e: &tokio_postgres::Error) -> bool {
+    if let Some(db_error) = e.as_db_error() {
+        return [
+            SqlState::CANNOT_CONNECT_NOW,
+            SqlState::ADMIN_SHUTDOWN,
+            SqlState::CRASH_SHUTDOWN,
+        ]
+        .contains(db_error.code());
+    }
+    std::error::Error::source(e)
+        .and_then(|source| source.downcast_ref::<std::io::Error>())
+        .is_some_and(|io| {
+            matches!(
+                io.kind(),
+                std::io::ErrorKind::ConnectionRefused
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::NotConnected
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::UnexpectedEof
+                    | std::io::ErrorKind::HostUnreachable
+                    | std::io::ErrorKind::NetworkUnreachable
+                    | std::io::ErrorKind::PermissionDenied
+            )
+        })
+}
+
+/// `value` quoted for a `key=value` connection string, escaping
+/// backslashes and quotes, so spaces, quotes and empty values stay intact.
+fn config_value(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// libpq `target_session_attrs` as tokio-postgres takes it. It has no
+/// `primary` or `standby`, so those become `read-write` and `read-only`,
+/// which pick the same server unless `default_transaction_read_only` is on.
+fn session_attrs_option(attrs: &str) -> &str {
+    match attrs {
+        "primary" => "read-write",
+        "standby" => "read-only",
+        other => other,
+    }
+}
+
 /// Helper to get TIME values
 fn try_get_time(row: &Row, index: usize) -> String {
     if let Ok(Some(v)) = row.try_get::<_, Option<NaiveTime>>(index) {
@@ -1279,3 +1797,197 @@ fn try_get_time(row: &Row, index: usize) -> String {
     // Fallback to string
     try_get::<String>(row, index)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tokio_postgres::config::Host;
+
+    use super::*;
+    use crate::db::connection::parse_postgres_url;
+
+    #[test]
+    fn passes_search_path_as_escaped_server_option() {
+        let postgres = Postgres {
+            user: "app".to_string(),
+            database: "db".to_string(),
+            search_path: Some(r#"app, "it's""#.to_string()),
+            ..Postgres::default()
+        };
+        let config =
+            tokio_postgres::Config::from_str(&postgres.connection_config())
+                .unwrap();
+        assert_eq!(
+            config.get_options(),
+            Some(r#"-c search_path=app,\ "it's""#)
+        );
+    }
+
+    #[test]
+    fn passes_host_lists_and_session_attrs() {
+        let postgres = Postgres {
+            host: Some("db1,::1".to_string()),
+            port: Some("5432,5433".to_string()),
+            user: "app".to_string(),
+            database: "db".to_string(),
+            target_session_attrs: Some("primary".to_string()),
+            ..Postgres::default()
+        };
+        let config =
+            tokio_postgres::Config::from_str(&postgres.connection_config())
+                .unwrap();
+        assert_eq!(config.get_hosts().len(), 2);
+        assert_eq!(config.get_ports(), [5432, 5433]);
+        assert_eq!(
+            config.get_target_session_attrs(),
+            tokio_postgres::config::TargetSessionAttrs::ReadWrite
+        );
+    }
+
+    #[test]
+    fn passes_bracketed_ipv6_host_lists() {
+        let (host, port, user, database) = parse_postgres_url(
+            "postgres://app@[::1]:5433,[fe80::1]:5434/orders",
+        );
+        let postgres = Postgres {
+            host: Some(host),
+            port: Some(port),
+            user,
+            database,
+            ..Postgres::default()
+        };
+        let config =
+            tokio_postgres::Config::from_str(&postgres.connection_config())
+                .unwrap();
+        assert_eq!(
+            config.get_hosts(),
+            [
+                Host::Tcp("::1".to_string()),
+                Host::Tcp("fe80::1".to_string())
+            ]
+        );
+        assert_eq!(config.get_ports(), [5433, 5434]);
+        assert_eq!(config.get_dbname(), Some("orders"));
+    }
+
+    #[test]
+    fn quotes_passwords_and_names() {
+        for password in ["two words", r"it's", r"back\slash", "user=x"] {
+            let postgres = Postgres {
+                user: "app user".to_string(),
+                database: "o'db".to_string(),
+                password: password.to_string(),
+                ..Postgres::default()
+            };
+            let config =
+                tokio_postgres::Config::from_str(&postgres.connection_config())
+                    .unwrap();
+            assert_eq!(config.get_password(), Some(password.as_bytes()));
+            assert_eq!(config.get_user(), Some("app user"));
+            assert_eq!(config.get_dbname(), Some("o'db"));
+        }
+    }
+
+    #[tokio::test]
+    async fn is_idle_before_the_session_connects() {
+        // Nothing listens on port 1, so connecting would fail
+        let postgres = Postgres {
+            host: Some("127.0.0.1".to_string()),
+            port: Some("1".to_string()),
+            ..Postgres::default()
+        };
+        let clone = postgres.clone();
+        assert_eq!(
+            clone.transaction_status().await.unwrap(),
+            Some(TransactionStatus::Idle)
+        );
+        assert!(postgres.session.try_get().unwrap().is_none());
+        assert!(clone.execute_in_session("SELECT 1").await.is_err());
+    }
+
+    /// The database of `docker-compose.yml`, started with `just docker-up`.
+    fn compose_database() -> Postgres {
+        Postgres {
+            host: Some("localhost".to_string()),
+            port: Some("5432".to_string()),
+            user: "d7s_user".to_string(),
+            password: "d7s_password".to_string(),
+            database: "d7s_test".to_string(),
+            ..Postgres::default()
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs the database from `just docker-up`"]
+    async fn frees_the_session_when_a_query_is_cut_off() {
+        let postgres = compose_database();
+        let started = std::time::Instant::now();
+
+        let limit = std::time::Duration::from_millis(200);
+        let time_box = TimeBox {
+            max_time: Some(limit),
+            max_rows: None,
+        };
+        let result = postgres
+            .execute_sql_within("SELECT pg_sleep(30)", time_box)
+            .await
+            .unwrap();
+        assert_eq!(result.cutoff, Some(Cutoff::Time(limit)));
+
+        let time_box = TimeBox {
+            max_time: None,
+            max_rows: Some(2),
+        };
+        let result = postgres
+            .execute_sql_within(
+                // Wide rows fill the server's send buffer, so each is sent
+                // as it is produced
+                "SELECT repeat('x', 10000), pg_sleep(0.01)
+                    FROM generate_series(1, 3000)",
+                time_box,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.cutoff, Some(Cutoff::Rows(2)));
+        assert_eq!(result.rows.len(), 2);
+
+        let rows = postgres.execute_in_session("SELECT 1").await.unwrap();
+        assert_eq!(rows.first().unwrap().values, ["1"]);
+        assert_eq!(
+            postgres.transaction_status().await.unwrap(),
+            Some(TransactionStatus::Idle)
+        );
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs the database from `just docker-up`"]
+    async fn changes_the_search_path_within_a_transaction() {
+        let postgres = compose_database();
+        postgres
+            .batch_execute_in_session("BEGIN; SAVEPOINT sp1")
+            .await
+            .unwrap();
+
+        let changed = postgres
+            .with_search_path(Some("pg_catalog"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            changed.transaction_status().await.unwrap(),
+            Some(TransactionStatus::InTransaction)
+        );
+        let rows = changed.execute_in_session("SHOW search_path").await;
+        assert_eq!(rows.unwrap().first().unwrap().values, ["pg_catalog"]);
+        assert_eq!(
+            changed.get_search_path().await.unwrap().as_deref(),
+            Some("pg_catalog")
+        );
+        changed
+            .batch_execute_in_session("ROLLBACK TO SAVEPOINT sp1; ROLLBACK")
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,90 @@
+use serde_json::Value;
+
+use crate::db::column_stats::format_number;
+
+/// The planner's estimate for a statement, read from `EXPLAIN`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueryEstimate {
+    /// Total cost in the planner's arbitrary units.
+    pub cost: f64,
+    pub rows: f64,
+}
+
+impl QueryEstimate {
+    /// Read the top plan node of `EXPLAIN (FORMAT JSON)` output.
+    #[must_use]
+    pub fn from_json_plan(plan: &str) -> Option<Self> {
+        let plan = serde_json::from_str::<Value>(plan).ok()?;
+        let node = plan.get(0)?.get("Plan")?;
+        Some(Self {
+            cost: node.get("Total Cost")?.as_f64()?,
+            rows: node.get("Plan Rows")?.as_f64()?,
+        })
+    }
+
+    /// One line for the warning modal.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        format!(
+            "est. cost {}, ~{} rows",
+            format_number(self.cost),
+            format_number(self.rows)
+        )
+    }
+}
+
+/// Limits above which interactive queries ask before running. Both unset
+/// means no `EXPLAIN` is run at all.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ExplainThresholds {
+    pub max_cost: Option<f64>,
+    pub max_rows: Option<f64>,
+}
+
+impl ExplainThresholds {
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.max_cost.is_some() || self.max_rows.is_some()
+    }
+
+    /// Whether `estimate` goes over either limit.
+    #[must_use]
+    pub fn exceeded_by(&self, estimate: &QueryEstimate) -> bool {
+        self.max_cost.is_some_and(|max| estimate.cost > max)
+            || self.max_rows.is_some_and(|max| estimate.rows > max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_top_plan_node() {
+        let plan = r#"[{"Plan": {"Node Type": "Seq Scan", "Startup Cost": 0.0,
+            "Total Cost": 18334.5, "Plan Rows": 1000000, "Plan Width": 8}}]"#;
+        let estimate = QueryEstimate::from_json_plan(plan).unwrap();
+        assert!((estimate.rows - 1_000_000.0).abs() < f64::EPSILON);
+        assert_eq!(estimate.summary(), "est. cost 18334.5, ~1000000 rows");
+        assert!(QueryEstimate::from_json_plan("Seq Scan on t").is_none());
+    }
+
+    #[test]
+    fn thresholds_trip_on_either_limit() {
+        let estimate = QueryEstimate {
+            cost: 500.0,
+            rows: 20.0,
+        };
+        let rows_only = ExplainThresholds {
+            max_cost: None,
+            max_rows: Some(10.0),
+        };
+        assert!(rows_only.exceeded_by(&estimate));
+        let cost_only = ExplainThresholds {
+            max_cost: Some(1000.0),
+            max_rows: None,
+        };
+        assert!(!cost_only.exceeded_by(&estimate));
+        assert!(!ExplainThresholds::default().is_enabled());
+    }
+}
@@ -0,0 +1,184 @@
+//! Plans from `EXPLAIN ANALYZE`, flattened to one line per node so two runs
+//! of a query can be compared node by node.
+
+use serde_json::Value;
+
+/// One node of an executed plan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanNode {
+    /// Nesting level below the top node.
+    pub depth: usize,
+    /// Node type with its index and relation, e.g.
+    /// `Index Scan using orders_pkey on orders`.
+    pub label: String,
+    /// Time spent in the node over all its loops.
+    pub time_ms: Option<f64>,
+    /// Rows the node returned over all its loops.
+    pub rows: Option<f64>,
+}
+
+/// An executed plan, top node first and children after their parent.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryPlan {
+    pub nodes: Vec<PlanNode>,
+    pub planning_ms: Option<f64>,
+    pub execution_ms: Option<f64>,
+}
+
+impl QueryPlan {
+    /// Read the output of `EXPLAIN (ANALYZE, FORMAT JSON)`.
+    #[must_use]
+    pub fn from_json_plan(plan: &str) -> Option<Self> {
+        let plan = serde_json::from_str::<Value>(plan).ok()?;
+        let plan = plan.get(0)?;
+        let mut nodes = Vec::new();
+        flatten(plan.get("Plan")?, 0, &mut nodes);
+        Some(Self {
+            nodes,
+            planning_ms: plan.get("Planning Time").and_then(Value::as_f64),
+            execution_ms: plan.get("Execution Time").and_then(Value::as_f64),
+        })
+    }
+}
+
+fn flatten(node: &Value, depth: usize, nodes: &mut Vec<PlanNode>) {
+    let text = |key: &str| node.get(key).and_then(Value::as_str);
+    let number = |key: &str| node.get(key).and_then(Value::as_f64);
+    let mut label = text("Node Type").unwrap_or("?").to_string();
+    if let Some(index) = text("Index Name") {
+        label = format!("{label} using {index}");
+    }
+    if let Some(relation) = text("Relation Name") {
+        label = format!("{label} on {relation}");
+    }
+    // Times and rows are averages per loop
+    let loops = number("Actual Loops").unwrap_or(1.0);
+    nodes.push(PlanNode {
+        depth,
+        label,
+        time_ms: number("Actual Total Time").map(|time| time * loops),
+        rows: number("Actual Rows").map(|rows| rows * loops),
+    });
+    for child in node
+        .get("Plans")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        flatten(child, depth + 1, nodes);
+    }
+}
+
+/// Nodes of two plans paired up: the longest run of nodes both plans have
+/// at the same depth, with the rest on their own.
+#[must_use]
+pub fn align_plans<'a>(
+    baseline: &'a QueryPlan,
+    current: &'a QueryPlan,
+) -> Vec<(Option<&'a PlanNode>, Option<&'a PlanNode>)> {
+    let (left, right) = (&baseline.nodes, &current.nodes);
+    let same = |i: usize, j: usize| {
+        left.get(i)
+            .zip(right.get(j))
+            .is_some_and(|(a, b)| a.depth == b.depth && a.label == b.label)
+    };
+
+    // Longest common subsequence of the suffixes starting at (i, j)
+    let width = right.len() + 1;
+    let mut common = vec![0_usize; (left.len() + 1) * width];
+    let at = |common: &[usize], i: usize, j: usize| {
+        common.get(i * width + j).copied().unwrap_or_default()
+    };
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            let length = if same(i, j) {
+                at(&common, i + 1, j + 1) + 1
+            } else {
+                at(&common, i + 1, j).max(at(&common, i, j + 1))
+            };
+            if let Some(cell) = common.get_mut(i * width + j) {
+                *cell = length;
+            }
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        if same(i, j) {
+            pairs.push((left.get(i), right.get(j)));
+            i += 1;
+            j += 1;
+        } else if at(&common, i + 1, j) >= at(&common, i, j + 1) {
+            pairs.push((left.get(i), None));
+            i += 1;
+        } else {
+            pairs.push((None, right.get(j)));
+            j += 1;
+        }
+    }
+    pairs.extend(left.iter().skip(i).map(|node| (Some(node), None)));
+    pairs.extend(right.iter().skip(j).map(|node| (None, Some(node))));
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEQ_SCAN: &str = r#"[{"Plan": {"Node Type": "Aggregate",
+        "Actual Total Time": 12.5, "Actual Rows": 1, "Actual Loops": 1,
+        "Plans": [{"Node Type": "Seq Scan", "Relation Name": "orders",
+            "Actual Total Time": 6.0, "Actual Rows": 40, "Actual Loops": 2}]},
+        "Planning Time": 0.2, "Execution Time": 12.9}]"#;
+
+    #[test]
+    fn flattens_nodes_with_totals_over_loops() {
+        let plan = QueryPlan::from_json_plan(SEQ_SCAN);
+        let nodes = plan.as_ref().map(|plan| plan.nodes.clone());
+        assert_eq!(
+            nodes,
+            Some(vec![
+                PlanNode {
+                    depth: 0,
+                    label: "Aggregate".to_string(),
+                    time_ms: Some(12.5),
+                    rows: Some(1.0),
+                },
+                PlanNode {
+                    depth: 1,
+                    label: "Seq Scan on orders".to_string(),
+                    time_ms: Some(12.0),
+                    rows: Some(80.0),
+                },
+            ])
+        );
+        assert_eq!(plan.and_then(|plan| plan.execution_ms), Some(12.9));
+    }
+
+    #[test]
+    fn pairs_matching_nodes_and_keeps_the_rest() {
+        let baseline = QueryPlan::from_json_plan(SEQ_SCAN).unwrap_or_default();
+        let mut current = baseline.clone();
+        if let Some(scan) = current.nodes.get_mut(1) {
+            scan.label = "Index Scan using orders_idx on orders".to_string();
+        }
+        let labels = align_plans(&baseline, &current)
+            .into_iter()
+            .map(|(left, right)| {
+                (
+                    left.map(|n| n.label.as_str()),
+                    right.map(|n| n.label.as_str()),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            [
+                (Some("Aggregate"), Some("Aggregate")),
+                (Some("Seq Scan on orders"), None),
+                (None, Some("Index Scan using orders_idx on orders")),
+            ]
+        );
+    }
+}
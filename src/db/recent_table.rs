@@ -0,0 +1,22 @@
+/// A table opened for data view, remembered per connection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecentTable {
+    /// Database the table lives in; `None` for backends without a
+    /// Databases step (`SQLite`).
+    pub database: Option<String>,
+    pub schema_name: String,
+    pub table_name: String,
+}
+
+impl RecentTable {
+    /// One-line description for the Recent list.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        self.database.as_ref().map_or_else(
+            || format!("{}.{}", self.schema_name, self.table_name),
+            |database| {
+                format!("{database}: {}.{}", self.schema_name, self.table_name)
+            },
+        )
+    }
+}
@@ -0,0 +1,121 @@
+/// Table column a result column was read from, as the server's row
+/// description gives it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnSource {
+    pub table_oid: u32,
+    /// Attribute number of the column within the table, from 1
+    pub column_id: i16,
+}
+
+/// The one table a result set was read from, with the table column behind
+/// each result column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultOrigin {
+    pub schema_name: String,
+    pub table_name: String,
+    /// Table column of each result column; `None` for computed ones.
+    pub columns: Vec<Option<String>>,
+}
+
+impl ResultOrigin {
+    /// Match `sources` against catalog rows of their table, each
+    /// `[schema, table, attnum, attname]`. `None` when the table is gone.
+    #[must_use]
+    pub fn from_catalog_rows<I>(
+        sources: &[Option<ColumnSource>],
+        rows: I,
+    ) -> Option<Self>
+    where
+        I: IntoIterator<Item = [String; 4]>,
+    {
+        let table_oid = single_source_table(sources)?;
+        let mut rows = rows.into_iter().peekable();
+        let [schema_name, table_name, ..] = rows.peek()?.clone();
+        let attributes = rows
+            .filter_map(|[_, _, attnum, attname]| {
+                Some((attnum.parse::<i16>().ok()?, attname))
+            })
+            .collect::<Vec<_>>();
+        let columns = sources
+            .iter()
+            .map(|source| {
+                let source = source.filter(|s| s.table_oid == table_oid)?;
+                attributes
+                    .iter()
+                    .find(|(attnum, _)| *attnum == source.column_id)
+                    .map(|(_, name)| name.clone())
+            })
+            .collect();
+        Some(Self {
+            schema_name,
+            table_name,
+            columns,
+        })
+    }
+
+    /// Result column read from table column `name`.
+    #[must_use]
+    pub fn result_column(&self, name: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|column| column.as_deref() == Some(name))
+    }
+}
+
+/// The table every column with a known source comes from, when there is
+/// exactly one; results of joins have several.
+#[must_use]
+pub fn single_source_table(sources: &[Option<ColumnSource>]) -> Option<u32> {
+    let mut tables = sources.iter().flatten().map(|s| s.table_oid);
+    let first = tables.next()?;
+    tables.all(|oid| oid == first).then_some(first)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(table_oid: u32, column_id: i16) -> Option<ColumnSource> {
+        Some(ColumnSource {
+            table_oid,
+            column_id,
+        })
+    }
+
+    #[test]
+    fn finds_the_single_source_table() {
+        assert_eq!(single_source_table(&[]), None);
+        assert_eq!(single_source_table(&[None, None]), None);
+        assert_eq!(
+            single_source_table(&[source(16384, 2), None, source(16384, 1)]),
+            Some(16384)
+        );
+        assert_eq!(
+            single_source_table(&[source(16384, 1), source(16390, 1)]),
+            None
+        );
+    }
+
+    #[test]
+    fn names_the_table_columns_behind_result_columns() {
+        let row = |attnum: &str, attname: &str| {
+            ["public", "orders", attnum, attname].map(str::to_string)
+        };
+        let sources = [source(16384, 3), None, source(16384, 1)];
+        let origin = ResultOrigin::from_catalog_rows(
+            &sources,
+            [row("1", "id"), row("2", "customer"), row("3", "total")],
+        )
+        .unwrap();
+        assert_eq!(origin.schema_name, "public");
+        assert_eq!(origin.table_name, "orders");
+        assert_eq!(
+            origin.columns,
+            [Some("total".to_string()), None, Some("id".to_string())]
+        );
+        assert_eq!(origin.result_column("id"), Some(2));
+        assert_eq!(origin.result_column("customer"), None);
+
+        assert_eq!(ResultOrigin::from_catalog_rows(&sources, []), None);
+    }
+}
@@ -0,0 +1,64 @@
+/// One column of two rows side by side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnDiff {
+    pub column: String,
+    pub left: String,
+    pub right: String,
+}
+
+impl ColumnDiff {
+    #[must_use]
+    pub fn differs(&self) -> bool {
+        self.left != self.right
+    }
+
+    /// The values differ only in whitespace or control characters, so they
+    /// look the same when printed as is.
+    #[must_use]
+    pub fn differs_invisibly(&self) -> bool {
+        let visible = |value: &str| {
+            value
+                .chars()
+                .filter(|c| !c.is_whitespace() && !c.is_control())
+                .collect::<String>()
+        };
+        self.differs() && visible(&self.left) == visible(&self.right)
+    }
+}
+
+/// Compare two rows column by column; missing cells count as empty.
+#[must_use]
+pub fn diff_rows(
+    columns: &[String],
+    left: &[String],
+    right: &[String],
+) -> Vec<ColumnDiff> {
+    columns
+        .iter()
+        .enumerate()
+        .map(|(idx, column)| ColumnDiff {
+            column: column.clone(),
+            left: left.get(idx).cloned().unwrap_or_default(),
+            right: right.get(idx).cloned().unwrap_or_default(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_differing_and_invisibly_differing_columns() {
+        let columns = ["id", "name", "code"].map(String::from);
+        let left = ["1", "Ann", "AB"].map(String::from);
+        let right = ["2", "Ann", "AB "].map(String::from);
+        let diffs = diff_rows(&columns, &left, &right);
+
+        let flags = diffs
+            .iter()
+            .map(|d| (d.differs(), d.differs_invisibly()))
+            .collect::<Vec<_>>();
+        assert_eq!(flags, [(true, false), (false, false), (true, true)]);
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+
+use crate::row_lookup::key_indices;
+
+/// A note on one row of a table. Stored per connection in the local
+/// database; the table itself is never written to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RowNote {
+    /// Primary key columns of the table when the note was written.
+    pub key_columns: Vec<String>,
+    /// The row's values in those columns.
+    pub key: Vec<String>,
+    pub note: String,
+}
+
+/// Notes on the rows of a table, by primary key value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RowNotes {
+    /// Primary key columns the notes are keyed by.
+    pub key_columns: Vec<String>,
+    notes: BTreeMap<Vec<String>, String>,
+}
+
+impl RowNotes {
+    /// The notes read back for a table. Notes keyed by other columns than
+    /// the first, written before the primary key changed, are left out.
+    #[must_use]
+    pub fn new(notes: Vec<RowNote>) -> Self {
+        let key_columns = notes
+            .first()
+            .map(|note| note.key_columns.clone())
+            .unwrap_or_default();
+        let notes = notes
+            .into_iter()
+            .filter(|note| note.key_columns == key_columns)
+            .map(|note| (note.key, note.note))
+            .collect();
+        Self { key_columns, notes }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    /// The note on the row with the key values `key`.
+    #[must_use]
+    pub fn get(&self, key: &[String]) -> Option<&str> {
+        self.notes.get(key).map(String::as_str)
+    }
+
+    /// Note `text` on the row with the key values `key`, or remove its note
+    /// when `text` is empty. Notes keyed by other columns are dropped.
+    pub fn set(
+        &mut self,
+        key_columns: &[String],
+        key: Vec<String>,
+        text: &str,
+    ) {
+        if self.key_columns != key_columns {
+            self.key_columns = key_columns.to_vec();
+            self.notes.clear();
+        }
+        if text.is_empty() {
+            self.notes.remove(&key);
+        } else {
+            self.notes.insert(key, text.to_string());
+        }
+    }
+
+    /// Indices of the key columns among `column_names`; `None` without
+    /// notes or when a key column isn't there.
+    #[must_use]
+    pub fn key_indices(&self, column_names: &[String]) -> Option<Vec<usize>> {
+        if self.is_empty() {
+            return None;
+        }
+        key_indices(&self.key_columns, column_names)
+    }
+
+    /// The note on `row`, its key columns being at `indices`.
+    #[must_use]
+    pub fn note_on(&self, indices: &[usize], row: &[String]) -> Option<&str> {
+        let key = indices
+            .iter()
+            .map(|&idx| row.get(idx).cloned())
+            .collect::<Option<Vec<_>>>()?;
+        self.get(&key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn finds_notes_by_key() {
+        let mut notes = RowNotes::new(vec![
+            RowNote {
+                key_columns: strings(&["id"]),
+                key: strings(&["7"]),
+                note: "double charged".to_string(),
+            },
+            RowNote {
+                key_columns: strings(&["uuid"]),
+                key: strings(&["a1"]),
+                note: "old key".to_string(),
+            },
+        ]);
+        assert_eq!(notes.get(&strings(&["a1"])), None);
+        let columns = strings(&["name", "ID"]);
+        let indices = notes.key_indices(&columns).unwrap();
+        assert_eq!(indices, [1]);
+        assert_eq!(
+            notes.note_on(&indices, &strings(&["ann", "7"])),
+            Some("double charged")
+        );
+        assert_eq!(notes.note_on(&indices, &strings(&["bob", "8"])), None);
+
+        notes.set(&strings(&["id"]), strings(&["7"]), "");
+        assert!(notes.is_empty());
+        assert_eq!(notes.key_indices(&columns), None);
+        notes.set(&strings(&["org", "id"]), strings(&["1", "2"]), "check");
+        assert_eq!(notes.get(&strings(&["1", "2"])), Some("check"));
+        assert_eq!(notes.key_columns, strings(&["org", "id"]));
+    }
+}
@@ -0,0 +1,37 @@
+use std::fmt::Write;
+
+use crate::sort::TableOrder;
+
+/// A saved table-data view: which table to open and how to present it.
+/// Stored per connection in the local database.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SavedView {
+    pub name: String,
+    pub schema_name: String,
+    pub table_name: String,
+    /// Search filter applied after the rows are loaded.
+    pub filter: Option<String>,
+    /// Column names hidden from the grid.
+    pub hidden_columns: Vec<String>,
+    /// Server-side order the rows are read in.
+    pub order: Option<TableOrder>,
+}
+
+impl SavedView {
+    /// One-line description for the Views list.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let mut summary =
+            format!("{}  {}.{}", self.name, self.schema_name, self.table_name);
+        if let Some(filter) = &self.filter {
+            let _ = write!(summary, "  /{filter}");
+        }
+        if !self.hidden_columns.is_empty() {
+            let _ = write!(summary, "  (-{} cols)", self.hidden_columns.len());
+        }
+        if let Some(order) = &self.order {
+            let _ = write!(summary, "  order {}", order.describe());
+        }
+        summary
+    }
+}
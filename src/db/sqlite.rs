@@ -1,31 +1,73 @@
-use std::{collections::HashMap, fmt::Write};
-
-use color_eyre::Result;
-use rusqlite::{Connection as SqliteConnection, params};
-use rusqlite_migration::{M, Migrations};
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fmt::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, mpsc},
+    thread,
+    time::Duration,
+};
 
-use crate::db::{
-    Column, Database, DatabaseInfo, DbRowId, Schema, Table, TableData,
-    TableDataPage, TableRow,
-    connection::{Connection, ConnectionType, Environment},
-    get_db_path, should_omit_for_insert_default,
+use color_eyre::{Result, eyre::eyre};
+use rusqlite::{
+    Connection as SqliteConnection, ErrorCode, OptionalExtension,
+    functions::FunctionFlags, params,
 };
+use rusqlite_migration::{M, Migrations, SchemaVersion};
+
+use crate::{
+    db::{
+        Column, ConnectError, Database, DatabaseInfo, DbRowId, Schema, Table,
+        TableData, TableDataPage, TableKind, TableRow, TransactionStatus,
+        backup::{backup_store, file_timestamp},
+        connection::{Connection, ConnectionType, Environment},
+        ddl::SchemaDdl,
+        dialect::{Dialect, SQLITE_CHECKSUM_FUNCTION, SqliteDialect},
+        foreign_key::{ForeignKey, ForeignKeyKind},
+        get_db_path,
+        keyset::KeysetPage,
+        recent_table::RecentTable,
+        row_note::RowNote,
+        saved_view::SavedView,
+        should_omit_for_insert_default,
+        table_compare::value_checksum,
+        time_box::{Cutoff, TimeBox, TimeBoxedRows},
+    },
+    sort::{NullsOrder, SortDirection, TableOrder},
+};
+
+/// Name of the database file the connection was opened with.
+pub const SQLITE_MAIN_SCHEMA: &str = "main";
+
+const DIALECT: SqliteDialect = SqliteDialect;
+
+/// Attached database name for a schema from the explorer. Older callers used
+/// `sqlite_schema` for the only database, which is `main`.
+fn sqlite_schema_name(schema_name: &str) -> &str {
+    if schema_name.is_empty() || schema_name == "sqlite_schema" {
+        SQLITE_MAIN_SCHEMA
+    } else {
+        schema_name
+    }
+}
 
-fn sqlite_quote_ident(ident: &str) -> String {
-    format!(r#""{}""#, ident.replace('"', "\"\""))
+/// `"schema"."table"` for statements that target an attached database.
+fn sqlite_qualified_table(schema_name: &str, table_name: &str) -> String {
+    DIALECT.qualified_table(sqlite_schema_name(schema_name), table_name)
 }
 
 fn sqlite_table_decltypes(
     conn: &SqliteConnection,
+    schema_name: &str,
     table_name: &str,
 ) -> Result<HashMap<String, String>, rusqlite::Error> {
-    let mut stmt =
-        conn.prepare(&format!("PRAGMA table_info('{table_name}')"))?;
+    let mut stmt = conn.prepare(DIALECT.columns_query())?;
     let mut m = HashMap::new();
-    let mut rows = stmt.query([])?;
+    let mut rows =
+        stmt.query(params![table_name, sqlite_schema_name(schema_name)])?;
     while let Some(row) = rows.next()? {
-        let name: String = row.get(1)?;
-        let decl: String = row.get::<_, Option<String>>(2)?.unwrap_or_default();
+        let name: String = row.get(0)?;
+        let decl: String = row.get::<_, Option<String>>(1)?.unwrap_or_default();
         m.insert(name, decl);
     }
     Ok(m)
@@ -77,6 +119,8 @@ fn sqlite_cast_keyword(decl: &str) -> &'static str {
 pub struct Sqlite {
     pub name: String,
     pub path: String,
+    /// Opened on first use and kept so `ATTACH DATABASE` outlives a statement.
+    pub connection: Mutex<Option<SqliteConnection>>,
 }
 
 impl TableData for Sqlite {
@@ -99,129 +143,203 @@ impl TableData for Sqlite {
 
 #[async_trait::async_trait]
 impl Database for Sqlite {
-    async fn test(&self) -> bool {
-        SqliteConnection::open(&self.path).is_ok()
+    fn dialect(&self) -> &'static dyn Dialect {
+        &DIALECT
+    }
+
+    async fn check_connection(&self) -> Result<(), ConnectError> {
+        self.with_connection(|_| Ok(())).map_err(|e| ConnectError {
+            message: e.to_string(),
+            transient: false,
+        })
+    }
+
+    async fn transaction_status(
+        &self,
+    ) -> Result<Option<TransactionStatus>, Box<dyn std::error::Error>> {
+        // SQLite keeps a transaction open through a failed statement, so
+        // it's never aborted
+        self.with_connection(|conn| {
+            Ok(Some(if conn.is_autocommit() {
+                TransactionStatus::Idle
+            } else {
+                TransactionStatus::InTransaction
+            }))
+        })
     }
 
     async fn execute_sql(
         &self,
         sql: &str,
     ) -> Result<Vec<TableRow>, Box<dyn std::error::Error>> {
-        // rusqlite is synchronous, so we just run it in the async context
-        let client = self.get_connection()?;
-
-        // Try to prepare the statement
-        let mut stmt = client.prepare(sql)?;
-
-        // Try to get column names
-        let column_names: Vec<String> = stmt
-            .column_names()
-            .iter()
-            .map(|s| (*s).to_string())
-            .collect();
-
-        let mut result = Vec::new();
-
-        // Try to query for rows
-        let mut rows_iter = stmt.query([])?;
+        self.execute_sql_within(sql, TimeBox::default())
+            .await
+            .map(|read| read.rows)
+    }
 
-        let mut found_row = false;
-        while let Some(row) = rows_iter.next()? {
-            found_row = true;
-            let mut values = Vec::new();
-            for i in 0..column_names.len() {
-                let value = convert_sqlite_value_to_string(row, i);
-                values.push(value);
+    async fn execute_sql_within(
+        &self,
+        sql: &str,
+        time_box: TimeBox,
+    ) -> Result<TimeBoxedRows, Box<dyn std::error::Error>> {
+        // rusqlite is synchronous, so we just run it in the async context
+        self.with_connection(|client| {
+            let mut stmt = client.prepare(sql)?;
+
+            // Statements without result columns (INSERT/UPDATE/ATTACH/...)
+            // must only be stepped once
+            if stmt.column_count() == 0 {
+                let affected_rows = stmt.execute([])?;
+                return Ok(TimeBoxedRows {
+                    rows: vec![TableRow {
+                        values: vec![format!("Affected rows: {affected_rows}")],
+                        column_names: vec!["Result".to_string()],
+                        column_types: Vec::new(),
+                        column_sources: Vec::new(),
+                    }],
+                    cutoff: None,
+                });
             }
-            result.push(TableRow {
-                values,
-                column_names: column_names.clone(),
-            });
-        }
 
-        // If no rows, treat as an execute (e.g. INSERT/UPDATE/DELETE)
-        if !found_row {
-            let affected_rows = client.execute(sql, [])?;
-            result.push(TableRow {
-                values: vec![format!("Affected rows: {}", affected_rows)],
-                column_names: vec!["Result".to_string()],
-            });
-        }
+            let column_names: Vec<String> = stmt
+                .column_names()
+                .iter()
+                .map(|s| (*s).to_string())
+                .collect();
+
+            let mut result = TimeBoxedRows::default();
+            let mut rows_iter = stmt.query([])?;
+            interrupt_after(client, time_box.max_time, || {
+                loop {
+                    let row = match rows_iter.next() {
+                        Ok(Some(row)) => row,
+                        Ok(None) => break,
+                        Err(e)
+                            if e.sqlite_error_code()
+                                == Some(ErrorCode::OperationInterrupted) =>
+                        {
+                            result.cutoff = time_box.max_time.map(Cutoff::Time);
+                            break;
+                        }
+                        Err(e) => return Err(e),
+                    };
+                    if time_box.rows_exceeded(result.rows.len() + 1) {
+                        result.cutoff = time_box.max_rows.map(Cutoff::Rows);
+                        break;
+                    }
+                    let values = (0..column_names.len())
+                        .map(|i| convert_sqlite_value_to_string(row, i))
+                        .collect();
+                    result.rows.push(TableRow {
+                        values,
+                        column_names: column_names.clone(),
+                        column_types: Vec::new(),
+                        column_sources: Vec::new(),
+                    });
+                }
+                Ok(())
+            })?;
 
-        Ok(result)
+            Ok(result)
+        })
     }
 
+    /// `main`, `temp` (once used) and every attached database.
     async fn get_schemas(
         &self,
     ) -> Result<Vec<Schema>, Box<dyn std::error::Error>> {
-        Ok(vec![Schema {
-            name: "sqlite_schema".to_string(),
-            owner: String::new(),
-        }])
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(DIALECT.schemas_query())?;
+            let schemas = stmt
+                .query_map([], |row| {
+                    Ok(Schema {
+                        name: row.get(1)?,
+                        owner: row
+                            .get::<_, Option<String>>(2)?
+                            .unwrap_or_default(),
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(schemas)
+        })
     }
 
     async fn get_tables(
         &self,
         schema_name: &str,
     ) -> Result<Vec<Table>, Box<dyn std::error::Error>> {
-        let conn = SqliteConnection::open(&self.path)?;
-
-        let mut stmt = conn.prepare(&format!(
-            "SELECT name FROM {schema_name} WHERE type='table';"
-        ))?;
-        let tables = stmt
-            .query_map([], |row| {
-                let name: String = row.get(0)?;
-
-                // TODO can probably query the whole db, store it in a hashmap and then key access it here
-                let mut size_stmt = conn.prepare(&format!(
-                    r#"SELECT SUM("pgsize") FROM "dbstat" WHERE name='{name}';"#
-                ))?;
-                let size: u32 = size_stmt.query_one([], |row| row.get(0))?;
-
-                Ok(Table {
-                    name,
-                    schema: schema_name.to_string(),
-                    size: Some(size.to_string()),
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
+        let schema_name = sqlite_schema_name(schema_name);
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(DIALECT.tables_query())?;
+            // dbstat is not available for every database (e.g. `temp`)
+            let mut size_stmt = conn
+                .prepare(
+                    r#"SELECT SUM("pgsize") FROM "dbstat"(?1) WHERE name = ?2;"#,
+                )
+                .ok();
+            let tables = stmt
+                .query_map(params![schema_name], |row| {
+                    let name: String = row.get(0)?;
+                    let kind: String = row.get(1)?;
+
+                    let size = size_stmt.as_mut().and_then(|size_stmt| {
+                        size_stmt
+                            .query_one(params![schema_name, name], |row| {
+                                row.get::<_, Option<i64>>(0)
+                            })
+                            .ok()
+                            .flatten()
+                    });
+
+                    Ok(Table {
+                        name,
+                        schema: schema_name.to_string(),
+                        size: size.map(|size| size.to_string()),
+                        kind: TableKind::from_code(&kind),
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(tables)
+            Ok(tables)
+        })
     }
 
     async fn get_columns(
         &self,
-        _schema_name: &str,
+        schema_name: &str,
         table_name: &str,
     ) -> Result<Vec<Column>, Box<dyn std::error::Error>> {
-        let conn = SqliteConnection::open(&self.path)?;
-
-        let mut stmt =
-            conn.prepare(&format!("PRAGMA table_info('{table_name}')"))?;
-        let columns = stmt
-            .query_map([], |row| {
-                let name: String = row.get(1)?;
-                let data_type: String = row.get(2)?;
-                let notnull: i64 = row.get(3)?;
-                let default_value: Option<String> = row.get(4)?;
-                Ok(Column {
-                    name,
-                    data_type,
-                    is_nullable: notnull == 0,
-                    default_value,
-                    description: None,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-
-        Ok(columns)
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(DIALECT.columns_query())?;
+            let columns = stmt
+                .query_map(
+                    params![table_name, sqlite_schema_name(schema_name)],
+                    |row| {
+                        let name: String = row.get(0)?;
+                        let data_type: String = row.get(1)?;
+                        let notnull: i64 = row.get(2)?;
+                        let default_value: Option<String> = row.get(3)?;
+                        Ok(Column {
+                            name,
+                            data_type,
+                            is_nullable: notnull == 0,
+                            default_value,
+                            description: None,
+                        })
+                    },
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(columns)
+        })
     }
 
     async fn get_table_data_page(
         &self,
         schema_name: &str,
         table_name: &str,
+        order: Option<&TableOrder>,
+        keyset: Option<&KeysetPage>,
         offset: u64,
         limit: u32,
     ) -> Result<TableDataPage, Box<dyn std::error::Error>> {
@@ -232,255 +350,349 @@ impl Database for Sqlite {
             .map(|col| col.name)
             .collect();
 
-        let conn = SqliteConnection::open(&self.path)?;
-
-        let column_count = columns.len();
-        let limit_i = i64::from(limit);
-        let offset_i = i64::try_from(offset).unwrap_or(i64::MAX);
-        let tq = sqlite_quote_ident(table_name);
-        let col_list = columns
-            .iter()
-            .map(|c| sqlite_quote_ident(c))
-            .collect::<Vec<_>>()
-            .join(", ");
-        let query_rowid =
-            format!("SELECT rowid, {col_list} FROM {tq} LIMIT ?1 OFFSET ?2");
-        let (data, row_ids) = if let Ok(mut stmt) = conn.prepare(&query_rowid) {
-            let mut row_ids = Vec::new();
-            let data = stmt
-                .query_map(params![limit_i, offset_i], |row| {
-                    let rid = row.get::<_, i64>(0)?;
-                    row_ids.push(Some(DbRowId::Sqlite(rid)));
-                    let values = (0..column_count)
-                        .map(|i| convert_sqlite_value_to_string(row, i + 1))
-                        .collect();
-                    Ok(values)
-                })?
-                .collect::<Result<Vec<_>, _>>()?;
-            (data, row_ids)
-        } else {
-            let mut stmt = conn.prepare(&format!(
-                "SELECT {col_list} FROM {tq} LIMIT ?1 OFFSET ?2"
-            ))?;
-            let data = stmt
-                .query_map(params![limit_i, offset_i], |row| {
-                    let values = (0..column_count)
-                        .map(|i| convert_sqlite_value_to_string(row, i))
-                        .collect();
-                    Ok(values)
-                })?
-                .collect::<Result<Vec<_>, _>>()?;
-            let row_ids = vec![None; data.len()];
-            (data, row_ids)
-        };
-
-        Ok(TableDataPage {
-            rows: data,
-            column_names: columns,
-            row_ids,
+        self.with_connection(|conn| {
+            let column_count = columns.len();
+            let limit_i = i64::from(limit);
+            let offset_i = i64::try_from(offset).unwrap_or(i64::MAX);
+            let tq = sqlite_qualified_table(schema_name, table_name);
+            let col_list = columns
+                .iter()
+                .map(|c| DIALECT.quote_ident(c))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let (filter, order_by) = match keyset {
+                Some(page) => (
+                    page.where_clause(&DIALECT),
+                    page.order_by_clause(&DIALECT),
+                ),
+                None => (
+                    String::new(),
+                    order
+                        .map(|order| DIALECT.order_by_clause(order))
+                        .unwrap_or_default(),
+                ),
+            };
+            let tail = [filter, order_by]
+                .into_iter()
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let paging = DIALECT.paging_clause(
+                &DIALECT.placeholder(1),
+                Some(&DIALECT.placeholder(2)),
+            );
+            let query_rowid = format!(
+                "SELECT rowid, {col_list} FROM {tq} {tail} {paging}"
+            );
+            let literal_paging = DIALECT.paging_clause(
+                &limit.to_string(),
+                Some(&offset.to_string()),
+            );
+            let (mut data, mut row_ids, with_rowid) = if let Ok(mut stmt) =
+                conn.prepare(&query_rowid)
+            {
+                let mut row_ids = Vec::new();
+                let data = stmt
+                    .query_map(params![limit_i, offset_i], |row| {
+                        let rid = row.get::<_, i64>(0)?;
+                        row_ids.push(Some(DbRowId::Sqlite(rid)));
+                        let values = (0..column_count)
+                            .map(|i| convert_sqlite_value_to_string(row, i + 1))
+                            .collect();
+                        Ok(values)
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                (data, row_ids, true)
+            } else {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {col_list} FROM {tq} {tail} {paging}"
+                ))?;
+                let data = stmt
+                    .query_map(params![limit_i, offset_i], |row| {
+                        let values = (0..column_count)
+                            .map(|i| convert_sqlite_value_to_string(row, i))
+                            .collect();
+                        Ok(values)
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                let row_ids = vec![None; data.len()];
+                (data, row_ids, false)
+            };
+            if keyset.is_some_and(KeysetPage::reads_backwards) {
+                data.reverse();
+                row_ids.reverse();
+            }
+            // Views and `WITHOUT ROWID` tables have no rowid
+            let rowid = if with_rowid { "rowid, " } else { "" };
+            let select =
+                format!("SELECT {rowid}{col_list} FROM {tq} {tail}");
+
+            Ok(TableDataPage {
+                rows: data,
+                column_names: columns,
+                row_ids,
+                sql: format!("{} {literal_paging}", select.trim_end()),
+            })
         })
     }
 
     async fn get_primary_key_columns(
         &self,
-        _schema_name: &str,
+        schema_name: &str,
         table_name: &str,
     ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-        let conn = SqliteConnection::open(&self.path)?;
-        let mut stmt =
-            conn.prepare(&format!("PRAGMA table_info('{table_name}')"))?;
-        let mut pk_cols: Vec<(i64, String)> = stmt
-            .query_map([], |row| {
-                let name: String = row.get(1)?;
-                let pk: i64 = row.get(5)?;
-                Ok((pk, name))
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-        pk_cols.retain(|(pk, _)| *pk > 0);
-        pk_cols.sort_by_key(|(pk, _)| *pk);
-        Ok(pk_cols.into_iter().map(|(_, n)| n).collect())
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(DIALECT.primary_key_query())?;
+            let pk_cols = stmt
+                .query_map(
+                    params![table_name, sqlite_schema_name(schema_name)],
+                    |row| row.get(0),
+                )?
+                .collect::<Result<Vec<String>, _>>()?;
+            Ok(pk_cols)
+        })
+    }
+
+    async fn get_foreign_keys(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<Vec<ForeignKey>, Box<dyn std::error::Error>> {
+        let schema_name = sqlite_schema_name(schema_name);
+        let rows = self.with_connection(|conn| {
+            let mut stmt = conn.prepare(DIALECT.foreign_keys_query())?;
+            let rows = stmt
+                .query_map(params![table_name, schema_name], |row| {
+                    Ok((
+                        [
+                            row.get::<_, String>(0)?,
+                            row.get(1)?,
+                            row.get(2)?,
+                            row.get(3)?,
+                        ],
+                        row.get::<_, Option<String>>(4)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })?;
+        let mut catalog_rows: Vec<([String; 5], ForeignKeyKind)> = Vec::new();
+        for ([name, column, parent_schema, parent_table], parent_column) in rows
+        {
+            // No `to` column: the parent's primary key, in order
+            let parent_column = if let Some(parent_column) = parent_column {
+                parent_column
+            } else {
+                let key = self
+                    .get_primary_key_columns(schema_name, &parent_table)
+                    .await?;
+                let position = catalog_rows
+                    .iter()
+                    .filter(|(row, _)| row.first() == Some(&name))
+                    .count();
+                key.get(position).cloned().unwrap_or_default()
+            };
+            catalog_rows.push((
+                [name, column, parent_schema, parent_table, parent_column],
+                ForeignKeyKind::Unvalidated,
+            ));
+        }
+        Ok(ForeignKey::from_catalog_rows(catalog_rows))
     }
 
     async fn update_table_cell(
         &self,
-        _schema_name: &str,
+        schema_name: &str,
         table_name: &str,
         set_column: &str,
         new_value: &str,
         primary_key: &[(String, String)],
         row_id_fallback: Option<DbRowId>,
     ) -> Result<u64, Box<dyn std::error::Error>> {
-        let conn = SqliteConnection::open(&self.path)?;
-        let decls = sqlite_table_decltypes(&conn, table_name)?;
-        let set_kw =
-            sqlite_cast_keyword(sqlite_resolve_decl(&decls, set_column));
-        let tq = sqlite_quote_ident(table_name);
-        let cq = sqlite_quote_ident(set_column);
-
-        if !primary_key.is_empty() {
-            let mut sql =
-                format!("UPDATE {tq} SET {cq} = CAST(?1 AS {set_kw}) WHERE ");
-            for (i, (k, _)) in primary_key.iter().enumerate() {
-                if i > 0 {
-                    sql.push_str(" AND ");
-                }
-                let param_num = i + 2;
-                let pk_kw = sqlite_cast_keyword(sqlite_resolve_decl(&decls, k));
-                let _ = write!(
-                    sql,
-                    "{} = CAST(?{param_num} AS {pk_kw})",
-                    sqlite_quote_ident(k)
+        self.with_connection(|conn| {
+            let decls = sqlite_table_decltypes(conn, schema_name, table_name)?;
+            let set_kw =
+                sqlite_cast_keyword(sqlite_resolve_decl(&decls, set_column));
+            let tq = sqlite_qualified_table(schema_name, table_name);
+            let cq = DIALECT.quote_ident(set_column);
+
+            if !primary_key.is_empty() {
+                let mut sql = format!(
+                    "UPDATE {tq} SET {cq} = CAST(?1 AS {set_kw}) WHERE "
                 );
+                for (i, (k, _)) in primary_key.iter().enumerate() {
+                    if i > 0 {
+                        sql.push_str(" AND ");
+                    }
+                    let param_num = i + 2;
+                    let pk_kw =
+                        sqlite_cast_keyword(sqlite_resolve_decl(&decls, k));
+                    let _ = write!(
+                        sql,
+                        "{} = CAST(?{param_num} AS {pk_kw})",
+                        DIALECT.quote_ident(k)
+                    );
+                }
+                let mut refs: Vec<&dyn rusqlite::ToSql> = Vec::new();
+                refs.push(&new_value);
+                for (_, v) in primary_key {
+                    refs.push(v);
+                }
+                let n = u64::try_from(conn.execute(&sql, refs.as_slice())?)
+                    .unwrap_or(0);
+                return Ok(n);
             }
-            let mut refs: Vec<&dyn rusqlite::ToSql> = Vec::new();
-            refs.push(&new_value);
-            for (_, v) in primary_key {
-                refs.push(v);
-            }
-            let n = u64::try_from(conn.execute(&sql, refs.as_slice())?)
-                .unwrap_or(0);
-            return Ok(n);
-        }
 
-        if let Some(DbRowId::Sqlite(rid)) = row_id_fallback {
-            let sql = format!(
-                "UPDATE {tq} SET {cq} = CAST(?1 AS {set_kw}) WHERE rowid = ?2"
-            );
-            let n = u64::try_from(conn.execute(&sql, params![new_value, rid])?)
-                .unwrap_or(0);
-            return Ok(n);
-        }
+            if let Some(DbRowId::Sqlite(rid)) = row_id_fallback {
+                let sql = format!(
+                    "UPDATE {tq} SET {cq} = CAST(?1 AS {set_kw}) WHERE rowid = ?2"
+                );
+                let n =
+                    u64::try_from(conn.execute(&sql, params![new_value, rid])?)
+                        .unwrap_or(0);
+                return Ok(n);
+            }
 
-        Err(
-            "Cannot update row: table has no usable primary key and rowid is not available"
-                .into(),
-        )
+            Err(
+                "Cannot update row: table has no usable primary key and rowid is not available"
+                    .into(),
+            )
+        })
     }
 
     async fn insert_table_row(
         &self,
-        _schema_name: &str,
+        schema_name: &str,
         table_name: &str,
         values: &[String],
     ) -> Result<u64, Box<dyn std::error::Error>> {
-        let columns = self.get_columns("sqlite_schema", table_name).await?;
+        let columns = self.get_columns(schema_name, table_name).await?;
         if values.len() != columns.len() {
             return Err("INSERT column count does not match table.".into());
         }
         let pk = self
-            .get_primary_key_columns("sqlite_schema", table_name)
+            .get_primary_key_columns(schema_name, table_name)
             .await
             .unwrap_or_default();
-        let conn = SqliteConnection::open(&self.path)?;
-        let decls = sqlite_table_decltypes(&conn, table_name)?;
-        let tq = sqlite_quote_ident(table_name);
-        let mut col_list: Vec<String> = Vec::new();
-        let mut val_parts: Vec<String> = Vec::new();
-        let mut refs: Vec<rusqlite::types::Value> = Vec::new();
-        for (i, c) in columns.iter().enumerate() {
-            let raw = values.get(i).map_or("", String::as_str);
-            let sqlite_rowid_pk_omit =
-                pk.first().is_some_and(|first_pk| {
-                    c.name == *first_pk || c.name.eq_ignore_ascii_case(first_pk)
-                }) && c.data_type.to_lowercase().contains("int");
-            if should_omit_for_insert_default(
-                c,
-                raw,
-                true,
-                sqlite_rowid_pk_omit,
-            ) {
-                continue;
-            }
-            if raw.trim().is_empty() || raw.eq_ignore_ascii_case("null") {
-                if c.is_nullable {
-                    col_list.push(sqlite_quote_ident(&c.name));
-                    val_parts.push("NULL".to_string());
+        self.with_connection(|conn| {
+            let decls = sqlite_table_decltypes(conn, schema_name, table_name)?;
+            let tq = sqlite_qualified_table(schema_name, table_name);
+            let mut col_list: Vec<String> = Vec::new();
+            let mut val_parts: Vec<String> = Vec::new();
+            let mut refs: Vec<rusqlite::types::Value> = Vec::new();
+            for (i, c) in columns.iter().enumerate() {
+                let raw = values.get(i).map_or("", String::as_str);
+                let sqlite_rowid_pk_omit =
+                    pk.first().is_some_and(|first_pk| {
+                        c.name == *first_pk
+                            || c.name.eq_ignore_ascii_case(first_pk)
+                    }) && c.data_type.to_lowercase().contains("int");
+                if should_omit_for_insert_default(
+                    c,
+                    raw,
+                    true,
+                    sqlite_rowid_pk_omit,
+                ) {
+                    continue;
+                }
+                if raw.trim().is_empty() || raw.eq_ignore_ascii_case("null") {
+                    if c.is_nullable {
+                        col_list.push(DIALECT.quote_ident(&c.name));
+                        val_parts.push("NULL".to_string());
+                    } else {
+                        return Err(format!(
+                            "Column '{}' is NOT NULL and has no value or default in the form.",
+                            c.name
+                        )
+                        .into());
+                    }
                 } else {
-                    return Err(format!(
-                        "Column '{}' is NOT NULL and has no value or default in the form.",
-                        c.name
-                    )
-                    .into());
+                    col_list.push(DIALECT.quote_ident(&c.name));
+                    let kw = sqlite_cast_keyword(sqlite_resolve_decl(
+                        &decls, &c.name,
+                    ));
+                    val_parts.push(format!("CAST(? AS {kw})"));
+                    refs.push(rusqlite::types::Value::Text(raw.to_string()));
                 }
-            } else {
-                col_list.push(sqlite_quote_ident(&c.name));
-                let kw =
-                    sqlite_cast_keyword(sqlite_resolve_decl(&decls, &c.name));
-                val_parts.push(format!("CAST(? AS {kw})"));
-                refs.push(rusqlite::types::Value::Text(raw.to_string()));
             }
-        }
-        if col_list.is_empty() {
-            let sql = format!("INSERT INTO {tq} DEFAULT VALUES");
-            return Ok(u64::try_from(conn.execute(&sql, [])?).unwrap_or(0));
-        }
-        let col_list = col_list.join(", ");
-        let sql = format!(
-            "INSERT INTO {tq} ({col_list}) VALUES ({})",
-            val_parts.join(", ")
-        );
-        let mut pvec: Vec<&dyn rusqlite::ToSql> = Vec::new();
-        for v in &refs {
-            pvec.push(v);
-        }
-        let n =
-            u64::try_from(conn.execute(&sql, pvec.as_slice())?).unwrap_or(0);
-        Ok(n)
+            if col_list.is_empty() {
+                let sql = format!("INSERT INTO {tq} DEFAULT VALUES");
+                return Ok(u64::try_from(conn.execute(&sql, [])?).unwrap_or(0));
+            }
+            let col_list = col_list.join(", ");
+            let sql = format!(
+                "INSERT INTO {tq} ({col_list}) VALUES ({})",
+                val_parts.join(", ")
+            );
+            let mut pvec: Vec<&dyn rusqlite::ToSql> = Vec::new();
+            for v in &refs {
+                pvec.push(v);
+            }
+            let n = u64::try_from(conn.execute(&sql, pvec.as_slice())?)
+                .unwrap_or(0);
+            Ok(n)
+        })
     }
 
     async fn delete_table_row(
         &self,
-        _schema_name: &str,
+        schema_name: &str,
         table_name: &str,
         primary_key: &[(String, String)],
         row_id_fallback: Option<DbRowId>,
     ) -> Result<u64, Box<dyn std::error::Error>> {
-        let conn = SqliteConnection::open(&self.path)?;
-        let decls = sqlite_table_decltypes(&conn, table_name)?;
-        let tq = sqlite_quote_ident(table_name);
-        if !primary_key.is_empty() {
-            let mut sql = format!("DELETE FROM {tq} WHERE ");
-            for (i, (k, _)) in primary_key.iter().enumerate() {
-                if i > 0 {
-                    sql.push_str(" AND ");
+        self.with_connection(|conn| {
+            let decls = sqlite_table_decltypes(conn, schema_name, table_name)?;
+            let tq = sqlite_qualified_table(schema_name, table_name);
+            if !primary_key.is_empty() {
+                let mut sql = format!("DELETE FROM {tq} WHERE ");
+                for (i, (k, _)) in primary_key.iter().enumerate() {
+                    if i > 0 {
+                        sql.push_str(" AND ");
+                    }
+                    let param_num = i + 1;
+                    let pk_kw =
+                        sqlite_cast_keyword(sqlite_resolve_decl(&decls, k));
+                    let _ = write!(
+                        sql,
+                        "{} = CAST(?{param_num} AS {pk_kw})",
+                        DIALECT.quote_ident(k)
+                    );
                 }
-                let param_num = i + 1;
-                let pk_kw = sqlite_cast_keyword(sqlite_resolve_decl(&decls, k));
-                let _ = write!(
-                    sql,
-                    "{} = CAST(?{param_num} AS {pk_kw})",
-                    sqlite_quote_ident(k)
-                );
+                let mut refs: Vec<&dyn rusqlite::ToSql> = Vec::new();
+                for (_, v) in primary_key {
+                    refs.push(v);
+                }
+                let n = u64::try_from(conn.execute(&sql, refs.as_slice())?)
+                    .unwrap_or(0);
+                return Ok(n);
             }
-            let mut refs: Vec<&dyn rusqlite::ToSql> = Vec::new();
-            for (_, v) in primary_key {
-                refs.push(v);
+            if let Some(DbRowId::Sqlite(rid)) = row_id_fallback {
+                let sql = format!("DELETE FROM {tq} WHERE rowid = ?1");
+                let n = u64::try_from(conn.execute(&sql, params![rid])?)
+                    .unwrap_or(0);
+                return Ok(n);
             }
-            let n = u64::try_from(conn.execute(&sql, refs.as_slice())?)
-                .unwrap_or(0);
-            return Ok(n);
-        }
-        if let Some(DbRowId::Sqlite(rid)) = row_id_fallback {
-            let sql = format!("DELETE FROM {tq} WHERE rowid = ?1");
-            let n =
-                u64::try_from(conn.execute(&sql, params![rid])?).unwrap_or(0);
-            return Ok(n);
-        }
-        Err("Cannot delete row: no primary key and no rowid".into())
+            Err("Cannot delete row: no primary key and no rowid".into())
+        })
     }
 
     async fn get_table_row_count(
         &self,
-        _schema_name: &str,
+        schema_name: &str,
         table_name: &str,
     ) -> Result<u64, Box<dyn std::error::Error>> {
-        let conn = SqliteConnection::open(&self.path)?;
-        let count: i64 = conn.query_row(
-            &format!("SELECT COUNT(*) FROM {table_name}"),
-            [],
-            |row| row.get(0),
-        )?;
-        Ok(count.cast_unsigned())
+        self.with_connection(|conn| {
+            let count: i64 = conn.query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM {}",
+                    sqlite_qualified_table(schema_name, table_name)
+                ),
+                [],
+                |row| row.get(0),
+            )?;
+            Ok(count.cast_unsigned())
+        })
     }
 
     async fn get_databases(
@@ -492,14 +704,81 @@ impl Database for Sqlite {
             name: self.path.clone(),
         }])
     }
+
+    async fn get_schema_ddl(
+        &self,
+        schema_name: &str,
+        with_extras: bool,
+    ) -> Result<SchemaDdl, Box<dyn std::error::Error>> {
+        let schema_name = sqlite_schema_name(schema_name);
+        // SQLite keeps each object's `CREATE` text; constraints are part of
+        // the table's. Shadow tables of virtual tables are left out, they
+        // are created with their table.
+        let query = format!(
+            "SELECT s.type, s.sql FROM {}.sqlite_schema s
+             JOIN pragma_table_list l ON l.schema = ?1 AND l.name = s.tbl_name
+             WHERE s.sql IS NOT NULL
+             AND s.type IN ('table', 'index')
+             AND l.type IN ('table', 'virtual')
+             AND s.name NOT LIKE 'sqlite_%'
+             ORDER BY s.name",
+            DIALECT.quote_ident(schema_name)
+        );
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(&query)?;
+            let mut ddl = SchemaDdl::default();
+            let rows = stmt.query_map(params![schema_name], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?;
+            for row in rows {
+                let (kind, sql) = row?;
+                if kind == "table" {
+                    ddl.tables.push(sql);
+                } else if with_extras {
+                    ddl.extras.push(sql);
+                }
+            }
+            Ok(ddl)
+        })
+    }
 }
 
 impl Sqlite {
-    fn get_connection(
+    #[must_use]
+    pub const fn new(name: String, path: String) -> Self {
+        Self {
+            name,
+            path,
+            connection: Mutex::new(None),
+        }
+    }
+
+    /// Run `f` against the shared connection, opening it on first use.
+    fn with_connection<T>(
         &self,
-    ) -> Result<SqliteConnection, Box<dyn std::error::Error>> {
-        // TODO move to field in Sqlite
-        Ok(SqliteConnection::open(&self.path)?)
+        f: impl FnOnce(&SqliteConnection) -> Result<T, Box<dyn std::error::Error>>,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        let mut guard = self.connection.lock().map_err(|_| {
+            std::io::Error::other("sqlite connection lock poisoned")
+        })?;
+        if guard.is_none() {
+            let conn = SqliteConnection::open(&self.path)?;
+            conn.create_scalar_function(
+                SQLITE_CHECKSUM_FUNCTION,
+                1,
+                FunctionFlags::SQLITE_UTF8
+                    | FunctionFlags::SQLITE_DETERMINISTIC,
+                |ctx| {
+                    Ok(ctx
+                        .get::<Option<String>>(0)?
+                        .map(|text| value_checksum(&text)))
+                },
+            )?;
+            *guard = Some(conn);
+        }
+        guard
+            .as_ref()
+            .map_or_else(|| Err("sqlite connection is not open".into()), f)
     }
 }
 
@@ -513,9 +792,25 @@ impl Sqlite {
 pub fn init_db() -> Result<()> {
     let db_path = get_db_path()?;
     let mut conn = SqliteConnection::open(db_path)?;
+    check_integrity(&conn)?;
+
+    let migrations = store_migrations();
+
+    // A store from an older version is kept as it was before migrating it
+    if !matches!(migrations.current_version(&conn)?, SchemaVersion::NoneSet)
+        && migrations.pending_migrations(&conn)? > 0
+    {
+        backup_store(&conn, "migration")
+            .map_err(|e| eyre!("Failed to back up before migrating: {e}"))?;
+    }
+    migrations.to_latest(&mut conn)?;
+
+    Ok(())
+}
 
-    // Base schema: Name, Type, Url, Environment, Metadata (JSONB as TEXT).
-    let migrations = Migrations::new(vec![
+/// Migrations of the local store, oldest first.
+fn store_migrations() -> Migrations<'static> {
+    Migrations::new(vec![
         M::up(
             "CREATE TABLE IF NOT EXISTS connections (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -527,11 +822,201 @@ pub fn init_db() -> Result<()> {
             );",
         )
         .down("DROP TABLE connections"),
-    ]);
+        M::up(
+            "CREATE TABLE IF NOT EXISTS saved_views (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                connection_name TEXT NOT NULL,
+                name TEXT NOT NULL,
+                schema_name TEXT NOT NULL,
+                table_name TEXT NOT NULL,
+                filter TEXT,
+                hidden_columns TEXT NOT NULL DEFAULT '[]',
+                UNIQUE (connection_name, name)
+            );",
+        )
+        .down("DROP TABLE saved_views"),
+        M::up(
+            "CREATE TABLE IF NOT EXISTS recent_tables (
+                connection_name TEXT NOT NULL,
+                database_name TEXT NOT NULL DEFAULT '',
+                schema_name TEXT NOT NULL,
+                table_name TEXT NOT NULL,
+                opened_seq INTEGER NOT NULL,
+                PRIMARY KEY (connection_name, database_name, schema_name, table_name)
+            );",
+        )
+        .down("DROP TABLE recent_tables"),
+        M::up(
+            "CREATE TABLE IF NOT EXISTS column_widths (
+                connection_name TEXT NOT NULL,
+                schema_name TEXT NOT NULL,
+                table_name TEXT NOT NULL,
+                column_name TEXT NOT NULL,
+                width INTEGER NOT NULL,
+                PRIMARY KEY (connection_name, schema_name, table_name, column_name)
+            );",
+        )
+        .down("DROP TABLE column_widths"),
+        M::up(
+            "CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );",
+        )
+        .down("DROP TABLE settings"),
+        M::up(
+            "CREATE TABLE IF NOT EXISTS scratch_buffers (
+                connection_name TEXT PRIMARY KEY,
+                sql TEXT NOT NULL
+            );",
+        )
+        .down("DROP TABLE scratch_buffers"),
+        M::up("ALTER TABLE connections ADD COLUMN notes TEXT;")
+            .down("ALTER TABLE connections DROP COLUMN notes"),
+        M::up("ALTER TABLE connections ADD COLUMN last_used_seq INTEGER;")
+            .down("ALTER TABLE connections DROP COLUMN last_used_seq"),
+        M::up("ALTER TABLE connections ADD COLUMN last_used_at TEXT;")
+            .down("ALTER TABLE connections DROP COLUMN last_used_at"),
+        M::up(
+            "CREATE TABLE IF NOT EXISTS row_color_rules (
+                connection_name TEXT NOT NULL,
+                schema_name TEXT NOT NULL,
+                table_name TEXT NOT NULL,
+                rules TEXT NOT NULL,
+                PRIMARY KEY (connection_name, schema_name, table_name)
+            );",
+        )
+        .down("DROP TABLE row_color_rules"),
+        M::up(
+            "CREATE TABLE IF NOT EXISTS row_notes (
+                connection_name TEXT NOT NULL,
+                schema_name TEXT NOT NULL,
+                table_name TEXT NOT NULL,
+                key_columns TEXT NOT NULL,
+                row_key TEXT NOT NULL,
+                note TEXT NOT NULL,
+                PRIMARY KEY (connection_name, schema_name, table_name, row_key)
+            );",
+        )
+        .down("DROP TABLE row_notes"),
+        M::up(
+            "ALTER TABLE saved_views ADD COLUMN order_column TEXT;
+             ALTER TABLE saved_views ADD COLUMN order_descending INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE saved_views ADD COLUMN order_nulls_first INTEGER NOT NULL DEFAULT 0;",
+        )
+        .down(
+            "ALTER TABLE saved_views DROP COLUMN order_nulls_first;
+             ALTER TABLE saved_views DROP COLUMN order_descending;
+             ALTER TABLE saved_views DROP COLUMN order_column;",
+        ),
+    ])
+}
 
-    migrations.to_latest(&mut conn)?;
+/// Fail with what `PRAGMA quick_check` found when the store's file is
+/// damaged, instead of migrating it.
+fn check_integrity(conn: &SqliteConnection) -> Result<()> {
+    let problems = conn
+        .prepare("PRAGMA quick_check")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    if problems == ["ok"] {
+        Ok(())
+    } else {
+        Err(eyre!("integrity check failed: {}", problems.join("; ")))
+    }
+}
 
-    Ok(())
+/// What [`recreate_store`] did with the store [`init_db`] couldn't open.
+#[derive(Debug)]
+pub struct StoreRecovery {
+    /// Where the old store was moved.
+    pub backup: PathBuf,
+    /// JSON copy of the connections that could still be read.
+    pub export: PathBuf,
+    /// How many of those were saved to the new store.
+    pub restored: usize,
+}
+
+/// Move a store that is damaged or fails to migrate aside and create a new
+/// one. The connections that can still be read are written to a JSON file
+/// next to it and saved to the new store; saved views, recent tables and
+/// settings are left in the backup.
+///
+/// # Errors
+///
+/// Returns an error if the export can't be written, the old store can't be
+/// moved or the new one can't be created.
+pub fn recreate_store() -> Result<StoreRecovery> {
+    let db_path = get_db_path()?;
+    let connections = SqliteConnection::open(&db_path)
+        .map(|conn| readable_connections(&conn))
+        .unwrap_or_default();
+
+    let stamp = file_timestamp();
+    let export = db_path.with_file_name(format!("connections-{stamp}.json"));
+    std::fs::write(
+        &export,
+        serde_json::to_string_pretty(&connections_json(&connections))?,
+    )?;
+
+    let backup = db_path.with_file_name(format!("d7s-{stamp}.db.corrupt"));
+    std::fs::rename(&db_path, &backup)?;
+    for suffix in ["-journal", "-wal", "-shm"] {
+        let sidecar = with_suffix(&db_path, suffix);
+        if sidecar.exists() {
+            std::fs::rename(sidecar, with_suffix(&backup, suffix))?;
+        }
+    }
+
+    init_db()?;
+    let restored = connections
+        .iter()
+        .filter(|connection| save_connection(connection).is_ok())
+        .count();
+    Ok(StoreRecovery {
+        backup,
+        export,
+        restored,
+    })
+}
+
+/// `path` with `suffix` appended to its file name, e.g. `d7s.db-wal`.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Connections of a possibly damaged store, skipping the rows that can't be
+/// read.
+fn readable_connections(conn: &SqliteConnection) -> Vec<Connection> {
+    let Ok(mut stmt) = conn.prepare("SELECT * FROM connections") else {
+        return Vec::new();
+    };
+    stmt.query_map([], connection_from_row).map_or_else(
+        |_| Vec::new(),
+        |rows| rows.filter_map(Result::ok).collect(),
+    )
+}
+
+/// Connections as exported by [`recreate_store`], without passwords.
+fn connections_json(connections: &[Connection]) -> serde_json::Value {
+    connections
+        .iter()
+        .map(|connection| {
+            serde_json::json!({
+                "name": connection.name,
+                "type": connection.r#type.to_string(),
+                "url": connection.url,
+                "environment": connection.environment.to_string(),
+                "notes": connection.notes,
+                "metadata": serde_json::from_str::<serde_json::Value>(
+                    &metadata_for_save(connection)
+                )
+                .unwrap_or_default(),
+            })
+        })
+        .collect()
 }
 
 /// Build metadata JSON for storage (includes `password_storage` if set).
@@ -585,12 +1070,13 @@ pub fn save_connection(
     let metadata = metadata_for_save(connection);
 
     conn.execute(
-        "INSERT INTO connections (name, type, url, environment, metadata) VALUES (?, ?, ?, ?, ?)",
+        "INSERT INTO connections (name, type, url, environment, notes, metadata) VALUES (?, ?, ?, ?, ?, ?)",
         params![
             connection.name,
             connection.r#type.to_string(),
             connection.url,
             connection.environment.to_string(),
+            connection.notes,
             metadata,
         ],
     )?;
@@ -608,39 +1094,77 @@ pub fn get_connections() -> Result<Vec<Connection>> {
     let conn = SqliteConnection::open(db_path)?;
 
     let mut stmt = conn.prepare(
-        "SELECT name, type, url, environment, metadata FROM connections ORDER BY name",
+        "SELECT name, type, url, environment, metadata, notes, last_used_seq, last_used_at FROM connections ORDER BY name",
     )?;
     let connections = stmt
-        .query_map([], |row| {
-            let name: String = row.get(0)?;
-            let type_str: String = row.get(1)?;
-            let url: String = row.get(2)?;
-            let env_str: String = row.get(3)?;
-            let metadata_str: Option<String> = row.get(4)?;
-
-            let r#type = type_str.parse().unwrap_or(ConnectionType::Postgres);
-            let environment = env_str.parse().unwrap_or(Environment::Dev);
-            let (metadata, password_storage) =
-                metadata_from_row(metadata_str.as_ref());
-
-            Ok(Connection {
-                name,
-                r#type,
-                url,
-                environment,
-                metadata,
-                selected_database: None,
-                schema: None,
-                table: None,
-                password: None,
-                password_storage,
-            })
-        })?
+        .query_map([], connection_from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(connections)
 }
 
+/// Connection from a row of the `connections` table, read by column name.
+fn connection_from_row(
+    row: &rusqlite::Row<'_>,
+) -> rusqlite::Result<Connection> {
+    let name: String = row.get("name")?;
+    let type_str: String = row.get("type")?;
+    let url: String = row.get("url")?;
+    let env_str: String = row.get("environment")?;
+    let metadata_str: Option<String> = row.get("metadata")?;
+    // Missing from stores a failed migration left before it was added
+    let notes: Option<String> = row.get("notes").ok().flatten();
+    let last_used: Option<i64> = row.get("last_used_seq").ok().flatten();
+    let last_used_at: Option<String> =
+        row.get("last_used_at").ok().flatten();
+
+    let r#type = type_str.parse().unwrap_or(ConnectionType::Postgres);
+    let environment = env_str.parse().unwrap_or(Environment::Dev);
+    let (metadata, password_storage) = metadata_from_row(metadata_str.as_ref());
+
+    Ok(Connection {
+        name,
+        r#type,
+        url,
+        environment,
+        notes,
+        metadata,
+        last_used,
+        last_used_at,
+        columns: Arc::default(),
+        transient: false,
+        selected_database: None,
+        schema: None,
+        table: None,
+        search_path: None,
+        session_search_path: None,
+        password: None,
+        password_storage,
+    })
+}
+
+/// Mark a connection as the most recently connected to
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn record_connection_use(
+    connection_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    conn.execute(
+        "UPDATE connections
+         SET last_used_seq = (SELECT COALESCE(MAX(last_used_seq), 0) + 1 FROM connections),
+             last_used_at = strftime('%Y-%m-%d %H:%M', 'now', 'localtime')
+         WHERE name = ?",
+        params![connection_name],
+    )?;
+
+    Ok(())
+}
+
 /// Update a connection in the database
 ///
 /// # Errors
@@ -657,21 +1181,46 @@ pub fn update_connection(
     let metadata = metadata_for_save(connection);
 
     conn.execute(
-        "UPDATE connections SET name = ?, type = ?, url = ?, environment = ?, metadata = ? WHERE name = ?",
+        "UPDATE connections SET name = ?, type = ?, url = ?, environment = ?, notes = ?, metadata = ? WHERE name = ?",
         params![
             connection.name,
             connection.r#type.to_string(),
             connection.url,
             connection.environment.to_string(),
+            connection.notes,
             metadata,
             old_name,
         ],
     )?;
+    conn.execute(
+        "UPDATE saved_views SET connection_name = ? WHERE connection_name = ?",
+        params![connection.name, old_name],
+    )?;
+    conn.execute(
+        "UPDATE recent_tables SET connection_name = ? WHERE connection_name = ?",
+        params![connection.name, old_name],
+    )?;
+    conn.execute(
+        "UPDATE column_widths SET connection_name = ? WHERE connection_name = ?",
+        params![connection.name, old_name],
+    )?;
+    conn.execute(
+        "UPDATE scratch_buffers SET connection_name = ? WHERE connection_name = ?",
+        params![connection.name, old_name],
+    )?;
+    conn.execute(
+        "UPDATE row_color_rules SET connection_name = ? WHERE connection_name = ?",
+        params![connection.name, old_name],
+    )?;
+    conn.execute(
+        "UPDATE row_notes SET connection_name = ? WHERE connection_name = ?",
+        params![connection.name, old_name],
+    )?;
 
     Ok(())
 }
 
-/// Delete a connection from the database
+/// Delete a connection from the database, backing the store up first
 ///
 /// # Errors
 ///
@@ -681,25 +1230,542 @@ pub fn delete_connection(
 ) -> Result<(), Box<dyn std::error::Error>> {
     let db_path = get_db_path()?;
     let conn = SqliteConnection::open(db_path)?;
+    backup_store(&conn, "delete").map_err(|e| e.to_string())?;
 
     conn.execute(
         "DELETE FROM connections WHERE name = ?",
         params![connection_name],
     )?;
+    conn.execute(
+        "DELETE FROM saved_views WHERE connection_name = ?",
+        params![connection_name],
+    )?;
+    conn.execute(
+        "DELETE FROM recent_tables WHERE connection_name = ?",
+        params![connection_name],
+    )?;
+    conn.execute(
+        "DELETE FROM column_widths WHERE connection_name = ?",
+        params![connection_name],
+    )?;
+    conn.execute(
+        "DELETE FROM scratch_buffers WHERE connection_name = ?",
+        params![connection_name],
+    )?;
+    conn.execute(
+        "DELETE FROM row_color_rules WHERE connection_name = ?",
+        params![connection_name],
+    )?;
+    conn.execute(
+        "DELETE FROM row_notes WHERE connection_name = ?",
+        params![connection_name],
+    )?;
 
     Ok(())
 }
 
-/// Convert a `SQLite` value to a string representation
-fn convert_sqlite_value_to_string(row: &rusqlite::Row, index: usize) -> String {
-    // Try to get as different types and convert to string
-    if let Ok(value) = row.get::<_, Option<String>>(index) {
-        return value.unwrap_or_else(|| "NULL".to_string());
-    }
+/// Save a view for a connection, replacing any view with the same name
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn save_view(
+    connection_name: &str,
+    view: &SavedView,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+    save_view_in(&conn, connection_name, view)
+}
 
-    if let Ok(value) = row.get::<_, Option<i64>>(index) {
-        return value.map_or_else(|| "NULL".to_string(), |v| v.to_string());
-    }
+fn save_view_in(
+    conn: &SqliteConnection,
+    connection_name: &str,
+    view: &SavedView,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let order = view.order.as_ref();
+    conn.execute(
+        "INSERT INTO saved_views (connection_name, name, schema_name, table_name, filter, hidden_columns,
+                                  order_column, order_descending, order_nulls_first)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT (connection_name, name) DO UPDATE SET
+            schema_name = excluded.schema_name,
+            table_name = excluded.table_name,
+            filter = excluded.filter,
+            hidden_columns = excluded.hidden_columns,
+            order_column = excluded.order_column,
+            order_descending = excluded.order_descending,
+            order_nulls_first = excluded.order_nulls_first",
+        params![
+            connection_name,
+            view.name,
+            view.schema_name,
+            view.table_name,
+            view.filter,
+            serde_json::to_string(&view.hidden_columns)?,
+            order.map(|order| &order.column),
+            order.is_some_and(|order| order.direction == SortDirection::Descending),
+            order.is_some_and(|order| order.nulls == NullsOrder::First),
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Get the saved views of a connection, ordered by name
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn get_views(connection_name: &str) -> Result<Vec<SavedView>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+    views_in(&conn, connection_name)
+}
+
+fn views_in(
+    conn: &SqliteConnection,
+    connection_name: &str,
+) -> Result<Vec<SavedView>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, schema_name, table_name, filter, hidden_columns,
+                order_column, order_descending, order_nulls_first
+         FROM saved_views WHERE connection_name = ? ORDER BY name",
+    )?;
+    let views = stmt
+        .query_map(params![connection_name], |row| {
+            let hidden_columns: String = row.get(4)?;
+            let order_column: Option<String> = row.get(5)?;
+            let descending: bool = row.get(6)?;
+            let nulls_first: bool = row.get(7)?;
+            Ok(SavedView {
+                name: row.get(0)?,
+                schema_name: row.get(1)?,
+                table_name: row.get(2)?,
+                filter: row.get(3)?,
+                hidden_columns: serde_json::from_str(&hidden_columns)
+                    .unwrap_or_default(),
+                order: order_column.map(|column| TableOrder {
+                    column,
+                    direction: if descending {
+                        SortDirection::Descending
+                    } else {
+                        SortDirection::Ascending
+                    },
+                    nulls: if nulls_first {
+                        NullsOrder::First
+                    } else {
+                        NullsOrder::Last
+                    },
+                }),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(views)
+}
+
+/// Delete a saved view of a connection
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn delete_view(
+    connection_name: &str,
+    view_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    conn.execute(
+        "DELETE FROM saved_views WHERE connection_name = ? AND name = ?",
+        params![connection_name, view_name],
+    )?;
+
+    Ok(())
+}
+
+/// Record that a table was opened, keeping the newest `keep` entries of the
+/// connection
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn record_recent_table(
+    connection_name: &str,
+    recent: &RecentTable,
+    keep: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    conn.execute(
+        "INSERT INTO recent_tables (connection_name, database_name, schema_name, table_name, opened_seq)
+         VALUES (?1, ?2, ?3, ?4, (SELECT COALESCE(MAX(opened_seq), 0) + 1 FROM recent_tables))
+         ON CONFLICT (connection_name, database_name, schema_name, table_name)
+         DO UPDATE SET opened_seq = excluded.opened_seq",
+        params![
+            connection_name,
+            recent.database.as_deref().unwrap_or_default(),
+            recent.schema_name,
+            recent.table_name,
+        ],
+    )?;
+    conn.execute(
+        "DELETE FROM recent_tables
+         WHERE connection_name = ?1 AND opened_seq NOT IN (
+            SELECT opened_seq FROM recent_tables
+            WHERE connection_name = ?1
+            ORDER BY opened_seq DESC LIMIT ?2
+         )",
+        params![connection_name, i64::try_from(keep).unwrap_or(i64::MAX)],
+    )?;
+
+    Ok(())
+}
+
+/// Get the recently opened tables of a connection, newest first
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn get_recent_tables(connection_name: &str) -> Result<Vec<RecentTable>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT database_name, schema_name, table_name FROM recent_tables
+         WHERE connection_name = ? ORDER BY opened_seq DESC",
+    )?;
+    let tables = stmt
+        .query_map(params![connection_name], |row| {
+            let database: String = row.get(0)?;
+            Ok(RecentTable {
+                database: (!database.is_empty()).then_some(database),
+                schema_name: row.get(1)?,
+                table_name: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(tables)
+}
+
+/// Save the display width of a table column for a connection
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn save_column_width(
+    connection_name: &str,
+    schema_name: &str,
+    table_name: &str,
+    column_name: &str,
+    width: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    conn.execute(
+        "INSERT INTO column_widths (connection_name, schema_name, table_name, column_name, width)
+         VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT (connection_name, schema_name, table_name, column_name)
+         DO UPDATE SET width = excluded.width",
+        params![
+            connection_name,
+            schema_name,
+            table_name,
+            column_name,
+            i64::try_from(width)?,
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Get the saved column widths of a table as `(column name, width)` pairs
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn get_column_widths(
+    connection_name: &str,
+    schema_name: &str,
+    table_name: &str,
+) -> Result<Vec<(String, usize)>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT column_name, width FROM column_widths
+         WHERE connection_name = ? AND schema_name = ? AND table_name = ?",
+    )?;
+    let widths = stmt
+        .query_map(params![connection_name, schema_name, table_name], |row| {
+            let width: i64 = row.get(1)?;
+            Ok((row.get(0)?, usize::try_from(width).unwrap_or_default()))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(widths)
+}
+
+/// Save the row coloring rules of a table for a connection; empty rules
+/// remove them
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn save_row_color_rules(
+    connection_name: &str,
+    schema_name: &str,
+    table_name: &str,
+    rules: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    if rules.trim().is_empty() {
+        conn.execute(
+            "DELETE FROM row_color_rules
+             WHERE connection_name = ? AND schema_name = ? AND table_name = ?",
+            params![connection_name, schema_name, table_name],
+        )?;
+    } else {
+        conn.execute(
+            "INSERT INTO row_color_rules (connection_name, schema_name, table_name, rules)
+             VALUES (?, ?, ?, ?)
+             ON CONFLICT (connection_name, schema_name, table_name)
+             DO UPDATE SET rules = excluded.rules",
+            params![connection_name, schema_name, table_name, rules],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Get the row coloring rules of a table, as typed
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn get_row_color_rules(
+    connection_name: &str,
+    schema_name: &str,
+    table_name: &str,
+) -> Result<Option<String>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    let rules = conn
+        .query_row(
+            "SELECT rules FROM row_color_rules
+             WHERE connection_name = ? AND schema_name = ? AND table_name = ?",
+            params![connection_name, schema_name, table_name],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    Ok(rules)
+}
+
+/// Save the note on a row of a table for a connection; an empty note
+/// removes it
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn save_row_note(
+    connection_name: &str,
+    schema_name: &str,
+    table_name: &str,
+    note: &RowNote,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+    let row_key = serde_json::to_string(&note.key)?;
+
+    if note.note.is_empty() {
+        conn.execute(
+            "DELETE FROM row_notes
+             WHERE connection_name = ? AND schema_name = ? AND table_name = ?
+               AND row_key = ?",
+            params![connection_name, schema_name, table_name, row_key],
+        )?;
+    } else {
+        conn.execute(
+            "INSERT INTO row_notes (connection_name, schema_name, table_name, key_columns, row_key, note)
+             VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT (connection_name, schema_name, table_name, row_key)
+             DO UPDATE SET key_columns = excluded.key_columns, note = excluded.note",
+            params![
+                connection_name,
+                schema_name,
+                table_name,
+                serde_json::to_string(&note.key_columns)?,
+                row_key,
+                note.note,
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Get the notes on rows of a table for a connection
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn get_row_notes(
+    connection_name: &str,
+    schema_name: &str,
+    table_name: &str,
+) -> Result<Vec<RowNote>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT key_columns, row_key, note FROM row_notes
+         WHERE connection_name = ? AND schema_name = ? AND table_name = ?
+         ORDER BY rowid",
+    )?;
+    let notes = stmt
+        .query_map(params![connection_name, schema_name, table_name], |row| {
+            let key_columns: String = row.get(0)?;
+            let key: String = row.get(1)?;
+            Ok(RowNote {
+                key_columns: serde_json::from_str(&key_columns)
+                    .unwrap_or_default(),
+                key: serde_json::from_str(&key).unwrap_or_default(),
+                note: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(notes)
+}
+
+/// Save the SQL executor buffer of a connection; an empty buffer removes it
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn save_scratch_buffer(
+    connection_name: &str,
+    sql: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    if sql.trim().is_empty() {
+        conn.execute(
+            "DELETE FROM scratch_buffers WHERE connection_name = ?",
+            params![connection_name],
+        )?;
+    } else {
+        conn.execute(
+            "INSERT INTO scratch_buffers (connection_name, sql) VALUES (?, ?)
+             ON CONFLICT (connection_name) DO UPDATE SET sql = excluded.sql",
+            params![connection_name, sql],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Get the saved SQL executor buffers of every connection, by connection
+/// name
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn get_all_scratch_buffers() -> Result<Vec<(String, String)>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT connection_name, sql FROM scratch_buffers
+         ORDER BY connection_name",
+    )?;
+    let buffers = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(buffers)
+}
+
+/// Get the saved SQL executor buffer of a connection
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn get_scratch_buffer(connection_name: &str) -> Result<Option<String>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    let sql = conn
+        .query_row(
+            "SELECT sql FROM scratch_buffers WHERE connection_name = ?",
+            params![connection_name],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    Ok(sql)
+}
+
+/// Save an app-wide setting, replacing its previous value
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn save_setting(
+    key: &str,
+    value: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?, ?)
+         ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+
+    Ok(())
+}
+
+/// Get an app-wide setting, if it was ever saved
+///
+/// # Errors
+///
+/// This function will return an error if the database cannot be opened or if the query fails.
+pub fn get_setting(key: &str) -> Result<Option<String>> {
+    let db_path = get_db_path()?;
+    let conn = SqliteConnection::open(db_path)?;
+
+    let value = conn
+        .query_row(
+            "SELECT value FROM settings WHERE key = ?",
+            params![key],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    Ok(value)
+}
+
+/// Convert a `SQLite` value to a string representation
+fn convert_sqlite_value_to_string(row: &rusqlite::Row, index: usize) -> String {
+    // Try to get as different types and convert to string
+    if let Ok(value) = row.get::<_, Option<String>>(index) {
+        return value.unwrap_or_else(|| "NULL".to_string());
+    }
+
+    if let Ok(value) = row.get::<_, Option<i64>>(index) {
+        return value.map_or_else(|| "NULL".to_string(), |v| v.to_string());
+    }
 
     if let Ok(value) = row.get::<_, Option<f64>>(index) {
         return value.map_or_else(|| "NULL".to_string(), |v| v.to_string());
@@ -715,3 +1781,193 @@ fn convert_sqlite_value_to_string(row: &rusqlite::Row, index: usize) -> String {
     // Fallback for unknown types
     "<unprintable>".to_string()
 }
+
+/// Run `f`, interrupting the statement `conn` is stepping once `limit`
+/// passes, so a single slow step is stopped too; the timer is gone by the
+/// time this returns, so it can't interrupt a later statement.
+fn interrupt_after<T>(
+    conn: &SqliteConnection,
+    limit: Option<Duration>,
+    f: impl FnOnce() -> T,
+) -> T {
+    let Some(limit) = limit else {
+        return f();
+    };
+    let handle = conn.get_interrupt_handle();
+    let (done, finished) = mpsc::channel::<()>();
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            if finished.recv_timeout(limit)
+                == Err(mpsc::RecvTimeoutError::Timeout)
+            {
+                handle.interrupt();
+            }
+        });
+        let output = f();
+        drop(done);
+        output
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn readings() -> Sqlite {
+        let db = Sqlite::new("test".to_string(), ":memory:".to_string());
+        for sql in [
+            "CREATE TABLE readings (v REAL, at TEXT)",
+            "INSERT INTO readings VALUES
+                (1, '2024-01-01'), (NULL, NULL), (4, '2024-01-02'),
+                (10, '2024-01-03')",
+        ] {
+            db.execute_sql(sql).await.unwrap();
+        }
+        db
+    }
+
+    #[test]
+    fn keeps_the_order_of_saved_views() {
+        let mut conn = SqliteConnection::open_in_memory().unwrap();
+        store_migrations().to_latest(&mut conn).unwrap();
+        let ordered = SavedView {
+            name: "late orders".to_string(),
+            schema_name: "public".to_string(),
+            table_name: "orders".to_string(),
+            filter: Some("status:late".to_string()),
+            hidden_columns: vec!["notes".to_string()],
+            order: Some(TableOrder {
+                column: "shipped_at".to_string(),
+                direction: SortDirection::Descending,
+                nulls: NullsOrder::First,
+            }),
+        };
+        let unordered = SavedView {
+            name: "all orders".to_string(),
+            order: None,
+            ..ordered.clone()
+        };
+        save_view_in(&conn, "prod", &ordered).unwrap();
+        save_view_in(&conn, "prod", &unordered).unwrap();
+
+        let views = views_in(&conn, "prod").unwrap();
+        assert_eq!(views, [unordered, ordered.clone()]);
+
+        // Saving under the same name replaces the order too
+        let reordered = SavedView {
+            order: Some(TableOrder {
+                column: "id".to_string(),
+                direction: SortDirection::Ascending,
+                nulls: NullsOrder::Last,
+            }),
+            ..ordered
+        };
+        save_view_in(&conn, "prod", &reordered).unwrap();
+        assert_eq!(views_in(&conn, "prod").unwrap().last(), Some(&reordered));
+    }
+
+    #[tokio::test]
+    async fn aggregates_and_buckets_columns_server_side() {
+        let db = readings().await;
+
+        let stats = db.get_column_stats("main", "readings", "v").await.unwrap();
+        assert_eq!((stats.count, stats.nulls), (3, 1));
+        assert_eq!(stats.sum, Some(15.0));
+
+        let histogram = db
+            .get_column_histogram("main", "readings", "v", false, 3)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(histogram.counts, [1, 1, 1]);
+
+        let histogram = db
+            .get_column_histogram("main", "readings", "at", true, 2)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(histogram.counts, [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn exports_tables_and_optionally_indexes() {
+        let db = readings().await;
+        for sql in [
+            "CREATE INDEX readings_at ON readings (at)",
+            "CREATE VIEW recent AS SELECT * FROM readings",
+        ] {
+            db.execute_sql(sql).await.unwrap();
+        }
+
+        let ddl = db.get_schema_ddl("main", false).await.unwrap();
+        assert_eq!(ddl.tables, ["CREATE TABLE readings (v REAL, at TEXT)"]);
+        assert!(ddl.extras.is_empty());
+
+        let ddl = db.get_schema_ddl("main", true).await.unwrap();
+        assert_eq!(ddl.extras, ["CREATE INDEX readings_at ON readings (at)"]);
+    }
+
+    #[tokio::test]
+    async fn interrupts_a_slow_step_at_the_time_limit() {
+        let db = readings().await;
+        let slow = "WITH RECURSIVE n(x) AS
+            (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 1000000000)
+            SELECT count(*) FROM n";
+        let limit = Duration::from_millis(50);
+        let time_box = TimeBox {
+            max_time: Some(limit),
+            max_rows: None,
+        };
+
+        let result = db.execute_sql_within(slow, time_box).await.unwrap();
+        assert_eq!(result.cutoff, Some(Cutoff::Time(limit)));
+        assert!(result.rows.is_empty());
+
+        let rows = db.execute_sql("SELECT 1").await.unwrap();
+        assert_eq!(rows.first().unwrap().values, ["1"]);
+    }
+
+    #[tokio::test]
+    async fn checksums_columns_with_the_registered_hash() {
+        let db = readings().await;
+        let checksums =
+            db.get_table_checksums("main", "readings").await.unwrap();
+        assert_eq!(checksums.rows, 4);
+        let at = checksums.columns.get(1).unwrap();
+        assert_eq!(at.non_null, 3);
+        let expected = ["2024-01-01", "2024-01-02", "2024-01-03"]
+            .iter()
+            .map(|value| i64::from(value_checksum(value)))
+            .sum::<i64>();
+        assert_eq!(at.checksum, Some(expected));
+    }
+
+    #[test]
+    fn exports_connections_readable_from_an_old_store() {
+        let conn = SqliteConnection::open_in_memory().unwrap();
+        check_integrity(&conn).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE connections (name TEXT, type TEXT, url TEXT,
+                environment TEXT, metadata TEXT);
+             INSERT INTO connections VALUES
+                ('prod', 'postgres', 'postgres://app@db:5432/app', 'prod',
+                 '{\"password_storage\":\"keyring\"}'),
+                (NULL, NULL, NULL, NULL, NULL);",
+        )
+        .unwrap();
+
+        let connections = readable_connections(&conn);
+        assert_eq!(connections.len(), 1);
+        assert_eq!(
+            connections_json(&connections),
+            serde_json::json!([{
+                "name": "prod",
+                "type": "postgres",
+                "url": "postgres://app@db:5432/app",
+                "environment": "prod",
+                "notes": null,
+                "metadata": {"password_storage": "keyring"},
+            }])
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Row counts and per-column checksums of two tables, as a cheap check that
+//! a copy (e.g. after a migration) holds the same data.
+
+use md5::{Digest, Md5};
+
+/// Aggregates of one column over a whole table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnChecksum {
+    pub name: String,
+    pub non_null: u64,
+    /// Sum of [`value_checksum`] over the non-NULL values, so the row order
+    /// doesn't matter. `None` when every value is NULL.
+    pub checksum: Option<i64>,
+}
+
+/// Row count and column checksums of a table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableChecksums {
+    pub rows: u64,
+    pub columns: Vec<ColumnChecksum>,
+}
+
+impl TableChecksums {
+    /// Read `COUNT(*)` followed by `COUNT(col), checksum(col)` per column
+    /// from one result row.
+    #[must_use]
+    pub fn from_aggregate_row(names: Vec<String>, values: &[String]) -> Self {
+        let number = |idx: usize| {
+            values.get(idx).and_then(|value| value.parse::<i64>().ok())
+        };
+        let columns = names
+            .into_iter()
+            .enumerate()
+            .map(|(idx, name)| ColumnChecksum {
+                name,
+                non_null: number(1 + idx * 2)
+                    .unwrap_or_default()
+                    .unsigned_abs(),
+                checksum: number(2 + idx * 2),
+            })
+            .collect();
+        Self {
+            rows: number(0).unwrap_or_default().unsigned_abs(),
+            columns,
+        }
+    }
+}
+
+/// First 32 bits of the value's MD5, as a signed integer. Matches the
+/// expression `PostgreSQL` computes server-side, so checksums compare across
+/// backends when the values print the same.
+#[must_use]
+pub fn value_checksum(text: &str) -> i32 {
+    let digest = Md5::digest(text.as_bytes());
+    let mut prefix = [0; 4];
+    prefix.copy_from_slice(digest.get(..4).unwrap_or(&[0; 4]));
+    i32::from_be_bytes(prefix)
+}
+
+/// How a column compares between the two tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareStatus {
+    Same,
+    Differs,
+    OnlyLeft,
+    OnlyRight,
+}
+
+/// One column of both tables, matched by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnComparison {
+    pub name: String,
+    pub left: Option<ColumnChecksum>,
+    pub right: Option<ColumnChecksum>,
+}
+
+impl ColumnComparison {
+    #[must_use]
+    pub fn status(&self) -> CompareStatus {
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) => {
+                if left.non_null == right.non_null
+                    && left.checksum == right.checksum
+                {
+                    CompareStatus::Same
+                } else {
+                    CompareStatus::Differs
+                }
+            }
+            (Some(_), None) => CompareStatus::OnlyLeft,
+            (None, _) => CompareStatus::OnlyRight,
+        }
+    }
+}
+
+/// Columns of `left` in order, then those only `right` has.
+#[must_use]
+pub fn compare_columns(
+    left: &TableChecksums,
+    right: &TableChecksums,
+) -> Vec<ColumnComparison> {
+    let find = |table: &TableChecksums, name: &str| {
+        table.columns.iter().find(|c| c.name == name).cloned()
+    };
+    let mut comparisons = left
+        .columns
+        .iter()
+        .map(|column| ColumnComparison {
+            name: column.name.clone(),
+            left: Some(column.clone()),
+            right: find(right, &column.name),
+        })
+        .collect::<Vec<_>>();
+    comparisons.extend(
+        right
+            .columns
+            .iter()
+            .filter(|column| find(left, &column.name).is_none())
+            .map(|column| ColumnComparison {
+                name: column.name.clone(),
+                left: None,
+                right: Some(column.clone()),
+            }),
+    );
+    comparisons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_aggregates_and_matches_columns_by_name() {
+        let left = TableChecksums::from_aggregate_row(
+            vec!["id".to_string(), "note".to_string()],
+            &["3", "3", "-12", "2", "40"].map(String::from),
+        );
+        assert_eq!(left.rows, 3);
+        assert_eq!(left.columns.get(1).and_then(|c| c.checksum), Some(40));
+
+        let right = TableChecksums::from_aggregate_row(
+            vec!["id".to_string(), "extra".to_string()],
+            &["3", "3", "-12", "0", "NULL"].map(String::from),
+        );
+        let statuses = compare_columns(&left, &right)
+            .iter()
+            .map(|c| (c.name.clone(), c.status()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            [
+                ("id".to_string(), CompareStatus::Same),
+                ("note".to_string(), CompareStatus::OnlyLeft),
+                ("extra".to_string(), CompareStatus::OnlyRight),
+            ]
+        );
+    }
+
+    #[test]
+    fn checksums_match_postgres() {
+        // ('x' || substr(md5('hello'), 1, 8))::bit(32)::int
+        assert_eq!(value_checksum("hello"), 1_564_557_354);
+    }
+}
@@ -0,0 +1,102 @@
+//! Rows of a table whose text column matches a search term.
+
+use crate::db::dialect::Dialect;
+
+/// How the term is matched against the column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextSearchMode {
+    /// Case-insensitive substring match (`ILIKE '%term%'`)
+    Contains,
+    /// Full-text match of the term's words, stemmed
+    /// (`to_tsvector @@ plainto_tsquery`)
+    FullText,
+}
+
+impl TextSearchMode {
+    pub const ALL: [Self; 2] = [Self::Contains, Self::FullText];
+
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Contains => "Contains the term (ILIKE)",
+            Self::FullText => {
+                "Matches its words (to_tsvector @@ plainto_tsquery)"
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+}
+
+/// `LIKE` pattern matching `term` anywhere, with `\` escaping the
+/// wildcards it contains.
+fn contains_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+/// Statement returning the rows of `schema.table` whose `column` matches
+/// `term`. `None` when the backend has no full-text search.
+#[must_use]
+pub fn text_search_query(
+    dialect: &dyn Dialect,
+    schema: &str,
+    table: &str,
+    column: &str,
+    term: &str,
+    mode: TextSearchMode,
+) -> Option<String> {
+    let column = dialect.quote_ident(column);
+    let condition = match mode {
+        TextSearchMode::Contains => dialect.contains_text_expr(
+            &column,
+            &dialect.quote_literal(&contains_pattern(term)),
+        ),
+        TextSearchMode::FullText => dialect
+            .full_text_match_expr(&column, &dialect.quote_literal(term))?,
+    };
+    Some(format!(
+        "SELECT *\nFROM {}\nWHERE {condition}",
+        dialect.qualified_table(schema, table)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::dialect::{PostgresDialect, SqliteDialect};
+
+    #[test]
+    fn matches_the_term_in_the_column() {
+        let query = |dialect: &dyn Dialect, term, mode| {
+            text_search_query(dialect, "public", "notes", "body", term, mode)
+        };
+        assert_eq!(
+            query(&PostgresDialect, "50%_off's", TextSearchMode::Contains)
+                .unwrap(),
+            "SELECT *\nFROM \"public\".\"notes\"\n\
+             WHERE \"body\"::text ILIKE '%50\\%\\_off''s%' ESCAPE '\\'"
+        );
+        assert_eq!(
+            query(&PostgresDialect, "late invoice", TextSearchMode::FullText)
+                .unwrap(),
+            "SELECT *\nFROM \"public\".\"notes\"\n\
+             WHERE to_tsvector(\"body\"::text) @@ plainto_tsquery('late invoice')"
+        );
+        assert_eq!(
+            query(&SqliteDialect, "late", TextSearchMode::Contains).unwrap(),
+            "SELECT *\nFROM \"public\".\"notes\"\n\
+             WHERE CAST(\"body\" AS TEXT) LIKE '%late%' ESCAPE '\\'"
+        );
+        assert_eq!(
+            query(&SqliteDialect, "late", TextSearchMode::FullText),
+            None
+        );
+    }
+}
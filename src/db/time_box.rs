@@ -0,0 +1,123 @@
+//! Limits on how long and how far the rows of an exploratory query are
+//! read, after which the rows read so far are shown as a partial result
+//! instead of waiting for the rest.
+
+use std::{fmt, time::Duration};
+
+use crate::db::TableRow;
+
+/// When to stop reading a query's rows; neither limit set reads them all.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeBox {
+    pub max_time: Option<Duration>,
+    pub max_rows: Option<usize>,
+}
+
+impl TimeBox {
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.max_time.is_some() || self.max_rows.is_some()
+    }
+
+    /// Whether `read` rows go past the row limit; one row past it is read
+    /// to tell a result of exactly that many rows from a longer one.
+    #[must_use]
+    pub fn rows_exceeded(&self, read: usize) -> bool {
+        self.max_rows.is_some_and(|max| read > max)
+    }
+
+    /// `rows` read in full, cut to the row limit.
+    #[must_use]
+    pub fn cut(&self, mut rows: Vec<TableRow>) -> TimeBoxedRows {
+        let cutoff = self.max_rows.filter(|_| self.rows_exceeded(rows.len()));
+        if let Some(max) = cutoff {
+            rows.truncate(max);
+        }
+        TimeBoxedRows {
+            rows,
+            cutoff: cutoff.map(Cutoff::Rows),
+        }
+    }
+}
+
+/// A time limit in seconds, such as `5` or `0.5`; `None` unless it is a
+/// positive number.
+#[must_use]
+pub fn parse_seconds(value: &str) -> Option<Duration> {
+    let seconds = value.trim().parse::<f64>().ok()?;
+    Duration::try_from_secs_f64(seconds)
+        .ok()
+        .filter(|limit| !limit.is_zero())
+}
+
+/// A row limit; `None` unless it is a positive whole number.
+#[must_use]
+pub fn parse_rows(value: &str) -> Option<usize> {
+    value.trim().parse().ok().filter(|&rows| rows > 0)
+}
+
+/// Which limit of a [`TimeBox`] stopped the reading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cutoff {
+    Time(Duration),
+    Rows(usize),
+}
+
+impl fmt::Display for Cutoff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Time(limit) => {
+                write!(f, "stopped after {} s", limit.as_secs_f64())
+            }
+            Self::Rows(limit) => write!(f, "stopped at {limit} rows"),
+        }
+    }
+}
+
+/// The rows read within a [`TimeBox`].
+#[derive(Debug, Default)]
+pub struct TimeBoxedRows {
+    pub rows: Vec<TableRow>,
+    /// Set when the query had rows past these that weren't read.
+    pub cutoff: Option<Cutoff>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(count: usize) -> Vec<TableRow> {
+        (0..count)
+            .map(|i| TableRow {
+                values: vec![i.to_string()],
+                column_names: vec!["n".to_string()],
+                column_types: Vec::new(),
+                column_sources: Vec::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cuts_rows_past_the_limit() {
+        let time_box = TimeBox {
+            max_time: None,
+            max_rows: Some(3),
+        };
+        let cut = time_box.cut(rows(5));
+        assert_eq!(cut.rows.len(), 3);
+        assert_eq!(cut.cutoff, Some(Cutoff::Rows(3)));
+        assert_eq!(time_box.cut(rows(3)).cutoff, None);
+        assert_eq!(TimeBox::default().cut(rows(5)).rows.len(), 5);
+
+        assert!(!TimeBox::default().is_enabled());
+        assert_eq!(parse_seconds(" 2.5 "), Some(Duration::from_millis(2500)));
+        assert_eq!(parse_seconds("0"), None);
+        assert_eq!(parse_seconds("-1"), None);
+        assert_eq!(parse_rows("500"), Some(500));
+        assert_eq!(parse_rows("0"), None);
+        assert_eq!(
+            Cutoff::Time(Duration::from_millis(2500)).to_string(),
+            "stopped after 2.5 s"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Statements ranked by `pg_stat_statements`, for finding the queries worth
+//! tuning.
+
+use crate::sort::parse_number;
+
+/// Rows fetched for the top queries list.
+pub const TOP_QUERIES_LIMIT: usize = 50;
+
+/// What the top queries are ranked by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopQueryOrder {
+    TotalTime,
+    MeanTime,
+    Calls,
+}
+
+impl TopQueryOrder {
+    pub const ALL: [Self; 3] = [Self::TotalTime, Self::MeanTime, Self::Calls];
+
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::TotalTime => "total time",
+            Self::MeanTime => "mean time",
+            Self::Calls => "calls",
+        }
+    }
+
+    /// Column of the top queries statement ranked on.
+    #[must_use]
+    pub const fn column(self) -> &'static str {
+        match self {
+            Self::TotalTime => "total_ms",
+            Self::MeanTime => "mean_ms",
+            Self::Calls => "calls",
+        }
+    }
+
+    #[must_use]
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+}
+
+/// One normalized statement with its execution totals.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopQuery {
+    /// Statement text with constants replaced by `$1`, `$2`, ...
+    pub query: String,
+    pub calls: u64,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub rows: u64,
+}
+
+impl TopQuery {
+    /// Read a row of `query, calls, total_ms, mean_ms, rows`.
+    #[must_use]
+    pub fn from_row(values: &[String]) -> Option<Self> {
+        let number = |idx: usize| values.get(idx).and_then(|v| parse_number(v));
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let count = |idx: usize| number(idx).map(|n| n.max(0.0) as u64);
+        Some(Self {
+            query: values.first()?.clone(),
+            calls: count(1)?,
+            total_ms: number(2)?,
+            mean_ms: number(3)?,
+            rows: count(4)?,
+        })
+    }
+
+    /// One line for a list: the totals and the statement on one line.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        format!(
+            "{:>10} total · {:>9} mean · {:>7} calls · {}",
+            format_duration(self.total_ms),
+            format_duration(self.mean_ms),
+            self.calls,
+            self.query.split_whitespace().collect::<Vec<_>>().join(" ")
+        )
+    }
+}
+
+/// Milliseconds in the largest unit that keeps them readable.
+fn format_duration(ms: f64) -> String {
+    if ms >= 60_000.0 {
+        format!("{:.1} min", ms / 60_000.0)
+    } else if ms >= 1000.0 {
+        format!("{:.2} s", ms / 1000.0)
+    } else {
+        format!("{ms:.2} ms")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_rows_and_summarises_on_one_line() {
+        let row = [
+            "SELECT *\n  FROM orders WHERE id = $1",
+            "1200",
+            "84000.5",
+            "70.0004",
+            "1200",
+        ]
+        .map(String::from);
+        let query = TopQuery::from_row(&row);
+        assert_eq!(query.as_ref().map(|q| q.calls), Some(1200));
+        assert_eq!(
+            query.map(|q| q.summary()),
+            Some(
+                "   1.4 min total ·  70.00 ms mean ·    1200 calls · \
+                 SELECT * FROM orders WHERE id = $1"
+                    .to_string()
+            )
+        );
+        assert_eq!(row.get(..3).and_then(TopQuery::from_row), None);
+    }
+}
@@ -0,0 +1,266 @@
+//! Links to a table, or a row of it, that open d7s there:
+//! `d7s://connection/[database/]schema/table[?pk=value]`. The connection is
+//! a saved one, found by name; a key of several columns is given as
+//! comma-separated values.
+
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use color_eyre::Result;
+use percent_encoding::{
+    AsciiSet, CONTROLS, PercentEncode, percent_decode_str, utf8_percent_encode,
+};
+
+use crate::{
+    app::App,
+    app_state::DatabaseExplorerState,
+    db::{connection::ConnectionType, recent_table::RecentTable},
+    row_lookup::key_indices,
+};
+
+/// Scheme of the links written; `conn://` is read too.
+const SCHEME: &str = "d7s://";
+const SCHEMES: [&str; 2] = [SCHEME, "conn://"];
+
+/// Characters escaped in a link's path segments and key.
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'&')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'=');
+
+/// A table, or a row of it, to open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeepLink {
+    /// Name of the saved connection.
+    pub connection: String,
+    /// Database to switch to; `None` keeps the connection's own.
+    pub database: Option<String>,
+    pub schema: String,
+    pub table: String,
+    /// Primary key value of the row, comma-separated for several columns.
+    pub key: Option<String>,
+}
+
+fn decode(text: &str) -> String {
+    percent_decode_str(text).decode_utf8_lossy().into_owned()
+}
+
+impl FromStr for DeepLink {
+    type Err = String;
+
+    fn from_str(link: &str) -> Result<Self, Self::Err> {
+        let link = link.trim();
+        let rest = SCHEMES
+            .iter()
+            .find_map(|scheme| link.strip_prefix(scheme))
+            .ok_or_else(|| format!("'{link}' doesn't start with {SCHEME}"))?;
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let segments: Vec<String> =
+            path.trim_end_matches('/').split('/').map(decode).collect();
+        let (connection, database, schema, table) = match segments.as_slice() {
+            [connection, schema, table] => (connection, None, schema, table),
+            [connection, database, schema, table] => {
+                (connection, Some(database), schema, table)
+            }
+            _ => {
+                return Err(format!(
+                    "'{link}' isn't {SCHEME}connection/[database/]schema/table"
+                ));
+            }
+        };
+        if [connection, schema, table]
+            .into_iter()
+            .chain(database)
+            .any(String::is_empty)
+        {
+            return Err(format!("'{link}' has an empty name"));
+        }
+        let key = query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("pk="))
+            .map(decode)
+            .filter(|key| !key.trim().is_empty());
+        Ok(Self {
+            connection: connection.clone(),
+            database: database.cloned(),
+            schema: schema.clone(),
+            table: table.clone(),
+            key,
+        })
+    }
+}
+
+impl Display for DeepLink {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fn encode(text: &str) -> PercentEncode<'_> {
+            utf8_percent_encode(text, SEGMENT)
+        }
+        write!(f, "{SCHEME}{}/", encode(&self.connection))?;
+        if let Some(database) = &self.database {
+            write!(f, "{}/", encode(database))?;
+        }
+        write!(f, "{}/{}", encode(&self.schema), encode(&self.table))?;
+        if let Some(key) = &self.key {
+            write!(f, "?pk={}", encode(key))?;
+        }
+        Ok(())
+    }
+}
+
+impl App<'_> {
+    /// Connect to the saved connection a link from the command line names;
+    /// the table is opened once the connection is.
+    pub(crate) async fn connect_to_link(&mut self) -> Result<()> {
+        let Some(link) = &self.pending_link else {
+            return Ok(());
+        };
+        let connections = &mut self.database_explorer.connections.table;
+        let Some(idx) = connections
+            .model
+            .items
+            .iter()
+            .position(|connection| connection.name == link.connection)
+        else {
+            let status =
+                format!("No saved connection named {}.", link.connection);
+            self.pending_link = None;
+            self.set_status(status);
+            return Ok(());
+        };
+        connections.view.state.select(Some(idx));
+        self.connect_to_database().await
+    }
+
+    /// Open the table and row of the link waiting for the connection just
+    /// opened
+    pub(crate) async fn follow_link(&mut self) -> Result<()> {
+        let Some(link) = self.pending_link.take().filter(|link| {
+            link.connection == self.database_explorer.connection.name
+        }) else {
+            return Ok(());
+        };
+        let recent = RecentTable {
+            database: link.database,
+            schema_name: link.schema.clone(),
+            table_name: link.table.clone(),
+        };
+        self.open_recent_table(&recent).await?;
+        if self.database_explorer.state
+            != DatabaseExplorerState::TableData(link.schema, link.table)
+        {
+            return Ok(());
+        }
+        if let Some(key) = link.key
+            && let Some(key_columns) = self.primary_key_columns().await
+        {
+            self.go_to_row(&key_columns, &key).await;
+        }
+        Ok(())
+    }
+
+    /// Copy a link to the table being browsed, and to the selected row
+    /// when the table has a primary key
+    pub(crate) async fn copy_link(&mut self) {
+        let explorer = &self.database_explorer;
+        let DatabaseExplorerState::TableData(schema, table) = &explorer.state
+        else {
+            return;
+        };
+        let (schema, table) = (schema.clone(), table.clone());
+        let database = explorer.database.clone();
+        let mut key = None;
+        if let Some(database) = database
+            && let Ok(key_columns) =
+                database.get_primary_key_columns(&schema, &table).await
+            && let Some(fd) = self.database_explorer.table_data.as_ref()
+            && let Some(row) = fd
+                .table
+                .view
+                .state
+                .selected()
+                .and_then(|idx| fd.table.model.items.get(idx))
+                .filter(|row| !row.is_draft)
+            && let Some(indices) = key_indices(&key_columns, &row.column_names)
+            && !indices.is_empty()
+        {
+            let values: Option<Vec<&str>> = indices
+                .iter()
+                .map(|&idx| row.values.get(idx).map(String::as_str))
+                .collect();
+            key = values.map(|values| values.join(","));
+        }
+        let connection = &self.database_explorer.connection;
+        let link = DeepLink {
+            connection: connection.name.clone(),
+            // SQLite has no databases to switch between
+            database: match connection.r#type {
+                ConnectionType::Postgres => {
+                    connection.selected_database.clone()
+                }
+                ConnectionType::Sqlite => None,
+            },
+            schema,
+            table,
+            key,
+        }
+        .to_string();
+        if Self::copy_to_clipboard(&link) {
+            self.set_status(format!("Copied link: {link}"));
+        } else {
+            self.set_status("Failed to copy to the clipboard.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_links() {
+        let link: DeepLink =
+            "d7s://prod/shop/public/orders?pk=123".parse().unwrap();
+        assert_eq!(
+            link,
+            DeepLink {
+                connection: "prod".to_string(),
+                database: Some("shop".to_string()),
+                schema: "public".to_string(),
+                table: "orders".to_string(),
+                key: Some("123".to_string()),
+            }
+        );
+        let link: DeepLink =
+            "conn://my%20db/main/line%2Fitems".parse().unwrap();
+        assert_eq!(link.connection, "my db");
+        assert_eq!(link.database, None);
+        assert_eq!(link.table, "line/items");
+        assert_eq!(link.key, None);
+        assert!("d7s://prod/orders".parse::<DeepLink>().is_err());
+        assert!("d7s://prod//orders".parse::<DeepLink>().is_err());
+        assert!("https://prod/public/orders".parse::<DeepLink>().is_err());
+    }
+
+    #[test]
+    fn writes_links_it_reads_back() {
+        let link = DeepLink {
+            connection: "staging eu".to_string(),
+            database: None,
+            schema: "public".to_string(),
+            table: "order items".to_string(),
+            key: Some("3,a&b".to_string()),
+        };
+        let text = link.to_string();
+        assert_eq!(text, "d7s://staging%20eu/public/order%20items?pk=3,a%26b");
+        assert_eq!(text.parse::<DeepLink>().unwrap(), link);
+    }
+}
@@ -0,0 +1,33 @@
+//! Where the disk goes: sizes per schema or per table, shown as SQL results
+//! so they can be sorted and refreshed like any other.
+
+use crate::{app::App, app_state::DatabaseExplorerState};
+
+impl App<'_> {
+    /// Disk used by every schema, or by every table of the schema being
+    /// browsed
+    pub(crate) async fn open_disk_usage(&mut self) {
+        let Some(database) = self.database_explorer.database.as_ref() else {
+            return;
+        };
+        let dialect = database.dialect();
+        let query = match &self.database_explorer.state {
+            DatabaseExplorerState::Tables(schema) => {
+                dialect.table_sizes_query(schema)
+            }
+            DatabaseExplorerState::Schemas => {
+                dialect.schema_sizes_query().map(str::to_string)
+            }
+            DatabaseExplorerState::Connections
+            | DatabaseExplorerState::Databases
+            | DatabaseExplorerState::Columns(..)
+            | DatabaseExplorerState::TableData(..)
+            | DatabaseExplorerState::SqlResults(_) => return,
+        };
+        let Some(query) = query else {
+            self.set_status("This database doesn't report disk usage.");
+            return;
+        };
+        self.execute_sql_statement_now(query).await;
+    }
+}
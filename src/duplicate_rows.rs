@@ -0,0 +1,82 @@
+//! Finding rows that repeat the values of columns meant to be unique.
+
+use crate::{
+    app::App,
+    app_state::DatabaseExplorerState,
+    db::duplicates::{duplicate_rows_query, parse_column_list},
+    ui::widgets::modal::{InputModal, InputPurpose},
+};
+
+impl App<'_> {
+    /// Ask which columns of the table being browsed should be unique
+    /// together, starting from the selected one
+    pub(crate) fn open_duplicate_rows_prompt(&mut self) {
+        let explorer = &self.database_explorer;
+        let DatabaseExplorerState::TableData(schema_name, table_name) =
+            &explorer.state
+        else {
+            return;
+        };
+        let selected = explorer
+            .table_data
+            .as_ref()
+            .and_then(|data| {
+                let table = &data.table;
+                table.column_name(table.view.state.selected_column()?)
+            })
+            .unwrap_or_default();
+        let prompt = format!(
+            "Rows of {schema_name}.{table_name} repeating these columns, comma-separated:"
+        );
+        let completions = self.column_completions(schema_name, table_name);
+        self.modal_manager.open_input_modal(
+            InputModal::new(
+                " Duplicate rows ",
+                prompt,
+                InputPurpose::DuplicateRows,
+            )
+            .with_value(selected)
+            .with_completions(completions),
+        );
+    }
+
+    /// Show the rows of the table being browsed that share their values in
+    /// `columns` with another row
+    pub(crate) async fn find_duplicate_rows(&mut self, columns: &str) {
+        let explorer = &self.database_explorer;
+        let DatabaseExplorerState::TableData(schema_name, table_name) =
+            &explorer.state
+        else {
+            return;
+        };
+        let Some(database) = explorer.database.as_ref() else {
+            return;
+        };
+        let known = explorer
+            .table_data
+            .as_ref()
+            .and_then(|data| data.table.model.dynamic_column_names.clone())
+            .unwrap_or_default();
+        let columns = parse_column_list(columns);
+        if columns.is_empty() {
+            self.set_status("Name at least one column.");
+            return;
+        }
+        if let Some(unknown) = columns
+            .iter()
+            .find(|column| !known.is_empty() && !known.contains(column))
+        {
+            self.set_status(format!(
+                "No column '{unknown}' in {schema_name}.{table_name}."
+            ));
+            return;
+        }
+        let sql = duplicate_rows_query(
+            database.dialect(),
+            schema_name,
+            table_name,
+            &columns,
+        );
+        self.execute_sql_statement_now(sql).await;
+    }
+}
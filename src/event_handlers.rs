@@ -1,9 +1,8 @@
 use color_eyre::Result;
-use crossterm::event::{
-    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
-};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
 use ratatui::{
     style::{Color, Style},
+    text::Line,
     widgets::{Block, Borders, TableState},
 };
 use ratatui_textarea::TextArea;
@@ -11,10 +10,28 @@ use ratatui_textarea::TextArea;
 use crate::{
     app::App,
     app_state::{AppState, DatabaseExplorerState},
-    db::connection::ConnectionType,
-    services::{ConnectionService, PasswordService},
+    confirmations::ConfirmAction,
+    db::{
+        connection::{
+            Connection, ConnectionType, check_target_session_attrs,
+            connection_string_from_env,
+        },
+        text_search::TextSearchMode,
+        top_query::TopQueryOrder,
+    },
+    macros::MacroKey,
+    services::{
+        ConnectionService, PasswordService, connection_service::TestOutcome,
+    },
     sql::safety::split_statements,
-    ui::widgets::modal::{ModalAction, TestResult},
+    ui::{
+        keymap::{self, KeyAction, Screen},
+        widgets::modal::{
+            ChoiceAction, ChoiceModal, ChoicePurpose, InfoModal, InputPurpose,
+            KeyringFallbackChoice, Modal, ModalAction, PasswordStorageType,
+            PlanBaselineChoice, StoreRecoveryChoice, TestResult,
+        },
+    },
 };
 
 impl App<'_> {
@@ -26,6 +43,7 @@ impl App<'_> {
         match event::read()? {
             Event::Key(key) if key.kind == KeyEventKind::Press => {
                 self.clear_status();
+                self.macros.record(key);
                 self.on_key_event(key).await?;
             }
             // Ignore non-press key events
@@ -43,22 +61,10 @@ impl App<'_> {
 
     /// Handles the key events and updates the state of [`App`].
     pub async fn on_key_event(&mut self, key: KeyEvent) -> Result<()> {
-        // Handle search filter input first
-        if let Some(textarea) = &mut self.search_filter {
-            if key.code == KeyCode::Esc {
-                self.clear_filter();
-                self.search_filter = None;
-                return Ok(());
-            }
-            if key.code == KeyCode::Enter {
-                self.apply_filter();
-                self.search_filter = None;
-                return Ok(());
-            }
-
-            if textarea.input(key) {
-                return Ok(());
-            }
+        // The search bar takes every key while it is open
+        if self.search_filter.is_some() {
+            self.handle_search_filter_key(key);
+            return Ok(());
         }
 
         // Handle modal events
@@ -66,165 +72,293 @@ impl App<'_> {
             return self.handle_modal_events(key).await;
         }
 
-        // Handle application shortcuts (q, n, d, e, t, s, Esc, Enter)
-        if self.handle_table_data_hotkeys(key).await? {
-            return Ok(());
-        }
-        if self.handle_hotkeys(key).await? {
-            return Ok(());
+        // The register after `Q` or `@` can be any key
+        if self.macros.is_pending() {
+            return self.handle_macro_key(key).await;
         }
 
-        // Paged table data: j/k at the window edge load the next/previous page
-        if self.state == AppState::DatabaseConnected
-            && matches!(
-                self.database_explorer.state,
-                DatabaseExplorerState::TableData(..)
-            )
-            && self.try_step_virtual_table_page(key).await?
-        {
-            return Ok(());
-        }
-
-        // Handle navigation keys (j/k/h/l, 0/$, g/G, /)
-        self.handle_navigation_keys(key);
+        self.handle_hotkeys(key).await
+    }
 
-        Ok(())
+    /// Run what `key` does on the current screen, as listed in [`KEYMAP`];
+    /// keys the screen doesn't bind are ignored
+    ///
+    /// [`KEYMAP`]: crate::ui::keymap::KEYMAP
+    async fn handle_hotkeys(&mut self, key: KeyEvent) -> Result<()> {
+        match keymap::action_for(&self.database_explorer.state, key) {
+            Some(action) => self.run_key_action(action, key).await,
+            None => Ok(()),
+        }
     }
 
-    /// Handle application shortcuts (q, n, d, e, E, t, Esc, Enter)
-    /// Returns true if the key was handled and should stop processing
     #[allow(clippy::too_many_lines)]
-    async fn handle_hotkeys(&mut self, key: KeyEvent) -> Result<bool> {
-        match (key.modifiers, key.code) {
-            (_, KeyCode::Char('q'))
-            | (KeyModifiers::CONTROL, KeyCode::Char('c' | 'C')) => {
-                self.quit();
-                Ok(true)
-            }
-            (_, KeyCode::Char('y')) => {
-                self.copy();
-                Ok(true)
-            }
-            (_, KeyCode::Char(c @ '1'..='5')) => {
-                if self.state == AppState::DatabaseConnected {
-                    let idx = usize::from(c as u8 - b'1');
-                    if let Some((schema, table)) =
-                        self.database_explorer.recent_tables.get(idx).cloned()
-                    {
-                        self.load_table_data(&schema, &table).await?;
-                    }
-                    Ok(true)
-                } else {
-                    Ok(false)
-                }
-            }
-            (_, KeyCode::Char('n')) => {
+    async fn run_key_action(
+        &mut self,
+        action: KeyAction,
+        key: KeyEvent,
+    ) -> Result<()> {
+        match action {
+            KeyAction::Quit => self.quit(),
+            KeyAction::ResizeTopBar { grow } => self.resize_top_bar(grow),
+            KeyAction::CycleCompactMode => self.cycle_compact_mode(),
+            KeyAction::ToggleAccessibleMode => self.toggle_accessible_mode(),
+            KeyAction::Copy => self.copy(),
+            KeyAction::Macro => self.handle_macro_key(key).await?,
+            KeyAction::Back => self.handle_escape(),
+            KeyAction::Open => {
                 if matches!(
                     self.database_explorer.state,
                     DatabaseExplorerState::Connections
                 ) {
-                    self.modal_manager.open_new_connection_modal();
+                    self.connect_to_database().await?;
+                } else {
+                    self.handle_database_navigation().await?;
                 }
-                Ok(true)
             }
-            (_, KeyCode::Char('d')) => {
-                if matches!(
-                    self.database_explorer.state,
-                    DatabaseExplorerState::Connections
-                ) {
-                    self.handle_delete_connection();
-                    return Ok(true);
+            KeyAction::Search => self.open_search_filter(),
+            // Paged table data: j/k at the window edge load the next or
+            // previous page
+            KeyAction::Move(code) => {
+                if !self.try_step_virtual_table_page(key).await? {
+                    self.handle_database_table_navigation(code);
                 }
-                Ok(false)
             }
-            (_, KeyCode::Char('e')) => {
-                if matches!(
-                    self.database_explorer.state,
-                    DatabaseExplorerState::Connections
-                ) {
-                    self.handle_edit_connection();
-                } else if self.state == AppState::DatabaseConnected {
-                    self.open_editor_requested = true;
-                }
-                Ok(true)
+            KeyAction::KeyHelp => self.open_key_help(),
+            KeyAction::NewConnection => {
+                self.modal_manager.open_new_connection_modal();
+            }
+            KeyAction::ImportConnection => self.import_connection_from_env(),
+            KeyAction::EditConnection => self.handle_edit_connection(),
+            KeyAction::DeleteConnection => self.handle_delete_connection(),
+            KeyAction::QuickConnect => self.open_quick_connect_prompt(),
+            KeyAction::TestConnection => {
+                self.handle_test_selected_connection();
             }
-            (_, KeyCode::Char('t')) => {
-                if self.state == AppState::DatabaseConnected {
-                    self.handle_toggle_table_view().await?;
+            KeyAction::TestAllConnections => {
+                self.handle_test_all_connections();
+            }
+            KeyAction::SortConnections => self.cycle_connection_sort(),
+            KeyAction::ConnectionColumns => {
+                self.open_connection_columns_prompt();
+            }
+            KeyAction::CopyConnectionUri => {
+                if let Some(connection) = self.get_selected_connection() {
+                    let modal = ChoiceModal::connection_uri(connection.clone());
+                    self.modal_manager.open_choice_modal(modal);
                 }
-                Ok(true)
             }
-            (_, KeyCode::Char('E')) => {
-                if matches!(
-                    self.database_explorer.state,
-                    DatabaseExplorerState::SqlResults(_)
-                ) {
-                    let statements = split_statements(
-                        &self.database_explorer.sql_executor.sql_input(),
-                    );
-                    if statements.is_empty() {
-                        self.set_status(
-                            "No SQL statements found in editor file.",
-                        );
-                        return Ok(true);
-                    }
-                    let options = statements
-                        .into_iter()
-                        .map(|s| s.text)
-                        .collect::<Vec<_>>();
-                    self.modal_manager.open_sql_query_selection_modal(options);
-                    return Ok(true);
-                }
-                Ok(false)
-            }
-            (_, KeyCode::Esc) => {
-                if self.modal_manager.is_any_modal_open() {
-                    self.modal_manager.close_active_modal();
-                } else if self
-                    .database_explorer
-                    .current_table_state_mut()
-                    .and_then(|s| s.selected_column())
-                    .is_some()
+            KeyAction::SqlEditor => self.open_editor_requested = true,
+            KeyAction::KeywordCase => self.toggle_keyword_case(),
+            KeyAction::MaskSecrets => self.toggle_secret_masking(),
+            KeyAction::Views => self.open_views_list(),
+            KeyAction::RecentTables => self.open_recent_tables_list(),
+            KeyAction::OpenRecentTable(idx) => {
+                if let Some(recent) =
+                    self.database_explorer.recent_tables.get(idx).cloned()
                 {
-                    self.reset_table_selection_state();
-                } else if matches!(
-                    self.database_explorer.state,
-                    DatabaseExplorerState::TableData(_, _)
-                ) && self.discard_table_draft()
+                    self.open_recent_table(&recent).await?;
+                }
+            }
+            KeyAction::ResultHistory => self.open_result_history(),
+            KeyAction::Psql => self.open_psql_requested = true,
+            KeyAction::SearchPath => self.open_search_path_prompt(),
+            KeyAction::TopQueries => {
+                self.modal_manager
+                    .open_choice_modal(ChoiceModal::top_queries_order());
+            }
+            KeyAction::RunSqlFile => self.open_source_prompt(),
+            KeyAction::GroupDigits => self.toggle_number_format(),
+            KeyAction::RelativeTimes => self.toggle_time_display(),
+            KeyAction::GoToTable => self.open_table_finder(),
+            KeyAction::Disconnect => self.disconnect(),
+            KeyAction::Savepoint => self.open_savepoint_prompt(),
+            KeyAction::RollbackToSavepoint => self.open_savepoint_list(),
+            KeyAction::DiffRows => self.show_row_diff(),
+            KeyAction::ColumnStats => self.show_column_stats().await,
+            KeyAction::Histogram => self.show_column_histogram().await,
+            KeyAction::GroupBy => self.open_group_summary(),
+            KeyAction::ExportDdl => self.open_schema_ddl_export(),
+            KeyAction::DiskUsage => self.open_disk_usage().await,
+            KeyAction::CompareTables => self.compare_current_table().await,
+            KeyAction::FkOrphans => self.check_orphans().await,
+            KeyAction::ToggleTableView => {
+                self.handle_toggle_table_view().await?;
+            }
+            KeyAction::Refresh => {
+                self.invalidate_catalog();
+                self.reload_current_table_data().await?;
+            }
+            KeyAction::NewRow => self.table_data_add_blank_draft()?,
+            KeyAction::CopyRow => self.table_data_duplicate_as_draft().await?,
+            KeyAction::CommitRow => self.table_data_commit_draft().await?,
+            KeyAction::DeleteRow => self.table_data_request_delete().await?,
+            KeyAction::MultiSelect => self.table_data_toggle_multi_select(),
+            KeyAction::SaveView => self.open_save_view_prompt(),
+            KeyAction::HideColumn => self.table_data_hide_selected_column(),
+            KeyAction::ShowAllColumns => {
+                if let Some(fd) = self.database_explorer.table_data.as_mut() {
+                    fd.table.show_all_columns();
+                }
+            }
+            KeyAction::ExpandColumn => self.table_data_toggle_expand_column(),
+            KeyAction::ResizeColumn { wider } => {
+                self.table_data_resize_column(wider);
+            }
+            KeyAction::MultiLine => {
+                if let Some(fd) = self.database_explorer.table_data.as_mut() {
+                    let view = &mut fd.table.view;
+                    view.multi_line = !view.multi_line;
+                }
+            }
+            KeyAction::WrapColumn => self.toggle_wrapped_column(),
+            KeyAction::PinRow => self.toggle_pinned_row(),
+            KeyAction::RowNote => self.open_row_note_editor().await,
+            KeyAction::CopyInList => self.copy_column_in_list(),
+            KeyAction::JsonPath => self.open_json_path_prompt(),
+            KeyAction::OrderBy => self.table_data_cycle_order().await,
+            KeyAction::NullsOrder => self.table_data_toggle_nulls_order().await,
+            KeyAction::Duplicates => self.open_duplicate_rows_prompt(),
+            KeyAction::RowColors => self.open_row_color_editor(),
+            KeyAction::SearchColumn => self.open_column_search(),
+            KeyAction::ShowQuery => self.show_fetch_sql = !self.show_fetch_sql,
+            KeyAction::QueryToEditor => {
+                if let Some(sql) = self.database_explorer.table_data_sql.clone()
                 {
-                    self.set_status("Draft discarded.");
-                } else if self.state == AppState::DatabaseConnected {
-                    let is_sql_executor = matches!(
-                        self.database_explorer.state,
-                        DatabaseExplorerState::SqlResults(_)
-                    );
-
-                    if is_sql_executor {
-                        self.escape_from_or_return_to_sql_editor();
-                    } else if self.has_active_filter() {
-                        self.clear_filter();
-                    } else {
-                        self.go_back_in_database();
-                    }
-                } else if self.state == AppState::ConnectionList {
-                    self.clear_filter();
+                    self.copy_into_sql_executor(&sql);
                 }
+            }
+            KeyAction::KeysetPaging => self.toggle_keyset_paging().await,
+            KeyAction::JumpToKey => self.open_key_jump_prompt().await,
+            KeyAction::GoToRow => self.open_row_lookup_prompt().await,
+            KeyAction::CopyLink => self.copy_link().await,
+            KeyAction::ClearFilterOrder => self.clear_sticky_predicates().await,
+            KeyAction::RunSql => self.open_sql_statement_selection(),
+            KeyAction::RunAgain => self.rerun_sql_statement().await,
+            KeyAction::PlanBaseline => self.plan_baseline_action().await,
+            KeyAction::IndexHints => self.open_index_suggestions().await,
+            KeyAction::Benchmark => self.open_benchmark_prompt(),
+            KeyAction::BlockInsert => self.open_block_insert_prompt(),
+            KeyAction::LineCommand => self.open_line_command_prompt(),
+            KeyAction::SortResults => self.sort_sql_results(),
+            KeyAction::DistinctRows => self.toggle_distinct_sql_results(),
+            KeyAction::MarkRow => {
+                self.database_explorer
+                    .sql_executor
+                    .table_state
+                    .toggle_selected_row_mark();
+            }
+            KeyAction::FetchMore => self.fetch_more_sql_results().await,
+            KeyAction::ExportResults => self.export_sql_results(),
+        }
+        Ok(())
+    }
 
-                Ok(true)
+    /// Every key of the current screen, with what it does
+    fn open_key_help(&mut self) {
+        let state = &self.database_explorer.state;
+        let lines = keymap::bindings(Screen::of(state))
+            .map(|binding| {
+                let hotkey = &binding.hotkey;
+                Line::from(format!(
+                    " {:<10} {}",
+                    hotkey.to_string(),
+                    hotkey.description.display_suffix()
+                ))
+            })
+            .collect();
+        let title = format!(" Keys: {} ", state.mode_label());
+        self.modal_manager
+            .open_info_modal(InfoModal::new(title, lines));
+    }
+
+    /// Offer the statements of the SQL buffer to run
+    fn open_sql_statement_selection(&mut self) {
+        let statements =
+            split_statements(&self.database_explorer.sql_executor.sql_input());
+        if statements.is_empty() {
+            self.set_status("No SQL statements found in editor file.");
+            return;
+        }
+        let options =
+            statements.into_iter().map(|s| s.text).collect::<Vec<_>>();
+        self.modal_manager.open_sql_query_selection_modal(
+            options,
+            self.database_explorer.sql_executor.selected_statement(),
+        );
+    }
+
+    /// Esc clears the column selection, a draft or the filter before going
+    /// back a level
+    fn handle_escape(&mut self) {
+        if self.modal_manager.is_any_modal_open() {
+            self.modal_manager.close_active_modal();
+        } else if self
+            .database_explorer
+            .current_table_state_mut()
+            .and_then(|s| s.selected_column())
+            .is_some()
+        {
+            self.reset_table_selection_state();
+        } else if matches!(
+            self.database_explorer.state,
+            DatabaseExplorerState::TableData(_, _)
+        ) && self.discard_table_draft()
+        {
+            self.set_status("Draft discarded.");
+        } else if self.state == AppState::DatabaseConnected {
+            let is_sql_executor = matches!(
+                self.database_explorer.state,
+                DatabaseExplorerState::SqlResults(_)
+            );
+
+            if self.has_active_filter() {
+                self.clear_filter();
+            } else if is_sql_executor {
+                self.escape_from_or_return_to_sql_editor();
+            } else {
+                self.go_back_in_database();
             }
-            (_, KeyCode::Enter) => {
-                if matches!(
-                    self.database_explorer.state,
-                    DatabaseExplorerState::Connections
-                ) {
-                    self.connect_to_database().await?;
-                } else {
-                    self.handle_database_navigation().await?;
-                }
-                Ok(true)
+        } else if self.state == AppState::ConnectionList
+            && !self.cancel_connect_retry()
+        {
+            self.clear_filter();
+        }
+    }
+
+    /// `Q<reg>` to record a macro, `@<reg>` to replay one
+    async fn handle_macro_key(&mut self, key: KeyEvent) -> Result<()> {
+        match self.macros.handle(key) {
+            MacroKey::Pass | MacroKey::Consumed => {}
+            MacroKey::Started(register) => {
+                self.set_status(format!("Recording @{register}, Q to stop"));
+            }
+            MacroKey::Stopped(register, count) => {
+                self.set_status(format!(
+                    "Recorded {count} keys to @{register}"
+                ));
             }
-            _ => Ok(false),
+            MacroKey::Empty(register) => {
+                self.set_status(format!("Register @{register} is empty"));
+            }
+            MacroKey::Replay(keys) => self.replay_macro(keys).await?,
         }
+        Ok(())
+    }
+
+    /// Feed recorded keys back through [`Self::on_key_event`]
+    async fn replay_macro(&mut self, keys: Vec<KeyEvent>) -> Result<()> {
+        if !self.macros.enter_replay() {
+            self.set_status("Macros nested too deep, replay stopped.");
+            return Ok(());
+        }
+        for key in keys {
+            if !self.running {
+                break;
+            }
+            Box::pin(self.on_key_event(key)).await?;
+        }
+        self.macros.exit_replay();
+        Ok(())
     }
 
     fn escape_from_or_return_to_sql_editor(&mut self) {
@@ -248,66 +382,53 @@ impl App<'_> {
         table_state.select(row);
     }
 
-    /// Handle navigation keys (j/k/h/l, 0/$, g/G, /)
-    fn handle_navigation_keys(&mut self, key: KeyEvent) {
-        match (key.modifiers, key.code) {
-            // Vim keybindings for table navigation (Connections and DatabaseConnected use same handler)
-            (_, KeyCode::Char('j') | KeyCode::Down) => {
-                self.handle_database_table_navigation(KeyCode::Down);
-            }
-            (_, KeyCode::Char('k') | KeyCode::Up) => {
-                self.handle_database_table_navigation(KeyCode::Up);
-            }
-            (_, KeyCode::Char('h' | 'b') | KeyCode::Left) => {
-                self.handle_database_table_navigation(KeyCode::Left);
-            }
-            (_, KeyCode::Char('l' | 'w') | KeyCode::Right) => {
-                self.handle_database_table_navigation(KeyCode::Right);
-            }
-            // Jump to edges
-            (_, KeyCode::Char('0')) => {
-                self.handle_database_table_navigation(KeyCode::Char('0'));
-            }
-            (_, KeyCode::Char('$')) => {
-                self.handle_database_table_navigation(KeyCode::Char('$'));
-            }
-            (_, KeyCode::Char('g')) => {
-                self.handle_database_table_navigation(KeyCode::Char('g'));
-            }
-            (_, KeyCode::Char('G')) => {
-                self.handle_database_table_navigation(KeyCode::Char('G'));
-            }
-            (_, KeyCode::Char('/')) => {
-                if !self.modal_manager.is_any_modal_open() {
-                    let mut search_bar = TextArea::default();
-                    search_bar.set_cursor_line_style(Style::default());
-                    search_bar.set_placeholder_text("/");
-                    search_bar.set_style(Style::default().fg(Color::White));
-                    search_bar.set_max_histories(0);
-                    search_bar.set_block(
-                        Block::default()
-                            .border_style(Color::White)
-                            .borders(Borders::ALL)
-                            .title(" Search Filter (ESC to cancel) "),
-                    );
-                    self.search_filter = Some(search_bar);
-                }
-            }
-            _ => {}
-        }
+    /// Open the search bar that filters the rows on screen
+    fn open_search_filter(&mut self) {
+        let mut search_bar = TextArea::default();
+        search_bar.set_cursor_line_style(Style::default());
+        search_bar.set_placeholder_text("/");
+        search_bar.set_style(Style::default().fg(Color::White));
+        search_bar.set_max_histories(0);
+        search_bar.set_block(
+            Block::default()
+                .border_style(Color::White)
+                .borders(Borders::ALL)
+                .title(" Search Filter (Enter to keep, ESC to cancel) "),
+        );
+        self.search_filter = Some(search_bar);
     }
 
     /// Handle delete connection action
     fn handle_delete_connection(&mut self) {
-        let Some(connection) = self.get_selected_connection() else {
+        let Some(connection) = self.get_selected_connection().cloned() else {
             return;
         };
+        if !self.confirmations.asks(ConfirmAction::DeleteConnection) {
+            self.delete_connection(&connection);
+            return;
+        }
         let message = format!(
             "Are you sure you want to delete\nthe connection '{}'?\n\nThis action cannot be undone.",
             connection.name
         );
-        self.modal_manager
-            .open_confirmation_modal(message, connection.clone());
+        self.modal_manager.open_confirmation_modal(message, connection);
+    }
+
+    /// Delete `connection` and its saved password
+    fn delete_connection(&mut self, connection: &Connection) {
+        // Delete from keyring only for Postgres (SQLite has no passwords)
+        if connection.r#type == ConnectionType::Postgres
+            && !connection.should_ask_every_time()
+        {
+            let _ = PasswordService::delete_from_keyring(&connection.name);
+        }
+
+        // Delete connection using ConnectionService
+        if let Err(e) = ConnectionService::delete(&connection.name) {
+            self.set_status(format!("Failed to delete connection: {e}"));
+        } else {
+            self.refresh_connections();
+        }
     }
 
     /// Handle edit connection action
@@ -315,12 +436,139 @@ impl App<'_> {
         let Some(connection) = self.get_selected_connection() else {
             return;
         };
-        let password = PasswordService::get_connection_password(connection);
+        let password = self
+            .password_service
+            .get_password(connection)
+            .unwrap_or_default();
         let connection = connection.clone();
         self.modal_manager
             .open_edit_connection_modal(&connection, password);
     }
 
+    /// Open the new connection form prefilled from `DATABASE_URL` or the
+    /// libpq `PG*` variables
+    fn import_connection_from_env(&mut self) {
+        let Some(url) =
+            connection_string_from_env(|name| std::env::var(name).ok())
+        else {
+            self.set_status(
+                "None of DATABASE_URL, PGHOST, PGPORT, PGUSER or PGDATABASE is set.",
+            );
+            return;
+        };
+        if let Err(e) = check_target_session_attrs(&url) {
+            self.set_status(e);
+            return;
+        }
+        self.modal_manager.open_new_connection_modal_with_url(&url);
+    }
+
+    /// Copy the URI of the connection being edited, without its password
+    fn copy_connection_modal_uri(&mut self) {
+        if let Some(uri) = self
+            .modal_manager
+            .get_connection_modal()
+            .and_then(Modal::uri_preview)
+            && Self::copy_to_clipboard(&uri)
+        {
+            self.set_status(format!("Copied: {uri}"));
+        }
+    }
+
+    /// Copy a connection's URI, looking up its password when asked to
+    fn copy_connection_uri(
+        &mut self,
+        connection: &Connection,
+        with_password: bool,
+    ) {
+        let password = with_password
+            .then(|| self.password_service.get_password(connection))
+            .flatten();
+        let uri = connection.connection_uri(password.as_deref());
+        if !Self::copy_to_clipboard(&uri) {
+            self.set_status("Failed to copy to the clipboard.");
+        } else if password.is_some() {
+            self.set_status("Copied the URI with its password.");
+        } else if with_password {
+            self.set_status(format!(
+                "No password saved for '{}'; copied: {uri}",
+                connection.name
+            ));
+        } else {
+            self.set_status(format!("Copied: {uri}"));
+        }
+    }
+
+    /// Test the selected connection in the background and report the
+    /// outcome in the status line
+    fn handle_test_selected_connection(&mut self) {
+        let Some(connection) = self.get_selected_connection().cloned() else {
+            return;
+        };
+        let connection = self.password_service.with_password(connection);
+        let label = format!("Testing {}", connection.name);
+        self.start_background_task(&label, |_| async move {
+            let report = ConnectionService::test_one(connection).await;
+            let line = format!("Connection test: {}", report.summary_line());
+            match report.outcome {
+                TestOutcome::Passed => Ok(line),
+                TestOutcome::Failed(_) | TestOutcome::Skipped(_) => Err(line),
+            }
+        });
+    }
+
+    /// Test every saved connection in the background and show a pass/fail
+    /// report once they have all answered
+    fn handle_test_all_connections(&mut self) {
+        let connections = match ConnectionService::get_all() {
+            Ok(connections) => connections,
+            Err(e) => {
+                self.set_status(format!("Failed to load connections: {e}"));
+                return;
+            }
+        };
+        if connections.is_empty() {
+            self.set_status("No saved connections to test.");
+            return;
+        }
+
+        let connections: Vec<_> = connections
+            .into_iter()
+            .map(|connection| self.password_service.with_password(connection))
+            .collect();
+        let label = format!("Testing {} connections", connections.len());
+        self.start_background_task(&label, |progress| async move {
+            let reports = ConnectionService::test_all(connections).await;
+
+            let failed = reports
+                .iter()
+                .filter(|r| matches!(r.outcome, TestOutcome::Failed(_)))
+                .count();
+            let lines = reports
+                .iter()
+                .map(|report| {
+                    let color = match report.outcome {
+                        TestOutcome::Passed => Color::Green,
+                        TestOutcome::Failed(_) => Color::Red,
+                        TestOutcome::Skipped(_) => Color::DarkGray,
+                    };
+                    Line::styled(
+                        report.summary_line(),
+                        Style::default().fg(color),
+                    )
+                })
+                .collect();
+            let title =
+                format!("Connection test: {failed}/{} failed", reports.len());
+            progress.summary(format!(" {title} "), lines);
+            if failed == 0 {
+                Ok(format!("{title}."))
+            } else {
+                Err(format!("{title}."))
+            }
+        });
+    }
+
     /// Handle toggle between table data and columns view
     async fn handle_toggle_table_view(&mut self) -> Result<()> {
         let state = self.database_explorer.state.clone();
@@ -340,11 +588,12 @@ impl App<'_> {
                     self.set_status(format!("Failed to load table data: {e}"));
                 }
             }
-            DatabaseExplorerState::Connections => todo!(),
-            DatabaseExplorerState::Databases => todo!(),
-            DatabaseExplorerState::Schemas => todo!(),
-            DatabaseExplorerState::Tables(_) => todo!(),
-            DatabaseExplorerState::SqlResults(_) => todo!(),
+            // Only a table has a structure and data to switch between
+            DatabaseExplorerState::Connections
+            | DatabaseExplorerState::Databases
+            | DatabaseExplorerState::Schemas
+            | DatabaseExplorerState::Tables(_)
+            | DatabaseExplorerState::SqlResults(_) => {}
         }
         Ok(())
     }
@@ -355,6 +604,72 @@ impl App<'_> {
 
         match action {
             ModalAction::Save => {
+                if let Some((purpose, index, action)) =
+                    self.modal_manager.take_choice()
+                {
+                    self.handle_choice(purpose, index, action).await?;
+                    self.modal_manager.cleanup_closed_modals();
+                    return Ok(());
+                }
+                if let Some((purpose, value)) = self.modal_manager.take_input()
+                {
+                    match purpose {
+                        InputPurpose::SaveView => self.save_current_view(value),
+                        InputPurpose::SearchPath => {
+                            self.set_session_search_path(&value).await;
+                        }
+                        InputPurpose::DuplicateRows => {
+                            self.find_duplicate_rows(&value).await;
+                        }
+                        InputPurpose::ConnectionColumns => {
+                            self.set_connection_columns(&value);
+                        }
+                        InputPurpose::QuickConnect => {
+                            self.quick_connect(&value).await?;
+                        }
+                        InputPurpose::JsonPath { column } => {
+                            self.extract_json_values(&column, &value).await;
+                        }
+                        InputPurpose::ColumnSearch { column, mode } => {
+                            self.search_column(&column, &value, mode).await;
+                        }
+                        InputPurpose::InsertExportTable => {
+                            self.export_sql_results_as_inserts(value);
+                        }
+                        InputPurpose::JumpToKey => {
+                            self.jump_to_key(&value).await;
+                        }
+                        InputPurpose::SavepointName => {
+                            self.create_savepoint(&value).await;
+                        }
+                        InputPurpose::SourceFile => {
+                            self.source_command(&value);
+                        }
+                        InputPurpose::RowColors => {
+                            self.set_row_color_rules(&value);
+                        }
+                        InputPurpose::GoToRow { key_columns } => {
+                            self.go_to_row(&key_columns, &value).await;
+                        }
+                        InputPurpose::Benchmark => {
+                            self.benchmark_statement(&value);
+                        }
+                        InputPurpose::BlockInsert => {
+                            self.block_insert(&value).await;
+                        }
+                        InputPurpose::LineCommand => {
+                            self.line_command(&value).await;
+                        }
+                        InputPurpose::RowNote { key_columns, key } => {
+                            self.set_row_note(key_columns, key, &value);
+                        }
+                        InputPurpose::GoToTable => {
+                            self.go_to_table(&value).await;
+                        }
+                    }
+                    self.modal_manager.cleanup_closed_modals();
+                    return Ok(());
+                }
                 if let Some(apply) = self.modal_manager.take_cell_value_apply()
                 {
                     self.apply_cell_value_edit(apply).await?;
@@ -408,6 +723,7 @@ impl App<'_> {
                 }
                 self.pending_row_deletes = None;
             }
+            ModalAction::CopyUri => self.copy_connection_modal_uri(),
             ModalAction::None => {}
         }
 
@@ -416,19 +732,7 @@ impl App<'_> {
             self.modal_manager.was_confirmation_modal_confirmed()
             && matches!(key.code, KeyCode::Enter)
         {
-            // Delete from keyring only for Postgres (SQLite has no passwords)
-            if connection.r#type == ConnectionType::Postgres
-                && !connection.should_ask_every_time()
-            {
-                let _ = PasswordService::delete_from_keyring(&connection.name);
-            }
-
-            // Delete connection using ConnectionService
-            if let Err(e) = ConnectionService::delete(&connection.name) {
-                self.set_status(format!("Failed to delete connection: {e}"));
-            } else {
-                self.refresh_connections();
-            }
+            self.delete_connection(&connection);
         }
 
         // Clean up closed modals
@@ -437,6 +741,102 @@ impl App<'_> {
         Ok(())
     }
 
+    /// Act on an option picked in a choice modal
+    async fn handle_choice(
+        &mut self,
+        purpose: ChoicePurpose,
+        index: usize,
+        action: ChoiceAction,
+    ) -> Result<()> {
+        match purpose {
+            ChoicePurpose::KeyringFallback {
+                connection,
+                original_name,
+            } => {
+                if let Some(choice) = KeyringFallbackChoice::from_index(index) {
+                    self.handle_keyring_fallback_choice(
+                        connection,
+                        original_name.as_deref(),
+                        choice,
+                    );
+                }
+            }
+            ChoicePurpose::Disconnect => {
+                if index == 0 {
+                    self.disconnect();
+                }
+            }
+            ChoicePurpose::SaveQuickConnection { connection } => {
+                if index == 0 {
+                    self.save_quick_connection(&connection);
+                }
+            }
+            ChoicePurpose::StoreRecovery => {
+                if let Some(choice) = StoreRecoveryChoice::from_index(index) {
+                    self.handle_store_recovery_choice(choice);
+                }
+            }
+            ChoicePurpose::OpenView { mut views } => {
+                if index < views.len() {
+                    let view = views.swap_remove(index);
+                    self.handle_view_choice(view, action).await?;
+                }
+            }
+            ChoicePurpose::OpenRecentTable { tables } => {
+                if let Some(recent) = tables.get(index) {
+                    self.open_recent_table(recent).await?;
+                }
+            }
+            ChoicePurpose::CopyConnectionUri { connection } => {
+                self.copy_connection_uri(&connection, index == 1);
+            }
+            ChoicePurpose::OpenHistoryResult { ids } => {
+                if let Some(&id) = ids.get(index) {
+                    self.handle_history_result_choice(id, action);
+                }
+            }
+            ChoicePurpose::ExportSchemaDdl { schema } => {
+                self.export_schema_ddl(schema, index == 1);
+            }
+            ChoicePurpose::ColumnSearchMode { column } => {
+                if let Some(mode) = TextSearchMode::from_index(index) {
+                    self.open_column_search_prompt(column, mode);
+                }
+            }
+            ChoicePurpose::TopQueriesOrder => {
+                if let Some(order) = TopQueryOrder::from_index(index) {
+                    self.open_top_queries(order).await;
+                }
+            }
+            ChoicePurpose::CopyIntoSqlExecutor { statements } => {
+                if let Some(sql) = statements.get(index) {
+                    self.copy_into_sql_executor(sql);
+                }
+            }
+            ChoicePurpose::PlanBaseline => {
+                if let Some(choice) = PlanBaselineChoice::from_index(index) {
+                    self.handle_plan_baseline_choice(choice).await;
+                }
+            }
+            ChoicePurpose::RollbackToSavepoint { savepoints } => {
+                if let Some(name) = savepoints.get(index) {
+                    self.rollback_to_savepoint(name).await;
+                }
+            }
+            ChoicePurpose::GroupSummary {
+                column,
+                sum_columns,
+            } => {
+                let sum_column = index
+                    .checked_sub(1)
+                    .and_then(|idx| sum_columns.get(idx))
+                    .copied();
+                self.show_group_summary(column, sum_column);
+            }
+        }
+        Ok(())
+    }
+
     /// Handle password modal save action
     async fn handle_password_modal_save(&mut self) -> Result<bool> {
         // Extract data from modal before attempting connection
@@ -476,6 +876,15 @@ impl App<'_> {
             {
                 password_modal.close();
             }
+        } else if let Some(retry) = self.connect_retry.as_mut() {
+            // Failed for a reason that may pass; keep the password for when
+            // a retry connects
+            retry.remember_password = true;
+            if let Some(password_modal) =
+                self.modal_manager.get_password_modal_mut()
+            {
+                password_modal.close();
+            }
         } else {
             // Connection failed, keep modal open so user can retry
             // Remove any password from session that might have been stored
@@ -513,25 +922,51 @@ impl App<'_> {
             && let Err(e) =
                 PasswordService::save_to_keyring(&connection.name, password)
         {
-            modal.test_result =
-                TestResult::Failed(format!("Failed to save password: {e}"));
+            if e.is_storage_unavailable() {
+                // Let the user decide how to continue instead of failing
+                self.modal_manager.open_choice_modal(
+                    ChoiceModal::keyring_fallback(
+                        connection,
+                        original_name,
+                        &e.to_string(),
+                    ),
+                );
+            } else {
+                modal.test_result =
+                    TestResult::Failed(format!("Failed to save password: {e}"));
+            }
             return;
         }
 
-        let save_result = original_name.as_ref().map_or_else(
-            || ConnectionService::create(&connection),
-            |orig_name| ConnectionService::update(orig_name, &connection),
+        self.persist_connection(&connection, original_name.as_deref());
+    }
+
+    /// Write the connection from the modal and close it on success.
+    fn persist_connection(
+        &mut self,
+        connection: &Connection,
+        original_name: Option<&str>,
+    ) {
+        let save_result = original_name.map_or_else(
+            || ConnectionService::create(connection),
+            |orig_name| ConnectionService::update(orig_name, connection),
         );
 
+        let Some(modal) = self.modal_manager.get_connection_modal_mut() else {
+            return;
+        };
+
         match save_result {
             Ok(()) => {
                 // If switching to "ask every time" on an existing connection,
                 // delete the old keyring credential after the save succeeds.
-                if let Some(ref orig_name) = original_name
+                if let Some(orig_name) = original_name
                     && connection.should_ask_every_time()
                 {
                     let _ = PasswordService::delete_from_keyring(orig_name);
                 }
+                self.password_service
+                    .carry_over_session_password(original_name, connection);
                 modal.close();
                 self.refresh_connections();
             }
@@ -541,6 +976,33 @@ impl App<'_> {
         }
     }
 
+    /// Continue a save after the keyring could not store the password.
+    fn handle_keyring_fallback_choice(
+        &mut self,
+        mut connection: Connection,
+        original_name: Option<&str>,
+        choice: KeyringFallbackChoice,
+    ) {
+        match choice {
+            KeyringFallbackChoice::Retry => {
+                self.handle_connection_modal_save();
+            }
+            KeyringFallbackChoice::AskEveryTime
+            | KeyringFallbackChoice::SessionOnly => {
+                let password = connection.password.take();
+                connection.password_storage =
+                    Some(PasswordStorageType::DontSave.to_string());
+                self.persist_connection(&connection, original_name);
+                if choice == KeyringFallbackChoice::SessionOnly
+                    && let Some(password) = password
+                {
+                    self.password_service
+                        .store_session_password(&connection, password);
+                }
+            }
+        }
+    }
+
     /// Handle connection modal test action
     async fn handle_connection_modal_test(&mut self) {
         let Some(modal) = self.modal_manager.get_connection_modal_mut() else {
@@ -0,0 +1,690 @@
+//! Writing result sets to files.
+
+use std::{
+    borrow::Cow,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    db::{connection::Connection, dialect::Dialect},
+    parquet_export::write_parquet,
+};
+
+/// Rows written between progress reports.
+const PROGRESS_INTERVAL: usize = 500;
+
+/// Rows added by each `INSERT` statement of an SQL script.
+const INSERT_BATCH_ROWS: usize = 100;
+
+/// File format of an export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Tsv,
+    /// An array with one object per row, keyed by column name.
+    Json,
+    /// `INSERT` statements adding the rows to a table named at export time.
+    Sql,
+    /// Typed columns for pandas, `DuckDB` and other analytics tools.
+    Parquet,
+}
+
+impl ExportFormat {
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Tsv => "tsv",
+            Self::Json => "json",
+            Self::Sql => "sql",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "csv" => Self::Csv,
+            "tsv" => Self::Tsv,
+            "json" => Self::Json,
+            "sql" | "insert" => Self::Sql,
+            "parquet" => Self::Parquet,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// When CSV fields are put in double quotes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Quoting {
+    /// Fields holding the delimiter, a quote or a line break
+    #[default]
+    Minimal,
+    /// Every field but NULLs
+    All,
+    /// Every field but NULLs and numbers
+    NonNumeric,
+    /// No field, even one holding the delimiter
+    Never,
+}
+
+impl FromStr for Quoting {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "minimal" => Self::Minimal,
+            "all" => Self::All,
+            "nonnumeric" | "non-numeric" => Self::NonNumeric,
+            "never" | "none" => Self::Never,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// Character encoding of an export file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    /// UTF-8 starting with a byte order mark, which Excel needs to read
+    /// the file as UTF-8
+    Utf8Bom,
+    /// ISO-8859-1; characters it lacks are written as `?`
+    Latin1,
+}
+
+impl TextEncoding {
+    /// Start a file: the byte order mark, if the encoding has one.
+    fn write_start(self, out: &mut impl Write) -> io::Result<()> {
+        match self {
+            Self::Utf8Bom => out.write_all("\u{feff}".as_bytes()),
+            Self::Utf8 | Self::Latin1 => Ok(()),
+        }
+    }
+
+    /// Write `line` and a line break.
+    fn write_line(self, out: &mut impl Write, line: &str) -> io::Result<()> {
+        match self {
+            Self::Utf8 | Self::Utf8Bom => out.write_all(line.as_bytes())?,
+            Self::Latin1 => {
+                let bytes = line
+                    .chars()
+                    .map(|c| u8::try_from(c).unwrap_or(b'?'))
+                    .collect::<Vec<_>>();
+                out.write_all(&bytes)?;
+            }
+        }
+        out.write_all(b"\n")
+    }
+}
+
+impl FromStr for TextEncoding {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "utf-8" | "utf8" => Self::Utf8,
+            "utf-8-bom" | "utf8-bom" => Self::Utf8Bom,
+            "latin1" | "latin-1" | "iso-8859-1" => Self::Latin1,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// How values are laid out in CSV and TSV files. JSON only follows the
+/// encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextOptions {
+    /// Field separator of CSV; TSV always uses tabs
+    pub delimiter: char,
+    pub quoting: Quoting,
+    /// Whether the first line names the columns
+    pub header: bool,
+    /// Written for NULL values
+    pub null: String,
+    pub encoding: TextEncoding,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quoting: Quoting::default(),
+            header: true,
+            null: NULL_TEXT.to_string(),
+            encoding: TextEncoding::default(),
+        }
+    }
+}
+
+/// How the grid shows NULL.
+const NULL_TEXT: &str = "NULL";
+
+/// Read an `export_delimiter` value: `comma`, `tab`, `pipe`, `semicolon`
+/// or a single character other than a quote or line break.
+fn parse_delimiter(value: &str) -> Option<char> {
+    let delimiter = match value.to_lowercase().as_str() {
+        "comma" => ',',
+        "tab" => '\t',
+        "pipe" => '|',
+        "semicolon" => ';',
+        _ => {
+            let mut chars = value.chars();
+            let c = chars.next()?;
+            if chars.next().is_some() {
+                return None;
+            }
+            c
+        }
+    };
+    (!matches!(delimiter, '"' | '\n' | '\r')).then_some(delimiter)
+}
+
+/// Where and how a connection's exports are written, from the `export_*`
+/// keys of its metadata.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportSettings {
+    /// Path pattern; `{table}`, `{date}` and `{time}` are filled in and a
+    /// leading `~/` is the home directory. A pattern ending in `/` is a
+    /// directory for the default file name.
+    pub path_pattern: Option<String>,
+    pub format: ExportFormat,
+    pub text: TextOptions,
+    /// Table an SQL script inserts into, written as given; asked for
+    /// before each export
+    pub insert_table: Option<String>,
+}
+
+impl ExportSettings {
+    #[must_use]
+    pub fn for_connection(connection: &Connection) -> Self {
+        let setting = |key: &str| {
+            connection
+                .metadata
+                .get(key)
+                .and_then(serde_json::Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+        // Untrimmed, as a tab delimiter or an empty NULL is all whitespace
+        let raw_setting = |key: &str| {
+            connection.metadata.get(key).and_then(serde_json::Value::as_str)
+        };
+        let defaults = TextOptions::default();
+        let text = TextOptions {
+            delimiter: raw_setting("export_delimiter")
+                .and_then(parse_delimiter)
+                .unwrap_or(defaults.delimiter),
+            quoting: setting("export_quoting")
+                .and_then(|quoting| quoting.parse().ok())
+                .unwrap_or_default(),
+            header: connection
+                .metadata
+                .get("export_header")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(defaults.header),
+            null: raw_setting("export_null")
+                .map_or(defaults.null, str::to_string),
+            encoding: setting("export_encoding")
+                .and_then(|encoding| encoding.parse().ok())
+                .unwrap_or_default(),
+        };
+        Self {
+            path_pattern: setting("export_path").map(str::to_string),
+            format: setting("export_format")
+                .and_then(|format| format.parse().ok())
+                .unwrap_or_default(),
+            text,
+            insert_table: None,
+        }
+    }
+
+    /// Write `rows` in the connection's format and layout, reporting
+    /// progress like [`write_csv`]. SQL scripts quote names and values
+    /// for `dialect`; Parquet types columns from `column_types`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `out` fails.
+    pub fn write(
+        &self,
+        out: &mut (impl Write + Send),
+        dialect: &dyn Dialect,
+        columns: &[String],
+        column_types: &[String],
+        rows: &[Vec<String>],
+        on_progress: impl FnMut(usize),
+    ) -> io::Result<()> {
+        let text = &self.text;
+        match self.format {
+            ExportFormat::Csv => {
+                write_csv(out, columns, rows, text, on_progress)
+            }
+            ExportFormat::Tsv => {
+                write_tsv(out, columns, rows, text, on_progress)
+            }
+            ExportFormat::Json => {
+                write_json(out, columns, rows, text.encoding, on_progress)
+            }
+            ExportFormat::Sql => write_inserts(
+                out,
+                dialect,
+                self.insert_table.as_deref().unwrap_or("query"),
+                columns,
+                rows,
+                text.encoding,
+                on_progress,
+            ),
+            ExportFormat::Parquet => {
+                write_parquet(out, columns, column_types, rows, on_progress)
+            }
+        }
+    }
+
+    /// File to export `table` (or `query`) to now.
+    #[must_use]
+    pub fn path(&self, table: &str) -> PathBuf {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let now = i64::try_from(now)
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .unwrap_or_default();
+        self.path_at(table, now)
+    }
+
+    /// File to write a `.sql` script for `name` (e.g. a schema) to now.
+    #[must_use]
+    pub fn script_path(&self, name: &str) -> PathBuf {
+        let mut path = self.path(name);
+        path.set_extension("sql");
+        path
+    }
+
+    // `{table}` etc. are placeholders of the pattern, not format arguments
+    #[allow(clippy::literal_string_with_formatting_args)]
+    fn path_at(&self, table: &str, now: DateTime<Utc>) -> PathBuf {
+        let ext = self.format.extension();
+        let default_name =
+            format!("{table}-{}.{ext}", now.format("%Y%m%d-%H%M%S"));
+        let Some(pattern) = &self.path_pattern else {
+            return PathBuf::from(default_name);
+        };
+        let mut path = pattern
+            .replace("{table}", table)
+            .replace("{date}", &now.format("%Y-%m-%d").to_string())
+            .replace("{time}", &now.format("%H%M%S").to_string());
+        if path.ends_with('/') {
+            path.push_str(&default_name);
+        }
+        let mut path = match path.strip_prefix("~/") {
+            Some(rest) => directories::BaseDirs::new().map_or_else(
+                || PathBuf::from(&path),
+                |dirs| dirs.home_dir().join(rest),
+            ),
+            None => PathBuf::from(path),
+        };
+        if path.extension().is_none() {
+            path.set_extension(ext);
+        }
+        path
+    }
+}
+
+/// Create `path` for writing, with any missing parent directories.
+///
+/// # Errors
+///
+/// Returns an error if a directory or the file can't be created.
+pub fn create_export_file(path: &Path) -> io::Result<BufWriter<File>> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    File::create(path).map(BufWriter::new)
+}
+
+/// Write `rows` as RFC 4180 CSV, with a header line of `columns` unless
+/// `options` leave it out, calling `on_progress` with the rows written so
+/// far every few hundred rows.
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn write_csv(
+    out: &mut impl Write,
+    columns: &[String],
+    rows: &[Vec<String>],
+    options: &TextOptions,
+    on_progress: impl FnMut(usize),
+) -> io::Result<()> {
+    let delimiter = options.delimiter.to_string();
+    let line = |values: &[String], is_header: bool| {
+        values
+            .iter()
+            .map(|value| {
+                if !is_header && value == NULL_TEXT {
+                    let null = &options.null;
+                    csv_field(null, options.delimiter, Quoting::Minimal)
+                } else {
+                    csv_field(value, options.delimiter, options.quoting)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(&delimiter)
+    };
+    write_lines(out, columns, rows, options, line, on_progress)
+}
+
+/// Write `rows` as tab-separated values, escaping tabs, line breaks and
+/// backslashes in fields.
+fn write_tsv(
+    out: &mut impl Write,
+    columns: &[String],
+    rows: &[Vec<String>],
+    options: &TextOptions,
+    on_progress: impl FnMut(usize),
+) -> io::Result<()> {
+    let line = |values: &[String], is_header: bool| {
+        values
+            .iter()
+            .map(|value| {
+                if !is_header && value == NULL_TEXT {
+                    return options.null.clone();
+                }
+                value
+                    .replace('\\', "\\\\")
+                    .replace('\t', "\\t")
+                    .replace('\n', "\\n")
+                    .replace('\r', "\\r")
+            })
+            .collect::<Vec<_>>()
+            .join("\t")
+    };
+    write_lines(out, columns, rows, options, line, on_progress)
+}
+
+/// Write the header (when wanted) and `rows`, one `line` each.
+fn write_lines(
+    out: &mut impl Write,
+    columns: &[String],
+    rows: &[Vec<String>],
+    options: &TextOptions,
+    line: impl Fn(&[String], bool) -> String,
+    mut on_progress: impl FnMut(usize),
+) -> io::Result<()> {
+    let encoding = options.encoding;
+    encoding.write_start(out)?;
+    if options.header {
+        encoding.write_line(out, &line(columns, true))?;
+    }
+    for (idx, row) in rows.iter().enumerate() {
+        encoding.write_line(out, &line(row, false))?;
+        if (idx + 1) % PROGRESS_INTERVAL == 0 {
+            on_progress(idx + 1);
+        }
+    }
+    on_progress(rows.len());
+    out.flush()
+}
+
+/// Write `rows` as a JSON array of objects, one per line. Values stay the
+/// strings shown in the grid, with `NULL` written as `null`.
+fn write_json(
+    out: &mut impl Write,
+    columns: &[String],
+    rows: &[Vec<String>],
+    encoding: TextEncoding,
+    mut on_progress: impl FnMut(usize),
+) -> io::Result<()> {
+    encoding.write_start(out)?;
+    encoding.write_line(out, "[")?;
+    for (idx, row) in rows.iter().enumerate() {
+        let object = columns
+            .iter()
+            .zip(row)
+            .map(|(column, value)| {
+                let value = if value == NULL_TEXT {
+                    serde_json::Value::Null
+                } else {
+                    serde_json::Value::String(value.clone())
+                };
+                (column.clone(), value)
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let separator = if idx + 1 < rows.len() { "," } else { "" };
+        let object = serde_json::Value::Object(object);
+        encoding.write_line(out, &format!("  {object}{separator}"))?;
+        if (idx + 1) % PROGRESS_INTERVAL == 0 {
+            on_progress(idx + 1);
+        }
+    }
+    encoding.write_line(out, "]")?;
+    on_progress(rows.len());
+    out.flush()
+}
+
+/// Write `rows` as `INSERT` statements adding them to `table`, a hundred
+/// rows each. Values are string literals, which both backends convert to
+/// the column's type, or `NULL`.
+fn write_inserts(
+    out: &mut impl Write,
+    dialect: &dyn Dialect,
+    table: &str,
+    columns: &[String],
+    rows: &[Vec<String>],
+    encoding: TextEncoding,
+    mut on_progress: impl FnMut(usize),
+) -> io::Result<()> {
+    let columns = columns
+        .iter()
+        .map(|column| dialect.quote_ident(column))
+        .collect::<Vec<_>>()
+        .join(", ");
+    encoding.write_start(out)?;
+    let mut done = 0;
+    for batch in rows.chunks(INSERT_BATCH_ROWS) {
+        encoding.write_line(out, &format!("INSERT INTO {table} ({columns})"))?;
+        for (idx, row) in batch.iter().enumerate() {
+            let values = row
+                .iter()
+                .map(|value| {
+                    if value == NULL_TEXT {
+                        NULL_TEXT.to_string()
+                    } else {
+                        dialect.quote_literal(value)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let keyword = if idx == 0 { "VALUES" } else { "      " };
+            let end = if idx + 1 < batch.len() { "," } else { ";" };
+            encoding.write_line(out, &format!("{keyword} ({values}){end}"))?;
+        }
+        done += batch.len();
+        if done % PROGRESS_INTERVAL == 0 {
+            on_progress(done);
+        }
+    }
+    on_progress(rows.len());
+    out.flush()
+}
+
+/// Quote a field as `quoting` asks; `Minimal` quotes it when it holds the
+/// delimiter, a quote or a line break.
+fn csv_field(value: &str, delimiter: char, quoting: Quoting) -> Cow<'_, str> {
+    let needs_quotes =
+        value.contains(['"', '\n', '\r']) || value.contains(delimiter);
+    let quote = match quoting {
+        Quoting::Minimal => needs_quotes,
+        Quoting::All => true,
+        Quoting::NonNumeric => needs_quotes || value.parse::<f64>().is_err(),
+        Quoting::Never => false,
+    };
+    if quote {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::dialect::PostgresDialect;
+
+    #[test]
+    fn quotes_fields_that_need_it() {
+        let mut out = Vec::new();
+        let columns = ["id".to_string(), "note".to_string()];
+        let rows = vec![
+            vec!["1".to_string(), "plain".to_string()],
+            vec!["2".to_string(), "say \"hi\", twice\n".to_string()],
+        ];
+        let mut reported = Vec::new();
+        write_csv(&mut out, &columns, &rows, &TextOptions::default(), |done| {
+            reported.push(done);
+        })
+        .unwrap();
+        assert_eq!(reported, [2]);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "id,note\n1,plain\n2,\"say \"\"hi\"\", twice\n\"\n"
+        );
+    }
+
+    #[test]
+    fn writes_tsv_and_json() {
+        let columns = ["id".to_string(), "note".to_string()];
+        let rows = vec![
+            vec!["1".to_string(), "a\tb".to_string()],
+            vec!["2".to_string(), "NULL".to_string()],
+        ];
+        let write = |format: ExportFormat| {
+            let mut out = Vec::new();
+            let settings = ExportSettings {
+                format,
+                ..ExportSettings::default()
+            };
+            settings
+                .write(&mut out, &PostgresDialect, &columns, &[], &rows, |_| {})
+                .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(write(ExportFormat::Tsv), "id\tnote\n1\ta\\tb\n2\tNULL\n");
+        assert_eq!(
+            write(ExportFormat::Json),
+            "[\n  {\"id\":\"1\",\"note\":\"a\\tb\"},\n  {\"id\":\"2\",\"note\":null}\n]\n"
+        );
+    }
+
+    #[test]
+    fn writes_batched_inserts() {
+        let columns = ["id".to_string(), "note".to_string()];
+        let rows = (1..=INSERT_BATCH_ROWS + 1)
+            .map(|id| vec![id.to_string(), "it's".to_string()])
+            .chain([vec!["0".to_string(), "NULL".to_string()]])
+            .collect::<Vec<_>>();
+        let settings = ExportSettings {
+            format: ExportFormat::Sql,
+            insert_table: Some("staging.notes".to_string()),
+            ..ExportSettings::default()
+        };
+        let mut out = Vec::new();
+        settings
+            .write(&mut out, &PostgresDialect, &columns, &[], &rows, |_| {})
+            .unwrap();
+        let script = String::from_utf8(out).unwrap();
+        let mut lines = script.lines();
+        assert_eq!(
+            lines.next(),
+            Some("INSERT INTO staging.notes (\"id\", \"note\")")
+        );
+        assert_eq!(lines.next(), Some("VALUES ('1', 'it''s'),"));
+        assert_eq!(lines.next(), Some("       ('2', 'it''s'),"));
+        assert!(script.ends_with(
+            "       ('100', 'it''s');\n\
+             INSERT INTO staging.notes (\"id\", \"note\")\n\
+             VALUES ('101', 'it''s'),\n       ('0', NULL);\n"
+        ));
+    }
+
+    #[test]
+    fn follows_the_connection_layout() {
+        let connection = Connection {
+            metadata: serde_json::json!({
+                "export_delimiter": "pipe",
+                "export_quoting": "nonnumeric",
+                "export_header": false,
+                "export_null": "",
+                "export_encoding": "latin1",
+            }),
+            ..Connection::default()
+        };
+        let settings = ExportSettings::for_connection(&connection);
+        let columns = ["id".to_string(), "name".to_string()];
+        let rows = vec![
+            vec!["1".to_string(), "Zoë|Ann".to_string()],
+            vec!["2".to_string(), "NULL".to_string()],
+            vec!["3".to_string(), "€".to_string()],
+        ];
+        let mut out = Vec::new();
+        settings
+            .write(&mut out, &PostgresDialect, &columns, &[], &rows, |_| {})
+            .unwrap();
+        assert_eq!(out, b"1|\"Zo\xeb|Ann\"\n2|\n3|\"?\"\n".to_vec());
+
+        let connection = Connection {
+            metadata: serde_json::json!({
+                "export_delimiter": "\t",
+                "export_quoting": "all",
+                "export_encoding": "utf-8-bom",
+            }),
+            ..Connection::default()
+        };
+        let settings = ExportSettings::for_connection(&connection);
+        let rows = vec![vec!["2".to_string(), "NULL".to_string()]];
+        let mut out = Vec::new();
+        settings
+            .write(&mut out, &PostgresDialect, &columns, &[], &rows, |_| {})
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\u{feff}\"id\"\t\"name\"\n\"2\"\tNULL\n"
+        );
+    }
+
+    #[test]
+    fn fills_in_the_path_pattern() {
+        let now = DateTime::from_timestamp(1_760_000_000, 0).unwrap();
+        let mut settings = ExportSettings::default();
+        assert_eq!(
+            settings.path_at("query", now),
+            PathBuf::from("query-20251009-085320.csv")
+        );
+
+        settings.format = ExportFormat::Json;
+        settings.path_pattern = Some("exports/{date}/{table}".to_string());
+        assert_eq!(
+            settings.path_at("orders", now),
+            PathBuf::from("exports/2025-10-09/orders.json")
+        );
+        settings.path_pattern = Some("out/".to_string());
+        assert_eq!(
+            settings.path_at("orders", now),
+            PathBuf::from("out/orders-20251009-085320.json")
+        );
+    }
+}
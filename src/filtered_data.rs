@@ -10,6 +10,8 @@ pub struct FilteredData<T: TableData + Clone> {
     pub original: Vec<T>,
     /// Table state with potentially filtered items
     pub table: TableDataState<T>,
+    /// Query of the active filter, if any
+    pub query: Option<String>,
 }
 
 impl<T: TableData + Clone> FilteredData<T> {
@@ -18,27 +20,37 @@ impl<T: TableData + Clone> FilteredData<T> {
         Self {
             original: data.clone(),
             table: TableDataState::new(data),
+            query: None,
         }
     }
 
-    /// Apply a filter to the data
+    /// Wrap an already prepared table state; its items become the original data
+    pub fn from_table(table: TableDataState<T>) -> Self {
+        Self {
+            original: table.model.items.clone(),
+            table,
+            query: None,
+        }
+    }
+
+    /// Apply a filter to the original data, keeping the selected row when it
+    /// matches
     pub fn apply_filter(&mut self, query: &str) {
+        let anchor = self.table.selection_anchor();
+        self.table.model.items.clone_from(&self.original);
         self.table.model.items = self.table.filter(query);
+        self.query = (!query.is_empty()).then(|| query.to_string());
         self.table.multi_row_selection.clear();
-        TableNavigationHandler::wrap_rows(
-            &mut self.table.view.state,
-            &self.table.model.items,
-        );
+        self.table.restore_selection(anchor.as_ref());
     }
 
     /// Clear the filter and restore original data
     pub fn clear_filter(&mut self) {
+        let anchor = self.table.selection_anchor();
         self.table.model.items.clone_from(&self.original);
+        self.query = None;
         self.table.multi_row_selection.clear();
-        TableNavigationHandler::wrap_rows(
-            &mut self.table.view.state,
-            &self.table.model.items,
-        );
+        self.table.restore_selection(anchor.as_ref());
     }
 
     /// Navigate the table using a key event
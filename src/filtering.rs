@@ -1,7 +1,49 @@
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent};
+
 use super::filtered_data::FilteredData;
-use crate::{app::App, app_state::DatabaseExplorerState};
+use crate::{
+    app::{App, FILTER_DEBOUNCE},
+    app_state::DatabaseExplorerState,
+};
 
 impl App<'_> {
+    /// Handle a key while the search bar is open. Every key stays with the
+    /// filter, so typing e.g. `d` can't reach the hotkeys: Esc drops the
+    /// filter, Enter keeps it and closes the bar, Up/Down move through the
+    /// matches and the rest edit the query, which is applied as you type.
+    pub(crate) fn handle_search_filter_key(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Esc {
+            self.filter_typed_at = None;
+            self.clear_filter();
+            self.search_filter = None;
+        } else if key.code == KeyCode::Enter {
+            self.filter_typed_at = None;
+            self.apply_filter();
+            self.search_filter = None;
+        } else if matches!(key.code, KeyCode::Up | KeyCode::Down) {
+            self.apply_typed_filter(true);
+            self.handle_database_table_navigation(key.code);
+        } else if let Some(textarea) = self.search_filter.as_mut()
+            && textarea.input(key)
+        {
+            self.filter_typed_at = Some(Instant::now());
+        }
+    }
+
+    /// Apply the search bar text once typing has paused for
+    /// [`FILTER_DEBOUNCE`], or straight away with `now`.
+    pub(crate) fn apply_typed_filter(&mut self, now: bool) {
+        let Some(typed_at) = self.filter_typed_at else {
+            return;
+        };
+        if now || typed_at.elapsed() >= FILTER_DEBOUNCE {
+            self.filter_typed_at = None;
+            self.apply_filter();
+        }
+    }
+
     /// Apply the current search filter to the active table
     pub fn apply_filter(&mut self) {
         let Some(search_filter) = &self.search_filter.clone() else {
@@ -40,7 +82,9 @@ impl App<'_> {
                 .table_data
                 .as_ref()
                 .is_some_and(FilteredData::is_filtered),
-            DatabaseExplorerState::SqlResults(_) => false,
+            DatabaseExplorerState::SqlResults(_) => {
+                explorer.sql_executor.filter().is_some()
+            }
         }
     }
 
@@ -76,7 +120,9 @@ impl App<'_> {
                     table_data.clear_filter();
                 }
             }
-            DatabaseExplorerState::SqlResults(_) => {}
+            DatabaseExplorerState::SqlResults(_) => {
+                explorer.sql_executor.set_filter("");
+            }
         }
     }
 
@@ -112,7 +158,9 @@ impl App<'_> {
                     table_data.apply_filter(query);
                 }
             }
-            DatabaseExplorerState::SqlResults(_) => {}
+            DatabaseExplorerState::SqlResults(_) => {
+                explorer.sql_executor.set_filter(query);
+            }
         }
     }
 }
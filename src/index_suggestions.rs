@@ -0,0 +1,43 @@
+//! Candidate indexes for the current statement, read off its plan.
+
+use crate::{app::App, ui::widgets::modal::ChoiceModal};
+
+impl App<'_> {
+    /// Suggest indexes for the filtered sequential scans in the plan of the
+    /// current statement
+    pub(crate) async fn open_index_suggestions(&mut self) {
+        let Some(sql) = self
+            .database_explorer
+            .sql_executor
+            .selected_statement()
+            .map(str::to_string)
+        else {
+            self.set_status("Run a statement first.");
+            return;
+        };
+        let Some(database) = self.database_explorer.database.as_ref() else {
+            return;
+        };
+        let dialect = database.dialect();
+        let suggestions = database
+            .suggest_indexes(&sql)
+            .await
+            .map_err(|e| e.to_string());
+        match suggestions {
+            Ok(Some(suggestions)) if suggestions.is_empty() => {
+                self.set_status(
+                    "No filtered sequential scans in the plan to index.",
+                );
+            }
+            Ok(Some(suggestions)) => {
+                self.modal_manager.open_choice_modal(
+                    ChoiceModal::index_suggestions(&suggestions, dialect),
+                );
+            }
+            Ok(None) => {
+                self.set_status("This database doesn't report query plans.");
+            }
+            Err(e) => self.set_status(format!("EXPLAIN failed: {e}")),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! A check at startup that the platform keyring can be reached, so a
+//! locked or missing store is explained up front with what to do about it,
+//! instead of surfacing as an error in the middle of saving a connection.
+
+use ratatui::{
+    style::{Modifier, Style},
+    text::Line,
+};
+
+use crate::{
+    app::App, auth, db::connection::ConnectionType,
+    ui::widgets::modal::InfoModal,
+};
+
+/// How to make the keyring reachable on this platform.
+const fn remediation() -> &'static [&'static str] {
+    if cfg!(target_os = "macos") {
+        &[
+            "- Unlock the login keychain in Keychain Access, or sign out and",
+            "  in again if it keeps a different password than your account.",
+        ]
+    } else if cfg!(target_os = "windows") {
+        &[
+            "- Check that the Credential Manager service is running, and that",
+            "  d7s runs as the user the passwords were saved for.",
+        ]
+    } else {
+        &[
+            "- Start a Secret Service provider, such as GNOME Keyring or",
+            "  KeePassXC with Secret Service integration, and unlock its",
+            "  default collection.",
+            "- Over SSH or in a container without a desktop session, start a",
+            "  D-Bus session first, e.g. `dbus-run-session -- d7s`.",
+        ]
+    }
+}
+
+/// The warning shown when the keyring can't be reached with `error`.
+fn warning_lines(error: &str) -> Vec<Line<'static>> {
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = vec![
+        Line::styled("Saved passwords can't be read or stored.", bold),
+        Line::raw(error.to_string()),
+        Line::default(),
+    ];
+    lines.extend(remediation().iter().map(|line| Line::raw(*line)));
+    lines.extend([
+        Line::default(),
+        Line::raw("Until then, tick \"Ask every time\" when saving a"),
+        Line::raw("connection to be asked for its password on connecting."),
+    ]);
+    lines
+}
+
+impl App<'_> {
+    /// Warn once, at startup, when the keyring a saved or new connection
+    /// would keep its password in can't be reached
+    pub(crate) fn check_keyring(&mut self) {
+        let connections = &self.database_explorer.connections.table.model.items;
+        let needs_keyring = connections.is_empty()
+            || connections.iter().any(|connection| {
+                connection.r#type == ConnectionType::Postgres
+                    && connection.uses_keyring()
+            });
+        if !needs_keyring {
+            return;
+        }
+        if let Err(e) = auth::check_storage() {
+            self.set_status(format!("Keyring unavailable: {e}"));
+            self.modal_manager.open_info_modal(InfoModal::new(
+                " Keyring unavailable ",
+                warning_lines(&e.to_string()),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explains_what_to_do() {
+        let lines = warning_lines("no storage access")
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines.first().map(String::as_str),
+            Some("Saved passwords can't be read or stored.")
+        );
+        assert!(lines.iter().any(|line| line == "no storage access"));
+        assert!(lines.iter().any(|line| line.starts_with("- ")));
+        assert!(lines.iter().all(|line| line.chars().count() <= 70));
+    }
+}
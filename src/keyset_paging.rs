@@ -0,0 +1,230 @@
+//! Keyset paging of table data: pages read by primary key from the rows
+//! at the ends of the page shown, and a jump to any key value.
+
+use std::time::Instant;
+
+use crate::{
+    app::App,
+    app_state::DatabaseExplorerState,
+    db::{TableDataPage, keyset::KeySeek},
+    metrics,
+    ui::widgets::{
+        modal::{InputModal, InputPurpose},
+        table::TableDataState,
+    },
+    virtual_table::{KeysetCursor, VIRTUAL_TABLE_PAGE_SIZE, VirtualTableMeta},
+};
+
+/// The key value typed for a key of `key_len` columns: the whole input
+/// for one column, else one comma-separated value per column.
+pub(crate) fn key_values(input: &str, key_len: usize) -> Option<Vec<String>> {
+    let values: Vec<String> = if key_len == 1 {
+        vec![input.trim().to_string()]
+    } else {
+        input.split(',').map(|value| value.trim().to_string()).collect()
+    };
+    (values.len() == key_len).then_some(values)
+}
+
+impl App<'_> {
+    /// Switch the table being browsed between paging by offset and by key.
+    pub(crate) async fn toggle_keyset_paging(&mut self) {
+        if self.database_explorer.table_keyset.take().is_none() {
+            self.enable_keyset_paging().await;
+            return;
+        }
+        match self.refetch_table_page(0).await {
+            Ok(()) => self.set_status("Paging by offset."),
+            Err(e) => self.set_status(format!("Failed to load rows: {e}")),
+        }
+    }
+
+    /// Page the table being browsed by its primary key from the first row;
+    /// `false` when it has none or the first page can't be read.
+    async fn enable_keyset_paging(&mut self) -> bool {
+        let explorer = &self.database_explorer;
+        let DatabaseExplorerState::TableData(schema, table) = &explorer.state
+        else {
+            return false;
+        };
+        let Some(database) = explorer.database.clone() else {
+            return false;
+        };
+        let (schema, table) = (schema.clone(), table.clone());
+        let key_columns =
+            match database.get_primary_key_columns(&schema, &table).await {
+                Ok(columns) if columns.is_empty() => {
+                    self.set_status(format!(
+                        "{table} has no primary key to page by."
+                    ));
+                    return false;
+                }
+                Ok(columns) => columns,
+                Err(e) => {
+                    self.set_status(format!(
+                        "Failed to read the primary key: {e}"
+                    ));
+                    return false;
+                }
+            };
+        let key = key_columns.join(", ");
+        let explorer = &mut self.database_explorer;
+        explorer.table_keyset = Some(KeysetCursor::new(key_columns));
+        let previous_order = explorer.table_order.take();
+        match self.fetch_keyset_page(KeySeek::Start).await {
+            Ok(_) => {
+                self.set_status(format!(
+                    "Paging by {key}; [ pages by offset again."
+                ));
+                true
+            }
+            Err(e) => {
+                let explorer = &mut self.database_explorer;
+                explorer.table_keyset = None;
+                explorer.table_order = previous_order;
+                self.set_status(format!("Failed to page by key: {e}"));
+                false
+            }
+        }
+    }
+
+    /// Ask for a primary key value to show rows from, paging by key first
+    /// if needed.
+    pub(crate) async fn open_key_jump_prompt(&mut self) {
+        if self.database_explorer.table_keyset.is_none()
+            && !self.enable_keyset_paging().await
+        {
+            return;
+        }
+        let Some(cursor) = self.database_explorer.table_keyset.as_ref() else {
+            return;
+        };
+        let prompt = match cursor.key_columns.as_slice() {
+            [column] => format!("Show rows from {column}:"),
+            columns => format!(
+                "Show rows from ({}), values separated by commas:",
+                columns.join(", ")
+            ),
+        };
+        self.modal_manager.open_input_modal(InputModal::new(
+            " Jump to key ",
+            prompt,
+            InputPurpose::JumpToKey,
+        ));
+    }
+
+    /// Show the rows from the key typed into the jump prompt on.
+    pub(crate) async fn jump_to_key(&mut self, input: &str) {
+        let Some(cursor) = self.database_explorer.table_keyset.as_ref() else {
+            return;
+        };
+        let key_len = cursor.key_columns.len();
+        let Some(values) = key_values(input, key_len) else {
+            self.set_status(format!(
+                "The key has {key_len} columns; give a value for each."
+            ));
+            return;
+        };
+        if self.discard_table_draft() {
+            self.set_status("Draft discarded (page change).");
+        }
+        match self.fetch_keyset_page(KeySeek::From(values)).await {
+            Ok(0) => self.set_status(format!("No rows from {input} on.")),
+            Ok(_) => {
+                if let Some(fd) = self.database_explorer.table_data.as_mut() {
+                    fd.table.view.state.select(Some(0));
+                }
+                self.set_status(format!("Rows from {input} on."));
+            }
+            Err(e) => self.set_status(format!("Failed to jump to key: {e}")),
+        }
+    }
+
+    /// Show the page after (`forward`) or before the one shown, by key.
+    pub(crate) async fn step_keyset_page(&mut self, forward: bool) {
+        let Some(seek) = self
+            .database_explorer
+            .table_keyset
+            .as_ref()
+            .and_then(|cursor| cursor.step(forward))
+        else {
+            return;
+        };
+        match self.fetch_keyset_page(seek).await {
+            Ok(0) if forward => self.set_status("Already at last page."),
+            Ok(0) => self.set_status("Already at first page."),
+            Ok(_) => {}
+            Err(e) => self.set_status(format!("Failed to load page: {e}")),
+        }
+    }
+
+    /// Read and show the page of the current table at `seek`; returns its
+    /// row count. An empty page other than the first leaves the page shown
+    /// in place, as there are no rows that way.
+    pub(crate) async fn fetch_keyset_page(
+        &mut self,
+        seek: KeySeek,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        self.discard_table_draft();
+        let explorer = &mut self.database_explorer;
+        let DatabaseExplorerState::TableData(schema, table) = &explorer.state
+        else {
+            return Ok(0);
+        };
+        let (Some(database), Some(cursor)) =
+            (explorer.database.as_ref(), explorer.table_keyset.as_ref())
+        else {
+            return Ok(0);
+        };
+        let page_size = explorer
+            .table_data_virtual
+            .as_ref()
+            .map_or(VIRTUAL_TABLE_PAGE_SIZE, |m| m.page_size);
+        let total_rows = explorer
+            .table_data_virtual
+            .as_ref()
+            .and_then(|m| m.total_rows);
+        let keyset = cursor.page(seek.clone());
+        let started = Instant::now();
+        let page = database
+            .get_table_data_page(
+                schema,
+                table,
+                None,
+                Some(&keyset),
+                0,
+                page_size,
+            )
+            .await;
+        metrics::record_page(&explorer.connection.name, started, &page);
+        let TableDataPage {
+            rows: data,
+            column_names,
+            row_ids,
+            sql,
+        } = page?;
+        let loaded = data.len();
+        if loaded == 0 && seek != KeySeek::Start {
+            if let Some(meta) = explorer.table_data_virtual.as_mut() {
+                match seek {
+                    KeySeek::After(_) => meta.has_more_after = false,
+                    KeySeek::Before(_) => meta.has_more_before = false,
+                    KeySeek::Start | KeySeek::From(_) => {}
+                }
+            }
+            return Ok(0);
+        }
+        let meta = VirtualTableMeta::from_keyset_fetch(
+            &seek, page_size, loaded, total_rows,
+        );
+        if let Some(cursor) = explorer.table_keyset.as_mut() {
+            cursor.shown(seek, &column_names, &data);
+        }
+        let mut table_state = TableDataState::default();
+        table_state.reset(data, &column_names, Some(row_ids));
+        explorer.replace_table_data_page(table_state);
+        explorer.table_data_virtual = Some(meta);
+        explorer.table_data_sql = Some(sql);
+        Ok(loaded)
+    }
+}
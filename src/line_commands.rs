@@ -0,0 +1,246 @@
+//! Line commands on the SQL buffer: toggle `--` comments, duplicate, delete
+//! and move a run of lines, typed into a prompt like the block insert
+//! instead of going through the external editor.
+
+use std::str::FromStr;
+
+use crate::{
+    app::App,
+    ui::widgets::modal::{InputModal, InputPurpose},
+};
+
+/// What to do with the lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineAction {
+    /// Comment the lines out with `--`, or uncomment them when they all
+    /// are.
+    ToggleComment,
+    /// Repeat the lines below themselves.
+    Duplicate,
+    Delete,
+    /// Swap the lines with the line above them.
+    MoveUp,
+    /// Swap the lines with the line below them.
+    MoveDown,
+}
+
+impl LineAction {
+    const fn from_letter(letter: &str) -> Option<Self> {
+        match letter.as_bytes() {
+            b"c" => Some(Self::ToggleComment),
+            b"y" => Some(Self::Duplicate),
+            b"d" => Some(Self::Delete),
+            b"k" => Some(Self::MoveUp),
+            b"j" => Some(Self::MoveDown),
+            _ => None,
+        }
+    }
+}
+
+/// An action on lines `first..=last` (1-based), read from `ACTION LINES`
+/// such as `c 3-5` or `d 7`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineCommand {
+    pub action: LineAction,
+    pub first: usize,
+    pub last: usize,
+}
+
+impl FromStr for LineCommand {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let usage = || {
+            format!(
+                "'{input}' isn't ACTION LINES with c, y, d, k or j, e.g. c 3-5"
+            )
+        };
+        let (action, lines) = input.trim().split_once(' ').ok_or_else(usage)?;
+        let action = LineAction::from_letter(action).ok_or_else(usage)?;
+        let lines = lines.trim();
+        let (first, last) = lines.split_once('-').unwrap_or((lines, lines));
+        let number =
+            |text: &str| text.trim().parse::<usize>().ok().filter(|&n| n > 0);
+        let (Some(first), Some(last)) = (number(first), number(last)) else {
+            return Err(usage());
+        };
+        if first > last {
+            return Err(format!("Line {first} comes after line {last}."));
+        }
+        Ok(Self {
+            action,
+            first,
+            last,
+        })
+    }
+}
+
+impl LineCommand {
+    /// `sql` with the action done on the lines. Fails when the lines run
+    /// past the end of `sql`, or there is no line to move them past.
+    ///
+    /// # Errors
+    ///
+    /// The message for the status line when the lines can't be changed.
+    pub fn apply(&self, sql: &str) -> Result<String, String> {
+        let mut lines = sql.lines().map(String::from).collect::<Vec<_>>();
+        if self.last > lines.len() {
+            return Err(format!(
+                "The SQL has {} lines; line {} is past its end.",
+                lines.len(),
+                self.last
+            ));
+        }
+        let range = self.first - 1..self.last;
+        match self.action {
+            LineAction::ToggleComment => {
+                if let Some(block) = lines.get_mut(range) {
+                    toggle_comments(block);
+                }
+            }
+            LineAction::Duplicate => {
+                let block = lines.get(range).unwrap_or_default().to_vec();
+                lines.splice(self.last..self.last, block);
+            }
+            LineAction::Delete => {
+                lines.drain(range);
+            }
+            LineAction::MoveUp => {
+                if self.first == 1 {
+                    return Err("The lines are already at the top.".into());
+                }
+                if let Some(block) = lines.get_mut(self.first - 2..self.last) {
+                    block.rotate_left(1);
+                }
+            }
+            LineAction::MoveDown => {
+                if self.last == lines.len() {
+                    return Err("The lines are already at the bottom.".into());
+                }
+                if let Some(block) = lines.get_mut(range.start..=self.last) {
+                    block.rotate_right(1);
+                }
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Uncomment `lines` when every line with text is a `--` comment, otherwise
+/// comment them out at the indentation of the least indented one. Blank
+/// lines are left alone.
+fn toggle_comments(lines: &mut [String]) {
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let texts = || lines.iter().filter(|line| !line.trim().is_empty());
+    if texts().all(|line| line.trim_start().starts_with("--")) {
+        for line in lines.iter_mut() {
+            let at = indent(line);
+            let Some(rest) = line.get(at..).and_then(|l| l.strip_prefix("--"))
+            else {
+                continue;
+            };
+            let rest = rest.strip_prefix(' ').unwrap_or(rest);
+            *line = format!("{}{rest}", line.get(..at).unwrap_or_default());
+        }
+        return;
+    }
+
+    let at = texts().map(|line| indent(line)).min().unwrap_or_default();
+    for line in lines.iter_mut().filter(|line| !line.trim().is_empty()) {
+        line.insert_str(at, "-- ");
+    }
+}
+
+impl App<'_> {
+    /// Ask for a line command, offering the lines of the selected statement
+    pub(crate) fn open_line_command_prompt(&mut self) {
+        let executor = &self.database_explorer.sql_executor;
+        let sql = executor.sql_input();
+        if sql.trim().is_empty() {
+            self.set_status("The SQL buffer is empty; press e to write some.");
+            return;
+        }
+        let lines = executor
+            .selected_statement_lines()
+            .unwrap_or((1, sql.lines().count()));
+        let lines = if lines.0 == lines.1 {
+            lines.0.to_string()
+        } else {
+            format!("{}-{}", lines.0, lines.1)
+        };
+        self.modal_manager.open_input_modal(
+            InputModal::new(
+                " Line command ",
+                "ACTION LINES: c comments or uncomments, y duplicates, \
+                 d deletes, k and j move up and down:",
+                InputPurpose::LineCommand,
+            )
+            .with_value(&format!("c {lines}")),
+        );
+    }
+
+    /// Do the line command typed into the prompt and load the SQL as if it
+    /// came back from the editor
+    pub(crate) async fn line_command(&mut self, input: &str) {
+        let sql = self.database_explorer.sql_executor.sql_input();
+        match input
+            .parse::<LineCommand>()
+            .and_then(|command| command.apply(&sql))
+        {
+            Ok(sql) => self.load_sql(&sql).await,
+            Err(e) => self.set_status(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &str, sql: &str) -> Result<String, String> {
+        input.parse::<LineCommand>()?.apply(sql)
+    }
+
+    #[test]
+    fn reads_action_and_lines() {
+        assert_eq!(
+            "c 2-4".parse::<LineCommand>().unwrap(),
+            LineCommand {
+                action: LineAction::ToggleComment,
+                first: 2,
+                last: 4,
+            }
+        );
+        let one = "j 3".parse::<LineCommand>().unwrap();
+        assert_eq!(
+            (one.action, one.first, one.last),
+            (LineAction::MoveDown, 3, 3)
+        );
+        assert!("x 1".parse::<LineCommand>().is_err());
+        assert!("d 4-2".parse::<LineCommand>().is_err());
+        assert!("d 0".parse::<LineCommand>().is_err());
+        assert!("d".parse::<LineCommand>().is_err());
+    }
+
+    #[test]
+    fn toggles_comments_at_the_least_indentation() {
+        let sql = "SELECT *\n  FROM t\n\n    WHERE id = 1";
+        let commented = run("c 2-4", sql).unwrap();
+        assert_eq!(commented, "SELECT *\n  -- FROM t\n\n  --   WHERE id = 1");
+        assert_eq!(run("c 2-4", &commented).unwrap(), sql);
+        // A mix of comments and code is commented out
+        assert_eq!(run("c 1-2", "-- a\nb").unwrap(), "-- -- a\n-- b");
+    }
+
+    #[test]
+    fn duplicates_deletes_and_moves_lines() {
+        let sql = "a\nb\nc\nd";
+        assert_eq!(run("y 2-3", sql).unwrap(), "a\nb\nc\nb\nc\nd");
+        assert_eq!(run("d 2-3", sql).unwrap(), "a\nd");
+        assert_eq!(run("k 3-4", sql).unwrap(), "a\nc\nd\nb");
+        assert_eq!(run("j 1-2", sql).unwrap(), "c\na\nb\nd");
+        assert!(run("k 1", sql).is_err());
+        assert!(run("j 4", sql).is_err());
+        assert!(run("d 5", sql).is_err());
+    }
+}
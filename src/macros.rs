@@ -0,0 +1,195 @@
+//! Vim-style keyboard macros. `Q<reg>` records the keys that follow into a
+//! register until the next `Q`, `@<reg>` replays them and `@@` replays the
+//! last register again. `q` stays quit, so recording starts from `Q`.
+
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+/// How deep macros may replay other macros; stops a register that replays
+/// itself from looping forever.
+pub const MAX_REPLAY_DEPTH: usize = 8;
+
+/// Prefix key waiting for its register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pending {
+    Record,
+    Replay,
+}
+
+/// What a key press means to the macro layer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroKey {
+    /// Not a macro key; handle it as usual.
+    Pass,
+    /// A prefix waiting for its register, or a cancelled prefix.
+    Consumed,
+    Started(char),
+    /// Recording into the register stopped after this many keys.
+    Stopped(char, usize),
+    /// Keys to feed back through the key handler.
+    Replay(Vec<KeyEvent>),
+    Empty(char),
+}
+
+/// Registers and the recording in progress.
+#[derive(Debug, Default)]
+pub struct MacroRecorder {
+    registers: HashMap<char, Vec<KeyEvent>>,
+    recording: Option<(char, Vec<KeyEvent>)>,
+    pending: Option<Pending>,
+    last_replayed: Option<char>,
+    depth: usize,
+}
+
+impl MacroRecorder {
+    /// Register being recorded into, if any.
+    #[must_use]
+    pub fn recording(&self) -> Option<char> {
+        self.recording.as_ref().map(|(register, _)| *register)
+    }
+
+    /// Whether `Q` or `@` is waiting for its register.
+    #[must_use]
+    pub const fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Append a key typed by the user to the recording in progress.
+    pub fn record(&mut self, key: KeyEvent) {
+        if let Some((_, keys)) = &mut self.recording {
+            keys.push(key);
+        }
+    }
+
+    /// Interpret `key` as a macro command. Only called where keys aren't
+    /// text input, so `Q` and `@` can still be typed into filters and forms.
+    pub fn handle(&mut self, key: KeyEvent) -> MacroKey {
+        if let Some(pending) = self.pending.take() {
+            let KeyCode::Char(c) = key.code else {
+                return MacroKey::Consumed;
+            };
+            return match pending {
+                Pending::Record if is_register(c) => {
+                    self.recording = Some((c, Vec::new()));
+                    MacroKey::Started(c)
+                }
+                Pending::Replay if c == '@' => {
+                    self.last_replayed.map_or(MacroKey::Consumed, |register| {
+                        self.replay(register)
+                    })
+                }
+                Pending::Replay if is_register(c) => self.replay(c),
+                Pending::Record | Pending::Replay => MacroKey::Consumed,
+            };
+        }
+
+        if key.code == KeyCode::Char('Q') {
+            if let Some((register, mut keys)) = self.recording.take() {
+                // Drop the `Q` that stopped the recording
+                keys.pop();
+                let count = keys.len();
+                self.registers.insert(register, keys);
+                return MacroKey::Stopped(register, count);
+            }
+            self.pending = Some(Pending::Record);
+            return MacroKey::Consumed;
+        }
+        if key.code == KeyCode::Char('@') {
+            self.pending = Some(Pending::Replay);
+            return MacroKey::Consumed;
+        }
+        MacroKey::Pass
+    }
+
+    fn replay(&mut self, register: char) -> MacroKey {
+        self.last_replayed = Some(register);
+        self.registers
+            .get(&register)
+            .filter(|keys| !keys.is_empty())
+            .map_or(MacroKey::Empty(register), |keys| {
+                MacroKey::Replay(keys.clone())
+            })
+    }
+
+    /// Enter one more level of replay; `false` once the limit is reached.
+    pub const fn enter_replay(&mut self) -> bool {
+        if self.depth >= MAX_REPLAY_DEPTH {
+            return false;
+        }
+        self.depth += 1;
+        true
+    }
+
+    pub const fn exit_replay(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+}
+
+const fn is_register(c: char) -> bool {
+    c.is_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+
+    use super::*;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    /// Feed keys as the app does: record first, then interpret.
+    fn press(recorder: &mut MacroRecorder, keys: &str) -> Vec<MacroKey> {
+        keys.chars()
+            .map(|c| {
+                recorder.record(key(c));
+                recorder.handle(key(c))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn records_and_replays_a_register() {
+        let mut recorder = MacroRecorder::default();
+        let results = press(&mut recorder, "Qajj/Q");
+        assert_eq!(results.get(1), Some(&MacroKey::Started('a')));
+        assert_eq!(recorder.recording(), None);
+        assert_eq!(results.last(), Some(&MacroKey::Stopped('a', 3)));
+
+        let replayed = press(&mut recorder, "@a");
+        assert_eq!(
+            replayed.last(),
+            Some(&MacroKey::Replay(vec![key('j'), key('j'), key('/')]))
+        );
+        let again = press(&mut recorder, "@@");
+        assert_eq!(again.last(), replayed.last());
+    }
+
+    #[test]
+    fn ignores_unset_registers_and_other_keys() {
+        let mut recorder = MacroRecorder::default();
+        assert_eq!(
+            press(&mut recorder, "@b").last(),
+            Some(&MacroKey::Empty('b'))
+        );
+        assert_eq!(
+            press(&mut recorder, "Q1").last(),
+            Some(&MacroKey::Consumed)
+        );
+        assert_eq!(recorder.recording(), None);
+        assert_eq!(press(&mut recorder, "j"), [MacroKey::Pass]);
+    }
+
+    #[test]
+    fn limits_replay_depth() {
+        let mut recorder = MacroRecorder::default();
+        for _ in 0..MAX_REPLAY_DEPTH {
+            assert!(recorder.enter_replay());
+        }
+        assert!(!recorder.enter_replay());
+        recorder.exit_replay();
+        assert!(recorder.enter_replay());
+    }
+}
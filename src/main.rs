@@ -1,27 +1,199 @@
 mod app;
 mod app_state;
 mod auth;
+mod autocomplete;
+mod background_task;
+mod benchmark;
+mod block_insert;
+mod cli;
+mod column_analysis;
+mod column_search;
+mod compare_plans;
+mod compare_tables;
+mod confirmations;
+mod connect_retry;
 mod connection_manager;
+mod connection_sort;
 mod database_explorer;
 mod database_explorer_state;
 mod db;
+mod deep_link;
+mod disk_usage;
+mod duplicate_rows;
 mod event_handlers;
+mod export;
 mod filtered_data;
 mod filtering;
+mod index_suggestions;
+mod keyring_check;
+mod keyset_paging;
+mod line_commands;
+mod macros;
+mod metrics;
+mod orphan_check;
+mod parquet_export;
+mod query_library;
 mod rendering;
+mod result_history;
+mod row_colors;
+mod row_lookup;
+mod row_notes;
+mod saved_views;
+mod savepoints;
 mod services;
+mod sort;
 mod sql;
+mod sql_source;
+mod statement_queue;
+mod store_recovery;
 mod table_data_actions;
+mod top_queries;
 mod ui;
 mod virtual_table;
 
+use std::path::Path;
+
 use app::App;
+use cli::{Cli, Command};
+use services::{
+    ConnectionService, PasswordService, connection_service::TestOutcome,
+};
 
 #[tokio::main]
 async fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
+
+    let cli = Cli::parse()?;
+    match cli.command {
+        Command::Help => {
+            Cli::print_help();
+            return Ok(());
+        }
+        Command::Version => {
+            Cli::print_version();
+            return Ok(());
+        }
+        Command::Run
+        | Command::TestConnections
+        | Command::RestoreBackup(_)
+        | Command::ExportQueries(_)
+        | Command::ImportQueries(_) => {}
+    }
+    let explain_thresholds = cli.explain_thresholds();
+    let time_box = cli.time_box();
+    let confirmations = cli.confirmations();
+    let connect_retries = cli.connect_retries();
+    let pool_limits = cli.pool_limits();
+    let palette = cli.palette();
+    let metrics_report = cli.metrics();
+    let source = cli.source;
+    let link = cli.link;
+    if let Some(data_dir) = cli.data_dir {
+        db::set_data_dir_override(data_dir);
+    }
+    db::pool::set_limits(pool_limits);
+
+    if cli.command == Command::TestConnections {
+        return test_connections().await;
+    }
+    if let Command::RestoreBackup(backup) = &cli.command {
+        return restore_backup(backup.as_deref());
+    }
+    if let Command::ExportQueries(path) = &cli.command {
+        return export_queries(path);
+    }
+    if let Command::ImportQueries(path) = &cli.command {
+        return import_queries(path);
+    }
+
+    if metrics_report.is_some() {
+        metrics::enable();
+    }
     let terminal = ratatui::init();
-    let result = App::default().init()?.run(terminal).await;
+    let result = App::default()
+        .with_explain_thresholds(explain_thresholds)
+        .with_time_box(time_box)
+        .with_confirmations(confirmations)
+        .with_connect_retries(connect_retries)
+        .with_source(source)
+        .with_link(link)
+        .with_palette(palette)
+        .init()?
+        .run(terminal)
+        .await;
     ratatui::restore();
+    if let Some(path) = metrics_report
+        && let Err(e) = metrics::write_report(&path)
+    {
+        eprintln!("Failed to write metrics to {}: {e}", path.display());
+    }
     result
 }
+
+/// `d7s test-connections`: print one line per saved connection and exit
+/// non-zero if any failed.
+async fn test_connections() -> color_eyre::Result<()> {
+    db::sqlite::init_db()?;
+    let passwords = PasswordService::new();
+    let connections = ConnectionService::get_all()?
+        .into_iter()
+        .map(|connection| passwords.with_password(connection))
+        .collect();
+    let reports = ConnectionService::test_all(connections).await;
+
+    for report in &reports {
+        println!("{}", report.summary_line());
+    }
+
+    let failed = reports
+        .iter()
+        .filter(|r| matches!(r.outcome, TestOutcome::Failed(_)))
+        .count();
+    println!("{} tested, {failed} failed", reports.len());
+    if failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// `d7s restore-backup [FILE]`: restore a store backup, or list them.
+fn restore_backup(backup: Option<&Path>) -> color_eyre::Result<()> {
+    let Some(backup) = backup else {
+        let dir = db::backup::backup_dir()?;
+        let backups = db::backup::list_backups(&dir)?;
+        if backups.is_empty() {
+            println!("No backups in {}", dir.display());
+            return Ok(());
+        }
+        println!("Backups in {}, newest first:", dir.display());
+        for path in backups {
+            if let Some(name) = path.file_name() {
+                println!("  {}", name.display());
+            }
+        }
+        println!("\nRestore one with: d7s restore-backup <FILE>");
+        return Ok(());
+    };
+    let previous = db::backup::restore_backup(backup)?;
+    println!("Restored {}", backup.display());
+    if let Some(previous) = previous {
+        println!("The replaced store was saved as {}", previous.display());
+    }
+    Ok(())
+}
+
+/// `d7s export-queries FILE`: write the SQL buffers to a file.
+fn export_queries(path: &Path) -> color_eyre::Result<()> {
+    db::sqlite::init_db()?;
+    let count = query_library::export_library(path)?;
+    println!("Exported {count} SQL buffers to {}", path.display());
+    Ok(())
+}
+
+/// `d7s import-queries FILE`: add the SQL buffers in a file.
+fn import_queries(path: &Path) -> color_eyre::Result<()> {
+    db::sqlite::init_db()?;
+    let count = query_library::import_library(path)?;
+    println!("Imported {count} SQL buffers from {}", path.display());
+    Ok(())
+}
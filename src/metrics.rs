@@ -0,0 +1,236 @@
+//! Counters of d7s' own work, kept only when asked for with `--metrics` or
+//! `D7S_METRICS` and written on exit in the Prometheus text format:
+//! statements and table pages run, rows read back and time spent waiting
+//! per connection, and hits of the column layout cache.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    path::Path,
+    sync::{
+        Mutex, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::db::TableDataPage;
+
+/// Path that writes the report to standard output instead of a file.
+pub const STDOUT: &str = "-";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static METRICS: OnceLock<Mutex<Metrics>> = OnceLock::new();
+
+fn metrics() -> &'static Mutex<Metrics> {
+    METRICS.get_or_init(|| Mutex::new(Metrics::default()))
+}
+
+/// What was run on one connection.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct ConnectionMetrics {
+    queries: u64,
+    errors: u64,
+    rows: u64,
+    seconds: f64,
+}
+
+impl ConnectionMetrics {
+    #[allow(clippy::cast_precision_loss)]
+    fn average_seconds(&self) -> f64 {
+        self.seconds / self.queries.max(1) as f64
+    }
+}
+
+/// How a metric's value is read from a connection's counters.
+type PerConnection = fn(&ConnectionMetrics) -> String;
+
+#[derive(Debug, Default)]
+struct Metrics {
+    connections: BTreeMap<String, ConnectionMetrics>,
+    cache_hits: u64,
+    cache_misses: u64,
+}
+
+impl Metrics {
+    fn record_query(
+        &mut self,
+        connection: &str,
+        elapsed: Duration,
+        rows: Option<usize>,
+    ) {
+        let entry = self.connections.entry(connection.to_string()).or_default();
+        entry.queries += 1;
+        entry.seconds += elapsed.as_secs_f64();
+        match rows {
+            Some(rows) => entry.rows += rows as u64,
+            None => entry.errors += 1,
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+        let families: [(&str, &str, &str, PerConnection); 5] = [
+            (
+                "d7s_queries_total",
+                "counter",
+                "Statements and table pages run.",
+                |m| m.queries.to_string(),
+            ),
+            (
+                "d7s_query_errors_total",
+                "counter",
+                "Statements and table pages that failed.",
+                |m| m.errors.to_string(),
+            ),
+            (
+                "d7s_rows_fetched_total",
+                "counter",
+                "Rows read back.",
+                |m| m.rows.to_string(),
+            ),
+            (
+                "d7s_query_duration_seconds_total",
+                "counter",
+                "Time spent waiting for the database.",
+                |m| m.seconds.to_string(),
+            ),
+            (
+                "d7s_query_duration_seconds_average",
+                "gauge",
+                "Average time a statement or table page took.",
+                |m| m.average_seconds().to_string(),
+            ),
+        ];
+        for (name, kind, help, value) in families {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} {kind}");
+            for (connection, metrics) in &self.connections {
+                let _ = writeln!(
+                    out,
+                    "{name}{{connection=\"{}\"}} {}",
+                    escape_label(connection),
+                    value(metrics)
+                );
+            }
+        }
+        for (name, help, value) in [
+            (
+                "d7s_column_cache_hits_total",
+                "Table column layouts found in the cache.",
+                self.cache_hits,
+            ),
+            (
+                "d7s_column_cache_misses_total",
+                "Table column layouts read from the database.",
+                self.cache_misses,
+            ),
+        ] {
+            let _ = writeln!(
+                out,
+                "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}"
+            );
+        }
+        out
+    }
+}
+
+/// `value` as a label value: backslashes, quotes and newlines escaped.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Start keeping metrics.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+fn with_metrics(update: impl FnOnce(&mut Metrics)) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Ok(mut metrics) = metrics().lock() {
+        update(&mut metrics);
+    }
+}
+
+/// Count a statement or table page run on `connection`: the rows it
+/// returned, or `None` when it failed.
+pub fn record_query(connection: &str, elapsed: Duration, rows: Option<usize>) {
+    with_metrics(|metrics| metrics.record_query(connection, elapsed, rows));
+}
+
+/// Count a table page read on `connection` since `started`.
+pub fn record_page<E>(
+    connection: &str,
+    started: Instant,
+    page: &Result<TableDataPage, E>,
+) {
+    let rows = page.as_ref().ok().map(|page| page.rows.len());
+    record_query(connection, started.elapsed(), rows);
+}
+
+/// Count a lookup in the column layout cache.
+pub fn record_cache_lookup(hit: bool) {
+    with_metrics(|metrics| {
+        if hit {
+            metrics.cache_hits += 1;
+        } else {
+            metrics.cache_misses += 1;
+        }
+    });
+}
+
+/// Write what was counted to `path`, or to standard output for
+/// [`STDOUT`].
+///
+/// # Errors
+///
+/// Fails when the file can't be written.
+pub fn write_report(path: &Path) -> std::io::Result<()> {
+    let report = metrics()
+        .lock()
+        .map(|metrics| metrics.render())
+        .unwrap_or_default();
+    if path == Path::new(STDOUT) {
+        print!("{report}");
+        Ok(())
+    } else {
+        std::fs::write(path, report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_counters_per_connection() {
+        let mut metrics = Metrics::default();
+        metrics.record_query("prod", Duration::from_millis(250), Some(40));
+        metrics.record_query("prod", Duration::from_millis(750), None);
+        metrics.record_query("my \"dev\"", Duration::ZERO, Some(1));
+        metrics.cache_hits = 3;
+        let report = metrics.render();
+        assert!(report.contains("# TYPE d7s_queries_total counter\n"));
+        assert!(report.contains("d7s_queries_total{connection=\"prod\"} 2\n"));
+        assert!(
+            report.contains("d7s_query_errors_total{connection=\"prod\"} 1\n")
+        );
+        assert!(
+            report.contains("d7s_rows_fetched_total{connection=\"prod\"} 40\n")
+        );
+        assert!(report.contains(
+            "d7s_query_duration_seconds_average{connection=\"prod\"} 0.5\n"
+        ));
+        assert!(
+            report
+                .contains("d7s_queries_total{connection=\"my \\\"dev\\\"\"} 1")
+        );
+        assert!(report.contains("d7s_column_cache_hits_total 3\n"));
+        assert!(report.contains("d7s_column_cache_misses_total 0\n"));
+    }
+}
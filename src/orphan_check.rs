@@ -0,0 +1,75 @@
+//! Counting rows whose foreign key points at a missing parent, for keys the
+//! database doesn't check (`NOT VALID`, unenforced or never declared).
+
+use crate::{
+    app::App,
+    db::{
+        Database,
+        foreign_key::{ForeignKey, ForeignKeyKind},
+    },
+    ui::widgets::{
+        modal::InfoModal,
+        orphan_report::{OrphanCheck, OrphanReport},
+    },
+};
+
+impl App<'_> {
+    /// Check every foreign key of the current table, declared or suggested
+    /// by a `<parent>_id` column, for orphaned rows
+    pub(crate) async fn check_orphans(&mut self) {
+        let Some((schema, table)) = self.current_table() else {
+            return;
+        };
+        let Some(database) = self.database_explorer.database.clone() else {
+            return;
+        };
+        let keys = match all_foreign_keys(database.as_ref(), &schema, &table)
+            .await
+            .map_err(|e| e.to_string())
+        {
+            Ok(keys) => keys,
+            Err(e) => {
+                self.set_status(format!("Failed to read foreign keys: {e}"));
+                return;
+            }
+        };
+        if keys.is_empty() {
+            self.set_status(format!(
+                "{schema}.{table} has no foreign keys or <table>_id columns."
+            ));
+            return;
+        }
+
+        let mut checks = Vec::with_capacity(keys.len());
+        for key in keys {
+            let check = if key.kind == ForeignKeyKind::Validated {
+                OrphanCheck::Skipped
+            } else {
+                match database.count_orphans(&schema, &table, &key).await {
+                    Ok(count) => OrphanCheck::Counted(count),
+                    Err(e) => OrphanCheck::Failed(e.to_string()),
+                }
+            };
+            checks.push((key, check));
+        }
+        let name = format!("{schema}.{table}");
+        let lines = OrphanReport::new(&name, &checks).lines();
+        self.modal_manager.open_info_modal(InfoModal::new(
+            format!(" Orphaned rows: {table} "),
+            lines,
+        ));
+    }
+}
+
+/// Declared foreign keys of a table, then the ones its column names suggest.
+async fn all_foreign_keys(
+    database: &dyn Database,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<ForeignKey>, Box<dyn std::error::Error>> {
+    let declared = database.get_foreign_keys(schema, table).await?;
+    let guessed = database
+        .guess_foreign_keys(schema, table, &declared)
+        .await?;
+    Ok(declared.into_iter().chain(guessed).collect())
+}
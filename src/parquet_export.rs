@@ -0,0 +1,290 @@
+//! Writing result sets as Parquet, typed from the columns' server types so
+//! they load as numbers, booleans and timestamps in pandas or `DuckDB`.
+
+use std::{
+    io::{self, Write},
+    str::FromStr,
+    sync::Arc,
+};
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Date32Array, Float32Array, Float64Array,
+    Int16Array, Int32Array, Int64Array, RecordBatch, StringArray,
+    TimestampMicrosecondArray,
+};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::NaiveDate;
+use parquet::{
+    arrow::ArrowWriter, basic::Compression,
+    file::properties::WriterProperties,
+};
+
+use crate::sort::parse_temporal;
+
+/// How the grid shows NULL.
+const NULL: &str = "NULL";
+
+/// Rows per row group, also written between progress reports.
+const ROW_GROUP_ROWS: usize = 10_000;
+
+/// Time zone of `timestamptz` columns; the driver reads them in UTC.
+const UTC: &str = "UTC";
+
+/// Parquet type a column is written as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Boolean,
+    Int16,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    Date,
+    Timestamp,
+    TimestampTz,
+    Text,
+}
+
+impl ColumnType {
+    /// Type for a PostgreSQL column type name. `numeric` becomes a double,
+    /// as its scale isn't known from the results.
+    fn from_server_type(name: &str) -> Self {
+        match name {
+            "bool" => Self::Boolean,
+            "int2" => Self::Int16,
+            "int4" => Self::Int32,
+            "int8" | "oid" => Self::Int64,
+            "float4" => Self::Float32,
+            "float8" | "numeric" => Self::Float64,
+            "date" => Self::Date,
+            "timestamp" => Self::Timestamp,
+            "timestamptz" => Self::TimestampTz,
+            _ => Self::Text,
+        }
+    }
+
+    fn data_type(self) -> DataType {
+        match self {
+            Self::Boolean => DataType::Boolean,
+            Self::Int16 => DataType::Int16,
+            Self::Int32 => DataType::Int32,
+            Self::Int64 => DataType::Int64,
+            Self::Float32 => DataType::Float32,
+            Self::Float64 => DataType::Float64,
+            Self::Date => DataType::Date32,
+            Self::Timestamp => {
+                DataType::Timestamp(TimeUnit::Microsecond, None)
+            }
+            Self::TimestampTz => {
+                DataType::Timestamp(TimeUnit::Microsecond, Some(UTC.into()))
+            }
+            Self::Text => DataType::Utf8,
+        }
+    }
+
+    /// `values` as a column of this type; `None` when one doesn't parse.
+    fn array(self, values: &[&str]) -> Option<ArrayRef> {
+        let array: ArrayRef = match self {
+            Self::Boolean => {
+                Arc::new(BooleanArray::from(parse_all(values, parse_bool)?))
+            }
+            Self::Int16 => {
+                Arc::new(Int16Array::from(parse_all(values, parse_number)?))
+            }
+            Self::Int32 => {
+                Arc::new(Int32Array::from(parse_all(values, parse_number)?))
+            }
+            Self::Int64 => {
+                Arc::new(Int64Array::from(parse_all(values, parse_number)?))
+            }
+            Self::Float32 => {
+                Arc::new(Float32Array::from(parse_all(values, parse_number)?))
+            }
+            Self::Float64 => {
+                Arc::new(Float64Array::from(parse_all(values, parse_number)?))
+            }
+            Self::Date => {
+                Arc::new(Date32Array::from(parse_all(values, epoch_days)?))
+            }
+            Self::Timestamp => Arc::new(TimestampMicrosecondArray::from(
+                parse_all(values, epoch_micros)?,
+            )),
+            Self::TimestampTz => Arc::new(
+                TimestampMicrosecondArray::from(parse_all(
+                    values,
+                    epoch_micros,
+                )?)
+                .with_timezone(UTC),
+            ),
+            Self::Text => Arc::new(StringArray::from(parse_all(values, Some)?)),
+        };
+        Some(array)
+    }
+}
+
+/// Each value through `parse`, with NULLs as `None`; `None` when a value
+/// doesn't parse.
+fn parse_all<'a, T>(
+    values: &[&'a str],
+    parse: impl Fn(&'a str) -> Option<T>,
+) -> Option<Vec<Option<T>>> {
+    values
+        .iter()
+        .map(|&value| {
+            if value == NULL {
+                Some(None)
+            } else {
+                parse(value).map(Some)
+            }
+        })
+        .collect()
+}
+
+fn parse_number<T: FromStr>(value: &str) -> Option<T> {
+    value.trim().parse().ok()
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" | "t" => Some(true),
+        "false" | "f" => Some(false),
+        _ => None,
+    }
+}
+
+fn epoch_days(value: &str) -> Option<i32> {
+    let date = NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok()?;
+    let days = date.signed_duration_since(NaiveDate::default()).num_days();
+    i32::try_from(days).ok()
+}
+
+fn epoch_micros(value: &str) -> Option<i64> {
+    Some(parse_temporal(value)?.and_utc().timestamp_micros())
+}
+
+/// The column of `values` typed as the server describes it, or from the
+/// values when it doesn't (SQLite): whole numbers, then any numbers. A
+/// column with a value its type can't read, such as a timestamp of
+/// `infinity`, is written as text.
+fn typed_column(
+    server_type: Option<&str>,
+    values: &[&str],
+) -> (DataType, ArrayRef) {
+    let candidates = match server_type {
+        Some(name) => vec![ColumnType::from_server_type(name)],
+        None if values.iter().all(|value| *value == NULL) => Vec::new(),
+        None => vec![ColumnType::Int64, ColumnType::Float64],
+    };
+    candidates
+        .iter()
+        .find_map(|ty| Some((ty.data_type(), ty.array(values)?)))
+        .unwrap_or_else(|| {
+            let text = values
+                .iter()
+                .map(|&value| (value != NULL).then_some(value))
+                .collect::<Vec<_>>();
+            let array: ArrayRef = Arc::new(StringArray::from(text));
+            (DataType::Utf8, array)
+        })
+}
+
+/// Write `rows` as a Snappy-compressed Parquet file, one column per entry
+/// of `columns` typed from `column_types` (empty when the backend doesn't
+/// report them), calling `on_progress` with the rows written after each
+/// row group.
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn write_parquet(
+    out: &mut (impl Write + Send),
+    columns: &[String],
+    column_types: &[String],
+    rows: &[Vec<String>],
+    mut on_progress: impl FnMut(usize),
+) -> io::Result<()> {
+    let (fields, arrays): (Vec<Field>, Vec<ArrayRef>) = columns
+        .iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            let values = rows
+                .iter()
+                .map(|row| row.get(idx).map_or(NULL, String::as_str))
+                .collect::<Vec<_>>();
+            let server_type = column_types.get(idx).map(String::as_str);
+            let (data_type, array) = typed_column(server_type, &values);
+            (Field::new(name, data_type, true), array)
+        })
+        .unzip();
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), arrays)
+        .map_err(io::Error::other)?;
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_max_row_group_size(ROW_GROUP_ROWS)
+        .build();
+    let mut writer = ArrowWriter::try_new(&mut *out, schema, Some(properties))
+        .map_err(io::Error::other)?;
+    let mut done = 0;
+    while done < rows.len() {
+        let len = ROW_GROUP_ROWS.min(rows.len() - done);
+        writer
+            .write(&batch.slice(done, len))
+            .map_err(io::Error::other)?;
+        done += len;
+        if done < rows.len() {
+            on_progress(done);
+        }
+    }
+    writer.close().map_err(io::Error::other)?;
+    on_progress(rows.len());
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::Array;
+
+    use super::*;
+
+    #[test]
+    fn types_columns_from_the_server_or_the_values() {
+        let data_type =
+            |server_type, values: &[&str]| typed_column(server_type, values).0;
+        assert_eq!(data_type(Some("int4"), &["1", "NULL"]), DataType::Int32);
+        assert_eq!(
+            data_type(Some("timestamptz"), &["2026-01-02T03:04:05+00:00"]),
+            DataType::Timestamp(TimeUnit::Microsecond, Some(UTC.into()))
+        );
+        assert_eq!(data_type(Some("date"), &["2026-01-02"]), DataType::Date32);
+        assert_eq!(data_type(Some("timestamp"), &["infinity"]), DataType::Utf8);
+        assert_eq!(data_type(Some("uuid"), &["NULL"]), DataType::Utf8);
+        assert_eq!(data_type(None, &["1", "2"]), DataType::Int64);
+        assert_eq!(data_type(None, &["1", "2.5"]), DataType::Float64);
+        assert_eq!(data_type(None, &["1", "a"]), DataType::Utf8);
+        assert_eq!(data_type(None, &["NULL"]), DataType::Utf8);
+
+        let (_, days) = typed_column(Some("date"), &["1970-01-11", "NULL"]);
+        let days = days.as_any().downcast_ref::<Date32Array>().unwrap();
+        assert_eq!(days.value(0), 10);
+        assert!(days.is_null(1));
+    }
+
+    #[test]
+    fn writes_a_parquet_file() {
+        let columns = ["id".to_string(), "note".to_string()];
+        let rows = vec![
+            vec!["1".to_string(), "a".to_string()],
+            vec!["2".to_string(), "NULL".to_string()],
+        ];
+        let mut out = Vec::new();
+        let mut reported = Vec::new();
+        write_parquet(&mut out, &columns, &[], &rows, |done| {
+            reported.push(done);
+        })
+        .unwrap();
+        assert_eq!(reported, [2]);
+        assert!(out.starts_with(b"PAR1") && out.ends_with(b"PAR1"));
+    }
+}
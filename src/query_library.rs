@@ -0,0 +1,145 @@
+//! Moving the SQL kept per connection to another machine as a JSON file.
+//!
+//! The SQL executor buffer is the only SQL d7s keeps between sessions; the
+//! result history lasts for one session, so there is no history to move.
+
+use std::{fs, path::Path};
+
+use color_eyre::{Result, eyre::eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    services::{ScratchBufferService, SettingsService},
+    sql::secrets::{SECRET_MASKING_SETTING, SecretMasking},
+};
+
+/// Version of the file layout, checked on import.
+const LIBRARY_VERSION: u32 = 1;
+
+/// Contents of an exported file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryLibrary {
+    pub version: u32,
+    pub buffers: Vec<SqlBuffer>,
+}
+
+/// SQL executor buffer of one connection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SqlBuffer {
+    /// Name of the connection; connections are matched by name on import
+    pub connection: String,
+    pub sql: String,
+}
+
+/// Buffer after importing `imported` over `existing`: the imported SQL
+/// when there is none, or both, existing first, so nothing typed on
+/// either machine is lost. `None` when the buffer already has it.
+#[must_use]
+pub fn merge_buffer(existing: Option<&str>, imported: &str) -> Option<String> {
+    let imported = imported.trim_end();
+    if imported.trim().is_empty() {
+        return None;
+    }
+    match existing.map(str::trim_end).filter(|sql| !sql.is_empty()) {
+        None => Some(imported.to_string()),
+        Some(existing) if existing.contains(imported) => None,
+        Some(existing) => Some(format!("{existing}\n\n{imported}")),
+    }
+}
+
+/// Write every connection's SQL buffer to `path`, with secrets masked
+/// unless masking is off; returns how many.
+///
+/// # Errors
+///
+/// Returns an error if the store can't be read or the file written.
+pub fn export_library(path: &Path) -> Result<usize> {
+    let masking = SecretMasking::from_saved(
+        SettingsService::get(SECRET_MASKING_SETTING)?.as_deref(),
+    );
+    let buffers = ScratchBufferService::get_all()?
+        .into_iter()
+        .map(|(connection, sql)| SqlBuffer {
+            connection,
+            sql: masking.apply(&sql),
+        })
+        .collect::<Vec<_>>();
+    let count = buffers.len();
+    let library = QueryLibrary {
+        version: LIBRARY_VERSION,
+        buffers,
+    };
+    fs::write(path, serde_json::to_string_pretty(&library)?)?;
+    Ok(count)
+}
+
+/// Add the SQL buffers in `path` to the connections of the same name;
+/// returns how many buffers changed. Buffers of connections not saved
+/// here yet are kept for when one is.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read or isn't an export, or the
+/// store can't be written.
+pub fn import_library(path: &Path) -> Result<usize> {
+    let contents = fs::read_to_string(path)?;
+    let library: QueryLibrary =
+        serde_json::from_str(&contents).map_err(|e| {
+            eyre!("{} is not a d7s query export: {e}", path.display())
+        })?;
+    if library.version > LIBRARY_VERSION {
+        return Err(eyre!(
+            "{} was exported by a newer d7s (version {})",
+            path.display(),
+            library.version
+        ));
+    }
+    let mut changed = 0;
+    for buffer in library.buffers {
+        let existing = ScratchBufferService::get(&buffer.connection)?;
+        if let Some(sql) = merge_buffer(existing.as_deref(), &buffer.sql) {
+            ScratchBufferService::save(&buffer.connection, &sql)?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_both_machines_sql() {
+        assert_eq!(merge_buffer(None, "SELECT 1;\n"), Some("SELECT 1;".into()));
+        assert_eq!(
+            merge_buffer(Some("  \n"), "SELECT 1;"),
+            Some("SELECT 1;".into())
+        );
+        assert_eq!(
+            merge_buffer(Some("SELECT 1;\nSELECT 2;"), "SELECT 2;"),
+            None
+        );
+        assert_eq!(merge_buffer(Some("SELECT 1;"), "\n"), None);
+        assert_eq!(
+            merge_buffer(Some("SELECT 1;\n"), "SELECT 2;"),
+            Some("SELECT 1;\n\nSELECT 2;".into())
+        );
+    }
+
+    #[test]
+    fn reads_what_it_writes() {
+        let library = QueryLibrary {
+            version: LIBRARY_VERSION,
+            buffers: vec![SqlBuffer {
+                connection: "orders-prod".to_string(),
+                sql: "SELECT * FROM orders;".to_string(),
+            }],
+        };
+        let json = serde_json::to_string_pretty(&library).unwrap();
+        assert_eq!(
+            serde_json::from_str::<QueryLibrary>(&json).unwrap(),
+            library
+        );
+    }
+}
@@ -1,27 +1,29 @@
 use ratatui::{
     Frame,
     prelude::*,
-    widgets::{Block, Borders},
+    widgets::{Block, Borders, Paragraph, Wrap},
 };
 
 use crate::{
-    app::{APP_NAME, App},
+    app::{APP_NAME, App, PKG_NAME},
     app_state::DatabaseExplorerState,
     db::{TableData, connection::Connection},
     filtered_data::FilteredData,
     ui::{
+        number_format::NumberFormat,
+        relative_time::TimeDisplay,
         sql_executor::SqlExecutor,
+        keymap::HotkeyContext,
+        layout::{self, MIN_TERMINAL_HEIGHT, MIN_TERMINAL_WIDTH},
         widgets::{
-            hotkey::Hotkey,
-            modal::ConnectionModalWidget,
+            modal::{ChoiceModal, ConnectionModalWidget, ModalType},
+            progress::ProgressIndicator,
             table::DataTable,
-            top_bar_view::{TABLE_DATA_VIEW_HOTKEYS, TopBarView},
+            top_bar_view::{CompactTopBar, TopBarView},
         },
     },
 };
 
-const TOPBAR_HEIGHT: u16 = 6;
-
 impl App<'_> {
     /// Renders the user interface.
     ///
@@ -31,12 +33,22 @@ impl App<'_> {
     /// - <https://github.com/ratatui/ratatui/tree/main/ratatui-widgets/examples>
     #[allow(clippy::too_many_lines)]
     pub fn render(&mut self, frame: &mut Frame) {
+        if layout::is_too_small(frame.area()) {
+            render_too_small(frame);
+            return;
+        }
+
         // Split layout: top bar, main content, and status line
         // Status line gets fixed 1 row, main content takes the rest
-        let mut main_layout =
-            vec![Constraint::Length(TOPBAR_HEIGHT), Constraint::Min(0)];
+        let mut main_layout = vec![
+            Constraint::Length(self.layout.top_bar_rows(frame.area().height)),
+            Constraint::Min(0),
+        ];
 
-        if !self.status_line.message().is_empty() {
+        if !self.status_line.message().is_empty()
+            || self.background_task.is_some()
+            || self.macros.recording().is_some()
+        {
             main_layout.push(Constraint::Length(1));
         }
 
@@ -47,49 +59,7 @@ impl App<'_> {
         let first_layout =
             layout.first().copied().unwrap_or_else(Rect::default);
 
-        let (current_connection, build_info, recent_hotkeys) = if matches!(
-            self.database_explorer.state,
-            DatabaseExplorerState::Connections
-        ) {
-            (
-                &Connection::default(),
-                Some(self.build_info.clone()),
-                Vec::new(),
-            )
-        } else {
-            (
-                &self.database_explorer.connection,
-                None,
-                self.database_explorer.recent_table_hotkeys(),
-            )
-        };
-        let table_data_ext: Vec<Hotkey> = if matches!(
-            self.database_explorer.state,
-            DatabaseExplorerState::TableData(_, _)
-        ) {
-            self.hotkeys
-                .iter()
-                .chain(TABLE_DATA_VIEW_HOTKEYS.iter())
-                .cloned()
-                .collect()
-        } else {
-            Vec::new()
-        };
-        let hotkey_bar: &[Hotkey] = if table_data_ext.is_empty() {
-            &self.hotkeys
-        } else {
-            &table_data_ext
-        };
-        frame.render_widget(
-            TopBarView {
-                current_connection,
-                recent_hotkeys: recent_hotkeys.as_slice(),
-                hotkeys: hotkey_bar,
-                app_name: APP_NAME,
-                build_info,
-            },
-            first_layout,
-        );
+        self.render_top_bar(frame, first_layout);
 
         // Create the main content area (layout[1] is the middle section)
         let layout_rect =
@@ -141,8 +111,15 @@ impl App<'_> {
                                 .count();
                             (vis, dr)
                         });
+                    let predicates =
+                        self.database_explorer.sticky_predicates();
+                    let sticky = if predicates.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" [{} · \\ clears]", predicates.join(" · "))
+                    };
                     format!(
-                        "{}{}",
+                        "{}{}{sticky}",
                         base.trim_end(),
                         meta.title_suffix(filtered, visible, local_draft_rows)
                     )
@@ -168,15 +145,111 @@ impl App<'_> {
         frame.render_widget(block, main_area);
         self.render_database_table(frame, inner_area);
 
-        // Render status line at the bottom
-        if !self.status_line.message().is_empty()
-            && let Some(status_layout) = layout.get(2)
-        {
-            frame.render_widget(self.status_line.clone(), *status_layout);
+        // Render status line at the bottom; a running task's progress
+        // takes its place
+        if let Some(status_layout) = layout.get(2) {
+            self.render_status_line(frame, *status_layout);
         }
 
         // Render modals using the modal manager
         self.render_modals(frame);
+
+        self.accessible.apply(frame.buffer_mut());
+        self.palette.apply(frame.buffer_mut());
+    }
+
+    /// Status message, behind a marker while a macro is being recorded
+    fn render_status_line(&self, frame: &mut Frame, area: Rect) {
+        if let Some(task) = &self.background_task {
+            let queued = self.statement_queue.describe();
+            frame.render_widget(ProgressIndicator { task, queued }, area);
+            return;
+        }
+        let Some(register) = self.macros.recording() else {
+            frame.render_widget(self.status_line.clone(), area);
+            return;
+        };
+        let [marker_area, message_area] =
+            Layout::horizontal([Constraint::Length(14), Constraint::Min(0)])
+                .areas(area);
+        frame.render_widget(
+            Span::styled(
+                format!("recording @{register}"),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            marker_area,
+        );
+        frame.render_widget(self.status_line.clone(), message_area);
+    }
+
+    /// What the keyboard acts on: the open modal, else what is browsed
+    fn hotkey_context(&self) -> HotkeyContext<'_> {
+        match self.modal_manager.active_modal_type() {
+            Some(kind) => HotkeyContext::Modal {
+                kind,
+                allow_delete: kind == ModalType::Choice
+                    && self
+                        .modal_manager
+                        .get_choice_modal()
+                        .is_some_and(ChoiceModal::allows_delete),
+            },
+            None => HotkeyContext::Explorer(&self.database_explorer.state),
+        }
+    }
+
+    /// Top bar with connection details, recent tables, hotkeys and logo, or
+    /// a single line of it in compact mode.
+    fn render_top_bar(&self, frame: &mut Frame, area: Rect) {
+        if self.layout.is_compact(frame.area().height) {
+            let connection = (!matches!(
+                self.database_explorer.state,
+                DatabaseExplorerState::Connections
+            ))
+            .then_some(&self.database_explorer.connection);
+            frame.render_widget(
+                CompactTopBar {
+                    app_name: PKG_NAME,
+                    connection,
+                    mode: self.database_explorer.state.mode_label(),
+                    transaction: connection
+                        .and(self.database_explorer.transaction_status),
+                },
+                area,
+            );
+            return;
+        }
+
+        let (current_connection, build_info, recent_hotkeys, transaction) =
+            if matches!(
+                self.database_explorer.state,
+                DatabaseExplorerState::Connections
+            ) {
+                (
+                    &Connection::default(),
+                    Some(self.build_info.clone()),
+                    Vec::new(),
+                    None,
+                )
+            } else {
+                (
+                    &self.database_explorer.connection,
+                    None,
+                    self.database_explorer.recent_table_hotkeys(),
+                    self.database_explorer.transaction_status,
+                )
+            };
+        let hotkeys = self.hotkey_context().hotkeys();
+        frame.render_widget(
+            TopBarView {
+                current_connection,
+                recent_hotkeys: recent_hotkeys.as_slice(),
+                hotkeys: &hotkeys,
+                app_name: APP_NAME,
+                build_info,
+                transaction,
+            },
+            area,
+        );
     }
 
     /// Render all active modals
@@ -209,6 +282,18 @@ impl App<'_> {
         if let Some(modal) = self.modal_manager.get_password_modal() {
             frame.render_widget(modal.clone(), area);
         }
+
+        if let Some(modal) = self.modal_manager.get_input_modal() {
+            frame.render_widget(modal.clone(), area);
+        }
+
+        if let Some(modal) = self.modal_manager.get_choice_modal() {
+            frame.render_widget(modal.clone(), area);
+        }
+
+        if let Some(modal) = self.modal_manager.get_info_modal() {
+            frame.render_widget(modal.clone(), area);
+        }
     }
 
     /// Render the appropriate database table based on explorer state
@@ -227,6 +312,8 @@ impl App<'_> {
                     frame,
                     explorer.databases.as_ref(),
                     area,
+                    self.number_format,
+                    self.time_display,
                 );
             }
             DatabaseExplorerState::Schemas => {
@@ -234,6 +321,8 @@ impl App<'_> {
                     frame,
                     explorer.schemas.as_ref(),
                     area,
+                    self.number_format,
+                    self.time_display,
                 );
             }
             DatabaseExplorerState::Tables(_) => {
@@ -241,6 +330,8 @@ impl App<'_> {
                     frame,
                     explorer.tables.as_ref(),
                     area,
+                    self.number_format,
+                    self.time_display,
                 );
             }
             DatabaseExplorerState::Columns(_, _) => {
@@ -248,16 +339,40 @@ impl App<'_> {
                     frame,
                     explorer.columns.as_ref(),
                     area,
+                    self.number_format,
+                    self.time_display,
                 );
             }
             DatabaseExplorerState::TableData(_, _) => {
+                let area = match explorer.table_data_sql.as_deref() {
+                    Some(sql) if self.show_fetch_sql => {
+                        let filter = explorer
+                            .table_data
+                            .as_ref()
+                            .and_then(|data| data.query.as_deref());
+                        render_fetch_sql(frame, sql, filter, area)
+                    }
+                    Some(_) | None => area,
+                };
                 render_filtered_data_table(
                     frame,
                     explorer.table_data.as_ref(),
                     area,
+                    self.number_format,
+                    self.time_display,
                 );
             }
             DatabaseExplorerState::SqlResults(_) => {
+                self.database_explorer
+                    .sql_executor
+                    .table_state
+                    .view
+                    .number_format = self.number_format;
+                self.database_explorer
+                    .sql_executor
+                    .table_state
+                    .view
+                    .time_display = self.time_display;
                 frame.render_stateful_widget(
                     SqlExecutor,
                     area,
@@ -272,12 +387,77 @@ fn render_filtered_data_table<T: TableData + Clone + std::fmt::Debug>(
     frame: &mut Frame,
     filtered_data: Option<&FilteredData<T>>,
     area: Rect,
+    number_format: NumberFormat,
+    time_display: TimeDisplay,
 ) {
     if let Some(filtered_data) = filtered_data {
+        let mut table = filtered_data.table.clone();
+        table.view.number_format = number_format;
+        table.view.time_display = time_display;
         frame.render_stateful_widget(
             DataTable::<T>::default(),
             area,
-            &mut filtered_data.table.clone(),
+            &mut table,
+        );
+    }
+}
+
+/// Most lines of the fetch query pane, borders included.
+const FETCH_SQL_PANE_HEIGHT: u16 = 8;
+
+/// Draw `sql` in a pane at the top of `area`, with the filter applied in
+/// d7s to the fetched rows as a comment; returns the area left below it.
+fn render_fetch_sql(
+    frame: &mut Frame,
+    sql: &str,
+    filter: Option<&str>,
+    area: Rect,
+) -> Rect {
+    let mut lines: Vec<Line> = sql.lines().map(Line::raw).collect();
+    if let Some(filter) = filter {
+        lines.push(
+            Line::raw(format!("-- filtered in d7s: {filter}"))
+                .style(Style::default().fg(Color::DarkGray)),
         );
     }
+    let height = u16::try_from(lines.len())
+        .unwrap_or(u16::MAX)
+        .saturating_add(2)
+        .min(FETCH_SQL_PANE_HEIGHT);
+    let [pane, rest] = Layout::vertical([
+        Constraint::Length(height),
+        Constraint::Min(0),
+    ])
+    .areas(area);
+    let paragraph = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(Block::bordered().title(" Query (E: to SQL editor) "));
+    frame.render_widget(paragraph, pane);
+    rest
+}
+
+/// In place of the screen on a terminal too small to draw it in: its size
+/// and the size needed.
+fn render_too_small(frame: &mut Frame) {
+    let area = frame.area();
+    let [_, middle, _] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(3),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+    let lines = vec![
+        Line::styled(
+            "Terminal too small",
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Line::raw(format!("{}x{}", area.width, area.height)),
+        Line::raw(format!("needs {MIN_TERMINAL_WIDTH}x{MIN_TERMINAL_HEIGHT}")),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        middle,
+    );
 }
@@ -0,0 +1,200 @@
+//! Result sets of the SQL executor kept for the rest of the session, so an
+//! earlier result can be reopened without running its query again.
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::DateTime;
+
+/// Result sets kept per connection; the oldest is dropped past this.
+pub const RESULT_HISTORY_LIMIT: usize = 20;
+
+/// Longest query text shown in the history list.
+const SUMMARY_SQL_WIDTH: usize = 60;
+
+/// One executed query and the rows loaded for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultSnapshot {
+    pub id: u64,
+    pub sql: String,
+    /// Database the query ran in; `None` for backends without a
+    /// Databases step (`SQLite`).
+    pub database: Option<String>,
+    /// UTC time of day the query ran.
+    pub executed_at: String,
+    pub elapsed: Duration,
+    pub column_names: Vec<String>,
+    /// Types of the columns, when the backend reported them.
+    pub column_types: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Whether the query has rows past the ones loaded.
+    pub has_more_rows: bool,
+    /// Whether secrets were masked out of `sql`, so it can't be run again.
+    pub masked: bool,
+}
+
+impl ResultSnapshot {
+    #[must_use]
+    pub fn new(
+        sql: &str,
+        database: Option<String>,
+        elapsed: Duration,
+        column_names: Vec<String>,
+        rows: Vec<Vec<String>>,
+    ) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let executed_at = i64::try_from(now)
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .map(|dt| dt.format("%H:%M:%S").to_string())
+            .unwrap_or_default();
+        Self {
+            id: 0,
+            sql: sql.to_string(),
+            database,
+            executed_at,
+            elapsed,
+            column_names,
+            column_types: Vec::new(),
+            rows,
+            has_more_rows: false,
+            masked: false,
+        }
+    }
+
+    /// One-line description for the history list: time, rows, duration and
+    /// the query collapsed onto one line.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let rows = format!(
+            "{}{} rows",
+            self.rows.len(),
+            if self.has_more_rows { "+" } else { "" }
+        );
+        let sql = self.sql.split_whitespace().collect::<Vec<_>>().join(" ");
+        let sql = if sql.chars().count() > SUMMARY_SQL_WIDTH {
+            let cut =
+                sql.chars().take(SUMMARY_SQL_WIDTH - 1).collect::<String>();
+            format!("{cut}…")
+        } else {
+            sql
+        };
+        format!(
+            "{}  {rows:>10}  {:>6} ms  {sql}",
+            self.executed_at,
+            self.elapsed.as_millis()
+        )
+    }
+}
+
+/// Result sets of the session, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct ResultHistory {
+    entries: VecDeque<ResultSnapshot>,
+    next_id: u64,
+    /// Entry shown in the SQL executor, which fetched rows are added to.
+    current: Option<u64>,
+}
+
+impl ResultHistory {
+    /// Keep `snapshot` as the newest entry and the one being shown.
+    pub fn push(&mut self, mut snapshot: ResultSnapshot) {
+        self.next_id += 1;
+        snapshot.id = self.next_id;
+        self.current = Some(snapshot.id);
+        self.entries.push_front(snapshot);
+        self.entries.truncate(RESULT_HISTORY_LIMIT);
+    }
+
+    /// Entries, newest first.
+    pub fn entries(&self) -> impl Iterator<Item = &ResultSnapshot> {
+        self.entries.iter()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Show the entry with `id` again.
+    pub fn open(&mut self, id: u64) -> Option<&ResultSnapshot> {
+        let snapshot = self.entries.iter().find(|s| s.id == id)?;
+        self.current = Some(id);
+        Some(snapshot)
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        self.entries.retain(|s| s.id != id);
+        if self.current == Some(id) {
+            self.current = None;
+        }
+    }
+
+    /// Rows fetched for the entry being shown.
+    pub fn append_to_current(&mut self, rows: &[Vec<String>], has_more: bool) {
+        let current = self.current;
+        if let Some(snapshot) =
+            self.entries.iter_mut().find(|s| Some(s.id) == current)
+        {
+            snapshot.rows.extend_from_slice(rows);
+            snapshot.has_more_rows = has_more;
+        }
+    }
+
+    /// The shown result stopped being one from the history.
+    pub const fn clear_current(&mut self) {
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(sql: &str, rows: usize) -> ResultSnapshot {
+        ResultSnapshot::new(
+            sql,
+            None,
+            Duration::from_millis(12),
+            vec!["id".to_string()],
+            (0..rows).map(|i| vec![i.to_string()]).collect(),
+        )
+    }
+
+    #[test]
+    fn keeps_newest_results_and_appends_to_the_shown_one() {
+        let mut history = ResultHistory::default();
+        for i in 0..=RESULT_HISTORY_LIMIT {
+            history.push(snapshot(&format!("SELECT {i}"), 1));
+        }
+        assert_eq!(history.entries().count(), RESULT_HISTORY_LIMIT);
+        assert_eq!(
+            history.entries().next().map(|s| s.sql.as_str()),
+            Some("SELECT 20")
+        );
+
+        let oldest = history.entries().last().map(|s| s.id).unwrap_or_default();
+        assert!(history.open(oldest).is_some());
+        history.append_to_current(&[vec!["x".to_string()]], true);
+        let reopened = history.open(oldest).unwrap();
+        assert_eq!(reopened.rows.len(), 2);
+        assert!(reopened.has_more_rows);
+
+        history.remove(oldest);
+        assert!(history.open(oldest).is_none());
+    }
+
+    #[test]
+    fn summarises_on_one_line() {
+        let mut result = snapshot("SELECT *\n  FROM users", 3);
+        result.has_more_rows = true;
+        let summary = result.summary();
+        assert!(summary.ends_with("3+ rows      12 ms  SELECT * FROM users"));
+        assert!(!summary.contains('\n'));
+    }
+}
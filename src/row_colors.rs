@@ -0,0 +1,267 @@
+//! Row coloring rules per table, e.g. `status = 'error' -> red`: rows of
+//! the table being browsed with a matching value are drawn in the rule's
+//! color. Rules are kept per connection and table; the first one a row
+//! matches wins.
+
+use std::{cmp::Ordering, str::FromStr};
+
+use ratatui::style::Color;
+
+use crate::{
+    app::App,
+    app_state::DatabaseExplorerState,
+    services::RowColorService,
+    sort::parse_number,
+    ui::widgets::modal::{InputModal, InputPurpose},
+};
+
+/// How a rule compares a row's value with its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl RuleOp {
+    /// Operators as typed, longest first so `<=` isn't read as `<`.
+    const ALL: [(&'static str, Self); 7] = [
+        ("!=", Self::Ne),
+        ("<>", Self::Ne),
+        ("<=", Self::Le),
+        (">=", Self::Ge),
+        ("=", Self::Eq),
+        ("<", Self::Lt),
+        (">", Self::Gt),
+    ];
+
+    const fn accepts(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Eq => ordering.is_eq(),
+            Self::Ne => ordering.is_ne(),
+            Self::Lt => ordering.is_lt(),
+            Self::Le => ordering.is_le(),
+            Self::Gt => ordering.is_gt(),
+            Self::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+/// `column op value -> color`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowColorRule {
+    pub column: String,
+    pub op: RuleOp,
+    pub value: String,
+    pub color: Color,
+}
+
+impl RowColorRule {
+    /// Whether a row whose value in the rule's column is `value` matches.
+    /// Numbers compare as numbers, anything else as text.
+    #[must_use]
+    pub fn matches(&self, value: &str) -> bool {
+        let ordering = match (parse_number(value), parse_number(&self.value)) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            _ => value.cmp(&self.value),
+        };
+        self.op.accepts(ordering)
+    }
+}
+
+impl FromStr for RowColorRule {
+    type Err = String;
+
+    /// A rule such as `status = 'error' -> red` or `total >= 1000 → #ff8800`.
+    /// The value may be quoted with `'`, doubling quotes inside it.
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let (condition, color) = rule
+            .rsplit_once("->")
+            .or_else(|| rule.rsplit_once('→'))
+            .ok_or_else(|| format!("'{}' has no '-> color'", rule.trim()))?;
+        let color = Color::from_str(color.trim())
+            .map_err(|_| format!("'{}' is not a color", color.trim()))?;
+        let (at, symbol, op) = condition
+            .char_indices()
+            .find_map(|(at, _)| {
+                let rest = condition.get(at..)?;
+                RuleOp::ALL
+                    .iter()
+                    .find(|(symbol, _)| rest.starts_with(symbol))
+                    .map(|&(symbol, op)| (at, symbol, op))
+            })
+            .ok_or_else(|| {
+                format!("'{}' has no =, !=, <, <=, > or >=", condition.trim())
+            })?;
+        let column = condition.get(..at).unwrap_or_default().trim();
+        let column = column.trim_matches('"').to_string();
+        if column.is_empty() {
+            return Err(format!("'{}' names no column", condition.trim()));
+        }
+        let value = condition
+            .get(at + symbol.len()..)
+            .unwrap_or_default()
+            .trim();
+        let value = value
+            .strip_prefix('\'')
+            .and_then(|v| v.strip_suffix('\''))
+            .map_or_else(|| value.to_string(), |v| v.replace("''", "'"));
+        Ok(Self {
+            column,
+            op,
+            value,
+            color,
+        })
+    }
+}
+
+/// The rules in `text`, separated by `;` outside quoted values.
+///
+/// # Errors
+///
+/// Fails with what is wrong with the first rule that can't be read.
+pub fn parse_rules(text: &str) -> Result<Vec<RowColorRule>, String> {
+    let mut rules = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (at, c) in text.char_indices().chain([(text.len(), ';')]) {
+        match c {
+            '\'' => quoted = !quoted,
+            ';' if !quoted => {
+                let rule = text.get(start..at).unwrap_or_default();
+                if !rule.trim().is_empty() {
+                    rules.push(rule.parse()?);
+                }
+                start = at + 1;
+            }
+            _ => {}
+        }
+    }
+    Ok(rules)
+}
+
+/// What clears a table's rules in the editor, as an empty prompt can't be
+/// submitted.
+const CLEAR_RULES: &str = "none";
+
+impl App<'_> {
+    /// Edit the row coloring rules of the table being browsed
+    pub(crate) fn open_row_color_editor(&mut self) {
+        let DatabaseExplorerState::TableData(schema_name, table_name) =
+            &self.database_explorer.state
+        else {
+            return;
+        };
+        let rules = RowColorService::get(
+            &self.database_explorer.connection.name,
+            schema_name,
+            table_name,
+        )
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+        let prompt = format!(
+            "Color rows of {schema_name}.{table_name}, e.g. status = 'error' \
+             -> red; separate rules with ; or enter {CLEAR_RULES}:"
+        );
+        self.modal_manager.open_input_modal(
+            InputModal::new(" Row colors ", prompt, InputPurpose::RowColors)
+                .with_value(&rules),
+        );
+    }
+
+    /// Save the rules typed into the editor for the table being browsed
+    /// and color its rows by them
+    pub(crate) fn set_row_color_rules(&mut self, text: &str) {
+        let text = if text.trim().eq_ignore_ascii_case(CLEAR_RULES) {
+            ""
+        } else {
+            text.trim()
+        };
+        let rules = match parse_rules(text) {
+            Ok(rules) => rules,
+            Err(e) => {
+                self.set_status(format!("Row colors not saved: {e}."));
+                return;
+            }
+        };
+        let explorer = &self.database_explorer;
+        let DatabaseExplorerState::TableData(schema_name, table_name) =
+            &explorer.state
+        else {
+            return;
+        };
+        if let Err(e) = RowColorService::save(
+            &explorer.connection.name,
+            schema_name,
+            table_name,
+            text,
+        ) {
+            self.set_status(format!("Failed to save row colors: {e}"));
+            return;
+        }
+        let count = rules.len();
+        if let Some(fd) = self.database_explorer.table_data.as_mut() {
+            fd.table.view.row_colors = rules;
+        }
+        self.set_status(match count {
+            0 => "Row colors cleared.".to_string(),
+            1 => "Rows colored by 1 rule.".to_string(),
+            n => format!("Rows colored by {n} rules."),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_rules() {
+        let rules =
+            parse_rules("status = 'error' -> red; total>=1000 → #ff8800")
+                .unwrap();
+        assert_eq!(
+            rules,
+            [
+                RowColorRule {
+                    column: "status".to_string(),
+                    op: RuleOp::Eq,
+                    value: "error".to_string(),
+                    color: Color::Red,
+                },
+                RowColorRule {
+                    column: "total".to_string(),
+                    op: RuleOp::Ge,
+                    value: "1000".to_string(),
+                    color: Color::Rgb(0xff, 0x88, 0x00),
+                },
+            ]
+        );
+        let quoted = parse_rules("note != 'it''s; fine' -> light green")
+            .unwrap();
+        assert_eq!(quoted.first().unwrap().value, "it's; fine");
+        assert!(parse_rules("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_rules_it_cannot_read() {
+        assert!(parse_rules("status = 'error'").is_err());
+        assert!(parse_rules("status = 'error' -> reddish").is_err());
+        assert!(parse_rules("status -> red").is_err());
+        assert!(parse_rules("= 'error' -> red").is_err());
+    }
+
+    #[test]
+    fn compares_numbers_as_numbers() {
+        let rule: RowColorRule = "total > 9 -> yellow".parse().unwrap();
+        assert!(rule.matches("10"));
+        assert!(!rule.matches("9.0"));
+        let rule: RowColorRule = "status <> 'ok' -> red".parse().unwrap();
+        assert!(rule.matches("error"));
+        assert!(!rule.matches("ok"));
+    }
+}
@@ -0,0 +1,249 @@
+//! Go to a row by primary key: read it with a `SELECT` for that key alone,
+//! then select it in the page shown, adding it above the page's rows when
+//! the page doesn't hold it.
+
+use std::sync::Arc;
+
+use crate::{
+    app::App,
+    app_state::DatabaseExplorerState,
+    db::{
+        TableDataPage,
+        keyset::{KeySeek, KeysetPage},
+    },
+    keyset_paging::key_values,
+    sort::parse_number,
+    ui::{
+        handlers::TableNavigationHandler,
+        widgets::{
+            modal::{InputModal, InputPurpose},
+            table::RawTableRow,
+        },
+    },
+};
+
+/// Whether a key value read back from the database is the one typed: the
+/// same text, ignoring case for UUIDs and the like, or the same number.
+fn same_key_value(read: &str, typed: &str) -> bool {
+    read == typed
+        || read.eq_ignore_ascii_case(typed)
+        || parse_number(read)
+            .zip(parse_number(typed))
+            .is_some_and(|(a, b)| a.total_cmp(&b).is_eq())
+}
+
+/// Indices of `key_columns` among `column_names`, matched ignoring case;
+/// `None` when one is missing.
+pub(crate) fn key_indices(
+    key_columns: &[String],
+    column_names: &[String],
+) -> Option<Vec<usize>> {
+    key_columns
+        .iter()
+        .map(|key| {
+            column_names
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(key))
+        })
+        .collect()
+}
+
+/// Position among `items` of the saved row with the key of `row`, the key
+/// columns being at `indices`.
+fn position_of(
+    items: &[RawTableRow],
+    indices: &[usize],
+    row: &[String],
+) -> Option<usize> {
+    items.iter().position(|item| {
+        !item.is_draft
+            && indices
+                .iter()
+                .all(|&idx| item.values.get(idx) == row.get(idx))
+    })
+}
+
+impl App<'_> {
+    /// Primary key columns of the table being browsed; `None`, with the
+    /// reason in the status bar, when it has none
+    pub(crate) async fn primary_key_columns(&mut self) -> Option<Vec<String>> {
+        let explorer = &self.database_explorer;
+        let DatabaseExplorerState::TableData(schema, table) = &explorer.state
+        else {
+            return None;
+        };
+        let database = explorer.database.clone()?;
+        let (schema, table) = (schema.clone(), table.clone());
+        match database.get_primary_key_columns(&schema, &table).await {
+            Ok(columns) if columns.is_empty() => {
+                self.set_status(format!(
+                    "{table} has no primary key to find rows by."
+                ));
+                None
+            }
+            Ok(columns) => Some(columns),
+            Err(e) => {
+                self.set_status(format!("Failed to read the primary key: {e}"));
+                None
+            }
+        }
+    }
+
+    /// Ask for the primary key value of a row to go to
+    pub(crate) async fn open_row_lookup_prompt(&mut self) {
+        let Some(key_columns) = self.primary_key_columns().await else {
+            return;
+        };
+        let DatabaseExplorerState::TableData(_, table) =
+            &self.database_explorer.state
+        else {
+            return;
+        };
+        let prompt = match key_columns.as_slice() {
+            [column] => format!("Go to the row of {table} with {column}:"),
+            columns => format!(
+                "Go to the row of {table} with ({}), values separated by \
+                 commas:",
+                columns.join(", ")
+            ),
+        };
+        self.modal_manager.open_input_modal(InputModal::new(
+            " Go to row ",
+            prompt,
+            InputPurpose::GoToRow { key_columns },
+        ));
+    }
+
+    /// Read the row whose key is typed into the prompt and select it
+    pub(crate) async fn go_to_row(
+        &mut self,
+        key_columns: &[String],
+        input: &str,
+    ) {
+        let Some(values) = key_values(input, key_columns.len()) else {
+            self.set_status(format!(
+                "The key has {} columns; give a value for each.",
+                key_columns.len()
+            ));
+            return;
+        };
+        let explorer = &self.database_explorer;
+        let DatabaseExplorerState::TableData(schema, table) = &explorer.state
+        else {
+            return;
+        };
+        let Some(database) = explorer.database.clone() else {
+            return;
+        };
+        let (schema, table) = (schema.clone(), table.clone());
+        let page = KeysetPage {
+            key_columns: key_columns.to_vec(),
+            seek: KeySeek::From(values.clone()),
+        };
+        let TableDataPage {
+            rows,
+            column_names,
+            row_ids,
+            ..
+        } = match database
+            .get_table_data_page(&schema, &table, None, Some(&page), 0, 1)
+            .await
+        {
+            Ok(page) => page,
+            Err(e) => {
+                self.set_status(format!("Failed to read the row: {e}"));
+                return;
+            }
+        };
+        let Some(indices) = key_indices(key_columns, &column_names) else {
+            return;
+        };
+        let found = rows.into_iter().zip(row_ids).next().filter(|(row, _)| {
+            indices.iter().zip(&values).all(|(&idx, typed)| {
+                row.get(idx).is_some_and(|read| same_key_value(read, typed))
+            })
+        });
+        let Some((row, db_row_id)) = found else {
+            self.set_status(format!("No row of {table} has key {input}."));
+            return;
+        };
+
+        let Some(fd) = self.database_explorer.table_data.as_mut() else {
+            return;
+        };
+        let mut cleared_filter = false;
+        if position_of(&fd.table.model.items, &indices, &row).is_none()
+            && fd.is_filtered()
+            && position_of(&fd.original, &indices, &row).is_some()
+        {
+            fd.clear_filter();
+            cleared_filter = true;
+        }
+        let status = if let Some(idx) =
+            position_of(&fd.table.model.items, &indices, &row)
+        {
+            fd.table.view.state.select(Some(idx));
+            if cleared_filter {
+                format!("Row {input} of {table}; filter cleared to show it.")
+            } else {
+                format!("Row {input} of {table}.")
+            }
+        } else {
+            let names = fd
+                .table
+                .model
+                .dynamic_column_names
+                .clone()
+                .unwrap_or_else(|| Arc::new(column_names));
+            let row = RawTableRow {
+                values: row,
+                column_names: names,
+                db_row_id,
+                is_draft: false,
+            };
+            fd.table.model.items.insert(0, row.clone());
+            fd.original.insert(0, row);
+            fd.table.multi_row_selection = fd
+                .table
+                .multi_row_selection
+                .iter()
+                .map(|&j| j + 1)
+                .collect();
+            fd.table.view.state.select(Some(0));
+            fd.table.recompute_column_widths();
+            format!("Row {input} of {table}, added above this page's rows.")
+        };
+        TableNavigationHandler::wrap_rows(
+            &mut fd.table.view.state,
+            &fd.table.model.items,
+        );
+        self.set_status(status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_typed_key() {
+        assert!(same_key_value("42", "42"));
+        assert!(same_key_value("42", "42.0"));
+        assert!(same_key_value(
+            "9b2e4c1a-0000-4000-8000-000000000001",
+            "9B2E4C1A-0000-4000-8000-000000000001"
+        ));
+        assert!(!same_key_value("43", "42"));
+        assert_eq!(key_values("3, EU", 2).unwrap(), ["3", "EU"]);
+        assert_eq!(key_values("a,b", 1).unwrap(), ["a,b"]);
+        assert_eq!(key_values("3", 2), None);
+    }
+
+    #[test]
+    fn finds_key_columns_by_name() {
+        let names = ["id", "Region", "total"].map(String::from);
+        let key = ["region", "id"].map(String::from);
+        assert_eq!(key_indices(&key, &names).unwrap(), [1, 0]);
+        assert_eq!(key_indices(&["missing".to_string()], &names), None);
+    }
+}
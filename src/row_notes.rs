@@ -0,0 +1,118 @@
+//! Notes on rows of the table being browsed, e.g. "refunded twice, see
+//! ticket 4411": kept in d7s' own database by connection, table and primary
+//! key, never written to the table, and marked in the grid.
+
+use crate::{
+    app::App,
+    app_state::DatabaseExplorerState,
+    db::row_note::RowNote,
+    row_lookup::key_indices,
+    services::RowNoteService,
+    ui::widgets::modal::{InputModal, InputPurpose},
+};
+
+/// What removes a row's note in the editor, as an empty prompt can't be
+/// submitted.
+const CLEAR_NOTE: &str = "none";
+
+impl App<'_> {
+    /// Edit the note on the selected row of the table being browsed
+    pub(crate) async fn open_row_note_editor(&mut self) {
+        let Some(key_columns) = self.primary_key_columns().await else {
+            return;
+        };
+        let Some(fd) = self.database_explorer.table_data.as_ref() else {
+            return;
+        };
+        let Some(row) = fd
+            .table
+            .view
+            .state
+            .selected()
+            .and_then(|idx| fd.table.model.items.get(idx))
+        else {
+            return;
+        };
+        if row.is_draft {
+            self.set_status("Save the new row before noting it.");
+            return;
+        }
+        let Some(key) =
+            key_indices(&key_columns, &row.column_names).and_then(|indices| {
+                indices
+                    .iter()
+                    .map(|&idx| row.values.get(idx).cloned())
+                    .collect::<Option<Vec<_>>>()
+            })
+        else {
+            self.set_status("The primary key isn't among the loaded columns.");
+            return;
+        };
+        let notes = &fd.table.view.row_notes;
+        let note = if notes.key_columns == key_columns {
+            notes.get(&key).unwrap_or_default().to_string()
+        } else {
+            String::new()
+        };
+        let prompt = format!(
+            "Note on the row with {} = {}, kept only in d7s; enter \
+             {CLEAR_NOTE} to remove it:",
+            key_columns.join(", "),
+            key.join(", ")
+        );
+        self.modal_manager.open_input_modal(
+            InputModal::new(
+                " Row note ",
+                prompt,
+                InputPurpose::RowNote { key_columns, key },
+            )
+            .with_value(&note),
+        );
+    }
+
+    /// Save the note typed into the editor on the row with `key` and mark
+    /// the row
+    pub(crate) fn set_row_note(
+        &mut self,
+        key_columns: Vec<String>,
+        key: Vec<String>,
+        text: &str,
+    ) {
+        let text = if text.trim().eq_ignore_ascii_case(CLEAR_NOTE) {
+            ""
+        } else {
+            text.trim()
+        };
+        let explorer = &self.database_explorer;
+        let DatabaseExplorerState::TableData(schema_name, table_name) =
+            &explorer.state
+        else {
+            return;
+        };
+        let note = RowNote {
+            key_columns,
+            key,
+            note: text.to_string(),
+        };
+        if let Err(e) = RowNoteService::save(
+            &explorer.connection.name,
+            schema_name,
+            table_name,
+            &note,
+        ) {
+            self.set_status(format!("Failed to save the note: {e}"));
+            return;
+        }
+        if let Some(fd) = self.database_explorer.table_data.as_mut() {
+            fd.table
+                .view
+                .row_notes
+                .set(&note.key_columns, note.key, text);
+        }
+        self.set_status(if text.is_empty() {
+            "Note removed."
+        } else {
+            "Note saved."
+        });
+    }
+}
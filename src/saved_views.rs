@@ -0,0 +1,145 @@
+//! Saved table-data views: table + filter + hidden columns + order, per
+//! connection.
+
+use color_eyre::Result;
+
+use crate::{
+    app::App,
+    app_state::{AppState, DatabaseExplorerState},
+    db::saved_view::SavedView,
+    services::ViewService,
+    ui::widgets::modal::{ChoiceAction, ChoiceModal, InputModal, InputPurpose},
+};
+
+impl App<'_> {
+    /// Ask for a name to save the table being browsed as a view
+    pub(crate) fn open_save_view_prompt(&mut self) {
+        let DatabaseExplorerState::TableData(schema_name, table_name) =
+            &self.database_explorer.state
+        else {
+            return;
+        };
+        let prompt = format!(
+            "Save {schema_name}.{table_name} with its filter, hidden columns and order as:"
+        );
+        self.modal_manager.open_input_modal(InputModal::new(
+            " Save view ",
+            prompt,
+            InputPurpose::SaveView,
+        ));
+    }
+
+    /// Save the current table, filter, hidden columns and order under `name`
+    pub(crate) fn save_current_view(&mut self, name: String) {
+        let explorer = &self.database_explorer;
+        let DatabaseExplorerState::TableData(schema_name, table_name) =
+            &explorer.state
+        else {
+            return;
+        };
+        let (filter, hidden_columns) = explorer
+            .table_data
+            .as_ref()
+            .map_or((None, Vec::new()), |fd| {
+                (fd.query.clone(), fd.table.hidden_column_names())
+            });
+        let view = SavedView {
+            name,
+            schema_name: schema_name.clone(),
+            table_name: table_name.clone(),
+            filter,
+            hidden_columns,
+            order: explorer.table_order.clone(),
+        };
+
+        match ViewService::save(&explorer.connection.name, &view) {
+            Ok(()) => self.set_status(format!("Saved view '{}'.", view.name)),
+            Err(e) => self.set_status(format!("Failed to save view: {e}")),
+        }
+    }
+
+    /// Show the saved views of the current connection
+    pub(crate) fn open_views_list(&mut self) {
+        if self.state != AppState::DatabaseConnected {
+            return;
+        }
+        match ViewService::get_all(&self.database_explorer.connection.name) {
+            Ok(views) if views.is_empty() => {
+                self.set_status(
+                    "No saved views. Press v while browsing a table to save one.",
+                );
+            }
+            Ok(views) => {
+                self.modal_manager
+                    .open_choice_modal(ChoiceModal::views(views));
+            }
+            Err(e) => self.set_status(format!("Failed to load views: {e}")),
+        }
+    }
+
+    /// Act on a view picked from the Views list
+    pub(crate) async fn handle_view_choice(
+        &mut self,
+        view: SavedView,
+        action: ChoiceAction,
+    ) -> Result<()> {
+        match action {
+            ChoiceAction::Select => self.open_saved_view(&view).await,
+            ChoiceAction::Delete => {
+                match ViewService::delete(
+                    &self.database_explorer.connection.name,
+                    &view.name,
+                ) {
+                    Ok(()) => {
+                        self.set_status(format!(
+                            "Deleted view '{}'.",
+                            view.name
+                        ));
+                    }
+                    Err(e) => {
+                        self.set_status(format!("Failed to delete view: {e}"));
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Load the view's table in its order, then hide its columns and apply
+    /// its filter.
+    async fn open_saved_view(&mut self, view: &SavedView) -> Result<()> {
+        self.open_table(&view.schema_name, &view.table_name).await?;
+        if self.database_explorer.state
+            != DatabaseExplorerState::TableData(
+                view.schema_name.clone(),
+                view.table_name.clone(),
+            )
+        {
+            return Ok(());
+        }
+
+        // Opening the table starts it unordered
+        if let Some(order) = &view.order {
+            self.database_explorer.table_order = Some(order.clone());
+            if let Err(e) = self.refetch_table_page(0).await {
+                self.database_explorer.table_order = None;
+                self.set_status(format!(
+                    "Failed to order view '{}' by {}: {e}",
+                    view.name, order.column
+                ));
+                return Ok(());
+            }
+        }
+
+        let Some(fd) = self.database_explorer.table_data.as_mut() else {
+            return Ok(());
+        };
+        fd.table.hide_columns_by_name(&view.hidden_columns);
+        match &view.filter {
+            Some(filter) => fd.apply_filter(filter),
+            None => fd.clear_filter(),
+        }
+        self.set_status(format!("Opened view '{}'.", view.name));
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+//! Savepoints in an open transaction: named points a data fix can be
+//! rolled back to without giving up the rest of the transaction.
+
+use crate::{
+    app::App,
+    db::{Database, TransactionStatus},
+    ui::widgets::modal::{ChoiceModal, InputModal, InputPurpose},
+};
+
+/// Set savepoint `name` on the session, adding it to `savepoints` once the
+/// server has it.
+async fn set_savepoint(
+    database: &dyn Database,
+    savepoints: &mut Vec<String>,
+    name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SAVEPOINT {}", database.dialect().quote_ident(name));
+    database.batch_execute_in_session(&sql).await?;
+    savepoints.push(name.to_string());
+    Ok(())
+}
+
+/// Roll the session back to savepoint `name`, dropping the savepoints set
+/// after it from `savepoints`; they are left as they are when it fails.
+async fn roll_back_to(
+    database: &dyn Database,
+    savepoints: &mut Vec<String>,
+    name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!(
+        "ROLLBACK TO SAVEPOINT {}",
+        database.dialect().quote_ident(name)
+    );
+    database.batch_execute_in_session(&sql).await?;
+    if let Some(idx) = savepoints.iter().position(|sp| sp == name) {
+        savepoints.truncate(idx + 1);
+    }
+    Ok(())
+}
+
+impl App<'_> {
+    /// Whether savepoints can be set or rolled back to now; says why not.
+    fn savepoints_available(&mut self) -> bool {
+        if !matches!(
+            self.database_explorer.transaction_status,
+            Some(TransactionStatus::InTransaction | TransactionStatus::Failed)
+        ) {
+            self.set_status("Savepoints need an open transaction; BEGIN one.");
+            return false;
+        }
+        if self.connection_busy() {
+            self.set_status("Wait for the .sql file to finish.");
+            return false;
+        }
+        true
+    }
+
+    /// Ask for the name of a savepoint to set
+    pub(crate) fn open_savepoint_prompt(&mut self) {
+        if !self.savepoints_available() {
+            return;
+        }
+        let name = format!("sp{}", self.database_explorer.savepoints.len() + 1);
+        self.modal_manager.open_input_modal(
+            InputModal::new(
+                " Savepoint ",
+                "Name of the savepoint to set:",
+                InputPurpose::SavepointName,
+            )
+            .with_value(&name),
+        );
+    }
+
+    /// Set a savepoint called `name` in the open transaction
+    pub(crate) async fn create_savepoint(&mut self, name: &str) {
+        let name = name.trim();
+        if self.database_explorer.savepoints.iter().any(|sp| sp == name) {
+            self.set_status(format!("There is already a savepoint {name}."));
+            return;
+        }
+        let Some(database) = self.database_explorer.database.clone() else {
+            return;
+        };
+        let savepoints = &mut self.database_explorer.savepoints;
+        let result = set_savepoint(database.as_ref(), savepoints, name).await;
+        self.refresh_transaction_status().await;
+        match result {
+            Ok(()) => self.set_status(format!("Savepoint {name} set.")),
+            Err(e) => self.set_status(format!("Failed to set savepoint: {e}")),
+        }
+    }
+
+    /// List the transaction's savepoints to roll back to one
+    pub(crate) fn open_savepoint_list(&mut self) {
+        if !self.savepoints_available() {
+            return;
+        }
+        let savepoints = self.database_explorer.savepoints.clone();
+        if savepoints.is_empty() {
+            self.set_status("No savepoints yet; ( sets one.");
+            return;
+        }
+        self.modal_manager
+            .open_choice_modal(ChoiceModal::savepoints(savepoints));
+    }
+
+    /// Undo everything since savepoint `name`, keeping it and dropping the
+    /// savepoints set after it
+    pub(crate) async fn rollback_to_savepoint(&mut self, name: &str) {
+        let Some(database) = self.database_explorer.database.clone() else {
+            return;
+        };
+        let savepoints = &mut self.database_explorer.savepoints;
+        let result = roll_back_to(database.as_ref(), savepoints, name).await;
+        self.refresh_transaction_status().await;
+        match result {
+            Ok(()) => {
+                self.set_status(format!("Rolled back to savepoint {name}."));
+            }
+            Err(e) => self.set_status(format!(
+                "Failed to roll back to savepoint {name}: {e}"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::sqlite::Sqlite;
+
+    #[tokio::test]
+    async fn keeps_the_savepoints_when_a_rollback_fails() {
+        let db = Sqlite::new("test".to_string(), ":memory:".to_string());
+        db.execute_in_session("BEGIN").await.unwrap();
+        let mut savepoints = Vec::new();
+        for name in ["sp1", "sp2", "sp3"] {
+            set_savepoint(&db, &mut savepoints, name).await.unwrap();
+        }
+        roll_back_to(&db, &mut savepoints, "sp2").await.unwrap();
+        assert_eq!(savepoints, ["sp1", "sp2"]);
+
+        // The transaction ended behind the stack's back, so the server has
+        // no savepoints left to roll back to
+        db.execute_in_session("COMMIT").await.unwrap();
+        assert!(roll_back_to(&db, &mut savepoints, "sp1").await.is_err());
+        assert_eq!(savepoints, ["sp1", "sp2"]);
+    }
+}
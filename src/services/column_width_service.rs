@@ -0,0 +1,36 @@
+use color_eyre::{Result, eyre::eyre};
+
+use crate::db::sqlite::{get_column_widths, save_column_width};
+
+/// Service for column widths set by hand, stored per connection and table
+pub struct ColumnWidthService;
+
+impl ColumnWidthService {
+    /// Get the saved widths of a table's columns by column name
+    pub fn get_all(
+        connection_name: &str,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<Vec<(String, usize)>> {
+        get_column_widths(connection_name, schema_name, table_name)
+    }
+
+    /// Save the width of one column
+    pub fn save(
+        connection_name: &str,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+        width: usize,
+    ) -> Result<()> {
+        save_column_width(
+            connection_name,
+            schema_name,
+            table_name,
+            column_name,
+            width,
+        )
+        .map_err(|e| eyre!("{}", e))?;
+        Ok(())
+    }
+}
@@ -1,12 +1,67 @@
+use std::time::Duration;
+
 use color_eyre::{Result, eyre::eyre};
+use tokio::task::JoinSet;
 
 use crate::db::{
-    connection::{Connection, ConnectionType},
+    connection::{Connection, ConnectionType, check_target_session_attrs},
     sqlite::{
-        delete_connection, get_connections, save_connection, update_connection,
+        delete_connection, get_connections, record_connection_use,
+        save_connection, update_connection,
     },
 };
 
+/// How long a single connection attempt may take during a batch test.
+const BATCH_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of testing one saved connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOutcome {
+    Passed,
+    Failed(String),
+    /// Not attempted, e.g. the password is only asked for interactively.
+    Skipped(String),
+}
+
+impl TestOutcome {
+    #[must_use]
+    pub const fn label(&self) -> &'static str {
+        match self {
+            Self::Passed => "PASS",
+            Self::Failed(_) => "FAIL",
+            Self::Skipped(_) => "SKIP",
+        }
+    }
+
+    /// Error or skip reason, if any.
+    #[must_use]
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Self::Passed => None,
+            Self::Failed(reason) | Self::Skipped(reason) => Some(reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionTestReport {
+    pub name: String,
+    pub outcome: TestOutcome,
+}
+
+impl ConnectionTestReport {
+    /// One line per connection, e.g. `FAIL  prod  connection refused`.
+    #[must_use]
+    pub fn summary_line(&self) -> String {
+        self.outcome.reason().map_or_else(
+            || format!("{}  {}", self.outcome.label(), self.name),
+            |reason| {
+                format!("{}  {}  {reason}", self.outcome.label(), self.name)
+            },
+        )
+    }
+}
+
 /// Service for managing database connections (CRUD operations)
 pub struct ConnectionService;
 
@@ -29,6 +84,12 @@ impl ConnectionService {
         Ok(())
     }
 
+    /// Record that a connection was connected to, for sorting by last use
+    pub fn record_use(name: &str) -> Result<()> {
+        record_connection_use(name).map_err(|e| eyre!("{}", e))?;
+        Ok(())
+    }
+
     /// Delete a connection by name
     pub fn delete(name: &str) -> Result<()> {
         delete_connection(name).map_err(|e| eyre!("{}", e))?;
@@ -43,6 +104,9 @@ impl ConnectionService {
         if connection.url.trim().is_empty() {
             return Err("Connection url is required".to_string());
         }
+        if connection.r#type == ConnectionType::Postgres {
+            check_target_session_attrs(&connection.url)?;
+        }
         Ok(())
     }
 
@@ -53,4 +117,119 @@ impl ConnectionService {
             ConnectionType::Sqlite => connection.to_sqlite().test().await,
         }
     }
+
+    /// Test one connection the way [`Self::test_all`] does, with its
+    /// timeout and error reason.
+    pub async fn test_one(connection: Connection) -> ConnectionTestReport {
+        let name = connection.name.clone();
+        Self::test_all(vec![connection])
+            .await
+            .pop()
+            .unwrap_or(ConnectionTestReport {
+                name,
+                outcome: TestOutcome::Skipped(String::new()),
+            })
+    }
+
+    /// Test every connection concurrently. Reports come back in the order
+    /// the connections were given.
+    ///
+    /// Connections carry the password to use (see
+    /// `PasswordService::with_password`); "ask every time" connections
+    /// without one are skipped and others without one connect without a
+    /// password.
+    pub async fn test_all(
+        connections: Vec<Connection>,
+    ) -> Vec<ConnectionTestReport> {
+        let mut reports = connections
+            .iter()
+            .map(|connection| ConnectionTestReport {
+                name: connection.name.clone(),
+                outcome: TestOutcome::Skipped(String::new()),
+            })
+            .collect::<Vec<_>>();
+
+        let mut tasks = JoinSet::new();
+        for (index, mut connection) in connections.into_iter().enumerate() {
+            if connection.r#type == ConnectionType::Postgres
+                && connection.password.is_none()
+            {
+                if connection.should_ask_every_time() {
+                    if let Some(report) = reports.get_mut(index) {
+                        report.outcome = TestOutcome::Skipped(
+                            "password is asked every time".to_string(),
+                        );
+                    }
+                    continue;
+                }
+                connection.password = Some(String::new());
+            }
+
+            tasks.spawn(async move {
+                let database = match connection.r#type {
+                    ConnectionType::Postgres => connection.to_postgres(),
+                    ConnectionType::Sqlite => connection.to_sqlite(),
+                };
+                let outcome = match tokio::time::timeout(
+                    BATCH_TEST_TIMEOUT,
+                    database.check_connection(),
+                )
+                .await
+                {
+                    Ok(Ok(())) => TestOutcome::Passed,
+                    Ok(Err(e)) => TestOutcome::Failed(e.message),
+                    Err(_) => TestOutcome::Failed(format!(
+                        "timed out after {}s",
+                        BATCH_TEST_TIMEOUT.as_secs()
+                    )),
+                };
+                (index, outcome)
+            });
+        }
+
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((index, outcome)) = joined
+                && let Some(report) = reports.get_mut(index)
+            {
+                report.outcome = outcome;
+            }
+        }
+
+        reports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sqlite_connection(name: &str, url: &str) -> Connection {
+        Connection {
+            name: name.to_string(),
+            url: url.to_string(),
+            r#type: ConnectionType::Sqlite,
+            ..Connection::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_all_keeps_order_and_reports_failures() {
+        let connections = vec![
+            sqlite_connection("memory", ":memory:"),
+            sqlite_connection("missing", "/nonexistent-dir/d7s/test.db"),
+        ];
+
+        let reports = ConnectionService::test_all(connections).await;
+
+        let names = reports.iter().map(|r| r.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["memory", "missing"]);
+        assert_eq!(
+            reports.first().map(|r| &r.outcome),
+            Some(&TestOutcome::Passed)
+        );
+        assert!(matches!(
+            reports.get(1).map(|r| &r.outcome),
+            Some(TestOutcome::Failed(_))
+        ));
+    }
 }
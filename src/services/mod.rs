@@ -1,5 +1,19 @@
+pub mod column_width_service;
 pub mod connection_service;
 pub mod password_service;
+pub mod recent_table_service;
+pub mod row_color_service;
+pub mod row_note_service;
+pub mod scratch_buffer_service;
+pub mod settings_service;
+pub mod view_service;
 
+pub use column_width_service::ColumnWidthService;
 pub use connection_service::ConnectionService;
 pub use password_service::PasswordService;
+pub use recent_table_service::RecentTableService;
+pub use row_color_service::RowColorService;
+pub use row_note_service::RowNoteService;
+pub use scratch_buffer_service::ScratchBufferService;
+pub use settings_service::SettingsService;
+pub use view_service::ViewService;
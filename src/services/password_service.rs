@@ -3,14 +3,14 @@ use std::collections::HashMap;
 use color_eyre::Result;
 
 use crate::{
-    auth::Keyring,
+    auth::{self, Keyring},
     db::connection::{Connection, ConnectionType},
 };
 
 /// Service for managing passwords across keyring and session storage
 pub struct PasswordService {
-    /// Session password storage (in-memory only, cleared when app exits)
-    /// Key format: "{user}@{host}:{port}/{database}"
+    /// Session password storage (in-memory only, cleared when app exits),
+    /// keyed by connection name
     session_passwords: HashMap<String, String>,
 }
 
@@ -28,7 +28,8 @@ impl PasswordService {
         }
     }
 
-    /// Generate a unique key for a connection to use in session password storage
+    /// Key of a connection in session password storage. Names are unique
+    /// among saved connections.
     fn connection_key(connection: &Connection) -> String {
         connection.name.clone()
     }
@@ -41,14 +42,14 @@ impl PasswordService {
         Ok(keyring.get_password()?)
     }
 
-    /// Save password to keyring for a connection
+    /// Save password to keyring for a connection. Keeps the keyring error so
+    /// callers can tell a locked store apart from other failures.
     pub fn save_to_keyring(
         connection_name: &str,
         password: &str,
-    ) -> Result<()> {
+    ) -> Result<(), auth::Error> {
         let keyring = Keyring::new(connection_name)?;
-        keyring.set_password(password)?;
-        Ok(())
+        keyring.set_password(password)
     }
 
     /// Delete password from keyring for a connection
@@ -89,9 +90,30 @@ impl PasswordService {
         }
     }
 
+    /// Keep a session password with a connection that was saved again,
+    /// possibly under a new name. A password entered in the form replaces
+    /// the kept one; switching to the keyring drops it.
+    pub fn carry_over_session_password(
+        &mut self,
+        original_name: Option<&str>,
+        connection: &Connection,
+    ) {
+        let previous = original_name
+            .and_then(|name| self.session_passwords.remove(name));
+        let password = connection
+            .password
+            .clone()
+            .filter(|password| !password.is_empty())
+            .or(previous);
+        if let Some(password) = password {
+            self.store_session_password(connection, password);
+        }
+    }
+
     // High-level API
 
-    /// Get password for a connection from the appropriate source
+    /// Get password for a connection from the appropriate source. Connect,
+    /// edit and test flows all read passwords through here.
     /// Returns Some(password) if found in session or keyring, None if needs prompting.
     /// `SQLite` connections have no password; returns None so caller connects without password.
     pub fn get_password(&self, connection: &Connection) -> Option<String> {
@@ -105,16 +127,43 @@ impl PasswordService {
         }
     }
 
-    /// Get password for connection, returning empty string if "ask every time" and not in session.
-    /// `SQLite` connections have no password; returns empty string.
-    pub fn get_connection_password(connection: &Connection) -> String {
-        if connection.r#type == ConnectionType::Sqlite {
-            return String::new();
+    /// `connection` with the password from [`Self::get_password`] filled in,
+    /// unless it already carries one.
+    #[must_use]
+    pub fn with_password(&self, mut connection: Connection) -> Connection {
+        if connection.password.is_none() {
+            connection.password = self.get_password(&connection);
         }
-        if connection.should_ask_every_time() {
-            String::new()
-        } else {
-            Self::get_from_keyring(&connection.name).unwrap_or_default()
+        connection
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ask_every_time(name: &str) -> Connection {
+        Connection {
+            name: name.to_string(),
+            password_storage: Some("dont_save".to_string()),
+            ..Connection::default()
         }
     }
+
+    #[test]
+    fn session_passwords_follow_renamed_connections() {
+        let mut service = PasswordService::new();
+        service.store_session_password(&ask_every_time("old"), "pw".into());
+
+        let renamed = ask_every_time("new");
+        service.carry_over_session_password(Some("old"), &renamed);
+        assert_eq!(service.get_password(&renamed), Some("pw".to_string()));
+        assert_eq!(service.get_password(&ask_every_time("old")), None);
+
+        let mut edited = ask_every_time("new");
+        edited.password = Some("typed".to_string());
+        service.carry_over_session_password(Some("new"), &edited);
+        let edited = service.with_password(ask_every_time("new"));
+        assert_eq!(edited.password.as_deref(), Some("typed"));
+    }
 }
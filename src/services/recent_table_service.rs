@@ -0,0 +1,26 @@
+use color_eyre::{Result, eyre::eyre};
+
+use crate::db::{
+    recent_table::RecentTable,
+    sqlite::{get_recent_tables, record_recent_table},
+};
+
+/// Tables remembered per connection in the Recent list.
+pub const RECENT_TABLES_LIMIT: usize = 20;
+
+/// Service for the recently opened tables of each connection
+pub struct RecentTableService;
+
+impl RecentTableService {
+    /// Get the recent tables of a connection, newest first
+    pub fn get_all(connection_name: &str) -> Result<Vec<RecentTable>> {
+        get_recent_tables(connection_name)
+    }
+
+    /// Record that a table was opened
+    pub fn record(connection_name: &str, recent: &RecentTable) -> Result<()> {
+        record_recent_table(connection_name, recent, RECENT_TABLES_LIMIT)
+            .map_err(|e| eyre!("{}", e))?;
+        Ok(())
+    }
+}
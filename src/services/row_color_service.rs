@@ -0,0 +1,29 @@
+use color_eyre::{Result, eyre::eyre};
+
+use crate::db::sqlite::{get_row_color_rules, save_row_color_rules};
+
+/// Service for row coloring rules, stored per connection and table
+pub struct RowColorService;
+
+impl RowColorService {
+    /// Get a table's rules as typed, e.g. `status = 'error' -> red`
+    pub fn get(
+        connection_name: &str,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<Option<String>> {
+        get_row_color_rules(connection_name, schema_name, table_name)
+    }
+
+    /// Save a table's rules, or forget them when empty
+    pub fn save(
+        connection_name: &str,
+        schema_name: &str,
+        table_name: &str,
+        rules: &str,
+    ) -> Result<()> {
+        save_row_color_rules(connection_name, schema_name, table_name, rules)
+            .map_err(|e| eyre!("{}", e))?;
+        Ok(())
+    }
+}
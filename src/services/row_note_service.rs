@@ -0,0 +1,33 @@
+use color_eyre::{Result, eyre::eyre};
+
+use crate::db::{
+    row_note::{RowNote, RowNotes},
+    sqlite::{get_row_notes, save_row_note},
+};
+
+/// Service for notes on table rows, stored per connection and table
+pub struct RowNoteService;
+
+impl RowNoteService {
+    /// Get the notes on a table's rows
+    pub fn get_all(
+        connection_name: &str,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<RowNotes> {
+        get_row_notes(connection_name, schema_name, table_name)
+            .map(RowNotes::new)
+    }
+
+    /// Save a note on a row, or forget it when empty
+    pub fn save(
+        connection_name: &str,
+        schema_name: &str,
+        table_name: &str,
+        note: &RowNote,
+    ) -> Result<()> {
+        save_row_note(connection_name, schema_name, table_name, note)
+            .map_err(|e| eyre!("{}", e))?;
+        Ok(())
+    }
+}
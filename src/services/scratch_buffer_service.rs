@@ -0,0 +1,27 @@
+use color_eyre::{Result, eyre::eyre};
+
+use crate::db::sqlite::{
+    get_all_scratch_buffers, get_scratch_buffer, save_scratch_buffer,
+};
+
+/// Service for the SQL executor buffer kept per connection between sessions
+pub struct ScratchBufferService;
+
+impl ScratchBufferService {
+    /// Get the buffer saved for a connection
+    pub fn get(connection_name: &str) -> Result<Option<String>> {
+        get_scratch_buffer(connection_name)
+    }
+
+    /// Get every saved buffer with its connection name
+    pub fn get_all() -> Result<Vec<(String, String)>> {
+        get_all_scratch_buffers()
+    }
+
+    /// Save a connection's buffer, or forget it when empty
+    pub fn save(connection_name: &str, sql: &str) -> Result<()> {
+        save_scratch_buffer(connection_name, sql)
+            .map_err(|e| eyre!("{}", e))?;
+        Ok(())
+    }
+}
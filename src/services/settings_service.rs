@@ -0,0 +1,19 @@
+use color_eyre::{Result, eyre::eyre};
+
+use crate::db::sqlite::{get_setting, save_setting};
+
+/// Service for app-wide settings stored as key/value pairs
+pub struct SettingsService;
+
+impl SettingsService {
+    /// Get a setting's saved value
+    pub fn get(key: &str) -> Result<Option<String>> {
+        get_setting(key)
+    }
+
+    /// Save a setting's value
+    pub fn save(key: &str, value: &str) -> Result<()> {
+        save_setting(key, value).map_err(|e| eyre!("{}", e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use color_eyre::{Result, eyre::eyre};
+
+use crate::db::{
+    saved_view::SavedView,
+    sqlite::{delete_view, get_views, save_view},
+};
+
+/// Service for saved table-data views, stored per connection
+pub struct ViewService;
+
+impl ViewService {
+    /// Get the saved views of a connection
+    pub fn get_all(connection_name: &str) -> Result<Vec<SavedView>> {
+        get_views(connection_name)
+    }
+
+    /// Save a view, replacing one with the same name
+    pub fn save(connection_name: &str, view: &SavedView) -> Result<()> {
+        if view.name.trim().is_empty() {
+            return Err(eyre!("View name is required"));
+        }
+        save_view(connection_name, view).map_err(|e| eyre!("{}", e))?;
+        Ok(())
+    }
+
+    /// Delete a view by name
+    pub fn delete(connection_name: &str, view_name: &str) -> Result<()> {
+        delete_view(connection_name, view_name).map_err(|e| eyre!("{}", e))?;
+        Ok(())
+    }
+}
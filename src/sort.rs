@@ -0,0 +1,313 @@
+//! Ordering of loaded rows by a column, comparing values by the type the
+//! column's values parse as rather than as plain text.
+
+use std::cmp::Ordering;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+
+/// How NULL cells are shown by the drivers.
+const NULL: &str = "NULL";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+impl SortDirection {
+    /// Arrow shown next to the sorted column's header.
+    #[must_use]
+    pub const fn arrow(self) -> &'static str {
+        match self {
+            Self::Ascending => "▲",
+            Self::Descending => "▼",
+        }
+    }
+}
+
+/// The column rows are ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnSort {
+    pub column: usize,
+    pub direction: SortDirection,
+}
+
+impl ColumnSort {
+    /// Next step of the `s` cycle on `column`: ascending, descending, then
+    /// back to the original order.
+    #[must_use]
+    pub const fn cycle(current: Option<Self>, column: usize) -> Option<Self> {
+        let direction = match current {
+            Some(sort) if sort.column == column => match sort.direction {
+                SortDirection::Ascending => SortDirection::Descending,
+                SortDirection::Descending => return None,
+            },
+            Some(_) | None => SortDirection::Ascending,
+        };
+        Some(Self { column, direction })
+    }
+}
+
+/// Where NULLs go in a server-side `ORDER BY`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NullsOrder {
+    First,
+    #[default]
+    Last,
+}
+
+impl NullsOrder {
+    #[must_use]
+    pub const fn toggled(self) -> Self {
+        match self {
+            Self::First => Self::Last,
+            Self::Last => Self::First,
+        }
+    }
+
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::First => "NULLs first",
+            Self::Last => "NULLs last",
+        }
+    }
+}
+
+/// Server-side order of a table's rows, kept while paging and refreshing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableOrder {
+    pub column: String,
+    pub direction: SortDirection,
+    pub nulls: NullsOrder,
+}
+
+impl TableOrder {
+    /// Next step of the `o` cycle on `column`: ascending, descending, then
+    /// unordered. The NULL placement carries over.
+    #[must_use]
+    pub fn cycle(current: Option<&Self>, column: &str) -> Option<Self> {
+        let nulls = current.map(|order| order.nulls).unwrap_or_default();
+        let direction = match current {
+            Some(order) if order.column == column => match order.direction {
+                SortDirection::Ascending => SortDirection::Descending,
+                SortDirection::Descending => return None,
+            },
+            Some(_) | None => SortDirection::Ascending,
+        };
+        Some(Self {
+            column: column.to_string(),
+            direction,
+            nulls,
+        })
+    }
+
+    /// One-line description for the status line.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {}, {}",
+            self.column,
+            self.direction.arrow(),
+            self.nulls.label()
+        )
+    }
+}
+
+/// What a column's non-NULL values all parse as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Numeric,
+    Temporal,
+    Text,
+}
+
+impl ColumnKind {
+    /// Infer the kind from a column's values; NULLs are ignored.
+    pub fn infer<'a>(values: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut numeric = true;
+        let mut temporal = true;
+        let mut any = false;
+        for value in values.into_iter().filter(|v| *v != NULL) {
+            any = true;
+            numeric &= parse_number(value).is_some();
+            temporal &= parse_temporal(value).is_some();
+            if !numeric && !temporal {
+                return Self::Text;
+            }
+        }
+        if !any {
+            Self::Text
+        } else if numeric {
+            Self::Numeric
+        } else if temporal {
+            Self::Temporal
+        } else {
+            Self::Text
+        }
+    }
+
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Numeric => "numeric",
+            Self::Temporal => "date/time",
+            Self::Text => "text",
+        }
+    }
+
+    /// Compare two cells of a column of this kind. NULLs sort after every
+    /// value; values that fail to parse fall back to text order.
+    #[must_use]
+    pub fn compare(self, a: &str, b: &str) -> Ordering {
+        match (a == NULL, b == NULL) {
+            (true, true) => return Ordering::Equal,
+            (true, false) => return Ordering::Greater,
+            (false, true) => return Ordering::Less,
+            (false, false) => {}
+        }
+        let typed = match self {
+            Self::Numeric => parse_number(a)
+                .zip(parse_number(b))
+                .map(|(a, b)| a.total_cmp(&b)),
+            Self::Temporal => parse_temporal(a)
+                .zip(parse_temporal(b))
+                .map(|(a, b)| a.cmp(&b)),
+            Self::Text => None,
+        };
+        typed.unwrap_or_else(|| a.cmp(b))
+    }
+}
+
+/// Row order for `sort` over `rows`; stable, so ties keep their loaded
+/// order. NULLs stay last in both directions.
+pub fn sorted_order<'a>(
+    rows: &'a [Vec<String>],
+    sort: ColumnSort,
+) -> (Vec<usize>, ColumnKind) {
+    let cell = |row: &'a Vec<String>| {
+        row.get(sort.column).map_or(NULL, String::as_str)
+    };
+    let kind = ColumnKind::infer(rows.iter().map(cell));
+    let mut order = (0..rows.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (
+            rows.get(a).map_or(NULL, cell),
+            rows.get(b).map_or(NULL, cell),
+        );
+        let ordering = kind.compare(a, b);
+        match sort.direction {
+            SortDirection::Ascending => ordering,
+            // Reverse values only; NULLs stay at the bottom
+            SortDirection::Descending if a == NULL || b == NULL => ordering,
+            SortDirection::Descending => ordering.reverse(),
+        }
+    });
+    (order, kind)
+}
+
+/// A cell as a number, if it is one.
+pub fn parse_number(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok().filter(|n| !n.is_nan())
+}
+
+/// Dates, times and timestamps as the drivers print them; times of day
+/// count from the epoch date so they compare among themselves.
+pub fn parse_temporal(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.naive_utc());
+    }
+    if let Ok(dt) = DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%#z") {
+        return Some(dt.naive_utc());
+    }
+    for format in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(dt);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(date.and_time(NaiveTime::MIN));
+    }
+    NaiveTime::parse_from_str(value, "%H:%M:%S%.f")
+        .ok()
+        .map(|time| NaiveDate::default().and_time(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(values: &[&str]) -> Vec<Vec<String>> {
+        values.iter().map(|v| vec![(*v).to_string()]).collect()
+    }
+
+    fn sorted(values: &[&str], direction: SortDirection) -> Vec<String> {
+        let rows = rows(values);
+        let (order, _) = sorted_order(
+            &rows,
+            ColumnSort {
+                column: 0,
+                direction,
+            },
+        );
+        order
+            .into_iter()
+            .filter_map(|i| rows.get(i).and_then(|r| r.first()).cloned())
+            .collect()
+    }
+
+    #[test]
+    fn sorts_numbers_numerically_with_nulls_last() {
+        assert_eq!(
+            sorted(&["10", "NULL", "9", "-1.5"], SortDirection::Ascending),
+            ["-1.5", "9", "10", "NULL"]
+        );
+        assert_eq!(
+            sorted(&["10", "NULL", "9"], SortDirection::Descending),
+            ["10", "9", "NULL"]
+        );
+    }
+
+    #[test]
+    fn sorts_dates_chronologically() {
+        let values = ["2024-02-01", "2023-12-31 23:59:00", "2024-01-15"];
+        assert_eq!(ColumnKind::infer(values), ColumnKind::Temporal);
+        assert_eq!(
+            sorted(&values, SortDirection::Ascending),
+            ["2023-12-31 23:59:00", "2024-01-15", "2024-02-01"]
+        );
+    }
+
+    #[test]
+    fn mixed_columns_sort_as_text() {
+        assert_eq!(ColumnKind::infer(["1", "b"]), ColumnKind::Text);
+        assert_eq!(ColumnKind::infer(["NULL"]), ColumnKind::Text);
+    }
+
+    #[test]
+    fn table_order_cycle_keeps_null_placement() {
+        let asc = TableOrder::cycle(None, "id").map(|order| TableOrder {
+            nulls: NullsOrder::First,
+            ..order
+        });
+        let desc = TableOrder::cycle(asc.as_ref(), "id");
+        assert_eq!(
+            desc.as_ref().map(|o| (o.direction, o.nulls)),
+            Some((SortDirection::Descending, NullsOrder::First))
+        );
+        assert_eq!(TableOrder::cycle(desc.as_ref(), "id"), None);
+        let other = TableOrder::cycle(desc.as_ref(), "name");
+        assert_eq!(other.map(|o| o.direction), Some(SortDirection::Ascending));
+    }
+
+    #[test]
+    fn cycles_through_directions() {
+        let asc = ColumnSort::cycle(None, 2);
+        assert_eq!(asc.map(|s| s.direction), Some(SortDirection::Ascending));
+        let desc = ColumnSort::cycle(asc, 2);
+        assert_eq!(desc.map(|s| s.direction), Some(SortDirection::Descending));
+        assert_eq!(ColumnSort::cycle(desc, 2), None);
+        assert_eq!(ColumnSort::cycle(desc, 1).map(|s| s.column), Some(1));
+    }
+}
@@ -0,0 +1,207 @@
+//! Optional conventional casing for SQL read back from the editor.
+
+use sqlparser::{
+    dialect::PostgreSqlDialect,
+    tokenizer::{Token, Tokenizer},
+};
+
+/// Setting key the keyword casing is stored under.
+pub const KEYWORD_CASE_SETTING: &str = "sql.keyword_case";
+
+/// What happens to keywords in SQL read back from the editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeywordCase {
+    #[default]
+    AsWritten,
+    /// [`uppercase_keywords`] on every edit.
+    Upper,
+}
+
+impl KeywordCase {
+    #[must_use]
+    pub const fn toggled(self) -> Self {
+        match self {
+            Self::AsWritten => Self::Upper,
+            Self::Upper => Self::AsWritten,
+        }
+    }
+
+    /// Stored form, also shown in the status line.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::AsWritten => "as written",
+            Self::Upper => "upper",
+        }
+    }
+
+    #[must_use]
+    pub fn from_saved(saved: Option<&str>) -> Self {
+        match saved.map(str::trim) {
+            Some("upper") => Self::Upper,
+            _ => Self::AsWritten,
+        }
+    }
+
+    #[must_use]
+    pub fn apply(self, sql: &str) -> String {
+        match self {
+            Self::AsWritten => sql.to_string(),
+            Self::Upper => uppercase_keywords(sql),
+        }
+    }
+}
+
+/// Words uppercased by [`uppercase_keywords`]. Kept to clause and operator
+/// keywords; words that are often column names too (`name`, `key`, `date`,
+/// `first`, ...) and type names are left as written.
+const KEYWORDS: &[&str] = &[
+    "add",
+    "all",
+    "alter",
+    "analyze",
+    "and",
+    "any",
+    "as",
+    "asc",
+    "begin",
+    "between",
+    "by",
+    "cascade",
+    "case",
+    "cast",
+    "check",
+    "column",
+    "commit",
+    "constraint",
+    "create",
+    "cross",
+    "default",
+    "delete",
+    "desc",
+    "distinct",
+    "drop",
+    "else",
+    "end",
+    "except",
+    "exists",
+    "explain",
+    "false",
+    "fetch",
+    "foreign",
+    "from",
+    "full",
+    "grant",
+    "group",
+    "having",
+    "if",
+    "ilike",
+    "in",
+    "index",
+    "inner",
+    "insert",
+    "intersect",
+    "into",
+    "is",
+    "join",
+    "lateral",
+    "left",
+    "like",
+    "limit",
+    "not",
+    "null",
+    "nulls",
+    "offset",
+    "on",
+    "or",
+    "order",
+    "outer",
+    "over",
+    "partition",
+    "primary",
+    "recursive",
+    "references",
+    "returning",
+    "revoke",
+    "right",
+    "rollback",
+    "select",
+    "set",
+    "table",
+    "then",
+    "true",
+    "truncate",
+    "union",
+    "unique",
+    "update",
+    "using",
+    "values",
+    "view",
+    "when",
+    "where",
+    "window",
+    "with",
+];
+
+/// `sql` with the [`KEYWORDS`] in upper case. Quoted identifiers, strings,
+/// comments and words after a `.` (e.g. `t.order`) are untouched; text the
+/// tokenizer can't read is returned as is.
+#[must_use]
+pub fn uppercase_keywords(sql: &str) -> String {
+    let Ok(tokens) =
+        Tokenizer::new(&PostgreSqlDialect {}, sql).tokenize_with_location()
+    else {
+        return sql.to_string();
+    };
+    // Character offset each line starts at; token locations are 1-based
+    // line and column
+    let line_starts = std::iter::once(0)
+        .chain(
+            sql.chars()
+                .enumerate()
+                .filter(|(_, c)| *c == '\n')
+                .map(|(idx, _)| idx + 1),
+        )
+        .collect::<Vec<_>>();
+    let mut chars = sql.chars().collect::<Vec<_>>();
+    let mut previous = None;
+    for token in tokens {
+        if let Token::Word(word) = &token.token
+            && word.quote_style.is_none()
+            && previous != Some(Token::Period)
+            && KEYWORDS.contains(&word.value.to_ascii_lowercase().as_str())
+            && let Some(start) = usize::try_from(token.span.start.line)
+                .ok()
+                .and_then(|line| line_starts.get(line.checked_sub(1)?))
+                .zip(usize::try_from(token.span.start.column).ok())
+                .and_then(|(line_start, column)| {
+                    line_start.checked_add(column.checked_sub(1)?)
+                })
+            && let Some(written) =
+                chars.get_mut(start..start + word.value.chars().count())
+        {
+            written.iter_mut().for_each(char::make_ascii_uppercase);
+        }
+        if !matches!(token.token, Token::Whitespace(_)) {
+            previous = Some(token.token);
+        }
+    }
+    chars.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uppercases_keywords_but_not_identifiers() {
+        let sql = "select o.id, o.\"order\", o.order, name -- from where\n\
+                   from orders o\n  where note = 'select' and o.id in (1)";
+        assert_eq!(
+            uppercase_keywords(sql),
+            "SELECT o.id, o.\"order\", o.order, name -- from where\n\
+             FROM orders o\n  WHERE note = 'select' AND o.id IN (1)"
+        );
+        assert_eq!(uppercase_keywords("select 'unclosed"), "select 'unclosed");
+    }
+}
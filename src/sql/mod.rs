@@ -1 +1,6 @@
+pub mod keywords;
+pub mod paging;
+pub mod parens;
+pub mod position;
 pub mod safety;
+pub mod secrets;
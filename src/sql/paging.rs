@@ -0,0 +1,63 @@
+use sqlparser::ast::{SetExpr, Statement};
+
+use super::safety::parse_with_known_dialects;
+
+/// What [`paged_query`] puts before the query, so positions in errors can
+/// be mapped back to it.
+pub const PAGED_QUERY_PREFIX: &str = "SELECT * FROM (";
+
+/// Wrap a single plain query so only `limit` rows from `offset` come back.
+/// Returns `None` for anything that can't be nested in a subquery, such as
+/// DML, `SELECT ... INTO`, locking reads or several statements.
+#[must_use]
+pub fn paged_query(sql: &str, offset: usize, limit: usize) -> Option<String> {
+    let statements = parse_with_known_dialects(sql)?;
+    let [Statement::Query(query)] = statements.as_slice() else {
+        return None;
+    };
+    let plain_body = match query.body.as_ref() {
+        SetExpr::Select(select) => select.into.is_none(),
+        SetExpr::Query(_)
+        | SetExpr::SetOperation { .. }
+        | SetExpr::Values(_)
+        | SetExpr::Table(_) => true,
+        SetExpr::Insert(_)
+        | SetExpr::Update(_)
+        | SetExpr::Delete(_)
+        | SetExpr::Merge(_) => false,
+    };
+    if !plain_body || !query.locks.is_empty() {
+        return None;
+    }
+
+    let inner = sql.trim().trim_end_matches(';').trim_end();
+    Some(format!(
+        "{PAGED_QUERY_PREFIX}{inner}\n) AS d7s_page LIMIT {limit} OFFSET {offset}"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::paged_query;
+
+    #[test]
+    fn wraps_plain_queries() {
+        assert_eq!(
+            paged_query("SELECT * FROM users;", 10, 5).as_deref(),
+            Some(
+                "SELECT * FROM (SELECT * FROM users\n) AS d7s_page LIMIT 5 OFFSET 10"
+            )
+        );
+        assert!(
+            paged_query("WITH t AS (SELECT 1) SELECT * FROM t", 0, 1).is_some()
+        );
+    }
+
+    #[test]
+    fn leaves_other_statements_alone() {
+        assert!(paged_query("DELETE FROM users", 0, 5).is_none());
+        assert!(paged_query("SELECT 1; SELECT 2", 0, 5).is_none());
+        assert!(paged_query("SELECT * FROM users FOR UPDATE", 0, 5).is_none());
+        assert!(paged_query("EXPLAIN SELECT 1", 0, 5).is_none());
+    }
+}
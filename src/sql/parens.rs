@@ -0,0 +1,168 @@
+//! Parenthesis matching over SQL text, ignoring brackets inside strings,
+//! quoted identifiers and comments.
+
+use crate::sql::position::SqlPosition;
+
+/// A parenthesis without a partner, at a 1-based line and column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnbalancedParen {
+    /// `(` when it is never closed, `)` when nothing opened it.
+    pub paren: char,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl UnbalancedParen {
+    /// One line for the warning modal.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let problem = if self.paren == '(' {
+            "is never closed"
+        } else {
+            "has no matching `(`"
+        };
+        format!(
+            "`{}` on line {}, column {} {problem}",
+            self.paren, self.line, self.column
+        )
+    }
+}
+
+/// The first `)` with nothing to close, or else the outermost `(` left
+/// open at the end of `sql`.
+#[must_use]
+pub fn unbalanced_paren(sql: &str) -> Option<UnbalancedParen> {
+    let chars = sql.chars().collect::<Vec<_>>();
+    let mut open = Vec::new();
+    let mut idx = 0;
+    while let Some(&c) = chars.get(idx) {
+        let next = chars.get(idx + 1).copied();
+        idx = if c == '-' && next == Some('-') {
+            skip_past(&chars, idx + 2, "\n")
+        } else if c == '/' && next == Some('*') {
+            skip_block_comment(&chars, idx + 2)
+        } else if c == '\'' {
+            skip_string(&chars, idx + 1, is_escape_string(&chars, idx))
+        } else if c == '"' || c == '`' {
+            skip_past(&chars, idx + 1, &c.to_string())
+        } else if let Some(tag) =
+            (c == '$').then(|| dollar_tag(&chars, idx)).flatten()
+        {
+            skip_past(&chars, idx + tag.chars().count(), &tag)
+        } else if c == '(' {
+            open.push(idx);
+            idx + 1
+        } else if c == ')' {
+            if open.pop().is_none() {
+                return Some(locate(&chars, idx, ')'));
+            }
+            idx + 1
+        } else {
+            idx + 1
+        };
+    }
+    open.first().map(|&idx| locate(&chars, idx, '('))
+}
+
+fn locate(chars: &[char], idx: usize, paren: char) -> UnbalancedParen {
+    let sql = chars.iter().collect::<String>();
+    let SqlPosition { line, column } = SqlPosition::of_char(&sql, idx);
+    UnbalancedParen {
+        paren,
+        line,
+        column,
+    }
+}
+
+/// Index just past the next `end` at or after `from`, or the end of the
+/// text when it never comes.
+fn skip_past(chars: &[char], from: usize, end: &str) -> usize {
+    let end = end.chars().collect::<Vec<_>>();
+    (from..chars.len())
+        .find(|&idx| chars.get(idx..idx + end.len()) == Some(end.as_slice()))
+        .map_or(chars.len(), |idx| idx + end.len())
+}
+
+/// `/* ... */`, which nests in `PostgreSQL`.
+fn skip_block_comment(chars: &[char], from: usize) -> usize {
+    let mut depth = 1;
+    let mut idx = from;
+    while let Some(&c) = chars.get(idx) {
+        let next = chars.get(idx + 1).copied();
+        if c == '*' && next == Some('/') {
+            depth -= 1;
+            idx += 2;
+            if depth == 0 {
+                return idx;
+            }
+        } else if c == '/' && next == Some('*') {
+            depth += 1;
+            idx += 2;
+        } else {
+            idx += 1;
+        }
+    }
+    idx
+}
+
+/// A `'...'` literal; a doubled quote is just an empty literal next to the
+/// first, so only `E'...'` strings need their backslashes handled.
+fn skip_string(chars: &[char], from: usize, backslash_escapes: bool) -> usize {
+    let mut idx = from;
+    while let Some(&c) = chars.get(idx) {
+        match c {
+            '\\' if backslash_escapes => idx += 2,
+            '\'' => return idx + 1,
+            _ => idx += 1,
+        }
+    }
+    idx
+}
+
+/// Whether the quote at `idx` opens a `PostgreSQL` `E'...'` string.
+fn is_escape_string(chars: &[char], idx: usize) -> bool {
+    let prefix = idx.checked_sub(1).and_then(|i| chars.get(i));
+    let before_prefix = idx.checked_sub(2).and_then(|i| chars.get(i));
+    matches!(prefix, Some('E' | 'e'))
+        && !before_prefix.is_some_and(|&c| c.is_alphanumeric() || c == '_')
+}
+
+/// The `$tag$` opening a dollar-quoted string at `idx`, if there is one.
+/// `$1` style parameters aren't tags.
+fn dollar_tag(chars: &[char], idx: usize) -> Option<String> {
+    let rest = chars.get(idx + 1..)?;
+    let len = rest.iter().position(|&c| c == '$')?;
+    let tag = rest.get(..len)?;
+    let valid = tag.first().is_none_or(|c| !c.is_ascii_digit())
+        && tag.iter().all(|&c| c.is_alphanumeric() || c == '_');
+    valid.then(|| format!("${}$", tag.iter().collect::<String>()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_parens_in_strings_identifiers_and_comments() {
+        let sql = "SELECT count(*), ')' AS \"(\", E'\\')' -- (\n\
+                   FROM t /* ( /* ) */ */ WHERE f($1) = $$)$$";
+        assert_eq!(unbalanced_paren(sql), None);
+    }
+
+    #[test]
+    fn finds_the_unbalanced_paren() {
+        assert_eq!(
+            unbalanced_paren("SELECT *\nFROM t WHERE (a = 1")
+                .map(|paren| paren.summary()),
+            Some("`(` on line 2, column 14 is never closed".to_string())
+        );
+        assert_eq!(
+            unbalanced_paren("SELECT f(a)) FROM t("),
+            Some(UnbalancedParen {
+                paren: ')',
+                line: 1,
+                column: 12
+            })
+        );
+    }
+}
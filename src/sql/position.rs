@@ -0,0 +1,82 @@
+//! Line and column of a character in SQL text, for pointing at errors.
+
+/// A 1-based line and column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SqlPosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl SqlPosition {
+    /// Where the character at 0-based `idx` of `sql` is.
+    #[must_use]
+    pub fn of_char(sql: &str, idx: usize) -> Self {
+        let mut position = Self { line: 1, column: 1 };
+        for c in sql.chars().take(idx) {
+            if c == '\n' {
+                position.line += 1;
+                position.column = 1;
+            } else {
+                position.column += 1;
+            }
+        }
+        position
+    }
+}
+
+/// The line of a statement an error points at, to show with a `^` under
+/// the offending character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorMarker {
+    pub position: SqlPosition,
+    pub line_text: String,
+}
+
+impl ErrorMarker {
+    /// Marker for the character at 0-based `idx` of `sql`, if it has one.
+    #[must_use]
+    pub fn new(sql: &str, idx: usize) -> Option<Self> {
+        if idx >= sql.chars().count() {
+            return None;
+        }
+        let position = SqlPosition::of_char(sql, idx);
+        // Tabs would throw the caret off
+        let line_text = sql.lines().nth(position.line - 1)?.replace('\t', " ");
+        Some(Self {
+            position,
+            line_text,
+        })
+    }
+
+    /// The statement line labelled with its number, and the caret line
+    /// under it.
+    #[must_use]
+    pub fn lines(&self) -> (String, String) {
+        let label = format!("line {}: ", self.position.line);
+        let caret = format!(
+            "{}^",
+            " ".repeat(label.chars().count() + self.position.column - 1)
+        );
+        (format!("{label}{}", self.line_text), caret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_at_the_offending_character() {
+        let sql = "SELECT id\nFORM users";
+        let marker = ErrorMarker::new(sql, 10);
+        assert_eq!(
+            marker.as_ref().map(|m| m.position),
+            Some(SqlPosition { line: 2, column: 1 })
+        );
+        assert_eq!(
+            marker.map(|m| m.lines()),
+            Some(("line 2: FORM users".to_string(), "        ^".to_string()))
+        );
+        assert_eq!(ErrorMarker::new(sql, 40), None);
+    }
+}
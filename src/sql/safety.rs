@@ -13,6 +13,8 @@ pub struct SqlStatement {
 pub enum StatementSafety {
     ReadOnly,
     RequiresConfirmation,
+    /// Drops or empties something, e.g. `DROP TABLE` or `TRUNCATE`.
+    Destructive,
 }
 
 /// Split SQL text into statements while respecting common SQL quoting/comment rules.
@@ -38,6 +40,8 @@ pub fn classify_statement(sql: &str) -> StatementSafety {
     {
         return if is_read_only_statement(&stmt) {
             StatementSafety::ReadOnly
+        } else if is_destructive_statement(&stmt) {
+            StatementSafety::Destructive
         } else {
             StatementSafety::RequiresConfirmation
         };
@@ -46,7 +50,7 @@ pub fn classify_statement(sql: &str) -> StatementSafety {
     StatementSafety::RequiresConfirmation
 }
 
-fn parse_with_known_dialects(sql: &str) -> Option<Vec<Statement>> {
+pub(super) fn parse_with_known_dialects(sql: &str) -> Option<Vec<Statement>> {
     let sql = sql.trim();
     if sql.is_empty() {
         return Some(Vec::new());
@@ -73,6 +77,20 @@ const fn is_read_only_statement(statement: &Statement) -> bool {
     )
 }
 
+const fn is_destructive_statement(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::Drop { .. }
+            | Statement::Truncate(_)
+            | Statement::DropFunction(_)
+            | Statement::DropProcedure { .. }
+            | Statement::DropTrigger(_)
+            | Statement::DropDomain(_)
+            | Statement::DropExtension(_)
+            | Statement::DropPolicy(_)
+    )
+}
+
 fn fallback_split(sql: &str) -> Vec<SqlStatement> {
     sql.split(';')
         .map(str::trim)
@@ -104,4 +122,11 @@ mod tests {
         let safety = classify_statement("DELETE FROM users");
         assert_eq!(safety, StatementSafety::RequiresConfirmation);
     }
+
+    #[test]
+    fn classifies_drop_and_truncate_as_destructive() {
+        for sql in ["DROP TABLE users", "truncate users", "DROP SCHEMA app"] {
+            assert_eq!(classify_statement(sql), StatementSafety::Destructive);
+        }
+    }
 }
@@ -0,0 +1,321 @@
+//! `:source FILE`: run the statements in a `.sql` file one after another
+//! off the key handler, then list how each of them went and how long it
+//! took.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Line,
+};
+
+use crate::{
+    app::App,
+    metrics,
+    sql::safety::split_statements,
+    ui::widgets::modal::{InputModal, InputPurpose},
+};
+
+/// What became of one statement of the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatementOutcome {
+    /// Ran in `elapsed`, returning `rows` rows.
+    Ran { rows: usize, elapsed: Duration },
+    Failed { error: String, elapsed: Duration },
+    /// Not run because an earlier statement failed.
+    Skipped,
+}
+
+impl StatementOutcome {
+    const fn elapsed(&self) -> Option<Duration> {
+        match self {
+            Self::Ran { elapsed, .. } | Self::Failed { elapsed, .. } => {
+                Some(*elapsed)
+            }
+            Self::Skipped => None,
+        }
+    }
+}
+
+/// Widest a statement is shown in the summary before it is cut with `…`.
+const SUMMARY_SQL_WIDTH: usize = 60;
+
+/// The file named by `:source FILE`, with a leading `~/` read as the home
+/// directory; `None` without a file.
+#[must_use]
+pub fn source_path(input: &str) -> Option<PathBuf> {
+    let input = input.trim();
+    let input = input.strip_prefix(':').unwrap_or(input);
+    let path = input
+        .strip_prefix("source ")
+        .map_or(input, str::trim_start)
+        .trim();
+    if path.is_empty() || path == "source" {
+        return None;
+    }
+    Some(match path.strip_prefix("~/") {
+        Some(rest) => directories::BaseDirs::new().map_or_else(
+            || PathBuf::from(path),
+            |dirs| dirs.home_dir().join(rest),
+        ),
+        None => PathBuf::from(path),
+    })
+}
+
+/// Summary table of the run: a header, then one row per statement with
+/// its number, the start of its SQL, rows, duration and status. The
+/// slowest statement that ran is picked out in yellow.
+#[must_use]
+pub fn summary_lines(
+    statements: &[String],
+    outcomes: &[StatementOutcome],
+) -> Vec<Line<'static>> {
+    let slowest = outcomes
+        .iter()
+        .enumerate()
+        .filter(|(_, outcome)| matches!(outcome, StatementOutcome::Ran { .. }))
+        .max_by_key(|(_, outcome)| outcome.elapsed())
+        .map(|(idx, _)| idx)
+        .filter(|_| outcomes.len() > 1);
+    let mut lines = vec![Line::styled(
+        format!(
+            "{:>3}  {:<SUMMARY_SQL_WIDTH$}  {:>8}  {:>9}  status",
+            "#", "statement", "rows", "time"
+        ),
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    lines.extend(statements.iter().zip(outcomes).enumerate().map(
+        |(idx, (statement, outcome))| {
+            summary_row(idx + 1, statement, outcome, slowest == Some(idx))
+        },
+    ));
+    let total: Duration =
+        outcomes.iter().filter_map(StatementOutcome::elapsed).sum();
+    lines.push(Line::raw(""));
+    lines.push(Line::raw(format!("Total: {} ms", total.as_millis())));
+    lines
+}
+
+/// Row `number` of the summary table.
+fn summary_row(
+    number: usize,
+    statement: &str,
+    outcome: &StatementOutcome,
+    slowest: bool,
+) -> Line<'static> {
+    let sql = statement.split_whitespace().collect::<Vec<_>>().join(" ");
+    let sql = if sql.chars().count() > SUMMARY_SQL_WIDTH {
+        let cut = sql.chars().take(SUMMARY_SQL_WIDTH - 1).collect::<String>();
+        format!("{cut}…")
+    } else {
+        sql
+    };
+    let time = outcome.elapsed().map_or_else(
+        || "-".to_string(),
+        |elapsed| format!("{} ms", elapsed.as_millis()),
+    );
+    let (rows, status, color) = match outcome {
+        StatementOutcome::Ran { rows, .. } => (
+            rows.to_string(),
+            "ok".to_string(),
+            if slowest { Color::Yellow } else { Color::Green },
+        ),
+        StatementOutcome::Failed { error, .. } => {
+            ("-".to_string(), format!("failed: {error}"), Color::Red)
+        }
+        StatementOutcome::Skipped => {
+            ("-".to_string(), "not run".to_string(), Color::DarkGray)
+        }
+    };
+    Line::styled(
+        format!(
+            "{number:>3}  {sql:<SUMMARY_SQL_WIDTH$}  {rows:>8}  {time:>9}  \
+             {status}"
+        ),
+        Style::default().fg(color),
+    )
+}
+
+/// Status message for the finished run, e.g. "fix.sql: 3 of 4 statements
+/// ran; statement 4 failed."
+#[must_use]
+pub fn summary_status(name: &str, outcomes: &[StatementOutcome]) -> String {
+    let ran = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome, StatementOutcome::Ran { .. }))
+        .count();
+    let failed = outcomes
+        .iter()
+        .position(|o| matches!(o, StatementOutcome::Failed { .. }));
+    match failed {
+        Some(idx) => format!(
+            "{name}: {ran} of {} statements ran; statement {} failed.",
+            outcomes.len(),
+            idx + 1
+        ),
+        None => format!("{name}: all {ran} statements ran."),
+    }
+}
+
+impl App<'_> {
+    /// Ask for a `.sql` file to run
+    pub(crate) fn open_source_prompt(&mut self) {
+        self.modal_manager.open_input_modal(
+            InputModal::new(
+                " Source ",
+                "Run the statements in a .sql file:",
+                InputPurpose::SourceFile,
+            )
+            .with_value(":source "),
+        );
+    }
+
+    /// Run the file typed into the `:source` prompt
+    pub(crate) fn source_command(&mut self, input: &str) {
+        match source_path(input) {
+            Some(path) => self.source_sql_file(&path),
+            None => self.set_status("Usage: :source path/to/file.sql"),
+        }
+    }
+
+    /// Run the statements in `path` in order, stopping at the first one
+    /// that fails, and list the outcome of each once done
+    pub(crate) fn source_sql_file(&mut self, path: &Path) {
+        let Some(database) = self.database_explorer.database.clone() else {
+            self.set_status("Connect to a database to run a .sql file.");
+            return;
+        };
+        let sql = match std::fs::read_to_string(path) {
+            Ok(sql) => sql,
+            Err(e) => {
+                self.set_status(format!(
+                    "Failed to read {}: {e}",
+                    path.display()
+                ));
+                return;
+            }
+        };
+        let statements: Vec<String> = split_statements(&sql)
+            .into_iter()
+            .map(|statement| statement.text)
+            .collect();
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        if statements.is_empty() {
+            self.set_status(format!("{name} has no statements to run."));
+            return;
+        }
+
+        let label = format!("Running {name}");
+        let connection = self.database_explorer.connection.name.clone();
+        self.start_connection_task(&label, |progress| async move {
+            let total = Some(statements.len() as u64);
+            progress.report(0, total);
+            let mut outcomes = Vec::with_capacity(statements.len());
+            for statement in &statements {
+                if outcomes
+                    .iter()
+                    .any(|o| matches!(o, StatementOutcome::Failed { .. }))
+                {
+                    outcomes.push(StatementOutcome::Skipped);
+                    continue;
+                }
+                let started = Instant::now();
+                let result = database.execute_in_session(statement).await;
+                metrics::record_query(
+                    &connection,
+                    started.elapsed(),
+                    result.as_ref().ok().map(Vec::len),
+                );
+                let outcome = match result {
+                    Ok(rows) => StatementOutcome::Ran {
+                        rows: rows.len(),
+                        elapsed: started.elapsed(),
+                    },
+                    Err(e) => StatementOutcome::Failed {
+                        error: e.to_string(),
+                        elapsed: started.elapsed(),
+                    },
+                };
+                outcomes.push(outcome);
+                progress.report(outcomes.len() as u64, total);
+            }
+            progress.summary(
+                format!(" Source: {name} "),
+                summary_lines(&statements, &outcomes),
+            );
+            let status = summary_status(&name, &outcomes);
+            if outcomes
+                .iter()
+                .any(|o| matches!(o, StatementOutcome::Failed { .. }))
+            {
+                Err(status)
+            } else {
+                Ok(status)
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_file_after_the_command() {
+        assert_eq!(
+            source_path(":source fixes/orders.sql"),
+            Some(PathBuf::from("fixes/orders.sql"))
+        );
+        assert_eq!(source_path("  fix.sql "), Some(PathBuf::from("fix.sql")));
+        assert_eq!(source_path(":source "), None);
+    }
+
+    #[test]
+    fn summary_names_the_failed_statement() {
+        let outcomes = [
+            StatementOutcome::Ran {
+                rows: 0,
+                elapsed: Duration::from_millis(2),
+            },
+            StatementOutcome::Failed {
+                error: "syntax error".to_string(),
+                elapsed: Duration::from_millis(5),
+            },
+            StatementOutcome::Skipped,
+        ];
+        assert_eq!(
+            summary_status("fix.sql", &outcomes),
+            "fix.sql: 1 of 3 statements ran; statement 2 failed."
+        );
+        let statements = vec![
+            "BEGIN".to_string(),
+            "UPDATE t\n   SET a = 1".to_string(),
+            "COMMIT".to_string(),
+        ];
+        let lines = summary_lines(&statements, &outcomes);
+        // Header, a row per statement, a blank line and the total
+        assert_eq!(lines.len(), 6);
+        let failed = lines.get(2).unwrap().to_string();
+        assert!(failed.starts_with("  2  UPDATE t SET a = 1  "));
+        assert!(failed.ends_with("      5 ms  failed: syntax error"));
+        assert_eq!(lines.last().unwrap().to_string(), "Total: 7 ms");
+    }
+
+    #[test]
+    fn cuts_long_statements_in_the_summary() {
+        let statement = format!("SELECT {}", "x, ".repeat(40));
+        let outcome = StatementOutcome::Ran {
+            rows: 1,
+            elapsed: Duration::from_millis(1),
+        };
+        let row = summary_row(1, &statement, &outcome, false).to_string();
+        assert!(row.contains("x, x…"));
+        assert!(!row.contains(&statement));
+    }
+}
@@ -0,0 +1,53 @@
+//! Statements from the SQL executor held back while a background task runs
+//! statements on the same client, so the two never interleave on it. They
+//! run in order once the task finishes.
+
+use std::collections::VecDeque;
+
+/// Statements waiting for the session, oldest first.
+#[derive(Debug, Default)]
+pub struct StatementQueue {
+    pending: VecDeque<String>,
+}
+
+impl StatementQueue {
+    pub fn push(&mut self, statement: String) {
+        self.pending.push_back(statement);
+    }
+
+    pub fn pop(&mut self) -> Option<String> {
+        self.pending.pop_front()
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// For the status line, e.g. "1 statement queued"; `None` when empty.
+    #[must_use]
+    pub fn describe(&self) -> Option<String> {
+        match self.pending.len() {
+            0 => None,
+            1 => Some("1 statement queued".to_string()),
+            n => Some(format!("{n} statements queued")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_statements_in_the_order_queued() {
+        let mut queue = StatementQueue::default();
+        assert_eq!(queue.describe(), None);
+        queue.push("SELECT 1".to_string());
+        assert_eq!(queue.describe().as_deref(), Some("1 statement queued"));
+        queue.push("SELECT 2".to_string());
+        assert_eq!(queue.describe().as_deref(), Some("2 statements queued"));
+        assert_eq!(queue.pop().as_deref(), Some("SELECT 1"));
+        assert_eq!(queue.pop().as_deref(), Some("SELECT 2"));
+        assert_eq!(queue.pop(), None);
+    }
+}
@@ -0,0 +1,43 @@
+//! Recovering from a local store that can't be opened or migrated, instead
+//! of failing to start.
+
+use crate::{
+    app::App,
+    db::sqlite::recreate_store,
+    ui::widgets::modal::{ChoiceModal, StoreRecoveryChoice},
+};
+
+impl App<'_> {
+    /// Offer to recreate the local store that failed to open with `error`
+    pub(crate) fn open_store_recovery(&mut self, error: &str) {
+        self.set_status(format!("Local store unavailable: {error}"));
+        self.modal_manager
+            .open_choice_modal(ChoiceModal::store_recovery(error));
+    }
+
+    pub(crate) fn handle_store_recovery_choice(
+        &mut self,
+        choice: StoreRecoveryChoice,
+    ) {
+        match choice {
+            StoreRecoveryChoice::Recreate => match recreate_store() {
+                Ok(recovery) => {
+                    self.refresh_connections();
+                    self.set_status(format!(
+                        "Recreated the local store with {} readable \
+                         connections. Old store: {}, export: {}",
+                        recovery.restored,
+                        recovery.backup.display(),
+                        recovery.export.display()
+                    ));
+                }
+                Err(e) => {
+                    self.set_status(format!(
+                        "Failed to recreate the local store: {e}"
+                    ));
+                }
+            },
+            StoreRecoveryChoice::Quit => self.quit(),
+        }
+    }
+}
@@ -1,15 +1,17 @@
 //! Table data view: draft rows, multi-select, insert/delete, refresh.
 
-use std::collections::BTreeSet;
+use std::{collections::BTreeSet, time::Instant};
 
 use color_eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent};
 
 use crate::{
     app::App,
-    app_state::{AppState, DatabaseExplorerState},
+    app_state::DatabaseExplorerState,
+    confirmations::ConfirmAction,
     db::{DbRowId, RowDeleteSpec, connection::ConnectionType},
-    filtered_data::FilteredData,
+    metrics,
+    services::ColumnWidthService,
+    sort::TableOrder,
     ui::{handlers::TableNavigationHandler, widgets::table::RawTableRow},
     virtual_table::{VIRTUAL_TABLE_PAGE_SIZE, VirtualTableMeta},
 };
@@ -38,61 +40,176 @@ impl App<'_> {
         );
     }
 
-    /// Remove client-only draft rows and refetch the current page from the server.
+    /// Remove client-only draft rows and refetch the current page from the
+    /// server, keeping the selected row selected.
     pub(crate) async fn reload_current_table_data(&mut self) -> Result<()> {
-        self.strip_draft_rows_from_table_data();
-        let DatabaseExplorerState::TableData(schema, table) =
-            self.database_explorer.state.clone()
-        else {
-            return Ok(());
-        };
+        let anchor = self
+            .database_explorer
+            .table_data
+            .as_ref()
+            .and_then(|fd| fd.table.selection_anchor());
         let offset = self
             .database_explorer
             .table_data_virtual
             .as_ref()
             .map_or(0, |m| m.window_start);
-        let page_size = self
-            .database_explorer
+        let refetched = match self.database_explorer.table_keyset.as_ref() {
+            Some(cursor) => {
+                let seek = cursor.seek.clone();
+                self.fetch_keyset_page(seek).await.map(|_| ())
+            }
+            None => self.refetch_table_page(offset).await,
+        };
+        match refetched {
+            Ok(()) => {
+                if let Some(fd) = self.database_explorer.table_data.as_mut() {
+                    fd.table.restore_selection(anchor.as_ref());
+                }
+                self.set_status("Refreshed.");
+            }
+            Err(e) => self.set_status(format!("Refresh failed: {e}")),
+        }
+        Ok(())
+    }
+
+    /// Remove draft rows and fetch the page of the current table starting at
+    /// `offset`, in the current server-side order.
+    pub(crate) async fn refetch_table_page(
+        &mut self,
+        offset: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.strip_draft_rows_from_table_data();
+        let explorer = &mut self.database_explorer;
+        let DatabaseExplorerState::TableData(schema, table) = &explorer.state
+        else {
+            return Ok(());
+        };
+        let page_size = explorer
             .table_data_virtual
             .as_ref()
             .map_or(VIRTUAL_TABLE_PAGE_SIZE, |m| m.page_size);
-        let total_rows = self
-            .database_explorer
+        let total_rows = explorer
             .table_data_virtual
             .as_ref()
             .and_then(|m| m.total_rows);
-        let Some(database) = self.database_explorer.database.as_ref() else {
+        let Some(database) = explorer.database.as_ref() else {
             return Ok(());
         };
-        match database
-            .get_table_data_page(&schema, &table, offset, page_size)
-            .await
-        {
-            Ok(page) => {
-                let crate::db::TableDataPage {
-                    rows: data,
-                    column_names,
-                    row_ids,
-                } = page;
-                let loaded = data.len();
-                let meta = VirtualTableMeta::from_fetch(
-                    offset, page_size, loaded, total_rows,
-                );
-                let mut table_state =
-                    crate::ui::widgets::table::TableDataState::default();
-                table_state.reset(data, &column_names, Some(row_ids));
-                self.database_explorer.table_data = Some(FilteredData {
-                    original: table_state.model.items.clone(),
-                    table: table_state,
-                });
-                self.database_explorer.table_data_virtual = Some(meta);
-                self.set_status("Refreshed.");
+        let started = Instant::now();
+        let page = database
+            .get_table_data_page(
+                schema,
+                table,
+                explorer.table_order.as_ref(),
+                None,
+                offset,
+                page_size,
+            )
+            .await;
+        metrics::record_page(&explorer.connection.name, started, &page);
+        let crate::db::TableDataPage {
+            rows: data,
+            column_names,
+            row_ids,
+            sql,
+        } = page?;
+        let loaded = data.len();
+        let meta =
+            VirtualTableMeta::from_fetch(offset, page_size, loaded, total_rows);
+        let mut table_state =
+            crate::ui::widgets::table::TableDataState::default();
+        table_state.reset(data, &column_names, Some(row_ids));
+        explorer.replace_table_data_page(table_state);
+        explorer.table_data_virtual = Some(meta);
+        explorer.table_data_sql = Some(sql);
+        Ok(())
+    }
+
+    /// Order the table server-side by the selected column, cycling
+    /// ascending, descending and unordered.
+    pub(crate) async fn table_data_cycle_order(&mut self) {
+        if self.keyset_paging_fixes_order() {
+            return;
+        }
+        let Some(column_name) =
+            self.database_explorer.table_data.as_ref().and_then(|fd| {
+                let column = fd.table.view.state.selected_column()?;
+                fd.table.column_name(column).map(str::to_string)
+            })
+        else {
+            self.set_status("Select a column (h/l) to order by it.");
+            return;
+        };
+        let order = TableOrder::cycle(
+            self.database_explorer.table_order.as_ref(),
+            &column_name,
+        );
+        self.apply_table_order(order).await;
+    }
+
+    /// Move NULLs to the other end of the current order.
+    pub(crate) async fn table_data_toggle_nulls_order(&mut self) {
+        if self.keyset_paging_fixes_order() {
+            return;
+        }
+        let Some(order) = self.database_explorer.table_order.clone() else {
+            self.set_status("Order by a column with o first.");
+            return;
+        };
+        self.apply_table_order(Some(TableOrder {
+            nulls: order.nulls.toggled(),
+            ..order
+        }))
+        .await;
+    }
+
+    /// Drop the filter, order and keyset paging kept on the table and show
+    /// its first page.
+    pub(crate) async fn clear_sticky_predicates(&mut self) {
+        let explorer = &mut self.database_explorer;
+        if explorer.sticky_predicates().is_empty() {
+            self.set_status("No filter or order to clear.");
+            return;
+        }
+        if let Some(fd) = explorer.table_data.as_mut() {
+            fd.clear_filter();
+        }
+        explorer.table_order = None;
+        explorer.table_keyset = None;
+        match self.refetch_table_page(0).await {
+            Ok(()) => self.set_status("Filter and order cleared."),
+            Err(e) => self.set_status(format!("Failed to load rows: {e}")),
+        }
+    }
+
+    /// Whether rows are paged by key, which fixes their order; says so.
+    fn keyset_paging_fixes_order(&mut self) -> bool {
+        let fixed = self.database_explorer.table_keyset.is_some();
+        if fixed {
+            self.set_status(
+                "Keyset paging orders by the primary key; [ turns it off.",
+            );
+        }
+        fixed
+    }
+
+    /// Refetch the first page in `order`, keeping the previous order if the
+    /// query fails.
+    async fn apply_table_order(&mut self, order: Option<TableOrder>) {
+        let previous = std::mem::replace(
+            &mut self.database_explorer.table_order,
+            order.clone(),
+        );
+        match (self.refetch_table_page(0).await, order) {
+            (Ok(()), Some(order)) => {
+                self.set_status(format!("Ordered by {}.", order.describe()));
             }
-            Err(e) => {
-                self.set_status(format!("Refresh failed: {e}"));
+            (Ok(()), None) => self.set_status("Natural order."),
+            (Err(e), _) => {
+                self.database_explorer.table_order = previous;
+                self.set_status(format!("Failed to order rows: {e}"));
             }
         }
-        Ok(())
     }
 
     pub(crate) fn discard_table_draft(&mut self) -> bool {
@@ -318,11 +435,7 @@ impl App<'_> {
             self.set_status("Multi-select applies to saved rows.");
             return;
         }
-        if fd.table.multi_row_selection.contains(&i) {
-            fd.table.multi_row_selection.remove(&i);
-        } else {
-            fd.table.multi_row_selection.insert(i);
-        }
+        fd.table.toggle_selected_row_mark();
     }
 
     /// Start delete: drafts removed locally; persisted rows get a confirmation modal.
@@ -456,6 +569,9 @@ impl App<'_> {
             preview.unwrap_or_else(|| "Delete row?".to_string())
         };
         self.pending_row_deletes = Some(db_specs);
+        if !self.confirmations.asks(ConfirmAction::RowDelete) {
+            return self.execute_pending_row_deletes().await;
+        }
         self.modal_manager.open_sql_execution_confirmation_modal(p);
         Ok(())
     }
@@ -507,49 +623,83 @@ impl App<'_> {
         Ok(())
     }
 
-    #[allow(clippy::wildcard_enum_match_arm)]
-    pub(crate) async fn handle_table_data_hotkeys(
-        &mut self,
-        key: KeyEvent,
-    ) -> Result<bool> {
-        if self.state != AppState::DatabaseConnected {
-            return Ok(false);
-        }
-        if !matches!(
-            self.database_explorer.state,
-            DatabaseExplorerState::TableData(_, _)
-        ) {
-            return Ok(false);
+    pub(crate) fn table_data_hide_selected_column(&mut self) {
+        let Some(fd) = self.database_explorer.table_data.as_mut() else {
+            return;
+        };
+        if fd.table.view.state.selected_column().is_none() {
+            self.set_status("Select a column (h/l) to hide it.");
+        } else if !fd.table.hide_selected_column() {
+            self.set_status("Can't hide the last visible column.");
         }
-        if !key.modifiers.is_empty() {
-            return Ok(false);
+    }
+
+    pub(crate) fn table_data_toggle_expand_column(&mut self) {
+        let Some(fd) = self.database_explorer.table_data.as_mut() else {
+            return;
+        };
+        if fd.table.view.state.selected_column().is_none() {
+            self.set_status("Select a column (h/l) to expand it.");
+        } else {
+            fd.table.toggle_expand_selected_column();
         }
-        match key.code {
-            KeyCode::Char('r' | 'R') => {
-                self.reload_current_table_data().await?;
-                Ok(true)
-            }
-            KeyCode::Char('a' | 'A') => {
-                self.table_data_add_blank_draft()?;
-                Ok(true)
-            }
-            KeyCode::Char('c' | 'C') => {
-                self.table_data_duplicate_as_draft().await?;
-                Ok(true)
-            }
-            KeyCode::Char('s' | 'S') => {
-                self.table_data_commit_draft().await?;
-                Ok(true)
-            }
-            KeyCode::Char('d' | 'D') => {
-                self.table_data_request_delete().await?;
-                Ok(true)
-            }
-            KeyCode::Char(' ') => {
-                self.table_data_toggle_multi_select();
-                Ok(true)
+    }
+
+    /// Wrap the selected column of the rows shown onto several lines, or
+    /// back onto one
+    pub(crate) fn toggle_wrapped_column(&mut self) {
+        let Some(rows) = self.database_explorer.current_rows_mut() else {
+            return;
+        };
+        match rows.toggle_wrap_selected_column() {
+            Some(true) => self.set_status("Wrapping the selected column."),
+            Some(false) => self.set_status("Column back on one line."),
+            None => self.set_status("Select a column (h/l) to wrap it."),
+        }
+    }
+
+    /// Pin the selected row above the grid, or unpin it
+    pub(crate) fn toggle_pinned_row(&mut self) {
+        let Some(rows) = self.database_explorer.current_rows_mut() else {
+            return;
+        };
+        match rows.toggle_pin_selected_row() {
+            Some(true) => {
+                let count = rows.view.pinned_rows.len();
+                self.set_status(format!("Pinned row ({count} pinned)."));
             }
-            _ => Ok(false),
+            Some(false) => self.set_status("Unpinned row."),
+            None => {}
+        }
+    }
+
+    /// Widen or narrow the selected column and remember its width for this
+    /// table.
+    pub(crate) fn table_data_resize_column(&mut self, wider: bool) {
+        let explorer = &mut self.database_explorer;
+        let DatabaseExplorerState::TableData(schema_name, table_name) =
+            &explorer.state
+        else {
+            return;
+        };
+        let Some(fd) = explorer.table_data.as_mut() else {
+            return;
+        };
+        let Some((idx, width)) = fd.table.resize_selected_column(wider) else {
+            self.set_status("Select a column (h/l) to resize it.");
+            return;
+        };
+        let Some(column_name) = fd.table.column_name(idx) else {
+            return;
+        };
+        if let Err(e) = ColumnWidthService::save(
+            &explorer.connection.name,
+            schema_name,
+            table_name,
+            column_name,
+            width,
+        ) {
+            self.set_status(format!("Failed to save column width: {e}"));
         }
     }
 }
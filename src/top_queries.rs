@@ -0,0 +1,34 @@
+//! The statements `pg_stat_statements` ranks highest, as a starting point
+//! for tuning.
+
+use crate::{
+    app::App, db::top_query::TopQueryOrder, ui::widgets::modal::ChoiceModal,
+};
+
+impl App<'_> {
+    /// List the current database's top queries ranked by `order`
+    pub(crate) async fn open_top_queries(&mut self, order: TopQueryOrder) {
+        let Some(database) = self.database_explorer.database.as_ref() else {
+            return;
+        };
+        let queries = database
+            .get_top_queries(order)
+            .await
+            .map_err(|e| e.to_string());
+        match queries {
+            Ok(Some(queries)) if queries.is_empty() => {
+                self.set_status("pg_stat_statements has no statements yet.");
+            }
+            Ok(Some(queries)) => self
+                .modal_manager
+                .open_choice_modal(ChoiceModal::top_queries(order, queries)),
+            Ok(None) => self.set_status(
+                "pg_stat_statements isn't installed in this database \
+                 (CREATE EXTENSION pg_stat_statements).",
+            ),
+            Err(e) => {
+                self.set_status(format!("Failed to read top queries: {e}"));
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Accessible mode for screen readers and low vision: plain ASCII in place
+//! of box-drawing, block and braille glyphs, high-contrast colors, and the
+//! view or dialog that just opened announced in the status line.
+
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+
+use crate::{app_state::DatabaseExplorerState, ui::widgets::modal::ModalType};
+
+/// Setting key the mode is stored under.
+pub const ACCESSIBLE_SETTING: &str = "display.accessible";
+/// Turns the mode on or off for a session, over the saved setting.
+pub const ACCESSIBLE_ENV: &str = "D7S_ACCESSIBLE";
+
+/// Whether the screen is drawn for screen readers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccessibleMode {
+    #[default]
+    Off,
+    On,
+}
+
+impl AccessibleMode {
+    #[must_use]
+    pub const fn toggled(self) -> Self {
+        match self {
+            Self::On => Self::Off,
+            Self::Off => Self::On,
+        }
+    }
+
+    /// Stored form, also shown in the status line.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::On => "on",
+            Self::Off => "off",
+        }
+    }
+
+    #[must_use]
+    pub fn from_saved(saved: Option<&str>) -> Self {
+        match saved.map(|s| s.trim().to_ascii_lowercase()).as_deref() {
+            Some("on" | "1" | "true" | "yes") => Self::On,
+            _ => Self::Off,
+        }
+    }
+
+    /// Redraw a drawn frame with plain glyphs and high contrast: text in
+    /// the terminal's own foreground, and highlighted cells reversed.
+    pub fn apply(self, buf: &mut Buffer) {
+        if self == Self::Off {
+            return;
+        }
+        for cell in &mut buf.content {
+            if let Some(plain) = plain_glyph(cell.symbol()) {
+                cell.set_symbol(plain);
+            }
+            if cell.bg != Color::Reset {
+                cell.modifier.insert(Modifier::REVERSED);
+                cell.fg = Color::Reset;
+                cell.bg = Color::Reset;
+            } else if matches!(
+                cell.fg,
+                Color::DarkGray | Color::Gray | Color::Black
+            ) {
+                cell.fg = Color::Reset;
+            }
+        }
+    }
+}
+
+/// ASCII for a box-drawing, block or braille glyph: `-`/`=` for lines
+/// across, `|` for lines down, `+` for corners and joins, `#` for blocks
+/// and `*` for the spinner's braille.
+fn plain_glyph(symbol: &str) -> Option<&'static str> {
+    let mut chars = symbol.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        return None;
+    };
+    match c {
+        '━' | '═' | '┅' | '┉' | '╍' => Some("="),
+        '─' | '┄' | '┈' | '╌' | '╴' | '╶' | '╸' | '╺' => Some("-"),
+        '│' | '┃' | '║' | '┆' | '┇' | '┊' | '┋' | '╎' | '╏' | '╵' | '╷' => {
+            Some("|")
+        }
+        '\u{2500}'..='\u{257f}' => Some("+"),
+        '\u{2580}'..='\u{259f}' => Some("#"),
+        '\u{2800}'..='\u{28ff}' => Some("*"),
+        _ => None,
+    }
+}
+
+/// What to announce on arriving at `state`, e.g. "Table data:
+/// public.orders".
+#[must_use]
+pub fn view_announcement(state: &DatabaseExplorerState) -> String {
+    match state {
+        DatabaseExplorerState::Tables(schema) => format!("Tables: {schema}"),
+        DatabaseExplorerState::Columns(schema, table)
+        | DatabaseExplorerState::TableData(schema, table) => {
+            format!("{}: {schema}.{table}", state.mode_label())
+        }
+        DatabaseExplorerState::Connections
+        | DatabaseExplorerState::Databases
+        | DatabaseExplorerState::Schemas
+        | DatabaseExplorerState::SqlResults(_) => {
+            state.mode_label().to_string()
+        }
+    }
+}
+
+/// What to announce when a dialog of `kind` opens.
+#[must_use]
+pub const fn modal_announcement(kind: ModalType) -> &'static str {
+    match kind {
+        ModalType::Connection => "Connection form open, Esc cancels",
+        ModalType::Confirmation | ModalType::SqlExecutionConfirmation => {
+            "Confirmation open, Enter presses the selected button"
+        }
+        ModalType::SqlQuerySelection => {
+            "Statement list open, Enter runs the selected statement"
+        }
+        ModalType::CellValue => {
+            "Value editor open, Alt+Left and Alt+Right step cells, Esc cancels"
+        }
+        ModalType::Password => "Password prompt open, Esc cancels",
+        ModalType::Choice => "List open, Enter picks, Esc cancels",
+        ModalType::Info => "Report open, Esc closes",
+        ModalType::Input => "Text prompt open, Enter submits, Esc cancels",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        layout::Rect,
+        style::Style,
+        widgets::{Block, Widget},
+    };
+
+    use super::*;
+
+    #[test]
+    fn draws_borders_in_ascii() {
+        let area = Rect::new(0, 0, 4, 3);
+        let mut buf = Buffer::empty(area);
+        Block::bordered().render(area, &mut buf);
+        buf.set_string(1, 1, "⠋█", Style::default().fg(Color::DarkGray));
+        AccessibleMode::On.apply(&mut buf);
+        assert_eq!(buf, Buffer::with_lines(["+--+", "|*#|", "+--+"]));
+    }
+
+    #[test]
+    fn reverses_highlighted_cells() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 1, 1));
+        buf.set_string(
+            0,
+            0,
+            "x",
+            Style::default().fg(Color::Yellow).bg(Color::DarkGray),
+        );
+        AccessibleMode::On.apply(&mut buf);
+        let cell = buf.cell((0, 0)).unwrap();
+        assert_eq!(cell.bg, Color::Reset);
+        assert!(cell.modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn announces_the_view() {
+        let state = DatabaseExplorerState::TableData(
+            "public".to_string(),
+            "orders".to_string(),
+        );
+        assert_eq!(view_announcement(&state), "Table data: public.orders");
+        assert_eq!(AccessibleMode::from_saved(Some("1")), AccessibleMode::On);
+        assert_eq!(AccessibleMode::from_saved(None), AccessibleMode::Off);
+    }
+}
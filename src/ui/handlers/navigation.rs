@@ -104,6 +104,7 @@ impl TableNavigationHandler {
                 } else {
                     view.state.select_column(Some(num_cols - 1));
                 }
+                Self::skip_hidden_columns(view, num_cols, false);
             }
             KeyCode::Char('l' | 'w') | KeyCode::Right => {
                 let num_cols =
@@ -121,21 +122,43 @@ impl TableNavigationHandler {
                 } else {
                     view.state.select_column(Some(0));
                 }
+                Self::skip_hidden_columns(view, num_cols, true);
             }
             KeyCode::Char('g') => {
                 view.state.select(Some(0));
                 Self::wrap_rows(&mut view.state, &model.items);
                 view.column_offset = 0;
             }
-            KeyCode::Char('G') => {
-                if !model.items.is_empty() {
-                    view.state.select(Some(model.items.len() - 1));
-                }
+            KeyCode::Char('G') if !model.items.is_empty() => {
+                view.state.select(Some(model.items.len() - 1));
             }
             _ => {}
         }
     }
 
+    /// Keep moving the column selection in the same direction while it sits
+    /// on a hidden column.
+    fn skip_hidden_columns(
+        view: &mut TableViewState,
+        num_cols: usize,
+        forward: bool,
+    ) {
+        for _ in 0..num_cols {
+            let Some(col) = view.state.selected_column() else {
+                return;
+            };
+            if !view.hidden_columns.contains(&col) {
+                return;
+            }
+            let next = if forward {
+                (col + 1) % num_cols
+            } else {
+                (col + num_cols - 1) % num_cols
+            };
+            view.state.select_column(Some(next));
+        }
+    }
+
     /// Handles navigation for table data widget
     #[allow(dead_code)]
     pub fn navigate<T: TableData + Clone>(
@@ -0,0 +1,532 @@
+//! The keys of every screen, in one table that both the top bar and the key
+//! handler read, so a key can't do something without being listed. The bar
+//! shows the common ones and `F1` lists them all.
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::{
+    app_state::DatabaseExplorerState,
+    ui::widgets::{hotkey::Hotkey, modal::ModalType},
+};
+
+/// What is browsed, which decides the keys that work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    Connections,
+    Databases,
+    Schemas,
+    Tables,
+    Columns,
+    TableData,
+    SqlResults,
+}
+
+impl Screen {
+    pub const ALL: [Self; 7] = [
+        Self::Connections,
+        Self::Databases,
+        Self::Schemas,
+        Self::Tables,
+        Self::Columns,
+        Self::TableData,
+        Self::SqlResults,
+    ];
+
+    #[must_use]
+    pub const fn of(state: &DatabaseExplorerState) -> Self {
+        match state {
+            DatabaseExplorerState::Connections => Self::Connections,
+            DatabaseExplorerState::Databases => Self::Databases,
+            DatabaseExplorerState::Schemas => Self::Schemas,
+            DatabaseExplorerState::Tables(_) => Self::Tables,
+            DatabaseExplorerState::Columns(..) => Self::Columns,
+            DatabaseExplorerState::TableData(..) => Self::TableData,
+            DatabaseExplorerState::SqlResults(_) => Self::SqlResults,
+        }
+    }
+}
+
+/// What a key does, run by `App::run_key_action`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    Quit,
+    ResizeTopBar {
+        grow: bool,
+    },
+    CycleCompactMode,
+    ToggleAccessibleMode,
+    Copy,
+    /// `Q<reg>` and `@<reg>`, which wait for the register.
+    Macro,
+    Back,
+    Open,
+    Search,
+    /// Move the selection as the key would in a table.
+    Move(KeyCode),
+    KeyHelp,
+    NewConnection,
+    ImportConnection,
+    EditConnection,
+    DeleteConnection,
+    QuickConnect,
+    TestConnection,
+    TestAllConnections,
+    SortConnections,
+    ConnectionColumns,
+    CopyConnectionUri,
+    SqlEditor,
+    KeywordCase,
+    MaskSecrets,
+    Views,
+    RecentTables,
+    OpenRecentTable(usize),
+    ResultHistory,
+    Psql,
+    SearchPath,
+    TopQueries,
+    RunSqlFile,
+    GroupDigits,
+    RelativeTimes,
+    GoToTable,
+    Disconnect,
+    Savepoint,
+    RollbackToSavepoint,
+    DiffRows,
+    ColumnStats,
+    Histogram,
+    GroupBy,
+    ExportDdl,
+    DiskUsage,
+    CompareTables,
+    FkOrphans,
+    ToggleTableView,
+    Refresh,
+    NewRow,
+    CopyRow,
+    CommitRow,
+    DeleteRow,
+    MultiSelect,
+    SaveView,
+    HideColumn,
+    ShowAllColumns,
+    ExpandColumn,
+    ResizeColumn {
+        wider: bool,
+    },
+    MultiLine,
+    WrapColumn,
+    PinRow,
+    RowNote,
+    CopyInList,
+    JsonPath,
+    OrderBy,
+    NullsOrder,
+    Duplicates,
+    RowColors,
+    SearchColumn,
+    ShowQuery,
+    QueryToEditor,
+    KeysetPaging,
+    JumpToKey,
+    GoToRow,
+    CopyLink,
+    ClearFilterOrder,
+    RunSql,
+    RunAgain,
+    PlanBaseline,
+    IndexHints,
+    Benchmark,
+    BlockInsert,
+    LineCommand,
+    SortResults,
+    DistinctRows,
+    MarkRow,
+    FetchMore,
+    ExportResults,
+}
+
+/// A key, the screens it works on and what it does there.
+#[derive(Debug, Clone)]
+pub struct KeyBinding {
+    pub screens: &'static [Screen],
+    pub hotkey: Hotkey,
+    pub action: KeyAction,
+    /// Listed in the top bar, not only under `F1`.
+    pub in_bar: bool,
+}
+
+const fn bar(
+    screens: &'static [Screen],
+    hotkey: Hotkey,
+    action: KeyAction,
+) -> KeyBinding {
+    KeyBinding {
+        screens,
+        hotkey,
+        action,
+        in_bar: true,
+    }
+}
+
+const fn help(
+    screens: &'static [Screen],
+    hotkey: Hotkey,
+    action: KeyAction,
+) -> KeyBinding {
+    KeyBinding {
+        screens,
+        hotkey,
+        action,
+        in_bar: false,
+    }
+}
+
+const EVERYWHERE: &[Screen] = &Screen::ALL;
+const CONNECTED: &[Screen] = &[
+    Screen::Databases,
+    Screen::Schemas,
+    Screen::Tables,
+    Screen::Columns,
+    Screen::TableData,
+    Screen::SqlResults,
+];
+const CONNECTIONS: &[Screen] = &[Screen::Connections];
+const SCHEMAS_AND_TABLES: &[Screen] = &[Screen::Schemas, Screen::Tables];
+const TABLES_AND_COLUMNS: &[Screen] = &[Screen::Tables, Screen::Columns];
+const TABLE: &[Screen] = &[Screen::Tables, Screen::Columns, Screen::TableData];
+const COLUMNS: &[Screen] = &[Screen::Columns];
+const TABLE_DATA: &[Screen] = &[Screen::TableData];
+const SQL_RESULTS: &[Screen] = &[Screen::SqlResults];
+const ROWS: &[Screen] = &[Screen::TableData, Screen::SqlResults];
+
+/// Every key, most specific screens first, which is the order they're
+/// listed in.
+#[rustfmt::skip]
+pub static KEYMAP: &[KeyBinding] = &[
+    bar(CONNECTIONS, Hotkey::new('n', "New Connection"), KeyAction::NewConnection),
+    help(CONNECTIONS, Hotkey::new('i', "Import from env"), KeyAction::ImportConnection),
+    bar(CONNECTIONS, Hotkey::new('e', "Edit Connection"), KeyAction::EditConnection),
+    bar(CONNECTIONS, Hotkey::new('d', "Delete Connection"), KeyAction::DeleteConnection),
+    bar(CONNECTIONS, Hotkey::new('o', "Open Connection"), KeyAction::Open),
+    bar(CONNECTIONS, Hotkey::new(':', "Quick connect"), KeyAction::QuickConnect),
+    bar(CONNECTIONS, Hotkey::new('t', "Test"), KeyAction::TestConnection),
+    bar(CONNECTIONS, Hotkey::new('T', "Test all"), KeyAction::TestAllConnections),
+    help(CONNECTIONS, Hotkey::new('s', "Sort"), KeyAction::SortConnections),
+    help(CONNECTIONS, Hotkey::new('C', "Columns"), KeyAction::ConnectionColumns),
+    help(CONNECTIONS, Hotkey::new('u', "Copy URI"), KeyAction::CopyConnectionUri),
+    bar(SCHEMAS_AND_TABLES, Hotkey::new('x', "Export DDL"), KeyAction::ExportDdl),
+    bar(TABLES_AND_COLUMNS, Hotkey::new('C', "Compare tables"), KeyAction::CompareTables),
+    bar(SCHEMAS_AND_TABLES, Hotkey::new('D', "Disk usage"), KeyAction::DiskUsage),
+    bar(TABLE, Hotkey::new('F', "FK orphans"), KeyAction::FkOrphans),
+    bar(COLUMNS, Hotkey::new('t', "Table data"), KeyAction::ToggleTableView),
+    bar(TABLE_DATA, Hotkey::new('t', "Table structure"), KeyAction::ToggleTableView),
+    bar(TABLE_DATA, Hotkey::new('r', "Refresh"), KeyAction::Refresh),
+    help(TABLE_DATA, Hotkey::new('R', "Refresh"), KeyAction::Refresh),
+    bar(TABLE_DATA, Hotkey::new('a', "New row"), KeyAction::NewRow),
+    help(TABLE_DATA, Hotkey::new('A', "New row"), KeyAction::NewRow),
+    help(TABLE_DATA, Hotkey::new('c', "Copy row"), KeyAction::CopyRow),
+    help(TABLE_DATA, Hotkey::new('C', "Copy row"), KeyAction::CopyRow),
+    bar(TABLE_DATA, Hotkey::new('s', "Commit row"), KeyAction::CommitRow),
+    help(TABLE_DATA, Hotkey::new('S', "Commit row"), KeyAction::CommitRow),
+    bar(TABLE_DATA, Hotkey::new('d', "Delete row"), KeyAction::DeleteRow),
+    help(TABLE_DATA, Hotkey::new('D', "Delete row"), KeyAction::DeleteRow),
+    help(TABLE_DATA, Hotkey::new(' ', "Select row"), KeyAction::MultiSelect),
+    help(TABLE_DATA, Hotkey::new('v', "Save view"), KeyAction::SaveView),
+    help(TABLE_DATA, Hotkey::new('-', "Hide column"), KeyAction::HideColumn),
+    help(TABLE_DATA, Hotkey::new('+', "Show all columns"), KeyAction::ShowAllColumns),
+    help(TABLE_DATA, Hotkey::new('=', "Show all columns"), KeyAction::ShowAllColumns),
+    help(TABLE_DATA, Hotkey::new('z', "Expand column"), KeyAction::ExpandColumn),
+    help(TABLE_DATA, Hotkey::new('>', "Widen column"), KeyAction::ResizeColumn { wider: true }),
+    help(TABLE_DATA, Hotkey::new('<', "Narrow column"), KeyAction::ResizeColumn { wider: false }),
+    help(TABLE_DATA, Hotkey::new('m', "Multi-line"), KeyAction::MultiLine),
+    help(TABLE_DATA, Hotkey::new(';', "Row note"), KeyAction::RowNote),
+    bar(TABLE_DATA, Hotkey::new('o', "Order by"), KeyAction::OrderBy),
+    help(TABLE_DATA, Hotkey::new('N', "NULLs first/last"), KeyAction::NullsOrder),
+    help(TABLE_DATA, Hotkey::new('X', "Duplicates"), KeyAction::Duplicates),
+    help(TABLE_DATA, Hotkey::new('*', "Row colors"), KeyAction::RowColors),
+    help(TABLE_DATA, Hotkey::new('?', "Search column"), KeyAction::SearchColumn),
+    help(TABLE_DATA, Hotkey::new('Y', "Show query"), KeyAction::ShowQuery),
+    help(TABLE_DATA, Hotkey::new('E', "Query to SQL editor"), KeyAction::QueryToEditor),
+    help(TABLE_DATA, Hotkey::new('[', "Keyset paging"), KeyAction::KeysetPaging),
+    help(TABLE_DATA, Hotkey::new(']', "Jump to key"), KeyAction::JumpToKey),
+    help(TABLE_DATA, Hotkey::new('\'', "Go to row"), KeyAction::GoToRow),
+    help(TABLE_DATA, Hotkey::new('&', "Copy link"), KeyAction::CopyLink),
+    help(TABLE_DATA, Hotkey::new('\\', "Clear filter/order"), KeyAction::ClearFilterOrder),
+    bar(SQL_RESULTS, Hotkey::new('E', "Run SQL"), KeyAction::RunSql),
+    bar(SQL_RESULTS, Hotkey::new('r', "Run again"), KeyAction::RunAgain),
+    help(SQL_RESULTS, Hotkey::new('B', "Plan baseline"), KeyAction::PlanBaseline),
+    help(SQL_RESULTS, Hotkey::new('I', "Index hints"), KeyAction::IndexHints),
+    help(SQL_RESULTS, Hotkey::new('%', "Benchmark"), KeyAction::Benchmark),
+    help(SQL_RESULTS, Hotkey::new('{', "Block insert"), KeyAction::BlockInsert),
+    help(SQL_RESULTS, Hotkey::new('}', "Line command"), KeyAction::LineCommand),
+    bar(SQL_RESULTS, Hotkey::new('s', "Sort"), KeyAction::SortResults),
+    help(SQL_RESULTS, Hotkey::new('!', "Distinct rows"), KeyAction::DistinctRows),
+    help(SQL_RESULTS, Hotkey::new(' ', "Mark row"), KeyAction::MarkRow),
+    bar(SQL_RESULTS, Hotkey::new('f', "Fetch more"), KeyAction::FetchMore),
+    bar(SQL_RESULTS, Hotkey::new('x', "Export"), KeyAction::ExportResults),
+    help(ROWS, Hotkey::new('p', "Pin row"), KeyAction::PinRow),
+    help(ROWS, Hotkey::new('_', "Wrap column"), KeyAction::WrapColumn),
+    help(ROWS, Hotkey::ctrl('y', "Copy IN list"), KeyAction::CopyInList),
+    help(ROWS, Hotkey::new('J', "JSON path"), KeyAction::JsonPath),
+    bar(CONNECTED, Hotkey::new('e', "SQL Editor"), KeyAction::SqlEditor),
+    bar(CONNECTED, Hotkey::ctrl('p', "Go to table"), KeyAction::GoToTable),
+    bar(CONNECTED, Hotkey::new('V', "Views"), KeyAction::Views),
+    help(CONNECTED, Hotkey::new('O', "Recent"), KeyAction::RecentTables),
+    help(CONNECTED, Hotkey::new('1', "Recent table 1"), KeyAction::OpenRecentTable(0)),
+    help(CONNECTED, Hotkey::new('2', "Recent table 2"), KeyAction::OpenRecentTable(1)),
+    help(CONNECTED, Hotkey::new('3', "Recent table 3"), KeyAction::OpenRecentTable(2)),
+    help(CONNECTED, Hotkey::new('4', "Recent table 4"), KeyAction::OpenRecentTable(3)),
+    help(CONNECTED, Hotkey::new('5', "Recent table 5"), KeyAction::OpenRecentTable(4)),
+    help(CONNECTED, Hotkey::new('L', "Result history"), KeyAction::ResultHistory),
+    help(CONNECTED, Hotkey::new(':', "Run .sql file"), KeyAction::RunSqlFile),
+    help(CONNECTED, Hotkey::new('(', "Savepoint"), KeyAction::Savepoint),
+    help(CONNECTED, Hotkey::new(')', "Roll back to savepoint"), KeyAction::RollbackToSavepoint),
+    help(CONNECTED, Hotkey::new('~', "Diff rows"), KeyAction::DiffRows),
+    help(CONNECTED, Hotkey::new('#', "Column stats"), KeyAction::ColumnStats),
+    help(CONNECTED, Hotkey::new('H', "Histogram"), KeyAction::Histogram),
+    help(CONNECTED, Hotkey::new('|', "Group by"), KeyAction::GroupBy),
+    help(CONNECTED, Hotkey::new('K', "Keyword case"), KeyAction::KeywordCase),
+    help(CONNECTED, Hotkey::new('M', "Mask secrets"), KeyAction::MaskSecrets),
+    help(CONNECTED, Hotkey::new(',', "Group digits"), KeyAction::GroupDigits),
+    help(CONNECTED, Hotkey::new('.', "Relative times"), KeyAction::RelativeTimes),
+    help(CONNECTED, Hotkey::new('P', "psql"), KeyAction::Psql),
+    help(CONNECTED, Hotkey::new('W', "search_path"), KeyAction::SearchPath),
+    help(CONNECTED, Hotkey::new('U', "Top queries"), KeyAction::TopQueries),
+    bar(CONNECTED, Hotkey::ctrl('d', "Disconnect"), KeyAction::Disconnect),
+    bar(EVERYWHERE, Hotkey::new('/', "Search"), KeyAction::Search),
+    bar(EVERYWHERE, Hotkey::new('y', "Copy value"), KeyAction::Copy),
+    help(EVERYWHERE, Hotkey::key(KeyCode::Enter, "Open"), KeyAction::Open),
+    help(EVERYWHERE, Hotkey::key(KeyCode::Esc, "Back / clear"), KeyAction::Back),
+    help(EVERYWHERE, Hotkey::new('j', "Down"), KeyAction::Move(KeyCode::Down)),
+    help(EVERYWHERE, Hotkey::key(KeyCode::Down, "Down"), KeyAction::Move(KeyCode::Down)),
+    help(EVERYWHERE, Hotkey::new('k', "Up"), KeyAction::Move(KeyCode::Up)),
+    help(EVERYWHERE, Hotkey::key(KeyCode::Up, "Up"), KeyAction::Move(KeyCode::Up)),
+    help(EVERYWHERE, Hotkey::new('h', "Left"), KeyAction::Move(KeyCode::Left)),
+    help(EVERYWHERE, Hotkey::new('b', "Left"), KeyAction::Move(KeyCode::Left)),
+    help(EVERYWHERE, Hotkey::key(KeyCode::Left, "Left"), KeyAction::Move(KeyCode::Left)),
+    help(EVERYWHERE, Hotkey::new('l', "Right"), KeyAction::Move(KeyCode::Right)),
+    help(EVERYWHERE, Hotkey::new('w', "Right"), KeyAction::Move(KeyCode::Right)),
+    help(EVERYWHERE, Hotkey::key(KeyCode::Right, "Right"), KeyAction::Move(KeyCode::Right)),
+    help(EVERYWHERE, Hotkey::new('0', "First column"), KeyAction::Move(KeyCode::Char('0'))),
+    help(EVERYWHERE, Hotkey::new('$', "Last column"), KeyAction::Move(KeyCode::Char('$'))),
+    help(EVERYWHERE, Hotkey::new('g', "Top"), KeyAction::Move(KeyCode::Char('g'))),
+    help(EVERYWHERE, Hotkey::new('G', "Bottom"), KeyAction::Move(KeyCode::Char('G'))),
+    help(EVERYWHERE, Hotkey::new('Q', "Record macro (Q<reg>)"), KeyAction::Macro),
+    help(EVERYWHERE, Hotkey::new('@', "Replay macro (@<reg>)"), KeyAction::Macro),
+    help(EVERYWHERE, Hotkey::new('Z', "Compact top bar"), KeyAction::CycleCompactMode),
+    help(EVERYWHERE, Hotkey::alt(KeyCode::Up, "Shrink top bar"), KeyAction::ResizeTopBar { grow: false }),
+    help(EVERYWHERE, Hotkey::alt(KeyCode::Down, "Grow top bar"), KeyAction::ResizeTopBar { grow: true }),
+    help(EVERYWHERE, Hotkey::alt(KeyCode::Char('a'), "Accessible mode"), KeyAction::ToggleAccessibleMode),
+    help(EVERYWHERE, Hotkey::new('q', "Quit"), KeyAction::Quit),
+    help(EVERYWHERE, Hotkey::ctrl('c', "Quit"), KeyAction::Quit),
+    bar(EVERYWHERE, Hotkey::key(KeyCode::F(1), "All keys"), KeyAction::KeyHelp),
+];
+
+/// The keys that work on `screen`, in the order they're listed.
+pub fn bindings(screen: Screen) -> impl Iterator<Item = &'static KeyBinding> {
+    KEYMAP
+        .iter()
+        .filter(move |binding| binding.screens.contains(&screen))
+}
+
+/// What `key` does on what `state` browses, if anything.
+#[must_use]
+pub fn action_for(
+    state: &DatabaseExplorerState,
+    key: KeyEvent,
+) -> Option<KeyAction> {
+    bindings(Screen::of(state))
+        .find(|binding| binding.hotkey.matches(key))
+        .map(|binding| binding.action)
+}
+
+const CONNECTION_MODAL_HOTKEYS: [Hotkey; 4] = [
+    Hotkey::key(KeyCode::Tab, "Next field"),
+    Hotkey::key(KeyCode::Enter, "Continue / press"),
+    Hotkey::ctrl('y', "Copy URI"),
+    Hotkey::key(KeyCode::Esc, "Cancel"),
+];
+
+/// Confirmation modals with a pair of buttons.
+const CONFIRM_MODAL_HOTKEYS: [Hotkey; 3] = [
+    Hotkey::key(KeyCode::Left, "Previous button"),
+    Hotkey::key(KeyCode::Right, "Next button"),
+    Hotkey::key(KeyCode::Enter, "Press"),
+];
+
+const LIST_MODAL_HOTKEYS: [Hotkey; 4] = [
+    Hotkey::new('j', "Down"),
+    Hotkey::new('k', "Up"),
+    Hotkey::key(KeyCode::Enter, "Select"),
+    Hotkey::key(KeyCode::Esc, "Cancel"),
+];
+
+const CHOICE_DELETE_HOTKEY: Hotkey = Hotkey::new('d', "Delete");
+
+const CELL_VALUE_MODAL_HOTKEYS: [Hotkey; 3] = [
+    Hotkey::key(KeyCode::Tab, "Editor / buttons"),
+    Hotkey::key(KeyCode::Enter, "Newline / press"),
+    Hotkey::key(KeyCode::Esc, "Cancel"),
+];
+
+const PASSWORD_MODAL_HOTKEYS: [Hotkey; 3] = [
+    Hotkey::key(KeyCode::Tab, "Buttons"),
+    Hotkey::key(KeyCode::Enter, "Connect / press"),
+    Hotkey::key(KeyCode::Esc, "Cancel"),
+];
+
+const INFO_MODAL_HOTKEYS: [Hotkey; 4] = [
+    Hotkey::new('j', "Scroll down"),
+    Hotkey::new('k', "Scroll up"),
+    Hotkey::new('g', "Top"),
+    Hotkey::key(KeyCode::Esc, "Close"),
+];
+
+const INPUT_MODAL_HOTKEYS: [Hotkey; 3] = [
+    Hotkey::key(KeyCode::Enter, "Submit"),
+    Hotkey::key(KeyCode::Tab, "Complete"),
+    Hotkey::key(KeyCode::Esc, "Cancel"),
+];
+
+/// What the keyboard is acting on, which decides the hotkeys listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyContext<'a> {
+    /// The open modal takes every key.
+    Modal {
+        kind: ModalType,
+        /// Whether a choice modal's options can be deleted with `d`.
+        allow_delete: bool,
+    },
+    Explorer(&'a DatabaseExplorerState),
+}
+
+impl HotkeyContext<'_> {
+    /// Hotkeys for the context, in the order they're listed.
+    #[must_use]
+    pub fn hotkeys(self) -> Vec<Hotkey> {
+        match self {
+            Self::Modal { kind, allow_delete } => {
+                modal_hotkeys(kind, allow_delete)
+            }
+            Self::Explorer(state) => explorer_hotkeys(state),
+        }
+    }
+}
+
+fn modal_hotkeys(kind: ModalType, allow_delete: bool) -> Vec<Hotkey> {
+    let hotkeys: &[Hotkey] = match kind {
+        ModalType::Connection => &CONNECTION_MODAL_HOTKEYS,
+        ModalType::Confirmation | ModalType::SqlExecutionConfirmation => {
+            &CONFIRM_MODAL_HOTKEYS
+        }
+        ModalType::SqlQuerySelection => &LIST_MODAL_HOTKEYS,
+        ModalType::Choice if allow_delete => {
+            return LIST_MODAL_HOTKEYS
+                .iter()
+                .chain(std::iter::once(&CHOICE_DELETE_HOTKEY))
+                .cloned()
+                .collect();
+        }
+        ModalType::Choice => &LIST_MODAL_HOTKEYS,
+        ModalType::CellValue => &CELL_VALUE_MODAL_HOTKEYS,
+        ModalType::Password => &PASSWORD_MODAL_HOTKEYS,
+        ModalType::Info => &INFO_MODAL_HOTKEYS,
+        ModalType::Input => &INPUT_MODAL_HOTKEYS,
+    };
+    hotkeys.to_vec()
+}
+
+fn explorer_hotkeys(state: &DatabaseExplorerState) -> Vec<Hotkey> {
+    bindings(Screen::of(state))
+        .filter(|binding| binding.in_bar)
+        .map(|binding| binding.hotkey.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(context: HotkeyContext<'_>) -> Vec<String> {
+        context.hotkeys().iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn lists_hotkeys_for_the_context() {
+        let connections = keys(HotkeyContext::Explorer(
+            &DatabaseExplorerState::Connections,
+        ));
+        assert_eq!(connections.concat(), "nedo:tT/yF1");
+
+        let schemas =
+            keys(HotkeyContext::Explorer(&DatabaseExplorerState::Schemas));
+        assert!(schemas.starts_with(&["x".to_string(), "D".to_string()]));
+        assert!(schemas.contains(&"Ctrl+d".to_string()));
+        assert!(!schemas.contains(&"t".to_string()));
+
+        let choice = HotkeyContext::Modal {
+            kind: ModalType::Choice,
+            allow_delete: true,
+        };
+        assert_eq!(keys(choice).concat(), "jkEnterEscd");
+        let connection_form = HotkeyContext::Modal {
+            kind: ModalType::Connection,
+            allow_delete: false,
+        };
+        assert!(keys(connection_form).contains(&"Ctrl+y".to_string()));
+    }
+
+    #[test]
+    fn binds_each_key_once_per_screen() {
+        for screen in Screen::ALL {
+            let mut seen = std::collections::HashSet::new();
+            for binding in bindings(screen) {
+                let hotkey = &binding.hotkey;
+                assert!(
+                    seen.insert((hotkey.keycode, hotkey.modifiers)),
+                    "{hotkey} is bound twice on {screen:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn finds_the_action_of_a_key() {
+        use crossterm::event::KeyModifiers;
+
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let connections = DatabaseExplorerState::Connections;
+        let schemas = DatabaseExplorerState::Schemas;
+        assert_eq!(
+            action_for(&connections, key(KeyCode::Char('e'))),
+            Some(KeyAction::EditConnection)
+        );
+        assert_eq!(
+            action_for(&schemas, key(KeyCode::Char('e'))),
+            Some(KeyAction::SqlEditor)
+        );
+        // Keys of other screens do nothing
+        assert_eq!(action_for(&connections, key(KeyCode::Char('x'))), None);
+        assert_eq!(
+            action_for(
+                &schemas,
+                KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT)
+            ),
+            Some(KeyAction::Move(KeyCode::Char('G')))
+        );
+        assert_eq!(
+            action_for(
+                &schemas,
+                KeyEvent::new(
+                    KeyCode::Char('C'),
+                    KeyModifiers::CONTROL | KeyModifiers::SHIFT
+                )
+            ),
+            Some(KeyAction::Quit)
+        );
+        assert_eq!(
+            action_for(&schemas, KeyEvent::new(KeyCode::Up, KeyModifiers::ALT)),
+            Some(KeyAction::ResizeTopBar { grow: false })
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use ratatui::layout::Rect;
+
+/// Top bar height before it's resized.
+pub const DEFAULT_TOP_BAR_HEIGHT: u16 = 6;
+/// Tallest the top bar can be made; 0 hides it.
+pub const MAX_TOP_BAR_HEIGHT: u16 = 16;
+
+/// Setting key the top bar height is stored under.
+pub const TOP_BAR_HEIGHT_SETTING: &str = "layout.top_bar_height";
+/// Setting key the compact mode is stored under.
+pub const COMPACT_MODE_SETTING: &str = "layout.compact";
+/// Terminals shorter than this get the compact top bar in auto mode.
+pub const COMPACT_HEIGHT_THRESHOLD: u16 = 24;
+/// Narrowest terminal the screen is drawn on; below it, or below
+/// [`MIN_TERMINAL_HEIGHT`], only a note to enlarge it is shown.
+pub const MIN_TERMINAL_WIDTH: u16 = 40;
+/// Shortest terminal the screen is drawn on.
+pub const MIN_TERMINAL_HEIGHT: u16 = 10;
+
+/// Whether `area` is too small to draw the screen in.
+#[must_use]
+pub const fn is_too_small(area: Rect) -> bool {
+    area.width < MIN_TERMINAL_WIDTH || area.height < MIN_TERMINAL_HEIGHT
+}
+
+/// A `width` by `height` box centered in `area`, shrunk to leave a cell
+/// free on each side when `area` is too small for it, so a modal never
+/// reaches past the edge of the terminal.
+#[must_use]
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width.saturating_sub(2));
+    let height = height.min(area.height.saturating_sub(2));
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// When the top bar collapses to a single line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompactMode {
+    /// Compact on terminals shorter than [`COMPACT_HEIGHT_THRESHOLD`].
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+impl CompactMode {
+    /// Next step of the toggle: auto, on, off.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Auto => Self::On,
+            Self::On => Self::Off,
+            Self::Off => Self::Auto,
+        }
+    }
+
+    /// Stored form, also shown in the status line.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::On => "on",
+            Self::Off => "off",
+        }
+    }
+
+    #[must_use]
+    pub fn from_saved(saved: Option<&str>) -> Self {
+        match saved.map(str::trim) {
+            Some("on") => Self::On,
+            Some("off") => Self::Off,
+            _ => Self::Auto,
+        }
+    }
+}
+
+/// Sizes of the screen regions that can be resized from the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutSettings {
+    pub top_bar_height: u16,
+    pub compact: CompactMode,
+}
+
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        Self {
+            top_bar_height: DEFAULT_TOP_BAR_HEIGHT,
+            compact: CompactMode::Auto,
+        }
+    }
+}
+
+impl LayoutSettings {
+    /// Restore from saved settings, ignoring heights out of range.
+    #[must_use]
+    pub fn from_saved(
+        top_bar_height: Option<&str>,
+        compact: Option<&str>,
+    ) -> Self {
+        let top_bar_height = top_bar_height
+            .and_then(|height| height.trim().parse::<u16>().ok())
+            .filter(|height| *height <= MAX_TOP_BAR_HEIGHT)
+            .unwrap_or(DEFAULT_TOP_BAR_HEIGHT);
+        Self {
+            top_bar_height,
+            compact: CompactMode::from_saved(compact),
+        }
+    }
+
+    /// Whether the top bar is collapsed on a terminal `terminal_height`
+    /// rows tall.
+    #[must_use]
+    pub const fn is_compact(self, terminal_height: u16) -> bool {
+        match self.compact {
+            CompactMode::Auto => terminal_height < COMPACT_HEIGHT_THRESHOLD,
+            CompactMode::On => true,
+            CompactMode::Off => false,
+        }
+    }
+
+    /// Rows the top bar takes; a hidden bar stays hidden in compact mode.
+    #[must_use]
+    pub fn top_bar_rows(self, terminal_height: u16) -> u16 {
+        if self.is_compact(terminal_height) {
+            self.top_bar_height.min(1)
+        } else {
+            self.top_bar_height
+        }
+    }
+
+    /// Grow or shrink the top bar by one row. Returns whether it changed.
+    pub fn resize_top_bar(&mut self, grow: bool) -> bool {
+        let height = if grow {
+            self.top_bar_height
+                .saturating_add(1)
+                .min(MAX_TOP_BAR_HEIGHT)
+        } else {
+            self.top_bar_height.saturating_sub(1)
+        };
+        let changed = height != self.top_bar_height;
+        self.top_bar_height = height;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resizes_within_bounds() {
+        let mut layout = LayoutSettings {
+            top_bar_height: 1,
+            ..LayoutSettings::default()
+        };
+        assert!(layout.resize_top_bar(false));
+        assert!(!layout.resize_top_bar(false));
+        assert_eq!(layout.top_bar_height, 0);
+
+        layout.top_bar_height = MAX_TOP_BAR_HEIGHT;
+        assert!(!layout.resize_top_bar(true));
+    }
+
+    #[test]
+    fn restores_saved_settings() {
+        let restored = LayoutSettings::from_saved(Some("9"), Some("off"));
+        assert_eq!(restored.top_bar_height, 9);
+        assert_eq!(restored.compact, CompactMode::Off);
+        assert_eq!(
+            LayoutSettings::from_saved(Some("999"), None),
+            LayoutSettings::default()
+        );
+    }
+
+    #[test]
+    fn compacts_small_terminals_in_auto_mode() {
+        let mut layout = LayoutSettings::default();
+        assert_eq!(layout.top_bar_rows(COMPACT_HEIGHT_THRESHOLD - 1), 1);
+        assert_eq!(
+            layout.top_bar_rows(COMPACT_HEIGHT_THRESHOLD),
+            DEFAULT_TOP_BAR_HEIGHT
+        );
+        layout.compact = CompactMode::Off;
+        assert!(!layout.is_compact(10));
+        layout.top_bar_height = 0;
+        layout.compact = CompactMode::On;
+        assert_eq!(layout.top_bar_rows(50), 0);
+    }
+
+    #[test]
+    fn centers_within_small_terminals() {
+        let area = Rect::new(0, 0, 80, 24);
+        assert_eq!(centered(area, 50, 8), Rect::new(15, 8, 50, 8));
+        let small = Rect::new(0, 0, 30, 6);
+        assert_eq!(centered(small, 50, 8), Rect::new(1, 1, 28, 4));
+        assert_eq!(centered(Rect::new(3, 2, 1, 1), 50, 8).area(), 0);
+        assert!(is_too_small(small));
+        assert!(!is_too_small(area));
+    }
+}
@@ -1,4 +1,10 @@
+pub mod accessible;
 pub mod handlers;
+pub mod keymap;
+pub mod layout;
+pub mod number_format;
+pub mod palette;
+pub mod relative_time;
 pub mod widgets;
 
 pub use widgets::*;
@@ -0,0 +1,141 @@
+//! Thousands separators and the locale's decimal mark for numbers in the
+//! grid. Only what is drawn changes: copying, editing and exports keep the
+//! values as the database returned them.
+
+/// How numbers in the grid are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberFormat {
+    /// As the database returned them.
+    #[default]
+    Raw,
+    /// Digits grouped in threes by `thousands`, with `decimal` before the
+    /// fraction.
+    Grouped { thousands: char, decimal: char },
+}
+
+impl NumberFormat {
+    /// Grouping used in `locale`, e.g. `de_DE.UTF-8`; English grouping for
+    /// `C`, `POSIX` and locales not known to differ.
+    #[must_use]
+    pub fn for_locale(locale: Option<&str>) -> Self {
+        let name = locale
+            .and_then(|l| l.split(['.', '@']).next())
+            .unwrap_or_default();
+        let (language, region) = name.split_once('_').unwrap_or((name, ""));
+        let (thousands, decimal) = match (language, region) {
+            ("de" | "fr" | "it" | "rm", "CH" | "LI") => ('\'', '.'),
+            (
+                "de" | "es" | "it" | "pt" | "nl" | "id" | "tr" | "da" | "el"
+                | "ro" | "hr" | "sl" | "sr",
+                _,
+            ) => ('.', ','),
+            (
+                "fr" | "ru" | "pl" | "sv" | "nb" | "nn" | "no" | "fi" | "cs"
+                | "sk" | "uk" | "hu" | "bg" | "lt" | "lv" | "et",
+                _,
+            ) => (' ', ','),
+            _ => (',', '.'),
+        };
+        Self::Grouped { thousands, decimal }
+    }
+
+    /// [`Self::for_locale`] for the locale numbers are formatted in:
+    /// `LC_ALL`, else `LC_NUMERIC`, else `LANG`.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .into_iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty());
+        Self::for_locale(locale.as_deref())
+    }
+
+    /// `value` as drawn, or `None` when it is drawn as it is: without
+    /// grouping, or when it isn't a plain decimal number. Numbers with a
+    /// leading zero, such as `007`, are taken to be codes and left alone.
+    #[must_use]
+    pub fn format(self, value: &str) -> Option<String> {
+        let Self::Grouped { thousands, decimal } = self else {
+            return None;
+        };
+        let (sign, unsigned) = match value.strip_prefix(['-', '+']) {
+            Some(rest) => (value.get(..1).unwrap_or_default(), rest),
+            None => ("", value),
+        };
+        let (int, fraction) = match unsigned.split_once('.') {
+            Some((int, fraction)) => (int, Some(fraction)),
+            None => (unsigned, None),
+        };
+        let digits = |s: &str| {
+            !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+        };
+        if !digits(int)
+            || fraction.is_some_and(|f| !digits(f))
+            || (int.len() > 1 && int.starts_with('0'))
+        {
+            return None;
+        }
+
+        let mut formatted = String::from(sign);
+        for (i, digit) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0 {
+                formatted.push(thousands);
+            }
+            formatted.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            formatted.push(decimal);
+            formatted.push_str(fraction);
+        }
+        Some(formatted)
+    }
+}
+
+/// Whether values of a column of type `name` are numbers.
+#[must_use]
+pub fn is_numeric_type(name: &str) -> bool {
+    matches!(
+        name.trim().to_ascii_lowercase().as_str(),
+        "int2"
+            | "int4"
+            | "int8"
+            | "float4"
+            | "float8"
+            | "numeric"
+            | "decimal"
+            | "smallint"
+            | "integer"
+            | "bigint"
+            | "real"
+            | "double precision"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_digits_for_the_locale() {
+        let english = NumberFormat::for_locale(Some("en_GB.UTF-8"));
+        assert_eq!(english.format("1234567.891").unwrap(), "1,234,567.891");
+        assert_eq!(english.format("-1000").unwrap(), "-1,000");
+        assert_eq!(english.format("999").unwrap(), "999");
+
+        let german = NumberFormat::for_locale(Some("de_DE.UTF-8"));
+        assert_eq!(german.format("12345.5").unwrap(), "12.345,5");
+        let swiss = NumberFormat::for_locale(Some("de_CH"));
+        assert_eq!(swiss.format("12345.5").unwrap(), "12'345.5");
+        let french = NumberFormat::for_locale(Some("fr_FR@euro"));
+        assert_eq!(french.format("0.25").unwrap(), "0,25");
+    }
+
+    #[test]
+    fn leaves_other_values_alone() {
+        let english = NumberFormat::for_locale(None);
+        for value in ["NULL", "1e10", "007", "12.", "1.2.3", "", "-", "abc"] {
+            assert_eq!(english.format(value), None, "{value}");
+        }
+        assert_eq!(NumberFormat::Raw.format("1234"), None);
+    }
+}
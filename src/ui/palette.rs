@@ -0,0 +1,172 @@
+//! Colors for terminals that show fewer than the 16 ANSI colors d7s draws
+//! with. Each frame is drawn as usual, then its colors are mapped down to
+//! the ones the terminal has.
+
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+
+/// Colors the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Palette {
+    /// The 16 ANSI colors, drawn as they are.
+    #[default]
+    Ansi16,
+    /// The 8 base colors, for consoles without the bright ones.
+    Ansi8,
+    /// No colors; highlighted cells are drawn reversed instead.
+    Monochrome,
+}
+
+impl Palette {
+    /// The palette named `16`, `8` or `none`.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "16" => Some(Self::Ansi16),
+            "8" => Some(Self::Ansi8),
+            "none" | "0" => Some(Self::Monochrome),
+            _ => None,
+        }
+    }
+
+    /// Guess the palette from `TERM`, `COLORTERM` and whether `NO_COLOR`
+    /// is set. Terminals announcing truecolor or 256 colors, and ones not
+    /// known to have fewer, get all 16.
+    #[must_use]
+    pub fn detect(
+        term: Option<&str>,
+        colorterm: Option<&str>,
+        no_color: bool,
+    ) -> Self {
+        if no_color {
+            return Self::Monochrome;
+        }
+        if colorterm.is_some_and(|c| matches!(c, "truecolor" | "24bit")) {
+            return Self::Ansi16;
+        }
+        match term.map(str::trim) {
+            None
+            | Some(
+                "" | "dumb" | "vt52" | "vt100" | "vt102" | "vt220" | "vt320",
+            ) => Self::Monochrome,
+            Some("linux" | "ansi" | "cons25" | "sun") => Self::Ansi8,
+            Some(_) => Self::Ansi16,
+        }
+    }
+
+    /// [`Self::detect`] for the running process.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let term = std::env::var("TERM").ok();
+        let colorterm = std::env::var("COLORTERM").ok();
+        let no_color =
+            std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        Self::detect(term.as_deref(), colorterm.as_deref(), no_color)
+    }
+
+    /// Map the colors of a drawn frame to the palette.
+    pub fn apply(self, buf: &mut Buffer) {
+        match self {
+            Self::Ansi16 => {}
+            Self::Ansi8 => {
+                for cell in &mut buf.content {
+                    cell.fg = base_color(cell.fg, false);
+                    cell.bg = base_color(cell.bg, true);
+                }
+            }
+            Self::Monochrome => {
+                for cell in &mut buf.content {
+                    if cell.bg != Color::Reset {
+                        cell.modifier.insert(Modifier::REVERSED);
+                    }
+                    cell.fg = Color::Reset;
+                    cell.bg = Color::Reset;
+                }
+            }
+        }
+    }
+}
+
+/// The base color closest to `color`. A dark gray background, used for
+/// highlights, becomes blue so it still stands out from black.
+const fn base_color(color: Color, background: bool) -> Color {
+    match color {
+        Color::LightRed => Color::Red,
+        Color::LightGreen => Color::Green,
+        Color::LightYellow => Color::Yellow,
+        Color::LightBlue => Color::Blue,
+        Color::LightMagenta => Color::Magenta,
+        Color::LightCyan => Color::Cyan,
+        Color::DarkGray if background => Color::Blue,
+        Color::White | Color::DarkGray => Color::Gray,
+        Color::Reset
+        | Color::Black
+        | Color::Red
+        | Color::Green
+        | Color::Yellow
+        | Color::Blue
+        | Color::Magenta
+        | Color::Cyan
+        | Color::Gray
+        | Color::Rgb(..)
+        | Color::Indexed(_) => color,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{layout::Rect, style::Style};
+
+    use super::*;
+
+    #[test]
+    fn detects_the_palette_from_the_environment() {
+        assert_eq!(
+            Palette::detect(Some("xterm-256color"), None, false),
+            Palette::Ansi16
+        );
+        assert_eq!(Palette::detect(Some("linux"), None, false), Palette::Ansi8);
+        assert_eq!(
+            Palette::detect(Some("linux"), Some("truecolor"), false),
+            Palette::Ansi16
+        );
+        assert_eq!(
+            Palette::detect(Some("vt220"), None, false),
+            Palette::Monochrome
+        );
+        assert_eq!(
+            Palette::detect(Some("xterm"), None, true),
+            Palette::Monochrome
+        );
+        assert_eq!(Palette::parse(" 8 "), Some(Palette::Ansi8));
+        assert_eq!(Palette::parse("256"), None);
+    }
+
+    #[test]
+    fn maps_drawn_colors_to_the_palette() {
+        let area = Rect::new(0, 0, 2, 1);
+        let mut buf = Buffer::empty(area);
+        buf.set_string(0, 0, "a", Style::default().fg(Color::LightGreen));
+        buf.set_string(
+            1,
+            0,
+            "b",
+            Style::default().fg(Color::Yellow).bg(Color::DarkGray),
+        );
+
+        let mut basic = buf.clone();
+        Palette::Ansi8.apply(&mut basic);
+        assert_eq!(basic.cell((0, 0)).unwrap().fg, Color::Green);
+        assert_eq!(basic.cell((1, 0)).unwrap().bg, Color::Blue);
+
+        Palette::Monochrome.apply(&mut buf);
+        let plain = buf.cell((0, 0)).unwrap();
+        assert_eq!(plain.fg, Color::Reset);
+        assert!(!plain.modifier.contains(Modifier::REVERSED));
+        let highlighted = buf.cell((1, 0)).unwrap();
+        assert_eq!(highlighted.bg, Color::Reset);
+        assert!(highlighted.modifier.contains(Modifier::REVERSED));
+    }
+}
@@ -0,0 +1,144 @@
+//! Timestamps in the grid as how long ago they were, e.g. `3 h ago`. Only
+//! what is drawn changes: the value editor, copies and exports keep the
+//! exact value.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, NaiveDateTime};
+
+use crate::sort::parse_temporal;
+
+/// How timestamps in the grid are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeDisplay {
+    /// As the database returned them.
+    #[default]
+    Exact,
+    /// As the time from now, e.g. `3 h ago` or `in 5 min`.
+    Relative,
+}
+
+impl TimeDisplay {
+    #[must_use]
+    pub const fn toggled(self) -> Self {
+        match self {
+            Self::Exact => Self::Relative,
+            Self::Relative => Self::Exact,
+        }
+    }
+}
+
+/// The current time in UTC, which timestamps without a zone are taken to
+/// be in.
+#[must_use]
+pub fn now_utc() -> NaiveDateTime {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    i64::try_from(since_epoch.as_secs())
+        .ok()
+        .and_then(|secs| {
+            DateTime::from_timestamp(secs, since_epoch.subsec_nanos())
+        })
+        .unwrap_or_default()
+        .naive_utc()
+}
+
+/// `value` as the time from `now`, or `None` when it isn't a timestamp:
+/// dates and times of day on their own are drawn as they are.
+#[must_use]
+pub fn relative_time(value: &str, now: NaiveDateTime) -> Option<String> {
+    if !is_timestamp(value) {
+        return None;
+    }
+    let then = parse_temporal(value)?;
+    // Rounded to the nearest second, so 2 d 23:59:59.9 reads as 3 d
+    let millis = (now - then).num_milliseconds();
+    let amount = match (millis.unsigned_abs() + 500) / 1_000 {
+        0..10 => return Some("just now".to_string()),
+        s @ 10..60 => format!("{s} s"),
+        s @ 60..3_600 => format!("{} min", s / 60),
+        s @ 3_600..86_400 => format!("{} h", s / 3_600),
+        s @ 86_400..31_536_000 => format!("{} d", s / 86_400),
+        s => format!("{} y", s / 31_536_000),
+    };
+    Some(if millis < 0 {
+        format!("in {amount}")
+    } else {
+        format!("{amount} ago")
+    })
+}
+
+/// Whether `value` has both a date and a time of day.
+fn is_timestamp(value: &str) -> bool {
+    let value = value.trim();
+    value.get(4..5) == Some("-") && value.contains(':')
+}
+
+/// Whether values of a column of type `name` are timestamps.
+#[must_use]
+pub fn is_timestamp_type(name: &str) -> bool {
+    matches!(
+        name.trim().to_ascii_lowercase().as_str(),
+        "timestamp"
+            | "timestamptz"
+            | "timestamp with time zone"
+            | "timestamp without time zone"
+            | "datetime"
+    )
+}
+
+/// Whether `value` is drawn as a relative time, for columns without a
+/// known type.
+#[must_use]
+pub fn is_timestamp_value(value: &str) -> bool {
+    is_timestamp(value) && parse_temporal(value).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> NaiveDateTime {
+        parse_temporal(value).unwrap()
+    }
+
+    #[test]
+    fn draws_the_time_from_now() {
+        let now = at("2026-05-10 12:00:00");
+        assert_eq!(
+            relative_time("2026-05-10 09:00:00", now).unwrap(),
+            "3 h ago"
+        );
+        assert_eq!(
+            relative_time("2026-05-10T11:59:55+00:00", now).unwrap(),
+            "just now"
+        );
+        assert_eq!(
+            relative_time("2026-05-10 14:00:00+02", now).unwrap(),
+            "just now"
+        );
+        assert_eq!(
+            relative_time("2026-05-10 12:05:00", now).unwrap(),
+            "in 5 min"
+        );
+        assert_eq!(
+            relative_time("2026-05-07 12:00:00.123", now).unwrap(),
+            "3 d ago"
+        );
+        assert_eq!(
+            relative_time("2024-05-10 12:00:00", now).unwrap(),
+            "2 y ago"
+        );
+    }
+
+    #[test]
+    fn leaves_other_values_alone() {
+        let now = at("2026-05-10 12:00:00");
+        for value in ["2026-05-10", "12:00:00", "NULL", "infinity", "42"] {
+            assert_eq!(relative_time(value, now), None, "{value}");
+        }
+        assert!(is_timestamp_type("timestamp with time zone"));
+        assert!(!is_timestamp_type("date"));
+    }
+}
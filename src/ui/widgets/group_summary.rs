@@ -0,0 +1,119 @@
+use ratatui::{
+    style::{Modifier, Style},
+    text::Line,
+};
+
+use crate::db::{column_stats::format_number, group_summary::Group};
+
+/// Widest a group's value is shown before it is cut with `…`.
+const MAX_VALUE_WIDTH: usize = 40;
+
+/// Groups of the loaded rows as a table: value, row count and, when a
+/// column was summed, its sum.
+pub struct GroupSummaryView<'a> {
+    pub groups: &'a [Group],
+    /// The grouped column.
+    pub column: &'a str,
+    /// The summed column, if any.
+    pub sum_column: Option<&'a str>,
+}
+
+impl<'a> GroupSummaryView<'a> {
+    #[must_use]
+    pub const fn new(
+        groups: &'a [Group],
+        column: &'a str,
+        sum_column: Option<&'a str>,
+    ) -> Self {
+        Self {
+            groups,
+            column,
+            sum_column,
+        }
+    }
+
+    /// The table as text lines, e.g. for an info modal.
+    #[must_use]
+    pub fn lines(&self) -> Vec<Line<'static>> {
+        let values = self
+            .groups
+            .iter()
+            .map(|group| fit(&group.value))
+            .collect::<Vec<_>>();
+        let value_width = values
+            .iter()
+            .map(|value| value.chars().count())
+            .chain([self.column.chars().count()])
+            .max()
+            .unwrap_or(0);
+        let sum_heading =
+            self.sum_column.map(|column| format!("sum({column})"));
+        let row = |value: &str, count: &str, sum: Option<&str>| {
+            let mut line = format!("{value:value_width$}  {count:>8}");
+            if let Some(sum) = sum {
+                line.push_str(&format!("  {sum:>16}"));
+            }
+            line
+        };
+
+        let rows: usize = self.groups.iter().map(|group| group.count).sum();
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let mut lines = vec![
+            Line::styled(
+                format!("{} groups in {rows} loaded rows", self.groups.len()),
+                bold,
+            ),
+            Line::default(),
+            Line::styled(
+                row(self.column, "count", sum_heading.as_deref()),
+                bold,
+            ),
+        ];
+        lines.extend(self.groups.iter().zip(&values).map(|(group, value)| {
+            let sum = self.sum_column.map(|_| {
+                group.sum.map_or_else(|| "-".to_string(), format_number)
+            });
+            Line::raw(row(value, &group.count.to_string(), sum.as_deref()))
+        }));
+        lines
+    }
+}
+
+/// `value` on one line, cut to [`MAX_VALUE_WIDTH`] characters.
+fn fit(value: &str) -> String {
+    let value = value.replace(['\n', '\r'], " ");
+    if value.chars().count() <= MAX_VALUE_WIDTH {
+        return value;
+    }
+    let mut cut = value.chars().take(MAX_VALUE_WIDTH - 1).collect::<String>();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::group_summary::group_rows;
+
+    #[test]
+    fn lists_groups_with_their_sums() {
+        let rows = [["eu", "10"], ["us", "NULL"], ["eu", "2.5"]]
+            .map(|row| row.map(String::from));
+        let groups = group_rows(rows.iter().map(|r| r.as_slice()), 0, Some(1));
+        let lines = GroupSummaryView::new(&groups, "region", Some("total"))
+            .lines()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "2 groups in 3 loaded rows",
+                "",
+                "region     count        sum(total)",
+                "eu             2              12.5",
+                "us             1                 -",
+            ]
+        );
+    }
+}
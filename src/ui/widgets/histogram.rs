@@ -0,0 +1,119 @@
+use chrono::DateTime;
+use ratatui::{
+    prelude::{Buffer, Rect, Widget},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+
+use crate::db::{column_stats::format_number, histogram::Histogram};
+
+/// Characters of the one-line sparkline, lowest to highest.
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Longest bar of the per-bucket chart.
+const MAX_BAR_WIDTH: u64 = 40;
+
+/// A sparkline of the bucket counts followed by one labelled bar per bucket.
+pub struct HistogramView<'a> {
+    pub histogram: &'a Histogram,
+}
+
+impl<'a> HistogramView<'a> {
+    #[must_use]
+    pub const fn new(histogram: &'a Histogram) -> Self {
+        Self { histogram }
+    }
+
+    /// The chart as text lines, e.g. for an info modal.
+    #[must_use]
+    pub fn lines(&self) -> Vec<Line<'static>> {
+        let counts = &self.histogram.counts;
+        let peak = counts.iter().copied().max().unwrap_or(0).max(1);
+
+        let spark = counts
+            .iter()
+            .map(|&count| {
+                let level = if count == 0 {
+                    0
+                } else {
+                    usize::try_from(count * 7 / peak).unwrap_or(7)
+                };
+                SPARK_LEVELS.get(level).copied().unwrap_or('█')
+            })
+            .collect::<String>();
+
+        let labels = (0..counts.len())
+            .map(|idx| self.label(self.histogram.bucket_start(idx)))
+            .collect::<Vec<_>>();
+        let label_width = labels.iter().map(String::len).max().unwrap_or(0);
+
+        let mut lines = vec![
+            Line::styled(spark, Style::default().fg(Color::Cyan)),
+            Line::default(),
+        ];
+        lines.extend(counts.iter().zip(labels).map(|(&count, label)| {
+            let bar = usize::try_from(count * MAX_BAR_WIDTH / peak)
+                .unwrap_or_default();
+            Line::from(vec![
+                Span::raw(format!("{label:>label_width$} │")),
+                Span::styled("█".repeat(bar), Style::default().fg(Color::Cyan)),
+                Span::raw(format!(" {count}")),
+            ])
+        }));
+        lines.push(Line::styled(
+            format!("max {}", self.label(self.histogram.max)),
+            Style::default().fg(Color::DarkGray),
+        ));
+        lines
+    }
+
+    fn label(&self, value: f64) -> String {
+        if !self.histogram.temporal {
+            return format_number(value);
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        DateTime::from_timestamp(value.floor() as i64, 0).map_or_else(
+            || format_number(value),
+            |dt| {
+                let span = self.histogram.max - self.histogram.min;
+                // Times of day only matter when the range is under a few days
+                if span < 3.0 * 86_400.0 {
+                    dt.format("%Y-%m-%d %H:%M").to_string()
+                } else {
+                    dt.format("%Y-%m-%d").to_string()
+                }
+            },
+        )
+    }
+}
+
+impl Widget for HistogramView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.lines()).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_sparkline_and_bars() {
+        let histogram = Histogram {
+            min: 0.0,
+            max: 4.0,
+            counts: vec![0, 2, 4],
+            temporal: false,
+        };
+        let lines = HistogramView::new(&histogram).lines();
+        assert_eq!(
+            lines.first().map(ToString::to_string).as_deref(),
+            Some("▁▄█")
+        );
+        let last_bar =
+            lines.get(4).map(ToString::to_string).unwrap_or_default();
+        assert!(last_bar.starts_with("2.666667 │"));
+        assert!(last_bar.ends_with(" 4"));
+    }
+}
@@ -1,6 +1,6 @@
 use std::{borrow::Cow, fmt::Display};
 
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use unicode_width::UnicodeWidthStr;
 
 #[derive(Debug, Clone)]
@@ -28,11 +28,20 @@ impl HotkeyDescription {
 #[derive(Debug, Clone)]
 pub struct Hotkey {
     pub keycode: KeyCode,
+    /// `CONTROL` and `ALT`, shown as `Ctrl+` and `Alt+` prefixes. Shift is
+    /// part of the character typed.
+    pub modifiers: KeyModifiers,
     pub description: HotkeyDescription,
 }
 
 impl Display for Hotkey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
         write!(f, "{}", self.keycode)
     }
 }
@@ -40,17 +49,56 @@ impl Display for Hotkey {
 impl Hotkey {
     #[must_use]
     pub const fn new(c: char, description: &'static str) -> Self {
+        Self::key(KeyCode::Char(c), description)
+    }
+
+    /// A key other than a character, e.g. `Enter` or `Esc`.
+    #[must_use]
+    pub const fn key(keycode: KeyCode, description: &'static str) -> Self {
+        Self {
+            keycode,
+            modifiers: KeyModifiers::NONE,
+            description: HotkeyDescription::Static(description),
+        }
+    }
+
+    #[must_use]
+    pub const fn ctrl(c: char, description: &'static str) -> Self {
         Self {
             keycode: KeyCode::Char(c),
+            modifiers: KeyModifiers::CONTROL,
+            description: HotkeyDescription::Static(description),
+        }
+    }
+
+    #[must_use]
+    pub const fn alt(keycode: KeyCode, description: &'static str) -> Self {
+        Self {
+            keycode,
+            modifiers: KeyModifiers::ALT,
             description: HotkeyDescription::Static(description),
         }
     }
 
+    /// Whether `key` is this hotkey. Shift is ignored, it is part of typing
+    /// `G` or `$`, and a letter held with Ctrl or Alt matches in either case.
+    #[must_use]
+    pub fn matches(&self, key: KeyEvent) -> bool {
+        let held = key.modifiers.difference(KeyModifiers::SHIFT);
+        let code = if let KeyCode::Char(c) = key.code
+            && !held.is_empty()
+        {
+            KeyCode::Char(c.to_ascii_lowercase())
+        } else {
+            key.code
+        };
+        held == self.modifiers && code == self.keycode
+    }
+
     #[must_use]
     #[allow(dead_code)]
     pub fn length(&self) -> u16 {
-        let key_len =
-            u16::try_from(self.keycode.to_string().len()).unwrap_or(1);
+        let key_len = u16::try_from(self.to_string().len()).unwrap_or(1);
         let desc_len = u16::try_from(UnicodeWidthStr::width(
             self.description.display_suffix().as_ref(),
         ))
@@ -1,10 +1,17 @@
 pub mod buttons;
+pub mod group_summary;
+pub mod histogram;
 pub mod hotkey;
 pub mod hotkey_view;
 pub mod modal;
+pub mod orphan_report;
+pub mod plan_compare;
+pub mod progress;
+pub mod row_diff;
 pub mod sql_executor;
 pub mod status_line;
 pub mod table;
+pub mod table_compare;
 pub mod top_bar_view;
 
 use unicode_width::UnicodeWidthStr;
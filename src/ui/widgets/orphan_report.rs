@@ -0,0 +1,158 @@
+use ratatui::{
+    prelude::{Buffer, Rect, Widget},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+
+use crate::db::foreign_key::{ForeignKey, ForeignKeyKind};
+
+/// Result of checking one foreign key for orphaned rows.
+pub enum OrphanCheck {
+    /// Validated keys can't have orphans, so they aren't counted.
+    Skipped,
+    Counted(u64),
+    Failed(String),
+}
+
+/// Per foreign key of a table, how many rows reference a missing parent.
+pub struct OrphanReport<'a> {
+    pub table: &'a str,
+    pub checks: &'a [(ForeignKey, OrphanCheck)],
+}
+
+impl<'a> OrphanReport<'a> {
+    #[must_use]
+    pub const fn new(
+        table: &'a str,
+        checks: &'a [(ForeignKey, OrphanCheck)],
+    ) -> Self {
+        Self { table, checks }
+    }
+
+    /// The report as text lines, e.g. for an info modal.
+    #[must_use]
+    pub fn lines(&self) -> Vec<Line<'static>> {
+        let orphaned = self
+            .checks
+            .iter()
+            .filter(
+                |(_, check)| matches!(check, OrphanCheck::Counted(n) if *n > 0),
+            )
+            .count();
+        let verdict = if orphaned == 0 {
+            Line::styled(
+                format!("No orphaned rows in {}", self.table),
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Line::styled(
+                format!(
+                    "{orphaned} of {} keys of {} have orphaned rows",
+                    self.checks.len(),
+                    self.table
+                ),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )
+        };
+        let mut lines = vec![verdict];
+        for (key, check) in self.checks {
+            let (result, style) = match check {
+                OrphanCheck::Skipped => (
+                    "checked by the database".to_string(),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                OrphanCheck::Counted(0) => {
+                    ("none".to_string(), Style::default().fg(Color::Green))
+                }
+                OrphanCheck::Counted(n) => (
+                    format!("{n} orphaned rows"),
+                    Style::default().fg(Color::Red),
+                ),
+                OrphanCheck::Failed(e) => {
+                    (format!("failed: {e}"), Style::default().fg(Color::Yellow))
+                }
+            };
+            let name = if key.kind == ForeignKeyKind::Convention {
+                String::new()
+            } else {
+                format!("{} ", key.name)
+            };
+            lines.push(Line::default());
+            lines.push(Line::from(vec![
+                Span::styled(
+                    name,
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("({})", key.kind.label()),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]));
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {}: ", key.describe())),
+                Span::styled(result, style),
+            ]));
+        }
+        lines
+    }
+}
+
+impl Widget for OrphanReport<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.lines()).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_each_key_and_the_orphaned_ones() {
+        let key = |name: &str, kind| ForeignKey {
+            name: name.to_string(),
+            kind,
+            columns: vec![format!("{name}_id")],
+            parent_schema: "public".to_string(),
+            parent_table: format!("{name}s"),
+            parent_columns: vec!["id".to_string()],
+        };
+        let checks = [
+            (
+                key("customer", ForeignKeyKind::Validated),
+                OrphanCheck::Skipped,
+            ),
+            (
+                key("region", ForeignKeyKind::NotValid),
+                OrphanCheck::Counted(3),
+            ),
+            (
+                key("product", ForeignKeyKind::Convention),
+                OrphanCheck::Counted(0),
+            ),
+        ];
+        let lines = OrphanReport::new("public.orders", &checks)
+            .lines()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "1 of 3 keys of public.orders have orphaned rows",
+                "",
+                "customer (validated)",
+                "  customer_id → public.customers.id: checked by the database",
+                "",
+                "region (NOT VALID)",
+                "  region_id → public.regions.id: 3 orphaned rows",
+                "",
+                "(by name)",
+                "  product_id → public.products.id: none",
+            ]
+        );
+    }
+}
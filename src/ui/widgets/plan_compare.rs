@@ -0,0 +1,214 @@
+use ratatui::{
+    prelude::{Buffer, Rect, Widget},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+
+use crate::db::query_plan::{PlanNode, QueryPlan, align_plans};
+
+/// Widest a node label is shown before it is cut with `…`.
+const MAX_LABEL_WIDTH: usize = 50;
+
+/// Two executed plans of a query side by side, one line per node, with the
+/// time each node took in both runs and the change.
+pub struct PlanCompareView<'a> {
+    pub baseline: &'a QueryPlan,
+    pub current: &'a QueryPlan,
+}
+
+impl<'a> PlanCompareView<'a> {
+    #[must_use]
+    pub const fn new(baseline: &'a QueryPlan, current: &'a QueryPlan) -> Self {
+        Self { baseline, current }
+    }
+
+    /// The comparison as text lines, e.g. for an info modal.
+    #[must_use]
+    pub fn lines(&self) -> Vec<Line<'static>> {
+        let pairs = align_plans(self.baseline, self.current);
+        let labels = pairs
+            .iter()
+            .map(|(baseline, current)| {
+                baseline.or(*current).map_or_else(String::new, |node| {
+                    fit(&format!("{}{}", "  ".repeat(node.depth), node.label))
+                })
+            })
+            .collect::<Vec<_>>();
+        let label_width = labels
+            .iter()
+            .map(|label| label.chars().count())
+            .max()
+            .unwrap_or(0);
+        let time = |node: Option<&PlanNode>| {
+            node.and_then(|node| node.time_ms)
+                .map_or_else(|| "-".to_string(), |time| format!("{time:.2} ms"))
+        };
+
+        let mut lines = vec![
+            headline(self.baseline.execution_ms, self.current.execution_ms),
+            Line::styled(
+                format!(
+                    "Planning {} → {}; node times include every loop",
+                    format_ms(self.baseline.planning_ms),
+                    format_ms(self.current.planning_ms)
+                ),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Line::default(),
+            Line::styled(
+                format!(
+                    "{:label_width$}  {:>12}  {:>12}  change",
+                    "node", "baseline", "now"
+                ),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+        ];
+        lines.extend(pairs.iter().zip(labels).map(
+            |(&(baseline, current), label)| {
+                let (change, style) = node_change(baseline, current);
+                Line::from(vec![
+                    Span::raw(format!(
+                        "{label:label_width$}  {:>12}  {:>12}  ",
+                        time(baseline),
+                        time(current)
+                    )),
+                    Span::styled(change, style),
+                ])
+            },
+        ));
+        lines
+    }
+}
+
+/// Total execution time of both runs and the change, coloured by whether
+/// the query got faster.
+fn headline(baseline: Option<f64>, current: Option<f64>) -> Line<'static> {
+    let text =
+        format!("Execution {} → {}", format_ms(baseline), format_ms(current));
+    let Some((baseline, current)) = baseline.zip(current) else {
+        return Line::styled(
+            text,
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+    };
+    let color = if current <= baseline {
+        Color::Green
+    } else {
+        Color::Red
+    };
+    Line::styled(
+        format!("{text} ({})", percent_change(baseline, current)),
+        Style::default().fg(color).add_modifier(Modifier::BOLD),
+    )
+}
+
+/// Time difference of a node between the runs, or whether it only appears
+/// in one of them.
+fn node_change(
+    baseline: Option<&PlanNode>,
+    current: Option<&PlanNode>,
+) -> (String, Style) {
+    let (baseline, current) = match (baseline, current) {
+        (Some(baseline), Some(current)) => (baseline, current),
+        (Some(_), None) => {
+            return ("gone".to_string(), Style::new().fg(Color::Yellow));
+        }
+        (None, _) => {
+            return ("new".to_string(), Style::new().fg(Color::Yellow));
+        }
+    };
+    let Some((before, after)) = baseline.time_ms.zip(current.time_ms) else {
+        return (String::new(), Style::new());
+    };
+    let delta = after - before;
+    let style = if delta < 0.0 {
+        Style::new().fg(Color::Green)
+    } else if delta > 0.0 {
+        Style::new().fg(Color::Red)
+    } else {
+        Style::new().fg(Color::DarkGray)
+    };
+    (
+        format!("{delta:+.2} ms ({})", percent_change(before, after)),
+        style,
+    )
+}
+
+fn percent_change(before: f64, after: f64) -> String {
+    if before == 0.0 {
+        return "-".to_string();
+    }
+    format!("{:+.0}%", (after - before) / before * 100.0)
+}
+
+fn format_ms(time: Option<f64>) -> String {
+    time.map_or_else(|| "-".to_string(), |time| format!("{time:.2} ms"))
+}
+
+/// `label` cut to [`MAX_LABEL_WIDTH`] characters.
+fn fit(label: &str) -> String {
+    if label.chars().count() <= MAX_LABEL_WIDTH {
+        return label.to_string();
+    }
+    let mut cut = label.chars().take(MAX_LABEL_WIDTH - 1).collect::<String>();
+    cut.push('…');
+    cut
+}
+
+impl Widget for PlanCompareView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.lines()).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(scan: &str, scan_ms: f64, total_ms: f64) -> QueryPlan {
+        QueryPlan {
+            nodes: vec![
+                PlanNode {
+                    depth: 0,
+                    label: "Aggregate".to_string(),
+                    time_ms: Some(total_ms),
+                    rows: Some(1.0),
+                },
+                PlanNode {
+                    depth: 1,
+                    label: scan.to_string(),
+                    time_ms: Some(scan_ms),
+                    rows: Some(10.0),
+                },
+            ],
+            planning_ms: Some(0.1),
+            execution_ms: Some(total_ms),
+        }
+    }
+
+    #[test]
+    fn shows_per_node_deltas_and_changed_nodes() {
+        let baseline = plan("Seq Scan on t", 8.0, 10.0);
+        let current = plan("Index Scan using t_idx on t", 0.5, 2.5);
+        let lines = PlanCompareView::new(&baseline, &current)
+            .lines()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines.first().map(String::as_str),
+            Some("Execution 10.00 ms → 2.50 ms (-75%)")
+        );
+        // Labels are as wide as "  Index Scan using t_idx on t"
+        assert_eq!(
+            lines.get(4),
+            Some(&format!(
+                "{:29}  {:>12}  {:>12}  -7.50 ms (-75%)",
+                "Aggregate", "10.00 ms", "2.50 ms"
+            ))
+        );
+        assert!(lines.get(5).is_some_and(|line| line.ends_with("gone")));
+        assert!(lines.get(6).is_some_and(|line| line.ends_with("new")));
+    }
+}
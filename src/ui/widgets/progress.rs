@@ -0,0 +1,72 @@
+use ratatui::{
+    prelude::{Buffer, Constraint, Layout, Rect, Widget},
+    style::{Color, Style},
+    symbols,
+    text::Span,
+    widgets::LineGauge,
+};
+
+use crate::background_task::BackgroundTask;
+
+/// Spinner frames, advanced once per redraw.
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Status-line view of a background task: a spinner and label, then a bar
+/// with the percentage once the task knows its total.
+pub struct ProgressIndicator<'a> {
+    pub task: &'a BackgroundTask,
+    /// Statements waiting for the task, e.g. "1 statement queued"
+    pub queued: Option<String>,
+}
+
+impl ProgressIndicator<'_> {
+    /// Spinner, label and, without a known total, the work done so far.
+    #[must_use]
+    pub fn label(&self) -> String {
+        let task = self.task;
+        let spinner = SPINNER.get(task.ticks % SPINNER.len()).unwrap_or(&'⠿');
+        let mut label = if task.ratio().is_none() && task.done > 0 {
+            format!("{spinner} {}… {}", task.label, task.done)
+        } else {
+            format!("{spinner} {}…", task.label)
+        };
+        if let Some(queued) = &self.queued {
+            label.push_str(" · ");
+            label.push_str(queued);
+        }
+        label
+    }
+}
+
+impl Widget for ProgressIndicator<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let label = self.label();
+        let Some(ratio) = self.task.ratio() else {
+            Span::styled(label, Style::default().fg(Color::Cyan))
+                .render(area, buf);
+            return;
+        };
+
+        let label_width =
+            u16::try_from(label.chars().count() + 1).unwrap_or(u16::MAX);
+        let [label_area, gauge_area] = Layout::horizontal([
+            Constraint::Length(label_width),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        Span::styled(label, Style::default().fg(Color::Cyan))
+            .render(label_area, buf);
+        LineGauge::default()
+            .ratio(ratio)
+            .label(format!(
+                "{:>3.0}% ({}/{})",
+                ratio * 100.0,
+                self.task.done,
+                self.task.total.unwrap_or_default()
+            ))
+            .filled_style(Style::default().fg(Color::Cyan))
+            .unfilled_style(Style::default().fg(Color::DarkGray))
+            .filled_symbol(symbols::line::THICK_HORIZONTAL)
+            .render(gauge_area, buf);
+    }
+}
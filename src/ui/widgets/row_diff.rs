@@ -0,0 +1,150 @@
+use ratatui::{
+    prelude::{Buffer, Rect, Widget},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+
+use crate::db::row_diff::ColumnDiff;
+
+/// Widest a value is shown before it is cut with `…`.
+const MAX_VALUE_WIDTH: usize = 40;
+
+/// Two rows side by side, one line per column, with the differing columns
+/// highlighted.
+pub struct RowDiffView<'a> {
+    pub diffs: &'a [ColumnDiff],
+    /// Headings of the two value columns, e.g. `row 3`.
+    pub labels: (&'a str, &'a str),
+}
+
+impl<'a> RowDiffView<'a> {
+    #[must_use]
+    pub const fn new(
+        diffs: &'a [ColumnDiff],
+        labels: (&'a str, &'a str),
+    ) -> Self {
+        Self { diffs, labels }
+    }
+
+    /// The comparison as text lines, e.g. for an info modal.
+    #[must_use]
+    pub fn lines(&self) -> Vec<Line<'static>> {
+        let shown = self
+            .diffs
+            .iter()
+            .map(|diff| {
+                // Escape values whose difference wouldn't show otherwise
+                let show = |value: &str| {
+                    if diff.differs_invisibly() {
+                        fit(&value.escape_debug().to_string())
+                    } else {
+                        fit(value)
+                    }
+                };
+                (diff, show(&diff.left), show(&diff.right))
+            })
+            .collect::<Vec<_>>();
+        let name_width = self
+            .diffs
+            .iter()
+            .map(|diff| diff.column.chars().count())
+            .max()
+            .unwrap_or(0);
+        let left_width = shown
+            .iter()
+            .map(|(_, left, _)| left.chars().count())
+            .chain([self.labels.0.chars().count()])
+            .max()
+            .unwrap_or(0);
+
+        let differing = self.diffs.iter().filter(|d| d.differs()).count();
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let mut lines = vec![
+            Line::styled(
+                format!("{differing} of {} columns differ", self.diffs.len()),
+                bold,
+            ),
+            Line::default(),
+            Line::styled(
+                format!(
+                    "{:name_width$}  {:left_width$}  {}",
+                    "", self.labels.0, self.labels.1
+                ),
+                bold,
+            ),
+        ];
+        lines.extend(shown.into_iter().map(|(diff, left, right)| {
+            let name = format!("{:name_width$}  ", diff.column);
+            let left = format!("{left:left_width$}  ");
+            if !diff.differs() {
+                let same = Style::default().fg(Color::DarkGray);
+                return Line::from(vec![
+                    Span::styled(name, same),
+                    Span::styled(left, same),
+                    Span::styled(right, same),
+                ]);
+            }
+            let mut spans = vec![
+                Span::styled(name, bold.fg(Color::Yellow)),
+                Span::styled(left, Style::default().fg(Color::Red)),
+                Span::styled(right, Style::default().fg(Color::Green)),
+            ];
+            if diff.differs_invisibly() {
+                spans.push(Span::styled(
+                    "  (whitespace)",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            Line::from(spans)
+        }));
+        lines
+    }
+}
+
+impl Widget for RowDiffView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.lines()).render(area, buf);
+    }
+}
+
+/// `value` on one line, cut to [`MAX_VALUE_WIDTH`] characters.
+fn fit(value: &str) -> String {
+    let value = value.replace(['\n', '\r'], " ");
+    if value.chars().count() <= MAX_VALUE_WIDTH {
+        return value;
+    }
+    let mut cut = value.chars().take(MAX_VALUE_WIDTH - 1).collect::<String>();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::row_diff::diff_rows;
+
+    #[test]
+    fn lists_every_column_and_escapes_invisible_differences() {
+        let columns = ["id", "code"].map(String::from);
+        let diffs = diff_rows(
+            &columns,
+            &["1", "AB"].map(String::from),
+            &["1", "AB\t"].map(String::from),
+        );
+        let lines = RowDiffView::new(&diffs, ("row 1", "row 2"))
+            .lines()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines.first().map(String::as_str),
+            Some("1 of 2 columns differ")
+        );
+        assert_eq!(lines.get(3).map(String::as_str), Some("id    1      1"));
+        assert_eq!(
+            lines.get(4).map(String::as_str),
+            Some("code  AB     AB\\t  (whitespace)")
+        );
+    }
+}
@@ -1,10 +1,21 @@
+use std::collections::HashSet;
+
 use ratatui::{
     prelude::*,
     widgets::{Paragraph, StatefulWidget, Wrap},
 };
 use ratatui_textarea::TextArea;
 
-use crate::ui::widgets::table::{DataTable, RawTableRow, TableDataState};
+use crate::{
+    db::{result_origin::ResultOrigin, time_box::Cutoff},
+    sort::{ColumnKind, ColumnSort, sorted_order},
+    sql::position::ErrorMarker,
+    ui::widgets::table::{DataTable, RawTableRow, TableDataState},
+};
+
+/// Rows fetched per round trip for queries that can be paged; more are
+/// fetched on request.
+pub const SQL_RESULT_ROW_LIMIT: usize = 1000;
 
 /// State for the SQL executor widget
 #[derive(Debug, Clone)]
@@ -12,10 +23,28 @@ pub struct SqlExecutorState {
     input: TextArea<'static>,
     pub results: Option<Vec<Vec<String>>>,
     pub column_names: Vec<String>,
+    /// Types of the result columns, when the backend reports them.
+    pub column_types: Vec<String>,
     pub error_message: Option<String>,
+    /// Where in the statement the error points, when the database says.
+    pub error_marker: Option<ErrorMarker>,
     selected_statement: Option<String>,
     pub is_active: bool,
     pub table_state: TableDataState<RawTableRow>,
+    /// Whether the query has rows past the ones fetched so far.
+    pub has_more_rows: bool,
+    /// Set when reading the rows stopped at a time or row limit, so the
+    /// rows shown are a partial result.
+    pub cutoff: Option<Cutoff>,
+    /// Search filter the shown rows match, if any.
+    filter: Option<String>,
+    /// Whether rows equal to one shown above them are hidden.
+    distinct: bool,
+    /// Loaded rows hidden as duplicates.
+    collapsed_rows: usize,
+    /// Table the results were read from, when it is a single one; its
+    /// cells can be edited.
+    pub origin: Option<ResultOrigin>,
 }
 
 impl Default for SqlExecutorState {
@@ -29,10 +58,18 @@ impl Default for SqlExecutorState {
             input,
             results: None,
             column_names: Vec::new(),
+            column_types: Vec::new(),
             error_message: None,
+            error_marker: None,
             selected_statement: None,
             is_active: false,
             table_state: TableDataState::default(),
+            has_more_rows: false,
+            cutoff: None,
+            filter: None,
+            distinct: false,
+            collapsed_rows: 0,
+            origin: None,
         }
     }
 }
@@ -54,8 +91,157 @@ impl SqlExecutorState {
     ) {
         self.results = Some(results.clone());
         self.column_names.clone_from(&column_names.to_vec());
+        self.column_types.clear();
         self.error_message = None;
         self.table_state.reset(results, column_names, None);
+        self.has_more_rows = false;
+        self.cutoff = None;
+        self.filter = None;
+        self.distinct = false;
+        self.collapsed_rows = 0;
+        self.origin = None;
+    }
+
+    /// Show the result column types under their names.
+    pub fn set_column_types(&mut self, column_types: Vec<String>) {
+        self.table_state.set_column_types(&column_types);
+        self.column_types = column_types;
+    }
+
+    /// Append the next chunk of a paged query, keeping the current sort and
+    /// selection.
+    pub fn append_results(&mut self, rows: Vec<Vec<String>>, has_more: bool) {
+        let Some(results) = self.results.as_mut() else {
+            return;
+        };
+        results.extend(rows);
+        self.has_more_rows = has_more;
+        self.cutoff = None;
+        let sort = self.table_state.view.sort;
+        self.sort_results(sort);
+    }
+
+    /// Set `column` of the loaded row equal to `row` to `value`, keeping
+    /// the sort, filter and selection.
+    pub fn update_result_cell(
+        &mut self,
+        row: &[String],
+        column: usize,
+        value: &str,
+    ) {
+        let Some(cell) = self
+            .results
+            .as_mut()
+            .and_then(|rows| rows.iter_mut().find(|r| r.as_slice() == row))
+            .and_then(|r| r.get_mut(column))
+        else {
+            return;
+        };
+        value.clone_into(cell);
+        self.sort_results(self.table_state.view.sort);
+    }
+
+    /// Banner shown above results that stop short of the whole result set.
+    #[must_use]
+    pub fn truncation_banner(&self) -> Option<String> {
+        let shown = self.results.as_ref()?.len();
+        match (self.cutoff, self.has_more_rows) {
+            (Some(cutoff), true) => Some(format!(
+                " Partial result: {cutoff}, showing {shown} rows — press f to fetch more / x to export all "
+            )),
+            (Some(cutoff), false) => Some(format!(
+                " Partial result: {cutoff}, showing {shown} rows — press x to export all "
+            )),
+            (None, true) => Some(format!(
+                " Showing first {shown} rows — press f to fetch more / x to export all "
+            )),
+            (None, false) => None,
+        }
+    }
+
+    /// Reorder the loaded results by `sort`, or back to the order the query
+    /// returned them in, leaving out duplicates when they are hidden.
+    /// Returns the kind the sorted column was compared as.
+    pub fn sort_results(&mut self, sort: Option<ColumnSort>) -> ColumnKind {
+        let Some(results) = self.results.as_ref() else {
+            return ColumnKind::Text;
+        };
+        let (order, kind) = sort.map_or_else(
+            || ((0..results.len()).collect(), ColumnKind::Text),
+            |sort| sorted_order(results, sort),
+        );
+        let mut rows: Vec<Vec<String>> = order
+            .into_iter()
+            .filter_map(|idx| results.get(idx).cloned())
+            .collect();
+        let loaded = rows.len();
+        if self.distinct {
+            let mut seen = HashSet::new();
+            rows.retain(|row| seen.insert(row.clone()));
+        }
+        self.collapsed_rows = loaded - rows.len();
+
+        let view = self.table_state.view.clone();
+        self.table_state.reset(rows, &self.column_names, None);
+        self.table_state.set_column_types(&self.column_types);
+        self.table_state.view = view;
+        self.table_state.view.sort = sort;
+        if let Some(query) = &self.filter {
+            self.table_state.model.items = self.table_state.filter(query);
+            let last = self.table_state.model.items.len().checked_sub(1);
+            let state = &mut self.table_state.view.state;
+            if state
+                .selected()
+                .is_some_and(|selected| last.is_none_or(|last| selected > last))
+            {
+                state.select(last);
+            }
+        }
+        kind
+    }
+
+    /// Search filter the shown rows match, if any.
+    #[must_use]
+    pub fn filter(&self) -> Option<&str> {
+        self.filter.as_deref()
+    }
+
+    /// Show only the loaded rows matching `query`, in the current sort, or
+    /// every row when it is empty. Rows fetched later are filtered too.
+    pub fn set_filter(&mut self, query: &str) {
+        let anchor = self.table_state.selection_anchor();
+        self.filter = (!query.is_empty()).then(|| query.to_string());
+        self.sort_results(self.table_state.view.sort);
+        self.table_state.restore_selection(anchor.as_ref());
+    }
+
+    /// Hide the loaded rows equal to one shown above them, or show them
+    /// again, keeping the selection on the same row. Returns whether
+    /// duplicates are now hidden.
+    pub fn toggle_distinct(&mut self) -> bool {
+        let anchor = self.table_state.selection_anchor();
+        self.distinct = !self.distinct;
+        self.sort_results(self.table_state.view.sort);
+        self.table_state.restore_selection(anchor.as_ref());
+        self.distinct
+    }
+
+    /// Loaded rows hidden as duplicates of a row shown.
+    #[must_use]
+    pub const fn collapsed_rows(&self) -> usize {
+        self.collapsed_rows
+    }
+
+    /// Banner shown above results while duplicates are hidden.
+    #[must_use]
+    pub fn distinct_banner(&self) -> Option<String> {
+        self.distinct.then(|| match self.collapsed_rows {
+            1 => " Distinct rows: 1 duplicate hidden — press ! to show it "
+                .to_string(),
+            n => format!(
+                " Distinct rows: {n} duplicates hidden — press ! to show all "
+            ),
+        })
     }
 
     #[allow(dead_code)]
@@ -64,11 +250,48 @@ impl SqlExecutorState {
         self.results = None;
     }
 
+    /// Show `error` with the line of the statement it points at.
+    pub fn set_error_at(&mut self, error: String, marker: ErrorMarker) {
+        self.set_error(error);
+        self.error_marker = Some(marker);
+    }
+
+    /// Line of the editor buffer the error points at, when the statement
+    /// that ran appears in the buffer as written.
+    #[must_use]
+    pub fn error_line_in_input(&self) -> Option<usize> {
+        let marker = self.error_marker.as_ref()?;
+        let statement = self.selected_statement()?.trim();
+        let input = self.sql_input();
+        let start = input.find(statement)?;
+        let lines_before = input.get(..start)?.matches('\n').count();
+        Some(lines_before + marker.position.line)
+    }
+
+    /// First and last line (1-based) of the buffer the selected statement
+    /// spans, when it appears in the buffer as written.
+    #[must_use]
+    pub fn selected_statement_lines(&self) -> Option<(usize, usize)> {
+        let statement = self.selected_statement()?.trim();
+        let input = self.sql_input();
+        let start = input.find(statement)?;
+        let first = input.get(..start)?.matches('\n').count() + 1;
+        Some((first, first + statement.matches('\n').count()))
+    }
+
     pub fn clear_results(&mut self) {
         self.results = None;
         self.column_names.clear();
+        self.column_types.clear();
         self.error_message = None;
+        self.error_marker = None;
         self.table_state.reset(vec![], &[], None);
+        self.has_more_rows = false;
+        self.cutoff = None;
+        self.filter = None;
+        self.distinct = false;
+        self.collapsed_rows = 0;
+        self.origin = None;
     }
 
     /// Replace the SQL input text entirely after loading from external editor
@@ -97,6 +320,10 @@ impl SqlExecutorState {
         self.selected_statement = Some(statement.into());
     }
 
+    pub fn clear_selected_statement(&mut self) {
+        self.selected_statement = None;
+    }
+
     #[must_use]
     pub fn selected_statement(&self) -> Option<&str> {
         self.selected_statement.as_deref()
@@ -111,16 +338,35 @@ impl StatefulWidget for SqlExecutor {
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         if let Some(error) = &state.error_message {
-            Paragraph::new(error.clone())
-                .style(Style::default().fg(Color::Red))
-                .wrap(Wrap { trim: true })
+            let style = Style::default().fg(Color::Red);
+            if let Some(marker) = &state.error_marker {
+                // Unwrapped, so the caret stays under its character
+                let (statement_line, caret) = marker.lines();
+                Paragraph::new(vec![
+                    Line::styled(error.clone(), style),
+                    Line::default(),
+                    Line::raw(statement_line),
+                    Line::styled(caret, style.add_modifier(Modifier::BOLD)),
+                ])
                 .render(area, buf);
+            } else {
+                Paragraph::new(error.clone())
+                    .style(style)
+                    .wrap(Wrap { trim: true })
+                    .render(area, buf);
+            }
         } else if let Some(results) = &state.results {
             if results.is_empty() {
                 Paragraph::new("No results")
                     .style(Style::default().fg(Color::Gray))
                     .render(area, buf);
             } else {
+                let area = [state.truncation_banner(), state.distinct_banner()]
+                    .into_iter()
+                    .flatten()
+                    .fold(area, |area, banner| {
+                        render_banner(banner, area, buf)
+                    });
                 DataTable::<RawTableRow>::default().render(
                     area,
                     buf,
@@ -134,3 +380,115 @@ impl StatefulWidget for SqlExecutor {
         }
     }
 }
+
+/// Draw `banner` on the first line of `area` and return the rest.
+fn render_banner(banner: String, area: Rect, buf: &mut Buffer) -> Rect {
+    let [banner_area, rest] =
+        Layout::vertical([Constraint::Length(1), Constraint::Fill(1)])
+            .areas(area);
+    Paragraph::new(banner)
+        .style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .render(banner_area, buf);
+    rest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shown(executor: &SqlExecutorState) -> Vec<String> {
+        executor
+            .table_state
+            .model
+            .items
+            .iter()
+            .filter_map(|row| row.values.first().cloned())
+            .collect()
+    }
+
+    #[test]
+    fn filters_loaded_and_fetched_results() {
+        let mut executor = SqlExecutorState::new();
+        let rows = |names: &[&str]| {
+            names.iter().map(|name| vec![(*name).to_string()]).collect()
+        };
+        executor.set_results(rows(&["ann", "bob", "anna"]), &["name".into()]);
+        executor.table_state.view.state.select(Some(2));
+
+        executor.set_filter("ann");
+        assert_eq!(shown(&executor), ["ann", "anna"]);
+        assert_eq!(executor.table_state.view.state.selected(), Some(1));
+
+        executor.append_results(rows(&["joanna", "carl"]), false);
+        assert_eq!(shown(&executor), ["ann", "anna", "joanna"]);
+
+        executor.set_filter("");
+        assert_eq!(executor.filter(), None);
+        assert_eq!(shown(&executor).len(), 5);
+        assert_eq!(executor.table_state.view.state.selected(), Some(2));
+    }
+
+    #[test]
+    fn hides_duplicate_rows() {
+        let mut executor = SqlExecutorState::new();
+        let rows = |names: &[&str]| {
+            names.iter().map(|name| vec![(*name).to_string()]).collect()
+        };
+        executor
+            .set_results(rows(&["ann", "bob", "ann", "ann"]), &["n".into()]);
+        executor.table_state.view.state.select(Some(1));
+
+        assert!(executor.toggle_distinct());
+        assert_eq!(shown(&executor), ["ann", "bob"]);
+        assert_eq!(executor.collapsed_rows(), 2);
+        assert_eq!(executor.table_state.view.state.selected(), Some(1));
+
+        executor.append_results(rows(&["bob", "cy"]), false);
+        assert_eq!(shown(&executor), ["ann", "bob", "cy"]);
+        assert_eq!(executor.collapsed_rows(), 3);
+
+        assert!(!executor.toggle_distinct());
+        assert_eq!(shown(&executor).len(), 6);
+        assert_eq!(executor.distinct_banner(), None);
+    }
+
+    #[test]
+    fn updates_a_cell_in_place() {
+        let mut executor = SqlExecutorState::new();
+        let rows = vec![vec!["1".to_string(), "ann".to_string()]];
+        executor.set_results(rows, &["id".into(), "name".into()]);
+
+        executor.update_result_cell(&["1".into(), "ann".into()], 1, "anne");
+        assert_eq!(
+            executor.results,
+            Some(vec![vec!["1".to_string(), "anne".to_string()]])
+        );
+        assert_eq!(
+            executor.table_state.model.items.first().map(|r| &r.values),
+            executor.results.as_ref().and_then(|r| r.first())
+        );
+    }
+
+    #[test]
+    fn shows_partial_results_in_the_banner() {
+        let mut executor = SqlExecutorState::new();
+        executor.set_results(vec![vec!["1".to_string()]], &["n".into()]);
+        assert_eq!(executor.truncation_banner(), None);
+
+        executor.cutoff = Some(Cutoff::Rows(1));
+        executor.has_more_rows = true;
+        assert!(executor.truncation_banner().is_some_and(|banner| {
+            banner.starts_with(" Partial result: stopped at 1 rows")
+                && banner.contains("press f")
+        }));
+
+        executor.append_results(vec![vec!["2".to_string()]], false);
+        assert_eq!(executor.cutoff, None);
+        assert_eq!(executor.truncation_banner(), None);
+    }
+}
@@ -1,15 +1,30 @@
-use std::{collections::BTreeSet, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
 
 use ratatui::{
-    layout::{Constraint, Rect},
+    layout::{Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::Text,
-    widgets::{Cell, HighlightSpacing, Row, StatefulWidget, Table, TableState},
+    text::{Line, Text},
+    widgets::{
+        Block, Borders, Cell, HighlightSpacing, Row, StatefulWidget, Table,
+        TableState,
+    },
 };
 
 use crate::{
-    db::{DbRowId, TableData},
-    ui::widgets::constraint_len_calculator,
+    db::{DbRowId, TableData, row_note::RowNotes},
+    row_colors::RowColorRule,
+    sort::{ColumnSort, TableOrder},
+    ui::{
+        number_format::{NumberFormat, is_numeric_type},
+        relative_time::{
+            TimeDisplay, is_timestamp_type, is_timestamp_value, now_utc,
+            relative_time,
+        },
+        widgets::constraint_len_calculator,
+    },
 };
 
 /// A wrapper type for raw table data with dynamic column names
@@ -33,6 +48,10 @@ impl TableData for RawTableRow {
         self.values.clone()
     }
 
+    fn col(&self, column: usize) -> String {
+        self.values.get(column).cloned().unwrap_or_default()
+    }
+
     fn num_columns(&self) -> usize {
         self.values.len()
     }
@@ -46,6 +65,14 @@ impl TableData for RawTableRow {
     fn is_draft_row(&self) -> bool {
         self.is_draft
     }
+
+    /// The row locator when the table has one, else every value.
+    fn selection_key(&self) -> String {
+        self.db_row_id.as_ref().map_or_else(
+            || self.values.join("\u{1f}"),
+            |id| format!("{id:?}"),
+        )
+    }
 }
 
 /// Model (data) for the table view
@@ -55,6 +82,8 @@ pub struct TableModel<T: TableData + Clone> {
     pub longest_item_lens: Vec<usize>,
     // For RawTableRow, we store column names here
     pub dynamic_column_names: Option<Arc<Vec<String>>>,
+    /// Types shown under the column names, when the query reported them.
+    pub dynamic_column_types: Option<Arc<Vec<String>>>,
 }
 
 /// View state for the table (UI state like selection, scrolling)
@@ -62,6 +91,43 @@ pub struct TableModel<T: TableData + Clone> {
 pub struct TableViewState {
     pub state: TableState,
     pub column_offset: usize,
+    /// Column indices left out of the grid; the data is kept.
+    pub hidden_columns: BTreeSet<usize>,
+    /// Column shown at its full width instead of [`MAX_COLUMN_WIDTH`].
+    pub expanded_column: Option<usize>,
+    /// Column whose values wrap onto as many lines as they need in every
+    /// row, while the other columns stay on one line.
+    pub wrapped_column: Option<usize>,
+    /// Column the rows are currently ordered by.
+    pub sort: Option<ColumnSort>,
+    /// Widths set by hand (`<`/`>`), by column index.
+    pub column_widths: BTreeMap<usize, usize>,
+    /// Grow the selected row to show every line of its cells, wrapped at
+    /// the column width.
+    pub multi_line: bool,
+    /// Values of rows kept above the grid while the rest scroll. Kept by
+    /// value so they survive sorting and paging.
+    pub pinned_rows: Vec<Vec<String>>,
+    /// How numbers in result grids are drawn; set by the app each frame.
+    pub number_format: NumberFormat,
+    /// How timestamps in result grids are drawn; set by the app each frame.
+    pub time_display: TimeDisplay,
+    /// Rules coloring rows by a column's value; the first match wins.
+    pub row_colors: Vec<RowColorRule>,
+    /// Notes on rows of the table being browsed, marked in the grid.
+    pub row_notes: RowNotes,
+}
+
+/// The selected row and where it was on screen, taken before the rows are
+/// replaced so [`TableDataState::restore_selection`] can find it again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelectionAnchor {
+    /// [`TableData::selection_key`] of the selected row.
+    key: Option<String>,
+    index: usize,
+    /// Rows between the top of the grid and the selected row.
+    row_on_screen: usize,
+    column: Option<usize>,
 }
 
 /// Combined state that holds both model and view state
@@ -93,39 +159,373 @@ impl<T: TableData + Clone> TableDataState<T> {
                 items,
                 longest_item_lens,
                 dynamic_column_names: None,
+                dynamic_column_types: None,
             },
             view: TableViewState {
                 state: TableState::default().with_selected(0),
                 column_offset: 0,
+                hidden_columns: BTreeSet::new(),
+                expanded_column: None,
+                wrapped_column: None,
+                sort: None,
+                column_widths: BTreeMap::new(),
+                multi_line: false,
+                pinned_rows: Vec::new(),
+                number_format: NumberFormat::Raw,
+                time_display: TimeDisplay::Exact,
+                row_colors: Vec::new(),
+                row_notes: RowNotes::default(),
             },
             multi_row_selection: BTreeSet::new(),
         }
     }
 
-    /// Filter items based on query
+    /// Filter items based on query. `key:value` terms for a field the rows
+    /// know (e.g. `kind:view`) must prefix that field; the rest of the query
+    /// is matched against every column.
     #[must_use]
     pub fn filter(&self, query: &str) -> Vec<T> {
         if query.is_empty() {
             return self.model.items.clone();
         }
 
-        let query_lower = query.to_lowercase();
         self.model
             .items
             .iter()
             .filter(|item| {
-                // Check if any column contains the query
-                for col_idx in 0..item.num_columns() {
-                    let col_value = item.col(col_idx);
-                    if col_value.to_lowercase().contains(&query_lower) {
-                        return true;
+                let mut text = Vec::new();
+                for term in query.split_whitespace() {
+                    let field =
+                        term.split_once(':').and_then(|(key, value)| {
+                            let field =
+                                item.filter_field(&key.to_lowercase())?;
+                            Some((field, value))
+                        });
+                    match field {
+                        Some((field, value)) => {
+                            if !field
+                                .to_lowercase()
+                                .starts_with(&value.to_lowercase())
+                            {
+                                return false;
+                            }
+                        }
+                        None => text.push(term),
                     }
                 }
-                false
+                if text.is_empty() {
+                    return true;
+                }
+                // Check if any column contains the rest of the query
+                let query_lower = text.join(" ").to_lowercase();
+                (0..item.num_columns()).any(|col_idx| {
+                    item.col(col_idx).to_lowercase().contains(&query_lower)
+                })
             })
             .cloned()
             .collect()
     }
+
+    /// Indices of the columns not hidden, in display order.
+    #[must_use]
+    pub fn shown_columns(&self) -> Vec<usize> {
+        (0..self.model.longest_item_lens.len())
+            .filter(|idx| !self.view.hidden_columns.contains(idx))
+            .collect()
+    }
+
+    /// Hide the selected column and move the selection to the next shown
+    /// one. The last shown column can't be hidden.
+    pub fn hide_selected_column(&mut self) -> bool {
+        let Some(selected) = self.view.state.selected_column() else {
+            return false;
+        };
+        if self.shown_columns().len() <= 1 {
+            return false;
+        }
+        self.view.hidden_columns.insert(selected);
+        let shown = self.shown_columns();
+        let next = shown
+            .iter()
+            .find(|&&idx| idx > selected)
+            .or_else(|| shown.last())
+            .copied();
+        self.view.state.select_column(next);
+        true
+    }
+
+    pub fn show_all_columns(&mut self) {
+        self.view.hidden_columns.clear();
+    }
+
+    /// Display width of a column: its longest value when expanded, else the
+    /// width set by hand, else its longest value capped at
+    /// [`MAX_COLUMN_WIDTH`].
+    #[must_use]
+    pub fn column_width(&self, idx: usize) -> usize {
+        let longest =
+            self.model.longest_item_lens.get(idx).copied().unwrap_or(0);
+        if self.view.expanded_column == Some(idx) {
+            return longest;
+        }
+        self.view
+            .column_widths
+            .get(&idx)
+            .copied()
+            .unwrap_or_else(|| longest.min(MAX_COLUMN_WIDTH))
+    }
+
+    /// Width of column `idx` as drawn: its width in `grouped` when its
+    /// numbers are grouped, else [`Self::column_width`].
+    fn drawn_width(
+        &self,
+        idx: usize,
+        grouped: &BTreeMap<usize, usize>,
+    ) -> usize {
+        grouped
+            .get(&idx)
+            .copied()
+            .unwrap_or_else(|| self.column_width(idx))
+    }
+
+    /// The number columns among `columns` of a result grid, with their
+    /// widths once digits are grouped. Empty when numbers are drawn raw.
+    fn grouped_number_columns(
+        &self,
+        columns: &[usize],
+    ) -> BTreeMap<usize, usize> {
+        let format = self.view.number_format;
+        if format == NumberFormat::Raw
+            || self.model.dynamic_column_names.is_none()
+        {
+            return BTreeMap::new();
+        }
+        columns
+            .iter()
+            .copied()
+            .filter(|&idx| self.is_number_column(idx, format))
+            .map(|idx| {
+                let width = self.column_width(idx);
+                if self.view.column_widths.contains_key(&idx) {
+                    return (idx, width);
+                }
+                let longest = self
+                    .model
+                    .items
+                    .iter()
+                    .filter_map(|item| format.format(&item.col(idx)))
+                    .map(|value| value.chars().count())
+                    .max()
+                    .unwrap_or(0);
+                let longest = if self.view.expanded_column == Some(idx) {
+                    longest
+                } else {
+                    longest.min(MAX_COLUMN_WIDTH)
+                };
+                (idx, width.max(longest))
+            })
+            .collect()
+    }
+
+    /// Whether column `idx` holds numbers: by its type when the query
+    /// reported types, else when every loaded value is one.
+    fn is_number_column(&self, idx: usize, format: NumberFormat) -> bool {
+        self.column_holds(idx, is_numeric_type, |value| {
+            format.format(value).is_some()
+        })
+    }
+
+    /// The timestamp columns among `columns` of a result grid, drawn as
+    /// relative times. Empty when timestamps are drawn exact.
+    fn relative_time_columns(&self, columns: &[usize]) -> BTreeSet<usize> {
+        if self.view.time_display == TimeDisplay::Exact
+            || self.model.dynamic_column_names.is_none()
+        {
+            return BTreeSet::new();
+        }
+        columns
+            .iter()
+            .copied()
+            .filter(|&idx| {
+                self.column_holds(idx, is_timestamp_type, is_timestamp_value)
+            })
+            .collect()
+    }
+
+    /// Whether column `idx` is of a type `by_type` accepts when the query
+    /// reported types, else whether `by_value` accepts every loaded value
+    /// that isn't empty or NULL, of which there is at least one.
+    fn column_holds(
+        &self,
+        idx: usize,
+        by_type: fn(&str) -> bool,
+        by_value: impl Fn(&str) -> bool,
+    ) -> bool {
+        if let Some(types) = &self.model.dynamic_column_types {
+            return types.get(idx).is_some_and(|ty| by_type(ty));
+        }
+        let mut found = false;
+        for item in &self.model.items {
+            let value = item.col(idx);
+            if value.is_empty() || value == "NULL" {
+                continue;
+            }
+            if !by_value(&value) {
+                return false;
+            }
+            found = true;
+        }
+        found
+    }
+
+    /// The row coloring rules whose column is in the grid, with that
+    /// column's index.
+    fn row_color_columns(&self) -> Vec<(usize, &RowColorRule)> {
+        let Some(names) = &self.model.dynamic_column_names else {
+            return Vec::new();
+        };
+        self.view
+            .row_colors
+            .iter()
+            .filter_map(|rule| {
+                names
+                    .iter()
+                    .position(|name| name.eq_ignore_ascii_case(&rule.column))
+                    .map(|idx| (idx, rule))
+            })
+            .collect()
+    }
+
+    /// Header text for a column, with an arrow when rows are sorted by it.
+    fn header_label(&self, idx: usize, name: &str) -> String {
+        let width = self.column_width(idx);
+        match self.view.sort {
+            Some(sort) if sort.column == idx => format!(
+                "{} {}",
+                fit_cell(name, width.saturating_sub(2)),
+                sort.direction.arrow()
+            ),
+            Some(_) | None => fit_cell(name, width),
+        }
+    }
+
+    /// Widen or narrow the selected column by [`COLUMN_RESIZE_STEP`].
+    /// Returns the selected column and its new width.
+    pub fn resize_selected_column(
+        &mut self,
+        wider: bool,
+    ) -> Option<(usize, usize)> {
+        let selected = self.view.state.selected_column()?;
+        if self.view.expanded_column == Some(selected) {
+            self.view.expanded_column = None;
+        }
+        let current = self.column_width(selected);
+        let width = if wider {
+            current.saturating_add(COLUMN_RESIZE_STEP)
+        } else {
+            current.saturating_sub(COLUMN_RESIZE_STEP)
+        }
+        .clamp(MIN_COLUMN_WIDTH, MAX_RESIZED_COLUMN_WIDTH);
+        self.view.column_widths.insert(selected, width);
+        Some((selected, width))
+    }
+
+    /// Add the selected row to the multi-row selection, or take it out.
+    pub fn toggle_selected_row_mark(&mut self) {
+        let Some(i) = self.view.state.selected() else {
+            return;
+        };
+        if !self.multi_row_selection.remove(&i) {
+            self.multi_row_selection.insert(i);
+        }
+    }
+
+    /// Pin the selected row above the grid, or unpin it when it already is.
+    /// Returns whether the row is now pinned.
+    pub fn toggle_pin_selected_row(&mut self) -> Option<bool> {
+        let values = self
+            .model
+            .items
+            .get(self.view.state.selected()?)?
+            .ref_array();
+        let pinned = &mut self.view.pinned_rows;
+        if let Some(pos) = pinned.iter().position(|row| *row == values) {
+            pinned.remove(pos);
+            return Some(false);
+        }
+        pinned.push(values);
+        Some(true)
+    }
+
+    /// Expand the selected column to its full width, or collapse it when it
+    /// is already expanded. Returns whether a column is now expanded.
+    pub fn toggle_expand_selected_column(&mut self) -> bool {
+        let selected = self.view.state.selected_column();
+        self.view.expanded_column = if self.view.expanded_column == selected {
+            None
+        } else {
+            selected
+        };
+        self.view.expanded_column.is_some()
+    }
+
+    /// Wrap the selected column's values onto several lines in every row,
+    /// or put them back on one line when it is already wrapped. Returns
+    /// whether it is now wrapped; `None` when no column is selected.
+    pub fn toggle_wrap_selected_column(&mut self) -> Option<bool> {
+        let selected = self.view.state.selected_column()?;
+        let wrapped = self.view.wrapped_column != Some(selected);
+        self.view.wrapped_column = wrapped.then_some(selected);
+        Some(wrapped)
+    }
+
+    /// Name the columns of rows whose columns are chosen at run time,
+    /// widening each column to fit its name.
+    pub fn set_column_names(&mut self, names: Vec<String>) {
+        let lens = self.model.longest_item_lens.iter_mut();
+        for (len, name) in lens.zip(&names) {
+            *len = (*len).max(unicode_width::UnicodeWidthStr::width(
+                name.as_str(),
+            ));
+        }
+        self.model.dynamic_column_names = Some(Arc::new(names));
+    }
+
+    /// The selected row, to select again after the rows change.
+    #[must_use]
+    pub fn selection_anchor(&self) -> Option<SelectionAnchor> {
+        let index = self.view.state.selected()?;
+        Some(SelectionAnchor {
+            key: self.model.items.get(index).map(TableData::selection_key),
+            index,
+            row_on_screen: index.saturating_sub(self.view.state.offset()),
+            column: self.view.state.selected_column(),
+        })
+    }
+
+    /// Select the anchored row again, wherever its key is now, or else the
+    /// row at its old position. The row keeps its place on screen.
+    pub fn restore_selection(&mut self, anchor: Option<&SelectionAnchor>) {
+        let Some(anchor) = anchor else {
+            return;
+        };
+        let items = &self.model.items;
+        let Some(last) = items.len().checked_sub(1) else {
+            self.view.state.select(None);
+            return;
+        };
+        let index = anchor
+            .key
+            .as_ref()
+            .and_then(|key| {
+                items.iter().position(|item| item.selection_key() == *key)
+            })
+            .unwrap_or_else(|| anchor.index.min(last));
+        self.view.state.select(Some(index));
+        *self.view.state.offset_mut() =
+            index.saturating_sub(anchor.row_on_screen);
+        self.view.state.select_column(anchor.column);
+    }
 }
 
 impl TableDataState<RawTableRow> {
@@ -158,11 +558,91 @@ impl TableDataState<RawTableRow> {
         self.model.items = raw_rows;
         self.model.longest_item_lens = longest_item_lens;
         self.model.dynamic_column_names = Some(column_names_arc);
+        self.model.dynamic_column_types = None;
         self.view.state.select(Some(0));
         self.view.column_offset = 0;
+        self.view.hidden_columns.clear();
+        self.view.expanded_column = None;
+        self.view.wrapped_column = None;
+        self.view.column_widths.clear();
+        self.view.sort = None;
+        self.view.pinned_rows.clear();
         self.multi_row_selection.clear();
     }
 
+    /// Show `types` under the column names; nothing when empty.
+    pub fn set_column_types(&mut self, types: &[String]) {
+        self.model.dynamic_column_types =
+            (!types.is_empty()).then(|| Arc::new(types.to_owned()));
+    }
+
+    /// Names of the hidden columns.
+    #[must_use]
+    pub fn hidden_column_names(&self) -> Vec<String> {
+        let Some(names) = self.model.dynamic_column_names.as_deref() else {
+            return Vec::new();
+        };
+        self.view
+            .hidden_columns
+            .iter()
+            .filter_map(|&idx| names.get(idx).cloned())
+            .collect()
+    }
+
+    /// Hide columns by name; unknown names are ignored.
+    pub fn hide_columns_by_name(&mut self, hidden: &[String]) {
+        let Some(names) = self.model.dynamic_column_names.clone() else {
+            return;
+        };
+        self.view.hidden_columns = names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| hidden.contains(name))
+            .map(|(idx, _)| idx)
+            .collect();
+        if self.shown_columns().is_empty() {
+            self.view.hidden_columns.clear();
+        }
+    }
+
+    /// Name of the column at `idx`.
+    #[must_use]
+    pub fn column_name(&self, idx: usize) -> Option<&str> {
+        self.model
+            .dynamic_column_names
+            .as_deref()
+            .and_then(|names| names.get(idx))
+            .map(String::as_str)
+    }
+
+    /// Point the header arrow at the column of a server-side order.
+    pub fn show_order(&mut self, order: Option<&TableOrder>) {
+        let names = self.model.dynamic_column_names.as_deref();
+        self.view.sort = order.zip(names).and_then(|(order, names)| {
+            Some(ColumnSort {
+                column: names.iter().position(|name| *name == order.column)?,
+                direction: order.direction,
+            })
+        });
+    }
+
+    /// Apply widths set by hand by column name; unknown names are ignored.
+    pub fn set_column_widths_by_name(&mut self, widths: &[(String, usize)]) {
+        let Some(names) = self.model.dynamic_column_names.clone() else {
+            return;
+        };
+        self.view.column_widths = widths
+            .iter()
+            .filter_map(|(name, width)| {
+                let idx = names.iter().position(|n| n == name)?;
+                Some((
+                    idx,
+                    (*width).clamp(MIN_COLUMN_WIDTH, MAX_RESIZED_COLUMN_WIDTH),
+                ))
+            })
+            .collect();
+    }
+
     /// Recompute column display widths after cell text changes.
     pub fn recompute_column_widths(&mut self) {
         let Some(names) = self.model.dynamic_column_names.as_deref() else {
@@ -173,6 +653,74 @@ impl TableDataState<RawTableRow> {
     }
 }
 
+/// Widest a column is drawn before its values are cut with `…`.
+pub const MAX_COLUMN_WIDTH: usize = 40;
+
+/// Narrowest and widest a column can be set by hand.
+const MIN_COLUMN_WIDTH: usize = 3;
+const MAX_RESIZED_COLUMN_WIDTH: usize = 500;
+
+/// Columns added or removed per `<`/`>` press.
+const COLUMN_RESIZE_STEP: usize = 2;
+
+/// Marks a cell whose value doesn't fit its column.
+const TRUNCATION_MARKER: char = '…';
+
+/// The first line of `value`, cut to `width` columns with a trailing `…` when
+/// it is wider or spans more lines.
+fn fit_cell(value: &str, width: usize) -> String {
+    use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+    let mut lines = value.lines();
+    let first = lines.next().unwrap_or_default();
+    let multi_line = lines.next().is_some();
+    if !multi_line && UnicodeWidthStr::width(first) <= width {
+        return first.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    let mut fitted = String::new();
+    let mut used = 0;
+    for c in first.chars() {
+        let w = UnicodeWidthChar::width(c).unwrap_or(0);
+        if used + w > width - 1 {
+            break;
+        }
+        used += w;
+        fitted.push(c);
+    }
+    fitted.push(TRUNCATION_MARKER);
+    fitted
+}
+
+/// Tallest a row grows in multi-line mode or with a wrapped column.
+const MAX_ROW_HEIGHT: usize = 10;
+
+/// All lines of `value`, each hard-wrapped at `width` columns.
+fn wrap_cell(value: &str, width: usize) -> Vec<String> {
+    use unicode_width::UnicodeWidthChar;
+
+    let width = width.max(1);
+    let mut wrapped = Vec::new();
+    for line in value.lines() {
+        let mut current = String::new();
+        let mut used = 0;
+        for c in line.chars() {
+            let w = UnicodeWidthChar::width(c).unwrap_or(0);
+            if used + w > width && !current.is_empty() {
+                wrapped.push(std::mem::take(&mut current));
+                used = 0;
+            }
+            used += w;
+            current.push(c);
+        }
+        wrapped.push(current);
+    }
+    wrapped
+}
+
 const fn col_width(len: usize) -> usize {
     len + 1
 }
@@ -316,15 +864,64 @@ impl<T: TableData + std::fmt::Debug + Clone> StatefulWidget for DataTable<T> {
             return;
         }
 
-        let selected_col_opt = state.view.state.selected_column();
-        let (visible_cols, relative_selected_col, scroll_start) =
+        let header_height = if state.model.dynamic_column_types.is_some() {
+            2
+        } else {
+            1
+        };
+
+        // Pinned rows get a strip of their own above the scrolling grid
+        let (pinned_area, area) = pinned_strip_height(
+            state.view.pinned_rows.len(),
+            header_height,
+            area.height,
+        )
+        .map_or((None, area), |height| {
+            let [pinned, rest] = Layout::vertical([
+                Constraint::Length(height),
+                Constraint::Min(0),
+            ])
+            .areas(area);
+            (Some(pinned), rest)
+        });
+
+        // Lay out only the shown columns, then map back to data indices
+        let shown = state.shown_columns();
+        let grouped = state.grouped_number_columns(&shown);
+        let number_format = state.view.number_format;
+        let relative = state.relative_time_columns(&shown);
+        let now = now_utc();
+        let shown_value = |value: &str, idx: usize| {
+            if relative.contains(&idx) {
+                return relative_time(value, now)
+                    .unwrap_or_else(|| value.to_string());
+            }
+            grouped
+                .contains_key(&idx)
+                .then(|| number_format.format(value))
+                .flatten()
+                .unwrap_or_else(|| value.to_string())
+        };
+        let shown_lens = shown
+            .iter()
+            .map(|&idx| state.drawn_width(idx, &grouped))
+            .collect::<Vec<_>>();
+        let selected_col_opt =
+            state.view.state.selected_column().and_then(|selected| {
+                shown.iter().position(|&idx| idx == selected)
+            });
+        let (shown_positions, relative_selected_col, scroll_start) =
             calculate_visible_columns_for_table(
-                &state.model.longest_item_lens,
+                &shown_lens,
                 state.view.column_offset,
                 selected_col_opt,
                 area.width,
             );
         state.view.column_offset = scroll_start;
+        let visible_cols = shown_positions
+            .iter()
+            .filter_map(|&pos| shown.get(pos).copied())
+            .collect::<Vec<_>>();
 
         let original_col = state.view.state.selected_column();
         state.view.state.select_column(relative_selected_col);
@@ -368,57 +965,193 @@ impl<T: TableData + std::fmt::Debug + Clone> StatefulWidget for DataTable<T> {
                     .map(|&idx| {
                         let col_name =
                             all_cols.get(idx).copied().unwrap_or_default();
-                        Cell::from(col_name)
+                        Cell::from(state.header_label(idx, col_name))
                     })
                     .collect::<Row>()
                     .height(1)
             },
             |dyn_cols| {
+                let types = state.model.dynamic_column_types.as_deref();
                 visible_cols
                     .iter()
                     .map(|&idx| {
-                        let col_name =
-                            dyn_cols.get(idx).cloned().unwrap_or_default();
-                        Cell::from(col_name)
+                        let col_name = dyn_cols
+                            .get(idx)
+                            .map(|name| state.header_label(idx, name))
+                            .unwrap_or_default();
+                        let Some(types) = types else {
+                            return Cell::from(col_name);
+                        };
+                        let col_type = types
+                            .get(idx)
+                            .map(|ty| {
+                                fit_cell(ty, state.drawn_width(idx, &grouped))
+                            })
+                            .unwrap_or_default();
+                        Cell::from(Text::from(vec![
+                            Line::from(col_name),
+                            Line::styled(
+                                col_type,
+                                Style::new().fg(Color::DarkGray),
+                            ),
+                        ]))
                     })
                     .collect::<Row>()
-                    .height(1)
+                    .height(header_height)
             },
         );
 
+        let cell_style = |item: Option<&T>, idx: usize| {
+            item.and_then(|item| item.col_color(idx))
+                .map_or_else(Style::new, |color| Style::new().fg(color))
+        };
+        let note_key = state
+            .model
+            .dynamic_column_names
+            .as_deref()
+            .and_then(|names| state.view.row_notes.key_indices(names));
+        let noted = |values: &[String]| {
+            note_key.as_deref().is_some_and(|indices| {
+                state.view.row_notes.note_on(indices, values).is_some()
+            })
+        };
+        // Rows on one line, but for the wrapped column when `wrap` is set
+        let fitted_row = |values: &[String], item: Option<&T>, wrap: bool| {
+            let marked = noted(values);
+            let wrapped = state.view.wrapped_column.filter(|_| wrap);
+            let cells = visible_cols
+                .iter()
+                .enumerate()
+                .map(|(pos, &idx)| {
+                    let marker =
+                        if marked && pos == 0 { NOTE_MARKER } else { "" };
+                    let width = state
+                        .drawn_width(idx, &grouped)
+                        .saturating_sub(marker.chars().count());
+                    let value = values
+                        .get(idx)
+                        .map(|value| shown_value(value, idx))
+                        .unwrap_or_default();
+                    let mut lines = if wrapped == Some(idx) {
+                        wrap_cell(&value, width)
+                    } else {
+                        Vec::new()
+                    };
+                    if lines.is_empty() {
+                        lines.push(fit_cell(&value, width));
+                    }
+                    if let Some(first) = lines.first_mut() {
+                        first.insert_str(0, marker);
+                    }
+                    lines
+                })
+                .collect::<Vec<_>>();
+            let height = cells
+                .iter()
+                .map(Vec::len)
+                .max()
+                .unwrap_or(1)
+                .clamp(1, MAX_ROW_HEIGHT);
+            cells
+                .into_iter()
+                .zip(&visible_cols)
+                .map(|(lines, &idx)| {
+                    Cell::from(Text::from_iter(lines))
+                        .style(cell_style(item, idx))
+                })
+                .collect::<Row>()
+                .height(u16::try_from(height).unwrap_or(1))
+        };
+
+        let row_colors = state.row_color_columns();
+        let selected_row = state.view.state.selected();
         let rows =
             state.model.items.iter().enumerate().map(|(row_idx, data)| {
                 let row_data = data.ref_array();
-                let mut row_style = Style::new();
+                let mut row_style = row_colors
+                    .iter()
+                    .find(|(idx, rule)| {
+                        row_data.get(*idx).is_some_and(|v| rule.matches(v))
+                    })
+                    .map_or_else(Style::new, |(_, rule)| {
+                        Style::new().fg(rule.color)
+                    });
+                if state.view.pinned_rows.contains(&row_data) {
+                    row_style = row_style.fg(PINNED_ROW_COLOR);
+                }
                 if data.is_draft_row() {
                     row_style = row_style.fg(Color::LightGreen);
                 }
                 if state.multi_row_selection.contains(&row_idx) {
                     row_style = row_style.bg(Color::Blue);
                 }
-                visible_cols
-                    .iter()
-                    .map(|&idx| {
-                        let value =
-                            row_data.get(idx).cloned().unwrap_or_default();
-                        Cell::from(value)
-                    })
-                    .collect::<Row>()
-                    .style(row_style)
-                    .height(1)
+                if state.view.multi_line && selected_row == Some(row_idx) {
+                    let cells = visible_cols
+                        .iter()
+                        .map(|&idx| {
+                            row_data.get(idx).map_or_else(Vec::new, |value| {
+                                wrap_cell(
+                                    &shown_value(value, idx),
+                                    state.drawn_width(idx, &grouped),
+                                )
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    let height = cells
+                        .iter()
+                        .map(Vec::len)
+                        .max()
+                        .unwrap_or(1)
+                        .clamp(1, MAX_ROW_HEIGHT);
+                    return cells
+                        .into_iter()
+                        .zip(&visible_cols)
+                        .map(|(lines, &idx)| {
+                            Cell::from(Text::from_iter(lines))
+                                .style(cell_style(Some(data), idx))
+                        })
+                        .collect::<Row>()
+                        .style(row_style)
+                        .height(u16::try_from(height).unwrap_or(1));
+                }
+                fitted_row(&row_data, Some(data), true).style(row_style)
             });
 
         let constraints = visible_cols
             .iter()
             .map(|&idx| {
-                let width =
-                    state.model.longest_item_lens.get(idx).unwrap_or(&0) + 1;
+                let width = col_width(state.drawn_width(idx, &grouped));
                 Constraint::Length(u16::try_from(width).unwrap_or(u16::MAX))
             })
             .collect::<Vec<_>>();
 
-        let t = Table::new(rows, constraints)
-            .header(header)
+        if let Some(pinned_area) = pinned_area {
+            let pinned_rows = state.view.pinned_rows.iter().map(|values| {
+                fitted_row(values, None, false)
+                    .style(Style::new().fg(PINNED_ROW_COLOR))
+            });
+            let pinned = Table::new(pinned_rows, constraints.clone())
+                .header(header.clone())
+                .column_highlight_style(selected_col_style)
+                .highlight_symbol(highlight_symbol.clone())
+                .highlight_spacing(highlight_spacing.clone())
+                .block(
+                    Block::new()
+                        .borders(Borders::BOTTOM)
+                        .border_style(Style::new().fg(Color::DarkGray)),
+                );
+            let mut pinned_state = TableState::default()
+                .with_selected_column(relative_selected_col);
+            StatefulWidget::render(pinned, pinned_area, buf, &mut pinned_state);
+        }
+
+        let t = Table::new(rows, constraints);
+        let t = if pinned_area.is_some() {
+            t
+        } else {
+            t.header(header)
+        };
+        let t = t
             .row_highlight_style(row_highlight_style)
             .column_highlight_style(selected_col_style)
             .cell_highlight_style(selected_cell_style)
@@ -430,6 +1163,31 @@ impl<T: TableData + std::fmt::Debug + Clone> StatefulWidget for DataTable<T> {
     }
 }
 
+/// Pinned rows and the grid rows matching them.
+const PINNED_ROW_COLOR: Color = Color::LightYellow;
+
+/// Drawn before the first cell of rows with a note.
+const NOTE_MARKER: &str = "✎ ";
+
+/// Grids shorter than this show no pinned strip.
+const MIN_HEIGHT_FOR_PINNED: u16 = 6;
+
+/// Height of the pinned strip: header, pinned rows and a rule, at most
+/// half the grid. `None` when nothing is pinned or the grid is too short.
+fn pinned_strip_height(
+    pinned: usize,
+    header_height: u16,
+    height: u16,
+) -> Option<u16> {
+    if pinned == 0 || height < MIN_HEIGHT_FOR_PINNED {
+        return None;
+    }
+    let wanted = u16::try_from(pinned)
+        .unwrap_or(u16::MAX)
+        .saturating_add(header_height + 1);
+    Some(wanted.min(height / 2))
+}
+
 // Helper function to calculate constraints for raw table data
 fn constraint_len_calculator_for_raw_data(
     items: &[RawTableRow],
@@ -485,3 +1243,246 @@ fn create_table_styles()
         HighlightSpacing::Always,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> TableDataState<RawTableRow> {
+        let mut table = TableDataState::default();
+        let names = ["id", "name", "email"].map(String::from);
+        table.reset(
+            vec![vec!["1".into(), "a".into(), "a@x".into()]],
+            &names,
+            None,
+        );
+        table
+    }
+
+    #[test]
+    fn widens_number_columns_for_grouped_digits() {
+        let mut table = TableDataState::default();
+        let names = ["id", "total", "zip"].map(String::from);
+        table.reset(
+            vec![
+                vec!["1".into(), "1234567.5".into(), "01234".into()],
+                vec!["2".into(), "NULL".into(), "98765".into()],
+            ],
+            &names,
+            None,
+        );
+        let all = table.shown_columns();
+        assert!(table.grouped_number_columns(&all).is_empty());
+
+        table.view.number_format = NumberFormat::for_locale(Some("en_US"));
+        let grouped = table.grouped_number_columns(&all);
+        assert_eq!(grouped.keys().copied().collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(grouped.get(&1), Some(&"1,234,567.5".len()));
+
+        table.set_column_types(&["int4", "text", "text"].map(String::from));
+        let grouped = table.grouped_number_columns(&all);
+        assert_eq!(grouped.keys().copied().collect::<Vec<_>>(), [0]);
+    }
+
+    #[test]
+    fn draws_timestamp_columns_as_relative_times() {
+        let mut table = TableDataState::default();
+        let names = ["id", "created_at", "day"].map(String::from);
+        table.reset(
+            vec![
+                vec![
+                    "1".into(),
+                    "2026-05-10 09:00:00".into(),
+                    "2026-05-10".into(),
+                ],
+                vec!["2".into(), "NULL".into(), "2026-05-11".into()],
+            ],
+            &names,
+            None,
+        );
+        let all = table.shown_columns();
+        assert!(table.relative_time_columns(&all).is_empty());
+
+        table.view.time_display = TimeDisplay::Relative;
+        let relative = table.relative_time_columns(&all);
+        assert_eq!(relative.into_iter().collect::<Vec<_>>(), [1]);
+
+        table.set_column_types(
+            &["int4", "text", "timestamptz"].map(String::from),
+        );
+        let relative = table.relative_time_columns(&all);
+        assert_eq!(relative.into_iter().collect::<Vec<_>>(), [2]);
+    }
+
+    #[test]
+    fn row_colors_follow_the_rules_column() {
+        let mut table = TableDataState::default();
+        let names = ["id", "Status"].map(String::from);
+        table.reset(vec![vec!["1".into(), "error".into()]], &names, None);
+        table.view.row_colors = crate::row_colors::parse_rules(
+            "status = 'error' -> red; missing = 1 -> blue",
+        )
+        .unwrap();
+        let columns = table.row_color_columns();
+        assert_eq!(columns.len(), 1);
+        assert_eq!(columns.first().unwrap().0, 1);
+    }
+
+    #[test]
+    fn hiding_columns_keeps_one_visible_and_moves_selection() {
+        let mut table = table();
+        table.view.state.select_column(Some(1));
+
+        assert!(table.hide_selected_column());
+        assert_eq!(table.shown_columns(), [0, 2]);
+        assert_eq!(table.view.state.selected_column(), Some(2));
+        assert_eq!(table.hidden_column_names(), ["name"]);
+
+        assert!(table.hide_selected_column());
+        assert!(!table.hide_selected_column());
+        assert_eq!(table.shown_columns(), [0]);
+    }
+
+    #[test]
+    fn hides_columns_by_name() {
+        let mut table = table();
+        table.hide_columns_by_name(&["email".to_string(), "gone".to_string()]);
+        assert_eq!(table.shown_columns(), [0, 1]);
+
+        table.show_all_columns();
+        assert_eq!(table.shown_columns(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn fits_cells_with_truncation_marker() {
+        assert_eq!(fit_cell("abc", 3), "abc");
+        assert_eq!(fit_cell("abcdef", 4), "abc…");
+        assert_eq!(fit_cell("ab\ncd", 10), "ab…");
+        assert_eq!(fit_cell("日本語", 4), "日…");
+    }
+
+    #[test]
+    fn resizes_columns_within_bounds() {
+        let mut table = table();
+        table.view.state.select_column(Some(1));
+        assert_eq!(table.resize_selected_column(true), Some((1, 6)));
+        assert_eq!(table.column_width(1), 6);
+        for _ in 0..5 {
+            table.resize_selected_column(false);
+        }
+        assert_eq!(table.column_width(1), MIN_COLUMN_WIDTH);
+
+        table.set_column_widths_by_name(&[
+            ("email".to_string(), 12),
+            ("gone".to_string(), 9),
+        ]);
+        assert_eq!(table.view.column_widths.len(), 1);
+        assert_eq!(table.column_width(2), 12);
+        assert_eq!(table.column_width(1), 4);
+    }
+
+    #[test]
+    fn wraps_cells_per_line() {
+        assert_eq!(wrap_cell("abcdef\ngh", 4), ["abcd", "ef", "gh"]);
+        assert_eq!(wrap_cell("", 4), Vec::<String>::new());
+        assert_eq!(wrap_cell("日本語", 4), ["日本", "語"]);
+    }
+
+    #[test]
+    fn expands_selected_column_past_the_cap() {
+        let mut table = TableDataState::default();
+        let long = "x".repeat(MAX_COLUMN_WIDTH + 10);
+        table.reset(vec![vec![long]], &["v".to_string()], None);
+        assert_eq!(table.column_width(0), MAX_COLUMN_WIDTH);
+
+        table.view.state.select_column(Some(0));
+        assert!(table.toggle_expand_selected_column());
+        assert_eq!(table.column_width(0), MAX_COLUMN_WIDTH + 10);
+        assert!(!table.toggle_expand_selected_column());
+        assert_eq!(table.column_width(0), MAX_COLUMN_WIDTH);
+    }
+
+    #[test]
+    fn wraps_the_selected_column() {
+        let mut table = table();
+        assert_eq!(table.toggle_wrap_selected_column(), None);
+
+        table.view.state.select_column(Some(1));
+        assert_eq!(table.toggle_wrap_selected_column(), Some(true));
+        assert_eq!(table.view.wrapped_column, Some(1));
+        table.view.state.select_column(Some(2));
+        assert_eq!(table.toggle_wrap_selected_column(), Some(true));
+        assert_eq!(table.view.wrapped_column, Some(2));
+        assert_eq!(table.toggle_wrap_selected_column(), Some(false));
+        assert_eq!(table.view.wrapped_column, None);
+    }
+
+    #[test]
+    fn keeps_the_selected_row_when_rows_change() {
+        let names = ["id".to_string()];
+        let rows = |ids: &[&str]| {
+            ids.iter().map(|id| vec![(*id).to_string()]).collect()
+        };
+        let mut table = TableDataState::default();
+        table.reset(rows(&["1", "2", "3", "4"]), &names, None);
+        table.view.state.select(Some(2));
+        *table.view.state.offset_mut() = 1;
+        let anchor = table.selection_anchor();
+
+        table.reset(rows(&["0", "1", "2", "3", "4"]), &names, None);
+        table.restore_selection(anchor.as_ref());
+        assert_eq!(table.view.state.selected(), Some(3));
+        assert_eq!(table.view.state.offset(), 2);
+
+        table.reset(rows(&["1", "2"]), &names, None);
+        table.restore_selection(anchor.as_ref());
+        assert_eq!(table.view.state.selected(), Some(1));
+
+        table.reset(vec![], &names, None);
+        table.restore_selection(anchor.as_ref());
+        assert_eq!(table.view.state.selected(), None);
+    }
+
+    #[test]
+    fn pins_rows_by_value() {
+        let mut table = table();
+        assert_eq!(table.toggle_pin_selected_row(), Some(true));
+        assert_eq!(table.view.pinned_rows.len(), 1);
+        assert_eq!(table.toggle_pin_selected_row(), Some(false));
+        assert!(table.view.pinned_rows.is_empty());
+
+        assert_eq!(pinned_strip_height(0, 1, 40), None);
+        assert_eq!(pinned_strip_height(3, 1, 40), Some(5));
+        assert_eq!(pinned_strip_height(3, 2, 40), Some(6));
+        assert_eq!(pinned_strip_height(30, 1, 20), Some(10));
+        assert_eq!(pinned_strip_height(1, 1, 5), None);
+    }
+
+    #[test]
+    fn filters_tables_by_kind_and_text() {
+        use crate::db::{Table, TableKind};
+
+        let table = |name: &str, kind| Table {
+            name: name.to_string(),
+            schema: "public".to_string(),
+            size: None,
+            kind,
+        };
+        let tables = TableDataState::new(vec![
+            table("orders", TableKind::Table),
+            table("order_totals", TableKind::View),
+            table("order_rollup", TableKind::MaterializedView),
+        ]);
+        let names = |query: &str| {
+            tables
+                .filter(query)
+                .into_iter()
+                .map(|t| t.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("kind:view"), ["order_totals"]);
+        assert_eq!(names("Kind:MAT order"), ["order_rollup"]);
+        assert_eq!(names("kind:view rollup"), Vec::<String>::new());
+        assert_eq!(names("totals"), ["order_totals"]);
+    }
+}
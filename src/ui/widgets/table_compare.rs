@@ -0,0 +1,183 @@
+use ratatui::{
+    prelude::{Buffer, Rect, Widget},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+
+use crate::db::table_compare::{
+    CompareStatus, TableChecksums, compare_columns,
+};
+
+/// Row counts of two tables and, per column, the non-NULL counts and
+/// whether the checksums match.
+pub struct TableCompareView<'a> {
+    pub left: &'a TableChecksums,
+    pub right: &'a TableChecksums,
+    /// Names of the two tables, e.g. `prod: public.orders`.
+    pub labels: (&'a str, &'a str),
+}
+
+impl<'a> TableCompareView<'a> {
+    #[must_use]
+    pub const fn new(
+        left: &'a TableChecksums,
+        right: &'a TableChecksums,
+        labels: (&'a str, &'a str),
+    ) -> Self {
+        Self {
+            left,
+            right,
+            labels,
+        }
+    }
+
+    /// The comparison as text lines, e.g. for an info modal.
+    #[must_use]
+    pub fn lines(&self) -> Vec<Line<'static>> {
+        let columns = compare_columns(self.left, self.right);
+        let differing = columns
+            .iter()
+            .filter(|c| c.status() != CompareStatus::Same)
+            .count();
+        let rows_match = self.left.rows == self.right.rows;
+        let verdict = verdict(rows_match, differing, columns.len());
+
+        let count = |checksum: Option<u64>| {
+            checksum.map_or_else(|| "-".to_string(), |n| n.to_string())
+        };
+        let mut table = vec![(
+            "rows".to_string(),
+            self.left.rows.to_string(),
+            self.right.rows.to_string(),
+            if rows_match {
+                CompareStatus::Same
+            } else {
+                CompareStatus::Differs
+            },
+        )];
+        table.extend(columns.iter().map(|c| {
+            (
+                c.name.clone(),
+                count(c.left.as_ref().map(|l| l.non_null)),
+                count(c.right.as_ref().map(|r| r.non_null)),
+                c.status(),
+            )
+        }));
+        let name_width = table
+            .iter()
+            .map(|(name, ..)| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let left_width = table
+            .iter()
+            .map(|(_, left, ..)| left.chars().count())
+            .chain([self.labels.0.chars().count()])
+            .max()
+            .unwrap_or(0);
+        let right_width = table
+            .iter()
+            .map(|(_, _, right, _)| right.chars().count())
+            .chain([self.labels.1.chars().count()])
+            .max()
+            .unwrap_or(0);
+
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let mut lines = vec![
+            verdict,
+            Line::styled(
+                "Counts are of non-NULL values; checksums are compared per column",
+                Style::default().fg(Color::DarkGray),
+            ),
+            Line::default(),
+            Line::styled(
+                format!(
+                    "{:name_width$}  {:>left_width$}  {:>right_width$}",
+                    "", self.labels.0, self.labels.1
+                ),
+                bold,
+            ),
+        ];
+        lines.extend(table.into_iter().map(|(name, left, right, status)| {
+            let (label, style) = status_label(status);
+            Line::from(vec![
+                Span::raw(format!(
+                    "{name:name_width$}  {left:>left_width$}  {right:>right_width$}  "
+                )),
+                Span::styled(label, style),
+            ])
+        }));
+        lines
+    }
+}
+
+/// Headline of the comparison.
+fn verdict(rows_match: bool, differing: usize, total: usize) -> Line<'static> {
+    let failed = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+    match (rows_match, differing) {
+        (true, 0) => Line::styled(
+            format!("Row counts and all {total} columns match"),
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        ),
+        (true, _) => Line::styled(
+            format!("{differing} of {total} columns differ"),
+            failed,
+        ),
+        (false, _) => Line::styled(
+            format!("Row counts differ; {differing} of {total} columns differ"),
+            failed,
+        ),
+    }
+}
+
+const fn status_label(status: CompareStatus) -> (&'static str, Style) {
+    match status {
+        CompareStatus::Same => ("same", Style::new().fg(Color::DarkGray)),
+        CompareStatus::Differs => ("differs", Style::new().fg(Color::Red)),
+        CompareStatus::OnlyLeft => {
+            ("only left", Style::new().fg(Color::Yellow))
+        }
+        CompareStatus::OnlyRight => {
+            ("only right", Style::new().fg(Color::Yellow))
+        }
+    }
+}
+
+impl Widget for TableCompareView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.lines()).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_row_counts_and_column_status() {
+        let left = TableChecksums::from_aggregate_row(
+            vec!["id".to_string(), "note".to_string()],
+            &["3", "3", "6", "2", "40"].map(String::from),
+        );
+        let right = TableChecksums::from_aggregate_row(
+            vec!["id".to_string(), "note".to_string()],
+            &["3", "3", "6", "2", "41"].map(String::from),
+        );
+        let lines = TableCompareView::new(&left, &right, ("a", "b"))
+            .lines()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines.first().map(String::as_str),
+            Some("1 of 2 columns differ")
+        );
+        assert_eq!(lines.get(4).map(String::as_str), Some("rows  3  3  same"));
+        assert_eq!(
+            lines.get(6).map(String::as_str),
+            Some("note  2  2  differs")
+        );
+    }
+}
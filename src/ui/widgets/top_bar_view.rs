@@ -1,35 +1,12 @@
 use ratatui::{
     prelude::{Buffer, Constraint, Layout, Rect, Widget},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
     widgets::Paragraph,
 };
 
 use super::{hotkey::Hotkey, hotkey_view::HotkeyView};
-use crate::db::connection::Connection;
-
-pub const CONNECTION_HOTKEYS: [Hotkey; 5] = [
-    Hotkey::new('n', "New Connection"),
-    Hotkey::new('e', "Edit Connection"),
-    Hotkey::new('d', "Delete Connection"),
-    Hotkey::new('o', "Open Connection"),
-    Hotkey::new('y', "Copy value"),
-];
-
-pub const DATABASE_HOTKEYS: [Hotkey; 5] = [
-    Hotkey::new('e', "SQL Editor"),
-    Hotkey::new('t', "Table structure"),
-    Hotkey::new('E', "Run SQL"),
-    Hotkey::new('/', "Search"),
-    Hotkey::new('y', "Copy value"),
-];
-
-/// Shown in addition to [`DATABASE_HOTKEYS`] while viewing table row data.
-pub const TABLE_DATA_VIEW_HOTKEYS: [Hotkey; 5] = [
-    Hotkey::new('r', "Refresh"),
-    Hotkey::new('a', "New row"),
-    Hotkey::new('c', "Copy row"),
-    Hotkey::new('s', "Commit row"),
-    Hotkey::new('d', "Delete row"),
-];
+use crate::db::{TransactionStatus, connection::Connection};
 
 /// Flex weights for the three middle segments (connection / MRU / primary hotkeys), matching the
 /// former 26% / 22% / 38% split of the space left of the app label column.
@@ -50,6 +27,8 @@ pub struct TopBarView<'a> {
     pub hotkeys: &'a [Hotkey],
     pub app_name: &'a str,
     pub build_info: Option<String>,
+    /// Transaction state of the session; `None` when not known.
+    pub transaction: Option<TransactionStatus>,
 }
 
 impl Widget for TopBarView<'_> {
@@ -82,10 +61,24 @@ impl Widget for TopBarView<'_> {
                 .areas(main_area);
 
         // Display build info if provided, otherwise show connection details
+        // under its notes, so they stay visible in a short top bar
         let left_content = if let Some(build_info) = self.build_info {
-            build_info
+            Text::from(build_info)
         } else {
-            self.current_connection.to_string()
+            let mut text = Text::from(self.current_connection.to_string());
+            if let Some(notes) = &self.current_connection.notes {
+                text.lines.insert(
+                    0,
+                    Line::from(vec![Span::raw(" "), notes_span(notes)]),
+                );
+            }
+            if let Some(status) = self.transaction {
+                text.lines.push(Line::from(vec![
+                    Span::raw(" "),
+                    transaction_span(status),
+                ]));
+            }
+            text
         };
 
         Paragraph::new(left_content).render(app_info_cell, buf);
@@ -105,3 +98,71 @@ impl Widget for TopBarView<'_> {
         Paragraph::new(padded).render(app_logo_cell, buf);
     }
 }
+
+/// A connection's notes, highlighted so e.g. "replica — read only" is
+/// hard to miss.
+fn notes_span(notes: &str) -> Span<'static> {
+    Span::styled(
+        format!("⚑ {notes}"),
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+/// The session's transaction state, colored like a traffic light so an
+/// open or failed transaction stands out.
+fn transaction_span(status: TransactionStatus) -> Span<'static> {
+    let style = match status {
+        TransactionStatus::Idle => Style::default().fg(Color::Green),
+        TransactionStatus::InTransaction => Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+        TransactionStatus::Failed => {
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        }
+    };
+    Span::styled(format!("txn: {}", status.label()), style)
+}
+
+/// Single-line top bar for small terminals: app name, connection and what
+/// is being browsed.
+pub struct CompactTopBar<'a> {
+    pub app_name: &'a str,
+    /// `None` on the connection list.
+    pub connection: Option<&'a Connection>,
+    pub mode: &'a str,
+    pub transaction: Option<TransactionStatus>,
+}
+
+impl Widget for CompactTopBar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let separator =
+            Span::styled(" │ ", Style::default().fg(Color::DarkGray));
+        let mut spans = vec![Span::styled(
+            format!(" {}", self.app_name),
+            Style::default().add_modifier(Modifier::BOLD),
+        )];
+        if let Some(connection) = self.connection {
+            spans.push(separator.clone());
+            spans.push(Span::raw(format!(
+                "{} ({})",
+                connection.name, connection.r#type
+            )));
+            if let Some(database) = &connection.selected_database {
+                spans.push(Span::raw(format!(" {database}")));
+            }
+            if let Some(notes) = &connection.notes {
+                spans.push(separator.clone());
+                spans.push(notes_span(notes));
+            }
+            if let Some(status) = self.transaction {
+                spans.push(separator.clone());
+                spans.push(transaction_span(status));
+            }
+        }
+        spans.push(separator);
+        spans.push(Span::styled(self.mode, Style::default().fg(Color::Cyan)));
+        Paragraph::new(Line::from(spans)).render(area, buf);
+    }
+}
@@ -1,5 +1,7 @@
 //! Paged ("virtual") table view: only one window of rows is loaded at a time.
 
+use crate::db::keyset::{KeySeek, KeysetPage, row_key};
+
 /// Rows loaded per fetch when browsing table data in the explorer.
 pub const VIRTUAL_TABLE_PAGE_SIZE: u32 = 200;
 
@@ -12,6 +14,8 @@ pub struct VirtualTableMeta {
     pub total_rows: Option<u64>,
     pub has_more_before: bool,
     pub has_more_after: bool,
+    /// Pages are read by primary key, so `window_start` isn't known
+    pub keyset: bool,
 }
 
 impl VirtualTableMeta {
@@ -36,6 +40,28 @@ impl VirtualTableMeta {
             total_rows,
             has_more_before,
             has_more_after,
+            keyset: false,
+        }
+    }
+
+    /// Metadata for a page read by key from `seek`.
+    #[must_use]
+    pub const fn from_keyset_fetch(
+        seek: &KeySeek,
+        page_size: u32,
+        loaded_row_count: usize,
+        total_rows: Option<u64>,
+    ) -> Self {
+        let (has_more_before, has_more_after) =
+            seek.more_rows(loaded_row_count >= page_size as usize);
+        Self {
+            window_start: 0,
+            page_size,
+            loaded_count: loaded_row_count,
+            total_rows,
+            has_more_before,
+            has_more_after,
+            keyset: true,
         }
     }
 
@@ -58,10 +84,17 @@ impl VirtualTableMeta {
         }
         let start = self.window_start + 1;
         let end = self.window_start + self.loaded_count as u64;
-        let mut s = self.total_rows.map_or_else(
-            || format!(" ({start}-{end} · j/k across pages)"),
-            |t| format!(" ({start}-{end} of {t} · j/k across pages)"),
-        );
+        let loaded = self.loaded_count;
+        let mut s = match (self.keyset, self.total_rows) {
+            (true, None) => format!(" ({loaded} by key · j/k across pages)"),
+            (true, Some(t)) => {
+                format!(" ({loaded} of {t} by key · j/k across pages)")
+            }
+            (false, None) => format!(" ({start}-{end} · j/k across pages)"),
+            (false, Some(t)) => {
+                format!(" ({start}-{end} of {t} · j/k across pages)")
+            }
+        };
         if local_draft_rows > 0 {
             let label = if local_draft_rows == 1 {
                 "draft"
@@ -91,3 +124,93 @@ const fn compute_has_more_after(
         None => true,
     }
 }
+
+/// Where keyset paging of the current table is: the key, the page shown
+/// and the keys at its ends, which the next and previous pages seek from.
+#[derive(Debug, Clone)]
+pub struct KeysetCursor {
+    /// Primary key columns, in key order
+    pub key_columns: Vec<String>,
+    /// Where the page shown was read from
+    pub seek: KeySeek,
+    first_key: Option<Vec<String>>,
+    last_key: Option<Vec<String>>,
+}
+
+impl KeysetCursor {
+    #[must_use]
+    pub const fn new(key_columns: Vec<String>) -> Self {
+        Self {
+            key_columns,
+            seek: KeySeek::Start,
+            first_key: None,
+            last_key: None,
+        }
+    }
+
+    /// The page to read from `seek`.
+    #[must_use]
+    pub fn page(&self, seek: KeySeek) -> KeysetPage {
+        KeysetPage {
+            key_columns: self.key_columns.clone(),
+            seek,
+        }
+    }
+
+    /// Seek of the page after (`forward`) or before the one shown; `None`
+    /// when its rows' keys weren't read.
+    #[must_use]
+    pub fn step(&self, forward: bool) -> Option<KeySeek> {
+        if forward {
+            self.last_key.clone().map(KeySeek::After)
+        } else {
+            self.first_key.clone().map(KeySeek::Before)
+        }
+    }
+
+    /// Remember the page read from `seek`, in key order.
+    pub fn shown(
+        &mut self,
+        seek: KeySeek,
+        column_names: &[String],
+        rows: &[Vec<String>],
+    ) {
+        let key = |row: &Vec<String>| {
+            row_key(&self.key_columns, column_names, row)
+        };
+        self.first_key = rows.first().and_then(key);
+        self.last_key = rows.last().and_then(key);
+        self.seek = seek;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyset_cursor_steps_from_the_page_ends() {
+        let mut cursor = KeysetCursor::new(vec!["id".to_string()]);
+        assert_eq!(cursor.step(true), None);
+        let columns = ["name".to_string(), "id".to_string()];
+        let rows = [
+            vec!["a".to_string(), "3".to_string()],
+            vec!["b".to_string(), "9".to_string()],
+        ];
+        cursor.shown(KeySeek::Start, &columns, &rows);
+        assert_eq!(cursor.step(true), Some(KeySeek::After(vec!["9".into()])));
+        assert_eq!(cursor.step(false), Some(KeySeek::Before(vec!["3".into()])));
+
+        let meta = VirtualTableMeta::from_keyset_fetch(
+            &KeySeek::After(vec!["9".into()]),
+            2,
+            1,
+            Some(10),
+        );
+        assert!(meta.has_more_before && !meta.has_more_after);
+        assert_eq!(
+            meta.title_suffix(false, 1, 0),
+            " (1 of 10 by key · j/k across pages)"
+        );
+    }
+}